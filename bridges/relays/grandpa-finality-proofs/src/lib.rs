@@ -0,0 +1,211 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! GRANDPA finality proofs served over RPC in the form expected by the bridge GRANDPA pallet.
+//!
+//! The justifications stored by the node may carry votes and votes ancestries that are not
+//! needed to prove finality, which `pallet-bridge-grandpa` rejects. The
+//! [`BridgeFinalityProofProvider`] wraps the GRANDPA [`FinalityProofProvider`] to verify and
+//! strip the justifications of the authority set change proofs before serving them, so relayers
+//! can submit them as is. It is given to [`sc_consensus_grandpa_rpc::Grandpa::new`] instead of
+//! the wrapped provider by the nodes serving bridge relayers.
+
+#![warn(missing_docs)]
+
+use bp_header_chain::{
+	justification::{
+		verify_and_optimize_justification, GrandpaJustification, JustificationVerificationContext,
+	},
+	AuthoritySet,
+};
+use codec::{Decode, Encode};
+use parking_lot::Mutex;
+use sc_client_api::backend::Backend;
+use sc_consensus_grandpa::{AuthoritySetChangeProof, FinalityProofError, FinalityProofProvider};
+use sc_consensus_grandpa_rpc::{
+	EncodedAuthoritySetChangeProof, EncodedFinalityProof, RpcFinalityProofProvider,
+};
+use schnellru::{ByLength, LruMap};
+use sp_consensus_grandpa::SetId;
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT, NumberFor};
+use std::sync::Arc;
+
+/// Log target of this crate.
+const LOG_TARGET: &str = "bridge";
+
+/// Number of optimized authority set change proofs kept in the cache.
+const OPTIMIZED_PROOFS_CACHE_SIZE: u32 = 1024;
+
+/// GRANDPA finality proof provider serving authority set change proofs whose justifications are
+/// verified and optimized for `pallet-bridge-grandpa`.
+pub struct BridgeFinalityProofProvider<B, Block: BlockT> {
+	inner: Arc<FinalityProofProvider<B, Block>>,
+	/// Optimized proofs of past authority set changes, keyed by set id. These never change once
+	/// the last block of the set is finalized.
+	optimized_proofs: Mutex<LruMap<SetId, AuthoritySetChangeProof<Block::Header>, ByLength>>,
+}
+
+impl<B, Block: BlockT> BridgeFinalityProofProvider<B, Block> {
+	/// Create a new provider wrapping the given GRANDPA finality proof provider.
+	pub fn new(inner: Arc<FinalityProofProvider<B, Block>>) -> Self {
+		BridgeFinalityProofProvider {
+			inner,
+			optimized_proofs: Mutex::new(LruMap::new(ByLength::new(OPTIMIZED_PROOFS_CACHE_SIZE))),
+		}
+	}
+}
+
+impl<B, Block> RpcFinalityProofProvider<Block> for BridgeFinalityProofProvider<B, Block>
+where
+	Block: BlockT,
+	NumberFor<Block>: finality_grandpa::BlockNumberOps,
+	B: Backend<Block> + Send + Sync + 'static,
+{
+	fn rpc_prove_finality(
+		&self,
+		block: NumberFor<Block>,
+	) -> Result<Option<EncodedFinalityProof>, FinalityProofError> {
+		self.inner.rpc_prove_finality(block)
+	}
+
+	/// Prove all authority set changes enacted in the `(from; to]` range of blocks, up to the
+	/// first set whose justification can't be verified against the authorities of the set.
+	fn rpc_prove_authority_set_changes(
+		&self,
+		from: NumberFor<Block>,
+		to: NumberFor<Block>,
+	) -> Result<Vec<EncodedAuthoritySetChangeProof>, FinalityProofError> {
+		let mut proofs = Vec::new();
+		for proof in self.inner.prove_authority_set_changes(from, to)? {
+			let set_id = proof.set_id;
+			let cached = self.optimized_proofs.lock().get(&set_id).cloned();
+			let optimized = match cached {
+				Some(optimized) => optimized,
+				None => match optimize_proof(proof) {
+					Some(optimized) => {
+						self.optimized_proofs.lock().insert(set_id, optimized.clone());
+						optimized
+					},
+					None => break,
+				},
+			};
+			proofs.push(EncodedAuthoritySetChangeProof(optimized.encode_call_args().into()));
+		}
+		Ok(proofs)
+	}
+}
+
+/// Verify the justification of the given authority set change proof against the authorities of
+/// the set, and strip it of the redundant votes and votes ancestries.
+///
+/// Returns `None` if the authorities of the set are unknown or if the justification is invalid.
+fn optimize_proof<Header: HeaderT>(
+	mut proof: AuthoritySetChangeProof<Header>,
+) -> Option<AuthoritySetChangeProof<Header>> {
+	let Some(authorities) = proof.authorities.clone() else {
+		log::trace!(target: LOG_TARGET, "Unknown authorities of GRANDPA set {}", proof.set_id);
+		return None
+	};
+
+	let target = (proof.header.hash(), *proof.header.number());
+	let verified = GrandpaJustification::<Header>::decode(&mut &proof.justification[..])
+		.map_err(|err| format!("{err:?}"))
+		.and_then(|mut justification| {
+			JustificationVerificationContext::try_from(AuthoritySet::new(authorities, proof.set_id))
+				.and_then(|context| {
+					verify_and_optimize_justification(target, &context, &mut justification)
+				})
+				.map(|_| justification)
+				.map_err(|err| format!("{err:?}"))
+		});
+	match verified {
+		Ok(justification) => {
+			proof.justification = justification.encode();
+			Some(proof)
+		},
+		Err(err) => {
+			log::warn!(
+				target: LOG_TARGET,
+				"Invalid justification of the last block #{:?} of GRANDPA set {}: {}",
+				target.1,
+				proof.set_id,
+				err,
+			);
+			None
+		},
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use bp_header_chain::justification::verify_justification;
+	use bp_test_utils::*;
+
+	type TestHeader = sp_runtime::testing::Header;
+
+	fn proof(
+		justification: &GrandpaJustification<TestHeader>,
+		authorities: Option<sp_consensus_grandpa::AuthorityList>,
+	) -> AuthoritySetChangeProof<TestHeader> {
+		AuthoritySetChangeProof {
+			header: test_header(1),
+			justification: justification.encode(),
+			set_id: TEST_GRANDPA_SET_ID,
+			authorities,
+		}
+	}
+
+	#[test]
+	fn proofs_are_stripped_of_what_the_bridge_pallet_rejects() {
+		// the justification carries an unknown authority vote and an unneeded header
+		let mut justification = make_default_justification::<TestHeader>(&test_header(1));
+		justification.commit.precommits.push(signed_precommit::<TestHeader>(
+			&Account(42),
+			header_id::<TestHeader>(1),
+			justification.round,
+			TEST_GRANDPA_SET_ID,
+		));
+		justification.votes_ancestries.push(test_header(10));
+		let context = verification_context(TEST_GRANDPA_SET_ID);
+		assert!(verify_justification(header_id::<TestHeader>(1), &context, &justification).is_err());
+
+		let optimized = optimize_proof(proof(&justification, Some(authority_list()))).unwrap();
+		assert_eq!(optimized.header, test_header(1));
+		let optimized =
+			GrandpaJustification::<TestHeader>::decode(&mut &optimized.justification[..]).unwrap();
+		assert_eq!(verify_justification(header_id::<TestHeader>(1), &context, &optimized), Ok(()));
+		assert_eq!(optimized, make_default_justification::<TestHeader>(&test_header(1)));
+	}
+
+	#[test]
+	fn proofs_with_unknown_authorities_or_invalid_justifications_are_rejected() {
+		let justification = make_default_justification::<TestHeader>(&test_header(1));
+		assert!(optimize_proof(proof(&justification, Some(authority_list()))).is_some());
+
+		// unknown authorities
+		assert!(optimize_proof(proof(&justification, None)).is_none());
+
+		// justification signed by other authorities
+		let other_authorities = vec![(Account(42).into(), 1)];
+		assert!(optimize_proof(proof(&justification, Some(other_authorities))).is_none());
+
+		// justification that can't be decoded
+		let mut invalid = proof(&justification, Some(authority_list()));
+		invalid.justification.truncate(1);
+		assert!(optimize_proof(invalid).is_none());
+	}
+}
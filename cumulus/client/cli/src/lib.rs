@@ -29,7 +29,10 @@ use codec::Encode;
 use sc_chain_spec::ChainSpec;
 use sc_client_api::ExecutorProvider;
 use sc_service::{
-	config::{PrometheusConfig, TelemetryEndpoints},
+	config::{
+		InformantOutputFormat, PrometheusConfig, RpcEndpoint, RpcRateLimit, TelemetryEndpoints,
+		TelemetryLocalSinkConfig,
+	},
 	BasePath, TransactionPoolOptions,
 };
 use sp_core::hexdisplay::HexDisplay;
@@ -418,6 +421,10 @@ impl sc_cli::CliConfiguration for NormalizedRunCmd {
 		self.base.telemetry_endpoints(chain_spec)
 	}
 
+	fn telemetry_local_sink(&self) -> sc_cli::Result<Option<TelemetryLocalSinkConfig>> {
+		self.base.telemetry_local_sink()
+	}
+
 	fn role(&self, is_dev: bool) -> sc_cli::Result<sc_cli::Role> {
 		self.base.role(is_dev)
 	}
@@ -426,6 +433,10 @@ impl sc_cli::CliConfiguration for NormalizedRunCmd {
 		self.base.force_authoring()
 	}
 
+	fn informant_output_format(&self) -> sc_cli::Result<InformantOutputFormat> {
+		self.base.informant_output_format()
+	}
+
 	fn prometheus_config(
 		&self,
 		default_listen_port: u16,
@@ -466,6 +477,18 @@ impl sc_cli::CliConfiguration for NormalizedRunCmd {
 		Ok(self.base.rpc_max_subscriptions_per_connection)
 	}
 
+	fn rpc_max_batch_request_len(&self) -> sc_cli::Result<Option<u32>> {
+		self.base.rpc_max_batch_request_len()
+	}
+
+	fn rpc_rate_limit(&self) -> sc_cli::Result<RpcRateLimit> {
+		self.base.rpc_rate_limit()
+	}
+
+	fn rpc_endpoints(&self, is_dev: bool) -> sc_cli::Result<Vec<RpcEndpoint>> {
+		self.base.rpc_endpoints(is_dev)
+	}
+
 	fn transaction_pool(&self, is_dev: bool) -> sc_cli::Result<TransactionPoolOptions> {
 		self.base.transaction_pool(is_dev)
 	}
@@ -31,7 +31,7 @@ use sc_executor::{
 use sc_network::NetworkBlock;
 use sc_network_sync::SyncingService;
 use sc_service::{Configuration, PartialComponents, TFullBackend, TFullClient, TaskManager};
-use sc_telemetry::{Telemetry, TelemetryHandle, TelemetryWorker, TelemetryWorkerHandle};
+use sc_telemetry::{Telemetry, TelemetryHandle, TelemetryWorkerHandle};
 use sc_transaction_pool_api::OffchainTransactionPoolFactory;
 use sp_keystore::KeystorePtr;
 use substrate_prometheus_endpoint::Registry;
@@ -76,16 +76,7 @@ pub fn new_partial(
 	>,
 	sc_service::Error,
 > {
-	let telemetry = config
-		.telemetry_endpoints
-		.clone()
-		.filter(|x| !x.is_empty())
-		.map(|endpoints| -> Result<_, sc_telemetry::Error> {
-			let worker = TelemetryWorker::new(16)?;
-			let telemetry = worker.handle().new_telemetry(endpoints);
-			Ok((worker, telemetry))
-		})
-		.transpose()?;
+	let telemetry = sc_service::new_telemetry(config)?;
 
 	let heap_pages = config
 		.default_heap_pages
@@ -214,6 +205,8 @@ async fn start_node_impl(
 				is_validator: parachain_config.role.is_authority(),
 				enable_http_requests: false,
 				custom_extensions: move |_| vec![],
+				scheduling_policy: parachain_config.offchain_worker.scheduling_policy.clone(),
+				prometheus_registry: prometheus_registry.clone(),
 			})
 			.run(client.clone(), task_manager.spawn_handle())
 			.boxed(),
@@ -772,13 +772,21 @@ pub fn node_config(
 		rpc_methods: Default::default(),
 		rpc_max_request_size: Default::default(),
 		rpc_max_response_size: Default::default(),
+		rpc_max_batch_request_len: Default::default(),
 		rpc_id_provider: None,
 		rpc_max_subs_per_conn: Default::default(),
+		rpc_rate_limit: Default::default(),
+		rpc_endpoints: Default::default(),
 		rpc_port: 9945,
 		prometheus_config: None,
 		telemetry_endpoints: None,
+		telemetry_local_sink: None,
 		default_heap_pages: None,
-		offchain_worker: OffchainWorkerConfig { enabled: true, indexing_enabled: false },
+		offchain_worker: OffchainWorkerConfig {
+			enabled: true,
+			indexing_enabled: false,
+			scheduling_policy: Default::default(),
+		},
 		force_authoring: false,
 		disable_grandpa: false,
 		dev_key_seed: Some(key_seed),
@@ -80,7 +80,6 @@ use prometheus_endpoint::Registry;
 #[cfg(feature = "full-node")]
 use service::KeystoreContainer;
 use service::RpcHandlers;
-use telemetry::TelemetryWorker;
 #[cfg(feature = "full-node")]
 use telemetry::{Telemetry, TelemetryWorkerHandle};
 
@@ -407,22 +406,12 @@ fn new_partial_basics(
 	jaeger_agent: Option<std::net::SocketAddr>,
 	telemetry_worker_handle: Option<TelemetryWorkerHandle>,
 ) -> Result<Basics, Error> {
-	let telemetry = config
-		.telemetry_endpoints
-		.clone()
-		.filter(|x| !x.is_empty())
-		.map(move |endpoints| -> Result<_, telemetry::Error> {
-			let (worker, mut worker_handle) = if let Some(worker_handle) = telemetry_worker_handle {
-				(None, worker_handle)
-			} else {
-				let worker = TelemetryWorker::new(16)?;
-				let worker_handle = worker.handle();
-				(Some(worker), worker_handle)
-			};
-			let telemetry = worker_handle.new_telemetry(endpoints);
-			Ok((worker, telemetry))
-		})
-		.transpose()?;
+	let telemetry = match telemetry_worker_handle {
+		Some(worker_handle) => service::new_telemetry_with_worker(config, worker_handle)?
+			.map(|telemetry| (None, telemetry)),
+		None =>
+			service::new_telemetry(config)?.map(|(worker, telemetry)| (Some(worker), telemetry)),
+	};
 
 	let heap_pages = config
 		.default_heap_pages
@@ -599,6 +588,9 @@ where
 				beefy: polkadot_rpc::BeefyDeps {
 					beefy_finality_proof_stream: beefy_rpc_links.from_voter_justif_stream.clone(),
 					beefy_best_block_stream: beefy_rpc_links.from_voter_best_beefy_stream.clone(),
+					beefy_justif_request_sender: beefy_rpc_links
+						.to_voter_justif_request_sender
+						.clone(),
 					subscription_executor,
 				},
 				backend: backend.clone(),
@@ -793,6 +785,11 @@ pub fn new_full<OverseerGenerator: OverseerGen>(
 
 	let shared_voter_state = rpc_setup;
 	let auth_disc_publish_non_global_ips = config.network.allow_non_globals_in_dht;
+	let auth_disc_persisted_cache_path = config
+		.network
+		.net_config_path
+		.as_ref()
+		.map(|path| path.join("authority_discovery_records"));
 	let mut net_config = sc_network::config::FullNetworkConfiguration::new(&config.network);
 
 	let genesis_hash = client.block_hash(0).ok().flatten().expect("Genesis block exists; qed");
@@ -823,6 +820,17 @@ pub fn new_full<OverseerGenerator: OverseerGen>(
 		net_config.add_request_response_protocol(beefy_req_resp_cfg);
 	}
 
+	// Validators exchange their authority discovery records directly.
+	let auth_disc_record_exchange = role.is_authority().then(|| {
+		let (record_exchange, protocol_config) =
+			sc_authority_discovery::record_exchange_protocol_config(
+				genesis_hash.as_ref(),
+				config.chain_spec.fork_id(),
+			);
+		net_config.add_request_response_protocol(protocol_config);
+		record_exchange
+	});
+
 	// validation/collation protocols are enabled only if `Overseer` is enabled
 	let peerset_protocol_names =
 		PeerSetProtocolNames::new(genesis_hash, config.chain_spec.fork_id());
@@ -869,11 +877,17 @@ pub fn new_full<OverseerGenerator: OverseerGen>(
 		Vec::new()
 	};
 
-	let warp_sync = Arc::new(grandpa::warp_proof::NetworkProvider::new(
+	let mut warp_sync = grandpa::warp_proof::NetworkProvider::new(
 		backend.clone(),
 		import_setup.1.shared_authority_set().clone(),
 		grandpa_hard_forks,
-	));
+	);
+	if let Some(checkpoint) = config.chain_spec.checkpoint() {
+		let checkpoint = grandpa::warp_proof::WarpSyncCheckpoint::from_chain_spec(checkpoint)
+			.map_err(|e| SubstrateServiceError::Other(e.to_string()))?;
+		warp_sync = warp_sync.with_checkpoint(checkpoint);
+	}
+	let warp_sync = Arc::new(warp_sync);
 
 	let (network, system_rpc_tx, tx_handler_controller, network_starter, sync_service) =
 		service::build_network(service::BuildNetworkParams {
@@ -905,6 +919,8 @@ pub fn new_full<OverseerGenerator: OverseerGen>(
 				is_validator: role.is_authority(),
 				enable_http_requests: false,
 				custom_extensions: move |_| vec![],
+				scheduling_policy: config.offchain_worker.scheduling_policy.clone(),
+				prometheus_registry: prometheus_registry.clone(),
 			})
 			.run(client.clone(), task_manager.spawn_handle())
 			.boxed(),
@@ -1017,6 +1033,8 @@ pub fn new_full<OverseerGenerator: OverseerGen>(
 					publish_non_global_ips: auth_disc_publish_non_global_ips,
 					// Require that authority discovery records are signed.
 					strict_record_validation: true,
+					record_exchange: auth_disc_record_exchange,
+					persisted_cache_path: auth_disc_persisted_cache_path,
 					..Default::default()
 				},
 				client.clone(),
@@ -24,7 +24,7 @@ use jsonrpsee::RpcModule;
 use polkadot_primitives::{AccountId, Balance, Block, BlockNumber, Hash, Nonce};
 use sc_client_api::AuxStore;
 use sc_consensus_beefy::communication::notification::{
-	BeefyBestBlockStream, BeefyVersionedFinalityProofStream,
+	BeefyBestBlockStream, BeefyJustifRequestSender, BeefyVersionedFinalityProofStream,
 };
 use sc_consensus_grandpa::FinalityProofProvider;
 pub use sc_rpc::{DenyUnsafe, SubscriptionTaskExecutor};
@@ -67,6 +67,8 @@ pub struct BeefyDeps {
 	pub beefy_finality_proof_stream: BeefyVersionedFinalityProofStream<Block>,
 	/// Receives notifications about best block events from BEEFY.
 	pub beefy_best_block_stream: BeefyBestBlockStream<Block>,
+	/// Asks BEEFY to request missing justifications from peers.
+	pub beefy_justif_request_sender: BeefyJustifRequestSender<Block>,
 	/// Executor to drive the subscription manager in the BEEFY RPC handler.
 	pub subscription_executor: sc_rpc::SubscriptionTaskExecutor,
 }
@@ -119,6 +121,7 @@ where
 	use mmr_rpc::{Mmr, MmrApiServer};
 	use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApiServer};
 	use sc_consensus_babe_rpc::{Babe, BabeApiServer};
+	use sc_consensus_beefy::finality_proof::FinalityProofProvider as BeefyFinalityProofProvider;
 	use sc_consensus_beefy_rpc::{Beefy, BeefyApiServer};
 	use sc_consensus_grandpa_rpc::{Grandpa, GrandpaApiServer};
 	use sc_sync_state_rpc::{SyncState, SyncStateApiServer};
@@ -161,13 +164,19 @@ where
 		.into_rpc(),
 	)?;
 	io.merge(
-		SyncState::new(chain_spec, client, shared_authority_set, babe_worker_handle)?.into_rpc(),
+		SyncState::new(chain_spec, client.clone(), shared_authority_set, babe_worker_handle)?
+			.into_rpc(),
 	)?;
 
 	io.merge(
-		Beefy::<Block>::new(
+		Beefy::<Block, _>::new(
 			beefy.beefy_finality_proof_stream,
 			beefy.beefy_best_block_stream,
+			Arc::new(BeefyFinalityProofProvider::new(
+				backend,
+				client,
+				beefy.beefy_justif_request_sender,
+			)),
 			beefy.subscription_executor,
 		)?
 		.into_rpc(),
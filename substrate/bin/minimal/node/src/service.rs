@@ -20,7 +20,7 @@ use runtime::{self, interface::OpaqueBlock as Block, RuntimeApi};
 use sc_client_api::backend::Backend;
 use sc_executor::WasmExecutor;
 use sc_service::{error::Error as ServiceError, Configuration, TaskManager};
-use sc_telemetry::{Telemetry, TelemetryWorker};
+use sc_telemetry::Telemetry;
 use sc_transaction_pool_api::OffchainTransactionPoolFactory;
 use std::sync::Arc;
 
@@ -51,16 +51,7 @@ pub fn new_partial(
 	>,
 	ServiceError,
 > {
-	let telemetry = config
-		.telemetry_endpoints
-		.clone()
-		.filter(|x| !x.is_empty())
-		.map(|endpoints| -> Result<_, sc_telemetry::Error> {
-			let worker = TelemetryWorker::new(16)?;
-			let telemetry = worker.handle().new_telemetry(endpoints);
-			Ok((worker, telemetry))
-		})
-		.transpose()?;
+	let telemetry = sc_service::new_telemetry(config)?;
 
 	let executor = sc_service::new_wasm_executor(&config);
 
@@ -148,6 +139,8 @@ pub fn new_full(config: Configuration, consensus: Consensus) -> Result<TaskManag
 				network_provider: network.clone(),
 				enable_http_requests: true,
 				custom_extensions: |_| vec![],
+				scheduling_policy: config.offchain_worker.scheduling_policy.clone(),
+				prometheus_registry: config.prometheus_registry().cloned(),
 			})
 			.run(client.clone(), task_manager.spawn_handle())
 			.boxed(),
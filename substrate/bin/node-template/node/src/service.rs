@@ -7,7 +7,7 @@ use sc_consensus_aura::{ImportQueueParams, SlotProportion, StartAuraParams};
 use sc_consensus_grandpa::SharedVoterState;
 pub use sc_executor::NativeElseWasmExecutor;
 use sc_service::{error::Error as ServiceError, Configuration, TaskManager, WarpSyncParams};
-use sc_telemetry::{Telemetry, TelemetryWorker};
+use sc_telemetry::Telemetry;
 use sc_transaction_pool_api::OffchainTransactionPoolFactory;
 use sp_consensus_aura::sr25519::AuthorityPair as AuraPair;
 use std::{sync::Arc, time::Duration};
@@ -64,16 +64,7 @@ pub fn new_partial(
 	>,
 	ServiceError,
 > {
-	let telemetry = config
-		.telemetry_endpoints
-		.clone()
-		.filter(|x| !x.is_empty())
-		.map(|endpoints| -> Result<_, sc_telemetry::Error> {
-			let worker = TelemetryWorker::new(16)?;
-			let telemetry = worker.handle().new_telemetry(endpoints);
-			Ok((worker, telemetry))
-		})
-		.transpose()?;
+	let telemetry = sc_service::new_telemetry(config)?;
 
 	let executor = sc_service::new_native_or_wasm_executor(config);
 	let (client, backend, keystore_container, task_manager) =
@@ -167,11 +158,18 @@ pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
 		grandpa_protocol_name.clone(),
 	));
 
-	let warp_sync = Arc::new(sc_consensus_grandpa::warp_proof::NetworkProvider::new(
+	let mut warp_sync = sc_consensus_grandpa::warp_proof::NetworkProvider::new(
 		backend.clone(),
 		grandpa_link.shared_authority_set().clone(),
 		Vec::default(),
-	));
+	);
+	if let Some(checkpoint) = config.chain_spec.checkpoint() {
+		let checkpoint =
+			sc_consensus_grandpa::warp_proof::WarpSyncCheckpoint::from_chain_spec(checkpoint)
+				.map_err(|e| ServiceError::Other(e.to_string()))?;
+		warp_sync = warp_sync.with_checkpoint(checkpoint);
+	}
+	let warp_sync = Arc::new(warp_sync);
 
 	let (network, system_rpc_tx, tx_handler_controller, network_starter, sync_service) =
 		sc_service::build_network(sc_service::BuildNetworkParams {
@@ -201,6 +199,8 @@ pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
 				network_provider: network.clone(),
 				enable_http_requests: true,
 				custom_extensions: |_| vec![],
+				scheduling_policy: config.offchain_worker.scheduling_policy.clone(),
+				prometheus_registry: config.prometheus_registry().cloned(),
 			})
 			.run(client.clone(), task_manager.spawn_handle())
 			.boxed(),
@@ -35,7 +35,7 @@ use sc_network::{event::Event, NetworkEventStream, NetworkService};
 use sc_network_sync::{warp::WarpSyncParams, SyncingService};
 use sc_service::{config::Configuration, error::Error as ServiceError, RpcHandlers, TaskManager};
 use sc_statement_store::Store as StatementStore;
-use sc_telemetry::{Telemetry, TelemetryWorker};
+use sc_telemetry::Telemetry;
 use sc_transaction_pool_api::OffchainTransactionPoolFactory;
 use sp_api::ProvideRuntimeApi;
 use sp_core::crypto::Pair;
@@ -138,6 +138,7 @@ pub fn create_extrinsic(
 pub fn new_partial(
 	config: &Configuration,
 	mixnet_config: Option<&sc_mixnet::Config>,
+	statement_store_options: sc_statement_store::Options,
 ) -> Result<
 	sc_service::PartialComponents<
 		FullClient,
@@ -163,16 +164,7 @@ pub fn new_partial(
 	>,
 	ServiceError,
 > {
-	let telemetry = config
-		.telemetry_endpoints
-		.clone()
-		.filter(|x| !x.is_empty())
-		.map(|endpoints| -> Result<_, sc_telemetry::Error> {
-			let worker = TelemetryWorker::new(16)?;
-			let telemetry = worker.handle().new_telemetry(endpoints);
-			Ok((worker, telemetry))
-		})
-		.transpose()?;
+	let telemetry = sc_service::new_telemetry(config)?;
 
 	let executor = sc_service::new_native_or_wasm_executor(&config);
 
@@ -243,7 +235,7 @@ pub fn new_partial(
 
 	let statement_store = sc_statement_store::Store::new_shared(
 		&config.data_path,
-		Default::default(),
+		statement_store_options,
 		client.clone(),
 		keystore_container.local_keystore(),
 		config.prometheus_registry(),
@@ -342,6 +334,7 @@ pub struct NewFullBase {
 pub fn new_full_base(
 	config: Configuration,
 	mixnet_config: Option<sc_mixnet::Config>,
+	statement_store_options: sc_statement_store::Options,
 	disable_hardware_benchmarks: bool,
 	with_startup_data: impl FnOnce(
 		&sc_consensus_babe::BabeBlockImport<Block, FullClient, FullGrandpaBlockImport>,
@@ -365,10 +358,15 @@ pub fn new_full_base(
 		transaction_pool,
 		other:
 			(rpc_builder, import_setup, rpc_setup, mut telemetry, statement_store, mixnet_api_backend),
-	} = new_partial(&config, mixnet_config.as_ref())?;
+	} = new_partial(&config, mixnet_config.as_ref(), statement_store_options)?;
 
 	let shared_voter_state = rpc_setup;
 	let auth_disc_publish_non_global_ips = config.network.allow_non_globals_in_dht;
+	let auth_disc_persisted_cache_path = config
+		.network
+		.net_config_path
+		.as_ref()
+		.map(|path| path.join("authority_discovery_records"));
 	let mut net_config = sc_network::config::FullNetworkConfiguration::new(&config.network);
 
 	let genesis_hash = client.block_hash(0).ok().flatten().expect("Genesis block exists; qed");
@@ -384,6 +382,16 @@ pub fn new_full_base(
 	);
 	net_config.add_notification_protocol(statement_handler_proto.set_config());
 
+	let auth_disc_record_exchange = config.role.is_authority().then(|| {
+		let (record_exchange, protocol_config) =
+			sc_authority_discovery::record_exchange_protocol_config(
+				genesis_hash.as_ref(),
+				config.chain_spec.fork_id(),
+			);
+		net_config.add_request_response_protocol(protocol_config);
+		record_exchange
+	});
+
 	let mixnet_protocol_name =
 		sc_mixnet::protocol_name(genesis_hash.as_ref(), config.chain_spec.fork_id());
 	if let Some(mixnet_config) = &mixnet_config {
@@ -393,11 +401,17 @@ pub fn new_full_base(
 		));
 	}
 
-	let warp_sync = Arc::new(grandpa::warp_proof::NetworkProvider::new(
+	let mut warp_sync = grandpa::warp_proof::NetworkProvider::new(
 		backend.clone(),
 		import_setup.1.shared_authority_set().clone(),
 		Vec::default(),
-	));
+	);
+	if let Some(checkpoint) = config.chain_spec.checkpoint() {
+		let checkpoint = grandpa::warp_proof::WarpSyncCheckpoint::from_chain_spec(checkpoint)
+			.map_err(|e| ServiceError::Other(e.to_string()))?;
+		warp_sync = warp_sync.with_checkpoint(checkpoint);
+	}
+	let warp_sync = Arc::new(warp_sync);
 
 	let (network, system_rpc_tx, tx_handler_controller, network_starter, sync_service) =
 		sc_service::build_network(sc_service::BuildNetworkParams {
@@ -434,6 +448,7 @@ pub fn new_full_base(
 	let enable_grandpa = !config.disable_grandpa;
 	let prometheus_registry = config.prometheus_registry().cloned();
 	let enable_offchain_worker = config.offchain_worker.enabled;
+	let offchain_scheduling_policy = config.offchain_worker.scheduling_policy.clone();
 
 	let rpc_handlers = sc_service::spawn_tasks(sc_service::SpawnTasksParams {
 		config,
@@ -546,6 +561,8 @@ pub fn new_full_base(
 			sc_authority_discovery::new_worker_and_service_with_config(
 				sc_authority_discovery::WorkerConfig {
 					publish_non_global_ips: auth_disc_publish_non_global_ips,
+					record_exchange: auth_disc_record_exchange,
+					persisted_cache_path: auth_disc_persisted_cache_path,
 					..Default::default()
 				},
 				client.clone(),
@@ -643,6 +660,8 @@ pub fn new_full_base(
 				custom_extensions: move |_| {
 					vec![Box::new(statement_store.clone().as_statement_store_ext()) as Box<_>]
 				},
+				scheduling_policy: offchain_scheduling_policy,
+				prometheus_registry: prometheus_registry.clone(),
 			})
 			.run(client.clone(), task_manager.spawn_handle())
 			.boxed(),
@@ -663,9 +682,16 @@ pub fn new_full_base(
 /// Builds a new service for a full client.
 pub fn new_full(config: Configuration, cli: Cli) -> Result<TaskManager, ServiceError> {
 	let mixnet_config = cli.mixnet_params.config(config.role.is_authority());
+	let statement_store_options = cli.statement_store_params.options();
 	let database_source = config.database.clone();
-	let task_manager = new_full_base(config, mixnet_config, cli.no_hardware_benchmarks, |_, _| ())
-		.map(|NewFullBase { task_manager, .. }| task_manager)?;
+	let task_manager = new_full_base(
+		config,
+		mixnet_config,
+		statement_store_options,
+		cli.no_hardware_benchmarks,
+		|_, _| (),
+	)
+	.map(|NewFullBase { task_manager, .. }| task_manager)?;
 
 	sc_storage_monitor::StorageMonitorService::try_spawn(
 		cli.storage_monitor,
@@ -743,6 +769,7 @@ mod tests {
 					new_full_base(
 						config,
 						None,
+						Default::default(),
 						false,
 						|block_import: &sc_consensus_babe::BabeBlockImport<Block, _, _>,
 						 babe_link: &sc_consensus_babe::BabeLink<Block>| {
@@ -918,7 +945,7 @@ mod tests {
 			crate::chain_spec::tests::integration_test_config_with_two_authorities(),
 			|config| {
 				let NewFullBase { task_manager, client, network, sync, transaction_pool, .. } =
-					new_full_base(config, None, false, |_, _| ())?;
+					new_full_base(config, None, Default::default(), false, |_, _| ())?;
 				Ok(sc_service_test::TestNetComponents::new(
 					task_manager,
 					client,
@@ -298,6 +298,7 @@ impl<'a> Iterator for BlockContentIterator<'a> {
 					sender,
 					signed_extra(0, kitchensink_runtime::ExistentialDeposit::get() + 1),
 				)),
+				extension: None,
 				function: match self.content.block_type {
 					BlockType::RandomTransfersKeepAlive =>
 						RuntimeCall::Balances(BalancesCall::transfer_keep_alive {
@@ -581,12 +582,14 @@ impl BenchKeyring {
 						key.sign(b)
 					}
 				});
-				UncheckedExtrinsic {
-					signature: Some((sp_runtime::MultiAddress::Id(signed), signature, extra)),
-					function: payload.0,
-				}
+				UncheckedExtrinsic::new_signed(
+					payload.0,
+					sp_runtime::MultiAddress::Id(signed),
+					signature,
+					extra,
+				)
 			},
-			None => UncheckedExtrinsic { signature: None, function: xt.function },
+			None => UncheckedExtrinsic::new_unsigned(xt.function),
 		}
 	}
 
@@ -18,7 +18,7 @@
 
 //! Substrate Client data backend
 
-use std::collections::HashSet;
+use std::{collections::HashSet, sync::Arc};
 
 use parking_lot::RwLock;
 
@@ -488,6 +488,26 @@ pub trait StorageProvider<Block: BlockT, B: Backend<Block>> {
 	) -> sp_blockchain::Result<Option<MerkleValue<Block::Hash>>>;
 }
 
+/// Storage of arbitrary content pinned by the node.
+///
+/// The content is stored along the indexed transactions and addressed the same way, by its
+/// Blake2-256 hash, so that it is returned by
+/// [`BlockBackend::indexed_transaction`](crate::BlockBackend::indexed_transaction) until it is
+/// unpinned. Pinning an indexed transaction keeps it after the block is pruned.
+pub trait ContentStore<Block: BlockT>: Send + Sync {
+	/// Store and pin `data`, returning its hash.
+	///
+	/// Pins are reference counted: content pinned several times is kept until it is unpinned as
+	/// many times.
+	fn pin(&self, data: Vec<u8>) -> sp_blockchain::Result<Block::Hash>;
+
+	/// Unpin the content with the given hash. Returns `false` if the content is not pinned.
+	fn unpin(&self, hash: Block::Hash) -> sp_blockchain::Result<bool>;
+
+	/// Returns the hashes of the pinned content.
+	fn pinned(&self) -> sp_blockchain::Result<Vec<Block::Hash>>;
+}
+
 /// Client backend.
 ///
 /// Manages the data layer.
@@ -570,6 +590,11 @@ pub trait Backend<Block: BlockT>: AuxStore + Send + Sync {
 	/// Returns a handle to offchain storage.
 	fn offchain_storage(&self) -> Option<Self::OffchainStorage>;
 
+	/// Returns a handle to the pinned content storage, if the backend supports it.
+	fn content_store(&self) -> Option<Arc<dyn ContentStore<Block>>> {
+		None
+	}
+
 	/// Pin the block to keep body, justification and state available after pruning.
 	/// Number of pins are reference counted. Users need to make sure to perform
 	/// one call to [`Self::unpin_block`] per call to [`Self::pin_block`].
@@ -26,6 +26,10 @@
 //! [`Service`].
 //!
 //! See [`Worker`] and [`Service`] for more documentation.
+//!
+//! Besides the DHT, authorities exchange the records they know directly with the connected
+//! authorities, see [`record_exchange_protocol_config`]. The records can be persisted to disk, see
+//! [`WorkerConfig::persisted_cache_path`], so that they are known right after a restart.
 
 pub use crate::{
 	error::Error,
@@ -33,7 +37,7 @@ pub use crate::{
 	worker::{AuthorityDiscovery, NetworkProvider, Role, Worker},
 };
 
-use std::{collections::HashSet, sync::Arc, time::Duration};
+use std::{collections::HashSet, path::PathBuf, sync::Arc, time::Duration};
 
 use futures::{
 	channel::{mpsc, oneshot},
@@ -41,9 +45,14 @@ use futures::{
 };
 
 use libp2p::{Multiaddr, PeerId};
-use sc_network::event::DhtEvent;
+use sc_network::{
+	event::DhtEvent,
+	request_responses::{IncomingRequest, ProtocolConfig},
+	ProtocolName,
+};
 use sp_authority_discovery::AuthorityId;
 use sp_blockchain::HeaderBackend;
+use sp_core::hexdisplay::HexDisplay;
 use sp_runtime::traits::Block as BlockT;
 
 mod error;
@@ -84,6 +93,23 @@ pub struct WorkerConfig {
 	///
 	/// Defaults to `false` to provide compatibility with old versions
 	pub strict_record_validation: bool,
+
+	/// The maximum interval in which the node will exchange records with other authorities
+	/// directly.
+	///
+	/// By default this is set to 5 minutes.
+	pub max_exchange_interval: Duration,
+
+	/// Protocol to exchange records with other authorities directly, see
+	/// [`record_exchange_protocol_config`].
+	///
+	/// If `None`, the records are only retrieved from the DHT.
+	pub record_exchange: Option<RecordExchange>,
+
+	/// File the known records are persisted to and loaded from on startup.
+	///
+	/// If `None`, the records are only kept in memory.
+	pub persisted_cache_path: Option<PathBuf>,
 }
 
 impl Default for WorkerConfig {
@@ -105,10 +131,60 @@ impl Default for WorkerConfig {
 			max_query_interval: Duration::from_secs(10 * 60),
 			publish_non_global_ips: true,
 			strict_record_validation: false,
+			max_exchange_interval: Duration::from_secs(5 * 60),
+			record_exchange: None,
+			persisted_cache_path: None,
 		}
 	}
 }
 
+/// Maximum size of a record exchange request, containing the records of the requester.
+const MAX_EXCHANGE_REQUEST_SIZE: u64 = 64 * 1024;
+
+/// Maximum size of a record exchange response, containing all the records known by the responder.
+const MAX_EXCHANGE_RESPONSE_SIZE: u64 = 2 * 1024 * 1024;
+
+/// Maximum number of queued incoming record exchange requests.
+const MAX_EXCHANGE_REQUEST_QUEUE: usize = 32;
+
+/// Incoming requests of the record exchange protocol, passed to the [`Worker`] through
+/// [`WorkerConfig::record_exchange`].
+pub struct RecordExchange {
+	pub(crate) protocol_name: ProtocolName,
+	pub(crate) requests: async_channel::Receiver<IncomingRequest>,
+}
+
+/// Create the request-response protocol through which authorities exchange their records directly.
+///
+/// The returned [`ProtocolConfig`] must be registered in the network, and the [`RecordExchange`]
+/// passed to the [`Worker`].
+pub fn record_exchange_protocol_config(
+	genesis_hash: &[u8],
+	fork_id: Option<&str>,
+) -> (RecordExchange, ProtocolConfig) {
+	let protocol_name: ProtocolName = match fork_id {
+		Some(fork_id) => format!(
+			"/{}/{}/authority-discovery/records/1",
+			HexDisplay::from(&genesis_hash),
+			fork_id
+		),
+		None => format!("/{}/authority-discovery/records/1", HexDisplay::from(&genesis_hash)),
+	}
+	.into();
+	let (tx, requests) = async_channel::bounded(MAX_EXCHANGE_REQUEST_QUEUE);
+
+	let config = ProtocolConfig {
+		name: protocol_name.clone(),
+		fallback_names: Vec::new(),
+		max_request_size: MAX_EXCHANGE_REQUEST_SIZE,
+		max_response_size: MAX_EXCHANGE_RESPONSE_SIZE,
+		request_timeout: Duration::from_secs(20),
+		inbound_queue: Some(tx),
+	};
+
+	(RecordExchange { protocol_name, requests }, config)
+}
+
 /// Create a new authority discovery [`Worker`] and [`Service`].
 ///
 /// See the struct documentation of each for more details.
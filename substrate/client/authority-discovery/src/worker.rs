@@ -19,17 +19,27 @@
 use crate::{
 	error::{Error, Result},
 	interval::ExpIncInterval,
-	ServicetoWorkerMsg, WorkerConfig,
+	RecordExchange, ServicetoWorkerMsg, WorkerConfig,
 };
 
 use std::{
 	collections::{HashMap, HashSet},
 	marker::PhantomData,
-	sync::Arc,
-	time::Duration,
+	path::PathBuf,
+	sync::{
+		atomic::{AtomicBool, Ordering},
+		Arc,
+	},
+	thread::JoinHandle,
+	time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
-use futures::{channel::mpsc, future, stream::Fuse, FutureExt, Stream, StreamExt};
+use futures::{
+	channel::{mpsc, oneshot},
+	future::{self, BoxFuture},
+	stream::{self, BoxStream, Fuse, FuturesUnordered},
+	FutureExt, Stream, StreamExt,
+};
 
 use addr_cache::AddrCache;
 use codec::{Decode, Encode};
@@ -37,13 +47,16 @@ use ip_network::IpNetwork;
 use libp2p::{core::multiaddr, identity::PublicKey, multihash::Multihash, Multiaddr, PeerId};
 use multihash::{Code, MultihashDigest};
 
-use log::{debug, error, log_enabled};
+use log::{debug, error, log_enabled, warn};
 use prometheus_endpoint::{register, Counter, CounterVec, Gauge, Opts, U64};
 use prost::Message;
 use rand::{seq::SliceRandom, thread_rng};
 
 use sc_network::{
-	event::DhtEvent, KademliaKey, NetworkDHTProvider, NetworkSigner, NetworkStateInfo, Signature,
+	event::DhtEvent,
+	request_responses::{IncomingRequest, OutgoingResponse},
+	IfDisconnected, KademliaKey, NetworkDHTProvider, NetworkRequest, NetworkSigner,
+	NetworkStateInfo, ProtocolName, ReputationChange, RequestFailure, Signature,
 };
 use sp_api::{ApiError, ProvideRuntimeApi};
 use sp_authority_discovery::{
@@ -73,6 +86,33 @@ const MAX_ADDRESSES_PER_AUTHORITY: usize = 10;
 /// Maximum number of in-flight DHT lookups at any given point in time.
 const MAX_IN_FLIGHT_LOOKUPS: usize = 8;
 
+/// Number of authorities the records are requested from at each exchange.
+const EXCHANGE_FANOUT: usize = 4;
+
+/// Maximum number of records sent or accepted in a single exchange.
+const MAX_EXCHANGED_RECORDS: usize = 2048;
+
+/// Reputation change for a peer sending records which can't be decoded.
+const INVALID_RECORDS_REPUTATION_CHANGE: ReputationChange =
+	ReputationChange::new(-(1 << 12), "Invalid authority discovery records");
+
+/// Response to an outgoing record exchange request.
+type ExchangeResponse =
+	std::result::Result<std::result::Result<Vec<u8>, RequestFailure>, oneshot::Canceled>;
+
+/// Latest valid record received for an authority.
+struct KnownRecord {
+	/// Encoded [`schema::SignedAuthorityRecord`].
+	signed_record: Vec<u8>,
+	creation_time: Option<u128>,
+}
+
+/// Content of a record whose signatures have been verified.
+struct VerifiedRecord {
+	addresses: Vec<Multiaddr>,
+	creation_time: Option<u128>,
+}
+
 /// Role an authority discovery [`Worker`] can run as.
 pub enum Role {
 	/// Publish own addresses and discover addresses of others.
@@ -107,6 +147,11 @@ pub enum Role {
 ///    network peerset.
 ///
 ///    5. Allow querying of the collected addresses via the [`crate::Service`].
+///
+/// Records carry their signed creation time, and a record older than the one already known for an
+/// authority is ignored. If the record exchange protocol is enabled, the [`Worker`] additionally
+/// sends its own records to a few connected authorities at an interval, and receives all the
+/// records they know in response.
 pub struct Worker<Client, Network, Block, DhtEventStream> {
 	/// Channel receiver for messages send by a [`crate::Service`].
 	from_service: Fuse<mpsc::Receiver<ServicetoWorkerMsg>>,
@@ -141,6 +186,28 @@ pub struct Worker<Client, Network, Block, DhtEventStream> {
 
 	addr_cache: addr_cache::AddrCache,
 
+	/// Authorities of the current and next set, excluding the local ones.
+	authorities: HashSet<AuthorityId>,
+	/// Latest record known for each authority, kept to be exchanged and persisted.
+	known_records: HashMap<AuthorityId, KnownRecord>,
+	/// Whether `known_records` changed since they were last persisted.
+	known_records_changed: bool,
+	/// Records of the local node, as last published.
+	own_records: Vec<(AuthorityId, Vec<u8>)>,
+
+	/// Interval at which to exchange records with other authorities directly.
+	exchange_interval: ExpIncInterval,
+	/// Name of the record exchange protocol, `None` if it is disabled.
+	exchange_protocol: Option<ProtocolName>,
+	/// Incoming record exchange requests.
+	exchange_requests: Fuse<BoxStream<'static, IncomingRequest>>,
+	/// In-flight outgoing record exchange requests.
+	pending_exchanges: FuturesUnordered<BoxFuture<'static, (PeerId, ExchangeResponse)>>,
+	/// Same value as in the configuration.
+	persisted_cache_path: Option<PathBuf>,
+	/// Whether the records are being written to `persisted_cache_path`.
+	persisting: Arc<AtomicBool>,
+
 	metrics: Option<Metrics>,
 
 	role: Role,
@@ -210,6 +277,14 @@ where
 		// is more simple.
 		let publish_if_changed_interval =
 			ExpIncInterval::new(config.keystore_refresh_interval, config.keystore_refresh_interval);
+		let exchange_interval =
+			ExpIncInterval::new(Duration::from_secs(2), config.max_exchange_interval);
+
+		let (exchange_protocol, exchange_requests) = match config.record_exchange {
+			Some(RecordExchange { protocol_name, requests }) =>
+				(Some(protocol_name), requests.boxed()),
+			None => (None, stream::pending().boxed()),
+		};
 
 		let addr_cache = AddrCache::new();
 
@@ -224,7 +299,7 @@ where
 			None => None,
 		};
 
-		Worker {
+		let mut worker = Worker {
 			from_service: from_service.fuse(),
 			client,
 			network,
@@ -238,10 +313,22 @@ where
 			pending_lookups: Vec::new(),
 			in_flight_lookups: HashMap::new(),
 			addr_cache,
+			authorities: HashSet::new(),
+			known_records: HashMap::new(),
+			known_records_changed: false,
+			own_records: Vec::new(),
+			exchange_interval,
+			exchange_protocol,
+			exchange_requests: exchange_requests.fuse(),
+			pending_exchanges: FuturesUnordered::new(),
+			persisted_cache_path: config.persisted_cache_path,
+			persisting: Arc::new(AtomicBool::new(false)),
 			role,
 			metrics,
 			phantom: PhantomData,
-		}
+		};
+		worker.load_persisted_records();
+		worker
 	}
 
 	/// Start the worker
@@ -284,6 +371,20 @@ where
 							"Failed to request addresses of authorities: {}", e,
 						);
 					}
+					self.persist_records();
+				},
+				// Exchange records with other authorities directly.
+				_ = self.exchange_interval.next().fuse() => {
+					self.start_record_exchanges();
+					self.persist_records();
+				},
+				// Answer the records exchanges started by other authorities.
+				request = self.exchange_requests.select_next_some() => {
+					self.handle_exchange_request(request);
+				},
+				// Process the records received from other authorities.
+				(peer, response) = self.pending_exchanges.select_next_some() => {
+					self.handle_exchange_response(peer, response);
 				},
 			}
 		}
@@ -359,7 +460,7 @@ where
 				.set(addresses.len().try_into().unwrap_or(std::u64::MAX));
 		}
 
-		let serialized_record = serialize_authority_record(addresses)?;
+		let serialized_record = serialize_authority_record(addresses, Some(unix_time_nanos()))?;
 		let peer_signature = sign_record_with_peer_id(&serialized_record, self.network.as_ref())?;
 
 		let keys_vec = keys.iter().cloned().collect::<Vec<_>>();
@@ -368,9 +469,14 @@ where
 			serialized_record,
 			Some(peer_signature),
 			key_store.as_ref(),
-			keys_vec,
+			keys_vec.clone(),
 		)?;
 
+		self.own_records = keys_vec
+			.into_iter()
+			.zip(kv_pairs.iter().map(|(_, value)| value.clone()))
+			.collect();
+
 		for (key, value) in kv_pairs.into_iter() {
 			self.network.put_value(key, value);
 		}
@@ -401,6 +507,10 @@ where
 			.collect::<Vec<_>>();
 
 		self.addr_cache.retain_ids(&authorities);
+		self.authorities = authorities.iter().cloned().collect();
+		let known_records = self.known_records.len();
+		self.known_records.retain(|id, _| self.authorities.contains(id));
+		self.known_records_changed |= self.known_records.len() != known_records;
 
 		authorities.shuffle(&mut thread_rng());
 		self.pending_lookups = authorities;
@@ -504,83 +614,356 @@ where
 			.remove(&remote_key)
 			.ok_or(Error::ReceivingUnexpectedRecord)?;
 
+		let records = values
+			.into_iter()
+			.map(|(_k, v)| Ok((self.verify_record(&authority_id, &v)?, v)))
+			.collect::<Result<Vec<_>>>()?;
+
+		self.insert_records(authority_id, records);
+		Ok(())
+	}
+
+	/// Verify the signatures of an encoded [`schema::SignedAuthorityRecord`] of `authority_id`.
+	fn verify_record(
+		&self,
+		authority_id: &AuthorityId,
+		signed_record: &[u8],
+	) -> Result<VerifiedRecord> {
+		let schema::SignedAuthorityRecord { record, auth_signature, peer_signature } =
+			schema::SignedAuthorityRecord::decode(signed_record).map_err(Error::DecodingProto)?;
+
+		let auth_signature = AuthoritySignature::decode(&mut &auth_signature[..])
+			.map_err(Error::EncodingDecodingScale)?;
+
+		if !AuthorityPair::verify(&auth_signature, &record, authority_id) {
+			return Err(Error::VerifyingDhtPayload)
+		}
+
+		let schema::AuthorityRecord { addresses, creation_time } =
+			schema::AuthorityRecord::decode(record.as_slice()).map_err(Error::DecodingProto)?;
+
+		let addresses: Vec<Multiaddr> = addresses
+			.into_iter()
+			.map(|a| a.try_into())
+			.collect::<std::result::Result<_, _>>()
+			.map_err(Error::ParsingMultiaddress)?;
+
+		let creation_time = creation_time
+			.map(|t| u128::decode(&mut &t.timestamp[..]))
+			.transpose()
+			.map_err(Error::EncodingDecodingScale)?;
+
+		let get_peer_id = |a: &Multiaddr| match a.iter().last() {
+			Some(multiaddr::Protocol::P2p(key)) => PeerId::from_multihash(key).ok(),
+			_ => None,
+		};
+
 		let local_peer_id = self.network.local_peer_id();
 
-		let remote_addresses: Vec<Multiaddr> = values
+		// Ignore [`Multiaddr`]s without [`PeerId`] or with own addresses.
+		let addresses: Vec<Multiaddr> = addresses
 			.into_iter()
-			.map(|(_k, v)| {
-				let schema::SignedAuthorityRecord { record, auth_signature, peer_signature } =
-					schema::SignedAuthorityRecord::decode(v.as_slice())
-						.map_err(Error::DecodingProto)?;
+			.filter(|a| get_peer_id(a).filter(|p| *p != local_peer_id).is_some())
+			.collect();
 
-				let auth_signature = AuthoritySignature::decode(&mut &auth_signature[..])
-					.map_err(Error::EncodingDecodingScale)?;
+		let remote_peer_id = single(addresses.iter().map(get_peer_id))
+			.map_err(|_| Error::ReceivingDhtValueFoundEventWithDifferentPeerIds)? // different peer_id in records
+			.flatten()
+			.ok_or(Error::ReceivingDhtValueFoundEventWithNoPeerIds)?; // no records with peer_id in them
 
-				if !AuthorityPair::verify(&auth_signature, &record, &authority_id) {
-					return Err(Error::VerifyingDhtPayload)
-				}
+		// At this point we know all the valid multiaddresses from the record, know that
+		// each of them belong to the same PeerId, we just need to check if the record is
+		// properly signed by the owner of the PeerId
 
-				let addresses: Vec<Multiaddr> = schema::AuthorityRecord::decode(record.as_slice())
-					.map(|a| a.addresses)
-					.map_err(Error::DecodingProto)?
-					.into_iter()
-					.map(|a| a.try_into())
-					.collect::<std::result::Result<_, _>>()
-					.map_err(Error::ParsingMultiaddress)?;
-
-				let get_peer_id = |a: &Multiaddr| match a.iter().last() {
-					Some(multiaddr::Protocol::P2p(key)) => PeerId::from_multihash(key).ok(),
-					_ => None,
-				};
-
-				// Ignore [`Multiaddr`]s without [`PeerId`] or with own addresses.
-				let addresses: Vec<Multiaddr> = addresses
-					.into_iter()
-					.filter(|a| get_peer_id(a).filter(|p| *p != local_peer_id).is_some())
-					.collect();
-
-				let remote_peer_id = single(addresses.iter().map(get_peer_id))
-					.map_err(|_| Error::ReceivingDhtValueFoundEventWithDifferentPeerIds)? // different peer_id in records
-					.flatten()
-					.ok_or(Error::ReceivingDhtValueFoundEventWithNoPeerIds)?; // no records with peer_id in them
-
-				// At this point we know all the valid multiaddresses from the record, know that
-				// each of them belong to the same PeerId, we just need to check if the record is
-				// properly signed by the owner of the PeerId
-
-				if let Some(peer_signature) = peer_signature {
-					let public_key = PublicKey::try_decode_protobuf(&peer_signature.public_key)
-						.map_err(Error::ParsingLibp2pIdentity)?;
-					let signature = Signature { public_key, bytes: peer_signature.signature };
-
-					if !signature.verify(record, &remote_peer_id) {
-						return Err(Error::VerifyingDhtPayload)
-					}
-				} else if self.strict_record_validation {
-					return Err(Error::MissingPeerIdSignature)
-				} else {
-					debug!(
-						target: LOG_TARGET,
-						"Received unsigned authority discovery record from {}", authority_id
-					);
-				}
-				Ok(addresses)
-			})
-			.collect::<Result<Vec<Vec<Multiaddr>>>>()?
+		if let Some(peer_signature) = peer_signature {
+			let public_key = PublicKey::try_decode_protobuf(&peer_signature.public_key)
+				.map_err(Error::ParsingLibp2pIdentity)?;
+			let signature = Signature { public_key, bytes: peer_signature.signature };
+
+			if !signature.verify(&record, &remote_peer_id) {
+				return Err(Error::VerifyingDhtPayload)
+			}
+		} else if self.strict_record_validation {
+			return Err(Error::MissingPeerIdSignature)
+		} else {
+			debug!(
+				target: LOG_TARGET,
+				"Received unsigned authority discovery record from {}", authority_id
+			);
+		}
+
+		Ok(VerifiedRecord { addresses, creation_time })
+	}
+
+	/// Cache the addresses of the verified `records` of `authority_id`, ignoring the ones older
+	/// than the record already known.
+	///
+	/// Returns whether any record was cached.
+	fn insert_records(
+		&mut self,
+		authority_id: AuthorityId,
+		records: Vec<(VerifiedRecord, Vec<u8>)>,
+	) -> bool {
+		// Records of old versions have no creation time, and are older than any which has one.
+		let known_creation_time = self.known_records.get(&authority_id).map(|r| r.creation_time);
+		let records = records
 			.into_iter()
-			.flatten()
+			.filter(|(record, _)| known_creation_time.map_or(true, |t| record.creation_time >= t))
+			.collect::<Vec<_>>();
+
+		let Some((newest, newest_signed)) = records.iter().max_by_key(|(r, _)| r.creation_time)
+		else {
+			debug!(target: LOG_TARGET, "Ignoring stale records of {}", authority_id);
+			return false
+		};
+		let known = KnownRecord {
+			signed_record: newest_signed.clone(),
+			creation_time: newest.creation_time,
+		};
+
+		let addresses: Vec<Multiaddr> = records
+			.into_iter()
+			.flat_map(|(record, _)| record.addresses)
 			.take(MAX_ADDRESSES_PER_AUTHORITY)
 			.collect();
+		if addresses.is_empty() {
+			return false
+		}
 
-		if !remote_addresses.is_empty() {
-			self.addr_cache.insert(authority_id, remote_addresses);
-			if let Some(metrics) = &self.metrics {
-				metrics
-					.known_authorities_count
-					.set(self.addr_cache.num_authority_ids().try_into().unwrap_or(std::u64::MAX));
+		self.addr_cache.insert(authority_id.clone(), addresses);
+		if self
+			.known_records
+			.get(&authority_id)
+			.map_or(true, |r| r.signed_record != known.signed_record)
+		{
+			self.known_records.insert(authority_id, known);
+			self.known_records_changed = true;
+		}
+
+		if let Some(metrics) = &self.metrics {
+			metrics
+				.known_authorities_count
+				.set(self.addr_cache.num_authority_ids().try_into().unwrap_or(std::u64::MAX));
+		}
+		true
+	}
+
+	/// Own records followed by the known records of other authorities.
+	fn records_to_share(&self) -> impl Iterator<Item = (&AuthorityId, &[u8])> {
+		self.own_records
+			.iter()
+			.map(|(id, record)| (id, record.as_slice()))
+			.chain(self.known_records.iter().map(|(id, r)| (id, r.signed_record.as_slice())))
+	}
+
+	/// Send the own records to a few connected authorities, requesting the records they know.
+	fn start_record_exchanges(&mut self) {
+		let Some(protocol) = &self.exchange_protocol else { return };
+
+		let request =
+			encode_records(self.own_records.iter().map(|(id, record)| (id, record.as_slice())));
+		let peers = self.addr_cache.peer_ids().cloned().collect::<Vec<_>>();
+		for peer in peers.choose_multiple(&mut thread_rng(), EXCHANGE_FANOUT).cloned() {
+			let (tx, rx) = oneshot::channel();
+			// Only exchange with the authorities already connected, the others are reached
+			// through the DHT.
+			self.network.start_request(
+				peer,
+				protocol.clone(),
+				request.clone(),
+				tx,
+				IfDisconnected::ImmediateError,
+			);
+			self.pending_exchanges.push(rx.map(move |response| (peer, response)).boxed());
+		}
+	}
+
+	/// Answer a record exchange request with the records we know.
+	///
+	/// Only the requests of peers known to be authorities are answered, and only their own records
+	/// are accepted from them.
+	fn handle_exchange_request(&mut self, request: IncomingRequest) {
+		let IncomingRequest { peer, payload, pending_response } = request;
+
+		let Some(peer_authorities) = self.addr_cache.get_authority_ids_by_peer_id(&peer).cloned()
+		else {
+			debug!(target: LOG_TARGET, "Refusing record exchange request from {}", peer);
+			let response = OutgoingResponse {
+				result: Err(()),
+				reputation_changes: Vec::new(),
+				sent_feedback: None,
+			};
+			let _ = pending_response.send(response);
+			return
+		};
+
+		let reputation_changes =
+			match self.handle_exchanged_records(&payload, Some(&peer_authorities)) {
+				Ok(_) => Vec::new(),
+				Err(e) => {
+					debug!(
+						target: LOG_TARGET,
+						"Invalid record exchange request from {}: {}", peer, e
+					);
+					vec![INVALID_RECORDS_REPUTATION_CHANGE]
+				},
+			};
+
+		let response = OutgoingResponse {
+			result: Ok(encode_records(self.records_to_share())),
+			reputation_changes,
+			sent_feedback: None,
+		};
+		if pending_response.send(response).is_err() {
+			debug!(target: LOG_TARGET, "Failed to answer record exchange request from {}", peer);
+		}
+	}
+
+	fn handle_exchange_response(&mut self, peer: PeerId, response: ExchangeResponse) {
+		match response {
+			Ok(Ok(payload)) =>
+				if let Err(e) = self.handle_exchanged_records(&payload, None) {
+					debug!(
+						target: LOG_TARGET,
+						"Invalid record exchange response from {}: {}", peer, e
+					);
+				},
+			Ok(Err(e)) => {
+				debug!(target: LOG_TARGET, "Failed to exchange records with {}: {:?}", peer, e)
+			},
+			Err(oneshot::Canceled) => {
+				debug!(target: LOG_TARGET, "Record exchange with {} was canceled", peer)
+			},
+		}
+	}
+
+	/// Cache the records received from another authority.
+	///
+	/// Records of authorities not in the current or next set are ignored, as are the ones already
+	/// known. The records of a request are limited to the ones of the requesting `authorities`,
+	/// while a response to our own request may carry up to `MAX_EXCHANGED_RECORDS` records.
+	/// Returns the number of cached records.
+	fn handle_exchanged_records(
+		&mut self,
+		payload: &[u8],
+		authorities: Option<&HashSet<AuthorityId>>,
+	) -> Result<usize> {
+		let records =
+			schema::AuthorityRecords::decode(payload).map_err(Error::DecodingProto)?.records;
+		let max_records = authorities.map_or(MAX_EXCHANGED_RECORDS, HashSet::len);
+
+		let mut cached = 0;
+		for schema::KeyedAuthorityRecord { authority_id, signed_record } in
+			records.into_iter().take(max_records)
+		{
+			let authority_id = AuthorityId::decode(&mut &authority_id[..])
+				.map_err(Error::EncodingDecodingScale)?;
+			if !self.authorities.contains(&authority_id) ||
+				authorities.map_or(false, |authorities| !authorities.contains(&authority_id))
+			{
+				continue
+			}
+			if self
+				.known_records
+				.get(&authority_id)
+				.map_or(false, |known| known.signed_record == signed_record)
+			{
+				continue
+			}
+
+			match self.verify_record(&authority_id, &signed_record) {
+				Ok(record) =>
+					if self.insert_records(authority_id, vec![(record, signed_record)]) {
+						cached += 1;
+					},
+				Err(e) => debug!(
+					target: LOG_TARGET,
+					"Ignoring exchanged record of {}: {}", authority_id, e
+				),
 			}
 		}
-		Ok(())
+
+		if let Some(metrics) = &self.metrics {
+			metrics.records_exchanged.inc_by(cached as u64);
+		}
+		Ok(cached)
+	}
+
+	/// Load the records persisted by a previous run.
+	///
+	/// The authority set is not known yet, so all the records with valid signatures are cached
+	/// until the first query of the authorities.
+	fn load_persisted_records(&mut self) {
+		let Some(path) = self.persisted_cache_path.clone() else { return };
+
+		let records = match std::fs::read(&path) {
+			Ok(payload) => match schema::AuthorityRecords::decode(payload.as_slice()) {
+				Ok(records) => records.records,
+				Err(e) => {
+					warn!(target: LOG_TARGET, "Failed to decode persisted records: {}", e);
+					return
+				},
+			},
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => return,
+			Err(e) => {
+				warn!(target: LOG_TARGET, "Failed to read persisted records {:?}: {}", path, e);
+				return
+			},
+		};
+
+		for schema::KeyedAuthorityRecord { authority_id, signed_record } in records {
+			let Ok(authority_id) = AuthorityId::decode(&mut &authority_id[..]) else { continue };
+			match self.verify_record(&authority_id, &signed_record) {
+				Ok(record) => {
+					self.insert_records(authority_id, vec![(record, signed_record)]);
+				},
+				Err(e) => debug!(
+					target: LOG_TARGET,
+					"Ignoring persisted record of {}: {}", authority_id, e
+				),
+			}
+		}
+
+		self.known_records_changed = false;
+		debug!(target: LOG_TARGET, "Loaded {} persisted records", self.known_records.len());
+	}
+
+	/// Persist the known records if they changed.
+	///
+	/// The records are written on a separate thread, returned unless there was nothing to write.
+	fn persist_records(&mut self) -> Option<JoinHandle<()>> {
+		let path = self.persisted_cache_path.clone()?;
+		if !self.known_records_changed || self.persisting.swap(true, Ordering::AcqRel) {
+			return None
+		}
+
+		let payload = encode_records(
+			self.known_records.iter().map(|(id, r)| (id, r.signed_record.as_slice())),
+		);
+		self.known_records_changed = false;
+		let persisting = self.persisting.clone();
+		let write = move || {
+			// Write a temporary file first, so that a crash can't leave a truncated file behind.
+			let tmp_path = path.with_extension("tmp");
+			if let Err(e) =
+				std::fs::write(&tmp_path, payload).and_then(|_| std::fs::rename(&tmp_path, &path))
+			{
+				warn!(target: LOG_TARGET, "Failed to persist records to {:?}: {}", path, e);
+			}
+			persisting.store(false, Ordering::Release);
+		};
+		match std::thread::Builder::new()
+			.name("authority-discovery-persist".into())
+			.spawn(write)
+		{
+			Ok(handle) => Some(handle),
+			Err(e) => {
+				warn!(target: LOG_TARGET, "Failed to spawn the thread persisting records: {}", e);
+				self.known_records_changed = true;
+				self.persisting.store(false, Ordering::Release);
+				None
+			},
+		}
 	}
 
 	/// Retrieve our public keys within the current and next authority set.
@@ -616,9 +999,15 @@ where
 /// NetworkProvider provides [`Worker`] with all necessary hooks into the
 /// underlying Substrate networking. Using this trait abstraction instead of
 /// `sc_network::NetworkService` directly is necessary to unit test [`Worker`].
-pub trait NetworkProvider: NetworkDHTProvider + NetworkStateInfo + NetworkSigner {}
+pub trait NetworkProvider:
+	NetworkDHTProvider + NetworkStateInfo + NetworkSigner + NetworkRequest
+{
+}
 
-impl<T> NetworkProvider for T where T: NetworkDHTProvider + NetworkStateInfo + NetworkSigner {}
+impl<T> NetworkProvider for T where
+	T: NetworkDHTProvider + NetworkStateInfo + NetworkSigner + NetworkRequest
+{
+}
 
 fn hash_authority_id(id: &[u8]) -> KademliaKey {
 	KademliaKey::new(&Code::Sha2_256.digest(id).digest())
@@ -643,14 +1032,36 @@ fn serialize_addresses(addresses: impl Iterator<Item = Multiaddr>) -> Vec<Vec<u8
 	addresses.map(|a| a.to_vec()).collect()
 }
 
-fn serialize_authority_record(addresses: Vec<Vec<u8>>) -> Result<Vec<u8>> {
+fn unix_time_nanos() -> u128 {
+	SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_nanos())
+}
+
+fn serialize_authority_record(
+	addresses: Vec<Vec<u8>>,
+	creation_time: Option<u128>,
+) -> Result<Vec<u8>> {
+	let creation_time = creation_time.map(|t| schema::TimestampInfo { timestamp: t.encode() });
 	let mut serialized_record = vec![];
-	schema::AuthorityRecord { addresses }
+	schema::AuthorityRecord { addresses, creation_time }
 		.encode(&mut serialized_record)
 		.map_err(Error::EncodingProto)?;
 	Ok(serialized_record)
 }
 
+/// Encode records as [`schema::AuthorityRecords`], to be exchanged or persisted.
+fn encode_records<'a>(records: impl Iterator<Item = (&'a AuthorityId, &'a [u8])>) -> Vec<u8> {
+	schema::AuthorityRecords {
+		records: records
+			.take(MAX_EXCHANGED_RECORDS)
+			.map(|(authority_id, signed_record)| schema::KeyedAuthorityRecord {
+				authority_id: authority_id.encode(),
+				signed_record: signed_record.to_vec(),
+			})
+			.collect(),
+	}
+	.encode_to_vec()
+}
+
 fn sign_record_with_peer_id(
 	serialized_record: &[u8],
 	network: &impl NetworkSigner,
@@ -705,6 +1116,7 @@ pub(crate) struct Metrics {
 	dht_event_received: CounterVec<U64>,
 	handle_value_found_event_failure: Counter<U64>,
 	known_authorities_count: Gauge<U64>,
+	records_exchanged: Counter<U64>,
 }
 
 impl Metrics {
@@ -764,6 +1176,13 @@ impl Metrics {
 				)?,
 				registry,
 			)?,
+			records_exchanged: register(
+				Counter::new(
+					"substrate_authority_discovery_records_exchanged_total",
+					"Number of records received from other authorities directly and cached.",
+				)?,
+				registry,
+			)?,
 		})
 	}
 }
@@ -130,6 +130,11 @@ impl AddrCache {
 		self.peer_id_to_authority_ids.get(peer_id)
 	}
 
+	/// Returns the [`PeerId`]s of all authorities in the cache.
+	pub fn peer_ids(&self) -> impl Iterator<Item = &PeerId> {
+		self.peer_id_to_authority_ids.keys()
+	}
+
 	/// Removes all [`PeerId`]s and [`Multiaddr`]s from the cache that are not related to the given
 	/// [`AuthorityId`]s.
 	pub fn retain_ids(&mut self, authority_ids: &[AuthorityId]) {
@@ -129,6 +129,7 @@ pub struct TestNetwork {
 	// vectors below.
 	pub put_value_call: Arc<Mutex<Vec<(KademliaKey, Vec<u8>)>>>,
 	pub get_value_call: Arc<Mutex<Vec<KademliaKey>>>,
+	pub start_request_call: Arc<Mutex<Vec<(PeerId, ProtocolName, Vec<u8>)>>>,
 	event_sender: mpsc::UnboundedSender<TestNetworkEvent>,
 	event_receiver: Option<mpsc::UnboundedReceiver<TestNetworkEvent>>,
 }
@@ -149,6 +150,7 @@ impl Default for TestNetwork {
 			external_addresses: vec!["/ip6/2001:db8::/tcp/30333".parse().unwrap()],
 			put_value_call: Default::default(),
 			get_value_call: Default::default(),
+			start_request_call: Default::default(),
 			event_sender: tx,
 			event_receiver: Some(rx),
 		}
@@ -181,6 +183,31 @@ impl NetworkDHTProvider for TestNetwork {
 	}
 }
 
+#[async_trait::async_trait]
+impl NetworkRequest for TestNetwork {
+	async fn request(
+		&self,
+		_target: PeerId,
+		_protocol: ProtocolName,
+		_request: Vec<u8>,
+		_connect: IfDisconnected,
+	) -> std::result::Result<Vec<u8>, RequestFailure> {
+		// The worker only starts requests.
+		Err(RequestFailure::UnknownProtocol)
+	}
+
+	fn start_request(
+		&self,
+		target: PeerId,
+		protocol: ProtocolName,
+		request: Vec<u8>,
+		_tx: oneshot::Sender<std::result::Result<Vec<u8>, RequestFailure>>,
+		_connect: IfDisconnected,
+	) {
+		self.start_request_call.lock().unwrap().push((target, protocol, request));
+	}
+}
+
 impl NetworkStateInfo for TestNetwork {
 	fn local_peer_id(&self) -> PeerId {
 		self.peer_id
@@ -213,9 +240,20 @@ fn build_dht_event<Signer: NetworkSigner>(
 	public_key: AuthorityId,
 	key_store: &MemoryKeystore,
 	network: Option<&Signer>,
+) -> Vec<(KademliaKey, Vec<u8>)> {
+	build_dht_event_created_at(addresses, public_key, key_store, network, None)
+}
+
+fn build_dht_event_created_at<Signer: NetworkSigner>(
+	addresses: Vec<Multiaddr>,
+	public_key: AuthorityId,
+	key_store: &MemoryKeystore,
+	network: Option<&Signer>,
+	creation_time: Option<u128>,
 ) -> Vec<(KademliaKey, Vec<u8>)> {
 	let serialized_record =
-		serialize_authority_record(serialize_addresses(addresses.into_iter())).unwrap();
+		serialize_authority_record(serialize_addresses(addresses.into_iter()), creation_time)
+			.unwrap();
 
 	let peer_signature = network.map(|n| sign_record_with_peer_id(&serialized_record, n).unwrap());
 	let kv_pairs = sign_record_with_authority_ids(
@@ -844,3 +882,142 @@ fn lookup_throttling() {
 		.boxed_local(),
 	);
 }
+
+type TestWorker = Worker<
+	TestApi,
+	TestNetwork,
+	Block,
+	std::pin::Pin<Box<futures::channel::mpsc::Receiver<DhtEvent>>>,
+>;
+
+fn new_test_worker(
+	authorities: Vec<AuthorityId>,
+	network: Arc<TestNetwork>,
+	config: WorkerConfig,
+) -> TestWorker {
+	let (_dht_event_tx, dht_event_rx) = channel(1);
+	let (_to_worker, from_service) = mpsc::channel(0);
+	Worker::new(
+		from_service,
+		Arc::new(TestApi { authorities }),
+		network,
+		Box::pin(dht_event_rx),
+		Role::PublishAndDiscover(Arc::new(MemoryKeystore::new())),
+		None,
+		config,
+	)
+}
+
+#[test]
+fn exchanged_records_only_replace_older_ones() {
+	let tester = DhtValueFoundTester::new();
+	let remote_public: AuthorityId = tester.remote_authority_public.into();
+	let signer = TestSigner { keypair: &tester.remote_node_key };
+	let signed_record = |idx, creation_time| {
+		build_dht_event_created_at(
+			vec![tester.multiaddr_with_peer_id(idx)],
+			remote_public.clone(),
+			&tester.remote_key_store,
+			Some(&signer),
+			Some(creation_time),
+		)
+		.pop()
+		.unwrap()
+		.1
+	};
+	let (old_record, new_record) = (signed_record(1, 1), signed_record(2, 2));
+
+	let network = Arc::new(TestNetwork::default());
+	let (record_exchange, _) = crate::record_exchange_protocol_config(&[0; 32], None);
+	let mut worker = new_test_worker(
+		vec![remote_public.clone()],
+		network.clone(),
+		WorkerConfig { record_exchange: Some(record_exchange), ..Default::default() },
+	);
+	block_on(worker.refill_pending_lookups_queue()).unwrap();
+
+	let payload = encode_records([(&remote_public, new_record.as_slice())].into_iter());
+	assert_eq!(worker.handle_exchanged_records(&payload, None).unwrap(), 1);
+	let payload = encode_records([(&remote_public, old_record.as_slice())].into_iter());
+	assert_eq!(worker.handle_exchanged_records(&payload, None).unwrap(), 0);
+	assert_eq!(
+		worker.addr_cache.get_addresses_by_authority_id(&remote_public),
+		Some(&HashSet::from([tester.multiaddr_with_peer_id(2)])),
+	);
+
+	// Records of authorities outside of the current and next set are ignored.
+	let unknown_key_store = MemoryKeystore::new();
+	let unknown_public: AuthorityId = unknown_key_store
+		.sr25519_generate_new(key_types::AUTHORITY_DISCOVERY, None)
+		.unwrap()
+		.into();
+	let unknown_record = build_dht_event_created_at(
+		vec![tester.multiaddr_with_peer_id(3)],
+		unknown_public.clone(),
+		&unknown_key_store,
+		Some(&signer),
+		Some(3),
+	)
+	.pop()
+	.unwrap()
+	.1;
+	let payload = encode_records([(&unknown_public, unknown_record.as_slice())].into_iter());
+	assert_eq!(worker.handle_exchanged_records(&payload, None).unwrap(), 0);
+
+	// The known records are sent to the authorities we know the addresses of.
+	worker.start_record_exchanges();
+	let requests = network.start_request_call.lock().unwrap().clone();
+	assert_eq!(requests.len(), 1);
+	assert_eq!(requests[0].0, tester.remote_node_key.public().to_peer_id());
+
+	// And returned to the authorities starting an exchange.
+	let mut exchange = |peer, payload| {
+		let (pending_response, response) = oneshot::channel();
+		worker.handle_exchange_request(IncomingRequest { peer, payload, pending_response });
+		block_on(response).unwrap()
+	};
+	let remote_peer = tester.remote_node_key.public().to_peer_id();
+	let response = exchange(remote_peer, encode_records(std::iter::empty())).result.unwrap();
+	let records = schema::AuthorityRecords::decode(response.as_slice()).unwrap().records;
+	assert_eq!(records.len(), 1);
+	assert_eq!(records[0].signed_record, new_record);
+
+	// Other peers are refused without looking at their records.
+	let payload = encode_records([(&remote_public, old_record.as_slice())].into_iter());
+	let response = exchange(PeerId::random(), payload);
+	assert_eq!(response.result, Err(()));
+	assert!(response.reputation_changes.is_empty());
+}
+
+#[test]
+fn records_are_persisted_and_loaded_on_startup() {
+	let tester = DhtValueFoundTester::new();
+	let remote_public: AuthorityId = tester.remote_authority_public.into();
+	let kv_pairs = build_dht_event_created_at(
+		vec![tester.multiaddr_with_peer_id(1)],
+		remote_public.clone(),
+		&tester.remote_key_store,
+		Some(&TestSigner { keypair: &tester.remote_node_key }),
+		Some(1),
+	);
+
+	let dir = tempfile::tempdir().unwrap();
+	let config = || WorkerConfig {
+		persisted_cache_path: Some(dir.path().join("authority-discovery-records")),
+		..Default::default()
+	};
+
+	let mut worker =
+		new_test_worker(vec![remote_public.clone()], Arc::new(TestNetwork::default()), config());
+	block_on(worker.refill_pending_lookups_queue()).unwrap();
+	worker.start_new_lookups();
+	worker.handle_dht_value_found_event(kv_pairs).unwrap();
+	worker.persist_records().unwrap().join().unwrap();
+	drop(worker);
+
+	let worker = new_test_worker(vec![], Arc::new(TestNetwork::default()), config());
+	assert_eq!(
+		worker.addr_cache.get_addresses_by_authority_id(&remote_public),
+		Some(&HashSet::from([tester.multiaddr_with_peer_id(1)])),
+	);
+}
@@ -317,6 +317,24 @@ struct ClientSpec<E> {
 	/// given block number until the `spec_version` on chain changes.
 	#[serde(default)]
 	code_substitutes: BTreeMap<String, Bytes>,
+	/// Trusted block from which warp sync starts instead of genesis.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	checkpoint: Option<SyncCheckpoint>,
+}
+
+/// Trusted block from which warp sync starts instead of genesis.
+///
+/// The warp proofs are requested from this block and verified against the given GRANDPA authority
+/// set, so only the authority set changes that happened after it have to be downloaded.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SyncCheckpoint {
+	/// SCALE encoded header of the block.
+	pub header: Bytes,
+	/// Id of the GRANDPA authority set that finalizes the blocks following this one.
+	pub grandpa_set_id: u64,
+	/// SCALE encoded list of the authorities of this set, with their weights.
+	pub grandpa_authorities: Bytes,
 }
 
 /// A type denoting empty extensions.
@@ -446,6 +464,7 @@ impl<G, E, EHF> ChainSpecBuilder<G, E, EHF> {
 			consensus_engine: (),
 			genesis: Default::default(),
 			code_substitutes: BTreeMap::new(),
+			checkpoint: None,
 		};
 
 		ChainSpec {
@@ -561,6 +580,7 @@ impl<G, E, EHF> ChainSpec<G, E, EHF> {
 			consensus_engine: (),
 			genesis: Default::default(),
 			code_substitutes: BTreeMap::new(),
+			checkpoint: None,
 		};
 
 		ChainSpec {
@@ -764,6 +784,14 @@ where
 			.map(|(h, c)| (h.clone(), c.0.clone()))
 			.collect()
 	}
+
+	fn checkpoint(&self) -> Option<&SyncCheckpoint> {
+		self.client_spec.checkpoint.as_ref()
+	}
+
+	fn set_checkpoint(&mut self, checkpoint: Option<SyncCheckpoint>) {
+		self.client_spec.checkpoint = checkpoint;
+	}
 }
 
 /// The `fun` will be called with the value at `path`.
@@ -908,6 +936,32 @@ mod tests {
 		assert_eq!(spec.extensions().my_property, "Test Extension");
 	}
 
+	#[test]
+	fn chain_spec_checkpoint_roundtrip() {
+		let mut spec = TestSpec2::from_json_bytes(Cow::Owned(
+			include_bytes!("../res/chain_spec2.json").to_vec(),
+		))
+		.unwrap();
+		assert_eq!(crate::ChainSpec::checkpoint(&spec), None);
+		assert!(!spec.as_json(false).unwrap().contains("checkpoint"));
+
+		let checkpoint = SyncCheckpoint {
+			header: vec![1, 2, 3].into(),
+			grandpa_set_id: 42,
+			grandpa_authorities: vec![4, 5].into(),
+		};
+		crate::ChainSpec::set_checkpoint(&mut spec, Some(checkpoint.clone()));
+
+		let json = spec.as_json(false).unwrap();
+		let value: json::Value = json::from_str(&json).unwrap();
+		assert_eq!(
+			value["checkpoint"],
+			json!({"header": "0x010203", "grandpaSetId": 42, "grandpaAuthorities": "0x0405"})
+		);
+		let spec = TestSpec2::from_json_bytes(Cow::Owned(json.into_bytes())).unwrap();
+		assert_eq!(crate::ChainSpec::checkpoint(&spec), Some(&checkpoint));
+	}
+
 	#[test]
 	fn chain_spec_raw_output_should_be_deterministic() {
 		let mut spec = TestSpec2::from_json_bytes(Cow::Owned(
@@ -330,7 +330,7 @@ mod json_patch;
 pub use self::{
 	chain_spec::{
 		update_code_in_json_chain_spec, ChainSpec as GenericChainSpec, ChainSpecBuilder,
-		NoExtension,
+		NoExtension, SyncCheckpoint,
 	},
 	extension::{get_extension, get_extension_mut, Extension, Fork, Forks, GetExtension, Group},
 	genesis_block::{
@@ -414,6 +414,17 @@ pub trait ChainSpec: BuildStorage + Send + Sync {
 	fn set_storage(&mut self, storage: Storage);
 	/// Returns code substitutes that should be used for the on chain wasm.
 	fn code_substitutes(&self) -> std::collections::BTreeMap<String, Vec<u8>>;
+	/// Trusted block from which warp sync starts instead of genesis, if any.
+	///
+	/// By default there is no checkpoint.
+	fn checkpoint(&self) -> Option<&SyncCheckpoint> {
+		None
+	}
+	/// Set the block from which warp sync starts.
+	///
+	/// By default the checkpoint is ignored, chain specs that support it must override both
+	/// methods.
+	fn set_checkpoint(&mut self, _checkpoint: Option<SyncCheckpoint>) {}
 }
 
 impl std::fmt::Debug for dyn ChainSpec {
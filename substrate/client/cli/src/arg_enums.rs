@@ -221,6 +221,25 @@ pub enum OffchainWorkerEnabled {
 	WhenAuthority,
 }
 
+/// Blocks the off-chain workers are run for.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+#[value(rename_all = "kebab-case")]
+pub enum OffchainWorkerTrigger {
+	/// Every imported block which is the new best block.
+	NewBest,
+	/// Every finalized block.
+	Finalized,
+}
+
+impl From<OffchainWorkerTrigger> for sc_offchain::WorkerTrigger {
+	fn from(trigger: OffchainWorkerTrigger) -> Self {
+		match trigger {
+			OffchainWorkerTrigger::NewBest => Self::NewBest,
+			OffchainWorkerTrigger::Finalized => Self::Finalized,
+		}
+	}
+}
+
 /// Syncing mode.
 #[derive(Debug, Clone, Copy, ValueEnum, PartialEq)]
 #[value(rename_all = "kebab-case")]
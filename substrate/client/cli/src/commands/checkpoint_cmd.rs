@@ -0,0 +1,103 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{error, params::SharedParams, CliConfiguration};
+use clap::Parser;
+use log::info;
+use sc_service::{ChainSpec, SyncCheckpoint};
+use std::{io::Write, path::PathBuf};
+
+/// The `checkpoint` command used to build a chain spec with a warp sync checkpoint.
+///
+/// The checkpoint is the output of the `sync_state_genCheckpoint` RPC method of a node following
+/// the chain, e.g. saved with `curl -H "Content-Type: application/json" -d
+/// '{"id":1,"jsonrpc":"2.0","method":"sync_state_genCheckpoint"}' http://127.0.0.1:9944`. It is
+/// added to the chain spec selected with `--chain`, which is printed to stdout.
+#[derive(Debug, Clone, Parser)]
+pub struct CheckpointCmd {
+	/// Path to the checkpoint, either the whole JSON-RPC response or only its `result`.
+	#[arg(long, value_name = "PATH")]
+	pub checkpoint: PathBuf,
+
+	/// Force raw genesis storage output.
+	#[arg(long)]
+	pub raw: bool,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+}
+
+impl CheckpointCmd {
+	/// Run the checkpoint command
+	pub fn run(&self, mut spec: Box<dyn ChainSpec>) -> error::Result<()> {
+		info!("Adding warp sync checkpoint from {}", self.checkpoint.display());
+		let checkpoint = parse_checkpoint(&std::fs::read(&self.checkpoint)?)?;
+		spec.set_checkpoint(Some(checkpoint));
+		if spec.checkpoint().is_none() {
+			return Err("The chain spec doesn't support warp sync checkpoints".into())
+		}
+
+		let json = sc_service::chain_ops::build_spec(&*spec, self.raw)?;
+		if std::io::stdout().write_all(json.as_bytes()).is_err() {
+			let _ = std::io::stderr().write_all(b"Error writing to stdout\n");
+		}
+		Ok(())
+	}
+}
+
+/// Decode a checkpoint, possibly wrapped in the JSON-RPC response that returned it.
+fn parse_checkpoint(json: &[u8]) -> error::Result<SyncCheckpoint> {
+	let mut value: serde_json::Value = serde_json::from_slice(json)
+		.map_err(|e| format!("Failed to parse the checkpoint: {}", e))?;
+	if let Some(error) = value.get("error") {
+		return Err(format!("The checkpoint request failed: {}", error).into())
+	}
+	if let Some(result) = value.get_mut("result") {
+		value = result.take();
+	}
+	serde_json::from_value(value).map_err(|e| format!("Invalid checkpoint: {}", e).into())
+}
+
+impl CliConfiguration for CheckpointCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parses_checkpoint_with_or_without_rpc_response() {
+		let checkpoint = SyncCheckpoint {
+			header: vec![1, 2, 3].into(),
+			grandpa_set_id: 42,
+			grandpa_authorities: vec![4, 5].into(),
+		};
+		let result = r#"{"header":"0x010203","grandpaSetId":42,"grandpaAuthorities":"0x0405"}"#;
+		assert_eq!(parse_checkpoint(result.as_bytes()).unwrap(), checkpoint);
+
+		let response = format!(r#"{{"jsonrpc":"2.0","id":1,"result":{}}}"#, result);
+		assert_eq!(parse_checkpoint(response.as_bytes()).unwrap(), checkpoint);
+
+		let error = r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32602,"message":"oops"}}"#;
+		assert!(parse_checkpoint(error.as_bytes()).is_err());
+	}
+}
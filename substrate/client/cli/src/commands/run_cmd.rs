@@ -27,14 +27,22 @@ use crate::{
 	RPC_DEFAULT_MAX_CONNECTIONS, RPC_DEFAULT_MAX_REQUEST_SIZE_MB, RPC_DEFAULT_MAX_RESPONSE_SIZE_MB,
 	RPC_DEFAULT_MAX_SUBS_PER_CONN,
 };
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use regex::Regex;
 use sc_service::{
-	config::{BasePath, PrometheusConfig, TransactionPoolOptions},
+	config::{
+		BasePath, InformantOutputFormat, PrometheusConfig, RateLimit, RpcEndpoint, RpcRateLimit,
+		TelemetryLocalSinkConfig, TransactionPoolOptions,
+	},
 	ChainSpec, Role,
 };
 use sc_telemetry::TelemetryEndpoints;
-use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::{
+	fmt::Display,
+	net::{IpAddr, Ipv4Addr, SocketAddr},
+	num::NonZeroU32,
+	str::FromStr,
+};
 
 /// The `run` command used to run a node.
 #[derive(Debug, Clone, Parser)]
@@ -90,14 +98,76 @@ pub struct RunCmd {
 	#[arg(long, default_value_t = RPC_DEFAULT_MAX_RESPONSE_SIZE_MB)]
 	pub rpc_max_response_size: u32,
 
+	/// Set the maximum number of calls in an RPC batch request.
+	///
+	/// `0` disables batch requests, by default they are not limited.
+	#[arg(long, value_name = "LEN")]
+	pub rpc_max_batch_request_len: Option<u32>,
+
 	/// Set the maximum concurrent subscriptions per connection.
 	#[arg(long, default_value_t = RPC_DEFAULT_MAX_SUBS_PER_CONN)]
 	pub rpc_max_subscriptions_per_connection: u32,
 
+	/// Limit the cost of the RPC calls of every connection per second.
+	///
+	/// A call costs `1` unless configured otherwise with `--rpc-method-cost`. Calls exceeding
+	/// the limit are rejected with a "rate limited" error. No limit is applied by default.
+	#[arg(long, value_name = "COST")]
+	pub rpc_rate_limit: Option<NonZeroU32>,
+
+	/// Limit the cost of the RPC calls of every client IP address per second.
+	///
+	/// The address of clients connecting through a `--rpc-rate-limit-trusted-proxy` is taken from
+	/// the `X-Forwarded-For` or `X-Real-IP` header set by the proxy.
+	#[arg(long, value_name = "COST")]
+	pub rpc_rate_limit_per_ip: Option<NonZeroU32>,
+
+	/// The address of a reverse proxy in front of the node whose `X-Forwarded-For` and
+	/// `X-Real-IP` headers are trusted by the rate limiter. Can be passed multiple times.
+	#[arg(long, value_name = "IP")]
+	pub rpc_rate_limit_trusted_proxy: Vec<IpAddr>,
+
+	/// The cost a client can spend at once before being rate limited.
+	///
+	/// Defaults to the respective `--rpc-rate-limit` and `--rpc-rate-limit-per-ip`.
+	#[arg(long, value_name = "COST")]
+	pub rpc_rate_limit_burst: Option<NonZeroU32>,
+
+	/// The cost of calling an RPC method, e.g. `state_call=10`. Can be passed multiple times.
+	#[arg(long, value_name = "METHOD=COST", value_parser = parse_method_cost)]
+	pub rpc_method_cost: Vec<(String, NonZeroU32)>,
+
 	/// Specify JSON-RPC server TCP port.
 	#[arg(long, value_name = "PORT")]
 	pub rpc_port: Option<u16>,
 
+	/// Listen for JSON-RPC on an endpoint with its own settings. Can be passed multiple times.
+	///
+	/// If given, the endpoints replace the listener configured by `--rpc-port`, `--rpc-external`
+	/// and `--unsafe-rpc-external`. An endpoint is a comma-separated list of `key=value` settings,
+	/// e.g. `listen-addr=0.0.0.0:9944,rpc-methods=safe,allow=chainHead_*,allow=transaction_*`.
+	///
+	/// Supported settings:
+	///
+	///  * `listen-addr`: the address to listen on, required.
+	///  * `retry-random-port`: listen on a random port if the address is not available.
+	///  * `rpc-methods`: `auto`, `safe` or `unsafe`.
+	///  * `allow`: a method or a method prefix followed by `*` to expose exclusively.
+	///  * `cors`: an allowed origin or `all`.
+	///  * `max-connections`, `max-subscriptions-per-connection`, `max-request-size`,
+	///    `max-response-size`, `max-batch-request-len`, `rate-limit`, `rate-limit-per-ip` and
+	///    `rate-limit-burst`.
+	///
+	/// `allow` and `cors` can be given multiple times. Settings that are not given fall back to
+	/// the respective `--rpc-*` option.
+	#[arg(
+		long,
+		value_name = "SETTINGS",
+		value_parser = parse_rpc_endpoint,
+		verbatim_doc_comment
+	)]
+	pub rpc_endpoint: Vec<RpcEndpointArg>,
+
 	/// Maximum number of RPC server connections.
 	#[arg(long, value_name = "COUNT", default_value_t = RPC_DEFAULT_MAX_CONNECTIONS)]
 	pub rpc_max_connections: u32,
@@ -117,6 +187,15 @@ pub struct RunCmd {
 	#[arg(long, value_name = "NAME")]
 	pub name: Option<String>,
 
+	/// Print the informant output to stdout as JSON objects, one per line, instead of logging
+	/// human-readable lines.
+	///
+	/// The periodic status contains the best and finalized blocks, the peers, the sync state,
+	/// the transferred bytes and rates and the import queue. Imported blocks and reorgs are
+	/// printed as well.
+	#[arg(long)]
+	pub informant_json: bool,
+
 	#[allow(missing_docs)]
 	#[clap(flatten)]
 	pub telemetry_params: TelemetryParams,
@@ -310,6 +389,10 @@ impl CliConfiguration for RunCmd {
 		})
 	}
 
+	fn telemetry_local_sink(&self) -> Result<Option<TelemetryLocalSinkConfig>> {
+		Ok(self.telemetry_params.local_sink())
+	}
+
 	fn role(&self, is_dev: bool) -> Result<Role> {
 		let keyring = self.get_keyring();
 		let is_authority = self.validator || is_dev || keyring.is_some();
@@ -336,6 +419,10 @@ impl CliConfiguration for RunCmd {
 		Ok(self.no_grandpa)
 	}
 
+	fn informant_output_format(&self) -> Result<InformantOutputFormat> {
+		Ok(InformantOutputFormat { json: self.informant_json, ..Default::default() })
+	}
+
 	fn rpc_max_connections(&self) -> Result<u32> {
 		Ok(self.rpc_max_connections)
 	}
@@ -388,6 +475,82 @@ impl CliConfiguration for RunCmd {
 		Ok(self.rpc_max_subscriptions_per_connection)
 	}
 
+	fn rpc_max_batch_request_len(&self) -> Result<Option<u32>> {
+		Ok(self.rpc_max_batch_request_len)
+	}
+
+	fn rpc_rate_limit(&self) -> Result<RpcRateLimit> {
+		let limit = |per_second| RateLimit {
+			per_second,
+			burst: self.rpc_rate_limit_burst.unwrap_or(per_second),
+		};
+
+		Ok(RpcRateLimit {
+			per_connection: self.rpc_rate_limit.map(limit),
+			per_ip: self.rpc_rate_limit_per_ip.map(limit),
+			trusted_proxies: self.rpc_rate_limit_trusted_proxy.clone(),
+			method_costs: self.rpc_method_cost.iter().cloned().collect(),
+		})
+	}
+
+	fn rpc_endpoints(&self, is_dev: bool) -> Result<Vec<RpcEndpoint>> {
+		if self.rpc_endpoint.is_empty() {
+			return Ok(Vec::new())
+		}
+
+		let cors = self.rpc_cors(is_dev)?;
+		let rate_limit = self.rpc_rate_limit()?;
+
+		Ok(self
+			.rpc_endpoint
+			.iter()
+			.map(|endpoint| {
+				let rpc_methods = endpoint.rpc_methods.unwrap_or(self.rpc_methods);
+				if rpc_methods == RpcMethods::Unsafe && !endpoint.listen_addr.ip().is_loopback() {
+					log::warn!(
+						"It isn't safe to expose RPC publicly without a proxy server that filters \
+						 available set of RPC methods. Endpoint: {}",
+						endpoint.listen_addr,
+					);
+				}
+
+				let burst = endpoint.rate_limit_burst.or(self.rpc_rate_limit_burst);
+				let limit =
+					|per_second| RateLimit { per_second, burst: burst.unwrap_or(per_second) };
+
+				RpcEndpoint {
+					listen_addr: endpoint.listen_addr,
+					retry_random_port: endpoint.retry_random_port,
+					rpc_methods: rpc_methods.into(),
+					allowed_methods: (!endpoint.allow.is_empty()).then(|| endpoint.allow.clone()),
+					cors: endpoint.cors.clone().map_or_else(|| cors.clone(), Into::into),
+					max_connections: endpoint.max_connections.unwrap_or(self.rpc_max_connections),
+					max_subs_per_conn: endpoint
+						.max_subscriptions_per_connection
+						.unwrap_or(self.rpc_max_subscriptions_per_connection),
+					max_request_size: endpoint
+						.max_request_size
+						.unwrap_or(self.rpc_max_request_size),
+					max_response_size: endpoint
+						.max_response_size
+						.unwrap_or(self.rpc_max_response_size),
+					max_batch_request_len: endpoint
+						.max_batch_request_len
+						.or(self.rpc_max_batch_request_len),
+					rate_limit: RpcRateLimit {
+						per_connection: endpoint
+							.rate_limit
+							.map(limit)
+							.or(rate_limit.per_connection),
+						per_ip: endpoint.rate_limit_per_ip.map(limit).or(rate_limit.per_ip),
+						trusted_proxies: rate_limit.trusted_proxies.clone(),
+						method_costs: rate_limit.method_costs.clone(),
+					},
+				}
+			})
+			.collect())
+	}
+
 	fn transaction_pool(&self, is_dev: bool) -> Result<TransactionPoolOptions> {
 		Ok(self.pool_config.transaction_pool(is_dev))
 	}
@@ -511,6 +674,120 @@ fn parse_cors(s: &str) -> Result<Cors> {
 	}
 }
 
+/// A JSON-RPC endpoint as given by `--rpc-endpoint`.
+///
+/// Settings that are `None` fall back to the respective `--rpc-*` option.
+#[derive(Debug, Clone)]
+pub struct RpcEndpointArg {
+	/// The address to listen on.
+	pub listen_addr: SocketAddr,
+	/// Listen on a random port if `listen_addr` is not available.
+	pub retry_random_port: bool,
+	/// RPC methods to expose.
+	pub rpc_methods: Option<RpcMethods>,
+	/// Methods or method prefixes followed by `*` to expose exclusively, empty to expose all.
+	pub allow: Vec<String>,
+	/// Allowed origins.
+	pub cors: Option<Cors>,
+	/// Maximum number of connections.
+	pub max_connections: Option<u32>,
+	/// Maximum number of subscriptions per connection.
+	pub max_subscriptions_per_connection: Option<u32>,
+	/// Maximum request payload size in megabytes.
+	pub max_request_size: Option<u32>,
+	/// Maximum response payload size in megabytes.
+	pub max_response_size: Option<u32>,
+	/// Maximum number of calls in a batch request.
+	pub max_batch_request_len: Option<u32>,
+	/// Limit of the call costs per second and connection.
+	pub rate_limit: Option<NonZeroU32>,
+	/// Limit of the call costs per second and client IP address.
+	pub rate_limit_per_ip: Option<NonZeroU32>,
+	/// The call costs a client can spend at once.
+	pub rate_limit_burst: Option<NonZeroU32>,
+}
+
+/// Parse the settings of an RPC endpoint.
+fn parse_rpc_endpoint(s: &str) -> std::result::Result<RpcEndpointArg, String> {
+	fn parse<T: FromStr>(key: &str, value: Option<&str>) -> std::result::Result<T, String>
+	where
+		T::Err: Display,
+	{
+		let value = value.ok_or_else(|| format!("Missing value of `{}`", key))?;
+		value.parse().map_err(|e| format!("Invalid value of `{}`: {}", key, e))
+	}
+
+	let mut listen_addr = None;
+	let mut endpoint = RpcEndpointArg {
+		listen_addr: SocketAddr::from((Ipv4Addr::LOCALHOST, 0)),
+		retry_random_port: false,
+		rpc_methods: None,
+		allow: Vec::new(),
+		cors: None,
+		max_connections: None,
+		max_subscriptions_per_connection: None,
+		max_request_size: None,
+		max_response_size: None,
+		max_batch_request_len: None,
+		rate_limit: None,
+		rate_limit_per_ip: None,
+		rate_limit_burst: None,
+	};
+
+	for setting in s.split(',').map(str::trim).filter(|setting| !setting.is_empty()) {
+		let (key, value) = match setting.split_once('=') {
+			Some((key, value)) => (key.trim(), Some(value.trim())),
+			None => (setting, None),
+		};
+
+		match key {
+			"listen-addr" => listen_addr = Some(parse(key, value)?),
+			"retry-random-port" =>
+				endpoint.retry_random_port = value.map_or(Ok(true), |v| parse(key, Some(v)))?,
+			"rpc-methods" => {
+				let value = value.ok_or_else(|| format!("Missing value of `{}`", key))?;
+				endpoint.rpc_methods = Some(RpcMethods::from_str(value, true)?);
+			},
+			"allow" => endpoint.allow.push(parse(key, value)?),
+			"cors" => match (endpoint.cors.take(), parse::<String>(key, value)?) {
+				(_, origin) if origin == "all" || origin == "*" => endpoint.cors = Some(Cors::All),
+				(Some(Cors::All), _) => endpoint.cors = Some(Cors::All),
+				(Some(Cors::List(mut origins)), origin) => {
+					origins.push(origin);
+					endpoint.cors = Some(Cors::List(origins));
+				},
+				(None, origin) => endpoint.cors = Some(Cors::List(vec![origin])),
+			},
+			"max-connections" => endpoint.max_connections = Some(parse(key, value)?),
+			"max-subscriptions-per-connection" =>
+				endpoint.max_subscriptions_per_connection = Some(parse(key, value)?),
+			"max-request-size" => endpoint.max_request_size = Some(parse(key, value)?),
+			"max-response-size" => endpoint.max_response_size = Some(parse(key, value)?),
+			"max-batch-request-len" => endpoint.max_batch_request_len = Some(parse(key, value)?),
+			"rate-limit" => endpoint.rate_limit = Some(parse(key, value)?),
+			"rate-limit-per-ip" => endpoint.rate_limit_per_ip = Some(parse(key, value)?),
+			"rate-limit-burst" => endpoint.rate_limit_burst = Some(parse(key, value)?),
+			other => return Err(format!("Unknown RPC endpoint setting `{}`", other)),
+		}
+	}
+
+	endpoint.listen_addr =
+		listen_addr.ok_or_else(|| "Missing `listen-addr` of RPC endpoint".to_string())?;
+	Ok(endpoint)
+}
+
+/// Parse the cost of an RPC method.
+fn parse_method_cost(s: &str) -> std::result::Result<(String, NonZeroU32), String> {
+	let (method, cost) = s
+		.split_once('=')
+		.ok_or_else(|| format!("Expected `METHOD=COST`, got `{}`", s))?;
+	let cost = cost
+		.trim()
+		.parse()
+		.map_err(|e| format!("Invalid cost of `{}`: {}", method, e))?;
+	Ok((method.trim().to_owned(), cost))
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -546,4 +823,66 @@ mod tests {
 		assert!(is_node_name_valid("visit.www").is_err());
 		assert!(is_node_name_valid("email@domain").is_err());
 	}
+
+	#[test]
+	fn method_cost_parsing() {
+		assert_eq!(
+			parse_method_cost("state_call=10"),
+			Ok(("state_call".to_owned(), NonZeroU32::new(10).unwrap()))
+		);
+		assert!(parse_method_cost("state_call").is_err());
+		assert!(parse_method_cost("state_call=0").is_err());
+		assert!(parse_method_cost("state_call=ten").is_err());
+	}
+
+	#[test]
+	fn rpc_endpoint_parsing() {
+		let endpoint = parse_rpc_endpoint(
+			"listen-addr=0.0.0.0:9944, rpc-methods=Safe,allow=chainHead_*,allow=transaction_*,\
+			 cors=http://localhost,cors=https://example.com,max-connections=10,retry-random-port",
+		)
+		.unwrap();
+
+		assert_eq!(endpoint.listen_addr, "0.0.0.0:9944".parse().unwrap());
+		assert_eq!(endpoint.rpc_methods, Some(RpcMethods::Safe));
+		assert_eq!(endpoint.allow, vec!["chainHead_*".to_string(), "transaction_*".to_string()]);
+		assert_eq!(
+			Option::<Vec<String>>::from(endpoint.cors.unwrap()),
+			Some(vec!["http://localhost".into(), "https://example.com".into()])
+		);
+		assert_eq!(endpoint.max_connections, Some(10));
+		assert_eq!(endpoint.max_request_size, None);
+		assert!(endpoint.retry_random_port);
+
+		let endpoint = parse_rpc_endpoint("listen-addr=127.0.0.1:9945,cors=all").unwrap();
+		assert!(matches!(endpoint.cors, Some(Cors::All)));
+		assert!(!endpoint.retry_random_port);
+
+		assert!(parse_rpc_endpoint("rpc-methods=safe").is_err());
+		assert!(parse_rpc_endpoint("listen-addr=127.0.0.1:9945,max-connections").is_err());
+		assert!(parse_rpc_endpoint("listen-addr=127.0.0.1:9945,unknown=1").is_err());
+	}
+
+	#[test]
+	fn rpc_endpoints_fall_back_to_global_options() {
+		let cmd = RunCmd::try_parse_from([
+			"",
+			"--rpc-max-connections=5",
+			"--rpc-rate-limit=20",
+			"--rpc-endpoint=listen-addr=0.0.0.0:9944,rpc-methods=safe,allow=chainHead_*",
+			"--rpc-endpoint=listen-addr=127.0.0.1:9945,max-connections=1,rate-limit=2",
+		])
+		.unwrap();
+
+		let endpoints = cmd.rpc_endpoints(false).unwrap();
+		assert_eq!(endpoints.len(), 2);
+
+		assert_eq!(endpoints[0].max_connections, 5);
+		assert_eq!(endpoints[0].allowed_methods, Some(vec!["chainHead_*".to_string()]));
+		assert_eq!(endpoints[0].rate_limit.per_connection.unwrap().per_second.get(), 20);
+
+		assert_eq!(endpoints[1].max_connections, 1);
+		assert_eq!(endpoints[1].allowed_methods, None);
+		assert_eq!(endpoints[1].rate_limit.per_connection.unwrap().burst.get(), 2);
+	}
 }
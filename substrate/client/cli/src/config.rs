@@ -26,9 +26,10 @@ use log::warn;
 use names::{Generator, Name};
 use sc_service::{
 	config::{
-		BasePath, Configuration, DatabaseSource, KeystoreConfig, NetworkConfiguration,
-		NodeKeyConfig, OffchainWorkerConfig, PrometheusConfig, PruningMode, Role, RpcMethods,
-		TelemetryEndpoints, TransactionPoolOptions, WasmExecutionMethod,
+		BasePath, Configuration, DatabaseSource, InformantOutputFormat, KeystoreConfig,
+		NetworkConfiguration, NodeKeyConfig, OffchainWorkerConfig, PrometheusConfig, PruningMode,
+		Role, RpcEndpoint, RpcMethods, RpcRateLimit, TelemetryEndpoints, TelemetryLocalSinkConfig,
+		TransactionPoolOptions, WasmExecutionMethod,
 	},
 	BlocksPruning, ChainSpec, TracingReceiver,
 };
@@ -330,6 +331,27 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 		Ok(RPC_DEFAULT_MAX_SUBS_PER_CONN)
 	}
 
+	/// Get the maximum number of calls in an RPC batch request.
+	///
+	/// By default batch requests are not limited.
+	fn rpc_max_batch_request_len(&self) -> Result<Option<u32>> {
+		Ok(None)
+	}
+
+	/// Get the rate limits of the RPC clients.
+	///
+	/// By default no limits are applied.
+	fn rpc_rate_limit(&self) -> Result<RpcRateLimit> {
+		Ok(Default::default())
+	}
+
+	/// Get the JSON-RPC endpoints.
+	///
+	/// By default a single endpoint is configured by the other `rpc_*` settings.
+	fn rpc_endpoints(&self, _is_dev: bool) -> Result<Vec<RpcEndpoint>> {
+		Ok(Vec::new())
+	}
+
 	/// Get the prometheus configuration (`None` if disabled)
 	///
 	/// By default this is `None`.
@@ -351,6 +373,13 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 		Ok(chain_spec.telemetry_endpoints().clone())
 	}
 
+	/// Get the local file or socket the telemetry messages are written to (if any)
+	///
+	/// By default this is `None`.
+	fn telemetry_local_sink(&self) -> Result<Option<TelemetryLocalSinkConfig>> {
+		Ok(None)
+	}
+
 	/// Get the default value for heap pages
 	///
 	/// By default this is `None`.
@@ -381,6 +410,13 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 		Ok(Default::default())
 	}
 
+	/// Get the output format of the informant
+	///
+	/// By default this is `InformantOutputFormat::default()`.
+	fn informant_output_format(&self) -> Result<InformantOutputFormat> {
+		Ok(Default::default())
+	}
+
 	/// Get the development key seed from the current object
 	///
 	/// By default this is `None`.
@@ -498,12 +534,16 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 			rpc_cors: self.rpc_cors(is_dev)?,
 			rpc_max_request_size: self.rpc_max_request_size()?,
 			rpc_max_response_size: self.rpc_max_response_size()?,
+			rpc_max_batch_request_len: self.rpc_max_batch_request_len()?,
 			rpc_id_provider: None,
 			rpc_max_subs_per_conn: self.rpc_max_subscriptions_per_connection()?,
+			rpc_rate_limit: self.rpc_rate_limit()?,
+			rpc_endpoints: self.rpc_endpoints(is_dev)?,
 			rpc_port: DCV::rpc_listen_port(),
 			prometheus_config: self
 				.prometheus_config(DCV::prometheus_listen_port(), &chain_spec)?,
 			telemetry_endpoints,
+			telemetry_local_sink: self.telemetry_local_sink()?,
 			default_heap_pages: self.default_heap_pages()?,
 			offchain_worker: self.offchain_worker(&role)?,
 			force_authoring: self.force_authoring()?,
@@ -516,7 +556,7 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 			announce_block: self.announce_block()?,
 			role,
 			base_path,
-			informant_output_format: Default::default(),
+			informant_output_format: self.informant_output_format()?,
 			runtime_cache_size,
 		})
 	}
@@ -20,7 +20,8 @@ use crate::{arg_enums::SyncMode, params::node_key_params::NodeKeyParams};
 use clap::Args;
 use sc_network::{
 	config::{
-		NetworkConfiguration, NodeKeyConfig, NonReservedPeerMode, SetConfig, TransportConfig,
+		BandwidthQuota, NetworkConfiguration, NodeKeyConfig, NonReservedPeerMode, SetConfig,
+		TransportConfig,
 	},
 	multiaddr::Protocol,
 };
@@ -145,7 +146,10 @@ pub struct NetworkParams {
 	#[arg(long, default_value = "20")]
 	pub kademlia_replication_factor: NonZeroUsize,
 
-	/// Join the IPFS network and serve transactions over bitswap protocol.
+	/// Join the IPFS network and serve transactions and pinned content over bitswap protocol.
+	///
+	/// Also enables the `ipfs_*` and `offchain_*Content` RPC methods, fetching the content that
+	/// is not stored locally from the connected peers.
 	#[arg(long)]
 	pub ipfs_server: bool,
 
@@ -160,6 +164,23 @@ pub struct NetworkParams {
 	)]
 	pub sync: SyncMode,
 
+	/// Lowest block of the history to download after a warp sync.
+	///
+	/// Once the warp synced node is close to the tip of the chain, the headers and bodies of the
+	/// skipped blocks are downloaded backwards from the warp sync target. By default this goes
+	/// down to genesis.
+	#[arg(long, value_name = "BLOCK")]
+	pub backfill_floor: Option<u64>,
+
+	/// Bandwidth quota of the protocols whose name ends with `PROTOCOL`, e.g.
+	/// `--bandwidth-quota /state/2=up:4096,peer-up:512`.
+	///
+	/// The caps are in KiB per second: `up` and `down` for the traffic with all the peers,
+	/// `peer-up` and `peer-down` for the traffic with a single peer. Can be passed multiple times.
+	/// The protocols without quota, such as GRANDPA, are not throttled.
+	#[arg(long, value_name = "PROTOCOL=CAPS", value_parser = parse_bandwidth_quota)]
+	pub bandwidth_quota: Vec<(String, BandwidthQuota)>,
+
 	/// Maximum number of blocks per request.
 	///
 	/// Try reducing this number from the default value if you have a slow network connection
@@ -261,10 +282,18 @@ impl NetworkParams {
 			yamux_window_size: None,
 			ipfs_server: self.ipfs_server,
 			sync_mode: self.sync.into(),
+			backfill_floor: self.backfill_floor,
+			bandwidth_quotas: self.bandwidth_quota.clone(),
 		}
 	}
 }
 
+fn parse_bandwidth_quota(s: &str) -> Result<(String, BandwidthQuota), String> {
+	let (protocol, quota) =
+		s.split_once('=').ok_or_else(|| "Expected `<PROTOCOL>=<CAPS>`".to_string())?;
+	Ok((protocol.to_string(), quota.parse()?))
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -315,4 +344,36 @@ mod tests {
 
 		assert_eq!(SyncMode::Warp, params.network_params.sync);
 	}
+
+	#[test]
+	fn bandwidth_quotas_are_parsed() {
+		let params = Cli::try_parse_from([
+			"",
+			"--bandwidth-quota",
+			"/state/2=up:4096,peer-up:512",
+			"--bandwidth-quota",
+			"/sync/warp=down:64",
+		])
+		.expect("Parses network params");
+
+		assert_eq!(
+			params.network_params.bandwidth_quota,
+			vec![
+				(
+					"/state/2".to_string(),
+					BandwidthQuota {
+						upload: Some(4096 * 1024),
+						upload_per_peer: Some(512 * 1024),
+						..Default::default()
+					}
+				),
+				(
+					"/sync/warp".to_string(),
+					BandwidthQuota { download: Some(64 * 1024), ..Default::default() }
+				),
+			]
+		);
+
+		assert!(Cli::try_parse_from(["", "--bandwidth-quota", "/state/2"]).is_err());
+	}
 }
@@ -23,11 +23,14 @@
 //! targeted at handling input parameter parsing providing
 //! a reasonable abstraction.
 
+use std::{num::NonZeroUsize, time::Duration};
+
 use clap::{ArgAction, Args};
 use sc_network::config::Role;
+use sc_offchain::SchedulingPolicy;
 use sc_service::config::OffchainWorkerConfig;
 
-use crate::{error, OffchainWorkerEnabled};
+use crate::{error, OffchainWorkerEnabled, OffchainWorkerTrigger};
 
 /// Offchain worker related parameters.
 #[derive(Debug, Clone, Args)]
@@ -47,6 +50,31 @@ pub struct OffchainWorkerParams {
 	/// Allows the runtime to write directly to offchain workers DB during block import.
 	#[arg(long = "enable-offchain-indexing", value_name = "ENABLE_OFFCHAIN_INDEXING", default_value_t = false, action = ArgAction::Set)]
 	pub indexing_enabled: bool,
+
+	/// Blocks the offchain workers are run for.
+	#[arg(
+		long = "offchain-worker-trigger",
+		value_name = "TRIGGER",
+		value_enum,
+		ignore_case = true,
+		default_value_t = OffchainWorkerTrigger::NewBest
+	)]
+	pub trigger: OffchainWorkerTrigger,
+
+	/// Maximum number of offchain workers running at the same time.
+	///
+	/// Defaults to the number of CPUs.
+	#[arg(long = "offchain-worker-max-concurrent", value_name = "COUNT")]
+	pub max_concurrent: Option<NonZeroUsize>,
+
+	/// Skip the offchain workers of a block while the maximum number of workers are already
+	/// running, instead of queuing them.
+	#[arg(long = "offchain-worker-skip-if-busy")]
+	pub skip_if_busy: bool,
+
+	/// Number of seconds after which the host functions of an offchain worker stop being served.
+	#[arg(long = "offchain-worker-timeout", value_name = "SECONDS")]
+	pub timeout: Option<u64>,
 }
 
 impl OffchainWorkerParams {
@@ -60,6 +88,21 @@ impl OffchainWorkerParams {
 		};
 
 		let indexing_enabled = self.indexing_enabled;
-		Ok(OffchainWorkerConfig { enabled, indexing_enabled })
+		Ok(OffchainWorkerConfig {
+			enabled,
+			indexing_enabled,
+			scheduling_policy: self.scheduling_policy(),
+		})
+	}
+
+	/// Returns the policy deciding when and how many offchain workers are run.
+	pub fn scheduling_policy(&self) -> SchedulingPolicy {
+		let default = SchedulingPolicy::default();
+		SchedulingPolicy {
+			trigger: self.trigger.into(),
+			max_concurrent: self.max_concurrent.unwrap_or(default.max_concurrent),
+			skip_if_busy: self.skip_if_busy,
+			timeout: self.timeout.map(Duration::from_secs),
+		}
 	}
 }
@@ -0,0 +1,59 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use clap::Args;
+
+/// Parameters used to create the statement store configuration.
+#[derive(Debug, Clone, Args)]
+pub struct StatementStoreParams {
+	/// Maximum number of statements in the statement store.
+	///
+	/// Once reached, a statement is only accepted in place of lower priority statements of the
+	/// same account. Defaults to 8192.
+	#[arg(long, value_name = "COUNT")]
+	pub statement_store_max_statements: Option<usize>,
+
+	/// Maximum total data size of the statements in the statement store, in megabytes.
+	///
+	/// Defaults to 64.
+	#[arg(long, value_name = "MB")]
+	pub statement_store_max_size: Option<usize>,
+
+	/// Number of seconds for which removed statements can't be added back to the statement store.
+	///
+	/// Defaults to 48 hours.
+	#[arg(long, value_name = "SECONDS")]
+	pub statement_store_purge_after: Option<u64>,
+}
+
+impl StatementStoreParams {
+	/// Returns the statement store options.
+	pub fn options(&self) -> sc_statement_store::Options {
+		let mut options = sc_statement_store::Options::default();
+		if let Some(max_statements) = self.statement_store_max_statements {
+			options.max_total_statements = max_statements;
+		}
+		if let Some(max_size) = self.statement_store_max_size {
+			options.max_total_size = max_size.saturating_mul(1024 * 1024);
+		}
+		if let Some(purge_after) = self.statement_store_purge_after {
+			options.purge_after_sec = purge_after;
+		}
+		options
+	}
+}
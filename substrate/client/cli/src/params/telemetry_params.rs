@@ -17,6 +17,8 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use clap::Args;
+use sc_telemetry::{LocalSinkConfig, LocalSinkOutput};
+use std::path::PathBuf;
 
 /// Parameters used to config telemetry.
 #[derive(Debug, Clone, Args)]
@@ -36,6 +38,55 @@ pub struct TelemetryParams {
 	/// Expected format is 'URL VERBOSITY', e.g. `--telemetry-url 'wss://foo/bar 0'`.
 	#[arg(long = "telemetry-url", value_name = "URL VERBOSITY", value_parser = parse_telemetry_endpoints)]
 	pub telemetry_endpoints: Vec<(String, u8)>,
+
+	/// Write the telemetry messages as newline-delimited JSON to this file.
+	///
+	/// This doesn't require a telemetry server and isn't affected by `--no-telemetry`. The file is
+	/// rotated once it reaches `--telemetry-file-max-size`.
+	#[arg(long, value_name = "PATH")]
+	pub telemetry_file: Option<PathBuf>,
+
+	/// Size in MiB above which the `--telemetry-file` is rotated.
+	#[arg(long, value_name = "MiB", default_value_t = 100)]
+	pub telemetry_file_max_size: u64,
+
+	/// Number of rotated telemetry files kept next to the `--telemetry-file`.
+	#[arg(long, value_name = "COUNT", default_value_t = 5)]
+	pub telemetry_file_max_files: usize,
+
+	/// Stream the telemetry messages as newline-delimited JSON to the Unix socket at this path.
+	///
+	/// Messages are dropped while nothing listens on the socket.
+	#[cfg(unix)]
+	#[arg(long, value_name = "PATH", conflicts_with = "telemetry_file")]
+	pub telemetry_socket: Option<PathBuf>,
+
+	/// Maximum verbosity of the messages written to the `--telemetry-file` or
+	/// `--telemetry-socket`, from 0 to 9.
+	#[arg(long, value_name = "VERBOSITY", default_value_t = 0)]
+	pub telemetry_local_verbosity: u8,
+}
+
+impl TelemetryParams {
+	/// Configuration of the local telemetry sink, `None` if neither a file nor a socket is given.
+	pub fn local_sink(&self) -> Option<LocalSinkConfig> {
+		#[cfg(unix)]
+		if let Some(path) = &self.telemetry_socket {
+			return Some(LocalSinkConfig {
+				output: LocalSinkOutput::UnixSocket(path.clone()),
+				verbosity: self.telemetry_local_verbosity,
+			})
+		}
+
+		self.telemetry_file.as_ref().map(|path| LocalSinkConfig {
+			output: LocalSinkOutput::File {
+				path: path.clone(),
+				max_size: self.telemetry_file_max_size.saturating_mul(1024 * 1024),
+				max_files: self.telemetry_file_max_files,
+			},
+			verbosity: self.telemetry_local_verbosity,
+		})
+	}
 }
 
 #[derive(Debug)]
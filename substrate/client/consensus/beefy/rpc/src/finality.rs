@@ -0,0 +1,81 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! BEEFY finality proofs served over RPC.
+
+use codec::Encode;
+use serde::{Deserialize, Serialize};
+
+use sc_client_api::Backend;
+use sc_consensus_beefy::finality_proof::{
+	FinalityProof, FinalityProofError, FinalityProofProvider,
+};
+use sp_api::ProvideRuntimeApi;
+use sp_consensus_beefy::MmrRootHash;
+use sp_core::Bytes;
+use sp_mmr_primitives::MmrApi;
+use sp_runtime::traits::{Block as BlockT, NumberFor};
+
+/// BEEFY finality proof of a block, bundled with the MMR proof of its leaf.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncodedFinalityProof<BlockHash> {
+	/// Hash of the block the justification is for.
+	pub block_hash: BlockHash,
+	/// SCALE-encoded `sp_consensus_beefy::VersionedFinalityProof`.
+	pub justification: Bytes,
+	/// SCALE-encoded vector of `EncodableOpaqueLeaf`, holding the leaf of the requested block.
+	pub leaves: Bytes,
+	/// SCALE-encoded MMR proof of `leaves` against the root signed in `justification`.
+	pub proof: Bytes,
+}
+
+impl<Block: BlockT> From<FinalityProof<Block>> for EncodedFinalityProof<Block::Hash> {
+	fn from(proof: FinalityProof<Block>) -> Self {
+		EncodedFinalityProof {
+			block_hash: proof.block,
+			justification: proof.justification.encode().into(),
+			leaves: proof.leaves.encode().into(),
+			proof: proof.proof.encode().into(),
+		}
+	}
+}
+
+/// Local trait mainly to allow mocking in tests.
+pub trait RpcFinalityProofProvider<Block: BlockT> {
+	/// Prove BEEFY finality of the given block number.
+	fn rpc_prove_finality(
+		&self,
+		block: NumberFor<Block>,
+	) -> Result<EncodedFinalityProof<Block::Hash>, FinalityProofError>;
+}
+
+impl<Block, BE, R> RpcFinalityProofProvider<Block> for FinalityProofProvider<Block, BE, R>
+where
+	Block: BlockT,
+	BE: Backend<Block>,
+	R: ProvideRuntimeApi<Block>,
+	R::Api: MmrApi<Block, MmrRootHash, NumberFor<Block>>,
+{
+	fn rpc_prove_finality(
+		&self,
+		block: NumberFor<Block>,
+	) -> Result<EncodedFinalityProof<Block::Hash>, FinalityProofError> {
+		self.prove_finality(block).map(Into::into)
+	}
+}
@@ -24,7 +24,7 @@ use parking_lot::RwLock;
 use std::sync::Arc;
 
 use sc_rpc::SubscriptionTaskExecutor;
-use sp_runtime::traits::Block as BlockT;
+use sp_runtime::traits::{Block as BlockT, NumberFor};
 
 use futures::{task::SpawnError, FutureExt, StreamExt};
 use jsonrpsee::{
@@ -35,12 +35,16 @@ use jsonrpsee::{
 };
 use log::warn;
 
-use sc_consensus_beefy::communication::notification::{
-	BeefyBestBlockStream, BeefyVersionedFinalityProofStream,
+use sc_consensus_beefy::{
+	communication::notification::{BeefyBestBlockStream, BeefyVersionedFinalityProofStream},
+	finality_proof::FinalityProofError,
 };
 
+mod finality;
 mod notification;
 
+pub use finality::{EncodedFinalityProof, RpcFinalityProofProvider};
+
 #[derive(Debug, thiserror::Error)]
 /// Top-level error type for the RPC handler
 pub enum Error {
@@ -50,21 +54,30 @@ pub enum Error {
 	/// The BEEFY RPC background task failed to spawn.
 	#[error("BEEFY RPC background task failed to spawn")]
 	RpcTaskFailure(#[from] SpawnError),
+	/// The BEEFY RPC blocking task did not complete.
+	#[error("BEEFY RPC blocking task failed: {0}")]
+	BlockingTaskFailure(#[from] tokio::task::JoinError),
+	/// BEEFY prove finality failed.
+	#[error("BEEFY prove finality rpc failed: {0}")]
+	ProveFinalityFailed(#[from] FinalityProofError),
 }
 
 /// The error codes returned by jsonrpc.
 pub enum ErrorCode {
 	/// Returned when BEEFY RPC endpoint is not ready.
 	NotReady = 1,
-	/// Returned on BEEFY RPC background task failure.
+	/// Returned on BEEFY RPC background or blocking task failure.
 	TaskFailure = 2,
+	/// Failed to prove finality.
+	ProveFinality = 3,
 }
 
 impl From<Error> for ErrorCode {
 	fn from(error: Error) -> Self {
 		match error {
 			Error::EndpointNotReady => ErrorCode::NotReady,
-			Error::RpcTaskFailure(_) => ErrorCode::TaskFailure,
+			Error::RpcTaskFailure(_) | Error::BlockingTaskFailure(_) => ErrorCode::TaskFailure,
+			Error::ProveFinalityFailed(_) => ErrorCode::ProveFinality,
 		}
 	}
 }
@@ -83,7 +96,7 @@ impl From<Error> for JsonRpseeError {
 
 // Provides RPC methods for interacting with BEEFY.
 #[rpc(client, server)]
-pub trait BeefyApi<Notification, Hash> {
+pub trait BeefyApi<Notification, Hash, Number> {
 	/// Returns the block most recently finalized by BEEFY, alongside its justification.
 	#[subscription(
 		name = "beefy_subscribeJustifications" => "beefy_justifications",
@@ -99,16 +112,27 @@ pub trait BeefyApi<Notification, Hash> {
 	/// In such case an error would be returned.
 	#[method(name = "beefy_getFinalizedHead")]
 	async fn latest_finalized(&self) -> RpcResult<Hash>;
+
+	/// Prove BEEFY finality of the given block number.
+	///
+	/// Returns the BEEFY justification of the earliest block at or above the given one for which
+	/// one is available locally, within a bounded number of blocks, bundled with the MMR proof of
+	/// the given block's leaf against the justified MMR root. If no justification is available
+	/// locally, one may be requested from peers and an error is returned; the call can be
+	/// retried later.
+	#[method(name = "beefy_proveFinality")]
+	async fn prove_finality(&self, block: Number) -> RpcResult<EncodedFinalityProof<Hash>>;
 }
 
 /// Implements the BeefyApi RPC trait for interacting with BEEFY.
-pub struct Beefy<Block: BlockT> {
+pub struct Beefy<Block: BlockT, ProofProvider> {
 	finality_proof_stream: BeefyVersionedFinalityProofStream<Block>,
 	beefy_best_block: Arc<RwLock<Option<Block::Hash>>>,
+	finality_proof_provider: Arc<ProofProvider>,
 	executor: SubscriptionTaskExecutor,
 }
 
-impl<Block> Beefy<Block>
+impl<Block, ProofProvider> Beefy<Block, ProofProvider>
 where
 	Block: BlockT,
 {
@@ -116,6 +140,7 @@ where
 	pub fn new(
 		finality_proof_stream: BeefyVersionedFinalityProofStream<Block>,
 		best_block_stream: BeefyBestBlockStream<Block>,
+		finality_proof_provider: Arc<ProofProvider>,
 		executor: SubscriptionTaskExecutor,
 	) -> Result<Self, Error> {
 		let beefy_best_block = Arc::new(RwLock::new(None));
@@ -128,15 +153,17 @@ where
 		});
 
 		executor.spawn("substrate-rpc-subscription", Some("rpc"), future.map(drop).boxed());
-		Ok(Self { finality_proof_stream, beefy_best_block, executor })
+		Ok(Self { finality_proof_stream, beefy_best_block, finality_proof_provider, executor })
 	}
 }
 
 #[async_trait]
-impl<Block> BeefyApiServer<notification::EncodedVersionedFinalityProof, Block::Hash>
-	for Beefy<Block>
+impl<Block, ProofProvider>
+	BeefyApiServer<notification::EncodedVersionedFinalityProof, Block::Hash, NumberFor<Block>>
+	for Beefy<Block, ProofProvider>
 where
 	Block: BlockT,
+	ProofProvider: RpcFinalityProofProvider<Block> + Send + Sync + 'static,
 {
 	fn subscribe_justifications(&self, mut sink: SubscriptionSink) -> SubscriptionResult {
 		let stream = self
@@ -160,6 +187,23 @@ where
 			.ok_or(Error::EndpointNotReady)
 			.map_err(Into::into)
 	}
+
+	async fn prove_finality(
+		&self,
+		block: NumberFor<Block>,
+	) -> RpcResult<EncodedFinalityProof<Block::Hash>> {
+		// Proving finality reads a bounded number of blocks from the backend and calls into the
+		// runtime, don't block the RPC server meanwhile.
+		let provider = self.finality_proof_provider.clone();
+		tokio::task::spawn_blocking(move || provider.rpc_prove_finality(block))
+			.await
+			.map_err(Error::from)?
+			.map_err(|e| {
+				warn!("Error proving finality: {}", e);
+				Error::ProveFinalityFailed(e)
+			})
+			.map_err(Into::into)
+	}
 }
 
 #[cfg(test)]
@@ -173,23 +217,51 @@ mod tests {
 		justification::BeefyVersionedFinalityProof,
 	};
 	use sp_consensus_beefy::{known_payloads, Payload, SignedCommitment};
+	use sp_core::H256;
 	use sp_runtime::traits::{BlakeTwo256, Hash};
 	use substrate_test_runtime_client::runtime::Block;
 
-	fn setup_io_handler() -> (RpcModule<Beefy<Block>>, BeefyVersionedFinalityProofSender<Block>) {
+	type TestRpcModule = RpcModule<Beefy<Block, TestFinalityProofProvider>>;
+
+	struct TestFinalityProofProvider {
+		finality_proof: Option<EncodedFinalityProof<H256>>,
+	}
+
+	impl RpcFinalityProofProvider<Block> for TestFinalityProofProvider {
+		fn rpc_prove_finality(
+			&self,
+			_block: NumberFor<Block>,
+		) -> Result<EncodedFinalityProof<H256>, FinalityProofError> {
+			self.finality_proof.clone().ok_or(FinalityProofError::JustificationRequested)
+		}
+	}
+
+	fn setup_io_handler() -> (TestRpcModule, BeefyVersionedFinalityProofSender<Block>) {
 		let (_, stream) = BeefyBestBlockStream::<Block>::channel();
 		setup_io_handler_with_best_block_stream(stream)
 	}
 
 	fn setup_io_handler_with_best_block_stream(
 		best_block_stream: BeefyBestBlockStream<Block>,
-	) -> (RpcModule<Beefy<Block>>, BeefyVersionedFinalityProofSender<Block>) {
+	) -> (TestRpcModule, BeefyVersionedFinalityProofSender<Block>) {
+		setup_io_handler_with_finality_proof(best_block_stream, None)
+	}
+
+	fn setup_io_handler_with_finality_proof(
+		best_block_stream: BeefyBestBlockStream<Block>,
+		finality_proof: Option<EncodedFinalityProof<H256>>,
+	) -> (TestRpcModule, BeefyVersionedFinalityProofSender<Block>) {
 		let (finality_proof_sender, finality_proof_stream) =
 			BeefyVersionedFinalityProofStream::<Block>::channel();
+		let finality_proof_provider = Arc::new(TestFinalityProofProvider { finality_proof });
 
-		let handler =
-			Beefy::new(finality_proof_stream, best_block_stream, sc_rpc::testing::test_executor())
-				.expect("Setting up the BEEFY RPC handler works");
+		let handler = Beefy::new(
+			finality_proof_stream,
+			best_block_stream,
+			finality_proof_provider,
+			sc_rpc::testing::test_executor(),
+		)
+		.expect("Setting up the BEEFY RPC handler works");
 
 		(handler.into_rpc(), finality_proof_sender)
 	}
@@ -300,4 +372,38 @@ mod tests {
 		assert_eq!(&recv_sub_id, sub.subscription_id());
 		assert_eq!(recv_finality_proof, finality_proof);
 	}
+
+	#[tokio::test]
+	async fn prove_finality_with_test_finality_proof_provider() {
+		let finality_proof = EncodedFinalityProof {
+			block_hash: BlakeTwo256::hash(b"42"),
+			justification: create_finality_proof().encode().into(),
+			leaves: vec![1u8, 2, 3].into(),
+			proof: vec![4u8, 5, 6].into(),
+		};
+		let (_, stream) = BeefyBestBlockStream::<Block>::channel();
+		let (rpc, _) = setup_io_handler_with_finality_proof(stream, Some(finality_proof.clone()));
+
+		let proof: EncodedFinalityProof<H256> =
+			rpc.call("beefy_proveFinality", [42]).await.unwrap();
+		assert_eq!(proof, finality_proof);
+	}
+
+	#[tokio::test]
+	async fn prove_finality_reports_provider_errors() {
+		let (rpc, _) = setup_io_handler();
+		let request = r#"{"jsonrpc":"2.0","method":"beefy_proveFinality","params":[42],"id":1}"#;
+		let expected = "{\
+			\"jsonrpc\":\"2.0\",\
+			\"error\":{\
+				\"code\":3,\
+				\"message\":\"BEEFY prove finality rpc failed: \
+					BEEFY justification not available locally, requested from peers\"\
+			},\
+			\"id\":1\
+		}";
+		let (response, _) = rpc.raw_json_request(request).await.unwrap();
+
+		assert_eq!(response.result, expected);
+	}
 }
@@ -17,7 +17,7 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use sc_utils::notification::{NotificationSender, NotificationStream, TracingKeyStr};
-use sp_runtime::traits::Block as BlockT;
+use sp_runtime::traits::{Block as BlockT, NumberFor};
 
 use crate::justification::BeefyVersionedFinalityProof;
 
@@ -40,6 +40,15 @@ pub type BeefyVersionedFinalityProofSender<Block> =
 pub type BeefyVersionedFinalityProofStream<Block> =
 	NotificationStream<BeefyVersionedFinalityProof<Block>, BeefyVersionedFinalityProofTracingKey>;
 
+/// The sending half of the notifications channel(s) used to ask the gadget
+/// to request the justification of a past block from peers.
+pub type BeefyJustifRequestSender<Block> = NotificationSender<NumberFor<Block>>;
+
+/// The receiving half of a notifications channel used to receive requests
+/// for justifications of past blocks on the gadget side.
+pub type BeefyJustifRequestStream<Block> =
+	NotificationStream<NumberFor<Block>, BeefyJustifRequestTracingKey>;
+
 /// Provides tracing key for BEEFY best block stream.
 #[derive(Clone)]
 pub struct BeefyBestBlockTracingKey;
@@ -53,3 +62,10 @@ pub struct BeefyVersionedFinalityProofTracingKey;
 impl TracingKeyStr for BeefyVersionedFinalityProofTracingKey {
 	const TRACING_KEY: &'static str = "mpsc_beefy_versioned_finality_proof_notification_stream";
 }
+
+/// Provides tracing key for BEEFY justification request stream.
+#[derive(Clone)]
+pub struct BeefyJustifRequestTracingKey;
+impl TracingKeyStr for BeefyJustifRequestTracingKey {
+	const TRACING_KEY: &'static str = "mpsc_beefy_justif_request_notification_stream";
+}
@@ -51,6 +51,8 @@ type ResponseReceiver = oneshot::Receiver<Response>;
 struct RequestInfo<B: Block> {
 	block: NumberFor<B>,
 	active_set: ValidatorSet<AuthorityId>,
+	/// Request is for an already BEEFY finalized block.
+	historical: bool,
 }
 
 enum State<B: Block> {
@@ -136,29 +138,48 @@ impl<B: Block> OnDemandJustificationsEngine<B> {
 	///
 	/// `active_set` will be used to verify validity of potential responses.
 	pub fn request(&mut self, block: NumberFor<B>, active_set: ValidatorSet<AuthorityId>) {
+		self.start_request(RequestInfo { block, active_set, historical: false })
+	}
+
+	/// Start new justification request for already BEEFY finalized `block`, if no other
+	/// request is in progress.
+	///
+	/// Unlike requests started through [`Self::request`], this one is not cancelled when
+	/// newer blocks get finalized.
+	pub fn request_historical(
+		&mut self,
+		block: NumberFor<B>,
+		active_set: ValidatorSet<AuthorityId>,
+	) {
+		self.start_request(RequestInfo { block, active_set, historical: true })
+	}
+
+	fn start_request(&mut self, req_info: RequestInfo<B>) {
 		// ignore new requests while there's already one pending
 		if matches!(self.state, State::AwaitingResponse(_, _, _)) {
 			return
 		}
-		self.reset_peers_cache_for_block(block);
+		self.reset_peers_cache_for_block(req_info.block);
 
 		// Start the requests engine - each unsuccessful received response will automatically
 		// trigger a new request to the next peer in the `peers_cache` until there are none left.
 		if let Some(peer) = self.try_next_peer() {
-			self.request_from_peer(peer, RequestInfo { block, active_set });
+			self.request_from_peer(peer, req_info);
 		} else {
 			metric_inc!(self, beefy_on_demand_justification_no_peer_to_request_from);
 			debug!(
 				target: BEEFY_SYNC_LOG_TARGET,
-				"🥩 no good peers to request justif #{:?} from", block
+				"🥩 no good peers to request justif #{:?} from", req_info.block
 			);
 		}
 	}
 
-	/// Cancel any pending request for block numbers smaller or equal to `block`.
+	/// Cancel any pending non-historical request for block numbers smaller or equal to `block`.
 	pub fn cancel_requests_older_than(&mut self, block: NumberFor<B>) {
 		match &self.state {
-			State::AwaitingResponse(_, req_info, _) if req_info.block <= block => {
+			State::AwaitingResponse(_, req_info, _)
+				if !req_info.historical && req_info.block <= block =>
+			{
 				debug!(
 					target: BEEFY_SYNC_LOG_TARGET,
 					"🥩 cancel pending request for justification #{:?}", req_info.block
@@ -0,0 +1,217 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! BEEFY block finality proof generation.
+//!
+//! Finality of block B is proved by providing:
+//! 1) the BEEFY justification for block F, where F is B or its earliest descendant with a
+//!    justification stored in the backend;
+//! 2) the MMR leaf of block B and its proof against the MMR state at block F, whose root is part of
+//!    the payload signed in the justification.
+//!
+//! Only the first [`MAX_SEARCHED_BLOCKS`] blocks starting with B are searched. If no
+//! justification is stored in the backend for any of them, the voter is asked to request the
+//! justification of the first mandatory block following B from its peers using the on-demand
+//! justifications protocol. The justification of a given block is requested at most once every
+//! [`JUSTIF_REQUEST_INTERVAL`], whatever the number of RPC calls, and at most
+//! [`MAX_JUSTIF_REQUESTS`] justifications are requested within that interval. Callers should
+//! retry later.
+
+use codec::{Decode, Encode};
+use log::{debug, trace};
+use parking_lot::Mutex;
+use sc_client_api::Backend;
+use sp_api::{ApiExt, ProvideRuntimeApi};
+use sp_blockchain::{Backend as BlockchainBackend, HeaderBackend};
+use sp_consensus_beefy::{MmrRootHash, BEEFY_ENGINE_ID};
+use sp_core::offchain::{storage::OffchainDb, OffchainDbExt};
+use sp_mmr_primitives::{EncodableOpaqueLeaf, MmrApi, Proof};
+use sp_runtime::{
+	generic::BlockId,
+	traits::{Block, NumberFor, One, Saturating},
+};
+use std::{
+	collections::HashMap,
+	sync::Arc,
+	time::{Duration, Instant},
+};
+
+use crate::{
+	communication::notification::BeefyJustifRequestSender,
+	justification::BeefyVersionedFinalityProof, worker::find_authorities_change, LOG_TARGET,
+};
+
+/// Maximum number of blocks, starting with the requested one, searched for a justification.
+pub const MAX_SEARCHED_BLOCKS: u32 = 64;
+
+/// Minimum interval between two requests of the same justification issued on behalf of the RPC.
+pub const JUSTIF_REQUEST_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Maximum number of justifications requested on behalf of the RPC within
+/// [`JUSTIF_REQUEST_INTERVAL`].
+pub const MAX_JUSTIF_REQUESTS: usize = 8;
+
+/// BEEFY finality proof of block B, bundled with the MMR proof of its leaf.
+#[derive(Debug, PartialEq, Encode, Decode)]
+pub struct FinalityProof<B: Block> {
+	/// The hash of block F for which justification is provided.
+	pub block: B::Hash,
+	/// BEEFY justification of block F.
+	pub justification: BeefyVersionedFinalityProof<B>,
+	/// The MMR leaf of block B.
+	pub leaves: Vec<EncodableOpaqueLeaf>,
+	/// Proof of `leaves` against the MMR root signed in `justification`.
+	pub proof: Proof<MmrRootHash>,
+}
+
+/// Errors occurring when trying to prove BEEFY finality.
+#[derive(Debug, thiserror::Error)]
+pub enum FinalityProofError {
+	/// The requested block has not yet been finalized.
+	#[error("Block not yet finalized")]
+	BlockNotYetFinalized,
+	/// No justification covering the requested block is stored locally. One has been requested
+	/// from peers, the proof can be retried later.
+	#[error("BEEFY justification not available locally, requested from peers")]
+	JustificationRequested,
+	/// No justification covering the requested block is stored locally and none could be
+	/// requested, either because there is no mandatory block to request one for or because
+	/// too many others were requested recently.
+	#[error("BEEFY justification not available")]
+	JustificationUnavailable,
+	/// The backend does not provide offchain storage, required to generate MMR proofs.
+	#[error("Offchain storage not available")]
+	OffchainStorageUnavailable,
+	/// Errors originating from the MMR runtime API.
+	#[error(transparent)]
+	Mmr(#[from] sp_mmr_primitives::Error),
+	/// Errors calling into the runtime.
+	#[error(transparent)]
+	RuntimeApi(#[from] sp_api::ApiError),
+	/// Errors originating from the client.
+	#[error(transparent)]
+	Client(#[from] sp_blockchain::Error),
+}
+
+/// BEEFY finality proof provider for serving RPC requests.
+pub struct FinalityProofProvider<B: Block, BE, R> {
+	backend: Arc<BE>,
+	runtime: Arc<R>,
+	justif_request_sender: BeefyJustifRequestSender<B>,
+	/// Blocks of the recent justification requests and when they were issued.
+	requests: Mutex<HashMap<NumberFor<B>, Instant>>,
+}
+
+impl<B, BE, R> FinalityProofProvider<B, BE, R>
+where
+	B: Block,
+	BE: Backend<B>,
+	R: ProvideRuntimeApi<B>,
+	R::Api: MmrApi<B, MmrRootHash, NumberFor<B>>,
+{
+	/// Create new finality proof provider using:
+	///
+	/// - backend for accessing blockchain data and offchain storage;
+	/// - runtime for generating MMR proofs;
+	/// - justif_request_sender for asking the voter to request missing justifications.
+	pub fn new(
+		backend: Arc<BE>,
+		runtime: Arc<R>,
+		justif_request_sender: BeefyJustifRequestSender<B>,
+	) -> Self {
+		FinalityProofProvider {
+			backend,
+			runtime,
+			justif_request_sender,
+			requests: Mutex::new(HashMap::new()),
+		}
+	}
+
+	/// Prove BEEFY finality of the given block number.
+	pub fn prove_finality(
+		&self,
+		block: NumberFor<B>,
+	) -> Result<FinalityProof<B>, FinalityProofError> {
+		let blockchain = self.backend.blockchain();
+		let info = blockchain.info();
+		if info.finalized_number < block {
+			trace!(
+				target: LOG_TARGET,
+				"🥩 Requested finality proof for #{:?} while we only have finalized #{:?}.",
+				block,
+				info.finalized_number,
+			);
+			return Err(FinalityProofError::BlockNotYetFinalized)
+		}
+
+		let last = info.finalized_number.min(block.saturating_add(MAX_SEARCHED_BLOCKS.into()));
+		let mut first_mandatory = None;
+		let mut current = block;
+		let (hash, justification) = loop {
+			let hash = blockchain.expect_block_hash_from_id(&BlockId::Number(current))?;
+			if let Some(encoded) = blockchain
+				.justifications(hash)?
+				.and_then(|j| j.into_justification(BEEFY_ENGINE_ID))
+			{
+				let justification = BeefyVersionedFinalityProof::<B>::decode(&mut &*encoded)
+					.map_err(|_| sp_blockchain::Error::JustificationDecode)?;
+				break (hash, justification)
+			}
+			if first_mandatory.is_none() &&
+				find_authorities_change::<B>(&blockchain.expect_header(hash)?).is_some()
+			{
+				first_mandatory = Some(current);
+			}
+			if current >= last {
+				return Err(self.request_justification(first_mandatory))
+			}
+			current += One::one();
+		};
+
+		let mut api = self.runtime.runtime_api();
+		let offchain_storage = self
+			.backend
+			.offchain_storage()
+			.ok_or(FinalityProofError::OffchainStorageUnavailable)?;
+		api.register_extension(OffchainDbExt::new(OffchainDb::new(offchain_storage)));
+		let (leaves, proof) =
+			api.generate_proof(info.finalized_hash, vec![block], Some(current))??;
+
+		Ok(FinalityProof { block: hash, justification, leaves, proof })
+	}
+
+	fn request_justification(&self, mandatory: Option<NumberFor<B>>) -> FinalityProofError {
+		let Some(block) = mandatory else { return FinalityProofError::JustificationUnavailable };
+
+		let mut requests = self.requests.lock();
+		requests.retain(|_, at| at.elapsed() < JUSTIF_REQUEST_INTERVAL);
+		if requests.contains_key(&block) {
+			return FinalityProofError::JustificationRequested
+		}
+		if requests.len() >= MAX_JUSTIF_REQUESTS {
+			return FinalityProofError::JustificationUnavailable
+		}
+		requests.insert(block, Instant::now());
+
+		debug!(target: LOG_TARGET, "🥩 Asking voter for justification of #{:?}.", block);
+		self.justif_request_sender
+			.notify(|| Ok::<_, ()>(block))
+			.expect("forwards closure result; the closure always returns Ok; qed.");
+		FinalityProofError::JustificationRequested
+	}
+}
@@ -0,0 +1,225 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! BEEFY fisherman.
+//!
+//! Checks incoming votes and finality proofs against the local finalized chain and reports
+//! authorities that voted on blocks which are not part of it (fork voting) to the runtime.
+
+use crate::{
+	error::Error,
+	justification::BeefyVersionedFinalityProof,
+	keystore::{BeefyKeystore, BeefySignatureHasher},
+	LOG_TARGET,
+};
+use log::debug;
+use parking_lot::Mutex;
+use sc_client_api::{Backend, HeaderBackend};
+use sp_api::{ApiExt, ProvideRuntimeApi};
+use sp_consensus_beefy::{
+	check_fork_voting_proof,
+	ecdsa_crypto::{AuthorityId, Signature},
+	known_payloads,
+	mmr::find_mmr_root_digest,
+	BeefyApi, ForkVotingProof, MmrRootHash, ValidatorSetId, VersionedFinalityProof, VoteMessage,
+};
+use sp_runtime::traits::{Block, Header, NumberFor};
+use std::{collections::BTreeSet, marker::PhantomData, sync::Arc};
+
+/// Version of the BEEFY runtime API that introduced reporting fork votes.
+const FORK_VOTING_REPORT_API_VERSION: u32 = 4;
+
+/// Maximum number of reported fork votes remembered to not report them again. The fork votes of
+/// the oldest rounds are forgotten first.
+const MAX_REPORTED_FORK_VOTES: usize = 4 * 1024;
+
+/// Helper wrapping the resources needed to detect and report fork votes.
+pub(crate) struct Fisherman<B: Block, BE, RuntimeApi> {
+	pub backend: Arc<BE>,
+	pub runtime: Arc<RuntimeApi>,
+	pub key_store: BeefyKeystore,
+	/// The validator set id, round and offender of the fork votes already reported.
+	reported: Mutex<BTreeSet<(ValidatorSetId, NumberFor<B>, AuthorityId)>>,
+	pub _phantom: PhantomData<B>,
+}
+
+impl<B, BE, R> Fisherman<B, BE, R>
+where
+	B: Block,
+	BE: Backend<B>,
+	R: ProvideRuntimeApi<B>,
+	R::Api: BeefyApi<B, AuthorityId>,
+{
+	pub fn new(backend: Arc<BE>, runtime: Arc<R>, key_store: BeefyKeystore) -> Self {
+		Fisherman {
+			backend,
+			runtime,
+			key_store,
+			reported: Default::default(),
+			_phantom: PhantomData,
+		}
+	}
+
+	/// Return the canonical header at `number`, or `None` if `number` is not yet finalized.
+	fn canonical_header(&self, number: NumberFor<B>) -> Result<Option<B::Header>, Error> {
+		let blockchain = self.backend.blockchain();
+		if number > blockchain.info().finalized_number {
+			return Ok(None)
+		}
+		let Some(hash) = blockchain.hash(number).map_err(|e| Error::Backend(e.to_string()))? else {
+			return Ok(None)
+		};
+		blockchain.header(hash).map_err(|e| Error::Backend(e.to_string()))
+	}
+
+	/// Check `vote` against the local finalized chain and report it if it was cast
+	/// on a block which is not part of it.
+	pub fn check_vote(
+		&self,
+		vote: VoteMessage<NumberFor<B>, AuthorityId, Signature>,
+	) -> Result<(), Error> {
+		let Some(canonical_header) = self.canonical_header(vote.commitment.block_number)? else {
+			return Ok(())
+		};
+		let proof = ForkVotingProof { vote, canonical_header };
+		if check_fork_voting_proof::<_, _, BeefySignatureHasher>(
+			&proof,
+			&proof.canonical_header.hash(),
+		) {
+			self.report_fork_voting(proof)?;
+		}
+		Ok(())
+	}
+
+	/// Check the commitment of `proof` against the local finalized chain and report all
+	/// signatories if it was for a block which is not part of it.
+	pub fn check_proof(&self, proof: BeefyVersionedFinalityProof<B>) -> Result<(), Error> {
+		let VersionedFinalityProof::V1(signed_commitment) = proof;
+		let commitment = signed_commitment.commitment;
+		let Some(canonical_header) = self.canonical_header(commitment.block_number)? else {
+			return Ok(())
+		};
+
+		// Only go through the individual signatures if the commitment conflicts
+		// with the canonical chain.
+		let canonical_root = find_mmr_root_digest::<B>(&canonical_header);
+		let voted_root =
+			commitment.payload.get_decoded::<MmrRootHash>(&known_payloads::MMR_ROOT_ID);
+		if canonical_root.is_none() || voted_root.is_none() || canonical_root == voted_root {
+			return Ok(())
+		}
+
+		let validator_set = self
+			.runtime
+			.runtime_api()
+			.validator_set(canonical_header.hash())
+			.map_err(Error::RuntimeApi)?
+			.filter(|set| set.id() == commitment.validator_set_id);
+		let Some(validator_set) = validator_set else {
+			debug!(
+				target: LOG_TARGET,
+				"🥩 Unknown validator set for fork voting proof at #{:?}", commitment.block_number
+			);
+			return Ok(())
+		};
+
+		let votes = validator_set
+			.validators()
+			.iter()
+			.zip(signed_commitment.signatures.into_iter())
+			.filter_map(|(id, signature)| {
+				signature.map(|signature| VoteMessage {
+					commitment: commitment.clone(),
+					id: id.clone(),
+					signature,
+				})
+			});
+		for vote in votes {
+			let proof = ForkVotingProof { vote, canonical_header: canonical_header.clone() };
+			if check_fork_voting_proof::<_, _, BeefySignatureHasher>(
+				&proof,
+				&proof.canonical_header.hash(),
+			) {
+				self.report_fork_voting(proof)?;
+			}
+		}
+		Ok(())
+	}
+
+	/// Report the given fork voting proof to the BEEFY runtime module, unless the fork vote
+	/// was already reported. The session membership proof of the offender is generated at the
+	/// canonical block the vote conflicts with, while the report itself is submitted at the
+	/// best block.
+	fn report_fork_voting(
+		&self,
+		proof: ForkVotingProof<B::Header, AuthorityId>,
+	) -> Result<(), Error> {
+		let offender_id = proof.offender_id().clone();
+		if self.key_store.authority_id(&[offender_id.clone()]).is_some() {
+			debug!(target: LOG_TARGET, "🥩 Skip fork voting report for own vote");
+			return Ok(())
+		}
+
+		let key = (proof.set_id(), proof.vote.commitment.block_number, offender_id.clone());
+		if self.reported.lock().contains(&key) {
+			debug!(target: LOG_TARGET, "🥩 Skip fork voting report for already reported vote");
+			return Ok(())
+		}
+
+		let canonical_hash = proof.canonical_header.hash();
+		let best_block_hash = self.backend.blockchain().info().best_hash;
+		let runtime_api = self.runtime.runtime_api();
+		let api_version = runtime_api
+			.api_version::<dyn BeefyApi<B, AuthorityId>>(best_block_hash)
+			.map_err(Error::RuntimeApi)?;
+		if api_version.map_or(true, |version| version < FORK_VOTING_REPORT_API_VERSION) {
+			debug!(
+				target: LOG_TARGET,
+				"🥩 Skip fork voting report, unsupported by the runtime API version {:?}", api_version
+			);
+			return Ok(())
+		}
+
+		// generate key ownership proof at the canonical block
+		let key_owner_proof = match runtime_api
+			.generate_key_ownership_proof(canonical_hash, proof.set_id(), offender_id)
+			.map_err(Error::RuntimeApi)?
+		{
+			Some(proof) => proof,
+			None => {
+				debug!(
+					target: LOG_TARGET,
+					"🥩 Fork voting offender not part of the authority set."
+				);
+				return Ok(())
+			},
+		};
+
+		// submit fork voting report at **best** block
+		runtime_api
+			.submit_report_fork_voting_unsigned_extrinsic(best_block_hash, proof, key_owner_proof)
+			.map_err(Error::RuntimeApi)?;
+
+		let mut reported = self.reported.lock();
+		reported.insert(key);
+		if reported.len() > MAX_REPORTED_FORK_VOTES {
+			reported.pop_first();
+		}
+		Ok(())
+	}
+}
@@ -19,7 +19,8 @@
 use crate::{
 	communication::{
 		notification::{
-			BeefyBestBlockSender, BeefyBestBlockStream, BeefyVersionedFinalityProofSender,
+			BeefyBestBlockSender, BeefyBestBlockStream, BeefyJustifRequestSender,
+			BeefyJustifRequestStream, BeefyVersionedFinalityProofSender,
 			BeefyVersionedFinalityProofStream,
 		},
 		peers::KnownPeers,
@@ -27,6 +28,7 @@ use crate::{
 			outgoing_requests_engine::OnDemandJustificationsEngine, BeefyJustifsRequestHandler,
 		},
 	},
+	fisherman::Fisherman,
 	import::BeefyBlockImport,
 	metrics::register_metrics,
 	round::Rounds,
@@ -60,12 +62,14 @@ use std::{
 
 mod aux_schema;
 mod error;
+mod fisherman;
 mod keystore;
 mod metrics;
 mod round;
 mod worker;
 
 pub mod communication;
+pub mod finality_proof;
 pub mod import;
 pub mod justification;
 
@@ -118,6 +122,10 @@ pub struct BeefyVoterLinks<B: Block> {
 	pub to_rpc_justif_sender: BeefyVersionedFinalityProofSender<B>,
 	/// Sends BEEFY best block hashes from voter to RPC.
 	pub to_rpc_best_block_sender: BeefyBestBlockSender<B>,
+
+	// RPC -> Voter links
+	/// Stream of past block numbers for which RPC asks the voter to request justifications.
+	pub from_rpc_justif_request_stream: BeefyJustifRequestStream<B>,
 }
 
 /// Links used by the BEEFY RPC layer, from the BEEFY background voter.
@@ -127,6 +135,8 @@ pub struct BeefyRPCLinks<B: Block> {
 	pub from_voter_justif_stream: BeefyVersionedFinalityProofStream<B>,
 	/// Stream of BEEFY best block hashes coming from the voter.
 	pub from_voter_best_beefy_stream: BeefyBestBlockStream<B>,
+	/// Asks the voter to request justifications of past blocks from peers.
+	pub to_voter_justif_request_sender: BeefyJustifRequestSender<B>,
 }
 
 /// Make block importer and link half necessary to tie the background voter to it.
@@ -149,6 +159,10 @@ where
 	let (to_rpc_best_block_sender, from_voter_best_beefy_stream) =
 		BeefyBestBlockStream::<B>::channel();
 
+	// RPC -> Voter links
+	let (to_voter_justif_request_sender, from_rpc_justif_request_stream) =
+		BeefyJustifRequestStream::<B>::channel();
+
 	// BlockImport -> Voter links
 	let (to_voter_justif_sender, from_block_import_justif_stream) =
 		BeefyVersionedFinalityProofStream::<B>::channel();
@@ -166,8 +180,13 @@ where
 		from_block_import_justif_stream,
 		to_rpc_justif_sender,
 		to_rpc_best_block_sender,
+		from_rpc_justif_request_stream,
+	};
+	let rpc_links = BeefyRPCLinks {
+		from_voter_best_beefy_stream,
+		from_voter_justif_stream,
+		to_voter_justif_request_sender,
 	};
-	let rpc_links = BeefyRPCLinks { from_voter_best_beefy_stream, from_voter_justif_stream };
 
 	(import, voter_links, rpc_links)
 }
@@ -324,6 +343,7 @@ pub async fn start_beefy_gadget<B, BE, C, N, P, R, S>(
 			runtime: runtime.clone(),
 			sync: sync.clone(),
 			key_store: key_store.clone().into(),
+			fisherman: Fisherman::new(backend.clone(), runtime.clone(), key_store.clone().into()),
 			comms: beefy_comms,
 			links: links.clone(),
 			metrics: metrics.clone(),
@@ -26,8 +26,10 @@ use crate::{
 			proofs_topic, tests::sign_commitment, votes_topic, GossipFilterCfg, GossipMessage,
 			GossipValidator,
 		},
+		notification::BeefyJustifRequestStream,
 		request_response::{on_demand_justifications_protocol_config, BeefyJustifsRequestHandler},
 	},
+	finality_proof::{FinalityProofError, FinalityProofProvider},
 	gossip_protocol_name,
 	justification::*,
 	load_or_init_voter_state, wait_for_runtime_pallet, BeefyRPCLinks, BeefyVoterLinks, KnownPeers,
@@ -55,16 +57,16 @@ use sp_consensus_beefy::{
 	ecdsa_crypto::{AuthorityId, Signature},
 	known_payloads,
 	mmr::{find_mmr_root_digest, MmrRootProvider},
-	BeefyApi, Commitment, ConsensusLog, EquivocationProof, Keyring as BeefyKeyring, MmrRootHash,
-	OpaqueKeyOwnershipProof, Payload, SignedCommitment, ValidatorSet, ValidatorSetId,
-	VersionedFinalityProof, VoteMessage, BEEFY_ENGINE_ID,
+	BeefyApi, Commitment, ConsensusLog, EquivocationProof, ForkVotingProof,
+	Keyring as BeefyKeyring, MmrRootHash, OpaqueKeyOwnershipProof, Payload, SignedCommitment,
+	ValidatorSet, ValidatorSetId, VersionedFinalityProof, VoteMessage, BEEFY_ENGINE_ID,
 };
 use sp_core::H256;
 use sp_keystore::{testing::MemoryKeystore, Keystore, KeystorePtr};
-use sp_mmr_primitives::{Error as MmrError, MmrApi};
+use sp_mmr_primitives::{EncodableOpaqueLeaf, Error as MmrError, MmrApi, Proof};
 use sp_runtime::{
 	codec::{Decode, Encode},
-	traits::{Header as HeaderT, NumberFor},
+	traits::{Block as BlockT, Header as HeaderT, NumberFor},
 	BuildStorage, DigestItem, EncodedJustification, Justifications, Storage,
 };
 use std::{marker::PhantomData, sync::Arc, task::Poll};
@@ -252,6 +254,8 @@ pub(crate) struct TestApi {
 	pub mmr_root_hash: MmrRootHash,
 	pub reported_equivocations:
 		Option<Arc<Mutex<Vec<EquivocationProof<NumberFor<Block>, AuthorityId, Signature>>>>>,
+	pub reported_fork_votes:
+		Option<Arc<Mutex<Vec<ForkVotingProof<<Block as BlockT>::Header, AuthorityId>>>>>,
 }
 
 impl TestApi {
@@ -265,6 +269,7 @@ impl TestApi {
 			validator_set: Some(validator_set.clone()),
 			mmr_root_hash,
 			reported_equivocations: None,
+			reported_fork_votes: None,
 		}
 	}
 
@@ -274,12 +279,17 @@ impl TestApi {
 			validator_set: Some(validator_set.clone()),
 			mmr_root_hash: GOOD_MMR_ROOT,
 			reported_equivocations: None,
+			reported_fork_votes: None,
 		}
 	}
 
 	pub fn allow_equivocations(&mut self) {
 		self.reported_equivocations = Some(Arc::new(Mutex::new(vec![])));
 	}
+
+	pub fn allow_fork_votes(&mut self) {
+		self.reported_fork_votes = Some(Arc::new(Mutex::new(vec![])));
+	}
 }
 
 // compiler gets confused and warns us about unused inner
@@ -295,6 +305,7 @@ impl ProvideRuntimeApi<Block> for TestApi {
 	}
 }
 sp_api::mock_impl_runtime_apis! {
+	#[api_version(4)]
 	impl BeefyApi<Block, AuthorityId> for RuntimeApi {
 		fn beefy_genesis() -> Option<NumberFor<Block>> {
 			Some(self.inner.beefy_genesis)
@@ -316,6 +327,18 @@ sp_api::mock_impl_runtime_apis! {
 			}
 		}
 
+		fn submit_report_fork_voting_unsigned_extrinsic(
+			proof: ForkVotingProof<<Block as BlockT>::Header, AuthorityId>,
+			_dummy: OpaqueKeyOwnershipProof,
+		) -> Option<()> {
+			if let Some(fork_votes_buf) = self.inner.reported_fork_votes.as_ref() {
+				fork_votes_buf.lock().push(proof);
+				None
+			} else {
+				panic!("Fork votes not expected, but following proof was reported: {:?}", proof);
+			}
+		}
+
 		fn generate_key_ownership_proof(
 			_dummy1: ValidatorSetId,
 			_dummy2: AuthorityId,
@@ -326,10 +349,19 @@ sp_api::mock_impl_runtime_apis! {
 		fn mmr_root() -> Result<MmrRootHash, MmrError> {
 			Ok(self.inner.mmr_root_hash)
 		}
+
+		fn generate_proof(
+			block_numbers: Vec<NumberFor<Block>>,
+			best_known_block_number: Option<NumberFor<Block>>,
+		) -> Result<(Vec<EncodableOpaqueLeaf>, Proof<MmrRootHash>), MmrError> {
+			let leaves = block_numbers.iter().map(EncodableOpaqueLeaf::from_leaf).collect();
+			let leaf_count = best_known_block_number.unwrap_or_default();
+			Ok((leaves, Proof { leaf_indices: block_numbers, leaf_count, items: vec![] }))
+		}
 	}
 }
 
-fn add_mmr_digest(builder: &mut impl BlockBuilderExt, mmr_hash: MmrRootHash) {
+pub(crate) fn add_mmr_digest(builder: &mut impl BlockBuilderExt, mmr_hash: MmrRootHash) {
 	builder
 		.push_deposit_log_digest_item(DigestItem::Consensus(
 			BEEFY_ENGINE_ID,
@@ -459,7 +491,7 @@ pub(crate) fn get_beefy_streams(
 	let mut versioned_finality_proof_streams = Vec::new();
 	peers.for_each(|(index, _)| {
 		let beefy_rpc_links = net.peer(index).data.beefy_rpc_links.lock().clone().unwrap();
-		let BeefyRPCLinks { from_voter_justif_stream, from_voter_best_beefy_stream } =
+		let BeefyRPCLinks { from_voter_justif_stream, from_voter_best_beefy_stream, .. } =
 			beefy_rpc_links;
 		best_block_streams.push(from_voter_best_beefy_stream.subscribe(100_000));
 		versioned_finality_proof_streams.push(from_voter_justif_stream.subscribe(100_000));
@@ -1489,3 +1521,63 @@ async fn gossipped_finality_proofs() {
 		}
 	}
 }
+
+#[tokio::test]
+async fn finality_proof_provider_serves_stored_or_requests_missing_justifications() {
+	let keys = &[BeefyKeyring::Alice];
+	let validator_set = ValidatorSet::new(make_beefy_ids(keys), 0).unwrap();
+	let mut net = BeefyTestNet::new(1);
+	let backend = net.peer(0).client().as_backend();
+
+	// push 25 blocks with `AuthorityChange` digests every 10 blocks
+	let hashes = net.generate_blocks_and_sync(25, 10, &validator_set, false).await;
+	// finalize 23 without justifications
+	net.peer(0).client().as_client().finalize_block(hashes[23], None).unwrap();
+
+	let (justif_request_sender, justif_request_stream) =
+		BeefyJustifRequestStream::<Block>::channel();
+	let mut justif_requests = justif_request_stream.subscribe(10);
+	let api = Arc::new(TestApi::with_validator_set(&validator_set));
+	let provider = FinalityProofProvider::new(backend.clone(), api, justif_request_sender);
+
+	// can't prove finality of non-finalized blocks
+	assert!(matches!(provider.prove_finality(24), Err(FinalityProofError::BlockNotYetFinalized)));
+
+	// no justifications stored, justification for next mandatory block is requested
+	assert!(matches!(provider.prove_finality(5), Err(FinalityProofError::JustificationRequested)));
+	assert_eq!(justif_requests.next().await, Some(10));
+	// the same justification is not requested again for every call
+	assert!(matches!(provider.prove_finality(7), Err(FinalityProofError::JustificationRequested)));
+	assert!(justif_requests.next().now_or_never().is_none());
+	// while the justification of another mandatory block is requested right away
+	assert!(matches!(provider.prove_finality(13), Err(FinalityProofError::JustificationRequested)));
+	assert_eq!(justif_requests.next().await, Some(20));
+	// no mandatory block after 21, nothing to request
+	assert!(matches!(
+		provider.prove_finality(21),
+		Err(FinalityProofError::JustificationUnavailable)
+	));
+
+	// append BEEFY justification for block 12
+	let commitment = Commitment {
+		payload: Payload::from_single_entry(known_payloads::MMR_ROOT_ID, vec![]),
+		block_number: 12,
+		validator_set_id: validator_set.id(),
+	};
+	let justif = VersionedFinalityProof::<_, Signature>::V1(SignedCommitment {
+		commitment,
+		signatures: vec![None],
+	});
+	backend
+		.append_justification(hashes[12], (BEEFY_ENGINE_ID, justif.encode()))
+		.unwrap();
+
+	// finality of blocks up to 12 is proved using the stored justification
+	let proof = provider.prove_finality(5).unwrap();
+	assert_eq!(proof.block, hashes[12]);
+	assert_eq!(proof.justification, justif);
+	assert_eq!(proof.leaves, vec![EncodableOpaqueLeaf::from_leaf(&5u64)]);
+	assert_eq!(proof.proof.leaf_indices, vec![5]);
+	assert_eq!(proof.proof.leaf_count, 12);
+	assert_eq!(provider.prove_finality(12).unwrap().block, hashes[12]);
+}
@@ -23,6 +23,7 @@ use crate::{
 		request_response::outgoing_requests_engine::{OnDemandJustificationsEngine, ResponseInfo},
 	},
 	error::Error,
+	fisherman::Fisherman,
 	justification::BeefyVersionedFinalityProof,
 	keystore::{BeefyKeystore, BeefySignatureHasher},
 	metric_inc, metric_set,
@@ -38,6 +39,7 @@ use sc_network_gossip::GossipEngine;
 use sc_utils::{mpsc::TracingUnboundedReceiver, notification::NotificationReceiver};
 use sp_api::ProvideRuntimeApi;
 use sp_arithmetic::traits::{AtLeast32Bit, Saturating};
+use sp_blockchain::Backend as BlockchainBackend;
 use sp_consensus::SyncOracle;
 use sp_consensus_beefy::{
 	check_equivocation_proof,
@@ -331,6 +333,8 @@ pub(crate) struct BeefyWorker<B: Block, BE, P, RuntimeApi, S> {
 	pub runtime: Arc<RuntimeApi>,
 	pub sync: Arc<S>,
 	pub key_store: BeefyKeystore,
+	/// Detects and reports votes on blocks which are not part of the finalized chain.
+	pub fisherman: Fisherman<B, BE, RuntimeApi>,
 
 	// communication (created once, but returned and reused if worker is restarted/reinitialized)
 	pub comms: BeefyComms<B>,
@@ -504,6 +508,9 @@ where
 		vote: VoteMessage<NumberFor<B>, AuthorityId, Signature>,
 	) -> Result<(), Error> {
 		let block_num = vote.commitment.block_number;
+		if let Err(e) = self.fisherman.check_vote(vote.clone()) {
+			debug!(target: LOG_TARGET, "🥩 Error {:?} on checking vote for fork voting.", e);
+		}
 		match self.voting_oracle().triage_round(block_num)? {
 			RoundAction::Process =>
 				if let Some(finality_proof) = self.handle_vote(vote)? {
@@ -532,6 +539,9 @@ where
 			VersionedFinalityProof::V1(ref sc) => sc,
 		};
 		let block_num = signed_commitment.commitment.block_number;
+		if let Err(e) = self.fisherman.check_proof(justification.clone()) {
+			debug!(target: LOG_TARGET, "🥩 Error {:?} on checking proof for fork voting.", e);
+		}
 		match self.voting_oracle().triage_round(block_num)? {
 			RoundAction::Process => {
 				debug!(target: LOG_TARGET, "🥩 Process justification for round: {:?}.", block_num);
@@ -556,6 +566,57 @@ where
 		Ok(())
 	}
 
+	/// Request the justification of already BEEFY finalized `block` from peers, on behalf of
+	/// the RPC layer.
+	fn request_historical_justification(&mut self, block: NumberFor<B>) -> Result<(), Error> {
+		if block > self.voting_oracle().best_beefy_block {
+			// Not finalized yet, the justification will be obtained through regular voting.
+			return Ok(())
+		}
+		let blockchain = self.backend.blockchain();
+		let header = blockchain
+			.expect_block_hash_from_id(&BlockId::Number(block))
+			.and_then(|hash| blockchain.expect_header(hash))
+			.map_err(|e| Error::Backend(e.to_string()))?;
+		// Mandatory blocks announce their validator set, fall back to runtime state otherwise.
+		let active_set = find_authorities_change::<B>(&header)
+			.or_else(|| self.runtime.runtime_api().validator_set(header.hash()).ok().flatten())
+			.ok_or_else(|| {
+				Error::Backend(format!("Could not find validator set active at #{:?}", block))
+			})?;
+		debug!(target: LOG_TARGET, "🥩 Requesting historical justification for #{:?}", block);
+		self.comms.on_demand_justifications.request_historical(block, active_set);
+		Ok(())
+	}
+
+	/// Store `justification` of an already BEEFY finalized block, if the backend doesn't
+	/// already have one for it.
+	///
+	/// Expects `justification` to be valid.
+	fn store_historical_justification(
+		&self,
+		justification: BeefyVersionedFinalityProof<B>,
+	) -> Result<(), Error> {
+		let block_num = match justification {
+			VersionedFinalityProof::V1(ref sc) => sc.commitment.block_number,
+		};
+		let blockchain = self.backend.blockchain();
+		let hash = blockchain
+			.expect_block_hash_from_id(&BlockId::Number(block_num))
+			.map_err(|e| Error::Backend(e.to_string()))?;
+		let known = blockchain
+			.justifications(hash)
+			.map_err(|e| Error::Backend(e.to_string()))?
+			.map_or(false, |justifs| justifs.get(BEEFY_ENGINE_ID).is_some());
+		if !known {
+			debug!(target: LOG_TARGET, "🥩 Store historical justification for #{:?}", block_num);
+			self.backend
+				.append_justification(hash, (BEEFY_ENGINE_ID, justification.encode()))
+				.map_err(|e| Error::Backend(e.to_string()))?;
+		}
+		Ok(())
+	}
+
 	fn handle_vote(
 		&mut self,
 		vote: VoteMessage<NumberFor<B>, AuthorityId, Signature>,
@@ -855,6 +916,9 @@ where
 				.fuse(),
 		);
 
+		let mut rpc_justif_requests =
+			self.links.from_rpc_justif_request_stream.subscribe(100_000).fuse();
+
 		self.process_new_state();
 		let error = loop {
 			// Mutable reference used to drive the gossip engine.
@@ -883,7 +947,16 @@ where
 				response_info = self.comms.on_demand_justifications.next().fuse() => {
 					match response_info {
 						ResponseInfo::ValidProof(justif, peer_report) => {
-							if let Err(err) = self.triage_incoming_justif(justif) {
+							let block_num = match justif {
+								VersionedFinalityProof::V1(ref sc) => sc.commitment.block_number,
+							};
+							let res = if block_num <= self.voting_oracle().best_beefy_block {
+								// Historical justification requested by RPC.
+								self.store_historical_justification(justif)
+							} else {
+								self.triage_incoming_justif(justif)
+							};
+							if let Err(err) = res {
 								debug!(target: LOG_TARGET, "🥩 {}", err);
 							}
 							self.comms.gossip_engine.report(peer_report.who, peer_report.cost_benefit);
@@ -929,6 +1002,15 @@ where
 						break Error::VotesGossipStreamTerminated;
 					}
 				},
+				// Process justification requests coming from RPC.
+				request = rpc_justif_requests.next() => {
+					if let Some(block) = request {
+						if let Err(err) = self.request_historical_justification(block) {
+							debug!(target: LOG_TARGET, "🥩 {}", err);
+						}
+					}
+					continue;
+				},
 				// Process peer reports.
 				report = self.comms.gossip_report_stream.next() => {
 					if let Some(PeerReport { who, cost_benefit }) = report {
@@ -1062,10 +1144,12 @@ where
 pub(crate) mod tests {
 	use super::*;
 	use crate::{
-		communication::notification::{BeefyBestBlockStream, BeefyVersionedFinalityProofStream},
+		communication::notification::{
+			BeefyBestBlockStream, BeefyJustifRequestStream, BeefyVersionedFinalityProofStream,
+		},
 		tests::{
-			create_beefy_keystore, get_beefy_streams, make_beefy_ids, BeefyPeer, BeefyTestNet,
-			TestApi,
+			add_mmr_digest, create_beefy_keystore, get_beefy_streams, make_beefy_ids, BeefyPeer,
+			BeefyTestNet, TestApi,
 		},
 		BeefyRPCLinks, KnownPeers,
 	};
@@ -1075,9 +1159,10 @@ pub(crate) mod tests {
 	use sc_network_sync::SyncingService;
 	use sc_network_test::TestNetFactory;
 	use sp_blockchain::Backend as BlockchainBackendT;
+	use sp_consensus::BlockOrigin;
 	use sp_consensus_beefy::{
 		generate_equivocation_proof, known_payloads, known_payloads::MMR_ROOT_ID,
-		mmr::MmrRootProvider, Keyring, Payload, SignedCommitment,
+		mmr::MmrRootProvider, Keyring, MmrRootHash, Payload, SignedCommitment,
 	};
 	use sp_runtime::traits::{Header as HeaderT, One};
 	use substrate_test_runtime_client::{
@@ -1129,15 +1214,21 @@ pub(crate) mod tests {
 			BeefyBestBlockStream::<Block>::channel();
 		let (_, from_block_import_justif_stream) =
 			BeefyVersionedFinalityProofStream::<Block>::channel();
+		let (to_voter_justif_request_sender, from_rpc_justif_request_stream) =
+			BeefyJustifRequestStream::<Block>::channel();
 
-		let beefy_rpc_links =
-			BeefyRPCLinks { from_voter_justif_stream, from_voter_best_beefy_stream };
+		let beefy_rpc_links = BeefyRPCLinks {
+			from_voter_justif_stream,
+			from_voter_best_beefy_stream,
+			to_voter_justif_request_sender,
+		};
 		*peer.data.beefy_rpc_links.lock() = Some(beefy_rpc_links);
 
 		let links = BeefyVoterLinks {
 			from_block_import_justif_stream,
 			to_rpc_justif_sender,
 			to_rpc_best_block_sender,
+			from_rpc_justif_request_stream,
 		};
 
 		let backend = peer.client().as_backend();
@@ -1184,11 +1275,13 @@ pub(crate) mod tests {
 			gossip_report_stream,
 			on_demand_justifications,
 		};
+		let fisherman = Fisherman::new(backend.clone(), api.clone(), Some(keystore.clone()).into());
 		BeefyWorker {
 			backend,
 			payload_provider,
 			runtime: api,
 			key_store: Some(keystore).into(),
+			fisherman,
 			links,
 			comms,
 			metrics,
@@ -1680,4 +1773,59 @@ pub(crate) mod tests {
 		// verify nothing reported to runtime
 		assert!(api_alice.reported_equivocations.as_ref().unwrap().lock().is_empty());
 	}
+
+	#[tokio::test]
+	async fn should_report_fork_votes_but_not_canonical_or_self_votes() {
+		let set_id = 0;
+		let keys = [Keyring::Alice];
+		let validator_set = ValidatorSet::new(make_beefy_ids(&keys), set_id).unwrap();
+		// Alice expects fork votes to be reported
+		let mut api_alice = TestApi::with_validator_set(&validator_set);
+		api_alice.allow_fork_votes();
+		let api_alice = Arc::new(api_alice);
+
+		let mut net = BeefyTestNet::new(1);
+		let mut worker = create_beefy_worker(net.peer(0), &keys[0], 1, validator_set.clone());
+		worker.fisherman.runtime = api_alice.clone();
+
+		// let there be a finalized block with num = 2 carrying the canonical MMR root:
+		let canonical_root = MmrRootHash::repeat_byte(0xbf);
+		let hashes = net.peer(0).generate_blocks(1, BlockOrigin::File, |mut builder| {
+			add_mmr_digest(&mut builder, canonical_root);
+			builder.build().unwrap().block
+		});
+		let backend = net.peer(0).client().as_backend();
+		backend.finalize_block(hashes[0], None).unwrap();
+
+		let vote = |root: MmrRootHash, keyring: Keyring| {
+			let payload = Payload::from_single_entry(MMR_ROOT_ID, root.encode());
+			let commitment = Commitment { payload, block_number: 2, validator_set_id: set_id };
+			let signature = keyring.sign(&commitment.encode());
+			VoteMessage { commitment, id: keyring.public(), signature }
+		};
+
+		// Bob voting on a fork is reported
+		let fork_vote = vote(MmrRootHash::repeat_byte(0x42), Keyring::Bob);
+		assert_eq!(worker.fisherman.check_vote(fork_vote.clone()), Ok(()));
+		{
+			let reported = api_alice.reported_fork_votes.as_ref().unwrap().lock();
+			assert_eq!(reported.len(), 1);
+			assert_eq!(reported[0].vote, fork_vote);
+			assert_eq!(reported[0].canonical_header.hash(), hashes[0]);
+		}
+		api_alice.reported_fork_votes.as_ref().unwrap().lock().clear();
+
+		// fork votes already reported are not reported again
+		assert_eq!(worker.fisherman.check_vote(fork_vote), Ok(()));
+		assert!(api_alice.reported_fork_votes.as_ref().unwrap().lock().is_empty());
+
+		// votes on the canonical block are not reported
+		assert_eq!(worker.fisherman.check_vote(vote(canonical_root, Keyring::Bob)), Ok(()));
+		assert!(api_alice.reported_fork_votes.as_ref().unwrap().lock().is_empty());
+
+		// fork votes done by 'self' are not reported
+		let self_vote = vote(MmrRootHash::repeat_byte(0x42), Keyring::Alice);
+		assert_eq!(worker.fisherman.check_vote(self_vote), Ok(()));
+		assert!(api_alice.reported_fork_votes.as_ref().unwrap().lock().is_empty());
+	}
 }
@@ -36,6 +36,9 @@ pub enum Error {
 	/// GRANDPA prove finality failed.
 	#[error("GRANDPA prove finality rpc failed: {0}")]
 	ProveFinalityFailed(#[from] sc_consensus_grandpa::FinalityProofError),
+	/// The GRANDPA RPC blocking task did not complete.
+	#[error("GRANDPA RPC blocking task failed: {0}")]
+	BlockingTaskFailure(#[from] tokio::task::JoinError),
 }
 
 /// The error codes returned by jsonrpc.
@@ -48,6 +51,8 @@ pub enum ErrorCode {
 	VoterStateTooLarge,
 	/// Failed to prove finality.
 	ProveFinality,
+	/// The blocking task failed.
+	TaskFailure,
 }
 
 impl From<Error> for ErrorCode {
@@ -57,6 +62,7 @@ impl From<Error> for ErrorCode {
 			Error::AuthoritySetIdReportedAsUnreasonablyLarge => ErrorCode::AuthoritySetTooLarge,
 			Error::VoterStateReportsUnreasonablyLargeNumbers => ErrorCode::VoterStateTooLarge,
 			Error::ProveFinalityFailed(_) => ErrorCode::ProveFinality,
+			Error::BlockingTaskFailure(_) => ErrorCode::TaskFailure,
 		}
 	}
 }
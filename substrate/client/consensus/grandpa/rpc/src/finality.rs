@@ -21,10 +21,17 @@ use serde::{Deserialize, Serialize};
 use sc_consensus_grandpa::FinalityProofProvider;
 use sp_runtime::traits::{Block as BlockT, NumberFor};
 
+/// Encoded [`sc_consensus_grandpa::FinalityProof`].
 #[derive(Serialize, Deserialize)]
 pub struct EncodedFinalityProof(pub sp_core::Bytes);
 
-/// Local trait mainly to allow mocking in tests.
+/// Proof of an authority set change, encoded as the `(header, justification)` arguments of
+/// `pallet-bridge-grandpa::submit_finality_proof`.
+#[derive(Serialize, Deserialize)]
+pub struct EncodedAuthoritySetChangeProof(pub sp_core::Bytes);
+
+/// Provider of the finality proofs served by the RPC, implemented by [`FinalityProofProvider`].
+/// Allows mocking in tests, or serving proofs for a specific use, e.g. bridges.
 pub trait RpcFinalityProofProvider<Block: BlockT> {
 	/// Prove finality for the given block number by returning a Justification for the last block of
 	/// the authority set.
@@ -32,6 +39,13 @@ pub trait RpcFinalityProofProvider<Block: BlockT> {
 		&self,
 		block: NumberFor<Block>,
 	) -> Result<Option<EncodedFinalityProof>, sc_consensus_grandpa::FinalityProofError>;
+
+	/// Prove all authority set changes enacted in the `(from; to]` range of blocks.
+	fn rpc_prove_authority_set_changes(
+		&self,
+		from: NumberFor<Block>,
+		to: NumberFor<Block>,
+	) -> Result<Vec<EncodedAuthoritySetChangeProof>, sc_consensus_grandpa::FinalityProofError>;
 }
 
 impl<B, Block> RpcFinalityProofProvider<Block> for FinalityProofProvider<B, Block>
@@ -46,4 +60,17 @@ where
 	) -> Result<Option<EncodedFinalityProof>, sc_consensus_grandpa::FinalityProofError> {
 		self.prove_finality(block).map(|x| x.map(|y| EncodedFinalityProof(y.into())))
 	}
+
+	fn rpc_prove_authority_set_changes(
+		&self,
+		from: NumberFor<Block>,
+		to: NumberFor<Block>,
+	) -> Result<Vec<EncodedAuthoritySetChangeProof>, sc_consensus_grandpa::FinalityProofError> {
+		self.prove_authority_set_changes(from, to).map(|proofs| {
+			proofs
+				.iter()
+				.map(|proof| EncodedAuthoritySetChangeProof(proof.encode_call_args().into()))
+				.collect()
+		})
+	}
 }
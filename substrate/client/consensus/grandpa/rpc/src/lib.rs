@@ -39,7 +39,9 @@ use sc_consensus_grandpa::GrandpaJustificationStream;
 use sc_rpc::SubscriptionTaskExecutor;
 use sp_runtime::traits::{Block as BlockT, NumberFor};
 
-use finality::{EncodedFinalityProof, RpcFinalityProofProvider};
+pub use finality::{
+	EncodedAuthoritySetChangeProof, EncodedFinalityProof, RpcFinalityProofProvider,
+};
 use notification::JustificationNotification;
 use report::{ReportAuthoritySet, ReportVoterState, ReportedRoundStates};
 
@@ -64,6 +66,20 @@ pub trait GrandpaApi<Notification, Hash, Number> {
 	/// in the set and all the intermediary headers to link them together.
 	#[method(name = "grandpa_proveFinality")]
 	async fn prove_finality(&self, block: Number) -> RpcResult<Option<EncodedFinalityProof>>;
+
+	/// Prove all authority set changes enacted after block `from` and up to block `to`, by
+	/// returning the header and justification of the last block of each set, in ascending order.
+	///
+	/// Each proof is encoded as the arguments of `pallet-bridge-grandpa::submit_finality_proof`.
+	/// Proofs stop at the first set without a known justification. Whether the justifications are
+	/// stripped of the votes and headers rejected by the pallet depends on the proof provider of
+	/// the node. At most 32 proofs are returned, the call can be repeated from the last one.
+	#[method(name = "grandpa_proveAuthoritySetChanges")]
+	async fn prove_authority_set_changes(
+		&self,
+		from: Number,
+		to: Number,
+	) -> RpcResult<Vec<EncodedAuthoritySetChangeProof>>;
 }
 
 /// Provides RPC methods for interacting with GRANDPA.
@@ -130,6 +146,24 @@ where
 			})
 			.map_err(Into::into)
 	}
+
+	async fn prove_authority_set_changes(
+		&self,
+		from: NumberFor<Block>,
+		to: NumberFor<Block>,
+	) -> RpcResult<Vec<EncodedAuthoritySetChangeProof>> {
+		// Proving the changes reads the justifications of many blocks, and may verify them,
+		// don't block the RPC server meanwhile.
+		let provider = self.finality_proof_provider.clone();
+		tokio::task::spawn_blocking(move || provider.rpc_prove_authority_set_changes(from, to))
+			.await
+			.map_err(error::Error::from)?
+			.map_err(|e| {
+				warn!("Error proving authority set changes: {}", e);
+				error::Error::ProveFinalityFailed(e)
+			})
+			.map_err(Into::into)
+	}
 }
 
 #[cfg(test)]
@@ -144,7 +178,8 @@ mod tests {
 	use parity_scale_codec::{Decode, Encode};
 	use sc_block_builder::BlockBuilderBuilder;
 	use sc_consensus_grandpa::{
-		report, AuthorityId, FinalityProof, GrandpaJustification, GrandpaJustificationSender,
+		report, AuthorityId, AuthoritySetChangeProof, FinalityProof, GrandpaJustification,
+		GrandpaJustificationSender,
 	};
 	use sp_blockchain::HeaderBackend;
 	use sp_core::{crypto::ByteArray, testing::TaskExecutor};
@@ -161,6 +196,7 @@ mod tests {
 
 	struct TestFinalityProofProvider {
 		finality_proof: Option<FinalityProof<Header>>,
+		authority_set_change_proofs: Vec<AuthoritySetChangeProof<Header>>,
 	}
 
 	fn voters() -> HashSet<AuthorityId> {
@@ -209,6 +245,19 @@ mod tests {
 					.into(),
 			)))
 		}
+
+		fn rpc_prove_authority_set_changes(
+			&self,
+			_from: NumberFor<Block>,
+			_to: NumberFor<Block>,
+		) -> Result<Vec<EncodedAuthoritySetChangeProof>, sc_consensus_grandpa::FinalityProofError>
+		{
+			Ok(self
+				.authority_set_change_proofs
+				.iter()
+				.map(|proof| EncodedAuthoritySetChangeProof(proof.encode_call_args().into()))
+				.collect())
+		}
 	}
 
 	impl ReportVoterState for TestVoterState {
@@ -259,11 +308,26 @@ mod tests {
 		RpcModule<Grandpa<TestAuthoritySet, VoterState, Block, TestFinalityProofProvider>>,
 		GrandpaJustificationSender<Block>,
 	)
+	where
+		VoterState: ReportVoterState + Send + Sync + 'static,
+	{
+		let finality_proof_provider =
+			TestFinalityProofProvider { finality_proof, authority_set_change_proofs: vec![] };
+		setup_io_handler_with_finality_proof_provider(voter_state, finality_proof_provider)
+	}
+
+	fn setup_io_handler_with_finality_proof_provider<VoterState>(
+		voter_state: VoterState,
+		finality_proof_provider: TestFinalityProofProvider,
+	) -> (
+		RpcModule<Grandpa<TestAuthoritySet, VoterState, Block, TestFinalityProofProvider>>,
+		GrandpaJustificationSender<Block>,
+	)
 	where
 		VoterState: ReportVoterState + Send + Sync + 'static,
 	{
 		let (justification_sender, justification_stream) = GrandpaJustificationStream::channel();
-		let finality_proof_provider = Arc::new(TestFinalityProofProvider { finality_proof });
+		let finality_proof_provider = Arc::new(finality_proof_provider);
 		let executor = Arc::new(TaskExecutor::default());
 
 		let rpc = Grandpa::new(
@@ -417,4 +481,40 @@ mod tests {
 		let finality_proof_rpc: FinalityProof<Header> = Decode::decode(&mut &bytes[..]).unwrap();
 		assert_eq!(finality_proof_rpc, finality_proof);
 	}
+
+	#[tokio::test]
+	async fn prove_authority_set_changes_with_test_finality_proof_provider() {
+		let justification = create_justification();
+		let authority_set_change_proofs = vec![
+			AuthoritySetChangeProof {
+				header: header(5),
+				justification: justification.encode(),
+				set_id: 0,
+				authorities: None,
+			},
+			AuthoritySetChangeProof {
+				header: header(8),
+				justification: justification.encode(),
+				set_id: 1,
+				authorities: None,
+			},
+		];
+		let finality_proof_provider = TestFinalityProofProvider {
+			finality_proof: None,
+			authority_set_change_proofs: authority_set_change_proofs.clone(),
+		};
+		let (rpc, _) =
+			setup_io_handler_with_finality_proof_provider(TestVoterState, finality_proof_provider);
+
+		let proofs: Vec<sp_core::Bytes> =
+			rpc.call("grandpa_proveAuthoritySetChanges", [0, 42]).await.unwrap();
+		assert_eq!(proofs.len(), 2);
+		for (bytes, proof) in proofs.iter().zip(authority_set_change_proofs) {
+			// Encoded as the arguments of the bridge pallet `submit_finality_proof` call.
+			let (header, justification): (Header, GrandpaJustification<Block>) =
+				Decode::decode(&mut &bytes[..]).unwrap();
+			assert_eq!(header, proof.header);
+			assert_eq!(justification.encode(), proof.justification);
+		}
+	}
 }
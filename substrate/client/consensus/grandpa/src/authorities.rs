@@ -47,6 +47,8 @@ pub enum Error<N, E> {
 		after the pending standard change at #{0}"
 	)]
 	ForcedAuthoritySetChangeDependencyUnsatisfied(N),
+	#[error("Authority set change at #{0} is inconsistent with the known changes.")]
+	InconsistentAuthoritySetChange(N),
 	#[error("Invalid operation in the pending changes tree: {0}")]
 	ForkTree(fork_tree::Error<E>),
 }
@@ -758,6 +760,41 @@ impl<N: Ord + Clone> AuthoritySetChanges<N> {
 		self.0.insert(idx, (set_id, block_number));
 	}
 
+	/// Returns the last block of the given set, if known.
+	pub(crate) fn last_block_of(&self, set_id: u64) -> Option<&N> {
+		let idx = self.0.binary_search_by_key(&set_id, |(set_id, _)| *set_id).ok()?;
+		Some(&self.0[idx].1)
+	}
+
+	/// Returns the most recent change, i.e. the id of the previous set and its last block.
+	pub(crate) fn last(&self) -> Option<&(u64, N)> {
+		self.0.last()
+	}
+
+	/// Insert a change found while importing the block history backwards, from the most recent
+	/// block to the oldest. The set id is derived from the change that follows, or from the
+	/// `current_set_id` if this is the most recent change.
+	///
+	/// Returns an error if the derived set id doesn't fit between the known changes.
+	pub(crate) fn insert_descending<E>(
+		&mut self,
+		block_number: N,
+		current_set_id: u64,
+	) -> Result<(), Error<N, E>> {
+		let idx = match self.0.binary_search_by_key(&block_number, |(_, n)| n.clone()) {
+			Ok(_) => return Ok(()),
+			Err(idx) => idx,
+		};
+
+		let next_set_id = self.0.get(idx).map_or(current_set_id, |(set_id, _)| *set_id);
+		let set_id = next_set_id
+			.checked_sub(1)
+			.filter(|set_id| idx == 0 || self.0[idx - 1].0 < *set_id)
+			.ok_or(Error::InconsistentAuthoritySetChange(block_number.clone()))?;
+		self.0.insert(idx, (set_id, block_number));
+		Ok(())
+	}
+
 	/// Returns an iterator over all historical authority set changes starting at the given block
 	/// number (excluded). The iterator yields a tuple representing the set id and the block number
 	/// of the last block in that set.
@@ -1691,6 +1728,45 @@ mod tests {
 		assert_eq!(authority_set_changes.get_set_id(101), AuthoritySetChangeId::Set(2, 101));
 	}
 
+	#[test]
+	fn authority_set_changes_insert_descending() {
+		// A change finalized after a warp sync to set 4.
+		let mut authority_set_changes = AuthoritySetChanges::empty();
+		authority_set_changes.append(4, 161);
+
+		authority_set_changes.insert_descending::<()>(121, 5).unwrap();
+		authority_set_changes.insert_descending::<()>(81, 5).unwrap();
+		assert_eq!(authority_set_changes.get_set_id(100), AuthoritySetChangeId::Set(3, 121));
+		assert_eq!(authority_set_changes.get_set_id(81), AuthoritySetChangeId::Unknown);
+
+		authority_set_changes.insert_descending::<()>(41, 5).unwrap();
+		authority_set_changes.insert_descending::<()>(1, 5).unwrap();
+		assert_eq!(authority_set_changes.get_set_id(1), AuthoritySetChangeId::Set(0, 1));
+		assert_eq!(authority_set_changes.get_set_id(60), AuthoritySetChangeId::Set(2, 81));
+
+		// Re-importing a change is a no-op.
+		authority_set_changes.insert_descending::<()>(41, 5).unwrap();
+		assert_eq!(authority_set_changes.get_set_id(41), AuthoritySetChangeId::Set(1, 41));
+
+		// Without any later change, the set id is derived from the current one.
+		let mut authority_set_changes = AuthoritySetChanges::empty();
+		authority_set_changes.insert_descending::<()>(121, 3).unwrap();
+		assert_eq!(authority_set_changes.0, vec![(2, 121)]);
+
+		// Changes can't share a set id, and no set precedes the first one.
+		authority_set_changes.insert_descending::<()>(81, 3).unwrap();
+		authority_set_changes.insert_descending::<()>(41, 3).unwrap();
+		assert!(matches!(
+			authority_set_changes.insert_descending::<()>(61, 3),
+			Err(Error::InconsistentAuthoritySetChange(61)),
+		));
+		assert!(matches!(
+			authority_set_changes.insert_descending::<()>(21, 3),
+			Err(Error::InconsistentAuthoritySetChange(21)),
+		));
+		assert_eq!(authority_set_changes.0, vec![(0, 41), (1, 81), (2, 121)]);
+	}
+
 	#[test]
 	fn authority_set_changes_for_complete_data() {
 		let mut authority_set_changes = AuthoritySetChanges::empty();
@@ -37,31 +37,45 @@
 //! of the U) could be returned.
 
 use log::{trace, warn};
+use parking_lot::Mutex;
+use schnellru::{ByLength, LruMap};
 use std::sync::Arc;
 
 use parity_scale_codec::{Decode, Encode};
 use sc_client_api::backend::Backend;
 use sp_blockchain::{Backend as BlockchainBackend, HeaderBackend};
-use sp_consensus_grandpa::GRANDPA_ENGINE_ID;
+use sp_consensus_grandpa::{AuthorityList, GRANDPA_ENGINE_ID};
 use sp_runtime::{
 	generic::BlockId,
-	traits::{Block as BlockT, Header as HeaderT, NumberFor, One},
+	traits::{Block as BlockT, Header as HeaderT, NumberFor, One, Zero},
 };
 
 use crate::{
 	authorities::{AuthoritySetChangeId, AuthoritySetChanges},
-	best_justification,
+	best_justification, find_forced_change, find_scheduled_change,
 	justification::GrandpaJustification,
-	SharedAuthoritySet, LOG_TARGET,
+	SetId, SharedAuthoritySet, LOG_TARGET,
 };
 
 const MAX_UNKNOWN_HEADERS: usize = 100_000;
 
+/// Maximum number of authority set change proofs returned for a single range.
+const MAX_AUTHORITY_SET_CHANGE_PROOFS: usize = 32;
+
+/// Number of authority set change proofs kept in the cache.
+const AUTHORITY_SET_CHANGE_PROOFS_CACHE_SIZE: u32 = 1024;
+
+type AuthoritySetChangeProofsCache<Block> =
+	LruMap<NumberFor<Block>, AuthoritySetChangeProof<<Block as BlockT>::Header>, ByLength>;
+
 /// Finality proof provider for serving network requests.
 #[derive(Clone)]
 pub struct FinalityProofProvider<BE, Block: BlockT> {
 	backend: Arc<BE>,
 	shared_authority_set: Option<SharedAuthoritySet<Block::Hash, NumberFor<Block>>>,
+	/// Proofs of past authority set changes, keyed by the last block of the set. These never
+	/// change once the block is finalized.
+	authority_set_change_proofs: Arc<Mutex<AuthoritySetChangeProofsCache<Block>>>,
 }
 
 impl<B, Block> FinalityProofProvider<B, Block>
@@ -78,7 +92,13 @@ where
 		backend: Arc<B>,
 		shared_authority_set: Option<SharedAuthoritySet<Block::Hash, NumberFor<Block>>>,
 	) -> Self {
-		FinalityProofProvider { backend, shared_authority_set }
+		FinalityProofProvider {
+			backend,
+			shared_authority_set,
+			authority_set_change_proofs: Arc::new(Mutex::new(LruMap::new(ByLength::new(
+				AUTHORITY_SET_CHANGE_PROOFS_CACHE_SIZE,
+			)))),
+		}
 	}
 
 	/// Create new finality proof provider for the service using:
@@ -130,6 +150,36 @@ where
 
 		prove_finality(&*self.backend, authority_set_changes, block, collect_unknown_headers)
 	}
+
+	/// Prove all authority set changes enacted in the `(from; to]` range of blocks, by returning
+	/// the header of the last block of each set along with its justification.
+	///
+	/// Proofs are returned in ascending order, up to the first set whose justification is not
+	/// available locally. The justifications are returned as stored, so they may carry votes and
+	/// headers not needed to prove finality.
+	pub fn prove_authority_set_changes(
+		&self,
+		from: NumberFor<Block>,
+		to: NumberFor<Block>,
+	) -> Result<Vec<AuthoritySetChangeProof<Block::Header>>, FinalityProofError> {
+		let authority_set_changes = if let Some(changes) = self
+			.shared_authority_set
+			.as_ref()
+			.map(SharedAuthoritySet::authority_set_changes)
+		{
+			changes
+		} else {
+			return Ok(Vec::new())
+		};
+
+		prove_authority_set_changes(
+			&*self.backend,
+			authority_set_changes,
+			&self.authority_set_change_proofs,
+			from,
+			to,
+		)
+	}
 }
 
 /// Finality for block B is proved by providing:
@@ -145,6 +195,31 @@ pub struct FinalityProof<Header: HeaderT> {
 	pub unknown_headers: Vec<Header>,
 }
 
+/// Proof of an authority set change, in the compact form expected by bridges: the header of the
+/// last block of the set and its justification, without any intermediary headers.
+#[derive(Debug, PartialEq, Encode, Decode, Clone)]
+pub struct AuthoritySetChangeProof<Header: HeaderT> {
+	/// Header of the last block of the authority set, which enacts the set change.
+	pub header: Header,
+	/// Encoded justification of `header`.
+	pub justification: Vec<u8>,
+	/// Id of the authority set.
+	pub set_id: SetId,
+	/// Authorities of the set which signed `justification`, if they were scheduled without delay
+	/// by the last block of the previous set.
+	pub authorities: Option<AuthorityList>,
+}
+
+impl<Header: HeaderT> AuthoritySetChangeProof<Header> {
+	/// Encode the proof as the `(header, justification)` call arguments expected by
+	/// `pallet-bridge-grandpa::submit_finality_proof`.
+	pub fn encode_call_args(&self) -> Vec<u8> {
+		let mut encoded = self.header.encode();
+		encoded.extend_from_slice(&self.justification);
+		encoded
+	}
+}
+
 /// Errors occurring when trying to prove finality
 #[derive(Debug, thiserror::Error)]
 pub enum FinalityProofError {
@@ -256,6 +331,125 @@ where
 	}))
 }
 
+/// Prove all authority set changes enacted in the `(from; to]` range of blocks, by returning the
+/// header of the last block of each set along with its justification.
+///
+/// The authorities of each set are read from the change signaled by the last block of the
+/// previous set, so they are unknown for the first set and for the sets following a forced or
+/// delayed change.
+///
+/// Proofs of past authority set changes never change, so they are served from `cache` if known.
+fn prove_authority_set_changes<Block, B>(
+	backend: &B,
+	authority_set_changes: AuthoritySetChanges<NumberFor<Block>>,
+	cache: &Mutex<AuthoritySetChangeProofsCache<Block>>,
+	from: NumberFor<Block>,
+	to: NumberFor<Block>,
+) -> Result<Vec<AuthoritySetChangeProof<Block::Header>>, FinalityProofError>
+where
+	Block: BlockT,
+	B: Backend<Block>,
+{
+	let finalized_number = backend.blockchain().info().finalized_number;
+	if finalized_number < to {
+		trace!(
+			target: LOG_TARGET,
+			"Requested authority set changes up to #{} while we only have finalized #{}.",
+			to,
+			finalized_number,
+		);
+		return Err(FinalityProofError::BlockNotYetFinalized)
+	}
+
+	let last_blocks_for_sets = authority_set_changes
+		.iter_from(from)
+		.ok_or(FinalityProofError::BlockNotInAuthoritySetChanges)?
+		.take_while(|(_, last_block_for_set)| *last_block_for_set <= to)
+		.take(MAX_AUTHORITY_SET_CHANGE_PROOFS);
+
+	let mut proofs = Vec::new();
+	for &(set_id, last_block_for_set) in last_blocks_for_sets {
+		if let Some(proof) = cache.lock().get(&last_block_for_set) {
+			proofs.push(proof.clone());
+			continue
+		}
+		let previous_set_end = set_id
+			.checked_sub(1)
+			.and_then(|previous_set_id| authority_set_changes.last_block_of(previous_set_id));
+		let authorities = previous_set_end
+			.map(|block| enacted_authorities(backend, *block))
+			.transpose()?
+			.flatten();
+		match prove_authority_set_change(backend, set_id, authorities, last_block_for_set)? {
+			Some(proof) => {
+				cache.lock().insert(last_block_for_set, proof.clone());
+				proofs.push(proof);
+			},
+			None => {
+				trace!(
+					target: LOG_TARGET,
+					"No justification found for the last block #{} of authority set. \
+					Returning {} proofs.",
+					last_block_for_set,
+					proofs.len(),
+				);
+				break
+			},
+		}
+	}
+
+	Ok(proofs)
+}
+
+/// Authorities of the set enacted at `block`, if the block signals a scheduled change with no
+/// delay.
+fn enacted_authorities<Block, B>(
+	backend: &B,
+	block: NumberFor<Block>,
+) -> Result<Option<AuthorityList>, FinalityProofError>
+where
+	Block: BlockT,
+	B: Backend<Block>,
+{
+	let hash = backend.blockchain().expect_block_hash_from_id(&BlockId::Number(block))?;
+	let header = backend.blockchain().expect_header(hash)?;
+	if find_forced_change::<Block>(&header).is_some() {
+		return Ok(None)
+	}
+	let change = find_scheduled_change::<Block>(&header).filter(|change| change.delay.is_zero());
+	Ok(change.map(|change| change.next_authorities))
+}
+
+/// Prove the authority set change enacted at `last_block_for_set` by returning its header and
+/// justification, if the latter is available.
+fn prove_authority_set_change<Block, B>(
+	backend: &B,
+	set_id: SetId,
+	authorities: Option<AuthorityList>,
+	last_block_for_set: NumberFor<Block>,
+) -> Result<Option<AuthoritySetChangeProof<Block::Header>>, FinalityProofError>
+where
+	Block: BlockT,
+	B: Backend<Block>,
+{
+	let hash = backend
+		.blockchain()
+		.expect_block_hash_from_id(&BlockId::Number(last_block_for_set))?;
+	let justification = backend
+		.blockchain()
+		.justifications(hash)?
+		.and_then(|justifications| justifications.into_justification(GRANDPA_ENGINE_ID));
+	match justification {
+		Some(justification) => Ok(Some(AuthoritySetChangeProof {
+			header: backend.blockchain().expect_header(hash)?,
+			justification,
+			set_id,
+			authorities,
+		})),
+		None => Ok(None),
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -264,9 +458,10 @@ mod tests {
 	use sc_block_builder::BlockBuilderBuilder;
 	use sc_client_api::{apply_aux, LockImportRun};
 	use sp_consensus::BlockOrigin;
-	use sp_consensus_grandpa::GRANDPA_ENGINE_ID as ID;
+	use sp_consensus_grandpa::{ConsensusLog, ScheduledChange, GRANDPA_ENGINE_ID as ID};
 	use sp_core::crypto::UncheckedFrom;
 	use sp_keyring::Ed25519Keyring;
+	use sp_runtime::{Digest, DigestItem};
 	use substrate_test_runtime_client::{
 		runtime::{Block, Header, H256},
 		Backend as TestBackend, ClientBlockImportExt, ClientExt, DefaultTestClientBuilderExt,
@@ -316,16 +511,37 @@ mod tests {
 	fn test_blockchain(
 		number_of_blocks: u64,
 		to_finalize: &[u64],
+	) -> (Arc<TestClient>, Arc<TestBackend>, Vec<Block>) {
+		test_blockchain_with_changes(number_of_blocks, to_finalize, &[])
+	}
+
+	/// Same as [`test_blockchain`], with the given blocks scheduling the given authorities with
+	/// no delay.
+	fn test_blockchain_with_changes(
+		number_of_blocks: u64,
+		to_finalize: &[u64],
+		changes: &[(u64, AuthorityList)],
 	) -> (Arc<TestClient>, Arc<TestBackend>, Vec<Block>) {
 		let builder = TestClientBuilder::new();
 		let backend = builder.backend();
 		let mut client = Arc::new(builder.build());
 
 		let mut blocks = Vec::new();
-		for _ in 0..number_of_blocks {
+		for number in 1..=number_of_blocks {
+			let mut digest = Digest::default();
+			if let Some((_, next_authorities)) = changes.iter().find(|(block, _)| *block == number)
+			{
+				let change =
+					ScheduledChange { next_authorities: next_authorities.clone(), delay: 0 };
+				digest.push(DigestItem::Consensus(
+					ID,
+					ConsensusLog::ScheduledChange(change).encode(),
+				));
+			}
 			let block = BlockBuilderBuilder::new(&*client)
 				.on_parent_block(client.chain_info().best_hash)
 				.with_parent_block_number(client.chain_info().best_number)
+				.with_inherent_digests(digest)
 				.build()
 				.unwrap()
 				.build()
@@ -555,6 +771,94 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn authority_set_change_proofs_using_authority_set_changes_work() {
+		let set1 = [
+			Ed25519Keyring::Alice,
+			Ed25519Keyring::Bob,
+			Ed25519Keyring::Charlie,
+			Ed25519Keyring::Dave,
+		];
+		let set1_authorities: AuthorityList =
+			set1.iter().map(|keyring| (keyring.public().into(), 1)).collect();
+		let set2_authorities: AuthorityList = vec![(Ed25519Keyring::Alice.public().into(), 1)];
+		let (client, backend, blocks) = test_blockchain_with_changes(
+			12,
+			&[4],
+			&[(5, set1_authorities.clone()), (8, set2_authorities)],
+		);
+		let block5 = &blocks[4];
+		let block7 = &blocks[6];
+		let block8 = &blocks[7];
+
+		let commit = create_commit(block5.clone(), 5, 0, &[Ed25519Keyring::Alice]);
+		let grandpa_just5 = GrandpaJustification::from_commit(&client, 5, commit).unwrap();
+		client
+			.finalize_block(block5.hash(), Some((ID, grandpa_just5.encode())))
+			.unwrap();
+		// All the authorities of set 1 voted, and the justification carries an unneeded header
+		let grandpa_just8 = sp_consensus_grandpa::GrandpaJustification::<Header> {
+			round: 8,
+			commit: create_commit(block8.clone(), 8, 1, &set1),
+			votes_ancestries: vec![block7.header().clone()],
+		};
+		client
+			.finalize_block(block8.hash(), Some((ID, grandpa_just8.encode())))
+			.unwrap();
+		// Block 11 is finalized without justification
+		client.finalize_block(blocks[10].hash(), None).unwrap();
+
+		let mut authority_set_changes = AuthoritySetChanges::empty();
+		authority_set_changes.append(0, 5);
+		authority_set_changes.append(1, 8);
+		authority_set_changes.append(2, 11);
+		let cache = Mutex::new(LruMap::new(ByLength::new(16)));
+
+		// Can't prove changes past the last finalized block
+		let proofs =
+			prove_authority_set_changes(&*backend, authority_set_changes.clone(), &cache, 0, 12);
+		assert!(matches!(proofs, Err(FinalityProofError::BlockNotYetFinalized)));
+
+		// Proofs stop at the first set without justification, the justifications are returned as
+		// stored, along with the authorities scheduled by the last block of the previous set
+		let proofs =
+			prove_authority_set_changes(&*backend, authority_set_changes.clone(), &cache, 0, 11)
+				.unwrap();
+		assert_eq!(
+			proofs,
+			vec![
+				AuthoritySetChangeProof {
+					header: block5.header().clone(),
+					justification: grandpa_just5.encode(),
+					set_id: 0,
+					authorities: None,
+				},
+				AuthoritySetChangeProof {
+					header: block8.header().clone(),
+					justification: grandpa_just8.encode(),
+					set_id: 1,
+					authorities: Some(set1_authorities),
+				},
+			],
+		);
+		assert_eq!(cache.lock().len(), 2);
+
+		// Proofs are ready to be submitted to the bridge pallet
+		assert_eq!(proofs[1].encode_call_args(), (block8.header(), &grandpa_just8).encode());
+
+		// The range start is excluded, the range end is included
+		let proofs =
+			prove_authority_set_changes(&*backend, authority_set_changes, &cache, 5, 8).unwrap();
+		assert_eq!(proofs.len(), 1);
+		assert_eq!(proofs[0].header, *block8.header());
+
+		// Missing data about the first sets
+		let mut authority_set_changes = AuthoritySetChanges::empty();
+		authority_set_changes.append(1, 8);
+		let proofs = prove_authority_set_changes(&*backend, authority_set_changes, &cache, 6, 8);
+		assert!(matches!(proofs, Err(FinalityProofError::BlockNotInAuthoritySetChanges)));
+	}
+
 	#[test]
 	fn finality_proof_in_last_set_fails_without_latest() {
 		let (_, backend, _) = test_blockchain(8, &[4, 5, 8]);
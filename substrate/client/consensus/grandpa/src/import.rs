@@ -552,8 +552,20 @@ where
 							.into(),
 					))
 				}
+				// The block history downloaded after a warp sync is imported backwards, so the
+				// gap always ends right at the imported block, even when a single block is left.
+				let descending =
+					self.inner.info().block_gap.map_or(false, |(_, end)| number == end);
 				let mut authority_set = self.authority_set.inner_locked();
-				authority_set.authority_set_changes.insert(number);
+				if descending {
+					let current_set_id = authority_set.set_id;
+					authority_set
+						.authority_set_changes
+						.insert_descending::<sp_blockchain::Error>(number, current_set_id)
+						.map_err(|e| ConsensusError::ClientImport(e.to_string()))?;
+				} else {
+					authority_set.authority_set_changes.insert(number);
+				}
 				crate::aux_schema::update_authority_set::<Block, _, _>(
 					&authority_set,
 					None,
@@ -22,6 +22,7 @@ use crate::{
 	best_justification, find_scheduled_change, AuthoritySetChanges, AuthoritySetHardFork,
 	BlockNumberOps, GrandpaJustification, SharedAuthoritySet,
 };
+use sc_chain_spec::SyncCheckpoint;
 use sc_client_api::Backend as ClientBackend;
 use sc_network_sync::warp::{EncodedProof, VerificationResult, WarpSyncProvider};
 use sp_blockchain::{Backend as BlockchainBackend, HeaderBackend};
@@ -51,6 +52,9 @@ pub enum Error {
 	/// Missing header or authority set change data.
 	#[error("Missing required data to be able to answer request.")]
 	MissingData,
+	/// Invalid warp sync checkpoint.
+	#[error("Invalid warp sync checkpoint: {0}")]
+	InvalidCheckpoint(String),
 }
 
 /// The maximum size in bytes of the `WarpSyncProof`.
@@ -235,6 +239,69 @@ impl<Block: BlockT> WarpSyncProof<Block> {
 	}
 }
 
+/// Trusted block from which warp sync starts, as stored in the `checkpoint` section of a chain
+/// spec.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WarpSyncCheckpoint<Block: BlockT> {
+	/// Last block finalized by the previous authority set.
+	pub header: Block::Header,
+	/// Id of the authority set that finalizes the blocks following `header`.
+	pub set_id: SetId,
+	/// Authorities of this set.
+	pub authorities: AuthorityList,
+}
+
+impl<Block: BlockT> WarpSyncCheckpoint<Block> {
+	/// Checkpoint at the start of the current authority set.
+	///
+	/// If the last block of the previous set is unknown, e.g. on a node that was warp synced and
+	/// did not see an authority set change since, the checkpoint is the last finalized block,
+	/// which is followed by blocks finalized by the current set as well.
+	pub fn current<Client>(
+		client: &Client,
+		authority_set: &SharedAuthoritySet<Block::Hash, NumberFor<Block>>,
+	) -> Result<Self, Error>
+	where
+		Client: HeaderBackend<Block>,
+	{
+		let (set_id, authorities, number) = {
+			// The authority set is updated under this lock when a block is finalized, so the
+			// finalized block read here belongs to the same set.
+			let authority_set = authority_set.inner();
+			let number = match authority_set.authority_set_changes.last() {
+				Some((previous_set_id, number)) if previous_set_id + 1 == authority_set.set_id =>
+					*number,
+				_ => client.info().finalized_number,
+			};
+			(authority_set.set_id, authority_set.current_authorities.clone(), number)
+		};
+		let hash = client.hash(number)?.ok_or(Error::MissingData)?;
+		let header = client.header(hash)?.ok_or(Error::MissingData)?;
+		Ok(Self { header, set_id, authorities })
+	}
+
+	/// Decode the checkpoint of a chain spec.
+	pub fn from_chain_spec(checkpoint: &SyncCheckpoint) -> Result<Self, Error> {
+		let header = Block::Header::decode_all(&mut &checkpoint.header[..])
+			.map_err(|e| Error::InvalidCheckpoint(format!("Failed to decode header: {e}")))?;
+		let authorities = AuthorityList::decode_all(&mut &checkpoint.grandpa_authorities[..])
+			.map_err(|e| Error::InvalidCheckpoint(format!("Failed to decode authorities: {e}")))?;
+		if authorities.is_empty() {
+			return Err(Error::InvalidCheckpoint("Empty authority set".into()))
+		}
+		Ok(Self { header, set_id: checkpoint.grandpa_set_id, authorities })
+	}
+
+	/// Encode the checkpoint for a chain spec.
+	pub fn to_chain_spec(&self) -> SyncCheckpoint {
+		SyncCheckpoint {
+			header: self.header.encode().into(),
+			grandpa_set_id: self.set_id,
+			grandpa_authorities: self.authorities.encode().into(),
+		}
+	}
+}
+
 /// Implements network API for warp sync.
 pub struct NetworkProvider<Block: BlockT, Backend: ClientBackend<Block>>
 where
@@ -243,6 +310,7 @@ where
 	backend: Arc<Backend>,
 	authority_set: SharedAuthoritySet<Block::Hash, NumberFor<Block>>,
 	hard_forks: HashMap<(Block::Hash, NumberFor<Block>), (SetId, AuthorityList)>,
+	checkpoint: Option<WarpSyncCheckpoint<Block>>,
 }
 
 impl<Block: BlockT, Backend: ClientBackend<Block>> NetworkProvider<Block, Backend>
@@ -262,8 +330,15 @@ where
 				.into_iter()
 				.map(|fork| (fork.block, (fork.set_id, fork.authorities)))
 				.collect(),
+			checkpoint: None,
 		}
 	}
+
+	/// Start warp sync from the given checkpoint instead of genesis.
+	pub fn with_checkpoint(mut self, checkpoint: WarpSyncCheckpoint<Block>) -> Self {
+		self.checkpoint = Some(checkpoint);
+		self
+	}
 }
 
 impl<Block: BlockT, Backend: ClientBackend<Block>> WarpSyncProvider<Block>
@@ -314,11 +389,17 @@ where
 	fn current_authorities(&self) -> AuthorityList {
 		self.authority_set.inner().current_authorities.clone()
 	}
+
+	fn checkpoint(&self) -> Option<(Block::Hash, SetId, AuthorityList)> {
+		self.checkpoint.as_ref().map(|checkpoint| {
+			(checkpoint.header.hash(), checkpoint.set_id, checkpoint.authorities.clone())
+		})
+	}
 }
 
 #[cfg(test)]
 mod tests {
-	use super::WarpSyncProof;
+	use super::{WarpSyncCheckpoint, WarpSyncProof};
 	use crate::{AuthoritySetChanges, GrandpaJustification};
 	use parity_scale_codec::Encode;
 	use rand::prelude::*;
@@ -329,8 +410,8 @@ mod tests {
 	use sp_keyring::Ed25519Keyring;
 	use std::sync::Arc;
 	use substrate_test_runtime_client::{
-		BlockBuilderExt, ClientBlockImportExt, ClientExt, DefaultTestClientBuilderExt,
-		TestClientBuilder, TestClientBuilderExt,
+		runtime::Block, BlockBuilderExt, ClientBlockImportExt, ClientExt,
+		DefaultTestClientBuilderExt, TestClientBuilder, TestClientBuilderExt,
 	};
 
 	#[test]
@@ -346,6 +427,7 @@ mod tests {
 		let mut current_authorities = vec![Ed25519Keyring::Alice];
 		let mut current_set_id = 0;
 		let mut authority_set_changes = Vec::new();
+		let mut checkpoint = None;
 
 		for n in 1..=100 {
 			let mut builder = BlockBuilderBuilder::new(&*client)
@@ -423,6 +505,17 @@ mod tests {
 
 				current_set_id += 1;
 				current_authorities = new_authorities;
+
+				if n == 50 {
+					checkpoint = Some(WarpSyncCheckpoint::<Block> {
+						header: client.header(target_hash).unwrap().unwrap(),
+						set_id: current_set_id,
+						authorities: current_authorities
+							.iter()
+							.map(|keyring| (keyring.public().into(), 1))
+							.collect(),
+					});
+				}
 			}
 		}
 
@@ -445,5 +538,21 @@ mod tests {
 
 		assert_eq!(new_set_id, current_set_id);
 		assert_eq!(new_authorities, expected_authorities);
+
+		// the proof can also start from a checkpoint, read back from a chain spec
+		let checkpoint =
+			WarpSyncCheckpoint::<Block>::from_chain_spec(&checkpoint.unwrap().to_chain_spec())
+				.unwrap();
+		let warp_sync_proof =
+			WarpSyncProof::generate(&*backend, checkpoint.header.hash(), &authority_set_changes)
+				.unwrap();
+		assert_eq!(warp_sync_proof.proofs.len(), 5);
+
+		let (new_set_id, new_authorities) = warp_sync_proof
+			.verify(checkpoint.set_id, checkpoint.authorities, &Default::default())
+			.unwrap();
+
+		assert_eq!(new_set_id, current_set_id);
+		assert_eq!(new_authorities, expected_authorities);
 	}
 }
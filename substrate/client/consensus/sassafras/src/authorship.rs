@@ -0,0 +1,659 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Types and functions related to block authoring and tickets generation.
+
+use super::*;
+
+use std::{future::Future, pin::Pin, thread, time::Duration};
+
+use futures::{channel::oneshot, prelude::*};
+use log::{error, info, warn};
+
+use sc_client_api::BlockchainEvents;
+use sc_consensus::block_import::{BlockImport, BlockImportParams, StateAction};
+use sc_consensus_epochs::descendent_query;
+use sc_consensus_slots::{InherentDataProviderExt, SlotInfo, StorageChanges};
+use sc_telemetry::TelemetryHandle;
+use sc_transaction_pool_api::OffchainTransactionPoolFactory;
+use sp_api::ApiExt;
+use sp_application_crypto::AppCrypto;
+use sp_blockchain::{Error as ClientError, HeaderBackend, HeaderMetadata};
+use sp_consensus::{BlockOrigin, Environment, Proposer, SelectChain};
+use sp_consensus_sassafras::ticket_id_threshold;
+use sp_core::{blake2_64, crypto::ByteArray, ed25519, Pair};
+use sp_inherents::CreateInherentDataProviders;
+use sp_keystore::KeystorePtr;
+use sp_runtime::DigestItem;
+
+/// Index of the authority responsible for a slot which has no ticket assigned.
+///
+/// The index is deterministically computed from the epoch randomness and the slot.
+pub(crate) fn secondary_authority_index(slot: Slot, epoch: &Epoch) -> AuthorityIndex {
+	let hash = blake2_64(&(epoch.randomness, slot).encode());
+	let rand = u64::from_le_bytes(hash);
+	(rand % epoch.authorities.len() as u64) as AuthorityIndex
+}
+
+/// Try to claim an epoch slot.
+///
+/// If `maybe_ticket` is `Some` the slot is assigned to a ticket and the claim
+/// succeeds only if the ticket belongs to one of the local authorities.
+/// Otherwise the slot is claimed by the fallback authority, if it is local.
+///
+/// The `epoch` must be the one the slot belongs to (see [`Epoch::clone_for_slot`]).
+pub(crate) fn claim_slot(
+	slot: Slot,
+	epoch: &Epoch,
+	maybe_ticket: Option<(TicketId, TicketBody)>,
+	keystore: &KeystorePtr,
+) -> Option<(SlotClaim, AuthorityId)> {
+	if epoch.authorities.is_empty() {
+		return None
+	}
+
+	let mut vrf_sign_data = vrf::slot_claim_sign_data(&epoch.randomness, slot, epoch.epoch_idx);
+
+	let (authority_idx, ticket_secret) = match maybe_ticket {
+		Some((ticket_id, ticket_body)) => {
+			trace!(target: LOG_TARGET, "Slot {} is assigned to ticket {:032x}", slot, ticket_id);
+			let (authority_idx, erased_secret) = epoch.tickets_aux.get(&ticket_id)?;
+			debug!(
+				target: LOG_TARGET,
+				"Ticket {:032x} owned by authority {} (attempt {})",
+				ticket_id,
+				authority_idx,
+				ticket_body.attempt_idx
+			);
+			vrf_sign_data
+				.push_vrf_input(vrf::revealed_key_input(
+					&epoch.randomness,
+					ticket_body.attempt_idx,
+					epoch.epoch_idx,
+				))
+				.expect("Sign data has enough space; qed");
+			(*authority_idx, Some(erased_secret))
+		},
+		None => {
+			trace!(target: LOG_TARGET, "Slot {} has no ticket, trying fallback claim", slot);
+			(secondary_authority_index(slot, epoch), None)
+		},
+	};
+
+	let authority_id = epoch.authorities.get(authority_idx as usize)?;
+	if !keystore.has_keys(&[(authority_id.to_raw_vec(), AuthorityId::ID)]) {
+		return None
+	}
+
+	let vrf_signature = keystore
+		.bandersnatch_vrf_sign(AuthorityId::ID, authority_id.as_ref(), &vrf_sign_data)
+		.ok()
+		.flatten()?;
+
+	let ticket_claim = ticket_secret.map(|erased_secret| {
+		let erased_pair = ed25519::Pair::from_seed(erased_secret);
+		let erased_signature = erased_pair.sign(&vrf_sign_data.challenge::<32>());
+		TicketClaim { erased_signature }
+	});
+
+	let claim = SlotClaim { authority_idx, slot, vrf_signature, ticket_claim };
+
+	Some((claim, authority_id.clone()))
+}
+
+/// Generate the tickets for the given epoch.
+///
+/// Tickets additional information is stored within the `Epoch` structure,
+/// only tickets with an identifier below the epoch threshold are returned.
+pub(crate) fn generate_epoch_tickets(
+	epoch: &mut Epoch,
+	keystore: &KeystorePtr,
+	ring_ctx: &vrf::RingContext,
+) -> Vec<TicketEnvelope> {
+	let mut tickets = Vec::new();
+
+	let randomness = epoch.randomness;
+	let epoch_idx = epoch.epoch_idx;
+	let attempts_number = epoch.config.attempts_number;
+	let threshold = ticket_id_threshold(
+		epoch.config.redundancy_factor,
+		epoch.epoch_duration as u32,
+		attempts_number,
+		epoch.authorities.len() as u32,
+	);
+	debug!(target: LOG_TARGET, "Tickets threshold for epoch {}: {:032x}", epoch_idx, threshold);
+
+	let authorities = epoch.authorities.clone();
+	let ring_members: Vec<_> =
+		authorities.iter().map(|authority| authority.clone().into_inner()).collect();
+
+	for (authority_idx, authority_id) in authorities.iter().enumerate() {
+		if !keystore.has_keys(&[(authority_id.to_raw_vec(), AuthorityId::ID)]) {
+			continue
+		}
+
+		let Some(prover) = ring_ctx.prover(&ring_members, authority_idx) else {
+			warn!(target: LOG_TARGET, "Unable to construct the ring prover");
+			continue
+		};
+
+		let make_ticket = |attempt_idx| {
+			let ticket_id_input = vrf::ticket_id_input(&randomness, attempt_idx, epoch_idx);
+
+			let ticket_id_output = keystore
+				.bandersnatch_vrf_output(AuthorityId::ID, authority_id.as_ref(), &ticket_id_input)
+				.ok()??;
+
+			let ticket_id = vrf::make_ticket_id(&ticket_id_input, &ticket_id_output);
+			if ticket_id >= threshold {
+				return None
+			}
+
+			let (erased_pair, erased_seed) = ed25519::Pair::generate();
+
+			let revealed_input = vrf::revealed_key_input(&randomness, attempt_idx, epoch_idx);
+			let revealed_output = keystore
+				.bandersnatch_vrf_output(AuthorityId::ID, authority_id.as_ref(), &revealed_input)
+				.ok()??;
+			let revealed_seed = vrf::make_revealed_key_seed(&revealed_input, &revealed_output);
+			let revealed_public = ed25519::Pair::from_seed(&revealed_seed).public();
+
+			let body =
+				TicketBody { attempt_idx, erased_public: erased_pair.public(), revealed_public };
+
+			let sign_data = vrf::ticket_body_sign_data(&body, ticket_id_input);
+
+			let signature = keystore
+				.bandersnatch_ring_vrf_sign(
+					AuthorityId::ID,
+					authority_id.as_ref(),
+					&sign_data,
+					&prover,
+				)
+				.ok()??;
+
+			Some((ticket_id, TicketEnvelope { body, signature }, erased_seed))
+		};
+
+		for attempt in 0..attempts_number {
+			if let Some((ticket_id, envelope, erased_secret)) = make_ticket(attempt) {
+				debug!(target: LOG_TARGET, "Generated ticket {:032x}", ticket_id);
+				tickets.push(envelope);
+				epoch.tickets_aux.insert(ticket_id, (authority_idx as u32, erased_secret));
+			}
+		}
+	}
+
+	tickets
+}
+
+struct SlotWorker<B: BlockT, C, E, I, SO, L> {
+	client: Arc<C>,
+	block_import: I,
+	env: E,
+	sync_oracle: SO,
+	justification_sync_link: L,
+	force_authoring: bool,
+	keystore: KeystorePtr,
+	epoch_changes: SharedEpochChanges<B, Epoch>,
+	genesis_config: sp_consensus_sassafras::Epoch,
+	block_proposal_slot_portion: SlotProportion,
+	max_block_proposal_slot_portion: Option<SlotProportion>,
+	telemetry: Option<TelemetryHandle>,
+}
+
+#[async_trait::async_trait]
+impl<B, C, E, I, ER, SO, L> sc_consensus_slots::SimpleSlotWorker<B>
+	for SlotWorker<B, C, E, I, SO, L>
+where
+	B: BlockT,
+	C: ProvideRuntimeApi<B> + HeaderBackend<B> + HeaderMetadata<B, Error = ClientError>,
+	C::Api: SassafrasApi<B>,
+	E: Environment<B, Error = ER> + Send + Sync,
+	E::Proposer: Proposer<B, Error = ER>,
+	I: BlockImport<B> + Send + Sync + 'static,
+	SO: SyncOracle + Send + Clone + Sync,
+	L: sc_consensus::JustificationSyncLink<B>,
+	ER: std::error::Error + Send + From<ConsensusError> + From<I::Error> + 'static,
+{
+	type Claim = (SlotClaim, AuthorityId);
+	type SyncOracle = SO;
+	type JustificationSyncLink = L;
+	type CreateProposer =
+		Pin<Box<dyn Future<Output = Result<E::Proposer, ConsensusError>> + Send + 'static>>;
+	type Proposer = E::Proposer;
+	type BlockImport = I;
+	type AuxData = ViableEpochDescriptor<B::Hash, NumberFor<B>, Epoch>;
+
+	fn logging_target(&self) -> &'static str {
+		LOG_TARGET
+	}
+
+	fn block_import(&mut self) -> &mut Self::BlockImport {
+		&mut self.block_import
+	}
+
+	fn aux_data(&self, parent: &B::Header, slot: Slot) -> Result<Self::AuxData, ConsensusError> {
+		self.epoch_changes
+			.shared_data()
+			.epoch_descriptor_for_child_of(
+				descendent_query(&*self.client),
+				&parent.hash(),
+				*parent.number(),
+				slot,
+			)
+			.map_err(|e| ConsensusError::ChainLookup(e.to_string()))?
+			.ok_or(ConsensusError::InvalidAuthoritiesSet)
+	}
+
+	fn authorities_len(&self, epoch_descriptor: &Self::AuxData) -> Option<usize> {
+		self.epoch_changes
+			.shared_data()
+			.viable_epoch(epoch_descriptor, |slot| Epoch::genesis(&self.genesis_config, slot))
+			.map(|epoch| epoch.as_ref().authorities.len())
+	}
+
+	async fn claim_slot(
+		&mut self,
+		parent_header: &B::Header,
+		slot: Slot,
+		epoch_descriptor: &ViableEpochDescriptor<B::Hash, NumberFor<B>, Epoch>,
+	) -> Option<Self::Claim> {
+		debug!(target: LOG_TARGET, "Attempting to claim slot {}", slot);
+
+		// Get the next slot ticket from the runtime.
+		let maybe_ticket =
+			self.client.runtime_api().slot_ticket(parent_header.hash(), slot).ok()?;
+
+		let epoch = self
+			.epoch_changes
+			.shared_data()
+			.viable_epoch(epoch_descriptor, |slot| Epoch::genesis(&self.genesis_config, slot))?
+			.as_ref()
+			.clone_for_slot(slot);
+
+		let claim = claim_slot(slot, &epoch, maybe_ticket, &self.keystore);
+		if claim.is_some() {
+			debug!(target: LOG_TARGET, "Claimed slot {}", slot);
+		}
+		claim
+	}
+
+	fn pre_digest_data(&self, _slot: Slot, (claim, _): &Self::Claim) -> Vec<DigestItem> {
+		vec![DigestItem::from(claim)]
+	}
+
+	async fn block_import_params(
+		&self,
+		header: B::Header,
+		header_hash: &B::Hash,
+		body: Vec<B::Extrinsic>,
+		storage_changes: StorageChanges<B>,
+		(_, public): Self::Claim,
+		epoch_descriptor: Self::AuxData,
+	) -> Result<BlockImportParams<B>, ConsensusError> {
+		let signature = self
+			.keystore
+			.bandersnatch_sign(
+				<AuthorityId as AppCrypto>::ID,
+				public.as_ref(),
+				header_hash.as_ref(),
+			)
+			.map_err(|e| ConsensusError::CannotSign(format!("{}. Key {:?}", e, public)))?
+			.map(AuthoritySignature::from)
+			.ok_or_else(|| {
+				ConsensusError::CannotSign(format!(
+					"Could not find key in keystore. Key {:?}",
+					public
+				))
+			})?;
+
+		let mut block = BlockImportParams::new(BlockOrigin::Own, header);
+		block.post_digests.push(DigestItem::from(&signature));
+		block.body = Some(body);
+		block.state_action =
+			StateAction::ApplyChanges(sc_consensus::StorageChanges::Changes(storage_changes));
+		block
+			.insert_intermediate(INTERMEDIATE_KEY, SassafrasIntermediate::<B> { epoch_descriptor });
+
+		Ok(block)
+	}
+
+	fn force_authoring(&self) -> bool {
+		self.force_authoring
+	}
+
+	fn sync_oracle(&mut self) -> &mut Self::SyncOracle {
+		&mut self.sync_oracle
+	}
+
+	fn justification_sync_link(&mut self) -> &mut Self::JustificationSyncLink {
+		&mut self.justification_sync_link
+	}
+
+	fn proposer(&mut self, block: &B::Header) -> Self::CreateProposer {
+		self.env
+			.init(block)
+			.map_err(|e| ConsensusError::ClientImport(format!("{:?}", e)))
+			.boxed()
+	}
+
+	fn telemetry(&self) -> Option<TelemetryHandle> {
+		self.telemetry.clone()
+	}
+
+	fn proposing_remaining_duration(&self, slot_info: &SlotInfo<B>) -> Duration {
+		let parent_slot = find_slot::<B>(&slot_info.chain_head).ok();
+
+		sc_consensus_slots::proposing_remaining_duration(
+			parent_slot,
+			slot_info,
+			&self.block_proposal_slot_portion,
+			self.max_block_proposal_slot_portion.as_ref(),
+			sc_consensus_slots::SlotLenienceType::Exponential,
+			self.logging_target(),
+		)
+	}
+}
+
+/// Authoring tickets generation worker.
+///
+/// Listens on the client's import notification stream for blocks which contain new epoch
+/// information, that is blocks that signals the begin of a new epoch.
+/// This event here triggers the begin of the generation of tickets for the next epoch.
+/// The tickets generated by the worker are saved within the epoch changes tree
+/// and are volatile.
+async fn start_tickets_worker<B, C, SC>(
+	client: Arc<C>,
+	keystore: KeystorePtr,
+	epoch_changes: SharedEpochChanges<B, Epoch>,
+	epoch_duration: u64,
+	select_chain: SC,
+	offchain_tx_pool_factory: OffchainTransactionPoolFactory<B>,
+) where
+	B: BlockT,
+	C: BlockchainEvents<B>
+		+ ProvideRuntimeApi<B>
+		+ HeaderBackend<B>
+		+ HeaderMetadata<B, Error = ClientError>
+		+ AuxStore,
+	C::Api: SassafrasApi<B>,
+	SC: SelectChain<B> + 'static,
+{
+	let mut notifications = client.import_notification_stream();
+
+	while let Some(notification) = notifications.next().await {
+		let epoch_desc = match find_next_epoch_digest::<B>(&notification.header) {
+			Ok(Some(epoch_desc)) => epoch_desc,
+			Err(err) => {
+				warn!(target: LOG_TARGET, "Error fetching next epoch digest: {}", err);
+				continue
+			},
+			_ => continue,
+		};
+
+		let number = *notification.header.number();
+		debug!(target: LOG_TARGET, "Generating tickets for epoch announced at block #{}", number);
+		let slot = match find_slot::<B>(&notification.header) {
+			Ok(slot) => slot,
+			Err(err) => {
+				warn!(target: LOG_TARGET, "Error fetching block slot: {}", err);
+				continue
+			},
+		};
+
+		let ring_ctx = match client.runtime_api().ring_context(notification.hash) {
+			Ok(Some(ctx)) => ctx,
+			Ok(None) => {
+				warn!(target: LOG_TARGET, "Ring context not initialized, skipping tickets");
+				continue
+			},
+			Err(err) => {
+				error!(target: LOG_TARGET, "Unable to fetch the ring context: {}", err);
+				continue
+			},
+		};
+
+		let (epoch_id, mut epoch) = {
+			let epoch_changes = epoch_changes.shared_data();
+
+			// The epoch announced by the block starts after the one the block belongs to.
+			// Any slot of the announced epoch can be used to look it up, as the tree node
+			// for that epoch is attached to the block itself.
+			let next_epoch = epoch_changes
+				.epoch_descriptor_for_child_of(
+					descendent_query(&*client),
+					&notification.hash,
+					number,
+					slot + epoch_duration,
+				)
+				.ok()
+				.flatten()
+				.and_then(|desc| match desc {
+					ViableEpochDescriptor::Signaled(id, _) => Some(id),
+					ViableEpochDescriptor::UnimportedGenesis(_) => None,
+				})
+				.and_then(|id| epoch_changes.epoch(&id).map(|epoch| (id, epoch.clone())));
+
+			let Some(next_epoch) = next_epoch else {
+				warn!(target: LOG_TARGET, "Missing epoch data for {:?}", notification.hash);
+				continue
+			};
+			next_epoch
+		};
+
+		if epoch.randomness != epoch_desc.randomness {
+			warn!(target: LOG_TARGET, "Inconsistent epoch data for {:?}", notification.hash);
+			continue
+		}
+
+		// Producing the ring signatures takes a while, it is done on a dedicated thread so that
+		// neither the slot worker nor the block import are held up.
+		let (tx, rx) = oneshot::channel();
+		let thread_keystore = keystore.clone();
+		let spawned = thread::Builder::new().name("sassafras-tickets".into()).spawn(move || {
+			let tickets = generate_epoch_tickets(&mut epoch, &thread_keystore, &ring_ctx);
+			let _ = tx.send((tickets, epoch.tickets_aux));
+		});
+		if let Err(err) = spawned {
+			error!(target: LOG_TARGET, "Unable to spawn the tickets generation thread: {}", err);
+			continue
+		}
+		let Ok((tickets, tickets_aux)) = rx.await else {
+			error!(target: LOG_TARGET, "Tickets generation thread terminated unexpectedly");
+			continue
+		};
+		if tickets.is_empty() {
+			continue
+		}
+
+		{
+			let mut epoch_changes = epoch_changes.shared_data();
+			let Some(epoch) = epoch_changes.epoch_mut(&epoch_id) else {
+				warn!(target: LOG_TARGET, "Epoch data pruned for {:?}", notification.hash);
+				continue
+			};
+			epoch.tickets_aux.extend(tickets_aux);
+
+			// Save tickets auxiliary data to be able to claim the slots later.
+			let res = aux_schema::write_epoch_changes::<B, _, _>(&*epoch_changes, |values| {
+				client.insert_aux(values, [])
+			});
+			if let Err(err) = res {
+				error!(target: LOG_TARGET, "Unable to persist tickets data: {}", err);
+			}
+		}
+
+		// Tickets are submitted on the best chain, the submission is valid only on forks
+		// sharing the same next epoch parameters.
+		let best_hash = match select_chain.best_chain().await {
+			Ok(header) => header.hash(),
+			Err(err) => {
+				error!(target: LOG_TARGET, "Error fetching best chain block id: {}", err);
+				continue
+			},
+		};
+
+		let mut runtime_api = client.runtime_api();
+
+		// Register the offchain tx pool to be able to use it from the runtime.
+		runtime_api
+			.register_extension(offchain_tx_pool_factory.offchain_transaction_pool(best_hash));
+
+		let tickets_count = tickets.len();
+		match runtime_api.submit_tickets_unsigned_extrinsic(best_hash, tickets) {
+			Ok(true) => info!(target: LOG_TARGET, "Submitted {} tickets", tickets_count),
+			Ok(false) => warn!(target: LOG_TARGET, "Tickets submission rejected by the runtime"),
+			Err(err) => error!(target: LOG_TARGET, "Error submitting tickets: {}", err),
+		}
+	}
+}
+
+/// Worker for Sassafras which implements `Future<Output=()>`. This must be polled.
+#[must_use]
+pub struct SassafrasWorker<B: BlockT> {
+	inner: Pin<Box<dyn Future<Output = ()> + Send + 'static>>,
+	_phantom: std::marker::PhantomData<B>,
+}
+
+impl<B: BlockT> Future for SassafrasWorker<B> {
+	type Output = ();
+
+	fn poll(
+		mut self: Pin<&mut Self>,
+		cx: &mut std::task::Context,
+	) -> std::task::Poll<Self::Output> {
+		self.inner.as_mut().poll(cx)
+	}
+}
+
+/// Parameters for Sassafras.
+pub struct SassafrasParams<B: BlockT, C, SC, EN, I, SO, L, CIDP> {
+	/// The client to use
+	pub client: Arc<C>,
+	/// The keystore that manages the keys of the node.
+	pub keystore: KeystorePtr,
+	/// The chain selection strategy
+	pub select_chain: SC,
+	/// The environment we are producing blocks for.
+	pub env: EN,
+	/// The underlying block-import object to supply our produced blocks to.
+	/// This must be a `SassafrasBlockImport` or a wrapper of it, otherwise
+	/// critical consensus logic will be omitted.
+	pub block_import: I,
+	/// A sync oracle
+	pub sync_oracle: SO,
+	/// Hook into the sync module to control the justification sync process.
+	pub justification_sync_link: L,
+	/// Something that can create the inherent data providers.
+	pub create_inherent_data_providers: CIDP,
+	/// Force authoring of blocks even if we are offline
+	pub force_authoring: bool,
+	/// State shared between import queue and authoring worker.
+	pub sassafras_link: SassafrasLink<B>,
+	/// The proportion of the slot dedicated to proposing.
+	pub block_proposal_slot_portion: SlotProportion,
+	/// The maximum proportion of the slot dedicated to proposing with any lenience factor applied
+	/// due to no blocks being produced.
+	pub max_block_proposal_slot_portion: Option<SlotProportion>,
+	/// Handle use to report telemetries.
+	pub telemetry: Option<TelemetryHandle>,
+	/// The offchain transaction pool factory.
+	///
+	/// Will be used when submitting tickets.
+	pub offchain_tx_pool_factory: OffchainTransactionPoolFactory<B>,
+}
+
+/// Start the Sassafras worker.
+pub fn start_sassafras<B, C, SC, EN, I, SO, L, CIDP, ER>(
+	SassafrasParams {
+		client,
+		keystore,
+		select_chain,
+		env,
+		block_import,
+		sync_oracle,
+		justification_sync_link,
+		create_inherent_data_providers,
+		force_authoring,
+		sassafras_link,
+		block_proposal_slot_portion,
+		max_block_proposal_slot_portion,
+		telemetry,
+		offchain_tx_pool_factory,
+	}: SassafrasParams<B, C, SC, EN, I, SO, L, CIDP>,
+) -> Result<SassafrasWorker<B>, ConsensusError>
+where
+	B: BlockT,
+	C: ProvideRuntimeApi<B>
+		+ HeaderBackend<B>
+		+ HeaderMetadata<B, Error = ClientError>
+		+ BlockchainEvents<B>
+		+ AuxStore
+		+ Send
+		+ Sync
+		+ 'static,
+	C::Api: SassafrasApi<B>,
+	SC: SelectChain<B> + 'static,
+	EN: Environment<B, Error = ER> + Send + Sync + 'static,
+	EN::Proposer: Proposer<B, Error = ER>,
+	I: BlockImport<B, Error = ConsensusError> + Send + Sync + 'static,
+	SO: SyncOracle + Send + Sync + Clone + 'static,
+	L: sc_consensus::JustificationSyncLink<B> + 'static,
+	CIDP: CreateInherentDataProviders<B, ()> + Send + Sync + 'static,
+	CIDP::InherentDataProviders: InherentDataProviderExt + Send,
+	ER: std::error::Error + Send + From<ConsensusError> + From<I::Error> + 'static,
+{
+	info!(target: LOG_TARGET, "🍁 Starting authorship worker");
+
+	let slot_worker = SlotWorker {
+		client: client.clone(),
+		block_import,
+		env,
+		sync_oracle: sync_oracle.clone(),
+		justification_sync_link,
+		force_authoring,
+		keystore: keystore.clone(),
+		epoch_changes: sassafras_link.epoch_changes.clone(),
+		genesis_config: sassafras_link.genesis_config.clone(),
+		block_proposal_slot_portion,
+		max_block_proposal_slot_portion,
+		telemetry,
+	};
+
+	let slot_worker = sc_consensus_slots::start_slot_worker(
+		sassafras_link.genesis_config.slot_duration,
+		select_chain.clone(),
+		sc_consensus_slots::SimpleSlotWorkerToSlotWorker(slot_worker),
+		sync_oracle,
+		create_inherent_data_providers,
+	);
+
+	let tickets_worker = start_tickets_worker(
+		client,
+		keystore,
+		sassafras_link.epoch_changes,
+		sassafras_link.genesis_config.epoch_duration,
+		select_chain,
+		offchain_tx_pool_factory,
+	);
+
+	let inner = future::select(Box::pin(slot_worker), Box::pin(tickets_worker));
+
+	Ok(SassafrasWorker { inner: Box::pin(inner.map(|_| ())), _phantom: Default::default() })
+}
@@ -0,0 +1,230 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Sassafras client tests.
+
+use super::*;
+
+use crate::{
+	authorship::{claim_slot, generate_epoch_tickets, secondary_authority_index},
+	verification::{check_header, VerificationParams},
+};
+use sc_consensus_slots::CheckedHeader;
+use sc_keystore::LocalKeystore;
+use sp_application_crypto::AppCrypto;
+use sp_consensus_sassafras::{ticket_id_threshold, SlotDuration};
+use sp_core::ed25519;
+use sp_keystore::{Keystore, KeystorePtr};
+use sp_runtime::{generic::Digest, traits::BlakeTwo256, DigestItem, OpaqueExtrinsic};
+
+type TestHeader = sp_runtime::generic::Header<u64, BlakeTwo256>;
+type TestBlock = sp_runtime::generic::Block<TestHeader, OpaqueExtrinsic>;
+
+const SEEDS: [&str; 3] = ["//Alice", "//Bob", "//Charlie"];
+
+fn create_keystore(seeds: &[&str]) -> KeystorePtr {
+	let keystore = LocalKeystore::in_memory();
+	for seed in seeds {
+		keystore
+			.bandersnatch_generate_new(AuthorityId::ID, Some(seed))
+			.expect("Creates authority key");
+	}
+	keystore.into()
+}
+
+fn create_test_epoch() -> Epoch {
+	let keystore = create_keystore(&[]);
+	let authorities = SEEDS
+		.iter()
+		.map(|seed| {
+			let public = keystore.bandersnatch_generate_new(AuthorityId::ID, Some(seed)).unwrap();
+			AuthorityId::from(public)
+		})
+		.collect();
+
+	sp_consensus_sassafras::Epoch {
+		epoch_idx: 3,
+		start_slot: 100.into(),
+		slot_duration: SlotDuration::from_millis(6000),
+		epoch_duration: 10,
+		authorities,
+		randomness: [1; RANDOMNESS_LENGTH],
+		config: EpochConfiguration { redundancy_factor: 2, attempts_number: 8 },
+	}
+	.into()
+}
+
+fn make_sealed_header(
+	claim: &SlotClaim,
+	keystore: &KeystorePtr,
+	public: &AuthorityId,
+) -> TestHeader {
+	let mut header = TestHeader::new(
+		1,
+		Default::default(),
+		Default::default(),
+		Default::default(),
+		Digest { logs: vec![DigestItem::from(claim)] },
+	);
+	let signature = keystore
+		.bandersnatch_sign(AuthorityId::ID, public.as_ref(), header.hash().as_ref())
+		.unwrap()
+		.map(AuthoritySignature::from)
+		.unwrap();
+	header.digest_mut().push(DigestItem::from(&signature));
+	header
+}
+
+fn verify_header(
+	header: TestHeader,
+	claim: &SlotClaim,
+	epoch: &Epoch,
+	maybe_ticket: Option<(TicketId, TicketBody)>,
+) -> Result<CheckedHeader<TestHeader, verification::VerifiedHeaderInfo>, Error<TestBlock>> {
+	check_header::<TestBlock>(VerificationParams {
+		header,
+		slot_claim: claim,
+		slot_now: claim.slot + 1,
+		epoch,
+		maybe_ticket,
+	})
+}
+
+#[test]
+fn claim_fallback_slots_works() {
+	let epoch = create_test_epoch();
+	let keystore = create_keystore(&SEEDS);
+
+	for slot in 100..110 {
+		let slot = Slot::from(slot);
+		let (claim, public) = claim_slot(slot, &epoch, None, &keystore).unwrap();
+
+		assert_eq!(claim.authority_idx, secondary_authority_index(slot, &epoch));
+		assert!(claim.ticket_claim.is_none());
+		assert_eq!(epoch.authorities[claim.authority_idx as usize], public);
+
+		let header = make_sealed_header(&claim, &keystore, &public);
+		assert!(matches!(
+			verify_header(header, &claim, &epoch, None),
+			Ok(CheckedHeader::Checked(_, _))
+		));
+	}
+
+	// Without keys nothing can be claimed.
+	let empty_keystore = create_keystore(&[]);
+	assert!(claim_slot(100.into(), &epoch, None, &empty_keystore).is_none());
+}
+
+#[test]
+fn fallback_claim_by_wrong_authority_is_rejected() {
+	let epoch = create_test_epoch();
+	let keystore = create_keystore(&SEEDS);
+	let slot = Slot::from(101);
+
+	let (mut claim, _) = claim_slot(slot, &epoch, None, &keystore).unwrap();
+	claim.authority_idx = (claim.authority_idx + 1) % epoch.authorities.len() as u32;
+	let public = epoch.authorities[claim.authority_idx as usize].clone();
+
+	let header = make_sealed_header(&claim, &keystore, &public);
+	assert!(matches!(verify_header(header, &claim, &epoch, None), Err(Error::InvalidAuthor(_, _))));
+}
+
+#[test]
+fn tickets_generation_and_claim_works() {
+	let mut epoch = create_test_epoch();
+	// Local node owns only the second authority key.
+	let keystore = create_keystore(&SEEDS[1..2]);
+	let ring_ctx = vrf::RingContext::new_testing();
+
+	let tickets = generate_epoch_tickets(&mut epoch, &keystore, &ring_ctx);
+	assert!(!tickets.is_empty());
+	assert_eq!(tickets.len(), epoch.tickets_aux.len());
+	assert!(epoch.tickets_aux.values().all(|(authority_idx, _)| *authority_idx == 1));
+
+	// Tickets are valid against the on-chain verification procedure.
+	let threshold = ticket_id_threshold(2, 10, 8, 3);
+	let ring_members: Vec<_> = epoch
+		.authorities
+		.iter()
+		.map(|authority| authority.clone().into_inner())
+		.collect();
+	let verifier = ring_ctx.verifier(&ring_members).unwrap();
+	let mut ticket_ids = Vec::new();
+	for ticket in &tickets {
+		let input = vrf::ticket_id_input(&epoch.randomness, ticket.body.attempt_idx, 3);
+		let ticket_id = vrf::make_ticket_id(&input, &ticket.signature.outputs[0]);
+		assert!(ticket_id < threshold);
+		assert!(epoch.tickets_aux.contains_key(&ticket_id));
+		let sign_data = vrf::ticket_body_sign_data(&ticket.body, input);
+		assert!(ticket.signature.ring_vrf_verify(&sign_data, &verifier));
+		ticket_ids.push(ticket_id);
+	}
+
+	// Claim a slot using one of the tickets.
+	let slot = Slot::from(105);
+	let ticket = (ticket_ids[0], tickets[0].body.clone());
+	let (claim, public) = claim_slot(slot, &epoch, Some(ticket.clone()), &keystore).unwrap();
+	assert_eq!(claim.authority_idx, 1);
+	assert!(claim.ticket_claim.is_some());
+
+	let header = make_sealed_header(&claim, &keystore, &public);
+	assert!(matches!(
+		verify_header(header.clone(), &claim, &epoch, Some(ticket.clone())),
+		Ok(CheckedHeader::Checked(_, _))
+	));
+
+	// The revealed key must match the one committed in the ticket.
+	let mut bad_ticket = ticket.clone();
+	bad_ticket.1.revealed_public = bad_ticket.1.erased_public;
+	assert!(matches!(
+		verify_header(header.clone(), &claim, &epoch, Some(bad_ticket)),
+		Err(Error::TicketClaimVerificationFailed)
+	));
+
+	// A ticket claim is not expected for a slot without ticket.
+	assert!(matches!(
+		verify_header(header, &claim, &epoch, None),
+		Err(Error::UnexpectedTicketClaim(_))
+	));
+
+	// Tickets we don't own can't be claimed.
+	let mut foreign_ticket = ticket;
+	foreign_ticket.0 = u128::MAX;
+	assert!(claim_slot(slot, &epoch, Some(foreign_ticket), &keystore).is_none());
+}
+
+#[test]
+fn fallback_claim_for_ticket_slot_is_rejected() {
+	let epoch = create_test_epoch();
+	let keystore = create_keystore(&SEEDS);
+
+	let ticket_body = TicketBody {
+		attempt_idx: 0,
+		erased_public: ed25519::Public::from_raw([1; 32]),
+		revealed_public: ed25519::Public::from_raw([2; 32]),
+	};
+
+	// Slot claimed as if it had no ticket assigned.
+	let slot = Slot::from(107);
+	let (claim, public) = claim_slot(slot, &epoch, None, &keystore).unwrap();
+	let header = make_sealed_header(&claim, &keystore, &public);
+	assert!(matches!(
+		verify_header(header, &claim, &epoch, Some((123, ticket_body))),
+		Err(Error::MissingTicketClaim(_))
+	));
+}
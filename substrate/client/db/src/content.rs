@@ -0,0 +1,174 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Content pinned by the node, stored in the indexed transactions column.
+
+use std::{collections::BTreeSet, marker::PhantomData, sync::Arc};
+
+use crate::{columns, utils::meta_keys, Database, DbHash, Transaction};
+use codec::{Decode, Encode};
+use parking_lot::Mutex;
+use sp_blockchain::{Error, Result as ClientResult};
+use sp_runtime::traits::Block as BlockT;
+
+/// Pinned content storage.
+///
+/// The number of pins of every content is kept under its own key in the meta column, so that
+/// unpinning never releases a reference held by an indexed transaction. The hashes of the pinned
+/// content are kept in memory, and only written when content is first pinned or last unpinned.
+pub struct ContentStore<Block> {
+	db: Arc<dyn Database<DbHash>>,
+	/// Hashes of the pinned content. Also serializes the updates of the pin counts.
+	pinned: Mutex<BTreeSet<DbHash>>,
+	_phantom: PhantomData<Block>,
+}
+
+impl<Block: BlockT> ContentStore<Block> {
+	/// Create the pinned content storage of the given database.
+	pub fn new(db: Arc<dyn Database<DbHash>>) -> ClientResult<Self> {
+		let pinned = match db.get(columns::META, meta_keys::PINNED_CONTENT) {
+			Some(pinned) => BTreeSet::decode(&mut &pinned[..])
+				.map_err(|e| Error::Backend(format!("Error decoding the pinned content: {}", e)))?,
+			None => Default::default(),
+		};
+		Ok(Self { db, pinned: Mutex::new(pinned), _phantom: PhantomData })
+	}
+
+	fn pin_count(&self, hash: &DbHash) -> ClientResult<u32> {
+		match self.db.get(columns::META, &pin_count_key(hash)) {
+			Some(count) => u32::decode(&mut &count[..])
+				.map_err(|e| Error::Backend(format!("Error decoding the pin count: {}", e))),
+			None => Ok(0),
+		}
+	}
+}
+
+/// Key of the pin count of the content with the given hash.
+fn pin_count_key(hash: &DbHash) -> Vec<u8> {
+	let mut key = meta_keys::PIN_COUNT_PREFIX.to_vec();
+	key.extend_from_slice(hash.as_ref());
+	key
+}
+
+impl<Block: BlockT> sc_client_api::backend::ContentStore<Block> for ContentStore<Block> {
+	fn pin(&self, data: Vec<u8>) -> ClientResult<Block::Hash> {
+		let hash = DbHash::from(sp_core::hashing::blake2_256(&data));
+		let mut pinned = self.pinned.lock();
+		let count = self.pin_count(&hash)?.saturating_add(1);
+
+		let mut transaction = Transaction::new();
+		transaction.store(columns::TRANSACTION, hash, data);
+		transaction.set_from_vec(columns::META, &pin_count_key(&hash), count.encode());
+		let first_pin = !pinned.contains(&hash);
+		if first_pin {
+			let mut updated = pinned.clone();
+			updated.insert(hash);
+			transaction.set_from_vec(columns::META, meta_keys::PINNED_CONTENT, updated.encode());
+		}
+		self.db.commit(transaction)?;
+		if first_pin {
+			pinned.insert(hash);
+		}
+
+		let mut block_hash = Block::Hash::default();
+		block_hash.as_mut().copy_from_slice(hash.as_ref());
+		Ok(block_hash)
+	}
+
+	fn unpin(&self, hash: Block::Hash) -> ClientResult<bool> {
+		let hash = DbHash::from_slice(hash.as_ref());
+		let mut pinned = self.pinned.lock();
+		let count = self.pin_count(&hash)?;
+		if count == 0 {
+			return Ok(false)
+		}
+
+		let mut transaction = Transaction::new();
+		transaction.release(columns::TRANSACTION, hash);
+		if count > 1 {
+			transaction.set_from_vec(columns::META, &pin_count_key(&hash), (count - 1).encode());
+		} else {
+			let mut updated = pinned.clone();
+			updated.remove(&hash);
+			transaction.remove(columns::META, &pin_count_key(&hash));
+			transaction.set_from_vec(columns::META, meta_keys::PINNED_CONTENT, updated.encode());
+		}
+		self.db.commit(transaction)?;
+		if count == 1 {
+			pinned.remove(&hash);
+		}
+		Ok(true)
+	}
+
+	fn pinned(&self) -> ClientResult<Vec<Block::Hash>> {
+		Ok(self
+			.pinned
+			.lock()
+			.iter()
+			.map(|hash| {
+				let mut block_hash = Block::Hash::default();
+				block_hash.as_mut().copy_from_slice(hash.as_ref());
+				block_hash
+			})
+			.collect())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::tests::Block;
+	use sc_client_api::backend::ContentStore as _;
+
+	#[test]
+	fn content_is_kept_until_unpinned() {
+		let db = sp_database::as_database(kvdb_memorydb::create(crate::utils::NUM_COLUMNS));
+		let store = ContentStore::<Block>::new(db.clone()).unwrap();
+
+		let hash = store.pin(b"content".to_vec()).unwrap();
+		assert_eq!(hash.as_ref(), &sp_core::hashing::blake2_256(b"content"));
+		assert_eq!(store.pin(b"content".to_vec()).unwrap(), hash);
+		assert_eq!(store.pinned().unwrap(), vec![hash]);
+
+		assert!(store.unpin(hash).unwrap());
+		assert_eq!(db.get(columns::TRANSACTION, hash.as_ref()), Some(b"content".to_vec()));
+		assert!(store.unpin(hash).unwrap());
+		assert_eq!(db.get(columns::TRANSACTION, hash.as_ref()), None);
+		assert!(store.pinned().unwrap().is_empty());
+
+		// Content that is not pinned is left alone.
+		assert!(!store.unpin(hash).unwrap());
+	}
+
+	#[test]
+	fn pins_are_restored() {
+		let db = sp_database::as_database(kvdb_memorydb::create(crate::utils::NUM_COLUMNS));
+		let store = ContentStore::<Block>::new(db.clone()).unwrap();
+		let first = store.pin(b"first".to_vec()).unwrap();
+		let second = store.pin(b"second".to_vec()).unwrap();
+		store.pin(b"second".to_vec()).unwrap();
+		assert!(store.unpin(first).unwrap());
+
+		let store = ContentStore::<Block>::new(db.clone()).unwrap();
+		assert_eq!(store.pinned().unwrap(), vec![second]);
+		assert!(store.unpin(second).unwrap());
+		assert!(store.unpin(second).unwrap());
+		assert!(!store.unpin(second).unwrap());
+		assert!(ContentStore::<Block>::new(db).unwrap().pinned().unwrap().is_empty());
+	}
+}
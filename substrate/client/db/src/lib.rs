@@ -33,6 +33,7 @@ pub mod offchain;
 pub mod bench;
 
 mod children;
+mod content;
 mod parity_db;
 mod pinned_blocks_cache;
 mod record_stats_state;
@@ -1108,6 +1109,7 @@ impl<T: Clone> FrozenForDuration<T> {
 pub struct Backend<Block: BlockT> {
 	storage: Arc<StorageDb<Block>>,
 	offchain_storage: offchain::LocalStorage,
+	content_store: Arc<content::ContentStore<Block>>,
 	blockchain: BlockchainDb<Block>,
 	canonicalization_delay: u64,
 	import_lock: Arc<RwLock<()>>,
@@ -1225,10 +1227,12 @@ impl<Block: BlockT> Backend<Block> {
 			StorageDb { db: db.clone(), state_db, prefix_keys: !db.supports_ref_counting() };
 
 		let offchain_storage = offchain::LocalStorage::new(db.clone());
+		let content_store = Arc::new(content::ContentStore::new(db.clone())?);
 
 		let backend = Backend {
 			storage: Arc::new(storage_db),
 			offchain_storage,
+			content_store,
 			blockchain,
 			canonicalization_delay,
 			import_lock: Default::default(),
@@ -1677,9 +1681,15 @@ impl<Block: BlockT> Backend<Block> {
 					);
 				}
 
-				if let Some((mut start, end)) = block_gap {
-					if number == start {
-						start += One::one();
+				if let Some((mut start, mut end)) = block_gap {
+					// The gap is filled forwards by gap sync or backwards by the block history
+					// download following a warp sync.
+					if number == start || number == end {
+						if number == start {
+							start += One::one();
+						} else {
+							end -= One::one();
+						}
 						utils::insert_number_to_key_mapping(
 							&mut transaction,
 							columns::KEY_LOOKUP,
@@ -2173,6 +2183,10 @@ impl<Block: BlockT> sc_client_api::backend::Backend<Block> for Backend<Block> {
 		Some(self.offchain_storage.clone())
 	}
 
+	fn content_store(&self) -> Option<Arc<dyn sc_client_api::backend::ContentStore<Block>>> {
+		Some(self.content_store.clone())
+	}
+
 	fn usage_info(&self) -> Option<UsageInfo> {
 		let (io_stats, state_stats) = self.io_stats.take_or_else(|| {
 			(
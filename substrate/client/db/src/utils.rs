@@ -56,6 +56,10 @@ pub mod meta_keys {
 	pub const LEAF_PREFIX: &[u8; 4] = b"leaf";
 	/// Children prefix list key.
 	pub const CHILDREN_PREFIX: &[u8; 8] = b"children";
+	/// Hashes of the pinned content.
+	pub const PINNED_CONTENT: &[u8; 6] = b"pinned";
+	/// Pin count prefix, followed by the hash of the content.
+	pub const PIN_COUNT_PREFIX: &[u8; 9] = b"pin_count";
 }
 
 /// Database metadata.
@@ -16,15 +16,12 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::OutputFormat;
+use crate::{BandwidthRates, InformantEvent, NodeStatus, OutputFormat, SyncPhase};
 use ansi_term::Colour;
 use log::info;
 use sc_client_api::ClientInfo;
 use sc_network::NetworkStatus;
-use sc_network_sync::{
-	warp::{WarpSyncPhase, WarpSyncProgress},
-	SyncState, SyncStatus,
-};
+use sc_network_sync::SyncStatus;
 use sp_runtime::traits::{Block as BlockT, CheckedDiv, NumberFor, Saturating, Zero};
 use std::{fmt, time::Instant};
 
@@ -40,16 +37,16 @@ use std::{fmt, time::Instant};
 ///
 /// Call `InformantDisplay::new` to initialize the state, then regularly call `display` with the
 /// information to display.
+///
+/// In [`OutputFormat::json`] mode, the line is replaced by the [`NodeStatus`] serialized as JSON.
 pub struct InformantDisplay<B: BlockT> {
 	/// Head of chain block number from the last time `display` has been called.
 	/// `None` if `display` has never been called.
 	last_number: Option<NumberFor<B>>,
 	/// The last time `display` or `new` has been called.
 	last_update: Instant,
-	/// The last seen total of bytes received.
-	last_total_bytes_inbound: u64,
-	/// The last seen total of bytes sent.
-	last_total_bytes_outbound: u64,
+	/// The transfer rates of the node.
+	bandwidth: BandwidthRates,
 	/// The format to print output in.
 	format: OutputFormat,
 }
@@ -60,8 +57,7 @@ impl<B: BlockT> InformantDisplay<B> {
 		InformantDisplay {
 			last_number: None,
 			last_update: Instant::now(),
-			last_total_bytes_inbound: 0,
-			last_total_bytes_outbound: 0,
+			bandwidth: BandwidthRates::new(),
 			format,
 		}
 	}
@@ -73,76 +69,45 @@ impl<B: BlockT> InformantDisplay<B> {
 		net_status: NetworkStatus,
 		sync_status: SyncStatus<B>,
 	) {
-		let best_number = info.chain.best_number;
-		let best_hash = info.chain.best_hash;
-		let finalized_number = info.chain.finalized_number;
-		let num_connected_peers = sync_status.num_connected_peers;
+		self.bandwidth.update(&net_status);
+		let node_status = NodeStatus::new(&info.chain, &net_status, &sync_status, &self.bandwidth);
+		let best_number = node_status.best_number;
+		let best_hash = node_status.best_hash;
+		let finalized_number = node_status.finalized_number;
+		let num_connected_peers = node_status.peers;
 		let speed = speed::<B>(best_number, self.last_number, self.last_update);
-		let total_bytes_inbound = net_status.total_bytes_inbound;
-		let total_bytes_outbound = net_status.total_bytes_outbound;
+		let avg_bytes_per_sec_inbound = node_status.bytes_inbound_per_sec;
+		let avg_bytes_per_sec_outbound = node_status.bytes_outbound_per_sec;
 
-		let now = Instant::now();
-		let elapsed = (now - self.last_update).as_secs();
-		self.last_update = now;
+		self.last_update = Instant::now();
 		self.last_number = Some(best_number);
 
-		let diff_bytes_inbound = total_bytes_inbound - self.last_total_bytes_inbound;
-		let diff_bytes_outbound = total_bytes_outbound - self.last_total_bytes_outbound;
-		let (avg_bytes_per_sec_inbound, avg_bytes_per_sec_outbound) = if elapsed > 0 {
-			self.last_total_bytes_inbound = total_bytes_inbound;
-			self.last_total_bytes_outbound = total_bytes_outbound;
-			(diff_bytes_inbound / elapsed, diff_bytes_outbound / elapsed)
-		} else {
-			(diff_bytes_inbound, diff_bytes_outbound)
-		};
+		if self.format.json {
+			InformantEvent::Status(&node_status).print();
+			return
+		}
 
-		let (level, status, target) =
-			match (sync_status.state, sync_status.state_sync, sync_status.warp_sync) {
-				// Do not set status to "Block history" when we are doing a major sync.
-				//
-				// A node could for example have been warp synced to the tip of the chain and
-				// shutdown. At the next start we still need to download the block history, but
-				// first will sync to the tip of the chain.
-				(
-					sync_status,
-					_,
-					Some(WarpSyncProgress { phase: WarpSyncPhase::DownloadingBlocks(n), .. }),
-				) if !sync_status.is_major_syncing() => ("⏩", "Block history".into(), format!(", #{}", n)),
-				(
-					_,
-					_,
-					Some(WarpSyncProgress { phase: WarpSyncPhase::AwaitingTargetBlock, .. }),
-				) => ("⏩", "Waiting for pending target block".into(), "".into()),
-				// Handle all phases besides the two phases we already handle above.
-				(_, _, Some(warp))
-					if !matches!(
-						warp.phase,
-						WarpSyncPhase::AwaitingTargetBlock | WarpSyncPhase::DownloadingBlocks(_)
-					) =>
-					(
-						"⏩",
-						"Warping".into(),
-						format!(
-							", {}, {:.2} Mib",
-							warp.phase,
-							(warp.total_bytes as f32) / (1024f32 * 1024f32)
-						),
-					),
-				(_, Some(state), _) => (
-					"⚙️ ",
-					"Downloading state".into(),
-					format!(
-						", {}%, {:.2} Mib",
-						state.percentage,
-						(state.size as f32) / (1024f32 * 1024f32)
-					),
-				),
-				(SyncState::Idle, _, _) => ("💤", "Idle".into(), "".into()),
-				(SyncState::Downloading { target }, _, _) =>
-					("⚙️ ", format!("Syncing{}", speed), format!(", target=#{target}")),
-				(SyncState::Importing { target }, _, _) =>
-					("⚙️ ", format!("Preparing{}", speed), format!(", target=#{target}")),
-			};
+		let (level, status, target) = match &node_status.sync {
+			SyncPhase::BlockHistory { number } =>
+				("⏩", "Block history".into(), format!(", #{}", number)),
+			SyncPhase::AwaitingTargetBlock =>
+				("⏩", "Waiting for pending target block".into(), "".into()),
+			SyncPhase::Warping { phase, total_bytes } => (
+				"⏩",
+				"Warping".into(),
+				format!(", {}, {:.2} Mib", phase, (*total_bytes as f32) / (1024f32 * 1024f32)),
+			),
+			SyncPhase::DownloadingState { percentage, size } => (
+				"⚙️ ",
+				"Downloading state".into(),
+				format!(", {}%, {:.2} Mib", percentage, (*size as f32) / (1024f32 * 1024f32)),
+			),
+			SyncPhase::Idle => ("💤", "Idle".into(), "".into()),
+			SyncPhase::Downloading { target } =>
+				("⚙️ ", format!("Syncing{}", speed), format!(", target=#{target}")),
+			SyncPhase::Importing { target } =>
+				("⚙️ ", format!("Preparing{}", speed), format!(", target=#{target}")),
+		};
 
 		if self.format.enable_color {
 			info!(
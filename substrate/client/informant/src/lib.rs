@@ -25,11 +25,21 @@ use log::{debug, info, trace};
 use sc_client_api::{BlockchainEvents, UsageProvider};
 use sc_network::NetworkStatusProvider;
 use sc_network_sync::SyncStatusProvider;
+use serde::Serialize;
 use sp_blockchain::HeaderMetadata;
 use sp_runtime::traits::{Block as BlockT, Header};
-use std::{collections::VecDeque, fmt::Display, sync::Arc, time::Duration};
+use std::{
+	collections::VecDeque,
+	fmt::Display,
+	io::{self, Write},
+	sync::Arc,
+	time::Duration,
+};
 
 mod display;
+mod status;
+
+pub use status::{BandwidthRates, NodeStatus, SyncPhase};
 
 /// Creates a stream that returns a new value every `duration`.
 fn interval(duration: Duration) -> impl Stream<Item = ()> + Unpin {
@@ -43,11 +53,50 @@ pub struct OutputFormat {
 	///
 	/// Is enabled by default.
 	pub enable_color: bool,
+	/// Print the status and the block events as JSON objects instead of human-readable lines.
+	///
+	/// The messages are written to stdout, one JSON object per line, bypassing the logger. Each
+	/// message is a [`NodeStatus`] or a block event, tagged with its `event` kind.
+	pub json: bool,
 }
 
 impl Default for OutputFormat {
 	fn default() -> Self {
-		Self { enable_color: true }
+		Self { enable_color: true, json: false }
+	}
+}
+
+/// Message printed by the informant in JSON mode.
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "camelCase")]
+enum InformantEvent<'a, Hash, Number> {
+	/// Periodic status of the node.
+	Status(&'a NodeStatus<Hash, Number>),
+	/// A block was imported.
+	Imported { number: Number, hash: Hash },
+	/// The best block moved to another fork.
+	#[serde(rename_all = "camelCase")]
+	Reorg {
+		from_number: Number,
+		from_hash: Hash,
+		to_number: Number,
+		to_hash: Hash,
+		ancestor_number: Number,
+		ancestor_hash: Hash,
+	},
+}
+
+impl<Hash: Serialize, Number: Serialize> InformantEvent<'_, Hash, Number> {
+	/// Write the event as a single line of JSON to stdout.
+	fn print(&self) {
+		let mut stdout = io::stdout().lock();
+		let result = serde_json::to_writer(&mut stdout, self)
+			.map_err(io::Error::from)
+			.and_then(|()| stdout.write_all(b"\n"))
+			.and_then(|()| stdout.flush());
+		if let Err(err) = result {
+			debug!("Could not print informant event: {}", err);
+		}
 	}
 }
 
@@ -89,11 +138,11 @@ where
 
 	futures::select! {
 		() = display_notifications.fuse() => (),
-		() = display_block_import(client).fuse() => (),
+		() = display_block_import(client, format.json).fuse() => (),
 	};
 }
 
-fn display_block_import<B: BlockT, C>(client: Arc<C>) -> impl Future<Output = ()>
+fn display_block_import<B: BlockT, C>(client: Arc<C>, json: bool) -> impl Future<Output = ()>
 where
 	C: UsageProvider<B> + HeaderMetadata<B> + BlockchainEvents<B>,
 	<C as HeaderMetadata<B>>::Error: Display,
@@ -115,6 +164,16 @@ where
 					sp_blockchain::lowest_common_ancestor(&*client, *last_hash, n.hash);
 
 				match maybe_ancestor {
+					Ok(ref ancestor) if ancestor.hash != *last_hash && json =>
+						InformantEvent::Reorg {
+							from_number: *last_num,
+							from_hash: *last_hash,
+							to_number: *n.header.number(),
+							to_hash: n.hash,
+							ancestor_number: ancestor.number,
+							ancestor_hash: ancestor.hash,
+						}
+						.print(),
 					Ok(ref ancestor) if ancestor.hash != *last_hash => info!(
 						"♻️  Reorg on #{},{} to #{},{}, common ancestor #{},{}",
 						Colour::Red.bold().paint(format!("{}", last_num)),
@@ -143,12 +202,16 @@ where
 				last_blocks.pop_front();
 			}
 
-			info!(
-				target: "substrate",
-				"✨ Imported #{} ({})",
-				Colour::White.bold().paint(format!("{}", n.header.number())),
-				n.hash,
-			);
+			if json {
+				InformantEvent::Imported { number: *n.header.number(), hash: n.hash }.print();
+			} else {
+				info!(
+					target: "substrate",
+					"✨ Imported #{} ({})",
+					Colour::White.bold().paint(format!("{}", n.header.number())),
+					n.hash,
+				);
+			}
 		}
 
 		future::ready(())
@@ -0,0 +1,225 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Machine-readable snapshot of the information displayed by the informant.
+
+use sc_network::NetworkStatus;
+use sc_network_sync::{
+	warp::{WarpSyncPhase, WarpSyncProgress},
+	SyncState, SyncStatus,
+};
+use serde::Serialize;
+use sp_blockchain::Info;
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT};
+use std::time::{Duration, Instant};
+
+/// Status of the node, as printed by the informant in JSON mode and returned by the
+/// `system_unstable_status` RPC.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeStatus<Hash, Number> {
+	/// Number of the best block.
+	pub best_number: Number,
+	/// Hash of the best block.
+	pub best_hash: Hash,
+	/// Number of the last finalized block.
+	pub finalized_number: Number,
+	/// Hash of the last finalized block.
+	pub finalized_hash: Hash,
+	/// Number of peers known to the syncing engine.
+	pub peers: u32,
+	/// Current phase of the syncing.
+	pub sync: SyncPhase<Number>,
+	/// Number of blocks queued for import.
+	pub queued_blocks: u32,
+	/// Total number of bytes received since the start of the node.
+	pub total_bytes_inbound: u64,
+	/// Total number of bytes sent since the start of the node.
+	pub total_bytes_outbound: u64,
+	/// Average number of bytes received per second since the previous status.
+	pub bytes_inbound_per_sec: u64,
+	/// Average number of bytes sent per second since the previous status.
+	pub bytes_outbound_per_sec: u64,
+}
+
+/// Phase of the syncing, in the order of precedence used to report it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "state", rename_all = "camelCase")]
+pub enum SyncPhase<Number> {
+	/// Downloading the history of a warp synced chain, once the tip has been reached.
+	BlockHistory {
+		/// Number of the highest block of the history still to download.
+		number: Number,
+	},
+	/// Warp sync is waiting for its target block.
+	AwaitingTargetBlock,
+	/// Warp syncing to the tip of the chain.
+	#[serde(rename_all = "camelCase")]
+	Warping {
+		/// Description of the warp sync phase.
+		phase: String,
+		/// Total bytes downloaded so far.
+		total_bytes: u64,
+	},
+	/// Downloading the state of a block.
+	DownloadingState {
+		/// Estimated download percentage.
+		percentage: u32,
+		/// Total state size in bytes downloaded so far.
+		size: u64,
+	},
+	/// Following the tip of the chain.
+	Idle,
+	/// Catching up with the chain.
+	Downloading {
+		/// Number of the block synced to.
+		target: Number,
+	},
+	/// All blocks are downloaded and being imported.
+	Importing {
+		/// Number of the block synced to.
+		target: Number,
+	},
+}
+
+impl<Hash, Number: Copy> NodeStatus<Hash, Number> {
+	/// Assemble the status from the chain, network and syncing information.
+	///
+	/// `bandwidth` must have been updated with `net_status` beforehand.
+	pub fn new<B>(
+		chain: &Info<B>,
+		net_status: &NetworkStatus,
+		sync_status: &SyncStatus<B>,
+		bandwidth: &BandwidthRates,
+	) -> Self
+	where
+		B: BlockT<Hash = Hash>,
+		B::Header: HeaderT<Number = Number>,
+	{
+		NodeStatus {
+			best_number: chain.best_number,
+			best_hash: chain.best_hash,
+			finalized_number: chain.finalized_number,
+			finalized_hash: chain.finalized_hash,
+			peers: sync_status.num_connected_peers,
+			sync: SyncPhase::new(sync_status),
+			queued_blocks: sync_status.queued_blocks,
+			total_bytes_inbound: net_status.total_bytes_inbound,
+			total_bytes_outbound: net_status.total_bytes_outbound,
+			bytes_inbound_per_sec: bandwidth.bytes_inbound_per_sec,
+			bytes_outbound_per_sec: bandwidth.bytes_outbound_per_sec,
+		}
+	}
+}
+
+impl<Number: Copy> SyncPhase<Number> {
+	/// Phase of the syncing described by `sync_status`.
+	pub fn new<B>(sync_status: &SyncStatus<B>) -> Self
+	where
+		B: BlockT,
+		B::Header: HeaderT<Number = Number>,
+	{
+		match (&sync_status.state, &sync_status.state_sync, &sync_status.warp_sync) {
+			// Do not report the block history download during a major sync.
+			//
+			// A node could for example have been warp synced to the tip of the chain and
+			// shutdown. At the next start we still need to download the block history, but
+			// first will sync to the tip of the chain.
+			(
+				state,
+				_,
+				Some(WarpSyncProgress { phase: WarpSyncPhase::DownloadingBlocks(n), .. }),
+			) if !state.is_major_syncing() => SyncPhase::BlockHistory { number: *n },
+			(_, _, Some(WarpSyncProgress { phase: WarpSyncPhase::AwaitingTargetBlock, .. })) =>
+				SyncPhase::AwaitingTargetBlock,
+			// Handle all phases besides the two phases we already handle above.
+			(_, _, Some(warp))
+				if !matches!(
+					warp.phase,
+					WarpSyncPhase::AwaitingTargetBlock | WarpSyncPhase::DownloadingBlocks(_)
+				) =>
+				SyncPhase::Warping { phase: warp.phase.to_string(), total_bytes: warp.total_bytes },
+			(_, Some(state), _) =>
+				SyncPhase::DownloadingState { percentage: state.percentage, size: state.size },
+			(SyncState::Idle, _, _) => SyncPhase::Idle,
+			(SyncState::Downloading { target }, _, _) => SyncPhase::Downloading { target: *target },
+			(SyncState::Importing { target }, _, _) => SyncPhase::Importing { target: *target },
+		}
+	}
+}
+
+/// Average transfer rates of the node, computed from successive samples of the byte totals.
+#[derive(Debug, Clone)]
+pub struct BandwidthRates {
+	/// The last time the rates have been computed.
+	last_update: Instant,
+	/// The total of bytes received at `last_update`.
+	last_total_bytes_inbound: u64,
+	/// The total of bytes sent at `last_update`.
+	last_total_bytes_outbound: u64,
+	/// Average number of bytes received per second.
+	bytes_inbound_per_sec: u64,
+	/// Average number of bytes sent per second.
+	bytes_outbound_per_sec: u64,
+}
+
+impl BandwidthRates {
+	/// Samples taken less than this after the previous one keep the previous rates, so that
+	/// frequent callers don't get rates computed over a meaningless interval.
+	const MIN_INTERVAL: Duration = Duration::from_secs(1);
+
+	/// Start measuring the rates, assuming no bytes were transferred so far.
+	pub fn new() -> Self {
+		BandwidthRates {
+			last_update: Instant::now(),
+			last_total_bytes_inbound: 0,
+			last_total_bytes_outbound: 0,
+			bytes_inbound_per_sec: 0,
+			bytes_outbound_per_sec: 0,
+		}
+	}
+
+	/// Update the rates with the byte totals of `net_status`.
+	pub fn update(&mut self, net_status: &NetworkStatus) {
+		let now = Instant::now();
+		let elapsed = now.duration_since(self.last_update);
+		if elapsed < Self::MIN_INTERVAL {
+			return
+		}
+
+		let elapsed_ms = elapsed.as_millis().max(1);
+		let rate = |total: u64, last: u64| {
+			(u128::from(total.saturating_sub(last)) * 1000 / elapsed_ms)
+				.try_into()
+				.unwrap_or(u64::MAX)
+		};
+		self.bytes_inbound_per_sec =
+			rate(net_status.total_bytes_inbound, self.last_total_bytes_inbound);
+		self.bytes_outbound_per_sec =
+			rate(net_status.total_bytes_outbound, self.last_total_bytes_outbound);
+		self.last_update = now;
+		self.last_total_bytes_inbound = net_status.total_bytes_inbound;
+		self.last_total_bytes_outbound = net_status.total_bytes_outbound;
+	}
+}
+
+impl Default for BandwidthRates {
+	fn default() -> Self {
+		Self::new()
+	}
+}
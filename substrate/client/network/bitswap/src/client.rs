@@ -0,0 +1,207 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Bitswap client, fetching content from the connected peers.
+
+use crate::{
+	content_cid,
+	schema::bitswap::{
+		message::{
+			wantlist::{Entry, WantType},
+			Wantlist,
+		},
+		Message as BitswapMessage,
+	},
+	BitswapError, LOG_TARGET, PROTOCOL_NAME,
+};
+use futures::{Stream, StreamExt};
+use libp2p_identity::PeerId;
+use log::{debug, trace};
+use parking_lot::Mutex;
+use prost::Message;
+use sc_network::{types::ProtocolName, Event, IfDisconnected, NetworkRequest};
+use std::{collections::HashMap, sync::Arc};
+
+/// Max number of peers asked for a content before giving up.
+const MAX_PEERS_PER_FETCH: usize = 8;
+
+/// Bitswap client.
+///
+/// Content is requested from the connected peers one after the other, until one of them returns
+/// it. The connected peers are learnt from the network events passed to [`BitswapClient::run`].
+#[derive(Clone)]
+pub struct BitswapClient {
+	network: Arc<dyn NetworkRequest + Send + Sync>,
+	/// Connected peers, with the number of notification substreams open with them.
+	peers: Arc<Mutex<HashMap<PeerId, usize>>>,
+}
+
+impl BitswapClient {
+	/// Create a new [`BitswapClient`].
+	pub fn new(network: Arc<dyn NetworkRequest + Send + Sync>) -> Self {
+		Self { network, peers: Default::default() }
+	}
+
+	/// Track the connected peers until the stream of network events ends.
+	pub async fn run(self, mut events: impl Stream<Item = Event> + Unpin) {
+		while let Some(event) = events.next().await {
+			match event {
+				Event::NotificationStreamOpened { remote, .. } => {
+					*self.peers.lock().entry(remote).or_default() += 1;
+				},
+				Event::NotificationStreamClosed { remote, .. } => {
+					let mut peers = self.peers.lock();
+					if let Some(streams) = peers.get_mut(&remote) {
+						*streams -= 1;
+						if *streams == 0 {
+							peers.remove(&remote);
+						}
+					}
+				},
+				_ => {},
+			}
+		}
+	}
+
+	/// Fetch the content with the given 256-bit Blake2b hash. Returns `None` if none of the asked
+	/// peers has it.
+	pub async fn fetch(&self, hash: [u8; 32]) -> Option<Vec<u8>> {
+		let cid = content_cid(hash);
+		let request = BitswapMessage {
+			wantlist: Some(Wantlist {
+				entries: vec![Entry {
+					block: cid.to_bytes(),
+					want_type: WantType::Block as i32,
+					send_dont_have: true,
+					..Default::default()
+				}],
+				full: false,
+			}),
+			..Default::default()
+		}
+		.encode_to_vec();
+
+		let peers = self.peers.lock().keys().take(MAX_PEERS_PER_FETCH).copied().collect::<Vec<_>>();
+		for peer in peers {
+			match self.request(peer, request.clone(), &hash).await {
+				Ok(Some(content)) => {
+					trace!(target: LOG_TARGET, "Fetched {cid} from {peer}");
+					return Some(content)
+				},
+				Ok(None) => trace!(target: LOG_TARGET, "{peer} doesn't have {cid}"),
+				Err(e) => debug!(target: LOG_TARGET, "Failed to fetch {cid} from {peer}: {e}"),
+			}
+		}
+		None
+	}
+
+	/// Send the wantlist to `peer` and return the block of the response matching `hash`.
+	async fn request(
+		&self,
+		peer: PeerId,
+		request: Vec<u8>,
+		hash: &[u8; 32],
+	) -> Result<Option<Vec<u8>>, BitswapError> {
+		let response = self
+			.network
+			.request(
+				peer,
+				ProtocolName::from(PROTOCOL_NAME),
+				request,
+				IfDisconnected::ImmediateError,
+			)
+			.await?;
+		let response = BitswapMessage::decode(&response[..])?;
+		Ok(response
+			.payload
+			.into_iter()
+			.map(|block| block.data)
+			.find(|data| sp_core::hashing::blake2_256(data) == *hash))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::BitswapRequestHandler;
+	use futures::channel::oneshot;
+	use sc_client_api::Backend as _;
+	use sc_network::{
+		request_responses::{IncomingRequest, OutgoingResponse},
+		RequestFailure,
+	};
+	use substrate_test_runtime_client::{prelude::*, TestClientBuilder};
+
+	/// Network passing the requests to a bitswap request handler.
+	struct TestNetwork(async_channel::Sender<IncomingRequest>);
+
+	#[async_trait::async_trait]
+	impl NetworkRequest for TestNetwork {
+		async fn request(
+			&self,
+			target: PeerId,
+			_protocol: ProtocolName,
+			request: Vec<u8>,
+			_connect: IfDisconnected,
+		) -> Result<Vec<u8>, RequestFailure> {
+			let (tx, rx) = oneshot::channel();
+			self.0
+				.send(IncomingRequest { peer: target, payload: request, pending_response: tx })
+				.await
+				.map_err(|_| RequestFailure::NotConnected)?;
+			let OutgoingResponse { result, .. } = rx.await.map_err(|_| RequestFailure::Refused)?;
+			result.map_err(|()| RequestFailure::Refused)
+		}
+
+		fn start_request(
+			&self,
+			_target: PeerId,
+			_protocol: ProtocolName,
+			_request: Vec<u8>,
+			tx: oneshot::Sender<Result<Vec<u8>, RequestFailure>>,
+			_connect: IfDisconnected,
+		) {
+			let _ = tx.send(Err(RequestFailure::Refused));
+		}
+	}
+
+	#[tokio::test]
+	async fn pinned_content_is_fetched() {
+		let (client, backend) = TestClientBuilder::with_tx_storage(u32::MAX).build_with_backend();
+		let hash = backend.content_store().unwrap().pin(vec![0x13, 0x37]).unwrap();
+
+		let (handler, config) = BitswapRequestHandler::new(Arc::new(client));
+		tokio::spawn(handler.run());
+
+		let bitswap = BitswapClient::new(Arc::new(TestNetwork(config.inbound_queue.unwrap())));
+		let peer = PeerId::random();
+		let (mut events_tx, events_rx) = futures::channel::mpsc::unbounded();
+		events_tx
+			.start_send(Event::NotificationStreamOpened {
+				remote: peer,
+				protocol: "/test/1".into(),
+				negotiated_fallback: None,
+				role: sc_network::ObservedRole::Full,
+				received_handshake: vec![],
+			})
+			.unwrap();
+		drop(events_tx);
+		bitswap.clone().run(events_rx).await;
+
+		assert_eq!(bitswap.fetch(hash.into()).await, Some(vec![0x13, 0x37]));
+		assert_eq!(bitswap.fetch([0; 32]).await, None);
+	}
+}
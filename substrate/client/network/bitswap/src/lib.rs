@@ -14,13 +14,13 @@
 // You should have received a copy of the GNU General Public License
 // along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
 
-//! Bitswap server for Substrate.
+//! Bitswap server and client for Substrate.
 //!
-//! Allows querying transactions by hash over standard bitswap protocol
+//! Allows querying transactions and pinned content by hash over standard bitswap protocol
 //! Only supports bitswap 1.2.0.
 //! CID is expected to reference 256-bit Blake2b transaction hash.
 
-use cid::{self, Version};
+use cid::{self, multihash::Code, Version};
 use futures::StreamExt;
 use libp2p_identity::PeerId;
 use log::{debug, error, trace};
@@ -38,8 +38,12 @@ use sp_runtime::traits::Block as BlockT;
 use std::{io, sync::Arc, time::Duration};
 use unsigned_varint::encode as varint_encode;
 
+mod client;
 mod schema;
 
+pub use cid::Cid;
+pub use client::BitswapClient;
+
 const LOG_TARGET: &str = "bitswap";
 
 // Undocumented, but according to JS the bitswap messages have a max size of 512*1024 bytes
@@ -57,6 +61,28 @@ const MAX_WANTED_BLOCKS: usize = 16;
 /// Bitswap protocol name
 const PROTOCOL_NAME: &'static str = "/ipfs/bitswap/1.2.0";
 
+/// Multicodec of raw binary content, used in the CIDs of the content added locally.
+const RAW_CODEC: u64 = 0x55;
+
+/// CID of the content with the given 256-bit Blake2b hash.
+pub fn content_cid(hash: [u8; 32]) -> Cid {
+	let multihash = cid::multihash::Multihash::wrap(u64::from(Code::Blake2b256), &hash)
+		.expect("A 32 bytes digest fits in a multihash; qed");
+	Cid::new_v1(RAW_CODEC, multihash)
+}
+
+/// 256-bit Blake2b hash of the content referenced by `cid`, or `None` if the CID is not a version
+/// 1 CID of such a hash.
+pub fn content_hash(cid: &Cid) -> Option<[u8; 32]> {
+	if cid.version() != Version::V1 ||
+		cid.hash().code() != u64::from(Code::Blake2b256) ||
+		cid.hash().size() != 32
+	{
+		return None
+	}
+	cid.hash().digest().try_into().ok()
+}
+
 /// Prefix represents all metadata of a CID, without the actual content.
 #[derive(PartialEq, Eq, Clone, Debug)]
 struct Prefix {
@@ -194,16 +220,13 @@ impl<B: BlockT> BitswapRequestHandler<B> {
 				},
 			};
 
-			if cid.version() != cid::Version::V1 ||
-				cid.hash().code() != u64::from(cid::multihash::Code::Blake2b256) ||
-				cid.hash().size() != 32
-			{
+			let Some(content_hash) = content_hash(&cid) else {
 				debug!(target: LOG_TARGET, "Ignoring unsupported CID {}: {}", peer, cid);
 				continue
-			}
+			};
 
 			let mut hash = B::Hash::default();
-			hash.as_mut().copy_from_slice(&cid.hash().digest()[0..32]);
+			hash.as_mut().copy_from_slice(&content_hash);
 			let transaction = match self.client.indexed_transaction(hash) {
 				Ok(ex) => ex,
 				Err(e) => {
@@ -284,6 +307,10 @@ pub enum BitswapError {
 	/// Too many blocks requested.
 	#[error("Too many block entries in the request.")]
 	TooManyEntries,
+
+	/// Request to a peer failed.
+	#[error(transparent)]
+	Request(#[from] sc_network::RequestFailure),
 }
 
 #[cfg(test)]
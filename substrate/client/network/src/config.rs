@@ -24,6 +24,7 @@
 pub use crate::{
 	discovery::DEFAULT_KADEMLIA_REPLICATION_FACTOR,
 	protocol::NotificationsSink,
+	quota::BandwidthQuota,
 	request_responses::{
 		IncomingRequest, OutgoingResponse, ProtocolConfig as RequestResponseConfig,
 	},
@@ -567,6 +568,15 @@ pub struct NetworkConfiguration {
 	/// Initial syncing mode.
 	pub sync_mode: SyncMode,
 
+	/// Lowest block of the history downloaded after a warp sync. `None` to download it down to
+	/// genesis.
+	pub backfill_floor: Option<u64>,
+
+	/// Bandwidth quotas of the protocols whose name ends with the given suffix, e.g. `/state/2`.
+	///
+	/// The first matching suffix applies. The protocols without quota are not throttled.
+	pub bandwidth_quotas: Vec<(String, BandwidthQuota)>,
+
 	/// True if Kademlia random discovery should be enabled.
 	///
 	/// If true, the node will automatically randomly walk the DHT in order to find new peers.
@@ -585,7 +595,8 @@ pub struct NetworkConfiguration {
 	/// `kademlia_replication_factor` peers to consider record successfully put.
 	pub kademlia_replication_factor: NonZeroUsize,
 
-	/// Enable serving block data over IPFS bitswap.
+	/// Enable serving block data and pinned content over IPFS bitswap, and fetching content from
+	/// the peers.
 	pub ipfs_server: bool,
 
 	/// Size of Yamux receive window of all substreams. `None` for the default (256kiB).
@@ -633,6 +644,8 @@ impl NetworkConfiguration {
 			max_parallel_downloads: 5,
 			max_blocks_per_request: 64,
 			sync_mode: SyncMode::Full,
+			backfill_floor: None,
+			bandwidth_quotas: Vec::new(),
 			enable_dht_random_walk: true,
 			allow_non_globals_in_dht: false,
 			kademlia_disjoint_query_paths: false,
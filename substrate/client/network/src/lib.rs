@@ -257,6 +257,7 @@ pub mod network_state;
 pub mod peer_info;
 pub mod peer_store;
 pub mod protocol_controller;
+pub mod quota;
 pub mod request_responses;
 pub mod transport;
 pub mod types;
@@ -18,18 +18,31 @@
 
 //! [`PeerStore`] manages peer reputations and provides connection candidates to
 //! [`crate::protocol_controller::ProtocolController`].
+//!
+//! Besides the reputations, it keeps the list of peers and IP ranges banned by the node operator.
+//! Both can be persisted to disk, see [`PeerStore::load`].
 
+use ip_network::IpNetwork;
 use libp2p::PeerId;
-use log::trace;
+use log::{trace, warn};
 use parking_lot::Mutex;
 use partial_sort::PartialSort;
 use sc_network_common::types::ReputationChange;
+use serde::{Deserialize, Serialize};
 use std::{
 	cmp::{Ord, Ordering, PartialOrd},
 	collections::{hash_map::Entry, HashMap, HashSet},
-	fmt::Debug,
-	sync::Arc,
-	time::{Duration, Instant},
+	fmt::{self, Debug},
+	fs, io,
+	net::IpAddr,
+	path::PathBuf,
+	str::FromStr,
+	sync::{
+		atomic::{AtomicBool, Ordering as AtomicOrdering},
+		Arc,
+	},
+	thread::{self, JoinHandle},
+	time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 use wasm_timer::Delay;
 
@@ -51,6 +64,8 @@ const INVERSE_DECREMENT: i32 = 50;
 /// Amount of time between the moment we last updated the [`PeerStore`] entry and the moment we
 /// remove it, once the reputation value reaches 0.
 const FORGET_AFTER: Duration = Duration::from_secs(3600);
+/// Interval at which the state of a persisted [`PeerStore`] is saved.
+const PERSIST_INTERVAL: Duration = Duration::from_secs(60);
 
 /// Trait providing peer reputation management and connection candidates.
 pub trait PeerStoreProvider: Debug + Send {
@@ -118,6 +133,98 @@ impl PeerStoreHandle {
 	pub fn add_known_peer(&mut self, peer_id: PeerId) {
 		self.inner.lock().add_known_peer(peer_id);
 	}
+
+	/// Ban a peer or a range of IP addresses for `duration`, or permanently if `None` or if the
+	/// expiration time can't be represented.
+	///
+	/// A banned peer is disconnected from all protocols, and new connections from and to the
+	/// banned peers or IP addresses are refused by the transport. Use
+	/// [`crate::NetworkService::ban`] to also close the established connections of a banned
+	/// peer. Connections already established from a banned IP range are kept.
+	pub fn ban(&self, target: BanTarget, duration: Option<Duration>) {
+		self.inner.lock().ban(target, duration);
+	}
+
+	/// Lift a ban set with [`Self::ban`]. Returns `false` if `target` was not banned.
+	pub fn unban(&self, target: &BanTarget) -> bool {
+		self.inner.lock().unban(target)
+	}
+
+	/// Get the bans set with [`Self::ban`] that have not expired yet.
+	pub fn bans(&self) -> Vec<Ban> {
+		self.inner
+			.lock()
+			.bans
+			.iter()
+			.map(|(target, expires)| Ban { target: *target, expires: *expires })
+			.collect()
+	}
+
+	/// Check whether the peer is banned with [`Self::ban`], regardless of its reputation.
+	pub fn has_peer_ban(&self, peer_id: &PeerId) -> bool {
+		self.inner.lock().bans.contains_key(&BanTarget::Peer(*peer_id))
+	}
+
+	/// Check whether the IP address is in a range banned with [`Self::ban`].
+	pub fn has_ip_ban(&self, ip: &IpAddr) -> bool {
+		self.inner.lock().bans.keys().any(|target| target.contains_ip(ip))
+	}
+}
+
+/// Peer or range of IP addresses banned with [`PeerStoreHandle::ban`].
+///
+/// Parsed from a base58 `PeerId`, an IP address or an IP range in CIDR notation, e.g.
+/// `192.0.2.0/24`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BanTarget {
+	/// Single peer.
+	Peer(PeerId),
+	/// All the peers connecting from or dialed at an address of the range.
+	IpRange(IpNetwork),
+}
+
+impl BanTarget {
+	fn contains_ip(&self, ip: &IpAddr) -> bool {
+		match self {
+			Self::Peer(_) => false,
+			Self::IpRange(range) => range.contains(*ip),
+		}
+	}
+}
+
+impl FromStr for BanTarget {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		if let Ok(peer_id) = s.parse::<PeerId>() {
+			return Ok(Self::Peer(peer_id))
+		}
+		if let Ok(ip) = s.parse::<IpAddr>() {
+			let netmask = if ip.is_ipv4() { 32 } else { 128 };
+			return IpNetwork::new(ip, netmask).map(Self::IpRange).map_err(|e| e.to_string())
+		}
+		s.parse::<IpNetwork>()
+			.map(Self::IpRange)
+			.map_err(|e| format!("Expected a PeerId, an IP address or an IP range: {}", e))
+	}
+}
+
+impl fmt::Display for BanTarget {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Peer(peer_id) => write!(f, "{}", peer_id),
+			Self::IpRange(range) => write!(f, "{}", range),
+		}
+	}
+}
+
+/// Ban set with [`PeerStoreHandle::ban`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ban {
+	/// Banned peer or IP range.
+	pub target: BanTarget,
+	/// Time at which the ban is lifted, `None` if it is permanent.
+	pub expires: Option<SystemTime>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -187,15 +294,36 @@ impl PeerInfo {
 	}
 }
 
+/// State of a [`PeerStore`] saved to disk.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PersistedState {
+	/// Seconds since the UNIX epoch at which the state was saved.
+	saved_at: u64,
+	/// Non-zero reputations, by base58 `PeerId`.
+	reputations: Vec<(String, i32)>,
+	/// Ban targets, with the seconds since the UNIX epoch at which the bans expire.
+	bans: Vec<(String, Option<u64>)>,
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+	time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
 #[derive(Debug)]
 struct PeerStoreInner {
 	peers: HashMap<PeerId, PeerInfo>,
 	protocols: Vec<ProtocolHandle>,
+	/// Bans set by the node operator, with their expiration time.
+	bans: HashMap<BanTarget, Option<SystemTime>>,
+	/// Whether `bans` changed since the state was last persisted.
+	bans_changed: bool,
 }
 
 impl PeerStoreInner {
 	fn is_banned(&self, peer_id: &PeerId) -> bool {
-		self.peers.get(peer_id).map_or(false, |info| info.is_banned())
+		self.peers.get(peer_id).map_or(false, |info| info.is_banned()) ||
+			self.bans.contains_key(&BanTarget::Peer(*peer_id))
 	}
 
 	fn register_protocol(&mut self, protocol_handle: ProtocolHandle) {
@@ -251,7 +379,8 @@ impl PeerStoreInner {
 			.peers
 			.iter()
 			.filter_map(|(peer_id, info)| {
-				(!info.is_banned() && !ignored.contains(peer_id)).then_some((*peer_id, *info))
+				(!self.is_banned(peer_id) && !ignored.contains(peer_id))
+					.then_some((*peer_id, *info))
 			})
 			.collect::<Vec<_>>();
 		let count = std::cmp::min(count, candidates.len());
@@ -275,6 +404,72 @@ impl PeerStoreInner {
 		let now = Instant::now();
 		self.peers
 			.retain(|_, info| info.reputation != 0 || info.last_updated + FORGET_AFTER > now);
+
+		let now = SystemTime::now();
+		let bans = self.bans.len();
+		self.bans.retain(|_, expires| expires.map_or(true, |expires| expires > now));
+		self.bans_changed |= self.bans.len() != bans;
+	}
+
+	fn ban(&mut self, target: BanTarget, duration: Option<Duration>) {
+		// A duration too large to be represented is a permanent ban.
+		let expires = duration.and_then(|duration| SystemTime::now().checked_add(duration));
+		self.bans.insert(target, expires);
+		self.bans_changed = true;
+
+		if let BanTarget::Peer(peer_id) = target {
+			self.protocols.iter().for_each(|handle| handle.disconnect_peer(peer_id));
+		}
+
+		log::info!(target: LOG_TARGET, "Banned {} for {:?}.", target, duration);
+	}
+
+	fn unban(&mut self, target: &BanTarget) -> bool {
+		let unbanned = self.bans.remove(target).is_some();
+		self.bans_changed |= unbanned;
+		unbanned
+	}
+
+	fn persisted_state(&self) -> PersistedState {
+		PersistedState {
+			saved_at: unix_secs(SystemTime::now()),
+			reputations: self
+				.peers
+				.iter()
+				.filter(|(_, info)| info.reputation != 0)
+				.map(|(peer_id, info)| (peer_id.to_base58(), info.reputation))
+				.collect(),
+			bans: self
+				.bans
+				.iter()
+				.map(|(target, expires)| (target.to_string(), expires.map(unix_secs)))
+				.collect(),
+		}
+	}
+
+	/// Restore a persisted state, decaying the reputations for the time elapsed since it was
+	/// saved.
+	fn restore(&mut self, state: PersistedState) {
+		let now = SystemTime::now();
+		let seconds_passed = unix_secs(now).saturating_sub(state.saved_at);
+
+		for (peer_id, reputation) in state.reputations {
+			let Ok(peer_id) = peer_id.parse::<PeerId>() else { continue };
+			let mut info = PeerInfo { reputation, last_updated: Instant::now() };
+			info.decay_reputation(seconds_passed);
+			if info.reputation != 0 {
+				self.peers.insert(peer_id, info);
+			}
+		}
+
+		for (target, expires) in state.bans {
+			let Ok(target) = target.parse::<BanTarget>() else { continue };
+			let expires =
+				expires.and_then(|secs| UNIX_EPOCH.checked_add(Duration::from_secs(secs)));
+			if expires.map_or(true, |expires| expires > now) {
+				self.bans.insert(target, expires);
+			}
+		}
 	}
 
 	fn add_known_peer(&mut self, peer_id: PeerId) {
@@ -298,6 +493,10 @@ impl PeerStoreInner {
 #[derive(Debug)]
 pub struct PeerStore {
 	inner: Arc<Mutex<PeerStoreInner>>,
+	/// File the state is persisted to, if any.
+	persisted_path: Option<PathBuf>,
+	/// Whether the state is being written to `persisted_path`.
+	persisting: Arc<AtomicBool>,
 }
 
 impl PeerStore {
@@ -310,19 +509,80 @@ impl PeerStore {
 					.map(|peer_id| (peer_id, PeerInfo::default()))
 					.collect(),
 				protocols: Vec::new(),
+				bans: HashMap::new(),
+				bans_changed: false,
 			})),
+			persisted_path: None,
+			persisting: Arc::new(AtomicBool::new(false)),
 		}
 	}
 
+	/// Create a new peer store from the list of bootnodes and the reputations and bans persisted
+	/// to `path` by a previous run, if any.
+	///
+	/// The state is saved back to `path` periodically while the peer store is running.
+	pub fn load(bootnodes: Vec<PeerId>, path: PathBuf) -> Self {
+		let mut peer_store = Self::new(bootnodes);
+
+		match fs::read(&path) {
+			Ok(data) => match serde_json::from_slice::<PersistedState>(&data) {
+				Ok(state) => peer_store.inner.lock().restore(state),
+				Err(e) =>
+					warn!(target: LOG_TARGET, "Failed to decode peer store {:?}: {}", path, e),
+			},
+			Err(e) if e.kind() == io::ErrorKind::NotFound => {},
+			Err(e) => warn!(target: LOG_TARGET, "Failed to read peer store {:?}: {}", path, e),
+		}
+
+		peer_store.persisted_path = Some(path);
+		peer_store
+	}
+
 	/// Get `PeerStoreHandle`.
 	pub fn handle(&self) -> PeerStoreHandle {
 		PeerStoreHandle { inner: self.inner.clone() }
 	}
 
+	/// Save the state to the file given to [`Self::load`].
+	///
+	/// The file is written on a dedicated thread, whose handle is returned. Nothing is done if
+	/// the previous state is still being written.
+	fn persist(&self) -> Option<JoinHandle<()>> {
+		let path = self.persisted_path.clone()?;
+		if self.persisting.swap(true, AtomicOrdering::AcqRel) {
+			return None
+		}
+
+		let state = {
+			let mut inner = self.inner.lock();
+			inner.bans_changed = false;
+			inner.persisted_state()
+		};
+		let persisting = self.persisting.clone();
+		let write = move || {
+			let data = serde_json::to_vec(&state).expect("Serializing to memory can't fail; qed");
+			let tmp_path = path.with_extension("tmp");
+			if let Err(e) = fs::write(&tmp_path, data).and_then(|_| fs::rename(&tmp_path, &path)) {
+				warn!(target: LOG_TARGET, "Failed to persist peer store to {:?}: {}", path, e);
+			}
+			persisting.store(false, AtomicOrdering::Release);
+		};
+
+		match thread::Builder::new().name("peer-store-persist".into()).spawn(write) {
+			Ok(handle) => Some(handle),
+			Err(e) => {
+				warn!(target: LOG_TARGET, "Failed to spawn the peer store persisting thread: {}", e);
+				self.persisting.store(false, AtomicOrdering::Release);
+				None
+			},
+		}
+	}
+
 	/// Drive the `PeerStore`, decaying reputation values over time and removing expired entries.
 	pub async fn run(self) {
 		let started = Instant::now();
 		let mut latest_time_update = started;
+		let mut latest_persist = started;
 
 		loop {
 			let now = Instant::now();
@@ -336,6 +596,14 @@ impl PeerStore {
 			};
 
 			self.inner.lock().progress_time(seconds_passed);
+
+			if self.persisted_path.is_some() &&
+				(now - latest_persist >= PERSIST_INTERVAL || self.inner.lock().bans_changed) &&
+				self.persist().is_some()
+			{
+				latest_persist = now;
+			}
+
 			let _ = Delay::new(Duration::from_secs(1)).await;
 		}
 	}
@@ -343,7 +611,9 @@ impl PeerStore {
 
 #[cfg(test)]
 mod tests {
-	use super::PeerInfo;
+	use super::{BanTarget, PeerInfo, PeerStore, PeerStoreProvider, BANNED_THRESHOLD};
+	use sc_network_common::types::ReputationChange;
+	use std::time::Duration;
 
 	#[test]
 	fn decaying_zero_reputation_yields_zero() {
@@ -410,4 +680,81 @@ mod tests {
 		peer_info.decay_reputation(SECONDS / 2);
 		assert_eq!(peer_info.reputation, 0);
 	}
+
+	#[test]
+	fn ban_targets_are_parsed() {
+		let peer_id = libp2p::PeerId::random();
+		assert_eq!(peer_id.to_base58().parse::<BanTarget>(), Ok(BanTarget::Peer(peer_id)));
+
+		let ip = "192.0.2.1".parse::<BanTarget>().unwrap();
+		assert_eq!(ip.to_string(), "192.0.2.1/32");
+		let range = "2001:db8::/32".parse::<BanTarget>().unwrap();
+		assert!(range.contains_ip(&"2001:db8::1".parse().unwrap()));
+		assert!(!range.contains_ip(&"2001:db9::1".parse().unwrap()));
+
+		assert!("192.0.2.1/24".parse::<BanTarget>().is_err());
+		assert!("not a peer".parse::<BanTarget>().is_err());
+	}
+
+	#[test]
+	fn bans_are_enforced_and_lifted() {
+		let peer_store = PeerStore::new(Vec::new());
+		let mut handle = peer_store.handle();
+		let (banned, other) = (libp2p::PeerId::random(), libp2p::PeerId::random());
+		handle.add_known_peer(banned);
+		handle.add_known_peer(other);
+
+		handle.ban(BanTarget::Peer(banned), None);
+		handle.ban("10.0.0.0/8".parse().unwrap(), Some(Duration::from_secs(60)));
+		assert!(handle.is_banned(&banned));
+		assert!(handle.has_peer_ban(&banned));
+		assert!(!handle.is_banned(&other));
+		assert!(handle.has_ip_ban(&"10.1.2.3".parse().unwrap()));
+		assert!(!handle.has_ip_ban(&"11.1.2.3".parse().unwrap()));
+		assert_eq!(handle.outgoing_candidates(10, Default::default()), vec![other]);
+		assert_eq!(handle.bans().len(), 2);
+
+		assert!(handle.unban(&BanTarget::Peer(banned)));
+		assert!(!handle.unban(&BanTarget::Peer(banned)));
+		assert!(!handle.is_banned(&banned));
+		assert_eq!(handle.bans().len(), 1);
+
+		// A duration overflowing the expiration time bans permanently.
+		handle.ban(BanTarget::Peer(banned), Some(Duration::MAX));
+		assert!(handle.is_banned(&banned));
+		assert!(handle
+			.bans()
+			.contains(&super::Ban { target: BanTarget::Peer(banned), expires: None }));
+	}
+
+	#[test]
+	fn persisted_state_is_restored_with_decay() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("peer_store.json");
+		let (reported, banned) = (libp2p::PeerId::random(), libp2p::PeerId::random());
+
+		let peer_store = PeerStore::load(Vec::new(), path.clone());
+		let mut handle = peer_store.handle();
+		handle.report_peer(reported, ReputationChange::new(BANNED_THRESHOLD - 1000, "test"));
+		handle.ban(BanTarget::Peer(banned), Some(Duration::from_secs(3600)));
+		handle.ban("192.0.2.0/24".parse().unwrap(), Some(Duration::from_secs(5)));
+		peer_store.persist().unwrap().join().unwrap();
+
+		// Pretend the state was saved ten seconds ago, so that the IP range ban expired since.
+		let mut state: super::PersistedState =
+			serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+		state.saved_at -= 10;
+		for (_, expires) in &mut state.bans {
+			*expires = expires.map(|expires| expires - 10);
+		}
+		std::fs::write(&path, serde_json::to_vec(&state).unwrap()).unwrap();
+
+		let handle = PeerStore::load(Vec::new(), path).handle();
+		let reputation = handle.peer_reputation(&reported);
+		assert!(reputation < 0);
+		assert!(reputation > BANNED_THRESHOLD - 1000);
+		assert!(handle.has_peer_ban(&banned));
+		// The IP range ban expired in the meantime.
+		assert_eq!(handle.bans().len(), 1);
+	}
 }
@@ -0,0 +1,392 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Bandwidth quotas of the notification and request-response protocols.
+//!
+//! A quota caps the bytes per second sent and received on a protocol, in total and per peer.
+//! Protocols without quota, typically GRANDPA and the parachain protocols, are never throttled, so
+//! that they keep the bandwidth the capped protocols leave.
+//!
+//! Every quota is a bucket allowed to hold one second worth of traffic. Depending on the traffic,
+//! the data exceeding it is either refused, or accounted for in advance and the caller is told how
+//! long to wait before sending it. Data is accepted as long as the bucket isn't full, even if it
+//! overflows it, so that messages larger than one second worth of traffic still go through.
+//! Notifications exceeding a quota are discarded and counted by the
+//! `substrate_sub_libp2p_notifications_quota_exceeded_total` metric.
+
+use crate::types::ProtocolName;
+
+use libp2p::PeerId;
+use parking_lot::Mutex;
+use std::{
+	collections::{hash_map::Entry, BTreeMap, HashMap},
+	str::FromStr,
+	sync::Arc,
+	time::{Duration, Instant},
+};
+
+/// Traffic allowed above the rate of a quota, as a duration at that rate.
+const BURST: Duration = Duration::from_secs(1);
+
+/// Bandwidth caps of a protocol, in bytes per second. `None` for no cap.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BandwidthQuota {
+	/// Bytes sent per second, to all the peers.
+	pub upload: Option<u64>,
+	/// Bytes received per second, from all the peers.
+	pub download: Option<u64>,
+	/// Bytes sent per second to a single peer.
+	pub upload_per_peer: Option<u64>,
+	/// Bytes received per second from a single peer.
+	pub download_per_peer: Option<u64>,
+}
+
+impl BandwidthQuota {
+	fn rate(&self, direction: Direction, per_peer: bool) -> Option<u64> {
+		match (direction, per_peer) {
+			(Direction::Upload, false) => self.upload,
+			(Direction::Download, false) => self.download,
+			(Direction::Upload, true) => self.upload_per_peer,
+			(Direction::Download, true) => self.download_per_peer,
+		}
+	}
+}
+
+/// Parses a comma-separated list of `<cap>:<KiB per second>`, where `<cap>` is one of `up`,
+/// `down`, `peer-up` and `peer-down`, e.g. `up:4096,peer-up:512`.
+impl FromStr for BandwidthQuota {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut quota = Self::default();
+		for cap in s.split(',') {
+			let (name, rate) = cap
+				.split_once(':')
+				.ok_or_else(|| format!("Expected `<cap>:<KiB per second>`, got `{}`", cap))?;
+			let rate = rate
+				.trim()
+				.parse::<u64>()
+				.map_err(|e| format!("Invalid rate `{}`: {}", rate, e))?
+				.saturating_mul(1024);
+			let field = match name.trim() {
+				"up" => &mut quota.upload,
+				"down" => &mut quota.download,
+				"peer-up" => &mut quota.upload_per_peer,
+				"peer-down" => &mut quota.download_per_peer,
+				name =>
+					return Err(format!(
+						"Unknown cap `{}`, expected `up`, `down`, `peer-up` or `peer-down`",
+						name
+					)),
+			};
+			*field = Some(rate);
+		}
+		Ok(quota)
+	}
+}
+
+/// Direction of the traffic accounted for in a [`BandwidthQuotas`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Direction {
+	/// Data sent to the peers.
+	Upload,
+	/// Data received from the peers.
+	Download,
+}
+
+/// Traffic accounted for in a quota.
+#[derive(Debug)]
+struct Bucket {
+	/// Time at which all the traffic accounted for is drained at the rate of the quota.
+	drained_at: Instant,
+	/// Identifies the bucket among the ones drained at the same time.
+	id: u64,
+}
+
+impl Bucket {
+	/// Time to wait before the bucket is back under its burst allowance.
+	fn delay(&self, now: Instant) -> Duration {
+		self.drained_at.saturating_duration_since(now).saturating_sub(BURST)
+	}
+
+	/// Time at which the traffic is drained once `bytes` are accounted for.
+	fn drained_after(&self, now: Instant, rate: u64, bytes: usize) -> Instant {
+		let transfer = Duration::from_secs_f64(bytes as f64 / rate.max(1) as f64);
+		std::cmp::max(self.drained_at, now) + transfer
+	}
+
+	/// Whether the traffic accounted for is below the burst allowance.
+	fn has_room(&self, now: Instant) -> bool {
+		self.drained_at.saturating_duration_since(now) < BURST
+	}
+
+	/// Account for `bytes` and return the time to wait before they fit in the burst allowance.
+	fn reserve(&mut self, now: Instant, rate: u64, bytes: usize) -> Duration {
+		self.drained_at = self.drained_after(now, rate, bytes);
+		self.delay(now)
+	}
+}
+
+/// The protocol, peer if the quota is per peer, and direction of the traffic in a bucket.
+type BucketKey = (ProtocolName, Option<PeerId>, Direction);
+
+#[derive(Debug, Default)]
+struct QuotasInner {
+	/// Quota of each protocol seen so far, resolved from the configured suffixes.
+	protocols: HashMap<ProtocolName, Option<BandwidthQuota>>,
+	/// Buckets of the protocols, and of the peers on the protocols with a per peer quota.
+	buckets: HashMap<BucketKey, Bucket>,
+	/// The buckets by the time they are drained at, after which they are dropped.
+	drained_at: BTreeMap<(Instant, u64), BucketKey>,
+	/// Identifier of the next bucket.
+	next_id: u64,
+}
+
+impl QuotasInner {
+	/// Drop the buckets whose traffic is drained by `now`, which are the same as empty ones.
+	fn drop_drained(&mut self, now: Instant) {
+		while let Some(&(drained_at, id)) = self.drained_at.keys().next() {
+			if drained_at > now {
+				break
+			}
+			if let Some(key) = self.drained_at.remove(&(drained_at, id)) {
+				self.buckets.remove(&key);
+			}
+		}
+	}
+
+	/// Apply `f` to the bucket of `key`, created empty if there is none.
+	fn with_bucket(
+		&mut self,
+		key: BucketKey,
+		now: Instant,
+		f: impl FnOnce(&mut Bucket) -> Duration,
+	) -> Duration {
+		let bucket = match self.buckets.entry(key.clone()) {
+			Entry::Occupied(entry) => entry.into_mut(),
+			Entry::Vacant(entry) => {
+				let id = self.next_id;
+				self.next_id += 1;
+				self.drained_at.insert((now, id), key.clone());
+				entry.insert(Bucket { drained_at: now, id })
+			},
+		};
+
+		let drained_at = bucket.drained_at;
+		let delay = f(bucket);
+		if bucket.drained_at != drained_at {
+			self.drained_at.remove(&(drained_at, bucket.id));
+			self.drained_at.insert((bucket.drained_at, bucket.id), key);
+		}
+		delay
+	}
+}
+
+/// Bandwidth quotas of all the protocols, shared between the components sending and receiving
+/// data.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct BandwidthQuotas {
+	/// Quotas by protocol name suffix, as configured.
+	quotas: Arc<Vec<(String, BandwidthQuota)>>,
+	inner: Arc<Mutex<QuotasInner>>,
+}
+
+impl BandwidthQuotas {
+	/// Create the quotas applying to the protocols whose name ends with the given suffixes. The
+	/// first matching suffix is used.
+	pub(crate) fn new(quotas: Vec<(String, BandwidthQuota)>) -> Self {
+		Self { quotas: Arc::new(quotas), inner: Default::default() }
+	}
+
+	/// Account for `bytes` sent to or received from `peer` and return how long to wait before
+	/// sending or processing them.
+	pub(crate) fn reserve(
+		&self,
+		protocol: &ProtocolName,
+		peer: &PeerId,
+		direction: Direction,
+		bytes: usize,
+	) -> Duration {
+		self.with_buckets(protocol, peer, direction, |now, rate, bucket| {
+			bucket.reserve(now, rate, bytes)
+		})
+	}
+
+	/// Account for `bytes` only if none of the quotas is exceeded. Returns `false`, without
+	/// accounting for anything, otherwise.
+	pub(crate) fn try_reserve(
+		&self,
+		protocol: &ProtocolName,
+		peer: &PeerId,
+		direction: Direction,
+		bytes: usize,
+	) -> bool {
+		let full = self.with_buckets(protocol, peer, direction, |now, _, bucket| {
+			if bucket.has_room(now) {
+				Duration::ZERO
+			} else {
+				bucket.delay(now).max(Duration::from_nanos(1))
+			}
+		});
+		if !full.is_zero() {
+			return false
+		}
+		self.reserve(protocol, peer, direction, bytes);
+		true
+	}
+
+	/// Time to wait before the quota allows more traffic.
+	pub(crate) fn delay(
+		&self,
+		protocol: &ProtocolName,
+		peer: &PeerId,
+		direction: Direction,
+	) -> Duration {
+		self.with_buckets(protocol, peer, direction, |now, _, bucket| bucket.delay(now))
+	}
+
+	/// Apply `f` to the total and per peer buckets that have a quota, returning the longest
+	/// delay.
+	fn with_buckets(
+		&self,
+		protocol: &ProtocolName,
+		peer: &PeerId,
+		direction: Direction,
+		mut f: impl FnMut(Instant, u64, &mut Bucket) -> Duration,
+	) -> Duration {
+		if self.quotas.is_empty() {
+			return Duration::ZERO
+		}
+
+		let mut inner = self.inner.lock();
+		let quotas = &self.quotas;
+		let Some(quota) = *inner.protocols.entry(protocol.clone()).or_insert_with(|| {
+			quotas
+				.iter()
+				.find(|(suffix, _)| protocol.ends_with(suffix.as_str()))
+				.map(|(_, quota)| *quota)
+		}) else {
+			return Duration::ZERO
+		};
+
+		let now = Instant::now();
+		inner.drop_drained(now);
+
+		[None, Some(*peer)]
+			.into_iter()
+			.filter_map(|peer| {
+				let rate = quota.rate(direction, peer.is_some())?;
+				let key = (protocol.clone(), peer, direction);
+				Some(inner.with_bucket(key, now, |bucket| f(now, rate, bucket)))
+			})
+			.max()
+			.unwrap_or_default()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn quota_is_parsed() {
+		assert_eq!(
+			"up:4,peer-down:1".parse::<BandwidthQuota>(),
+			Ok(BandwidthQuota {
+				upload: Some(4096),
+				download_per_peer: Some(1024),
+				..Default::default()
+			})
+		);
+		assert!("up".parse::<BandwidthQuota>().is_err());
+		assert!("left:4".parse::<BandwidthQuota>().is_err());
+		assert!("up:fast".parse::<BandwidthQuota>().is_err());
+	}
+
+	#[test]
+	fn traffic_above_burst_is_delayed() {
+		let quotas = BandwidthQuotas::new(vec![(
+			"/state/2".into(),
+			BandwidthQuota { upload: Some(1000), upload_per_peer: Some(500), ..Default::default() },
+		)]);
+		let state: ProtocolName = "/0123/state/2".into();
+		let grandpa: ProtocolName = "/0123/grandpa/1".into();
+		let (a, b) = (PeerId::random(), PeerId::random());
+
+		// One second worth of the per peer quota goes through right away, and the data is
+		// accepted until the quota is exceeded.
+		assert!(quotas.reserve(&state, &a, Direction::Upload, 500).is_zero());
+		assert!(quotas.try_reserve(&state, &a, Direction::Upload, 500));
+		assert!(!quotas.try_reserve(&state, &a, Direction::Upload, 1));
+		let delay = quotas.delay(&state, &a, Direction::Upload);
+		assert!(delay > Duration::from_millis(900) && delay <= Duration::from_secs(1));
+
+		// The total quota is shared by the peers.
+		assert!(quotas.reserve(&state, &b, Direction::Upload, 100).is_zero());
+		assert!(!quotas.try_reserve(&state, &b, Direction::Upload, 100));
+		let delay = quotas.delay(&state, &b, Direction::Upload);
+		assert!(!delay.is_zero() && delay <= Duration::from_millis(100));
+
+		// Downloads and protocols without quota are not throttled.
+		assert!(quotas.reserve(&state, &a, Direction::Download, 1_000_000).is_zero());
+		assert!(quotas.reserve(&grandpa, &a, Direction::Upload, 1_000_000).is_zero());
+	}
+
+	#[test]
+	fn refused_traffic_is_not_accounted_for() {
+		let quotas = BandwidthQuotas::new(vec![(
+			"/state/2".into(),
+			BandwidthQuota {
+				download: Some(1000),
+				download_per_peer: Some(100),
+				..Default::default()
+			},
+		)]);
+		let state: ProtocolName = "/0123/state/2".into();
+		let (a, b) = (PeerId::random(), PeerId::random());
+
+		// A message larger than the burst allowance goes through an empty bucket.
+		assert!(quotas.try_reserve(&state, &a, Direction::Download, 500));
+		// Then the peer is over its own quota, which doesn't take from the other peers.
+		for _ in 0..10 {
+			assert!(!quotas.try_reserve(&state, &a, Direction::Download, 500));
+		}
+		assert!(quotas.try_reserve(&state, &b, Direction::Download, 400));
+		assert!(!quotas.try_reserve(&state, &b, Direction::Download, 1));
+	}
+
+	#[test]
+	fn drained_buckets_are_dropped() {
+		let quotas = BandwidthQuotas::new(vec![(
+			"/state/2".into(),
+			BandwidthQuota { upload_per_peer: Some(1_000_000), ..Default::default() },
+		)]);
+		let state: ProtocolName = "/0123/state/2".into();
+
+		for _ in 0..100 {
+			quotas.reserve(&state, &PeerId::random(), Direction::Upload, 1);
+		}
+		let mut inner = quotas.inner.lock();
+		assert_eq!(inner.buckets.len(), 100);
+		assert_eq!(inner.drained_at.len(), 100);
+
+		inner.drop_drained(Instant::now() + Duration::from_secs(1));
+		assert!(inner.buckets.is_empty());
+		assert!(inner.drained_at.is_empty());
+	}
+}
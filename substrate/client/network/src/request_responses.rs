@@ -33,14 +33,22 @@
 //!
 //! - If provided, a ["requests processing"](ProtocolConfig::inbound_queue) channel
 //! is used to handle incoming requests.
+//!
+//! - On the protocols with a [bandwidth quota](crate::quota), the incoming requests exceeding the
+//! download quota, or arriving while the upload quota is exceeded, are refused before a response
+//! is built. The responses are accounted for in the upload quota once built. The outgoing
+//! requests are queued while the quota is exceeded and sent one at a time, or fail with a timeout
+//! if they waited longer than the request timeout of their protocol.
 
 use crate::{
 	peer_store::{PeerStoreProvider, BANNED_THRESHOLD},
+	quota::{BandwidthQuotas, Direction},
 	types::ProtocolName,
 	ReputationChange,
 };
 
 use futures::{channel::oneshot, prelude::*};
+use futures_timer::Delay;
 use libp2p::{
 	core::{Endpoint, Multiaddr},
 	request_response::{self, Behaviour, Codec, Message, ProtocolSupport, ResponseChannel},
@@ -54,7 +62,7 @@ use libp2p::{
 };
 
 use std::{
-	collections::{hash_map::Entry, HashMap},
+	collections::{hash_map::Entry, HashMap, VecDeque},
 	io, iter,
 	pin::Pin,
 	task::{Context, Poll},
@@ -282,6 +290,32 @@ pub struct RequestResponsesBehaviour {
 
 	/// Primarily used to get a reputation of a node.
 	peer_store: Box<dyn PeerStoreProvider>,
+
+	/// Request timeout of each protocol.
+	request_timeouts: HashMap<ProtocolName, Duration>,
+
+	/// Outgoing requests waiting for the download quota of their protocol, in order of arrival.
+	delayed_requests: HashMap<ProtocolName, VecDeque<DelayedRequest>>,
+
+	/// Last request sent from `delayed_requests` for each protocol. The next one is only sent
+	/// once its response has been accounted for in the quota.
+	released_requests: HashMap<ProtocolName, ProtocolRequestId>,
+
+	/// Wakes up the behaviour when a delayed request can be sent or times out.
+	delayed_requests_timer: Option<Delay>,
+
+	/// Bandwidth quotas of the protocols.
+	quotas: BandwidthQuotas,
+}
+
+/// Outgoing request delayed by the download quota of its protocol.
+struct DelayedRequest {
+	target: PeerId,
+	request: Vec<u8>,
+	pending_response: oneshot::Sender<Result<Vec<u8>, RequestFailure>>,
+	connect: IfDisconnected,
+	/// Time at which the request times out if it is still waiting.
+	deadline: Instant,
 }
 
 /// Generated by the response builder and waiting to be processed.
@@ -299,8 +333,10 @@ impl RequestResponsesBehaviour {
 	pub fn new(
 		list: impl Iterator<Item = ProtocolConfig>,
 		peer_store: Box<dyn PeerStoreProvider>,
+		quotas: BandwidthQuotas,
 	) -> Result<Self, RegisterError> {
 		let mut protocols = HashMap::new();
+		let mut request_timeouts = HashMap::new();
 		for protocol in list {
 			let mut cfg = Config::default();
 			cfg.set_connection_keep_alive(Duration::from_secs(10));
@@ -323,6 +359,7 @@ impl RequestResponsesBehaviour {
 				cfg,
 			);
 
+			request_timeouts.insert(protocol.name.clone(), protocol.request_timeout);
 			match protocols.entry(protocol.name) {
 				Entry::Vacant(e) => e.insert((rq_rp, protocol.inbound_queue)),
 				Entry::Occupied(e) => return Err(RegisterError::DuplicateProtocol(e.key().clone())),
@@ -336,6 +373,11 @@ impl RequestResponsesBehaviour {
 			pending_responses_arrival_time: Default::default(),
 			send_feedback: Default::default(),
 			peer_store,
+			request_timeouts,
+			delayed_requests: Default::default(),
+			released_requests: Default::default(),
+			delayed_requests_timer: None,
+			quotas,
 		})
 	}
 
@@ -355,14 +397,43 @@ impl RequestResponsesBehaviour {
 	) {
 		log::trace!(target: "sub-libp2p", "send request to {target} ({protocol_name:?}), {} bytes", request.len());
 
-		if let Some((protocol, _)) = self.protocols.get_mut(protocol_name) {
-			if protocol.is_connected(target) || connect.should_connect() {
-				let request_id = protocol.send_request(target, request);
-				let prev_req_id = self.pending_requests.insert(
-					(protocol_name.to_string().into(), request_id).into(),
-					(Instant::now(), pending_response),
-				);
+		let protocol: ProtocolName = protocol_name.to_string().into();
+		if let Some(timeout) = self.request_timeouts.get(&protocol) {
+			// Keep the order of the requests once some of them are delayed.
+			let queued = self.delayed_requests.get(&protocol).map_or(false, |q| !q.is_empty());
+			if queued || !self.quotas.delay(&protocol, target, Direction::Download).is_zero() {
+				self.delayed_requests.entry(protocol).or_default().push_back(DelayedRequest {
+					target: *target,
+					request,
+					pending_response,
+					connect,
+					deadline: Instant::now() + *timeout,
+				});
+				return
+			}
+		}
+
+		self.start_request(target, protocol, request, pending_response, connect);
+	}
+
+	/// Sends a request right away, returning its id if it was sent.
+	fn start_request(
+		&mut self,
+		target: &PeerId,
+		protocol: ProtocolName,
+		request: Vec<u8>,
+		pending_response: oneshot::Sender<Result<Vec<u8>, RequestFailure>>,
+		connect: IfDisconnected,
+	) -> Option<ProtocolRequestId> {
+		if let Some((behaviour, _)) = self.protocols.get_mut(&protocol) {
+			if behaviour.is_connected(target) || connect.should_connect() {
+				let request_id: ProtocolRequestId =
+					(protocol, behaviour.send_request(target, request)).into();
+				let prev_req_id = self
+					.pending_requests
+					.insert(request_id.clone(), (Instant::now(), pending_response));
 				debug_assert!(prev_req_id.is_none(), "Expect request id to be unique.");
+				return Some(request_id)
 			} else if pending_response.send(Err(RequestFailure::NotConnected)).is_err() {
 				log::debug!(
 					target: "sub-libp2p",
@@ -376,9 +447,79 @@ impl RequestResponsesBehaviour {
 				target: "sub-libp2p",
 				"Unknown protocol {:?}. At the same time local \
 				 node is no longer interested in the result.",
-				protocol_name,
+				protocol,
 			);
 		}
+		None
+	}
+
+	/// Sends the delayed requests the download quota allows, one at a time per protocol, and fails
+	/// the ones that waited longer than their request timeout.
+	fn poll_delayed_requests(&mut self, cx: &mut Context) {
+		let now = Instant::now();
+		let mut next_wake_up: Option<Instant> = None;
+		let mut ready = Vec::new();
+
+		self.released_requests
+			.retain(|_, request_id| self.pending_requests.contains_key(request_id));
+
+		for (protocol, queue) in &mut self.delayed_requests {
+			// All the requests of a protocol have the same timeout, so the oldest expire first.
+			while queue.front().map_or(false, |request| request.deadline <= now) {
+				let request = queue.pop_front().expect("Checked above; qed");
+				log::debug!(
+					target: "sub-libp2p",
+					"Request to {} ({protocol:?}) timed out waiting for the bandwidth quota",
+					request.target,
+				);
+				let _ = request
+					.pending_response
+					.send(Err(RequestFailure::Network(OutboundFailure::Timeout)));
+			}
+
+			if let Some(oldest) = queue.front() {
+				next_wake_up =
+					Some(next_wake_up.map_or(oldest.deadline, |t| t.min(oldest.deadline)));
+			}
+
+			// The response of the previous one will wake us up.
+			if self.released_requests.contains_key(protocol) {
+				continue
+			}
+
+			let mut shortest_delay: Option<Duration> = None;
+			let position = queue.iter().position(|request| {
+				let delay = self.quotas.delay(protocol, &request.target, Direction::Download);
+				shortest_delay = Some(shortest_delay.map_or(delay, |d| d.min(delay)));
+				delay.is_zero()
+			});
+			match position {
+				Some(position) => ready.push((
+					protocol.clone(),
+					queue.remove(position).expect("Position is in the queue; qed"),
+				)),
+				None =>
+					if let Some(delay) = shortest_delay {
+						let at = now + delay;
+						next_wake_up = Some(next_wake_up.map_or(at, |t| t.min(at)));
+					},
+			}
+		}
+		self.delayed_requests.retain(|_, queue| !queue.is_empty());
+
+		for (protocol, DelayedRequest { target, request, pending_response, connect, .. }) in ready {
+			if let Some(request_id) =
+				self.start_request(&target, protocol.clone(), request, pending_response, connect)
+			{
+				self.released_requests.insert(protocol, request_id);
+			}
+		}
+
+		self.delayed_requests_timer = next_wake_up.and_then(|at| {
+			let mut timer = Delay::new(at.saturating_duration_since(now));
+			// Registers the waker, `at` is always in the future.
+			timer.poll_unpin(cx).is_pending().then_some(timer)
+		});
 	}
 }
 
@@ -552,6 +693,11 @@ impl NetworkBehaviour for RequestResponsesBehaviour {
 		params: &mut impl PollParameters,
 	) -> Poll<ToSwarm<Self::OutEvent, THandlerInEvent<Self>>> {
 		'poll_all: loop {
+			// Send the outgoing requests the download quota now allows.
+			if !self.delayed_requests.is_empty() {
+				self.poll_delayed_requests(cx);
+			}
+
 			// Poll to see if any response is ready to be sent back.
 			while let Poll::Ready(Some(outcome)) = self.pending_responses.poll_next_unpin(cx) {
 				let RequestProcessingOutcome {
@@ -568,6 +714,8 @@ impl NetworkBehaviour for RequestResponsesBehaviour {
 				};
 
 				if let Ok(payload) = result {
+					// The request was only accepted if the upload quota had room for its response.
+					self.quotas.reserve(&protocol_name, &peer, Direction::Upload, payload.len());
 					if let Some((protocol, _)) = self.protocols.get_mut(&*protocol_name) {
 						log::trace!(target: "sub-libp2p", "send response to {peer} ({protocol_name:?}), {} bytes", payload.len());
 
@@ -646,6 +794,31 @@ impl NetworkBehaviour for RequestResponsesBehaviour {
 								continue 'poll_protocol
 							}
 
+							// The request is refused, rather than delayed, if it exceeds the quota
+							// of the peer or of the protocol. A peer over its own quota isn't
+							// charged to the protocol, so that it can't starve the other peers.
+							// Requests are also refused while there is no room for their response,
+							// before any work is done to build it.
+							if !self.quotas.delay(protocol, &peer, Direction::Upload).is_zero() {
+								log::debug!(
+									target: "sub-libp2p",
+									"Upload quota exceeded, refusing request from {peer} ({protocol:?})",
+								);
+								continue 'poll_protocol
+							}
+							if !self.quotas.try_reserve(
+								protocol,
+								&peer,
+								Direction::Download,
+								request.len(),
+							) {
+								log::debug!(
+									target: "sub-libp2p",
+									"Bandwidth quota exceeded, refusing request from {peer} ({protocol:?})",
+								);
+								continue 'poll_protocol
+							}
+
 							let (tx, rx) = oneshot::channel();
 
 							// Submit the request to the "response builder" passed by the user at
@@ -699,11 +872,14 @@ impl NetworkBehaviour for RequestResponsesBehaviour {
 								.remove(&(protocol.clone(), request_id).into())
 							{
 								Some((started, pending_response)) => {
+									let len =
+										response.as_ref().map_or(0usize, |response| response.len());
 									log::trace!(
 										target: "sub-libp2p",
 										"received response from {peer} ({protocol:?}), {} bytes",
-										response.as_ref().map_or(0usize, |response| response.len()),
+										len,
 									);
+									self.quotas.reserve(protocol, &peer, Direction::Download, len);
 
 									let delivered = pending_response
 										.send(response.map_err(|()| RequestFailure::Refused))
@@ -1007,7 +1183,12 @@ mod tests {
 			.multiplex(libp2p::yamux::Config::default())
 			.boxed();
 
-		let behaviour = RequestResponsesBehaviour::new(list, Box::new(MockPeerStore {})).unwrap();
+		let behaviour = RequestResponsesBehaviour::new(
+			list,
+			Box::new(MockPeerStore {}),
+			BandwidthQuotas::default(),
+		)
+		.unwrap();
 
 		let runtime = tokio::runtime::Runtime::new().unwrap();
 		let mut swarm = SwarmBuilder::with_executor(
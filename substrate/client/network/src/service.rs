@@ -36,9 +36,10 @@ use crate::{
 	network_state::{
 		NetworkState, NotConnectedPeer as NetworkStateNotConnectedPeer, Peer as NetworkStatePeer,
 	},
-	peer_store::{PeerStoreHandle, PeerStoreProvider},
+	peer_store::{Ban, BanTarget, PeerStoreHandle, PeerStoreProvider},
 	protocol::{self, NotifsHandlerError, Protocol, Ready},
 	protocol_controller::{self, ProtoSetConfig, ProtocolController, SetId},
+	quota::{BandwidthQuotas, Direction},
 	request_responses::{IfDisconnected, RequestFailure},
 	service::{
 		signature::{Signature, SigningError},
@@ -71,7 +72,7 @@ use libp2p::{
 	Multiaddr, PeerId,
 };
 use log::{debug, error, info, trace, warn};
-use metrics::{Histogram, HistogramVec, MetricSources, Metrics};
+use metrics::{CounterVec, Histogram, HistogramVec, MetricSources, Metrics, U64};
 use parking_lot::Mutex;
 
 use sc_network_common::ExHashT;
@@ -90,6 +91,7 @@ use std::{
 		atomic::{AtomicUsize, Ordering},
 		Arc,
 	},
+	time::Duration,
 };
 
 pub use behaviour::{InboundFailure, OutboundFailure, ResponseFailure};
@@ -124,6 +126,9 @@ pub struct NetworkService<B: BlockT + 'static, H: ExHashT> {
 	/// Field extracted from the [`Metrics`] struct and necessary to report the
 	/// notifications-related metrics.
 	notifications_sizes_metric: Option<HistogramVec>,
+	/// Field extracted from the [`Metrics`] struct to count the notifications discarded because
+	/// of a bandwidth quota.
+	notifications_quota_exceeded_metric: Option<CounterVec<U64>>,
 	/// Protocol name -> `SetId` mapping for notification protocols. The map never changes after
 	/// initialization.
 	notification_protocol_ids: HashMap<ProtocolName, SetId>,
@@ -132,6 +137,10 @@ pub struct NetworkService<B: BlockT + 'static, H: ExHashT> {
 	protocol_handles: Vec<protocol_controller::ProtocolHandle>,
 	/// Shortcut to sync protocol handle (`protocol_handles[0]`).
 	sync_protocol_handle: protocol_controller::ProtocolHandle,
+	/// Peer reputations and bans.
+	peer_store_handle: PeerStoreHandle,
+	/// Bandwidth quotas of the protocols, shared with the [`Behaviour`].
+	bandwidth_quotas: BandwidthQuotas,
 	/// Marker to pin the `H` generic. Serves no purpose except to not break backwards
 	/// compatibility.
 	_marker: PhantomData<H>,
@@ -265,6 +274,7 @@ where
 				config_mem,
 				network_config.yamux_window_size,
 				yamux_maximum_buffer_size,
+				params.peer_store.clone(),
 			)
 		};
 
@@ -388,6 +398,7 @@ where
 
 		let num_connected = Arc::new(AtomicUsize::new(0));
 		let external_addresses = Arc::new(Mutex::new(HashSet::new()));
+		let bandwidth_quotas = BandwidthQuotas::new(network_config.bandwidth_quotas.clone());
 
 		// Build the swarm.
 		let (mut swarm, bandwidth): (Swarm<Behaviour<B>>, _) = {
@@ -437,6 +448,7 @@ where
 					request_response_protocols,
 					params.peer_store.clone(),
 					external_addresses.clone(),
+					bandwidth_quotas.clone(),
 				);
 
 				match result {
@@ -522,9 +534,14 @@ where
 			notifications_sizes_metric: metrics
 				.as_ref()
 				.map(|metrics| metrics.notifications_sizes.clone()),
+			notifications_quota_exceeded_metric: metrics
+				.as_ref()
+				.map(|metrics| metrics.notifications_quota_exceeded_total.clone()),
 			notification_protocol_ids,
 			protocol_handles,
 			sync_protocol_handle,
+			peer_store_handle: params.peer_store.clone(),
+			bandwidth_quotas,
 			_marker: PhantomData,
 			_block: Default::default(),
 		});
@@ -749,6 +766,28 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkService<B, H> {
 		rx.await.map_err(|_| ())
 	}
 
+	/// Ban a peer or a range of IP addresses for `duration`, or permanently if `None`.
+	///
+	/// The connections to a banned peer are closed. See [`PeerStoreHandle::ban`].
+	pub fn ban(&self, target: BanTarget, duration: Option<Duration>) {
+		self.peer_store_handle.ban(target, duration);
+
+		if let BanTarget::Peer(peer_id) = target {
+			let _ =
+				self.to_worker.unbounded_send(ServiceToWorkerMsg::ClosePeerConnections(peer_id));
+		}
+	}
+
+	/// Lift a ban set with [`Self::ban`]. Returns `false` if `target` was not banned.
+	pub fn unban(&self, target: &BanTarget) -> bool {
+		self.peer_store_handle.unban(target)
+	}
+
+	/// Get the bans set with [`Self::ban`] that have not expired yet.
+	pub fn bans(&self) -> Vec<Ban> {
+		self.peer_store_handle.bans()
+	}
+
 	/// Utility function to extract `PeerId` from each `Multiaddr` for peer set updates.
 	///
 	/// Returns an `Err` if one of the given addresses is invalid or contains an
@@ -1028,6 +1067,21 @@ where
 			}
 		};
 
+		if !self
+			.bandwidth_quotas
+			.try_reserve(&protocol, &target, Direction::Upload, message.len())
+		{
+			debug!(
+				target: "sub-libp2p",
+				"Bandwidth quota exceeded, discarding notification to {}, {:?}",
+				target, protocol,
+			);
+			if let Some(metric) = self.notifications_quota_exceeded_metric.as_ref() {
+				metric.with_label_values(&["out", &protocol]).inc();
+			}
+			return
+		}
+
 		if let Some(notifications_sizes_metric) = self.notifications_sizes_metric.as_ref() {
 			notifications_sizes_metric
 				.with_label_values(&["out", &protocol])
@@ -1065,7 +1119,12 @@ where
 			.as_ref()
 			.map(|histogram| histogram.with_label_values(&["out", &protocol]));
 
-		Ok(Box::new(NotificationSender { sink, protocol_name: protocol, notification_size_metric }))
+		Ok(Box::new(NotificationSender {
+			sink,
+			protocol_name: protocol,
+			notification_size_metric,
+			quotas: self.bandwidth_quotas.clone(),
+		}))
 	}
 
 	fn set_notification_handshake(&self, protocol: ProtocolName, handshake: Vec<u8>) {
@@ -1130,6 +1189,9 @@ pub struct NotificationSender {
 	/// Field extracted from the [`Metrics`] struct and necessary to report the
 	/// notifications-related metrics.
 	notification_size_metric: Option<Histogram>,
+
+	/// Bandwidth quotas of the protocols.
+	quotas: BandwidthQuotas,
 }
 
 #[async_trait::async_trait]
@@ -1137,6 +1199,12 @@ impl NotificationSenderT for NotificationSender {
 	async fn ready(
 		&self,
 	) -> Result<Box<dyn NotificationSenderReadyT + '_>, NotificationSenderError> {
+		// Wait for the upload quota of the protocol before reserving a slot.
+		let delay = self.quotas.delay(&self.protocol_name, self.sink.peer_id(), Direction::Upload);
+		if !delay.is_zero() {
+			futures_timer::Delay::new(delay).await;
+		}
+
 		Ok(Box::new(NotificationSenderReady {
 			ready: match self.sink.reserve_notification().await {
 				Ok(r) => Some(r),
@@ -1145,6 +1213,7 @@ impl NotificationSenderT for NotificationSender {
 			peer_id: self.sink.peer_id(),
 			protocol_name: &self.protocol_name,
 			notification_size_metric: self.notification_size_metric.clone(),
+			quotas: &self.quotas,
 		}))
 	}
 }
@@ -1163,10 +1232,20 @@ pub struct NotificationSenderReady<'a> {
 	/// Field extracted from the [`Metrics`] struct and necessary to report the
 	/// notifications-related metrics.
 	notification_size_metric: Option<Histogram>,
+
+	/// Bandwidth quotas of the protocols.
+	quotas: &'a BandwidthQuotas,
 }
 
 impl<'a> NotificationSenderReadyT for NotificationSenderReady<'a> {
 	fn send(&mut self, notification: Vec<u8>) -> Result<(), NotificationSenderError> {
+		self.quotas.reserve(
+			self.protocol_name,
+			self.peer_id,
+			Direction::Upload,
+			notification.len(),
+		);
+
 		if let Some(notification_size_metric) = &self.notification_size_metric {
 			notification_size_metric.observe(notification.len() as f64);
 		}
@@ -1209,6 +1288,7 @@ enum ServiceToWorkerMsg {
 		pending_response: oneshot::Sender<Result<NetworkState, RequestFailure>>,
 	},
 	DisconnectPeer(PeerId, ProtocolName),
+	ClosePeerConnections(PeerId),
 	SetNotificationHandshake(ProtocolName, Vec<u8>),
 }
 
@@ -1353,6 +1433,9 @@ where
 				.behaviour_mut()
 				.user_protocol_mut()
 				.disconnect_peer(&who, protocol_name),
+			ServiceToWorkerMsg::ClosePeerConnections(who) => {
+				let _ = self.network_service.disconnect_peer_id(who);
+			},
 			ServiceToWorkerMsg::SetNotificationHandshake(protocol, handshake) => self
 				.network_service
 				.behaviour_mut()
@@ -1550,7 +1633,31 @@ where
 					debug_assert!(_previous_value.is_some());
 				}
 			},
-			SwarmEvent::Behaviour(BehaviourOut::NotificationsReceived { remote, messages }) => {
+			SwarmEvent::Behaviour(BehaviourOut::NotificationsReceived { remote, mut messages }) => {
+				// Notifications above the download quota of their protocol are discarded.
+				let quotas = &self.service.bandwidth_quotas;
+				let metrics = self.metrics.as_ref();
+				messages.retain(|(protocol, message)| {
+					if quotas.try_reserve(protocol, &remote, Direction::Download, message.len()) {
+						return true
+					}
+					debug!(
+						target: "sub-libp2p",
+						"Bandwidth quota exceeded, discarding notification from {}, {:?}",
+						remote, protocol,
+					);
+					if let Some(metrics) = metrics {
+						metrics
+							.notifications_quota_exceeded_total
+							.with_label_values(&["in", protocol])
+							.inc();
+					}
+					false
+				});
+				if messages.is_empty() {
+					return
+				}
+
 				if let Some(metrics) = self.metrics.as_ref() {
 					for (protocol, message) in &messages {
 						metrics
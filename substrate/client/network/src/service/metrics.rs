@@ -18,8 +18,8 @@
 
 use crate::transport::BandwidthSinks;
 use prometheus_endpoint::{
-	self as prometheus, Counter, CounterVec, Gauge, GaugeVec, HistogramOpts, MetricSource, Opts,
-	PrometheusError, Registry, SourcedCounter, SourcedGauge, U64,
+	self as prometheus, Counter, Gauge, GaugeVec, HistogramOpts, MetricSource, Opts,
+	PrometheusError, Registry, SourcedCounter, SourcedGauge,
 };
 use std::{
 	str,
@@ -29,7 +29,7 @@ use std::{
 	},
 };
 
-pub use prometheus_endpoint::{Histogram, HistogramVec};
+pub use prometheus_endpoint::{CounterVec, Histogram, HistogramVec, U64};
 
 /// Registers all networking metrics with the given registry.
 pub fn register(registry: &Registry, sources: MetricSources) -> Result<Metrics, PrometheusError> {
@@ -61,6 +61,7 @@ pub struct Metrics {
 	pub kbuckets_num_nodes: GaugeVec<U64>,
 	pub listeners_local_addresses: Gauge<U64>,
 	pub listeners_errors_total: Counter<U64>,
+	pub notifications_quota_exceeded_total: CounterVec<U64>,
 	pub notifications_sizes: HistogramVec,
 	pub notifications_streams_closed_total: CounterVec<U64>,
 	pub notifications_streams_opened_total: CounterVec<U64>,
@@ -153,6 +154,13 @@ impl Metrics {
 				"substrate_sub_libp2p_listeners_errors_total",
 				"Total number of non-fatal errors reported by a listener"
 			)?, registry)?,
+			notifications_quota_exceeded_total: prometheus::register(CounterVec::new(
+				Opts::new(
+					"substrate_sub_libp2p_notifications_quota_exceeded_total",
+					"Total number of notifications discarded because of a bandwidth quota"
+				),
+				&["direction", "protocol"]
+			)?, registry)?,
 			notifications_sizes: prometheus::register(HistogramVec::new(
 				HistogramOpts {
 					common_opts: Opts::new(
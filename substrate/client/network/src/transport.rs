@@ -18,16 +18,21 @@
 
 //! Transport that serves as a common ground for all connections.
 
+use crate::peer_store::PeerStoreHandle;
+
 use either::Either;
+use futures::future;
 use libp2p::{
 	core::{
 		muxing::StreamMuxerBox,
 		transport::{Boxed, OptionalTransport},
 		upgrade,
 	},
-	dns, identity, noise, tcp, websocket, PeerId, Transport, TransportExt,
+	dns, identity,
+	multiaddr::Protocol,
+	noise, tcp, websocket, Multiaddr, PeerId, Transport, TransportExt,
 };
-use std::{sync::Arc, time::Duration};
+use std::{io, net::IpAddr, sync::Arc, time::Duration};
 
 pub use libp2p::bandwidth::BandwidthSinks;
 
@@ -44,6 +49,9 @@ pub use libp2p::bandwidth::BandwidthSinks;
 /// high-level protocols combined, or to some generously high value if you are sure that a maximum
 /// size is enforced on all high-level protocols.
 ///
+/// Connections from and to the peers and IP addresses banned in `peer_store` are refused. Banned
+/// IP addresses are checked before the handshake, banned peers right after it.
+///
 /// Returns a `BandwidthSinks` object that allows querying the average bandwidth produced by all
 /// the connections spawned with this transport.
 pub fn build_transport(
@@ -51,6 +59,7 @@ pub fn build_transport(
 	memory_only: bool,
 	yamux_window_size: Option<u32>,
 	yamux_maximum_buffer_size: usize,
+	peer_store: PeerStoreHandle,
 ) -> (Boxed<(PeerId, StreamMuxerBox)>, Arc<BandwidthSinks>) {
 	// Build the base layer of the transport.
 	let transport = if !memory_only {
@@ -80,6 +89,17 @@ pub fn build_transport(
 		Either::Right(OptionalTransport::some(libp2p::core::transport::MemoryTransport::default()))
 	};
 
+	let ip_filter = peer_store.clone();
+	let transport = transport.and_then(move |stream, endpoint| {
+		let banned = multiaddr_ip(endpoint.get_remote_address())
+			.map_or(false, |ip| ip_filter.has_ip_ban(&ip));
+		future::ready(if banned {
+			Err(io::Error::new(io::ErrorKind::PermissionDenied, "IP address is banned"))
+		} else {
+			Ok(stream)
+		})
+	});
+
 	let authentication_config = noise::Config::new(&keypair).expect("Can create noise config. qed");
 	let multiplexing_config = {
 		let mut yamux_config = libp2p::yamux::Config::default();
@@ -100,7 +120,23 @@ pub fn build_transport(
 		.authenticate(authentication_config)
 		.multiplex(multiplexing_config)
 		.timeout(Duration::from_secs(20))
+		.and_then(move |(peer_id, muxer), _| {
+			future::ready(if peer_store.has_peer_ban(&peer_id) {
+				Err(io::Error::new(io::ErrorKind::PermissionDenied, "Peer is banned"))
+			} else {
+				Ok((peer_id, muxer))
+			})
+		})
 		.boxed();
 
 	transport.with_bandwidth_logging()
 }
+
+/// IP address of a multiaddress starting with `/ip4` or `/ip6`.
+fn multiaddr_ip(addr: &Multiaddr) -> Option<IpAddr> {
+	match addr.iter().next()? {
+		Protocol::Ip4(ip) => Some(ip.into()),
+		Protocol::Ip6(ip) => Some(ip.into()),
+		_ => None,
+	}
+}
@@ -0,0 +1,304 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Download of the block history left out by a warp sync.
+//!
+//! The blocks are downloaded backwards, starting from the parent of the lowest block we have
+//! and going down to genesis or to a configured block. As every block is checked against the
+//! parent hash of the block above it, the downloaded history is known to lead to the warp sync
+//! target before it is even imported.
+
+use libp2p::PeerId;
+use log::debug;
+use sc_network_common::sync::message::{
+	BlockAttributes, BlockData, BlockRequest, Direction, FromBlock,
+};
+use sp_runtime::traits::{
+	Block as BlockT, Header as HeaderT, NumberFor, One, SaturatedConversion, Saturating,
+};
+use std::{
+	collections::{BTreeMap, HashMap},
+	ops::RangeInclusive,
+};
+
+const LOG_TARGET: &'static str = "sync";
+
+/// Maximum number of blocks downloaded below the highest missing block.
+const MAX_DOWNLOAD_AHEAD: u32 = 2048;
+
+/// Backwards download of the block history.
+pub(crate) struct Backfill<B: BlockT> {
+	/// Number of the highest block not downloaded and verified yet.
+	next_number: NumberFor<B>,
+	/// Hash the block `next_number` must have, i.e. the parent hash of the block above it.
+	next_hash: B::Hash,
+	/// Number of the lowest block to download.
+	floor: NumberFor<B>,
+	/// Ranges being downloaded, by peer.
+	downloading: HashMap<PeerId, RangeInclusive<NumberFor<B>>>,
+	/// Downloaded blocks waiting for the blocks above them, by the number of their highest block.
+	/// The blocks of every response are stored in ascending order.
+	downloaded: BTreeMap<NumberFor<B>, (PeerId, Vec<BlockData<B>>)>,
+}
+
+impl<B: BlockT> Backfill<B> {
+	/// Download the blocks from `floor` to `number`, whose hash is `hash`.
+	pub(crate) fn new(number: NumberFor<B>, hash: B::Hash, floor: NumberFor<B>) -> Self {
+		Self {
+			next_number: number,
+			next_hash: hash,
+			floor,
+			downloading: HashMap::new(),
+			downloaded: BTreeMap::new(),
+		}
+	}
+
+	/// Number of the lowest block to download.
+	pub(crate) fn floor(&self) -> NumberFor<B> {
+		self.floor
+	}
+
+	/// Number of the highest block not downloaded yet.
+	pub(crate) fn next_number(&self) -> NumberFor<B> {
+		self.next_number
+	}
+
+	/// Get a request for the highest range of blocks neither downloaded nor being downloaded, if
+	/// `peer_best` is high enough to serve it.
+	pub(crate) fn block_request(
+		&mut self,
+		peer_id: PeerId,
+		peer_best: NumberFor<B>,
+		attrs: BlockAttributes,
+		max_blocks_per_request: u32,
+	) -> Option<(RangeInclusive<NumberFor<B>>, BlockRequest<B>)> {
+		if self.downloading.contains_key(&peer_id) {
+			return None
+		}
+
+		let mut ranges = self
+			.downloading
+			.values()
+			.cloned()
+			.chain(self.downloaded.iter().map(|(top, (_, blocks))| {
+				(*top + One::one() - (blocks.len() as u32).into())..=*top
+			}))
+			.collect::<Vec<_>>();
+		ranges.sort_by(|a, b| b.end().cmp(a.end()));
+
+		// Walk down the contiguous ranges to the first missing block.
+		let mut top = self.next_number;
+		let mut below = None;
+		for range in ranges {
+			if *range.end() < top {
+				below = Some(*range.end());
+				break
+			}
+			if *range.start() <= top {
+				if *range.start() <= self.floor {
+					return None
+				}
+				top = *range.start() - One::one();
+			}
+		}
+
+		if top < self.floor ||
+			self.next_number - top >= MAX_DOWNLOAD_AHEAD.into() ||
+			peer_best < top
+		{
+			return None
+		}
+
+		let mut bottom = std::cmp::max(
+			self.floor,
+			top.saturating_sub(max_blocks_per_request.saturating_sub(1).into()),
+		);
+		if let Some(below) = below {
+			bottom = std::cmp::max(bottom, below + One::one());
+		}
+
+		let request = BlockRequest::<B> {
+			id: 0,
+			fields: attrs,
+			from: FromBlock::Number(top),
+			direction: Direction::Descending,
+			max: Some((top - bottom + One::one()).saturated_into::<u32>()),
+		};
+		self.downloading.insert(peer_id, bottom..=top);
+		Some((bottom..=top, request))
+	}
+
+	/// Store the `blocks` downloaded from `peer_id`, in ascending order.
+	pub(crate) fn on_blocks(&mut self, peer_id: PeerId, blocks: Vec<BlockData<B>>) {
+		let Some(range) = self.downloading.remove(&peer_id) else { return };
+		let Some(top) = blocks.last().and_then(|b| b.header.as_ref()).map(|h| *h.number()) else {
+			return
+		};
+		if top != *range.end() {
+			return
+		}
+		self.downloaded.insert(top, (peer_id, blocks));
+	}
+
+	/// Forget the range being downloaded from `peer_id`.
+	pub(crate) fn clear_peer_download(&mut self, peer_id: &PeerId) {
+		self.downloading.remove(peer_id);
+	}
+
+	/// Take the downloaded blocks directly below the ones already verified, in descending order.
+	///
+	/// Each block is checked to be the parent of the one above it. On the first mismatch, the
+	/// rest of the response is dropped and the peer that sent it is returned.
+	pub(crate) fn ready_blocks(&mut self) -> (Vec<(PeerId, BlockData<B>)>, Option<PeerId>) {
+		let mut ready = Vec::new();
+		while self.next_number >= self.floor {
+			let Some((peer_id, blocks)) = self.downloaded.remove(&self.next_number) else { break };
+			for block in blocks.into_iter().rev() {
+				let Some(header) = block.header.as_ref() else { return (ready, Some(peer_id)) };
+				if block.hash != self.next_hash || *header.number() != self.next_number {
+					debug!(
+						target: LOG_TARGET,
+						"Block history from {} does not match: expected #{} ({:?}), got #{} ({:?})",
+						peer_id,
+						self.next_number,
+						self.next_hash,
+						header.number(),
+						block.hash,
+					);
+					return (ready, Some(peer_id))
+				}
+				self.next_hash = *header.parent_hash();
+				self.next_number = self.next_number - One::one();
+				ready.push((peer_id, block));
+			}
+		}
+		(ready, None)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_runtime::traits::Zero;
+	use substrate_test_runtime_client::runtime::{Block, Hash, Header};
+
+	fn chain(len: u64) -> Vec<Header> {
+		let mut headers: Vec<Header> = Vec::new();
+		for number in 0..len {
+			let parent_hash = headers.last().map_or(Hash::zero(), |h| h.hash());
+			headers.push(Header::new(
+				number,
+				Default::default(),
+				Default::default(),
+				parent_hash,
+				Default::default(),
+			));
+		}
+		headers
+	}
+
+	fn response(headers: &[Header], range: RangeInclusive<u64>) -> Vec<BlockData<Block>> {
+		headers[*range.start() as usize..=*range.end() as usize]
+			.iter()
+			.map(|header| BlockData {
+				hash: header.hash(),
+				header: Some(header.clone()),
+				body: Some(Vec::new()),
+				indexed_body: None,
+				receipt: None,
+				message_queue: None,
+				justification: None,
+				justifications: None,
+			})
+			.collect()
+	}
+
+	fn backfill(headers: &[Header], floor: u64) -> Backfill<Block> {
+		let top = headers.len() - 1;
+		Backfill::new(top as u64 - 1, *headers[top].parent_hash(), floor)
+	}
+
+	#[test]
+	fn requests_descending_ranges_down_to_floor() {
+		let headers = chain(21);
+		let mut backfill = backfill(&headers, 5);
+		let (a, b, c) = (PeerId::random(), PeerId::random(), PeerId::random());
+
+		let (range, request) = backfill.block_request(a, 100, BlockAttributes::HEADER, 8).unwrap();
+		assert_eq!(range, 12..=19);
+		assert_eq!(request.from, FromBlock::Number(19));
+		assert_eq!(request.direction, Direction::Descending);
+		assert_eq!(request.max, Some(8));
+		assert!(backfill.block_request(a, 100, BlockAttributes::HEADER, 8).is_none());
+
+		let (range, _) = backfill.block_request(b, 100, BlockAttributes::HEADER, 8).unwrap();
+		assert_eq!(range, 5..=11);
+		assert!(backfill.block_request(c, 100, BlockAttributes::HEADER, 8).is_none());
+
+		// A range given up by a peer is requested again.
+		backfill.clear_peer_download(&a);
+		assert!(backfill.block_request(c, 10, BlockAttributes::HEADER, 8).is_none());
+		let (range, _) = backfill.block_request(c, 100, BlockAttributes::HEADER, 8).unwrap();
+		assert_eq!(range, 12..=19);
+	}
+
+	#[test]
+	fn verifies_blocks_from_the_top() {
+		let headers = chain(21);
+		let mut backfill = backfill(&headers, 1);
+		let (a, b) = (PeerId::random(), PeerId::random());
+
+		backfill.block_request(a, 100, BlockAttributes::HEADER, 10).unwrap();
+		backfill.block_request(b, 100, BlockAttributes::HEADER, 10).unwrap();
+
+		// The lower range can't be verified before the upper one.
+		backfill.on_blocks(b, response(&headers, 1..=9));
+		assert!(backfill.ready_blocks().0.is_empty());
+
+		backfill.on_blocks(a, response(&headers, 10..=19));
+		let (ready, bad_peer) = backfill.ready_blocks();
+		assert_eq!(bad_peer, None);
+		let numbers = ready.iter().map(|(_, b)| *b.header.as_ref().unwrap().number());
+		assert_eq!(numbers.collect::<Vec<_>>(), (1..=19).rev().collect::<Vec<_>>());
+		assert!(backfill.next_number().is_zero());
+		assert!(backfill.block_request(a, 100, BlockAttributes::HEADER, 10).is_none());
+	}
+
+	#[test]
+	fn rejects_blocks_not_leading_to_the_target() {
+		let headers = chain(21);
+		let fork = chain(21)
+			.into_iter()
+			.map(|mut header| {
+				header.state_root = Hash::repeat_byte(1);
+				header
+			})
+			.collect::<Vec<_>>();
+		let mut backfill = backfill(&headers, 1);
+		let (a, b) = (PeerId::random(), PeerId::random());
+
+		backfill.block_request(a, 100, BlockAttributes::HEADER, 10).unwrap();
+		backfill.on_blocks(a, response(&fork, 10..=19));
+		let (ready, bad_peer) = backfill.ready_blocks();
+		assert!(ready.is_empty());
+		assert_eq!(bad_peer, Some(a));
+
+		let (range, _) = backfill.block_request(b, 100, BlockAttributes::HEADER, 10).unwrap();
+		assert_eq!(range, 10..=19);
+	}
+}
@@ -29,6 +29,7 @@
 //! order to update it.
 
 use crate::{
+	backfill::Backfill,
 	blocks::BlockCollection,
 	extra_requests::ExtraRequests,
 	schema::v1::StateResponse,
@@ -81,6 +82,9 @@ const MAX_IMPORTING_BLOCKS: usize = 2048;
 /// Maximum blocks to download ahead of any gap.
 const MAX_DOWNLOAD_AHEAD: u32 = 2048;
 
+/// Maximum blocks waiting for import above which no block history is requested.
+const MAX_IMPORTING_BLOCKS_FOR_BACKFILL: usize = 256;
+
 /// Maximum blocks to look backwards. The gap is the difference between the highest block and the
 /// common block of a node.
 const MAX_BLOCKS_TO_LOOK_BACKWARDS: u32 = MAX_DOWNLOAD_AHEAD / 2;
@@ -178,12 +182,6 @@ impl Default for AllowedRequests {
 	}
 }
 
-struct GapSync<B: BlockT> {
-	blocks: BlockCollection<B>,
-	best_queued_number: NumberFor<B>,
-	target: NumberFor<B>,
-}
-
 /// Action that the parent of [`ChainSync`] should perform after reporting a network or block event.
 #[derive(Debug)]
 pub enum ChainSyncAction<B: BlockT> {
@@ -251,8 +249,10 @@ pub struct ChainSync<B: BlockT, Client> {
 	/// Enable importing existing blocks. This is used used after the state download to
 	/// catch up to the latest state while re-importing blocks.
 	import_existing: bool,
-	/// Gap download process.
-	gap_sync: Option<GapSync<B>>,
+	/// Backwards download of the block history missing after a warp sync.
+	gap_sync: Option<Backfill<B>>,
+	/// Lowest block of the history to download after a warp sync.
+	backfill_floor: NumberFor<B>,
 	/// Pending actions.
 	actions: Vec<ChainSyncAction<B>>,
 }
@@ -348,6 +348,7 @@ where
 		max_parallel_downloads: u32,
 		max_blocks_per_request: u32,
 		warp_sync_config: Option<WarpSyncConfig<B>>,
+		backfill_floor: Option<NumberFor<B>>,
 	) -> Result<Self, ClientError> {
 		let mut sync = Self {
 			client,
@@ -367,6 +368,7 @@ where
 			warp_sync: None,
 			import_existing: false,
 			gap_sync: None,
+			backfill_floor: backfill_floor.unwrap_or_else(Zero::zero),
 			warp_sync_config,
 			warp_sync_target_block_header: None,
 			actions: Vec::new(),
@@ -409,7 +411,7 @@ where
 
 		let warp_sync_progress = match (&self.warp_sync, &self.mode, &self.gap_sync) {
 			(_, _, Some(gap_sync)) => Some(WarpSyncProgress {
-				phase: WarpSyncPhase::DownloadingBlocks(gap_sync.best_queued_number),
+				phase: WarpSyncPhase::DownloadingBlocks(gap_sync.next_number()),
 				total_bytes: 0,
 			}),
 			(None, SyncMode::Warp, _) => Some(WarpSyncProgress {
@@ -704,31 +706,32 @@ where
 					PeerSyncState::DownloadingGap(_) => {
 						peer.state = PeerSyncState::Available;
 						if let Some(gap_sync) = &mut self.gap_sync {
-							gap_sync.blocks.clear_peer_download(peer_id);
-							if let Some(start_block) =
-								validate_blocks::<B>(&blocks, peer_id, Some(request))?
-							{
-								gap_sync.blocks.insert(start_block, blocks, *peer_id);
+							if validate_blocks::<B>(&blocks, peer_id, Some(request))?.is_some() {
+								gap_sync.on_blocks(*peer_id, blocks);
+							} else {
+								gap_sync.clear_peer_download(peer_id);
 							}
 							gap = true;
-							let blocks: Vec<_> = gap_sync
-								.blocks
-								.ready_blocks(gap_sync.best_queued_number + One::one())
+							let (ready, bad_peer) = gap_sync.ready_blocks();
+							if let Some(bad_peer) = bad_peer {
+								self.actions.push(ChainSyncAction::DropPeer(BadPeer(
+									bad_peer,
+									rep::BAD_BLOCK,
+								)));
+							}
+							let blocks: Vec<_> = ready
 								.into_iter()
-								.map(|block_data| {
-									let justifications =
-										block_data.block.justifications.or_else(|| {
-											legacy_justification_mapping(
-												block_data.block.justification,
-											)
-										});
+								.map(|(origin, block)| {
+									let justifications = block.justifications.or_else(|| {
+										legacy_justification_mapping(block.justification)
+									});
 									IncomingBlock {
-										hash: block_data.block.hash,
-										header: block_data.block.header,
-										body: block_data.block.body,
-										indexed_body: block_data.block.indexed_body,
+										hash: block.hash,
+										header: block.header,
+										body: block.body,
+										indexed_body: block.indexed_body,
 										justifications,
-										origin: block_data.origin,
+										origin: Some(origin),
 										allow_missing_state: true,
 										import_existing: self.import_existing,
 										skip_execution: true,
@@ -738,9 +741,9 @@ where
 								.collect();
 							debug!(
 								target: LOG_TARGET,
-								"Drained {} gap blocks from {}",
+								"Drained {} history blocks down to #{}",
 								blocks.len(),
-								gap_sync.best_queued_number,
+								gap_sync.next_number(),
 							);
 							blocks
 						} else {
@@ -1160,7 +1163,7 @@ where
 	pub fn peer_disconnected(&mut self, peer_id: &PeerId) {
 		self.blocks.clear_peer_download(peer_id);
 		if let Some(gap_sync) = &mut self.gap_sync {
-			gap_sync.blocks.clear_peer_download(peer_id)
+			gap_sync.clear_peer_download(peer_id)
 		}
 		self.peers.remove(peer_id);
 		self.extra_justifications.peer_disconnected(peer_id);
@@ -1270,11 +1273,6 @@ where
 		if self.fork_targets.remove(hash).is_some() {
 			trace!(target: LOG_TARGET, "Completed fork sync {hash:?}");
 		}
-		if let Some(gap_sync) = &mut self.gap_sync {
-			if number > gap_sync.best_queued_number && number <= gap_sync.target {
-				gap_sync.best_queued_number = number;
-			}
-		}
 		if number > self.best_queued_number {
 			self.best_queued_number = number;
 			self.best_queued_hash = *hash;
@@ -1387,13 +1385,28 @@ where
 			}
 		}
 
+		self.gap_sync = None;
 		if let Some((start, end)) = info.block_gap {
-			debug!(target: LOG_TARGET, "Starting gap sync #{start} - #{end}");
-			self.gap_sync = Some(GapSync {
-				best_queued_number: start - One::one(),
-				target: end,
-				blocks: BlockCollection::new(),
-			});
+			let floor = std::cmp::max(start, self.backfill_floor);
+			// The history is downloaded from the parent of the block right above the gap.
+			let above = self
+				.client
+				.hash(end + One::one())?
+				.and_then(|hash| self.client.header(hash).transpose())
+				.transpose()?;
+			match above {
+				Some(header) if floor <= end => {
+					debug!(target: LOG_TARGET, "Starting block history download #{end} - #{floor}");
+					self.gap_sync = Some(Backfill::new(end, *header.parent_hash(), floor));
+				},
+				Some(_) => {
+					debug!(target: LOG_TARGET, "Block history is downloaded down to #{floor}");
+				},
+				None => {
+					let number = end + One::one();
+					warn!(target: LOG_TARGET, "Missing block #{number} above the block gap");
+				},
+			}
 		}
 		trace!(
 			target: LOG_TARGET,
@@ -1661,18 +1674,17 @@ where
 					trace!(target: LOG_TARGET, "Downloading fork {hash:?} from {id}");
 					peer.state = PeerSyncState::DownloadingStale(hash);
 					Some((id, req))
-				} else if let Some((range, req)) = gap_sync.as_mut().and_then(|sync| {
-					peer_gap_block_request(
-						&id,
-						peer,
-						&mut sync.blocks,
-						attrs,
-						sync.target,
-						sync.best_queued_number,
-						max_blocks_per_request,
-					)
-				}) {
-					peer.state = PeerSyncState::DownloadingGap(range.start);
+				} else if let Some((range, req)) = gap_sync
+					.as_mut()
+					.filter(|_| {
+						// The block history has the lowest priority, so it is only downloaded once
+						// we are close to the tip and while the import queue is not busy.
+						!is_major_syncing && queue.len() <= MAX_IMPORTING_BLOCKS_FOR_BACKFILL
+					})
+					.and_then(|sync| {
+						sync.block_request(id, peer.best_number, attrs, max_blocks_per_request)
+					}) {
+					peer.state = PeerSyncState::DownloadingGap(*range.start());
 					trace!(
 						target: LOG_TARGET,
 						"New gap block request for {}, (best:{}, common:{}) {:?}",
@@ -1887,9 +1899,6 @@ where
 		for (_, hash) in &results {
 			self.queue_blocks.remove(hash);
 			self.blocks.clear_queued(hash);
-			if let Some(gap_sync) = &mut self.gap_sync {
-				gap_sync.blocks.clear_queued(hash);
-			}
 		}
 		for (result, hash) in results {
 			if has_error {
@@ -1960,7 +1969,7 @@ where
 						self.restart();
 					}
 					let gap_sync_complete =
-						self.gap_sync.as_ref().map_or(false, |s| s.target == number);
+						self.gap_sync.as_ref().map_or(false, |s| s.floor() == number);
 					if gap_sync_complete {
 						info!(
 							target: LOG_TARGET,
@@ -2200,39 +2209,6 @@ fn peer_block_request<B: BlockT>(
 	Some((range, request))
 }
 
-/// Get a new block request for the peer if any.
-fn peer_gap_block_request<B: BlockT>(
-	id: &PeerId,
-	peer: &PeerSync<B>,
-	blocks: &mut BlockCollection<B>,
-	attrs: BlockAttributes,
-	target: NumberFor<B>,
-	common_number: NumberFor<B>,
-	max_blocks_per_request: u32,
-) -> Option<(Range<NumberFor<B>>, BlockRequest<B>)> {
-	let range = blocks.needed_blocks(
-		*id,
-		max_blocks_per_request,
-		std::cmp::min(peer.best_number, target),
-		common_number,
-		1,
-		MAX_DOWNLOAD_AHEAD,
-	)?;
-
-	// The end is not part of the range.
-	let last = range.end.saturating_sub(One::one());
-	let from = FromBlock::Number(last);
-
-	let request = BlockRequest::<B> {
-		id: 0,
-		fields: attrs,
-		from,
-		direction: Direction::Descending,
-		max: Some((range.end - range.start).saturated_into::<u32>()),
-	};
-	Some((range, request))
-}
-
 /// Get pending fork sync targets for a peer.
 fn fork_sync_request<B: BlockT>(
 	id: &PeerId,
@@ -38,7 +38,7 @@ fn processes_empty_response_on_justification_request_for_unknown_block() {
 	let client = Arc::new(TestClientBuilder::new().build());
 	let peer_id = PeerId::random();
 
-	let mut sync = ChainSync::new(SyncMode::Full, client.clone(), 1, 64, None).unwrap();
+	let mut sync = ChainSync::new(SyncMode::Full, client.clone(), 1, 64, None, None).unwrap();
 
 	let (a1_hash, a1_number) = {
 		let a1 = BlockBuilderBuilder::new(&*client)
@@ -91,7 +91,7 @@ fn processes_empty_response_on_justification_request_for_unknown_block() {
 fn restart_doesnt_affect_peers_downloading_finality_data() {
 	let mut client = Arc::new(TestClientBuilder::new().build());
 
-	let mut sync = ChainSync::new(SyncMode::Full, client.clone(), 1, 64, None).unwrap();
+	let mut sync = ChainSync::new(SyncMode::Full, client.clone(), 1, 64, None, None).unwrap();
 
 	let peer_id1 = PeerId::random();
 	let peer_id2 = PeerId::random();
@@ -275,7 +275,7 @@ fn do_ancestor_search_when_common_block_to_best_qeued_gap_is_to_big() {
 	let mut client = Arc::new(TestClientBuilder::new().build());
 	let info = client.info();
 
-	let mut sync = ChainSync::new(SyncMode::Full, client.clone(), 5, 64, None).unwrap();
+	let mut sync = ChainSync::new(SyncMode::Full, client.clone(), 5, 64, None, None).unwrap();
 
 	let peer_id1 = PeerId::random();
 	let peer_id2 = PeerId::random();
@@ -421,7 +421,7 @@ fn can_sync_huge_fork() {
 
 	let info = client.info();
 
-	let mut sync = ChainSync::new(SyncMode::Full, client.clone(), 5, 64, None).unwrap();
+	let mut sync = ChainSync::new(SyncMode::Full, client.clone(), 5, 64, None, None).unwrap();
 
 	let finalized_block = blocks[MAX_BLOCKS_TO_LOOK_BACKWARDS as usize * 2 - 1].clone();
 	let just = (*b"TEST", Vec::new());
@@ -554,7 +554,7 @@ fn syncs_fork_without_duplicate_requests() {
 
 	let info = client.info();
 
-	let mut sync = ChainSync::new(SyncMode::Full, client.clone(), 5, 64, None).unwrap();
+	let mut sync = ChainSync::new(SyncMode::Full, client.clone(), 5, 64, None, None).unwrap();
 
 	let finalized_block = blocks[MAX_BLOCKS_TO_LOOK_BACKWARDS as usize * 2 - 1].clone();
 	let just = (*b"TEST", Vec::new());
@@ -689,7 +689,7 @@ fn removes_target_fork_on_disconnect() {
 	let mut client = Arc::new(TestClientBuilder::new().build());
 	let blocks = (0..3).map(|_| build_block(&mut client, None, false)).collect::<Vec<_>>();
 
-	let mut sync = ChainSync::new(SyncMode::Full, client.clone(), 1, 64, None).unwrap();
+	let mut sync = ChainSync::new(SyncMode::Full, client.clone(), 1, 64, None, None).unwrap();
 
 	let peer_id1 = PeerId::random();
 	let common_block = blocks[1].clone();
@@ -714,7 +714,7 @@ fn can_import_response_with_missing_blocks() {
 
 	let empty_client = Arc::new(TestClientBuilder::new().build());
 
-	let mut sync = ChainSync::new(SyncMode::Full, empty_client.clone(), 1, 64, None).unwrap();
+	let mut sync = ChainSync::new(SyncMode::Full, empty_client.clone(), 1, 64, None, None).unwrap();
 
 	let peer_id1 = PeerId::random();
 	let best_block = blocks[3].clone();
@@ -745,7 +745,7 @@ fn ancestor_search_repeat() {
 #[test]
 fn sync_restart_removes_block_but_not_justification_requests() {
 	let mut client = Arc::new(TestClientBuilder::new().build());
-	let mut sync = ChainSync::new(SyncMode::Full, client.clone(), 1, 64, None).unwrap();
+	let mut sync = ChainSync::new(SyncMode::Full, client.clone(), 1, 64, None, None).unwrap();
 
 	let peers = vec![PeerId::random(), PeerId::random()];
 
@@ -887,7 +887,7 @@ fn request_across_forks() {
 		fork_blocks
 	};
 
-	let mut sync = ChainSync::new(SyncMode::Full, client.clone(), 5, 64, None).unwrap();
+	let mut sync = ChainSync::new(SyncMode::Full, client.clone(), 5, 64, None, None).unwrap();
 
 	// Add the peers, all at the common ancestor 100.
 	let common_block = blocks.last().unwrap();
@@ -21,6 +21,7 @@
 pub use service::syncing_service::SyncingService;
 pub use types::{SyncEvent, SyncEventStream, SyncState, SyncStatus, SyncStatusProvider};
 
+mod backfill;
 mod block_announce_validator;
 mod chain_sync;
 mod extra_requests;
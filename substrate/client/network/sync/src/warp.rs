@@ -74,6 +74,12 @@ pub trait WarpSyncProvider<Block: BlockT>: Send + Sync {
 	/// Get current list of authorities. This is supposed to be genesis authorities when starting
 	/// sync.
 	fn current_authorities(&self) -> AuthorityList;
+	/// Get the trusted block the first proof is requested from, with the id and list of
+	/// authorities of the set that finalizes the blocks after it. When `None`, sync starts at
+	/// genesis with set `0` and the [`Self::current_authorities`].
+	fn checkpoint(&self) -> Option<(Block::Hash, SetId, AuthorityList)> {
+		None
+	}
 }
 
 /// Reported warp sync phase.
@@ -91,7 +97,7 @@ pub enum WarpSyncPhase<Block: BlockT> {
 	DownloadingState,
 	/// Importing state.
 	ImportingState,
-	/// Downloading block history.
+	/// Downloading block history, backwards from the given block.
 	DownloadingBlocks(NumberFor<Block>),
 }
 
@@ -202,12 +208,25 @@ where
 	/// authorities. Alternatively we can pass a target block when we want to skip downloading
 	/// proofs, in this case we will continue polling until the target block is known.
 	pub fn new(client: Arc<Client>, warp_sync_config: WarpSyncConfig<B>) -> Self {
-		let last_hash = client.hash(Zero::zero()).unwrap().expect("Genesis header always exists");
 		match warp_sync_config {
 			WarpSyncConfig::WithProvider(warp_sync_provider) => {
+				let (last_hash, set_id, authorities) = match warp_sync_provider.checkpoint() {
+					Some((hash, set_id, authorities)) => {
+						log::info!(
+							target: LOG_TARGET,
+							"Warp syncing from checkpoint {hash:?} (set_id={set_id})",
+						);
+						(hash, set_id, authorities)
+					},
+					None => (
+						client.hash(Zero::zero()).unwrap().expect("Genesis header always exists"),
+						0,
+						warp_sync_provider.current_authorities(),
+					),
+				};
 				let phase = Phase::WarpProof {
-					set_id: 0,
-					authorities: warp_sync_provider.current_authorities(),
+					set_id,
+					authorities,
 					last_hash,
 					warp_sync_provider: warp_sync_provider.clone(),
 				};
@@ -1234,7 +1234,8 @@ async fn warp_sync() {
 		sync_mode: SyncMode::Warp,
 		..Default::default()
 	});
-	let gap_end = net.peer(0).push_blocks(63, false).pop().unwrap();
+	let gap_start = net.peer(0).push_blocks(1, false).pop().unwrap();
+	let gap_end = net.peer(0).push_blocks(62, false).pop().unwrap();
 	let target = net.peer(0).push_blocks(1, false).pop().unwrap();
 	net.peer(1).push_blocks(64, false);
 	net.peer(2).push_blocks(64, false);
@@ -1243,10 +1244,13 @@ async fn warp_sync() {
 	assert!(!net.peer(3).client().has_state_at(&BlockId::Number(1)));
 	assert!(net.peer(3).client().has_state_at(&BlockId::Number(64)));
 
-	// Wait for peer 1 download block history
+	// Wait for peer 1 download block history, backwards from the target down to genesis.
 	futures::future::poll_fn::<(), _>(|cx| {
 		net.poll(cx);
-		if net.peer(3).has_body(gap_end) && net.peer(3).has_body(target) {
+		if net.peer(3).has_body(gap_start) &&
+			net.peer(3).has_body(gap_end) &&
+			net.peer(3).has_body(target)
+		{
 			Poll::Ready(())
 		} else {
 			Poll::Pending
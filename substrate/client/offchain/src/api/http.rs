@@ -1127,4 +1127,53 @@ mod tests {
 		// Check that the http client initialized, because it was used.
 		assert!(Lazy::into_value(Arc::try_unwrap(shared_client.0).unwrap()).is_ok());
 	}
+
+	#[test]
+	fn mock_server_answers_with_routes() {
+		use crate::testing::MockHttpServer;
+		use sp_core::offchain::testing::{HttpRoute, MockResponse};
+
+		let server = MockHttpServer::start();
+		server.add_route(
+			HttpRoute::new("/price/*", MockResponse::ok("42").with_header("X-Source", "mock"))
+				.with_method("GET"),
+		);
+		server.add_route(HttpRoute::new("/slow", MockResponse::timeout()));
+
+		let (mut api, worker) = http(SHARED_CLIENT.clone());
+		std::thread::spawn(move || tokio::runtime::Runtime::new().unwrap().block_on(worker));
+
+		let deadline = timestamp::now().add(Duration::from_millis(10_000));
+		let price = api.request_start("GET", &server.url("/price/dot?currency=usd")).unwrap();
+		api.request_write_body(price, &[], Some(deadline)).unwrap();
+		assert_eq!(api.response_wait(&[price], Some(deadline)), [HttpRequestStatus::Finished(200)]);
+		assert!(api.response_headers(price).contains(&(b"x-source".to_vec(), b"mock".to_vec())));
+		let mut buf = vec![0; 2048];
+		let n = api.response_read_body(price, &mut buf, Some(deadline)).unwrap();
+		assert_eq!(&buf[..n], b"42");
+
+		let missing = api.request_start("POST", &server.url("/price/dot")).unwrap();
+		api.request_write_body(missing, b"body", Some(deadline)).unwrap();
+		api.request_write_body(missing, &[], Some(deadline)).unwrap();
+		assert_eq!(
+			api.response_wait(&[missing], Some(deadline)),
+			[HttpRequestStatus::Finished(404)]
+		);
+
+		let slow = api.request_start("GET", &server.url("/slow")).unwrap();
+		api.request_write_body(slow, &[], Some(deadline)).unwrap();
+		let short_deadline = timestamp::now().add(Duration::from_millis(200));
+		assert_eq!(
+			api.response_wait(&[slow], Some(short_deadline)),
+			[HttpRequestStatus::DeadlineReached]
+		);
+
+		let requests = server.take_requests();
+		assert_eq!(
+			requests.iter().map(|r| (r.method.as_str(), r.uri.as_str())).collect::<Vec<_>>(),
+			[("GET", "/price/dot?currency=usd"), ("POST", "/price/dot"), ("GET", "/slow")]
+		);
+		assert_eq!(requests[1].body, b"body");
+		assert!(server.take_requests().is_empty());
+	}
 }
@@ -35,13 +35,22 @@
 
 #![warn(missing_docs)]
 
-use std::{fmt, sync::Arc};
+use std::{
+	fmt,
+	num::NonZeroUsize,
+	sync::{
+		atomic::{AtomicBool, AtomicUsize, Ordering},
+		Arc,
+	},
+	time::{Duration, Instant},
+};
 
 use futures::{
 	future::{ready, Future},
 	prelude::*,
 };
 use parking_lot::Mutex;
+use prometheus_endpoint::Registry;
 use sc_client_api::BlockchainEvents;
 use sc_network::{NetworkPeers, NetworkStateInfo};
 use sc_transaction_pool_api::OffchainTransactionPoolFactory;
@@ -53,6 +62,10 @@ use sp_runtime::traits::{self, Header};
 use threadpool::ThreadPool;
 
 mod api;
+mod metrics;
+
+#[cfg(any(test, feature = "test-helpers"))]
+pub mod testing;
 
 pub use sp_core::offchain::storage::OffchainDb;
 pub use sp_offchain::{OffchainWorkerApi, STORAGE_PREFIX};
@@ -90,6 +103,50 @@ impl offchain::OffchainStorage for NoOffchainStorage {
 	}
 }
 
+/// Blocks the offchain workers are run for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WorkerTrigger {
+	/// Every imported block which is the new best block.
+	#[default]
+	NewBest,
+	/// Every finalized block.
+	///
+	/// Blocks finalized together only trigger the workers of the last one.
+	Finalized,
+}
+
+/// When and how many offchain workers are run.
+#[derive(Debug, Clone)]
+pub struct SchedulingPolicy {
+	/// Blocks the offchain workers are run for.
+	pub trigger: WorkerTrigger,
+	/// Maximum number of offchain workers running at the same time.
+	///
+	/// Further workers are queued until one finishes.
+	pub max_concurrent: NonZeroUsize,
+	/// Skip the offchain workers of a block instead of queuing them when `max_concurrent` workers
+	/// are already running.
+	pub skip_if_busy: bool,
+	/// Wall-clock time after which the host functions of a running worker stop being served.
+	///
+	/// Pending and further HTTP requests of the worker then fail, and its slot is freed for the
+	/// following workers. The runtime call itself can not be interrupted, so a worker busy
+	/// computing keeps running on an extra thread until it returns. At most `max_concurrent`
+	/// threads are added, further timed out workers keep their slot.
+	pub timeout: Option<Duration>,
+}
+
+impl Default for SchedulingPolicy {
+	fn default() -> Self {
+		Self {
+			trigger: WorkerTrigger::NewBest,
+			max_concurrent: NonZeroUsize::new(num_cpus::get().max(1)).expect("At least 1; qed"),
+			skip_if_busy: false,
+			timeout: None,
+		}
+	}
+}
+
 /// Options for [`OffchainWorkers`]
 pub struct OffchainWorkerOptions<RA, Block: traits::Block, Storage, CE> {
 	/// Provides access to the runtime api.
@@ -124,12 +181,16 @@ pub struct OffchainWorkerOptions<RA, Block: traits::Block, Storage, CE> {
 	/// }
 	/// ```
 	pub custom_extensions: CE,
+	/// When and how many offchain workers are run.
+	pub scheduling_policy: SchedulingPolicy,
+	/// Registry for the offchain workers metrics.
+	pub prometheus_registry: Option<Registry>,
 }
 
 /// An offchain workers manager.
 pub struct OffchainWorkers<RA, Block: traits::Block, Storage> {
 	runtime_api_provider: Arc<RA>,
-	thread_pool: Mutex<ThreadPool>,
+	thread_pool: Arc<Mutex<ThreadPool>>,
 	shared_http_client: api::SharedClient,
 	enable_http_requests: bool,
 	keystore: Option<KeystorePtr>,
@@ -138,6 +199,10 @@ pub struct OffchainWorkers<RA, Block: traits::Block, Storage> {
 	network_provider: Arc<dyn NetworkProvider + Send + Sync>,
 	is_validator: bool,
 	custom_extensions: Box<dyn Fn(Block::Hash) -> Vec<Box<dyn Extension>> + Send>,
+	scheduling_policy: SchedulingPolicy,
+	/// Number of workers spawned which did not finish yet, including the queued ones.
+	pending_workers: Arc<AtomicUsize>,
+	metrics: metrics::MetricsLink,
 }
 
 impl<RA, Block: traits::Block, Storage> OffchainWorkers<RA, Block, Storage> {
@@ -152,14 +217,16 @@ impl<RA, Block: traits::Block, Storage> OffchainWorkers<RA, Block, Storage> {
 			is_validator,
 			enable_http_requests,
 			custom_extensions,
+			scheduling_policy,
+			prometheus_registry,
 		}: OffchainWorkerOptions<RA, Block, Storage, CE>,
 	) -> Self {
 		Self {
 			runtime_api_provider,
-			thread_pool: Mutex::new(ThreadPool::with_name(
+			thread_pool: Arc::new(Mutex::new(ThreadPool::with_name(
 				"offchain-worker".into(),
-				num_cpus::get(),
-			)),
+				scheduling_policy.max_concurrent.get(),
+			))),
 			shared_http_client: api::SharedClient::new(),
 			enable_http_requests,
 			keystore,
@@ -168,7 +235,90 @@ impl<RA, Block: traits::Block, Storage> OffchainWorkers<RA, Block, Storage> {
 			is_validator,
 			network_provider,
 			custom_extensions: Box::new(custom_extensions),
+			scheduling_policy,
+			pending_workers: Arc::new(AtomicUsize::new(0)),
+			metrics: metrics::MetricsLink::new(prometheus_registry.as_ref()),
+		}
+	}
+
+	/// Whether the offchain workers of a new block should be skipped, as per the policy.
+	fn is_busy(&self) -> bool {
+		self.scheduling_policy.skip_if_busy &&
+			self.pending_workers.load(Ordering::Acquire) >=
+				self.scheduling_policy.max_concurrent.get()
+	}
+}
+
+/// The slot of a spawned worker among the `max_concurrent` ones.
+///
+/// The slot is freed once the worker finishes or times out. A timed out worker keeps running on a
+/// thread added to the pool until it returns, unless the pool already has `max_threads`.
+struct WorkerSlot {
+	pending_workers: Arc<AtomicUsize>,
+	thread_pool: Arc<Mutex<ThreadPool>>,
+	max_threads: usize,
+	freed: AtomicBool,
+}
+
+impl WorkerSlot {
+	fn new(
+		pending_workers: &Arc<AtomicUsize>,
+		thread_pool: &Arc<Mutex<ThreadPool>>,
+		max_threads: usize,
+	) -> Arc<Self> {
+		pending_workers.fetch_add(1, Ordering::AcqRel);
+		Arc::new(Self {
+			pending_workers: pending_workers.clone(),
+			thread_pool: thread_pool.clone(),
+			max_threads,
+			freed: AtomicBool::new(false),
+		})
+	}
+
+	/// Free the slot of a worker which timed out, adding a thread for the following workers.
+	///
+	/// The slot is kept if the pool can't grow any further.
+	fn time_out(&self) {
+		let mut thread_pool = self.thread_pool.lock();
+		if thread_pool.max_count() >= self.max_threads {
+			tracing::debug!(
+				target: LOG_TARGET,
+				"Timed out offchain worker keeps its slot, {} threads are running",
+				thread_pool.max_count(),
+			);
+			return
+		}
+		if self.free() {
+			let threads = thread_pool.max_count() + 1;
+			thread_pool.set_num_threads(threads);
+		}
+	}
+
+	/// Free the slot of a worker which finished, removing the thread added if it timed out.
+	fn finish(&self) {
+		let mut thread_pool = self.thread_pool.lock();
+		if !self.free() {
+			let threads = thread_pool.max_count() - 1;
+			thread_pool.set_num_threads(threads);
+		}
+	}
+
+	/// Free the slot, returning whether it was still taken.
+	fn free(&self) -> bool {
+		let taken = !self.freed.swap(true, Ordering::AcqRel);
+		if taken {
+			self.pending_workers.fetch_sub(1, Ordering::AcqRel);
 		}
+		taken
+	}
+}
+
+/// Finishes the worker of a slot when dropped, even if the worker panicked.
+struct RunningWorker(Arc<WorkerSlot>);
+
+impl Drop for RunningWorker {
+	fn drop(&mut self) {
+		self.0.finish();
 	}
 }
 
@@ -187,27 +337,45 @@ where
 	RA::Api: OffchainWorkerApi<Block>,
 	Storage: offchain::OffchainStorage + 'static,
 {
-	/// Run the offchain workers on every block import.
+	/// Run the offchain workers on the blocks selected by the [`SchedulingPolicy`].
 	pub async fn run<BE: BlockchainEvents<Block>>(
 		self,
 		import_events: Arc<BE>,
 		spawner: impl SpawnNamed,
 	) {
-		import_events
-			.import_notification_stream()
-			.for_each(move |n| {
-				if n.is_new_best {
+		let headers = match self.scheduling_policy.trigger {
+			WorkerTrigger::NewBest => import_events
+				.import_notification_stream()
+				.filter_map(|n| {
+					if !n.is_new_best {
+						tracing::debug!(
+							target: LOG_TARGET,
+							"Skipping offchain workers for non-canon block: {:?}",
+							n.header,
+						)
+					}
+					ready(n.is_new_best.then_some(n.header))
+				})
+				.boxed(),
+			WorkerTrigger::Finalized =>
+				import_events.finality_notification_stream().map(|n| n.header).boxed(),
+		};
+
+		headers
+			.for_each(move |header| {
+				if self.is_busy() {
+					tracing::debug!(
+						target: LOG_TARGET,
+						"Skipping offchain workers for {:?}, previous ones still running",
+						header.hash(),
+					);
+					self.metrics.report(|metrics| metrics.skipped.inc());
+				} else {
 					spawner.spawn(
 						"offchain-on-block",
 						Some("offchain-worker"),
-						self.on_block_imported(&n.header).boxed(),
+						self.on_block_imported(&header).boxed(),
 					);
-				} else {
-					tracing::debug!(
-						target: LOG_TARGET,
-						"Skipping offchain workers for non-canon block: {:?}",
-						n.header,
-					)
 				}
 
 				ready(())
@@ -259,8 +427,19 @@ where
 			let db = self.offchain_db.clone();
 			let tx_pool = self.transaction_pool.clone();
 			let custom_extensions = (*self.custom_extensions)(hash);
+			let slot = WorkerSlot::new(
+				&self.pending_workers,
+				&self.thread_pool,
+				2 * self.scheduling_policy.max_concurrent.get(),
+			);
+			let running = RunningWorker(slot.clone());
+			let metrics = self.metrics.clone();
+			let (started_sender, started) = futures::channel::oneshot::channel();
 
 			self.spawn_worker(move || {
+				let _running = running;
+				let _ = started_sender.send(());
+				let started = Instant::now();
 				let mut runtime = client.runtime_api();
 				let api = Box::new(api);
 				tracing::debug!(target: LOG_TARGET, "Running offchain workers at {hash:?}");
@@ -300,9 +479,34 @@ where
 						e
 					);
 				}
+
+				metrics.report(|metrics| metrics.duration.observe(started.elapsed().as_secs_f64()));
 			});
 
-			runner.process()
+			let timeout = self.scheduling_policy.timeout;
+			let metrics = self.metrics.clone();
+			async move {
+				let Some(timeout) = timeout else { return runner.process().await };
+				let process = runner.process();
+				// Queued workers only time out once they have been running for `timeout`.
+				let timed_out = async move {
+					match started.await {
+						Ok(()) => futures_timer::Delay::new(timeout).await,
+						Err(_) => futures::future::pending().await,
+					}
+				};
+				futures::pin_mut!(process, timed_out);
+				if let futures::future::Either::Right(_) =
+					futures::future::select(process, timed_out).await
+				{
+					tracing::warn!(
+						target: LOG_TARGET,
+						"Offchain workers at {hash:?} timed out after {timeout:?}",
+					);
+					metrics.report(|metrics| metrics.timed_out.inc());
+					slot.time_out();
+				}
+			}
 		});
 
 		async move {
@@ -446,6 +650,8 @@ mod tests {
 			is_validator: false,
 			enable_http_requests: false,
 			custom_extensions: |_| Vec::new(),
+			scheduling_policy: Default::default(),
+			prometheus_registry: None,
 		});
 		futures::executor::block_on(offchain.on_block_imported(&header));
 
@@ -457,6 +663,84 @@ mod tests {
 		));
 	}
 
+	#[test]
+	fn skips_workers_when_busy_if_configured() {
+		let client = Arc::new(substrate_test_runtime_client::new());
+		let new_offchain = |skip_if_busy| {
+			OffchainWorkers::new(OffchainWorkerOptions {
+				runtime_api_provider: client.clone(),
+				keystore: None,
+				offchain_db: None::<NoOffchainStorage>,
+				transaction_pool: None,
+				network_provider: Arc::new(TestNetwork()),
+				is_validator: false,
+				enable_http_requests: false,
+				custom_extensions: |_| Vec::new(),
+				scheduling_policy: SchedulingPolicy {
+					max_concurrent: NonZeroUsize::new(1).unwrap(),
+					skip_if_busy,
+					..Default::default()
+				},
+				prometheus_registry: None,
+			})
+		};
+
+		let offchain = new_offchain(true);
+		assert!(!offchain.is_busy());
+		let slot = WorkerSlot::new(&offchain.pending_workers, &offchain.thread_pool, 2);
+		assert!(offchain.is_busy());
+		slot.finish();
+		assert!(!offchain.is_busy());
+
+		let offchain = new_offchain(false);
+		let _slot = WorkerSlot::new(&offchain.pending_workers, &offchain.thread_pool, 2);
+		assert!(!offchain.is_busy());
+	}
+
+	#[test]
+	fn timed_out_workers_free_their_slot() {
+		let pending_workers = Arc::new(AtomicUsize::new(0));
+		let thread_pool = Arc::new(Mutex::new(ThreadPool::new(1)));
+		let slot = WorkerSlot::new(&pending_workers, &thread_pool, 2);
+		assert_eq!(pending_workers.load(Ordering::Acquire), 1);
+
+		// The timed out worker keeps running on an extra thread.
+		slot.time_out();
+		assert_eq!(pending_workers.load(Ordering::Acquire), 0);
+		assert_eq!(thread_pool.lock().max_count(), 2);
+
+		slot.finish();
+		assert_eq!(pending_workers.load(Ordering::Acquire), 0);
+		assert_eq!(thread_pool.lock().max_count(), 1);
+
+		// A worker finishing in time doesn't change the pool.
+		let slot = WorkerSlot::new(&pending_workers, &thread_pool, 2);
+		slot.finish();
+		slot.time_out();
+		assert_eq!(pending_workers.load(Ordering::Acquire), 0);
+		assert_eq!(thread_pool.lock().max_count(), 1);
+	}
+
+	#[test]
+	fn timed_out_workers_keep_their_slot_once_the_pool_is_full() {
+		let pending_workers = Arc::new(AtomicUsize::new(0));
+		let thread_pool = Arc::new(Mutex::new(ThreadPool::new(1)));
+		let first = WorkerSlot::new(&pending_workers, &thread_pool, 2);
+		let second = WorkerSlot::new(&pending_workers, &thread_pool, 2);
+
+		first.time_out();
+		second.time_out();
+		assert_eq!(pending_workers.load(Ordering::Acquire), 1);
+		assert_eq!(thread_pool.lock().max_count(), 2);
+
+		// The second worker is finished as if it never timed out.
+		second.finish();
+		assert_eq!(pending_workers.load(Ordering::Acquire), 0);
+		assert_eq!(thread_pool.lock().max_count(), 2);
+		first.finish();
+		assert_eq!(thread_pool.lock().max_count(), 1);
+	}
+
 	#[test]
 	fn offchain_index_set_and_clear_works() {
 		use sp_core::offchain::OffchainStorage;
@@ -0,0 +1,85 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Offchain workers Prometheus metrics.
+
+use std::sync::Arc;
+
+use prometheus_endpoint::{
+	register, Counter, Histogram, HistogramOpts, PrometheusError, Registry, U64,
+};
+
+#[derive(Clone, Default)]
+pub(crate) struct MetricsLink(Arc<Option<Metrics>>);
+
+impl MetricsLink {
+	pub fn new(registry: Option<&Registry>) -> Self {
+		Self(Arc::new(registry.and_then(|registry| {
+			Metrics::register(registry)
+				.map_err(|err| {
+					log::warn!("Failed to register prometheus metrics: {}", err);
+				})
+				.ok()
+		})))
+	}
+
+	pub fn report(&self, do_this: impl FnOnce(&Metrics)) {
+		if let Some(metrics) = self.0.as_ref() {
+			do_this(metrics);
+		}
+	}
+}
+
+/// Offchain workers Prometheus metrics.
+pub(crate) struct Metrics {
+	pub duration: Histogram,
+	pub skipped: Counter<U64>,
+	pub timed_out: Counter<U64>,
+}
+
+impl Metrics {
+	pub fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			duration: register(
+				Histogram::with_opts(
+					HistogramOpts::new(
+						"substrate_offchain_worker_duration_seconds",
+						"Time spent running the offchain workers of a block",
+					)
+					.buckets(vec![0.01, 0.05, 0.1, 0.5, 1.0, 2.0, 5.0, 10.0, 30.0, 60.0]),
+				)?,
+				registry,
+			)?,
+			skipped: register(
+				Counter::new(
+					"substrate_offchain_worker_skipped_total",
+					"Number of blocks whose offchain workers were skipped as the previous ones were \
+					 still running",
+				)?,
+				registry,
+			)?,
+			timed_out: register(
+				Counter::new(
+					"substrate_offchain_worker_timed_out_total",
+					"Number of offchain workers whose host functions were stopped by the timeout",
+				)?,
+				registry,
+			)?,
+		})
+	}
+}
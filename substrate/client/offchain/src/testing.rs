@@ -0,0 +1,158 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Local HTTP server standing in for the external services queried by offchain workers.
+//!
+//! Unlike [`sp_core::offchain::testing::TestOffchainExt`], the requests go through the real HTTP
+//! client of the node, so that the offchain worker only has to be pointed at
+//! [`MockHttpServer::url`]. The responses are configured with the same [`HttpRoute`]s, matched
+//! against the path and query of the requests.
+
+use std::{convert::Infallible, net::SocketAddr, sync::Arc, time::Duration};
+
+use futures::{channel::oneshot, future};
+use hyper::{
+	service::{make_service_fn, service_fn},
+	Body, Request, Response, Server,
+};
+use parking_lot::Mutex;
+use sp_core::offchain::testing::{HttpRoute, RecordedRequest};
+
+#[derive(Default)]
+struct Shared {
+	routes: Vec<HttpRoute>,
+	requests: Vec<RecordedRequest>,
+}
+
+/// HTTP server answering with the configured routes, stopped when dropped.
+pub struct MockHttpServer {
+	addr: SocketAddr,
+	shared: Arc<Mutex<Shared>>,
+	shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl MockHttpServer {
+	/// Start the server on a free local port.
+	pub fn start() -> Self {
+		let shared = Arc::new(Mutex::new(Shared::default()));
+		let (shutdown, shutdown_rx) = oneshot::channel();
+		let (addr_tx, addr_rx) = std::sync::mpsc::channel();
+
+		let server_shared = shared.clone();
+		std::thread::spawn(move || {
+			let rt = tokio::runtime::Builder::new_current_thread()
+				.enable_all()
+				.build()
+				.expect("Failed to build the runtime of the mock HTTP server");
+			rt.block_on(async move {
+				let server =
+					Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service_fn(move |_| {
+						let shared = server_shared.clone();
+						async move {
+							Ok::<_, Infallible>(service_fn(move |req| respond(shared.clone(), req)))
+						}
+					}));
+				let _ = addr_tx.send(server.local_addr());
+				// Connections still waiting for a response are dropped with the runtime.
+				let _ = future::select(Box::pin(server), shutdown_rx).await;
+			});
+		});
+
+		let addr = addr_rx.recv().expect("The mock HTTP server failed to start");
+		Self { addr, shared, shutdown: Some(shutdown) }
+	}
+
+	/// Address the server listens on.
+	pub fn addr(&self) -> SocketAddr {
+		self.addr
+	}
+
+	/// URL of `path` on the server, e.g. `/price/dot`.
+	pub fn url(&self, path: &str) -> String {
+		format!("http://{}{}", self.addr, path)
+	}
+
+	/// Add a route matched against the path and query of the requests.
+	///
+	/// Routes are matched in the insertion order, requests matching none are answered with
+	/// `404 Not Found`.
+	pub fn add_route(&self, route: HttpRoute) {
+		self.shared.lock().routes.push(route);
+	}
+
+	/// Return the requests received since the last call, with the path and query as `uri`.
+	pub fn take_requests(&self) -> Vec<RecordedRequest> {
+		std::mem::take(&mut self.shared.lock().requests)
+	}
+}
+
+impl Drop for MockHttpServer {
+	fn drop(&mut self) {
+		if let Some(shutdown) = self.shutdown.take() {
+			let _ = shutdown.send(());
+		}
+	}
+}
+
+async fn respond(
+	shared: Arc<Mutex<Shared>>,
+	req: Request<Body>,
+) -> Result<Response<Body>, hyper::Error> {
+	let (parts, body) = req.into_parts();
+	let request = RecordedRequest {
+		method: parts.method.to_string(),
+		uri: parts.uri.path_and_query().map_or("/", |p| p.as_str()).to_owned(),
+		headers: parts
+			.headers
+			.iter()
+			.map(|(name, value)| {
+				(name.to_string(), String::from_utf8_lossy(value.as_bytes()).into_owned())
+			})
+			.collect(),
+		body: hyper::body::to_bytes(body).await?.to_vec(),
+	};
+
+	let response = {
+		let mut shared = shared.lock();
+		let response = shared
+			.routes
+			.iter()
+			.find(|route| route.matches(&request.method, &request.uri))
+			.map(|route| route.response().clone());
+		shared.requests.push(request);
+		response
+	};
+	let Some(response) = response else {
+		let mut not_found = Response::new(Body::empty());
+		*not_found.status_mut() = hyper::StatusCode::NOT_FOUND;
+		return Ok(not_found)
+	};
+
+	match response.latency {
+		Some(latency) => tokio::time::sleep(Duration::from_millis(latency.millis())).await,
+		None => future::pending().await,
+	}
+
+	let mut builder = Response::builder().status(response.status);
+	for (name, value) in &response.headers {
+		builder = builder.header(name, value);
+	}
+	Ok(builder
+		.body(Body::from(response.body))
+		.expect("Invalid status or header in the mock response"))
+}
@@ -0,0 +1,63 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! IPFS RPC errors.
+
+use jsonrpsee::{
+	core::Error as JsonRpseeError,
+	types::error::{CallError, ErrorObject, INVALID_PARAMS_CODE},
+};
+
+/// IPFS RPC Result type.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// IPFS RPC errors.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+	/// Client error.
+	#[error("Client error: {}", .0)]
+	Client(Box<dyn std::error::Error + Send + Sync>),
+	/// The CID is invalid or does not reference a 256-bit Blake2b hash.
+	#[error("Invalid CID: {0}")]
+	InvalidCid(String),
+	/// The content was found neither locally nor on the connected peers.
+	#[error("Content {0} not found")]
+	NotFound(String),
+	/// The content is not pinned.
+	#[error("Content {0} is not pinned")]
+	NotPinned(String),
+	/// Call to an unsafe RPC was denied.
+	#[error(transparent)]
+	UnsafeRpcCalled(#[from] crate::policy::UnsafeRpcError),
+}
+
+/// Base error code for all IPFS errors.
+const BASE_ERROR: i32 = crate::error::base::IPFS;
+
+impl From<Error> for JsonRpseeError {
+	fn from(e: Error) -> Self {
+		let code = match e {
+			Error::Client(_) => BASE_ERROR + 1,
+			Error::InvalidCid(_) => INVALID_PARAMS_CODE,
+			Error::NotFound(_) => BASE_ERROR + 3,
+			Error::NotPinned(_) => BASE_ERROR + 4,
+			Error::UnsafeRpcCalled(err) => return err.into(),
+		};
+		CallError::Custom(ErrorObject::owned(code, e.to_string(), None::<()>)).into()
+	}
+}
@@ -0,0 +1,53 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Substrate IPFS API.
+
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use sp_core::Bytes;
+
+pub mod error;
+
+/// Substrate IPFS RPC API
+///
+/// Content is addressed by the version 1 CID of its 256-bit Blake2b hash, like the indexed
+/// transactions served over bitswap.
+#[rpc(client, server)]
+pub trait IpfsApi {
+	/// Store and pin the given content, returning its CID.
+	#[method(name = "ipfs_add")]
+	fn add(&self, data: Bytes) -> RpcResult<String>;
+
+	/// Get the content with the given CID, fetching it from the connected peers if it is not
+	/// stored locally. Unsafe, as fetching content costs network bandwidth.
+	#[method(name = "ipfs_get")]
+	async fn get(&self, cid: String) -> RpcResult<Bytes>;
+
+	/// Pin the content with the given CID, fetching it from the connected peers if it is not
+	/// stored locally. The content is kept and served over bitswap until it is unpinned.
+	#[method(name = "offchain_pinContent")]
+	async fn pin_content(&self, cid: String) -> RpcResult<()>;
+
+	/// Unpin the content with the given CID.
+	#[method(name = "offchain_unpinContent")]
+	fn unpin_content(&self, cid: String) -> RpcResult<()>;
+
+	/// Returns the CIDs of the pinned content. Unsafe, as it discloses the local pin state.
+	#[method(name = "offchain_pinnedContent")]
+	fn pinned_content(&self) -> RpcResult<Vec<String>>;
+}
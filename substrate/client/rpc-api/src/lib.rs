@@ -24,13 +24,16 @@
 
 mod error;
 mod policy;
+mod stream;
 
 pub use policy::DenyUnsafe;
+pub use stream::StreamEvent;
 
 pub mod author;
 pub mod chain;
 pub mod child_state;
 pub mod dev;
+pub mod ipfs;
 pub mod mixnet;
 pub mod offchain;
 pub mod state;
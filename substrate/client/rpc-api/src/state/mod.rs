@@ -25,6 +25,8 @@ use sp_core::{
 };
 use sp_version::RuntimeVersion;
 
+use crate::StreamEvent;
+
 pub mod error;
 pub mod helpers;
 
@@ -129,6 +131,34 @@ pub trait StateApi<Hash> {
 	)]
 	fn subscribe_storage(&self, keys: Option<Vec<StorageKey>>);
 
+	/// Stream the storage entries with the given prefix in chunks of at most `chunk_size`.
+	///
+	/// Unlike `state_getPairs`, the result is not limited by the maximum response size.
+	#[subscription(
+		name = "state_streamPairs" => "state_pairsChunk",
+		unsubscribe = "state_unstreamPairs",
+		item = StreamEvent<(StorageKey, StorageData)>,
+	)]
+	fn stream_pairs(&self, prefix: StorageKey, chunk_size: Option<u32>, hash: Option<Hash>);
+
+	/// Stream up to `count` keys with the given prefix in chunks of at most `chunk_size`.
+	///
+	/// If `start_key` is passed, the keys following it in lexicographic order are streamed.
+	/// Unlike `state_getKeysPaged`, the result is not limited by the maximum response size.
+	#[subscription(
+		name = "state_streamKeys" => "state_keysChunk",
+		unsubscribe = "state_unstreamKeys",
+		item = StreamEvent<StorageKey>,
+	)]
+	fn stream_keys(
+		&self,
+		prefix: Option<StorageKey>,
+		count: u32,
+		start_key: Option<StorageKey>,
+		chunk_size: Option<u32>,
+		hash: Option<Hash>,
+	);
+
 	/// The `traceBlock` RPC provides a way to trace the re-execution of a single
 	/// block, collecting Spans and Events from both the client and the relevant WASM runtime.
 	/// The Spans and Events are conceptually equivalent to those from the [Tracing][1] crate.
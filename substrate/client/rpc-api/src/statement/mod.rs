@@ -19,10 +19,56 @@
 //! Substrate Statement Store RPC API.
 
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
-use sp_core::Bytes;
+use serde::{Deserialize, Serialize};
+use sp_core::{Bytes, H256};
+use std::collections::BTreeMap;
+
+use crate::StreamEvent;
 
 pub mod error;
 
+/// Global limits of the statement store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatementStoreLimits {
+	/// Maximum number of statements in the store.
+	pub max_total_statements: u64,
+	/// Maximum total data size of the statements in the store, in bytes.
+	pub max_total_size: u64,
+}
+
+/// Usage of the statement store.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatementStoreStatus {
+	/// Current global limits.
+	pub limits: StatementStoreLimits,
+	/// Number of statements in the store.
+	pub total_statements: u64,
+	/// Total data size of the statements in the store, in bytes.
+	pub total_size: u64,
+	/// Number of removed statements that can't be added back yet.
+	pub expired_statements: u64,
+	/// Number of statements rejected since the node started, by reason.
+	pub rejections: BTreeMap<String, u64>,
+}
+
+/// Event of a `statement_subscribe` subscription.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "event")]
+pub enum StatementEvent {
+	/// A matching statement was accepted into the store.
+	New {
+		/// The SCALE-encoded statement.
+		statement: Bytes,
+	},
+	/// A matching statement expired and was removed from the store.
+	Expired {
+		/// The hash of the statement.
+		hash: H256,
+	},
+}
+
 /// Substrate statement RPC API
 #[rpc(client, server)]
 pub trait StatementApi {
@@ -30,6 +76,16 @@ pub trait StatementApi {
 	#[method(name = "statement_dump")]
 	fn dump(&self) -> RpcResult<Vec<Bytes>>;
 
+	/// Stream all statements, SCALE-encoded, in chunks of at most `chunk_size`.
+	///
+	/// Unlike `statement_dump`, the result is not limited by the maximum response size.
+	#[subscription(
+		name = "statement_streamDump" => "statement_dumpChunk",
+		unsubscribe = "statement_unstreamDump",
+		item = StreamEvent<Bytes>,
+	)]
+	fn stream_dump(&self, chunk_size: Option<u32>);
+
 	/// Return the data of all known statements which include all topics and have no `DecryptionKey`
 	/// field.
 	#[method(name = "statement_broadcasts")]
@@ -57,4 +113,27 @@ pub trait StatementApi {
 	/// Remove a statement from the store.
 	#[method(name = "statement_remove")]
 	fn remove(&self, statement_hash: [u8; 32]) -> RpcResult<()>;
+
+	/// Return the usage and limits of the statement store, and why statements were rejected.
+	#[method(name = "statement_status")]
+	fn status(&self) -> RpcResult<StatementStoreStatus>;
+
+	/// Change the global limits of the statement store.
+	///
+	/// The limits are kept across restarts, until the node is started with other configured
+	/// limits. If the store is above the new limits, the lowest priority statements of the
+	/// accounts using the most space are removed.
+	#[method(name = "statement_setLimits")]
+	fn set_limits(&self, limits: StatementStoreLimits) -> RpcResult<()>;
+
+	/// Subscribe to the statements which include all topics and whose decryption key is
+	/// identified as `dest`, or which have no `DecryptionKey` field if `dest` is not passed.
+	///
+	/// Only statements accepted into or expiring from the store after subscribing are notified.
+	#[subscription(
+		name = "statement_subscribe" => "statement_event",
+		unsubscribe = "statement_unsubscribe",
+		item = StatementEvent,
+	)]
+	fn subscribe(&self, match_all_topics: Vec<[u8; 32]>, dest: Option<[u8; 32]>);
 }
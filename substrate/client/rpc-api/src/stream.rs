@@ -0,0 +1,64 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Results streamed in chunks by subscriptions.
+
+use serde::{Deserialize, Serialize};
+
+/// Event of a subscription streaming a result in chunks.
+///
+/// The subscription emits any number of [`StreamEvent::Items`] followed by either
+/// [`StreamEvent::Done`] or [`StreamEvent::Error`], after which no further events are emitted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "event")]
+pub enum StreamEvent<T> {
+	/// The next chunk of the result.
+	Items {
+		/// The items of the chunk.
+		items: Vec<T>,
+	},
+	/// The whole result has been streamed.
+	Done,
+	/// Building the result failed.
+	Error {
+		/// The reason of the failure.
+		error: String,
+	},
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn stream_event_serialization() {
+		let items = StreamEvent::Items { items: vec![1, 2] };
+		let json = serde_json::to_string(&items).unwrap();
+		assert_eq!(json, r#"{"event":"items","items":[1,2]}"#);
+		assert_eq!(serde_json::from_str::<StreamEvent<u32>>(&json).unwrap(), items);
+
+		assert_eq!(
+			serde_json::to_string(&StreamEvent::<u32>::Done).unwrap(),
+			r#"{"event":"done"}"#
+		);
+		assert_eq!(
+			serde_json::to_string(&StreamEvent::<u32>::Error { error: "failed".into() }).unwrap(),
+			r#"{"event":"error","error":"failed"}"#
+		);
+	}
+}
@@ -36,6 +36,9 @@ pub enum Error {
 	/// Peer argument is malformatted.
 	#[error("{0}")]
 	MalformattedPeerArg(String),
+	/// The peer or IP range to unban is not banned.
+	#[error("{0} is not banned")]
+	NotBanned(String),
 }
 
 // Base code for all system errors.
@@ -44,6 +47,8 @@ const BASE_ERROR: i32 = crate::error::base::SYSTEM;
 const NOT_HEALTHY_ERROR: i32 = BASE_ERROR + 1;
 // Peer argument is malformatted.
 const MALFORMATTED_PEER_ARG_ERROR: i32 = BASE_ERROR + 2;
+// The peer or IP range to unban is not banned.
+const NOT_BANNED_ERROR: i32 = BASE_ERROR + 3;
 
 impl From<Error> for JsonRpseeError {
 	fn from(e: Error) -> Self {
@@ -55,6 +60,8 @@ impl From<Error> for JsonRpseeError {
 				e,
 				None::<()>,
 			)),
+			Error::NotBanned(_) =>
+				CallError::Custom(ErrorObject::owned(NOT_BANNED_ERROR, e.to_string(), None::<()>)),
 		}
 		.into()
 	}
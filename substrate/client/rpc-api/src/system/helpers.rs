@@ -71,6 +71,16 @@ pub struct PeerInfo<Hash, Number> {
 	pub best_number: Number,
 }
 
+/// Peer or range of IP addresses banned by the node.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BannedPeer {
+	/// Banned base58-encoded PeerId, or IP range in CIDR notation.
+	pub target: String,
+	/// Seconds since the UNIX epoch at which the ban expires, `None` if it is permanent.
+	pub expires_at: Option<u64>,
+}
+
 /// The role the node is running as
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub enum NodeRole {
@@ -92,6 +102,74 @@ pub struct SyncState<Number> {
 	pub highest_block: Number,
 }
 
+/// Status of the node, as printed by the informant.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeStatus<Hash, Number> {
+	/// Number of the best block.
+	pub best_number: Number,
+	/// Hash of the best block.
+	pub best_hash: Hash,
+	/// Number of the last finalized block.
+	pub finalized_number: Number,
+	/// Hash of the last finalized block.
+	pub finalized_hash: Hash,
+	/// Number of peers known to the syncing engine.
+	pub peers: u32,
+	/// Current phase of the syncing.
+	pub sync: SyncPhase<Number>,
+	/// Number of blocks queued for import.
+	pub queued_blocks: u32,
+	/// Total number of bytes received since the start of the node.
+	pub total_bytes_inbound: u64,
+	/// Total number of bytes sent since the start of the node.
+	pub total_bytes_outbound: u64,
+	/// Average number of bytes received per second since the previous status.
+	pub bytes_inbound_per_sec: u64,
+	/// Average number of bytes sent per second since the previous status.
+	pub bytes_outbound_per_sec: u64,
+}
+
+/// Phase of the syncing of the node.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "camelCase")]
+pub enum SyncPhase<Number> {
+	/// Downloading the history of a warp synced chain, once the tip has been reached.
+	BlockHistory {
+		/// Number of the last downloaded block.
+		number: Number,
+	},
+	/// Warp sync is waiting for its target block.
+	AwaitingTargetBlock,
+	/// Warp syncing to the tip of the chain.
+	#[serde(rename_all = "camelCase")]
+	Warping {
+		/// Description of the warp sync phase.
+		phase: String,
+		/// Total bytes downloaded so far.
+		total_bytes: u64,
+	},
+	/// Downloading the state of a block.
+	DownloadingState {
+		/// Estimated download percentage.
+		percentage: u32,
+		/// Total state size in bytes downloaded so far.
+		size: u64,
+	},
+	/// Following the tip of the chain.
+	Idle,
+	/// Catching up with the chain.
+	Downloading {
+		/// Number of the block synced to.
+		target: Number,
+	},
+	/// All blocks are downloaded and being imported.
+	Importing {
+		/// Number of the block synced to.
+		target: Number,
+	},
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -145,4 +223,25 @@ mod tests {
 			r#"{"startingBlock":12,"currentBlock":50,"highestBlock":50}"#,
 		);
 	}
+
+	#[test]
+	fn should_serialize_node_status() {
+		assert_eq!(
+			::serde_json::to_string(&NodeStatus {
+				best_number: 50u32,
+				best_hash: 5u32,
+				finalized_number: 48u32,
+				finalized_hash: 4u32,
+				peers: 3,
+				sync: SyncPhase::Downloading { target: 60u32 },
+				queued_blocks: 2,
+				total_bytes_inbound: 1000,
+				total_bytes_outbound: 500,
+				bytes_inbound_per_sec: 10,
+				bytes_outbound_per_sec: 5,
+			})
+			.unwrap(),
+			r#"{"bestNumber":50,"bestHash":5,"finalizedNumber":48,"finalizedHash":4,"peers":3,"sync":{"state":"downloading","target":60},"queuedBlocks":2,"totalBytesInbound":1000,"totalBytesOutbound":500,"bytesInboundPerSec":10,"bytesOutboundPerSec":5}"#,
+		);
+	}
 }
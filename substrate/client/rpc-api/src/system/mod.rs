@@ -23,7 +23,9 @@ use jsonrpsee::{
 	proc_macros::rpc,
 };
 
-pub use self::helpers::{Health, NodeRole, PeerInfo, SyncState, SystemInfo};
+pub use self::helpers::{
+	BannedPeer, Health, NodeRole, NodeStatus, PeerInfo, SyncPhase, SyncState, SystemInfo,
+};
 
 pub mod error;
 pub mod helpers;
@@ -83,6 +85,13 @@ pub trait SystemApi<Hash, Number> {
 	#[method(name = "system_unstable_networkState")]
 	async fn system_network_state(&self) -> RpcResult<JsonValue>;
 
+	/// Returns a snapshot of the status printed by the informant: best and finalized blocks,
+	/// peers, sync state, bandwidth and import queue.
+	///
+	/// **Warning**: This API is not stable. Its output may change between releases.
+	#[method(name = "system_unstable_status")]
+	async fn system_status(&self) -> RpcResult<NodeStatus<Hash, Number>>;
+
 	/// Adds a reserved peer. Returns the empty string or an error. The string
 	/// parameter should encode a `p2p` multiaddr.
 	///
@@ -100,6 +109,24 @@ pub trait SystemApi<Hash, Number> {
 	#[method(name = "system_reservedPeers")]
 	async fn system_reserved_peers(&self) -> RpcResult<Vec<String>>;
 
+	/// Bans a peer or a range of IP addresses for `duration` seconds, or permanently if no
+	/// duration is given. The bans are kept across restarts.
+	///
+	/// The target is either a base58-encoded PeerId, an IP address or an IP range in CIDR
+	/// notation, e.g. `198.51.100.0/24`. Connections from and to the banned peers and addresses
+	/// are refused, and a banned PeerId is disconnected right away.
+	#[method(name = "system_banPeer")]
+	async fn system_ban_peer(&self, target: String, duration: Option<u64>) -> RpcResult<()>;
+
+	/// Lifts a ban set with `system_banPeer`. The target must be given as returned by
+	/// `system_bannedPeers`.
+	#[method(name = "system_unbanPeer")]
+	async fn system_unban_peer(&self, target: String) -> RpcResult<()>;
+
+	/// Returns the peers and IP ranges banned with `system_banPeer`.
+	#[method(name = "system_bannedPeers")]
+	async fn system_banned_peers(&self) -> RpcResult<Vec<BannedPeer>>;
+
 	/// Returns the roles the node is running as.
 	#[method(name = "system_nodeRoles")]
 	async fn system_node_roles(&self) -> RpcResult<Vec<NodeRole>>;
@@ -21,7 +21,12 @@
 #![warn(missing_docs)]
 
 pub mod middleware;
+mod proxy;
 
+use crate::{
+	middleware::rate_limit::RateLimiter,
+	proxy::{Proxy, ProxySecretLayer},
+};
 use http::header::HeaderValue;
 use jsonrpsee::{
 	server::{
@@ -33,7 +38,10 @@ use jsonrpsee::{
 use std::{error::Error as StdError, net::SocketAddr};
 use tower_http::cors::{AllowOrigin, CorsLayer};
 
-pub use crate::middleware::RpcMetrics;
+pub use crate::middleware::{
+	rate_limit::{RateLimit, RpcRateLimit},
+	RpcMetrics,
+};
 pub use jsonrpsee::core::{
 	id_providers::{RandomIntegerIdProvider, RandomStringIdProvider},
 	traits::IdProvider,
@@ -47,8 +55,8 @@ pub type Server = ServerHandle;
 /// RPC server configuration.
 #[derive(Debug)]
 pub struct Config<'a, M: Send + Sync + 'static> {
-	/// Socket addresses.
-	pub addrs: [SocketAddr; 2],
+	/// Socket addresses, the server listens on the first one it can bind to.
+	pub addrs: Vec<SocketAddr>,
 	/// CORS.
 	pub cors: Option<&'a Vec<String>>,
 	/// Maximum connections.
@@ -59,10 +67,18 @@ pub struct Config<'a, M: Send + Sync + 'static> {
 	pub max_payload_in_mb: u32,
 	/// Maximum rpc response payload size.
 	pub max_payload_out_mb: u32,
+	/// Maximum number of calls in a batch request, `None` if not limited.
+	pub max_batch_len: Option<u32>,
 	/// Metrics.
 	pub metrics: Option<RpcMetrics>,
+	/// Rate limits of the clients.
+	pub rate_limit: RpcRateLimit,
 	/// RPC API.
 	pub rpc_api: RpcModule<M>,
+	/// Methods to expose, `None` to expose all of `rpc_api`.
+	///
+	/// Entries are either method names or prefixes followed by `*`, e.g. `chainHead_*`.
+	pub allowed_methods: Option<Vec<String>>,
 	/// Subscription ID provider.
 	pub id_provider: Option<Box<dyn IdProvider>>,
 	/// Tokio runtime handle.
@@ -78,30 +94,50 @@ pub async fn start_server<M: Send + Sync + 'static>(
 		cors,
 		max_payload_in_mb,
 		max_payload_out_mb,
+		max_batch_len,
 		max_connections,
 		max_subs_per_conn,
 		metrics,
+		rate_limit,
 		id_provider,
 		tokio_handle,
 		rpc_api,
+		allowed_methods,
 	} = config;
 
+	let max_request_body_size = max_payload_in_mb.saturating_mul(MEGABYTE);
+
 	let host_filter = hosts_filtering(cors.is_some(), &addrs);
 
+	// The server only listens on a loopback address behind the proxy enforcing the limits.
+	let proxy = if rate_limit.is_enabled() || max_batch_len.is_some() {
+		let limiter = RateLimiter::new(rate_limit, metrics.clone());
+		let proxy =
+			Proxy::bind(&addrs, limiter, max_batch_len, max_connections, max_request_body_size)
+				.await?;
+		Some(proxy)
+	} else {
+		None
+	};
+	let server_addrs =
+		if proxy.is_some() { vec![SocketAddr::from(([127, 0, 0, 1], 0))] } else { addrs };
+
 	let middleware = tower::ServiceBuilder::new()
+		// Reject the requests that bypass the proxy.
+		.layer(proxy.as_ref().map_or(ProxySecretLayer(None), Proxy::secret_layer))
 		// Proxy `GET /health` requests to internal `system_health` method.
 		.layer(ProxyGetRequestLayer::new("/health", "system_health")?)
 		.layer(try_into_cors(cors)?);
 
 	let mut builder = ServerBuilder::new()
-		.max_request_body_size(max_payload_in_mb.saturating_mul(MEGABYTE))
+		.max_request_body_size(max_request_body_size)
 		.max_response_body_size(max_payload_out_mb.saturating_mul(MEGABYTE))
 		.max_connections(max_connections)
 		.max_subscriptions_per_connection(max_subs_per_conn)
 		.ping_interval(std::time::Duration::from_secs(30))
 		.set_host_filtering(host_filter)
 		.set_middleware(middleware)
-		.custom_tokio_runtime(tokio_handle);
+		.custom_tokio_runtime(tokio_handle.clone());
 
 	if let Some(provider) = id_provider {
 		builder = builder.set_id_provider(provider);
@@ -109,17 +145,23 @@ pub async fn start_server<M: Send + Sync + 'static>(
 		builder = builder.set_id_provider(RandomStringIdProvider::new(16));
 	};
 
-	let rpc_api = build_rpc_api(rpc_api);
-	let (handle, addr) = if let Some(metrics) = metrics {
-		let server = builder.set_logger(metrics).build(&addrs[..]).await?;
+	let rpc_api = build_rpc_api(rpc_api, allowed_methods.as_deref());
+	let (handle, mut addr) = if let Some(metrics) = metrics {
+		let server = builder.set_logger(metrics).build(&server_addrs[..]).await?;
 		let addr = server.local_addr();
 		(server.start(rpc_api)?, addr)
 	} else {
-		let server = builder.build(&addrs[..]).await?;
+		let server = builder.build(&server_addrs[..]).await?;
 		let addr = server.local_addr();
 		(server.start(rpc_api)?, addr)
 	};
 
+	if let Some(proxy) = proxy {
+		let server_addr = addr?;
+		addr = Ok(proxy.local_addr()?);
+		tokio_handle.spawn(proxy.run(server_addr, handle.clone().stopped()));
+	}
+
 	log::info!(
 		"Running JSON-RPC server: addr={}, allowed origins={}",
 		addr.map_or_else(|_| "unknown".to_string(), |a| a.to_string()),
@@ -143,7 +185,20 @@ fn hosts_filtering(enabled: bool, addrs: &[SocketAddr]) -> AllowHosts {
 	}
 }
 
-fn build_rpc_api<M: Send + Sync + 'static>(mut rpc_api: RpcModule<M>) -> RpcModule<M> {
+fn build_rpc_api<M: Send + Sync + 'static>(
+	mut rpc_api: RpcModule<M>,
+	allowed_methods: Option<&[String]>,
+) -> RpcModule<M> {
+	if let Some(allowed) = allowed_methods {
+		let denied = rpc_api
+			.method_names()
+			.filter(|method| !is_method_allowed(allowed, method))
+			.collect::<Vec<_>>();
+		for method in denied {
+			rpc_api.remove_method(method);
+		}
+	}
+
 	let mut available_methods = rpc_api.method_names().collect::<Vec<_>>();
 	// The "rpc_methods" is defined below and we want it to be part of the reported methods.
 	available_methods.push("rpc_methods");
@@ -160,6 +215,14 @@ fn build_rpc_api<M: Send + Sync + 'static>(mut rpc_api: RpcModule<M>) -> RpcModu
 	rpc_api
 }
 
+/// Whether `method` is one of the `allowed` names or starts with one of the `allowed` prefixes.
+fn is_method_allowed(allowed: &[String], method: &str) -> bool {
+	allowed.iter().any(|allowed| match allowed.strip_suffix('*') {
+		Some(prefix) => method.starts_with(prefix),
+		None => allowed == method,
+	})
+}
+
 fn try_into_cors(
 	maybe_cors: Option<&Vec<String>>,
 ) -> Result<CorsLayer, Box<dyn StdError + Send + Sync>> {
@@ -182,3 +245,31 @@ fn format_cors(maybe_cors: Option<&Vec<String>>) -> String {
 		format!("{:?}", ["*"])
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn methods_are_allowed_by_name_or_prefix() {
+		let allowed = vec!["chainHead_*".to_string(), "system_health".to_string()];
+
+		assert!(is_method_allowed(&allowed, "chainHead_unstable_follow"));
+		assert!(is_method_allowed(&allowed, "system_health"));
+		assert!(!is_method_allowed(&allowed, "system_healthy"));
+		assert!(!is_method_allowed(&allowed, "author_submitExtrinsic"));
+	}
+
+	#[test]
+	fn disallowed_methods_are_removed() {
+		let mut module = RpcModule::new(());
+		for method in ["chainHead_unstable_follow", "author_rotateKeys", "system_health"] {
+			module.register_method(method, |_, _| Ok(())).unwrap();
+		}
+
+		let module = build_rpc_api(module, Some(&["chainHead_*".to_string()]));
+		let mut methods = module.method_names().collect::<Vec<_>>();
+		methods.sort();
+		assert_eq!(methods, vec!["chainHead_unstable_follow", "rpc_methods"]);
+	}
+}
@@ -18,6 +18,15 @@
 
 //! RPC middleware to collect prometheus metrics on RPC calls.
 
+pub mod batch_limit;
+pub mod rate_limit;
+
+use http::{
+	header::{HeaderValue, CONTENT_LENGTH, CONTENT_TYPE},
+	request::Parts,
+	Request, Response, StatusCode,
+};
+use hyper::body::{Body, HttpBody};
 use jsonrpsee::server::logger::{HttpRequest, Logger, MethodKind, Params, TransportProtocol};
 use prometheus_endpoint::{
 	register, Counter, CounterVec, HistogramOpts, HistogramVec, Opts, PrometheusError, Registry,
@@ -54,6 +63,8 @@ pub struct RpcMetrics {
 	calls_started: CounterVec<U64>,
 	/// Number of calls completed.
 	calls_finished: CounterVec<U64>,
+	/// Number of calls rejected by the rate limiter.
+	calls_throttled: CounterVec<U64>,
 	/// Number of Websocket sessions opened.
 	ws_sessions_opened: Option<Counter<U64>>,
 	/// Number of Websocket sessions closed.
@@ -116,6 +127,16 @@ impl RpcMetrics {
 					)?,
 					metrics_registry,
 				)?,
+				calls_throttled: register(
+					CounterVec::new(
+						Opts::new(
+							"substrate_rpc_calls_throttled",
+							"Number of RPC calls rejected because the client exceeded its rate limit",
+						),
+						&["protocol", "method"],
+					)?,
+					metrics_registry,
+				)?,
 				ws_sessions_opened: register(
 					Counter::new(
 						"substrate_rpc_sessions_opened",
@@ -137,6 +158,13 @@ impl RpcMetrics {
 			Ok(None)
 		}
 	}
+
+	/// Note a call rejected by the rate limiter.
+	pub(crate) fn on_throttled(&self, transport: TransportProtocol, name: &str) {
+		self.calls_throttled
+			.with_label_values(&[transport_label_str(transport), name])
+			.inc();
+	}
 }
 
 impl Logger for RpcMetrics {
@@ -222,3 +250,55 @@ fn transport_label_str(t: TransportProtocol) -> &'static str {
 		TransportProtocol::WebSocket => "ws",
 	}
 }
+
+/// A request body read by a middleware.
+pub(crate) enum ReadBody {
+	/// The body was read into memory.
+	Read(Parts, Vec<u8>),
+	/// The body was not read because its announced length is too large.
+	///
+	/// The server rejects such requests without processing them.
+	Unread(Request<Body>),
+	/// The body turned out to be too large while reading it.
+	TooLarge,
+}
+
+/// Read the body of `req` into memory, unless it is larger than `max_size` bytes.
+pub(crate) async fn read_body(req: Request<Body>, max_size: u32) -> Result<ReadBody, hyper::Error> {
+	let too_large = |len: u64| len > max_size as u64;
+	let content_length = req
+		.headers()
+		.get(CONTENT_LENGTH)
+		.and_then(|v| v.to_str().ok())
+		.and_then(|v| v.parse::<u64>().ok());
+	if content_length.map_or(false, too_large) {
+		return Ok(ReadBody::Unread(req))
+	}
+
+	let (parts, mut body) = req.into_parts();
+	let mut bytes = Vec::new();
+	while let Some(chunk) = body.data().await {
+		bytes.extend_from_slice(&chunk?);
+		if too_large(bytes.len() as u64) {
+			return Ok(ReadBody::TooLarge)
+		}
+	}
+
+	Ok(ReadBody::Read(parts, bytes))
+}
+
+/// The response to a request whose body exceeds the maximum size.
+pub(crate) fn payload_too_large() -> Response<Body> {
+	Response::builder()
+		.status(StatusCode::PAYLOAD_TOO_LARGE)
+		.body(Body::empty())
+		.expect("Status code and empty body are valid; qed")
+}
+
+/// A response carrying the JSON-RPC response `body`.
+pub(crate) fn json_response(body: &serde_json::Value) -> Response<Body> {
+	Response::builder()
+		.header(CONTENT_TYPE, HeaderValue::from_static("application/json; charset=utf-8"))
+		.body(Body::from(body.to_string()))
+		.expect("Header and body are valid; qed")
+}
@@ -0,0 +1,73 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! RPC middleware to limit the number of calls in a batch request.
+//!
+//! Like the rate limits, the limit is enforced by the proxy in front of the server, so it applies
+//! to batches sent over HTTP and over a WebSocket connection alike.
+
+use serde_json::Value;
+
+/// JSON-RPC error code of batch requests with too many calls.
+pub const BATCH_TOO_LARGE_CODE: i32 = -32010;
+
+/// JSON-RPC error message of batch requests with too many calls.
+pub const BATCH_TOO_LARGE_MSG: &str = "Batch request exceeds the maximum number of calls";
+
+/// Whether `request` is a batch request with more than `max_batch_len` calls.
+///
+/// Invalid requests are left to the server to reject.
+pub(crate) fn exceeds(request: &Value, max_batch_len: u32) -> bool {
+	let batch_len = request.as_array().map(Vec::len);
+	if batch_len.map_or(false, |len| len > max_batch_len as usize) {
+		log::debug!(
+			target: "rpc",
+			"Rejected batch request of {:?} calls, at most {} are allowed",
+			batch_len,
+			max_batch_len,
+		);
+		return true
+	}
+	false
+}
+
+/// The response to a batch request with too many calls.
+pub(crate) fn batch_too_large_response() -> Value {
+	serde_json::json!({
+		"jsonrpc": "2.0",
+		"error": { "code": BATCH_TOO_LARGE_CODE, "message": BATCH_TOO_LARGE_MSG },
+		"id": null,
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::json;
+
+	#[test]
+	fn only_batches_exceeding_the_limit_are_rejected() {
+		let call = json!({ "jsonrpc": "2.0", "method": "system_health", "id": 1 });
+		assert!(!exceeds(&call, 0));
+		assert!(exceeds(&json!([call.clone()]), 0));
+		assert!(!exceeds(&json!([call.clone(), call.clone()]), 2));
+		assert!(exceeds(&json!([call.clone(), call.clone(), call]), 2));
+		// Invalid requests are left to the server to reject.
+		assert!(!exceeds(&Value::Null, 0));
+	}
+}
@@ -0,0 +1,438 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! RPC middleware to rate limit the calls of clients.
+//!
+//! Every client is given a token bucket per connection and per IP address. Each call takes as many
+//! tokens from the buckets as its method costs. Calls of a client without enough tokens left are
+//! rejected with a JSON-RPC error carrying [`RATE_LIMITED_CODE`].
+//!
+//! The server does not expose the remote address of a connection nor the messages sent over a
+//! WebSocket connection to its middleware, so the limits are enforced by a proxy accepting the
+//! connections of the clients in front of the server. It parses every HTTP request and every
+//! WebSocket message once and charges the client for its calls. The server only accepts the
+//! requests forwarded by the proxy.
+
+use super::RpcMetrics;
+use http::header::HeaderMap;
+use jsonrpsee::server::logger::TransportProtocol;
+use parking_lot::Mutex;
+use schnellru::{ByLength, LruMap};
+use serde_json::Value;
+use std::{
+	collections::HashMap,
+	net::{IpAddr, Ipv6Addr, SocketAddr},
+	num::NonZeroU32,
+	sync::Arc,
+	time::Instant,
+};
+
+/// JSON-RPC error code of calls rejected by the rate limiter.
+///
+/// Same as the "limit exceeded" code of EIP-1474, which is understood by most JSON-RPC clients.
+pub const RATE_LIMITED_CODE: i32 = -32005;
+
+/// JSON-RPC error message of calls rejected by the rate limiter.
+pub const RATE_LIMITED_MSG: &str = "Rate limited";
+
+/// Maximum number of clients to keep a bucket for.
+///
+/// The bucket of the least recently charged client is dropped once this is exceeded.
+const MAX_TRACKED_IPS: u32 = 16 * 1024;
+
+/// Length of the prefix of the IPv6 addresses sharing a bucket.
+///
+/// A single host is usually assigned a whole /64 network to pick its addresses from.
+const IPV6_CLIENT_PREFIX_LEN: u32 = 64;
+
+/// A token bucket limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+	/// Number of tokens added to the bucket every second.
+	pub per_second: NonZeroU32,
+	/// Maximum number of tokens the bucket holds, i.e. the cost that can be spent at once.
+	pub burst: NonZeroU32,
+}
+
+/// Rate limits of the RPC server.
+#[derive(Debug, Clone, Default)]
+pub struct RpcRateLimit {
+	/// Limit of every connection, `None` if not limited.
+	pub per_connection: Option<RateLimit>,
+	/// Limit of every client IP address, `None` if not limited.
+	///
+	/// The address is the remote address of the connection, unless it is one of the
+	/// `trusted_proxies`. IPv6 clients in the same /64 network share their limit.
+	pub per_ip: Option<RateLimit>,
+	/// Reverse proxies whose `X-Forwarded-For` and `X-Real-IP` headers are trusted.
+	///
+	/// The address of a client connected through one of them is taken from these headers.
+	pub trusted_proxies: Vec<IpAddr>,
+	/// The cost of calling a method, methods not in here cost `1`.
+	pub method_costs: HashMap<String, NonZeroU32>,
+}
+
+impl RpcRateLimit {
+	/// Whether any limit is set.
+	pub fn is_enabled(&self) -> bool {
+		self.per_connection.is_some() || self.per_ip.is_some()
+	}
+
+	fn cost(&self, method: &str) -> u64 {
+		self.method_costs.get(method).map_or(1, |cost| cost.get() as u64)
+	}
+}
+
+/// A token bucket.
+#[derive(Debug)]
+struct Bucket {
+	limit: RateLimit,
+	tokens: f64,
+	last_refill: Instant,
+}
+
+impl Bucket {
+	fn new(limit: RateLimit, now: Instant) -> Self {
+		Self { limit, tokens: limit.burst.get() as f64, last_refill: now }
+	}
+
+	fn refill(&mut self, now: Instant) {
+		let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+		self.tokens = (self.tokens + elapsed * self.limit.per_second.get() as f64)
+			.min(self.limit.burst.get() as f64);
+		self.last_refill = now;
+	}
+
+	fn has(&self, cost: u64) -> bool {
+		self.tokens >= cost as f64
+	}
+
+	fn take(&mut self, cost: u64) {
+		self.tokens -= cost as f64;
+	}
+}
+
+/// The rate limiter of a server, shared by its connections.
+pub(crate) struct RateLimiter {
+	config: RpcRateLimit,
+	metrics: Option<RpcMetrics>,
+	ips: Mutex<LruMap<IpAddr, Bucket>>,
+}
+
+impl RateLimiter {
+	/// Create a new rate limiter enforcing `config`.
+	pub(crate) fn new(config: RpcRateLimit, metrics: Option<RpcMetrics>) -> Arc<Self> {
+		let ips = LruMap::new(ByLength::new(MAX_TRACKED_IPS));
+		Arc::new(Self { config, metrics, ips: Mutex::new(ips) })
+	}
+
+	/// The rate limiter of a new connection from `remote_addr`.
+	pub(crate) fn connection(self: &Arc<Self>, remote_addr: SocketAddr) -> ConnectionLimiter {
+		let bucket = self.config.per_connection.map(|limit| Bucket::new(limit, Instant::now()));
+		ConnectionLimiter { shared: self.clone(), remote_addr, bucket: Mutex::new(bucket) }
+	}
+}
+
+/// The rate limiter of a single connection.
+pub(crate) struct ConnectionLimiter {
+	shared: Arc<RateLimiter>,
+	remote_addr: SocketAddr,
+	bucket: Mutex<Option<Bucket>>,
+}
+
+impl ConnectionLimiter {
+	/// The IP address of the client sending a request with the given `headers`.
+	pub(crate) fn client_ip(&self, headers: &HeaderMap) -> IpAddr {
+		client_ip(self.remote_addr.ip(), headers, &self.shared.config.trusted_proxies)
+	}
+
+	/// Charge the client at `ip` for the calls of a single or batch `request`.
+	///
+	/// Returns `false` without charging anything if the client exceeds its rate limit.
+	pub(crate) fn charge(&self, ip: IpAddr, request: &Value, transport: TransportProtocol) -> bool {
+		if !self.shared.config.is_enabled() {
+			return true
+		}
+
+		let methods = request_methods(request);
+		let cost = methods.iter().map(|method| self.shared.config.cost(method)).sum::<u64>().max(1);
+		if self.try_charge(ip, cost, Instant::now()) {
+			return true
+		}
+
+		log::debug!(
+			target: "rpc",
+			"Rejected calls of {} costing {}: rate limited: {:?}",
+			ip,
+			cost,
+			methods,
+		);
+		if let Some(metrics) = &self.shared.metrics {
+			methods.iter().for_each(|method| metrics.on_throttled(transport, method));
+		}
+		false
+	}
+
+	/// Take `cost` tokens from the buckets of the connection and `ip`.
+	///
+	/// Returns `false` without taking any token if one of the buckets does not hold enough.
+	fn try_charge(&self, ip: IpAddr, cost: u64, now: Instant) -> bool {
+		let mut connection = self.bucket.lock();
+		if let Some(bucket) = connection.as_mut() {
+			bucket.refill(now);
+			if !bucket.has(cost) {
+				return false
+			}
+		}
+
+		if let Some(limit) = self.shared.config.per_ip {
+			let mut ips = self.shared.ips.lock();
+			let Some(bucket) = ips.get_or_insert(client_key(ip), || Bucket::new(limit, now)) else {
+				return false
+			};
+			bucket.refill(now);
+			if !bucket.has(cost) {
+				return false
+			}
+			bucket.take(cost);
+		}
+
+		if let Some(bucket) = connection.as_mut() {
+			bucket.take(cost);
+		}
+		true
+	}
+}
+
+/// The IP address of a client connected from `remote_ip`.
+///
+/// Only if `remote_ip` is one of the `trusted_proxies`, the address is taken from the headers it
+/// sets. `X-Forwarded-For` lists the addresses a request was forwarded from, the last one being
+/// added by the proxy in front of the node. The last address that is not a trusted proxy is the
+/// one of the client, all before it are under the control of the client.
+fn client_ip(remote_ip: IpAddr, headers: &HeaderMap, trusted_proxies: &[IpAddr]) -> IpAddr {
+	if !trusted_proxies.contains(&remote_ip) {
+		return remote_ip
+	}
+
+	let forwarded_for = headers
+		.get_all("x-forwarded-for")
+		.iter()
+		.rev()
+		.filter_map(|value| value.to_str().ok())
+		.flat_map(|value| value.rsplit(','))
+		.map(|ip| ip.trim().parse::<IpAddr>().ok())
+		.find(|ip| ip.map_or(true, |ip| !trusted_proxies.contains(&ip)));
+	if let Some(ip) = forwarded_for {
+		return ip.unwrap_or(remote_ip)
+	}
+
+	headers
+		.get("x-real-ip")
+		.and_then(|value| value.to_str().ok())
+		.and_then(|value| value.trim().parse().ok())
+		.unwrap_or(remote_ip)
+}
+
+/// The key of the bucket of the client at `ip`.
+///
+/// IPv6 addresses are truncated to their /64 network, the one of an IPv4-mapped address is the
+/// IPv4 address.
+fn client_key(ip: IpAddr) -> IpAddr {
+	match ip {
+		IpAddr::V4(_) => ip,
+		IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+			Some(ip) => IpAddr::V4(ip),
+			None => {
+				let mask = u128::MAX << (128 - IPV6_CLIENT_PREFIX_LEN);
+				IpAddr::V6(Ipv6Addr::from(u128::from(ip) & mask))
+			},
+		},
+	}
+}
+
+/// The methods called by a single or batch request.
+fn request_methods(request: &Value) -> Vec<String> {
+	let method = |call: &Value| call.get("method").and_then(Value::as_str).map(String::from);
+
+	match request {
+		Value::Array(calls) => calls.iter().filter_map(method).collect(),
+		call => method(call).into_iter().collect(),
+	}
+}
+
+/// The response to a rejected single or batch `request`.
+pub(crate) fn rate_limited_response(request: &Value) -> Value {
+	let error = |call: &Value| {
+		serde_json::json!({
+			"jsonrpc": "2.0",
+			"error": { "code": RATE_LIMITED_CODE, "message": RATE_LIMITED_MSG },
+			"id": call.get("id").cloned().unwrap_or(Value::Null),
+		})
+	};
+
+	match request {
+		Value::Array(calls) => Value::Array(calls.iter().map(error).collect()),
+		call => error(call),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use http::header::HeaderValue;
+	use std::time::Duration;
+
+	fn limit(per_second: u32, burst: u32) -> Option<RateLimit> {
+		Some(RateLimit {
+			per_second: NonZeroU32::new(per_second).unwrap(),
+			burst: NonZeroU32::new(burst).unwrap(),
+		})
+	}
+
+	fn connection(limiter: &Arc<RateLimiter>, ip: &str) -> ConnectionLimiter {
+		limiter.connection(SocketAddr::new(ip.parse().unwrap(), 30333))
+	}
+
+	fn ip(ip: &str) -> IpAddr {
+		ip.parse().unwrap()
+	}
+
+	#[test]
+	fn connection_bucket_refills() {
+		let limiter = RateLimiter::new(
+			RpcRateLimit { per_connection: limit(2, 4), ..Default::default() },
+			None,
+		);
+		let connection = connection(&limiter, "10.0.0.1");
+		let now = Instant::now();
+		let ip = ip("10.0.0.1");
+
+		assert!(connection.try_charge(ip, 3, now));
+		assert!(!connection.try_charge(ip, 2, now));
+		assert!(connection.try_charge(ip, 1, now));
+
+		// Refills at most up to the burst.
+		let later = now + Duration::from_secs(10);
+		assert!(!connection.try_charge(ip, 5, later));
+		assert!(connection.try_charge(ip, 4, later));
+	}
+
+	#[test]
+	fn ip_bucket_is_shared_by_connections() {
+		let limiter =
+			RateLimiter::new(RpcRateLimit { per_ip: limit(1, 2), ..Default::default() }, None);
+		let first = connection(&limiter, "10.0.0.1");
+		let second = connection(&limiter, "10.0.0.1");
+		let now = Instant::now();
+
+		assert!(first.try_charge(ip("10.0.0.1"), 2, now));
+		assert!(!second.try_charge(ip("10.0.0.1"), 1, now));
+		assert!(second.try_charge(ip("10.0.0.2"), 1, now));
+	}
+
+	#[test]
+	fn ipv6_bucket_is_shared_by_network() {
+		let limiter =
+			RateLimiter::new(RpcRateLimit { per_ip: limit(1, 2), ..Default::default() }, None);
+		let connection = connection(&limiter, "2001:db8::1");
+		let now = Instant::now();
+
+		assert!(connection.try_charge(ip("2001:db8::1"), 1, now));
+		assert!(connection.try_charge(ip("2001:db8::ffff:1:2:3"), 1, now));
+		assert!(!connection.try_charge(ip("2001:db8::2"), 1, now));
+		assert!(connection.try_charge(ip("2001:db8:0:1::1"), 1, now));
+
+		assert_eq!(client_key(ip("::ffff:10.0.0.1")), ip("10.0.0.1"));
+	}
+
+	#[test]
+	fn least_recently_charged_ip_bucket_is_dropped() {
+		let limiter =
+			RateLimiter::new(RpcRateLimit { per_ip: limit(1, 1), ..Default::default() }, None);
+		let connection = connection(&limiter, "10.0.0.1");
+		let now = Instant::now();
+
+		assert!(connection.try_charge(ip("10.0.0.1"), 1, now));
+		for i in 0..MAX_TRACKED_IPS {
+			assert!(connection.try_charge(IpAddr::from((i + 1).to_be_bytes()), 1, now));
+		}
+		assert_eq!(limiter.ips.lock().len(), MAX_TRACKED_IPS as usize);
+		assert!(connection.try_charge(ip("10.0.0.1"), 1, now));
+	}
+
+	#[test]
+	fn rejected_calls_take_no_tokens() {
+		let limiter = RateLimiter::new(
+			RpcRateLimit { per_connection: limit(1, 1), per_ip: limit(1, 2), ..Default::default() },
+			None,
+		);
+		let connection = connection(&limiter, "10.0.0.1");
+		let now = Instant::now();
+		let ip = ip("10.0.0.1");
+
+		assert!(!connection.try_charge(ip, 2, now));
+		assert!(connection.try_charge(ip, 1, now));
+		assert!(connection.try_charge(ip, 1, now + Duration::from_secs(1)));
+	}
+
+	#[test]
+	fn proxy_headers_are_only_trusted_from_trusted_proxies() {
+		let trusted = [ip("10.0.0.1"), ip("10.0.0.2")];
+		let mut headers = HeaderMap::new();
+		assert_eq!(client_ip(ip("10.0.0.1"), &headers, &trusted), ip("10.0.0.1"));
+
+		headers.insert("x-real-ip", HeaderValue::from_static("1.1.1.1"));
+		assert_eq!(client_ip(ip("10.0.0.1"), &headers, &trusted), ip("1.1.1.1"));
+		assert_eq!(client_ip(ip("2.2.2.2"), &headers, &trusted), ip("2.2.2.2"));
+
+		// Addresses added by trusted proxies are skipped, all before the client's are spoofable.
+		headers.insert("x-forwarded-for", HeaderValue::from_static("3.3.3.3, 4.4.4.4, 10.0.0.2"));
+		assert_eq!(client_ip(ip("10.0.0.1"), &headers, &trusted), ip("4.4.4.4"));
+		assert_eq!(client_ip(ip("2.2.2.2"), &headers, &trusted), ip("2.2.2.2"));
+
+		headers.insert("x-forwarded-for", HeaderValue::from_static("invalid, 10.0.0.2"));
+		assert_eq!(client_ip(ip("10.0.0.1"), &headers, &trusted), ip("10.0.0.1"));
+	}
+
+	#[test]
+	fn methods_of_batches_are_charged() {
+		let mut method_costs = HashMap::new();
+		method_costs.insert("state_call".into(), NonZeroU32::new(10).unwrap());
+		let limiter = RateLimiter::new(
+			RpcRateLimit { per_connection: limit(1, 11), method_costs, ..Default::default() },
+			None,
+		);
+		let connection = connection(&limiter, "10.0.0.1");
+
+		let request = serde_json::json!([
+			{ "jsonrpc": "2.0", "method": "state_call", "id": 1 },
+			{ "jsonrpc": "2.0", "method": "system_health", "id": 2 },
+		]);
+		assert_eq!(
+			request_methods(&request),
+			vec!["state_call".to_string(), "system_health".to_string()]
+		);
+		assert!(connection.charge(ip("10.0.0.1"), &request, TransportProtocol::WebSocket));
+		assert!(!connection.charge(ip("10.0.0.1"), &request, TransportProtocol::WebSocket));
+
+		let single = serde_json::json!({ "jsonrpc": "2.0", "method": "state_call", "id": 1 });
+		assert_eq!(request_methods(&single), vec!["state_call".to_string()]);
+		assert_eq!(rate_limited_response(&single)["error"]["code"], Value::from(RATE_LIMITED_CODE));
+	}
+}
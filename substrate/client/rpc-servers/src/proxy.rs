@@ -0,0 +1,318 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Proxy in front of the RPC server enforcing the batch and rate limits of its clients.
+//!
+//! The proxy accepts the connections of the clients and forwards every HTTP request and WebSocket
+//! message within the limits to the server, which only listens on a loopback address. Every client
+//! connection is forwarded over its own connections to the server.
+//!
+//! Any local process can connect to the server, so every forwarded request carries a secret of the
+//! proxy. The [`ProxySecretLayer`] of the server rejects the requests without it.
+
+mod websocket;
+
+use crate::middleware::{
+	batch_limit, json_response, payload_too_large,
+	rate_limit::{rate_limited_response, ConnectionLimiter, RateLimiter},
+	read_body, ReadBody,
+};
+use http::{
+	header::{HeaderMap, HeaderValue, UPGRADE},
+	Request, Response, StatusCode,
+};
+use hyper::{
+	body::Body,
+	client::conn::{self as client_conn, SendRequest},
+	server::conn::Http,
+	service::service_fn,
+};
+use jsonrpsee::server::logger::TransportProtocol;
+use parking_lot::Mutex;
+use serde_json::Value;
+use std::{
+	error::Error as StdError,
+	future::Future,
+	net::{IpAddr, SocketAddr},
+	pin::Pin,
+	sync::Arc,
+	task::{Context, Poll},
+};
+use tokio::{
+	net::{TcpListener, TcpStream},
+	sync::Semaphore,
+};
+use tower::{Layer, Service};
+
+type BoxError = Box<dyn StdError + Send + Sync>;
+
+/// Header carrying the secret of the proxy in the requests it forwards.
+const SECRET_HEADER: &str = "x-substrate-rpc-proxy";
+
+/// The proxy of a server.
+pub(crate) struct Proxy {
+	listener: TcpListener,
+	secret: HeaderValue,
+	limiter: Arc<RateLimiter>,
+	max_batch_len: Option<u32>,
+	max_connections: u32,
+	max_request_body_size: u32,
+}
+
+impl Proxy {
+	/// Listen on the first of `addrs` that can be bound to.
+	pub(crate) async fn bind(
+		addrs: &[SocketAddr],
+		limiter: Arc<RateLimiter>,
+		max_batch_len: Option<u32>,
+		max_connections: u32,
+		max_request_body_size: u32,
+	) -> std::io::Result<Self> {
+		let listener = TcpListener::bind(addrs).await?;
+		let secret = HeaderValue::from_str(&format!("{:032x}", rand::random::<u128>()))
+			.expect("Hex digits are valid in a header; qed");
+		Ok(Self {
+			listener,
+			secret,
+			limiter,
+			max_batch_len,
+			max_connections,
+			max_request_body_size,
+		})
+	}
+
+	/// The middleware of the server only accepting the requests forwarded by this proxy.
+	pub(crate) fn secret_layer(&self) -> ProxySecretLayer {
+		ProxySecretLayer(Some(self.secret.clone()))
+	}
+
+	/// The address the proxy listens on.
+	pub(crate) fn local_addr(&self) -> std::io::Result<SocketAddr> {
+		self.listener.local_addr()
+	}
+
+	/// Forward the connections of the clients to the server at `server_addr` until `stopped`
+	/// resolves.
+	pub(crate) async fn run(self, server_addr: SocketAddr, stopped: impl Future<Output = ()>) {
+		let Self {
+			listener,
+			secret,
+			limiter,
+			max_batch_len,
+			max_connections,
+			max_request_body_size,
+		} = self;
+		let connections = Arc::new(Semaphore::new(max_connections as usize));
+		let mut stopped = std::pin::pin!(stopped);
+
+		loop {
+			let (stream, remote_addr) = tokio::select! {
+				_ = &mut stopped => return,
+				accepted = listener.accept() => match accepted {
+					Ok(accepted) => accepted,
+					Err(e) => {
+						log::debug!(target: "rpc", "Failed to accept RPC connection: {}", e);
+						continue
+					},
+				},
+			};
+			let Ok(permit) = connections.clone().try_acquire_owned() else {
+				log::debug!(
+					target: "rpc",
+					"Rejected RPC connection of {}: too many connections",
+					remote_addr,
+				);
+				continue
+			};
+
+			let connection = Arc::new(Connection {
+				server_addr,
+				secret: secret.clone(),
+				max_batch_len,
+				max_request_body_size,
+				limiter: limiter.connection(remote_addr),
+				idle_server: Mutex::new(None),
+			});
+			let service = service_fn(move |req| connection.clone().forward(req));
+			tokio::spawn(async move {
+				let _ = stream.set_nodelay(true);
+				let serve = Http::new().http1_only(true).serve_connection(stream, service);
+				if let Err(e) = serve.with_upgrades().await {
+					log::debug!(target: "rpc", "RPC connection of {} failed: {}", remote_addr, e);
+				}
+				drop(permit);
+			});
+		}
+	}
+}
+
+/// A client connection.
+struct Connection {
+	server_addr: SocketAddr,
+	secret: HeaderValue,
+	max_batch_len: Option<u32>,
+	max_request_body_size: u32,
+	limiter: ConnectionLimiter,
+	/// An idle connection to the server the HTTP requests of the client can be forwarded over.
+	idle_server: Mutex<Option<SendRequest<Body>>>,
+}
+
+impl Connection {
+	/// Forward `req` to the server if it is within the limits.
+	async fn forward(self: Arc<Self>, mut req: Request<Body>) -> Result<Response<Body>, BoxError> {
+		let ip = self.limiter.client_ip(req.headers());
+
+		if is_websocket_upgrade(req.headers()) {
+			// Opening a connection costs as much as a call.
+			if !self.limiter.charge(ip, &Value::Null, TransportProtocol::WebSocket) {
+				return Ok(json_response(&rate_limited_response(&Value::Null)))
+			}
+
+			let client = hyper::upgrade::on(&mut req);
+			req.headers_mut().insert(SECRET_HEADER, self.secret.clone());
+			let (mut sender, connection) = connect(self.server_addr).await?;
+			tokio::spawn(connection);
+			let mut response = sender.send_request(req).await?;
+			if response.status() == StatusCode::SWITCHING_PROTOCOLS {
+				let server = hyper::upgrade::on(&mut response);
+				tokio::spawn(async move {
+					match tokio::try_join!(client, server) {
+						Ok((client, server)) => websocket::forward(client, server, &self, ip).await,
+						Err(e) =>
+							log::debug!(target: "rpc", "Failed to upgrade to WebSocket: {}", e),
+					}
+				});
+			}
+			return Ok(response)
+		}
+
+		let (parts, bytes) = match read_body(req, self.max_request_body_size).await? {
+			ReadBody::Read(parts, bytes) => (parts, bytes),
+			// Rejected by the server without processing any call.
+			ReadBody::Unread(req) => return self.send(req).await,
+			ReadBody::TooLarge => return Ok(payload_too_large()),
+		};
+
+		let request = serde_json::from_slice::<Value>(&bytes).unwrap_or(Value::Null);
+		if let Some(response) = self.reject(ip, &request, TransportProtocol::Http) {
+			return Ok(json_response(&response))
+		}
+
+		self.send(Request::from_parts(parts, Body::from(bytes))).await
+	}
+
+	/// The error response to a single or batch `request` of the client at `ip` if it exceeds a
+	/// limit, `None` if it can be forwarded.
+	fn reject(&self, ip: IpAddr, request: &Value, transport: TransportProtocol) -> Option<Value> {
+		if self.max_batch_len.map_or(false, |max| batch_limit::exceeds(request, max)) {
+			return Some(batch_limit::batch_too_large_response())
+		}
+		(!self.limiter.charge(ip, request, transport)).then(|| rate_limited_response(request))
+	}
+
+	/// Send `req` to the server over an idle connection of the client, or a new one if there is
+	/// none.
+	async fn send(&self, mut req: Request<Body>) -> Result<Response<Body>, BoxError> {
+		req.headers_mut().insert(SECRET_HEADER, self.secret.clone());
+
+		let idle = self.idle_server.lock().take();
+		let idle = match idle {
+			Some(mut sender) => sender.ready().await.ok().map(|()| sender),
+			None => None,
+		};
+		let mut sender = match idle {
+			Some(sender) => sender,
+			None => {
+				let (sender, connection) = connect(self.server_addr).await?;
+				tokio::spawn(connection);
+				sender
+			},
+		};
+
+		let response = sender.send_request(req).await;
+		self.idle_server.lock().get_or_insert(sender);
+		Ok(response?)
+	}
+}
+
+/// Open a connection to the server at `addr`.
+async fn connect(
+	addr: SocketAddr,
+) -> Result<(SendRequest<Body>, client_conn::Connection<TcpStream, Body>), BoxError> {
+	let stream = TcpStream::connect(addr).await?;
+	stream.set_nodelay(true)?;
+	Ok(client_conn::handshake(stream).await?)
+}
+
+fn is_websocket_upgrade(headers: &HeaderMap) -> bool {
+	headers
+		.get(UPGRADE)
+		.map_or(false, |value| value.as_bytes().eq_ignore_ascii_case(b"websocket"))
+}
+
+/// Middleware of the server rejecting the requests that do not carry the secret of its proxy.
+///
+/// Passes every request if the server has no proxy.
+#[derive(Debug, Clone)]
+pub(crate) struct ProxySecretLayer(pub(crate) Option<HeaderValue>);
+
+impl<S> Layer<S> for ProxySecretLayer {
+	type Service = ProxySecret<S>;
+
+	fn layer(&self, inner: S) -> Self::Service {
+		ProxySecret { inner, secret: self.0.clone() }
+	}
+}
+
+/// Service of the [`ProxySecretLayer`].
+#[derive(Debug, Clone)]
+pub(crate) struct ProxySecret<S> {
+	inner: S,
+	secret: Option<HeaderValue>,
+}
+
+impl<S> Service<Request<Body>> for ProxySecret<S>
+where
+	S: Service<Request<Body>, Response = Response<Body>>,
+	S::Error: Into<BoxError> + 'static,
+	S::Future: Send + 'static,
+{
+	type Response = S::Response;
+	type Error = BoxError;
+	type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+	fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+		self.inner.poll_ready(cx).map_err(Into::into)
+	}
+
+	fn call(&mut self, req: Request<Body>) -> Self::Future {
+		if let Some(secret) = &self.secret {
+			if req.headers().get(SECRET_HEADER) != Some(secret) {
+				log::debug!(target: "rpc", "Rejected RPC request not forwarded by the proxy");
+				let response = Response::builder()
+					.status(StatusCode::FORBIDDEN)
+					.body(Body::empty())
+					.expect("Status and empty body are valid; qed");
+				return Box::pin(async move { Ok(response) })
+			}
+		}
+
+		let response = self.inner.call(req);
+		Box::pin(async move { response.await.map_err(Into::into) })
+	}
+}
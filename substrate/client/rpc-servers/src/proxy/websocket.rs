@@ -0,0 +1,302 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Forwarding of the frames of a WebSocket connection (RFC 6455).
+//!
+//! The messages of the client are reassembled to check them against the limits, but forwarded as
+//! the frames they were received as. The frames of the server are forwarded as they are.
+
+use super::Connection;
+use hyper::upgrade::Upgraded;
+use jsonrpsee::server::logger::TransportProtocol;
+use serde_json::Value;
+use std::{io, net::IpAddr, sync::Arc};
+use tokio::{
+	io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+	sync::Mutex,
+};
+
+/// Opcode of a frame carrying a text message.
+const OPCODE_TEXT: u8 = 0x1;
+
+/// Opcodes of control frames start at this one.
+const OPCODE_CONTROL: u8 = 0x8;
+
+/// Forward the frames between the `client` and the `server` until either closes the connection.
+///
+/// Messages of the client exceeding a limit are answered with an error instead.
+pub(super) async fn forward(
+	client: Upgraded,
+	server: Upgraded,
+	connection: &Connection,
+	ip: IpAddr,
+) {
+	let (client_read, client_write) = tokio::io::split(client);
+	let (server_read, server_write) = tokio::io::split(server);
+	let client_write = Arc::new(Mutex::new(ClientWriter::new(client_write)));
+
+	let result = tokio::select! {
+		result = forward_to_client(server_read, client_write.clone()) => result,
+		result = forward_to_server(client_read, server_write, client_write, connection, ip) => result,
+	};
+	if let Err(e) = result {
+		log::debug!(target: "rpc", "WebSocket connection of {} failed: {}", ip, e);
+	}
+}
+
+/// Forward the messages of the client within the limits to the server.
+async fn forward_to_server<R, W, C>(
+	mut client: R,
+	mut server: W,
+	client_write: Arc<Mutex<ClientWriter<C>>>,
+	connection: &Connection,
+	ip: IpAddr,
+) -> io::Result<()>
+where
+	R: AsyncRead + Unpin,
+	W: AsyncWrite + Unpin,
+	C: AsyncWrite + Unpin,
+{
+	// The frames of the message received so far and their unmasked payload.
+	let mut frames = Vec::new();
+	let mut message = Vec::new();
+
+	while let Some(header) = FrameHeader::read(&mut client).await? {
+		let max_message_size = connection.max_request_body_size as u64;
+		if header.len.saturating_add(message.len() as u64) > max_message_size {
+			return Err(io::Error::new(io::ErrorKind::InvalidData, "Message too large"))
+		}
+		let mut payload = vec![0; header.len as usize];
+		client.read_exact(&mut payload).await?;
+
+		if header.opcode >= OPCODE_CONTROL {
+			server.write_all(&header.raw).await?;
+			server.write_all(&payload).await?;
+			server.flush().await?;
+			continue
+		}
+
+		frames.extend_from_slice(&header.raw);
+		frames.extend_from_slice(&payload);
+		header.unmask(&mut payload);
+		message.extend_from_slice(&payload);
+		if !header.fin {
+			continue
+		}
+
+		let request = serde_json::from_slice::<Value>(&message).unwrap_or(Value::Null);
+		match connection.reject(ip, &request, TransportProtocol::WebSocket) {
+			None => {
+				server.write_all(&frames).await?;
+				server.flush().await?;
+			},
+			Some(response) => {
+				let response = response.to_string();
+				client_write.lock().await.send_text(response.as_bytes()).await?;
+			},
+		}
+		frames.clear();
+		message.clear();
+	}
+
+	Ok(())
+}
+
+/// Forward the frames of the server to the client.
+async fn forward_to_client<R, C>(
+	mut server: R,
+	client_write: Arc<Mutex<ClientWriter<C>>>,
+) -> io::Result<()>
+where
+	R: AsyncRead + Unpin,
+	C: AsyncWrite + Unpin,
+{
+	while let Some(header) = FrameHeader::read(&mut server).await? {
+		let mut client = client_write.lock().await;
+		client.io.write_all(&header.raw).await?;
+		let copied = tokio::io::copy(&mut (&mut server).take(header.len), &mut client.io).await?;
+		if copied != header.len {
+			return Err(io::ErrorKind::UnexpectedEof.into())
+		}
+
+		if header.opcode < OPCODE_CONTROL {
+			client.in_message = !header.fin;
+		}
+		client.send_pending().await?;
+		client.io.flush().await?;
+	}
+
+	Ok(())
+}
+
+/// The sending half of the client connection.
+struct ClientWriter<W> {
+	io: W,
+	/// Whether the server is in the middle of sending a fragmented message.
+	in_message: bool,
+	/// Frames to send once the server finished sending its message.
+	pending: Vec<Vec<u8>>,
+}
+
+impl<W: AsyncWrite + Unpin> ClientWriter<W> {
+	fn new(io: W) -> Self {
+		Self { io, in_message: false, pending: Vec::new() }
+	}
+
+	/// Send a text message to the client, without interrupting a message of the server.
+	async fn send_text(&mut self, text: &[u8]) -> io::Result<()> {
+		self.pending.push(text_frame(text));
+		self.send_pending().await?;
+		self.io.flush().await
+	}
+
+	async fn send_pending(&mut self) -> io::Result<()> {
+		if self.in_message {
+			return Ok(())
+		}
+		for frame in std::mem::take(&mut self.pending) {
+			self.io.write_all(&frame).await?;
+		}
+		Ok(())
+	}
+}
+
+/// The header of a frame.
+#[derive(Debug, PartialEq)]
+struct FrameHeader {
+	fin: bool,
+	opcode: u8,
+	mask: Option<[u8; 4]>,
+	/// The length of the payload.
+	len: u64,
+	/// The header as received.
+	raw: Vec<u8>,
+}
+
+impl FrameHeader {
+	/// Read the header of the next frame, `None` if the connection was closed.
+	async fn read<R: AsyncRead + Unpin>(io: &mut R) -> io::Result<Option<Self>> {
+		let mut raw = vec![0; 2];
+		match io.read_exact(&mut raw).await {
+			Ok(_) => (),
+			Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+			Err(e) => return Err(e),
+		}
+
+		let fin = raw[0] & 0x80 != 0;
+		let opcode = raw[0] & 0x0f;
+		let masked = raw[1] & 0x80 != 0;
+		let len = match raw[1] & 0x7f {
+			126 => {
+				let mut len = [0; 2];
+				io.read_exact(&mut len).await?;
+				raw.extend_from_slice(&len);
+				u16::from_be_bytes(len) as u64
+			},
+			127 => {
+				let mut len = [0; 8];
+				io.read_exact(&mut len).await?;
+				raw.extend_from_slice(&len);
+				u64::from_be_bytes(len)
+			},
+			len => len as u64,
+		};
+		let mask = if masked {
+			let mut mask = [0; 4];
+			io.read_exact(&mut mask).await?;
+			raw.extend_from_slice(&mask);
+			Some(mask)
+		} else {
+			None
+		};
+
+		Ok(Some(Self { fin, opcode, mask, len, raw }))
+	}
+
+	fn unmask(&self, payload: &mut [u8]) {
+		if let Some(mask) = self.mask {
+			payload
+				.iter_mut()
+				.zip(mask.iter().cycle())
+				.for_each(|(byte, mask)| *byte ^= mask);
+		}
+	}
+}
+
+/// An unmasked, unfragmented frame carrying the text message `payload`.
+fn text_frame(payload: &[u8]) -> Vec<u8> {
+	let mut frame = vec![0x80 | OPCODE_TEXT];
+	match payload.len() {
+		len @ 0..=125 => frame.push(len as u8),
+		len @ 126..=0xffff => {
+			frame.push(126);
+			frame.extend_from_slice(&(len as u16).to_be_bytes());
+		},
+		len => {
+			frame.push(127);
+			frame.extend_from_slice(&(len as u64).to_be_bytes());
+		},
+	}
+	frame.extend_from_slice(payload);
+	frame
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn read_header(bytes: &[u8]) -> Option<FrameHeader> {
+		let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+		runtime.block_on(FrameHeader::read(&mut &bytes[..])).unwrap()
+	}
+
+	#[test]
+	fn masked_frames_are_read_and_unmasked() {
+		// A masked "Hello" from RFC 6455, section 5.7.
+		let frame = [0x81, 0x85, 0x37, 0xfa, 0x21, 0x3d, 0x7f, 0x9f, 0x4d, 0x51, 0x58];
+		let header = read_header(&frame).unwrap();
+		assert_eq!(
+			header,
+			FrameHeader {
+				fin: true,
+				opcode: OPCODE_TEXT,
+				mask: Some([0x37, 0xfa, 0x21, 0x3d]),
+				len: 5,
+				raw: frame[..6].to_vec(),
+			}
+		);
+
+		let mut payload = frame[6..].to_vec();
+		header.unmask(&mut payload);
+		assert_eq!(payload, b"Hello");
+	}
+
+	#[test]
+	fn text_frames_are_read_back() {
+		for len in [0, 125, 126, 0xffff, 0x10000] {
+			let frame = text_frame(&vec![b'a'; len]);
+			let header = read_header(&frame).unwrap();
+			assert!(header.fin);
+			assert_eq!(header.mask, None);
+			assert_eq!(header.len, len as u64);
+			assert_eq!(frame.len(), header.raw.len() + len);
+		}
+
+		assert_eq!(read_header(&[]), None);
+	}
+}
@@ -0,0 +1,145 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Substrate IPFS API.
+
+#[cfg(test)]
+mod tests;
+
+use self::error::{Error, Result};
+use jsonrpsee::core::{async_trait, RpcResult};
+use sc_client_api::{backend::ContentStore, BlockBackend};
+use sc_network_bitswap::{content_cid, content_hash, BitswapClient, Cid};
+pub use sc_rpc_api::ipfs::*;
+use sc_rpc_api::DenyUnsafe;
+use sp_core::Bytes;
+use sp_runtime::traits::Block as BlockT;
+use std::sync::Arc;
+
+/// IPFS API
+pub struct Ipfs<Block: BlockT, Client> {
+	client: Arc<Client>,
+	store: Arc<dyn ContentStore<Block>>,
+	/// Fetches the content that is not stored locally. `None` to only serve local content.
+	bitswap: Option<BitswapClient>,
+	deny_unsafe: DenyUnsafe,
+}
+
+impl<Block: BlockT, Client> Ipfs<Block, Client>
+where
+	Client: BlockBackend<Block> + Send + Sync + 'static,
+{
+	/// Create new instance of IPFS API.
+	pub fn new(
+		client: Arc<Client>,
+		store: Arc<dyn ContentStore<Block>>,
+		bitswap: Option<BitswapClient>,
+		deny_unsafe: DenyUnsafe,
+	) -> Self {
+		Ipfs { client, store, bitswap, deny_unsafe }
+	}
+
+	/// Get the content with the given CID, from the local storage or the connected peers.
+	async fn content(&self, cid: &str) -> Result<Vec<u8>> {
+		let hash = parse_cid(cid)?;
+		if let Some(content) = self
+			.client
+			.indexed_transaction(to_chain_hash(cid, hash)?)
+			.map_err(|e| Error::Client(Box::new(e)))?
+		{
+			return Ok(content)
+		}
+
+		match &self.bitswap {
+			Some(bitswap) => bitswap.fetch(hash).await,
+			None => None,
+		}
+		.ok_or_else(|| Error::NotFound(cid.into()))
+	}
+}
+
+/// Parse a CID and return the hash of the content it references.
+fn parse_cid(cid: &str) -> Result<[u8; 32]> {
+	let parsed = cid.parse::<Cid>().map_err(|e| Error::InvalidCid(format!("{cid}: {e}")))?;
+	content_hash(&parsed).ok_or_else(|| Error::InvalidCid(format!("{cid}: unsupported hash")))
+}
+
+/// The hash the chain indexes the content referenced by `cid` under, failing if the chain does
+/// not use 256-bit hashes.
+fn to_chain_hash<H: Default + AsMut<[u8]>>(cid: &str, hash: [u8; 32]) -> Result<H> {
+	let mut chain_hash = H::default();
+	if chain_hash.as_mut().len() != hash.len() {
+		return Err(Error::InvalidCid(format!("{cid}: the chain does not use 256-bit hashes")))
+	}
+	chain_hash.as_mut().copy_from_slice(&hash);
+	Ok(chain_hash)
+}
+
+/// CID of the content with the given hash.
+fn to_cid<H: AsRef<[u8]>>(hash: H) -> Result<String> {
+	let hash = hash.as_ref();
+	let content = <[u8; 32]>::try_from(hash).map_err(|_| {
+		Error::Client(format!("Unsupported content hash of {} bytes", hash.len()).into())
+	})?;
+	Ok(content_cid(content).to_string())
+}
+
+#[async_trait]
+impl<Block, Client> IpfsApiServer for Ipfs<Block, Client>
+where
+	Block: BlockT,
+	Client: BlockBackend<Block> + Send + Sync + 'static,
+{
+	fn add(&self, data: Bytes) -> RpcResult<String> {
+		self.deny_unsafe.check_if_safe()?;
+
+		let hash = self.store.pin(data.0).map_err(|e| Error::Client(Box::new(e)))?;
+		Ok(to_cid(hash)?)
+	}
+
+	async fn get(&self, cid: String) -> RpcResult<Bytes> {
+		self.deny_unsafe.check_if_safe()?;
+
+		Ok(self.content(&cid).await.map(Into::into)?)
+	}
+
+	async fn pin_content(&self, cid: String) -> RpcResult<()> {
+		self.deny_unsafe.check_if_safe()?;
+
+		let content = self.content(&cid).await?;
+		self.store.pin(content).map_err(|e| Error::Client(Box::new(e)))?;
+		Ok(())
+	}
+
+	fn unpin_content(&self, cid: String) -> RpcResult<()> {
+		self.deny_unsafe.check_if_safe()?;
+
+		let hash = to_chain_hash(&cid, parse_cid(&cid)?)?;
+		if !self.store.unpin(hash).map_err(|e| Error::Client(Box::new(e)))? {
+			return Err(Error::NotPinned(cid).into())
+		}
+		Ok(())
+	}
+
+	fn pinned_content(&self) -> RpcResult<Vec<String>> {
+		self.deny_unsafe.check_if_safe()?;
+
+		let pinned = self.store.pinned().map_err(|e| Error::Client(Box::new(e)))?;
+		Ok(pinned.into_iter().map(to_cid).collect::<Result<_>>()?)
+	}
+}
@@ -0,0 +1,101 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use super::*;
+use assert_matches::assert_matches;
+use jsonrpsee::{core::Error as JsonRpseeError, types::error::CallError};
+use sc_client_api::Backend as _;
+use substrate_test_runtime_client::{prelude::*, TestClientBuilder};
+
+fn ipfs(
+	deny_unsafe: DenyUnsafe,
+) -> Ipfs<substrate_test_runtime_client::runtime::Block, TestClient> {
+	let (client, backend) = TestClientBuilder::new().build_with_backend();
+	Ipfs::new(Arc::new(client), backend.content_store().unwrap(), None, deny_unsafe)
+}
+
+#[tokio::test]
+async fn added_content_is_kept_until_unpinned() {
+	let ipfs = ipfs(DenyUnsafe::No);
+	let content = Bytes(b"content".to_vec());
+
+	let cid = ipfs.add(content.clone()).unwrap();
+	assert_eq!(cid, content_cid(sp_core::hashing::blake2_256(&content)).to_string());
+	assert_matches!(ipfs.get(cid.clone()).await, Ok(ref c) if *c == content);
+	assert_eq!(ipfs.pinned_content().unwrap(), vec![cid.clone()]);
+
+	ipfs.pin_content(cid.clone()).await.unwrap();
+	ipfs.unpin_content(cid.clone()).unwrap();
+	assert_matches!(ipfs.get(cid.clone()).await, Ok(ref c) if *c == content);
+
+	ipfs.unpin_content(cid.clone()).unwrap();
+	assert!(ipfs.pinned_content().unwrap().is_empty());
+	assert_matches!(
+		ipfs.get(cid.clone()).await,
+		Err(JsonRpseeError::Call(CallError::Custom(err))) => {
+			assert_eq!(err.message(), format!("Content {cid} not found"))
+		}
+	);
+	assert_matches!(
+		ipfs.unpin_content(cid.clone()),
+		Err(JsonRpseeError::Call(CallError::Custom(err))) => {
+			assert_eq!(err.message(), format!("Content {cid} is not pinned"))
+		}
+	);
+	assert_matches!(ipfs.get("not a cid".into()).await, Err(_));
+}
+
+#[tokio::test]
+async fn storing_and_fetching_content_is_unsafe() {
+	let ipfs = ipfs(DenyUnsafe::Yes);
+
+	assert_matches!(
+		ipfs.add(Bytes(b"content".to_vec())),
+		Err(JsonRpseeError::Call(CallError::Custom(err))) => {
+			assert_eq!(err.message(), "RPC call is unsafe to be called externally")
+		}
+	);
+	assert_matches!(
+		ipfs.get(to_cid(sp_core::hashing::blake2_256(b"content")).unwrap()).await,
+		Err(JsonRpseeError::Call(CallError::Custom(err))) => {
+			assert_eq!(err.message(), "RPC call is unsafe to be called externally")
+		}
+	);
+	assert_matches!(
+		ipfs.pinned_content(),
+		Err(JsonRpseeError::Call(CallError::Custom(err))) => {
+			assert_eq!(err.message(), "RPC call is unsafe to be called externally")
+		}
+	);
+}
+
+#[test]
+fn hashes_of_other_lengths_are_rejected() {
+	let hash = sp_core::hashing::blake2_256(b"content");
+	let cid = to_cid(hash).unwrap();
+
+	assert_matches!(to_chain_hash::<sp_core::H256>(&cid, hash), Ok(h) if h.0 == hash);
+	assert_matches!(to_chain_hash::<sp_core::H160>(&cid, hash), Err(Error::InvalidCid(_)));
+	assert_matches!(to_cid([0u8; 20]), Err(Error::Client(_)));
+	assert_matches!(
+		JsonRpseeError::from(Error::InvalidCid(cid)),
+		JsonRpseeError::Call(CallError::Custom(err)) => {
+			assert_eq!(err.code(), jsonrpsee::types::error::INVALID_PARAMS_CODE)
+		}
+	);
+}
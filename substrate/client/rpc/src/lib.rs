@@ -34,12 +34,15 @@ pub use sc_rpc_api::DenyUnsafe;
 pub mod author;
 pub mod chain;
 pub mod dev;
+pub mod ipfs;
 pub mod mixnet;
 pub mod offchain;
 pub mod state;
 pub mod statement;
 pub mod system;
 
+mod stream;
+
 #[cfg(any(test, feature = "test-helpers"))]
 pub mod testing;
 
@@ -162,6 +162,26 @@ where
 
 	/// New storage subscription
 	fn subscribe_storage(&self, sink: SubscriptionSink, keys: Option<Vec<StorageKey>>);
+
+	/// Stream the keys with prefix along with their values in chunks of `chunk_size`.
+	fn stream_pairs(
+		&self,
+		sink: SubscriptionSink,
+		block: Option<Block::Hash>,
+		prefix: StorageKey,
+		chunk_size: usize,
+	);
+
+	/// Stream up to `count` keys with prefix in chunks of `chunk_size`.
+	fn stream_keys(
+		&self,
+		sink: SubscriptionSink,
+		block: Option<Block::Hash>,
+		prefix: Option<StorageKey>,
+		count: u32,
+		start_key: Option<StorageKey>,
+		chunk_size: usize,
+	);
 }
 
 /// Create new state API that works on full node.
@@ -345,6 +365,51 @@ where
 		self.backend.subscribe_storage(sink, keys);
 		Ok(())
 	}
+
+	fn stream_pairs(
+		&self,
+		mut sink: SubscriptionSink,
+		prefix: StorageKey,
+		chunk_size: Option<u32>,
+		block: Option<Block::Hash>,
+	) -> SubscriptionResult {
+		if let Err(err) = self.deny_unsafe.check_if_safe() {
+			let _ = sink.reject(JsonRpseeError::from(err));
+			return Ok(())
+		}
+
+		self.backend
+			.stream_pairs(sink, block, prefix, crate::stream::chunk_size(chunk_size));
+		Ok(())
+	}
+
+	fn stream_keys(
+		&self,
+		mut sink: SubscriptionSink,
+		prefix: Option<StorageKey>,
+		count: u32,
+		start_key: Option<StorageKey>,
+		chunk_size: Option<u32>,
+		block: Option<Block::Hash>,
+	) -> SubscriptionResult {
+		// Streaming more keys than `state_getKeysPaged` returns is only allowed for unsafe RPC.
+		if count > STORAGE_KEYS_PAGED_MAX_COUNT {
+			if let Err(err) = self.deny_unsafe.check_if_safe() {
+				let _ = sink.reject(JsonRpseeError::from(err));
+				return Ok(())
+			}
+		}
+
+		self.backend.stream_keys(
+			sink,
+			block,
+			prefix,
+			count,
+			start_key,
+			crate::stream::chunk_size(chunk_size),
+		);
+		Ok(())
+	}
 }
 
 /// Child state backend API.
@@ -457,6 +457,83 @@ where
 		self.executor.spawn("substrate-rpc-subscription", Some("rpc"), fut.boxed());
 	}
 
+	fn stream_pairs(
+		&self,
+		mut sink: SubscriptionSink,
+		block: Option<Block::Hash>,
+		prefix: StorageKey,
+		chunk_size: usize,
+	) {
+		let block = match self.block_or_best(block) {
+			Ok(block) => block,
+			Err(e) => {
+				let _ = sink.reject(JsonRpseeError::from(client_err(e)));
+				return
+			},
+		};
+
+		// The storage iterator can't be kept across chunks, so every chunk starts a new one
+		// right after the last key of the previous chunk.
+		let client = self.client.clone();
+		let mut last_key = None;
+		let stream = crate::stream::chunked(move || -> ClientResult<_> {
+			let chunk = client
+				.storage_pairs(block, Some(&prefix), last_key.as_ref())?
+				.take(chunk_size)
+				.collect::<Vec<_>>();
+			let Some((key, _)) = chunk.last() else { return Ok(None) };
+			last_key = Some(key.clone());
+			Ok(Some(chunk))
+		});
+
+		let fut = async move {
+			sink.pipe_from_stream(stream.boxed()).await;
+		};
+
+		self.executor.spawn("substrate-rpc-subscription", Some("rpc"), fut.boxed());
+	}
+
+	fn stream_keys(
+		&self,
+		mut sink: SubscriptionSink,
+		block: Option<Block::Hash>,
+		prefix: Option<StorageKey>,
+		count: u32,
+		start_key: Option<StorageKey>,
+		chunk_size: usize,
+	) {
+		let block = match self.block_or_best(block) {
+			Ok(block) => block,
+			Err(e) => {
+				let _ = sink.reject(JsonRpseeError::from(client_err(e)));
+				return
+			},
+		};
+
+		let client = self.client.clone();
+		let mut last_key = start_key;
+		let mut remaining = count as usize;
+		let stream = crate::stream::chunked(move || -> ClientResult<_> {
+			if remaining == 0 {
+				return Ok(None)
+			}
+			let chunk = client
+				.storage_keys(block, prefix.as_ref(), last_key.as_ref())?
+				.take(chunk_size.min(remaining))
+				.collect::<Vec<_>>();
+			let Some(key) = chunk.last() else { return Ok(None) };
+			last_key = Some(key.clone());
+			remaining -= chunk.len();
+			Ok(Some(chunk))
+		});
+
+		let fut = async move {
+			sink.pipe_from_stream(stream.boxed()).await;
+		};
+
+		self.executor.spawn("substrate-rpc-subscription", Some("rpc"), fut.boxed());
+	}
+
 	fn trace_block(
 		&self,
 		block: Block::Hash,
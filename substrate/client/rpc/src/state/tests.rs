@@ -26,7 +26,7 @@ use jsonrpsee::{
 	types::{error::CallError as RpcCallError, EmptyServerParams as EmptyParams, ErrorObject},
 };
 use sc_block_builder::BlockBuilderBuilder;
-use sc_rpc_api::DenyUnsafe;
+use sc_rpc_api::{DenyUnsafe, StreamEvent};
 use sp_consensus::BlockOrigin;
 use sp_core::{hash::H256, storage::ChildInfo};
 use std::sync::Arc;
@@ -591,3 +591,82 @@ async fn concrete_storage_subscriptions_are_rpc_safe() {
 
 	assert!(sub.is_ok());
 }
+
+#[tokio::test]
+async fn should_stream_pairs_in_chunks() {
+	let client = TestClientBuilder::new()
+		.add_extra_storage(b":map:acc1".to_vec(), vec![1])
+		.add_extra_storage(b":map:acc2".to_vec(), vec![2])
+		.add_extra_storage(b":map:acc3".to_vec(), vec![3])
+		.build();
+	let (api, _child) = new_full(Arc::new(client), test_executor(), DenyUnsafe::No);
+	let api_rpc = api.into_rpc();
+
+	let mut sub = api_rpc
+		.subscribe("state_streamPairs", (StorageKey(b":map".to_vec()), 2))
+		.await
+		.unwrap();
+
+	let pair = |key: &[u8], value| (StorageKey(key.to_vec()), StorageData(vec![value]));
+	assert_eq!(
+		sub.next::<StreamEvent<(StorageKey, StorageData)>>().await.unwrap().unwrap().0,
+		StreamEvent::Items { items: vec![pair(b":map:acc1", 1), pair(b":map:acc2", 2)] }
+	);
+	assert_eq!(
+		sub.next::<StreamEvent<(StorageKey, StorageData)>>().await.unwrap().unwrap().0,
+		StreamEvent::Items { items: vec![pair(b":map:acc3", 3)] }
+	);
+	assert_eq!(
+		sub.next::<StreamEvent<(StorageKey, StorageData)>>().await.unwrap().unwrap().0,
+		StreamEvent::Done
+	);
+}
+
+#[tokio::test]
+async fn should_stream_at_most_count_keys() {
+	let client = TestClientBuilder::new()
+		.add_extra_storage(b":map:acc1".to_vec(), vec![1])
+		.add_extra_storage(b":map:acc2".to_vec(), vec![2])
+		.add_extra_storage(b":map:acc3".to_vec(), vec![3])
+		.build();
+	let (api, _child) = new_full(Arc::new(client), test_executor(), DenyUnsafe::No);
+	let api_rpc = api.into_rpc();
+
+	let prefix = StorageKey(b":map".to_vec());
+	let start_key = StorageKey(b":map:acc1".to_vec());
+	let mut sub = api_rpc.subscribe("state_streamKeys", (prefix, 5, start_key, 1)).await.unwrap();
+
+	let mut keys = Vec::new();
+	loop {
+		match sub.next::<StreamEvent<StorageKey>>().await.unwrap().unwrap().0 {
+			StreamEvent::Items { items } => {
+				assert_eq!(items.len(), 1);
+				keys.extend(items);
+			},
+			event => {
+				assert_eq!(event, StreamEvent::Done);
+				break
+			},
+		}
+	}
+	assert_eq!(keys, vec![StorageKey(b":map:acc2".to_vec()), StorageKey(b":map:acc3".to_vec())]);
+}
+
+#[tokio::test]
+async fn streaming_many_keys_is_rpc_unsafe() {
+	let client = Arc::new(substrate_test_runtime_client::new());
+	let (api, _child) = new_full(client, test_executor(), DenyUnsafe::Yes);
+	let api_rpc = api.into_rpc();
+
+	let prefix = StorageKey(b":map".to_vec());
+	let err = api_rpc
+		.subscribe("state_streamKeys", (prefix.clone(), STORAGE_KEYS_PAGED_MAX_COUNT + 1))
+		.await;
+	assert_matches!(err, Err(RpcError::Call(RpcCallError::Custom(e))) if e.message() == "RPC call is unsafe to be called externally");
+
+	assert!(api_rpc.subscribe("state_streamKeys", (prefix, 10)).await.is_ok());
+	assert!(api_rpc
+		.subscribe("state_streamPairs", [StorageKey(b":map".to_vec())])
+		.await
+		.is_err());
+}
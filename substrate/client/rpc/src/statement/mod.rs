@@ -18,18 +18,27 @@
 
 //! Substrate statement store API.
 
+use crate::SubscriptionTaskExecutor;
 use codec::{Decode, Encode};
-use jsonrpsee::core::{async_trait, RpcResult};
+use futures::{FutureExt, StreamExt};
+use jsonrpsee::{
+	core::{async_trait, Error as JsonRpseeError, RpcResult},
+	types::SubscriptionResult,
+	SubscriptionSink,
+};
 /// Re-export the API for backward compatibility.
-pub use sc_rpc_api::statement::{error::Error, StatementApiServer};
+pub use sc_rpc_api::statement::{
+	error::Error, StatementApiServer, StatementEvent, StatementStoreLimits, StatementStoreStatus,
+};
 use sc_rpc_api::DenyUnsafe;
 use sp_core::Bytes;
-use sp_statement_store::{StatementSource, SubmitResult};
+use sp_statement_store::{StatementFilter, StatementSource, StoreLimits, SubmitResult, MAX_TOPICS};
 use std::sync::Arc;
 
 /// Statement store API
 pub struct StatementStore {
 	store: Arc<dyn sp_statement_store::StatementStore>,
+	executor: SubscriptionTaskExecutor,
 	deny_unsafe: DenyUnsafe,
 }
 
@@ -37,9 +46,10 @@ impl StatementStore {
 	/// Create new instance of Offchain API.
 	pub fn new(
 		store: Arc<dyn sp_statement_store::StatementStore>,
+		executor: SubscriptionTaskExecutor,
 		deny_unsafe: DenyUnsafe,
 	) -> Self {
-		StatementStore { store, deny_unsafe }
+		StatementStore { store, executor, deny_unsafe }
 	}
 }
 
@@ -53,6 +63,50 @@ impl StatementApiServer for StatementStore {
 		Ok(statements.into_iter().map(|(_, s)| s.encode().into()).collect())
 	}
 
+	fn stream_dump(
+		&self,
+		mut sink: SubscriptionSink,
+		chunk_size: Option<u32>,
+	) -> SubscriptionResult {
+		if let Err(err) = self.deny_unsafe.check_if_safe() {
+			let _ = sink.reject(JsonRpseeError::from(err));
+			return Ok(())
+		}
+
+		let hashes = match self.store.statement_hashes() {
+			Ok(hashes) => hashes,
+			Err(e) => {
+				let _ = sink.reject(JsonRpseeError::from(Error::StatementStore(e.to_string())));
+				return Ok(())
+			},
+		};
+
+		// Statements are only read from the store once the previous chunk has been sent. Those
+		// removed in the meantime are skipped.
+		let chunk_size = crate::stream::chunk_size(chunk_size);
+		let store = self.store.clone();
+		let mut hashes = hashes.into_iter();
+		let stream = crate::stream::chunked(move || {
+			let mut chunk = Vec::with_capacity(chunk_size);
+			for hash in hashes.by_ref() {
+				if let Some(statement) = store.statement(&hash)? {
+					chunk.push(Bytes::from(statement.encode()));
+					if chunk.len() == chunk_size {
+						break
+					}
+				}
+			}
+			Ok::<_, sp_statement_store::Error>((!chunk.is_empty()).then_some(chunk))
+		});
+
+		let fut = async move {
+			sink.pipe_from_stream(stream.boxed()).await;
+		};
+
+		self.executor.spawn("substrate-rpc-subscription", Some("rpc"), fut.boxed());
+		Ok(())
+	}
+
 	fn broadcasts(&self, match_all_topics: Vec<[u8; 32]>) -> RpcResult<Vec<Bytes>> {
 		Ok(self
 			.store
@@ -105,4 +159,78 @@ impl StatementApiServer for StatementStore {
 	fn remove(&self, hash: [u8; 32]) -> RpcResult<()> {
 		Ok(self.store.remove(&hash).map_err(|e| Error::StatementStore(e.to_string()))?)
 	}
+
+	fn status(&self) -> RpcResult<StatementStoreStatus> {
+		self.deny_unsafe.check_if_safe()?;
+
+		let status = self.store.status().map_err(|e| Error::StatementStore(e.to_string()))?;
+		Ok(StatementStoreStatus {
+			limits: StatementStoreLimits {
+				max_total_statements: status.limits.max_total_statements as u64,
+				max_total_size: status.limits.max_total_size as u64,
+			},
+			total_statements: status.total_statements as u64,
+			total_size: status.total_size as u64,
+			expired_statements: status.expired_statements as u64,
+			rejections: status
+				.rejections
+				.into_iter()
+				.map(|(reason, count)| (reason.as_str().into(), count))
+				.collect(),
+		})
+	}
+
+	fn set_limits(&self, limits: StatementStoreLimits) -> RpcResult<()> {
+		self.deny_unsafe.check_if_safe()?;
+
+		let limits = StoreLimits {
+			max_total_statements: limits.max_total_statements.try_into().unwrap_or(usize::MAX),
+			max_total_size: limits.max_total_size.try_into().unwrap_or(usize::MAX),
+		};
+		Ok(self
+			.store
+			.set_limits(limits)
+			.map_err(|e| Error::StatementStore(e.to_string()))?)
+	}
+
+	fn subscribe(
+		&self,
+		mut sink: SubscriptionSink,
+		match_all_topics: Vec<[u8; 32]>,
+		dest: Option<[u8; 32]>,
+	) -> SubscriptionResult {
+		if let Err(err) = self.deny_unsafe.check_if_safe() {
+			let _ = sink.reject(JsonRpseeError::from(err));
+			return Ok(())
+		}
+
+		if match_all_topics.len() > MAX_TOPICS {
+			let _ = sink.reject(JsonRpseeError::from(Error::StatementStore(format!(
+				"At most {MAX_TOPICS} topics can be matched."
+			))));
+			return Ok(())
+		}
+
+		let events = match self.store.subscribe(StatementFilter { match_all_topics, dest }) {
+			Ok(events) => events,
+			Err(e) => {
+				let _ = sink.reject(JsonRpseeError::from(Error::StatementStore(e.to_string())));
+				return Ok(())
+			},
+		};
+
+		let stream = events.map(|event| match event {
+			sp_statement_store::StatementEvent::New(statement) =>
+				StatementEvent::New { statement: statement.encode().into() },
+			sp_statement_store::StatementEvent::Expired(hash) =>
+				StatementEvent::Expired { hash: hash.into() },
+		});
+
+		let fut = async move {
+			sink.pipe_from_stream(stream).await;
+		};
+
+		self.executor.spawn("substrate-rpc-subscription", Some("rpc"), fut.boxed());
+		Ok(())
+	}
 }
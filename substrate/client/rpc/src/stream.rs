@@ -0,0 +1,106 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Helpers for subscriptions streaming a result in chunks.
+
+use futures::{stream, Stream};
+use sc_rpc_api::StreamEvent;
+
+/// The number of items per chunk if the caller does not pass one.
+const DEFAULT_CHUNK_SIZE: u32 = 100;
+
+/// The maximum number of items per chunk.
+const MAX_CHUNK_SIZE: u32 = 1000;
+
+/// The chunk size to use for the `requested` one, clamped to `1..=MAX_CHUNK_SIZE`.
+pub(crate) fn chunk_size(requested: Option<u32>) -> usize {
+	requested.unwrap_or(DEFAULT_CHUNK_SIZE).clamp(1, MAX_CHUNK_SIZE) as usize
+}
+
+/// Stream the chunks returned by `next_chunk` until it returns `Ok(None)` or fails.
+///
+/// `next_chunk` is called lazily, once the previous chunk has been consumed. It reads from the
+/// database, so it is run on a blocking thread.
+pub(crate) fn chunked<T, E, F>(next_chunk: F) -> impl Stream<Item = StreamEvent<T>>
+where
+	T: Send + 'static,
+	E: ToString + Send + 'static,
+	F: FnMut() -> Result<Option<Vec<T>>, E> + Send + 'static,
+{
+	stream::unfold(Some(next_chunk), |next_chunk| async move {
+		let mut next_chunk = next_chunk?;
+		let read = tokio::task::spawn_blocking(move || {
+			let chunk = next_chunk();
+			(next_chunk, chunk)
+		});
+		match read.await {
+			Ok((next_chunk, Ok(Some(items)))) =>
+				Some((StreamEvent::Items { items }, Some(next_chunk))),
+			Ok((_, Ok(None))) => Some((StreamEvent::Done, None)),
+			Ok((_, Err(e))) => Some((StreamEvent::Error { error: e.to_string() }, None)),
+			Err(e) => Some((StreamEvent::Error { error: e.to_string() }, None)),
+		}
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use futures::StreamExt;
+
+	#[test]
+	fn chunk_size_is_clamped() {
+		assert_eq!(chunk_size(None), DEFAULT_CHUNK_SIZE as usize);
+		assert_eq!(chunk_size(Some(0)), 1);
+		assert_eq!(chunk_size(Some(10)), 10);
+		assert_eq!(chunk_size(Some(u32::MAX)), MAX_CHUNK_SIZE as usize);
+	}
+
+	#[tokio::test]
+	async fn chunked_ends_with_done_or_error() {
+		let mut chunks = vec![vec![1, 2], vec![3]].into_iter();
+		let events = chunked(move || Ok::<_, String>(chunks.next())).collect::<Vec<_>>().await;
+		assert_eq!(
+			events,
+			vec![
+				StreamEvent::Items { items: vec![1, 2] },
+				StreamEvent::Items { items: vec![3] },
+				StreamEvent::Done,
+			]
+		);
+
+		let mut calls = 0;
+		let events = chunked(move || {
+			calls += 1;
+			if calls == 1 {
+				Ok(Some(vec![1]))
+			} else {
+				Err("failed")
+			}
+		})
+		.collect::<Vec<_>>()
+		.await;
+		assert_eq!(
+			events,
+			vec![
+				StreamEvent::Items { items: vec![1] },
+				StreamEvent::Error { error: "failed".into() }
+			]
+		);
+	}
+}
@@ -21,10 +21,11 @@ use assert_matches::assert_matches;
 use futures::prelude::*;
 use jsonrpsee::{
 	core::Error as RpcError,
+	rpc_params,
 	types::{error::CallError, EmptyServerParams as EmptyParams},
 	RpcModule,
 };
-use sc_network::{self, config::Role, PeerId};
+use sc_network::{self, config::Role, peer_store::BanTarget, PeerId};
 use sc_rpc_api::system::helpers::PeerInfo;
 use sc_utils::mpsc::tracing_unbounded;
 use sp_core::H256;
@@ -98,6 +99,21 @@ fn api<T: Into<Option<Status>>>(sync: T) -> RpcModule<System<Block>> {
 						.unwrap(),
 					);
 				},
+				Request::Status(sender) => {
+					let _ = sender.send(NodeStatus {
+						best_number: 2,
+						best_hash: Default::default(),
+						finalized_number: 1,
+						finalized_hash: Default::default(),
+						peers: status.peers as u32,
+						sync: SyncPhase::Idle,
+						queued_blocks: 0,
+						total_bytes_inbound: 1024,
+						total_bytes_outbound: 512,
+						bytes_inbound_per_sec: 64,
+						bytes_outbound_per_sec: 32,
+					});
+				},
 				Request::NetworkAddReservedPeer(peer, sender) => {
 					let _ = match sc_network::config::parse_str_addr(&peer) {
 						Ok(_) => sender.send(Ok(())),
@@ -116,6 +132,25 @@ fn api<T: Into<Option<Status>>>(sync: T) -> RpcModule<System<Block>> {
 					let _ = sender
 						.send(vec!["QmSk5HQbn6LhUwDiNMseVUjuRYhEtYj4aUZ6WfWoGURpdV".to_string()]);
 				},
+				Request::NetworkBanPeer(target, _, sender) => {
+					let _ = match target.parse::<BanTarget>() {
+						Ok(_) => sender.send(Ok(())),
+						Err(e) => sender.send(Err(error::Error::MalformattedPeerArg(e))),
+					};
+				},
+				Request::NetworkUnbanPeer(target, sender) => {
+					let _ = if target == "198.51.100.0/24" {
+						sender.send(Ok(()))
+					} else {
+						sender.send(Err(error::Error::NotBanned(target)))
+					};
+				},
+				Request::NetworkBannedPeers(sender) => {
+					let _ = sender.send(vec![BannedPeer {
+						target: "198.51.100.0/24".to_string(),
+						expires_at: Some(1_700_000_000),
+					}]);
+				},
 				Request::NodeRoles(sender) => {
 					let _ = sender.send(vec![NodeRole::Authority]);
 				},
@@ -287,6 +322,18 @@ async fn system_network_state() {
 	);
 }
 
+#[tokio::test]
+async fn system_status() {
+	let status: NodeStatus<H256, u64> = api(Status { peers: 1, ..Default::default() })
+		.call("system_unstable_status", EmptyParams::new())
+		.await
+		.unwrap();
+	assert_eq!(status.best_number, 2);
+	assert_eq!(status.peers, 1);
+	assert_eq!(status.sync, SyncPhase::Idle);
+	assert_eq!((status.bytes_inbound_per_sec, status.bytes_outbound_per_sec), (64, 32));
+}
+
 #[tokio::test]
 async fn system_node_roles() {
 	let node_roles: Vec<NodeRole> =
@@ -338,6 +385,49 @@ async fn system_network_reserved_peers() {
 	assert_eq!(reserved_peers, vec!["QmSk5HQbn6LhUwDiNMseVUjuRYhEtYj4aUZ6WfWoGURpdV".to_string()],);
 }
 
+#[tokio::test]
+async fn system_network_ban_peer() {
+	let _good: () = api(None)
+		.call("system_banPeer", rpc_params!["198.51.100.0/24", Some(3600)])
+		.await
+		.expect("IP range can be banned");
+	let _good: () = api(None)
+		.call(
+			"system_banPeer",
+			rpc_params!["QmSk5HQbn6LhUwDiNMseVUjuRYhEtYj4aUZ6WfWoGURpdV", None::<u64>],
+		)
+		.await
+		.expect("PeerId can be banned");
+
+	assert_matches!(
+		api(None).call::<_, ()>("system_banPeer", rpc_params!["198.51.100.1/24", None::<u64>]).await,
+		Err(RpcError::Call(CallError::Custom(err))) if err.message().contains("Expected a PeerId")
+	);
+}
+
+#[tokio::test]
+async fn system_network_unban_peer() {
+	let _good: () = api(None)
+		.call("system_unbanPeer", ["198.51.100.0/24"])
+		.await
+		.expect("banned IP range can be unbanned");
+
+	assert_matches!(
+		api(None).call::<_, ()>("system_unbanPeer", ["203.0.113.0/24"]).await,
+		Err(RpcError::Call(CallError::Custom(err))) if err.message() == "203.0.113.0/24 is not banned"
+	);
+}
+
+#[tokio::test]
+async fn system_network_banned_peers() {
+	let banned_peers: Vec<BannedPeer> =
+		api(None).call("system_bannedPeers", EmptyParams::new()).await.unwrap();
+	assert_eq!(
+		banned_peers,
+		vec![BannedPeer { target: "198.51.100.0/24".to_string(), expires_at: Some(1_700_000_000) }],
+	);
+}
+
 #[test]
 fn test_add_reset_log_filter() {
 	const EXPECTED_BEFORE_ADD: &'static str = "EXPECTED_BEFORE_ADD";
@@ -44,9 +44,9 @@ use sc_keystore::LocalKeystore;
 use sc_network::{
 	config::{FullNetworkConfiguration, SyncMode},
 	peer_store::PeerStore,
-	NetworkService, NetworkStateInfo, NetworkStatusProvider,
+	NetworkEventStream, NetworkRequest, NetworkService, NetworkStateInfo, NetworkStatusProvider,
 };
-use sc_network_bitswap::BitswapRequestHandler;
+use sc_network_bitswap::{BitswapClient, BitswapRequestHandler};
 use sc_network_common::role::Roles;
 use sc_network_light::light_client_requests::handler::LightClientRequestHandler;
 use sc_network_sync::{
@@ -64,7 +64,10 @@ use sc_rpc::{
 	DenyUnsafe, SubscriptionTaskExecutor,
 };
 use sc_rpc_spec_v2::{chain_head::ChainHeadApiServer, transaction::TransactionApiServer};
-use sc_telemetry::{telemetry, ConnectionMessage, Telemetry, TelemetryHandle, SUBSTRATE_INFO};
+use sc_telemetry::{
+	telemetry, ConnectionMessage, Telemetry, TelemetryEndpoints, TelemetryHandle, TelemetryWorker,
+	TelemetryWorkerHandle, SUBSTRATE_INFO,
+};
 use sc_transaction_pool_api::{MaintainedTransactionPool, TransactionPool};
 use sc_utils::mpsc::{tracing_unbounded, TracingUnboundedSender};
 use sp_api::{CallApiAt, ProvideRuntimeApi};
@@ -255,6 +258,53 @@ pub fn new_wasm_executor<H: HostFunctions>(config: &Configuration) -> WasmExecut
 		.build()
 }
 
+/// Creates the [`Telemetry`] of the node and the [`TelemetryWorker`] to run according to
+/// [`Configuration`].
+///
+/// Returns `None` if neither telemetry endpoints nor a local sink are configured.
+pub fn new_telemetry(
+	config: &Configuration,
+) -> sc_telemetry::Result<Option<(TelemetryWorker, Telemetry)>> {
+	let Some(endpoints) = telemetry_endpoints(config) else { return Ok(None) };
+	let worker = TelemetryWorker::new(16)?;
+	let telemetry = register_telemetry(config, &mut worker.handle(), endpoints)?;
+	Ok(Some((worker, telemetry)))
+}
+
+/// Creates the [`Telemetry`] of the node according to [`Configuration`], registered with an
+/// already running worker, e.g. the one of another node of the process.
+///
+/// Returns `None` if neither telemetry endpoints nor a local sink are configured.
+pub fn new_telemetry_with_worker(
+	config: &Configuration,
+	mut worker_handle: TelemetryWorkerHandle,
+) -> sc_telemetry::Result<Option<Telemetry>> {
+	telemetry_endpoints(config)
+		.map(|endpoints| register_telemetry(config, &mut worker_handle, endpoints))
+		.transpose()
+}
+
+/// The telemetry endpoints to connect to, empty ones if only a local sink is configured.
+fn telemetry_endpoints(config: &Configuration) -> Option<TelemetryEndpoints> {
+	config
+		.telemetry_endpoints
+		.clone()
+		.filter(|x| !x.is_empty())
+		.or_else(|| config.telemetry_local_sink.as_ref().map(|_| Default::default()))
+}
+
+fn register_telemetry(
+	config: &Configuration,
+	worker_handle: &mut TelemetryWorkerHandle,
+	endpoints: TelemetryEndpoints,
+) -> sc_telemetry::Result<Telemetry> {
+	let telemetry = worker_handle.new_telemetry(endpoints);
+	match config.telemetry_local_sink.clone() {
+		Some(local_sink) => telemetry.with_local_sink(local_sink),
+		None => Ok(telemetry),
+	}
+}
+
 /// Create an instance of default DB-backend backend.
 pub fn new_db_backend<Block>(
 	settings: DatabaseSettings,
@@ -318,14 +368,26 @@ where
 
 /// Shared network instance implementing a set of mandatory traits.
 pub trait SpawnTaskNetwork<Block: BlockT>:
-	NetworkStateInfo + NetworkStatusProvider + Send + Sync + 'static
+	NetworkStateInfo
+	+ NetworkStatusProvider
+	+ NetworkRequest
+	+ NetworkEventStream
+	+ Send
+	+ Sync
+	+ 'static
 {
 }
 
 impl<T, Block> SpawnTaskNetwork<Block> for T
 where
 	Block: BlockT,
-	T: NetworkStateInfo + NetworkStatusProvider + Send + Sync + 'static,
+	T: NetworkStateInfo
+		+ NetworkStatusProvider
+		+ NetworkRequest
+		+ NetworkEventStream
+		+ Send
+		+ Sync
+		+ 'static,
 {
 }
 
@@ -471,6 +533,17 @@ where
 		),
 	);
 
+	// Fetches the content requested through the IPFS RPC from the peers.
+	let bitswap = config.network.ipfs_server.then(|| {
+		let bitswap = BitswapClient::new(Arc::new(network.clone()));
+		spawn_handle.spawn(
+			"bitswap-client",
+			Some("networking"),
+			bitswap.clone().run(network.event_stream("bitswap-client")),
+		);
+		bitswap
+	});
+
 	let rpc_id_provider = config.rpc_id_provider.take();
 
 	// jsonrpsee RPC
@@ -484,6 +557,7 @@ where
 			system_rpc_tx.clone(),
 			&config,
 			backend.clone(),
+			bitswap.clone(),
 			&*rpc_builder,
 		)
 	};
@@ -583,6 +657,7 @@ fn gen_rpc_module<TBl, TBackend, TCl, TRpc, TExPool>(
 	system_rpc_tx: TracingUnboundedSender<sc_rpc::system::Request<TBl>>,
 	config: &Configuration,
 	backend: Arc<TBackend>,
+	bitswap: Option<BitswapClient>,
 	rpc_builder: &(dyn Fn(DenyUnsafe, SubscriptionTaskExecutor) -> Result<RpcModule<TRpc>, Error>),
 ) -> Result<RpcModule<()>, Error>
 where
@@ -659,6 +734,13 @@ where
 		rpc_api.merge(offchain).map_err(|e| Error::Application(e.into()))?;
 	}
 
+	if let (Some(bitswap), Some(store)) = (bitswap, backend.content_store()) {
+		let ipfs =
+			sc_rpc::ipfs::Ipfs::new(client.clone(), store, Some(bitswap), deny_unsafe).into_rpc();
+
+		rpc_api.merge(ipfs).map_err(|e| Error::Application(e.into()))?;
+	}
+
 	// Part of the RPC v2 spec.
 	rpc_api.merge(transaction_v2).map_err(|e| Error::Application(e.into()))?;
 	rpc_api.merge(chain_head_v2).map_err(|e| Error::Application(e.into()))?;
@@ -857,15 +939,18 @@ where
 	);
 	net_config.add_notification_protocol(transactions_handler_proto.set_config());
 
-	// Create `PeerStore` and initialize it with bootnode peer ids.
-	let peer_store = PeerStore::new(
-		net_config
-			.network_config
-			.boot_nodes
-			.iter()
-			.map(|bootnode| bootnode.peer_id)
-			.collect(),
-	);
+	// Create `PeerStore` and initialize it with bootnode peer ids, and with the reputations and
+	// bans of the previous run if the network configuration is stored on disk.
+	let bootnodes = net_config
+		.network_config
+		.boot_nodes
+		.iter()
+		.map(|bootnode| bootnode.peer_id)
+		.collect();
+	let peer_store = match &net_config.network_config.net_config_path {
+		Some(path) => PeerStore::load(bootnodes, path.join("peer_store.json")),
+		None => PeerStore::new(bootnodes),
+	};
 	let peer_store_handle = peer_store.handle();
 	spawn_handle.spawn("peer-store", Some("networking"), peer_store.run());
 
@@ -597,14 +597,14 @@ where
 		}
 
 		let info = self.backend.blockchain().info();
-		let gap_block = info
-			.block_gap
-			.map_or(false, |(start, _)| *import_headers.post().number() == start);
+		let number = *import_headers.post().number();
+		let gap_block = info.block_gap.map_or(false, |(start, _)| number == start) ||
+			self.is_block_gap_top(number, hash)?;
 
 		// the block is lower than our last finalized block so it must revert
 		// finality, refusing import.
 		if status == blockchain::BlockStatus::Unknown &&
-			*import_headers.post().number() <= info.finalized_number &&
+			number <= info.finalized_number &&
 			!gap_block
 		{
 			return Err(sp_blockchain::Error::NotInFinalizedChain)
@@ -837,6 +837,12 @@ where
 				StateAction::ApplyChanges(sc_consensus::StorageChanges::Changes(_)),
 			) => return Ok(PrepareStorageChangesResult::Discard(ImportResult::MissingState)),
 			(_, StateAction::ApplyChanges(changes)) => (true, Some(changes)),
+			(BlockStatus::Unknown, StateAction::Skip)
+				if self.is_block_gap_top(
+					*import_block.header.number(),
+					import_block.post_hash(),
+				)? =>
+				(false, None),
 			(BlockStatus::Unknown, _) =>
 				return Ok(PrepareStorageChangesResult::Discard(ImportResult::UnknownParent)),
 			(_, StateAction::Skip) => (false, None),
@@ -883,6 +889,23 @@ where
 		Ok(PrepareStorageChangesResult::Import(storage_changes))
 	}
 
+	/// Whether the block is the highest block missing from the block gap left by a warp sync,
+	/// i.e. the parent of the block right above the gap. Such a block is imported without its
+	/// parent while the block history is downloaded backwards.
+	fn is_block_gap_top(
+		&self,
+		number: NumberFor<Block>,
+		hash: Block::Hash,
+	) -> sp_blockchain::Result<bool> {
+		let blockchain = self.backend.blockchain();
+		match blockchain.info().block_gap {
+			Some((_, end)) if end == number => {},
+			_ => return Ok(false),
+		}
+		let Some(above) = blockchain.hash(number + One::one())? else { return Ok(false) };
+		Ok(blockchain.header(above)?.map_or(false, |header| *header.parent_hash() == hash))
+	}
+
 	fn apply_finality_with_block_hash(
 		&self,
 		operation: &mut ClientImportOperation<Block, B>,
@@ -1811,6 +1834,10 @@ where
 		{
 			BlockStatus::InChainWithState | BlockStatus::Queued => {},
 			BlockStatus::Unknown if allow_missing_parent => {},
+			BlockStatus::Unknown
+				if self
+					.is_block_gap_top(number, hash)
+					.map_err(|e| ConsensusError::ClientImport(e.to_string()))? => {},
 			BlockStatus::Unknown => return Ok(ImportResult::UnknownParent),
 			BlockStatus::InChainPruned if allow_missing_state => {},
 			BlockStatus::InChainPruned => return Ok(ImportResult::MissingState),
@@ -20,6 +20,7 @@
 
 pub use sc_client_db::{BlocksPruning, Database, DatabaseSource, PruningMode};
 pub use sc_executor::{WasmExecutionMethod, WasmtimeInstantiationStrategy};
+pub use sc_informant::OutputFormat as InformantOutputFormat;
 pub use sc_network::{
 	config::{
 		MultiaddrWithPeerId, NetworkConfiguration, NodeKeyConfig, NonDefaultSetConfig, ProtocolId,
@@ -30,10 +31,11 @@ pub use sc_network::{
 	},
 	Multiaddr,
 };
+pub use sc_rpc_server::{RateLimit, RpcRateLimit};
 
 use prometheus_endpoint::Registry;
 use sc_chain_spec::ChainSpec;
-pub use sc_telemetry::TelemetryEndpoints;
+pub use sc_telemetry::{LocalSinkConfig as TelemetryLocalSinkConfig, TelemetryEndpoints};
 pub use sc_transaction_pool::Options as TransactionPoolOptions;
 use sp_core::crypto::SecretString;
 use std::{
@@ -92,18 +94,29 @@ pub struct Configuration {
 	pub rpc_max_request_size: u32,
 	/// Maximum payload of a rpc response.
 	pub rpc_max_response_size: u32,
+	/// Maximum number of calls in a rpc batch request, `None` if not limited.
+	pub rpc_max_batch_request_len: Option<u32>,
 	/// Custom JSON-RPC subscription ID provider.
 	///
 	/// Default: [`crate::RandomStringSubscriptionId`].
 	pub rpc_id_provider: Option<Box<dyn crate::RpcSubscriptionIdProvider>>,
 	/// Maximum allowed subscriptions per rpc connection
 	pub rpc_max_subs_per_conn: u32,
+	/// Rate limits of the rpc clients.
+	pub rpc_rate_limit: RpcRateLimit,
+	/// JSON-RPC listeners, each with its own settings.
+	///
+	/// If not empty, these replace the listener configured by `rpc_addr` and the other `rpc_*`
+	/// settings.
+	pub rpc_endpoints: Vec<RpcEndpoint>,
 	/// JSON-RPC server default port.
 	pub rpc_port: u16,
 	/// Prometheus endpoint configuration. `None` if disabled.
 	pub prometheus_config: Option<PrometheusConfig>,
 	/// Telemetry service URL. `None` if disabled.
 	pub telemetry_endpoints: Option<TelemetryEndpoints>,
+	/// Local file or socket the telemetry messages are also written to. `None` if disabled.
+	pub telemetry_local_sink: Option<TelemetryLocalSinkConfig>,
 	/// The default number of 64KB pages to allocate for Wasm execution
 	pub default_heap_pages: Option<u64>,
 	/// Should offchain workers be executed.
@@ -178,6 +191,8 @@ pub struct OffchainWorkerConfig {
 	pub enabled: bool,
 	/// allow writes from the runtime to the offchain worker database.
 	pub indexing_enabled: bool,
+	/// When and how many offchain workers are run.
+	pub scheduling_policy: sc_offchain::SchedulingPolicy,
 }
 
 /// Configuration of the Prometheus endpoint.
@@ -263,6 +278,35 @@ impl Default for RpcMethods {
 	}
 }
 
+/// A JSON-RPC listener.
+#[derive(Debug, Clone)]
+pub struct RpcEndpoint {
+	/// The address to listen on.
+	pub listen_addr: SocketAddr,
+	/// Listen on a random port if `listen_addr` is not available.
+	pub retry_random_port: bool,
+	/// RPC methods to expose.
+	pub rpc_methods: RpcMethods,
+	/// Further restricts the exposed methods to the listed ones, `None` to not restrict them.
+	///
+	/// Entries are either method names or prefixes followed by `*`, e.g. `chainHead_*`.
+	pub allowed_methods: Option<Vec<String>>,
+	/// CORS settings, `None` if all origins are allowed.
+	pub cors: Option<Vec<String>>,
+	/// Maximum number of connections.
+	pub max_connections: u32,
+	/// Maximum number of subscriptions per connection.
+	pub max_subs_per_conn: u32,
+	/// Maximum payload of a request in megabytes.
+	pub max_request_size: u32,
+	/// Maximum payload of a response in megabytes.
+	pub max_response_size: u32,
+	/// Maximum number of calls in a batch request, `None` if not limited.
+	pub max_batch_request_len: Option<u32>,
+	/// Rate limits of the clients.
+	pub rate_limit: RpcRateLimit,
+}
+
 #[static_init::dynamic(drop, lazy)]
 static mut BASE_PATH_TEMP: Option<TempDir> = None;
 
@@ -34,7 +34,11 @@ mod client;
 mod metrics;
 mod task_manager;
 
-use std::{collections::HashMap, net::SocketAddr};
+use std::{
+	collections::HashMap,
+	net::SocketAddr,
+	time::{Duration, UNIX_EPOCH},
+};
 
 use codec::{Decode, Encode};
 use futures::{channel::mpsc, pin_mut, FutureExt, StreamExt};
@@ -42,9 +46,10 @@ use jsonrpsee::{core::Error as JsonRpseeError, RpcModule};
 use log::{debug, error, warn};
 use sc_client_api::{blockchain::HeaderBackend, BlockBackend, BlockchainEvents, ProofProvider};
 use sc_network::{
-	config::MultiaddrWithPeerId, NetworkBlock, NetworkPeers, NetworkStateInfo, PeerId,
+	config::MultiaddrWithPeerId, peer_store::BanTarget, NetworkBlock, NetworkPeers,
+	NetworkStateInfo, NetworkStatusProvider, PeerId,
 };
-use sc_network_sync::SyncingService;
+use sc_network_sync::{SyncStatusProvider, SyncingService};
 use sc_utils::mpsc::TracingUnboundedReceiver;
 use sp_blockchain::HeaderMetadata;
 use sp_consensus::SyncOracle;
@@ -53,9 +58,10 @@ use sp_runtime::traits::{Block as BlockT, Header as HeaderT};
 pub use self::{
 	builder::{
 		build_network, new_client, new_db_backend, new_full_client, new_full_parts,
-		new_full_parts_with_genesis_builder, new_native_or_wasm_executor, new_wasm_executor,
-		spawn_tasks, BuildNetworkParams, KeystoreContainer, NetworkStarter, SpawnTasksParams,
-		TFullBackend, TFullCallExecutor, TFullClient,
+		new_full_parts_with_genesis_builder, new_native_or_wasm_executor, new_telemetry,
+		new_telemetry_with_worker, new_wasm_executor, spawn_tasks, BuildNetworkParams,
+		KeystoreContainer, NetworkStarter, SpawnTasksParams, TFullBackend, TFullCallExecutor,
+		TFullClient,
 	},
 	client::{ClientConfig, LocalCallExecutor},
 	error::Error,
@@ -67,11 +73,12 @@ pub use sc_chain_spec::{
 };
 
 pub use config::{
-	BasePath, BlocksPruning, Configuration, DatabaseSource, PruningMode, Role, RpcMethods, TaskType,
+	BasePath, BlocksPruning, Configuration, DatabaseSource, PruningMode, Role, RpcEndpoint,
+	RpcMethods, TaskType,
 };
 pub use sc_chain_spec::{
 	ChainSpec, ChainType, Extension as ChainSpecExtension, GenericChainSpec, NoExtension,
-	Properties, RuntimeGenesis,
+	Properties, RuntimeGenesis, SyncCheckpoint,
 };
 
 pub use sc_consensus::ImportQueue;
@@ -229,6 +236,8 @@ pub async fn build_system_rpc_future<
 ) {
 	// Current best block at initialization, to report to the RPC layer.
 	let starting_block = client.info().best_number;
+	// Transfer rates reported by the status RPC, averaged between consecutive requests.
+	let mut bandwidth = sc_informant::BandwidthRates::new();
 
 	loop {
 		// Answer incoming RPC requests.
@@ -286,6 +295,22 @@ pub async fn build_system_rpc_future<
 					break
 				}
 			},
+			sc_rpc::system::Request::Status(sender) => {
+				let Ok(net_status) = network_service.status().await else { break };
+				match sync_service.status().await {
+					Ok(sync_status) => {
+						bandwidth.update(&net_status);
+						let status = sc_informant::NodeStatus::new(
+							&client.info(),
+							&net_status,
+							&sync_status,
+							&bandwidth,
+						);
+						let _ = sender.send(rpc_node_status(status));
+					},
+					Err(_) => log::error!("`SyncingEngine` shut down"),
+				}
+			},
 			sc_rpc::system::Request::NetworkAddReservedPeer(peer_addr, sender) => {
 				let result = match MultiaddrWithPeerId::try_from(peer_addr) {
 					Ok(peer) => network_service.add_reserved_peer(peer),
@@ -315,6 +340,37 @@ pub async fn build_system_rpc_future<
 					break
 				}
 			},
+			sc_rpc::system::Request::NetworkBanPeer(target, duration, sender) => {
+				let _ = match target.parse::<BanTarget>() {
+					Ok(target) => {
+						network_service.ban(target, duration.map(Duration::from_secs));
+						sender.send(Ok(()))
+					},
+					Err(e) =>
+						sender.send(Err(sc_rpc::system::error::Error::MalformattedPeerArg(e))),
+				};
+			},
+			sc_rpc::system::Request::NetworkUnbanPeer(target, sender) => {
+				let _ = match target.parse::<BanTarget>() {
+					Ok(parsed) if network_service.unban(&parsed) => sender.send(Ok(())),
+					Ok(_) => sender.send(Err(sc_rpc::system::error::Error::NotBanned(target))),
+					Err(e) =>
+						sender.send(Err(sc_rpc::system::error::Error::MalformattedPeerArg(e))),
+				};
+			},
+			sc_rpc::system::Request::NetworkBannedPeers(sender) => {
+				let bans = network_service
+					.bans()
+					.into_iter()
+					.map(|ban| sc_rpc::system::BannedPeer {
+						target: ban.target.to_string(),
+						expires_at: ban.expires.and_then(|expires| {
+							expires.duration_since(UNIX_EPOCH).ok().map(|d| d.as_secs())
+						}),
+					})
+					.collect();
+				let _ = sender.send(bans);
+			},
 			sc_rpc::system::Request::NodeRoles(sender) => {
 				use sc_rpc::system::NodeRole;
 
@@ -348,11 +404,11 @@ pub async fn build_system_rpc_future<
 
 // Wrapper for HTTP and WS servers that makes sure they are properly shut down.
 mod waiting {
-	pub struct Server(pub Option<sc_rpc_server::Server>);
+	pub struct Server(pub Vec<sc_rpc_server::Server>);
 
 	impl Drop for Server {
 		fn drop(&mut self) {
-			if let Some(server) = self.0.take() {
+			for server in self.0.drain(..) {
 				// This doesn't not wait for the server to be stopped but fires the signal.
 				let _ = server.stop();
 			}
@@ -360,6 +416,48 @@ mod waiting {
 	}
 }
 
+/// Subscription ID provider shared by the RPC servers of all endpoints.
+#[derive(Debug, Clone)]
+struct SharedIdProvider(Arc<dyn RpcSubscriptionIdProvider>);
+
+impl RpcSubscriptionIdProvider for SharedIdProvider {
+	fn next_id(&self) -> jsonrpsee::types::SubscriptionId<'static> {
+		self.0.next_id()
+	}
+}
+
+/// Convert the status assembled by the informant into its RPC representation.
+fn rpc_node_status<Hash, Number>(
+	status: sc_informant::NodeStatus<Hash, Number>,
+) -> sc_rpc::system::NodeStatus<Hash, Number> {
+	use sc_informant::SyncPhase;
+	use sc_rpc::system::SyncPhase as RpcSyncPhase;
+
+	let sync = match status.sync {
+		SyncPhase::BlockHistory { number } => RpcSyncPhase::BlockHistory { number },
+		SyncPhase::AwaitingTargetBlock => RpcSyncPhase::AwaitingTargetBlock,
+		SyncPhase::Warping { phase, total_bytes } => RpcSyncPhase::Warping { phase, total_bytes },
+		SyncPhase::DownloadingState { percentage, size } =>
+			RpcSyncPhase::DownloadingState { percentage, size },
+		SyncPhase::Idle => RpcSyncPhase::Idle,
+		SyncPhase::Downloading { target } => RpcSyncPhase::Downloading { target },
+		SyncPhase::Importing { target } => RpcSyncPhase::Importing { target },
+	};
+	sc_rpc::system::NodeStatus {
+		best_number: status.best_number,
+		best_hash: status.best_hash,
+		finalized_number: status.finalized_number,
+		finalized_hash: status.finalized_hash,
+		peers: status.peers,
+		sync,
+		queued_blocks: status.queued_blocks,
+		total_bytes_inbound: status.total_bytes_inbound,
+		total_bytes_outbound: status.total_bytes_outbound,
+		bytes_inbound_per_sec: status.bytes_inbound_per_sec,
+		bytes_outbound_per_sec: status.bytes_outbound_per_sec,
+	}
+}
+
 /// Starts RPC servers.
 fn start_rpc_servers<R>(
 	config: &Configuration,
@@ -383,33 +481,68 @@ where
 		addr
 	};
 
-	let addr = config.rpc_addr.unwrap_or_else(|| ([127, 0, 0, 1], config.rpc_port).into());
-	let backup_addr = backup_port(addr);
+	let endpoints = if config.rpc_endpoints.is_empty() {
+		vec![RpcEndpoint {
+			listen_addr: config
+				.rpc_addr
+				.unwrap_or_else(|| ([127, 0, 0, 1], config.rpc_port).into()),
+			retry_random_port: true,
+			rpc_methods: config.rpc_methods,
+			allowed_methods: None,
+			cors: config.rpc_cors.clone(),
+			max_connections: config.rpc_max_connections,
+			max_subs_per_conn: config.rpc_max_subs_per_conn,
+			max_request_size: config.rpc_max_request_size,
+			max_response_size: config.rpc_max_response_size,
+			max_batch_request_len: config.rpc_max_batch_request_len,
+			rate_limit: config.rpc_rate_limit.clone(),
+		}]
+	} else {
+		config.rpc_endpoints.clone()
+	};
+
 	let metrics = sc_rpc_server::RpcMetrics::new(config.prometheus_registry())?;
+	let id_provider = rpc_id_provider.map(|provider| SharedIdProvider(provider.into()));
+
+	let mut servers = Vec::with_capacity(endpoints.len());
+	for endpoint in endpoints {
+		let addr = endpoint.listen_addr;
+		let mut addrs = vec![addr];
+		if endpoint.retry_random_port {
+			addrs.push(backup_port(addr));
+		}
 
-	let server_config = sc_rpc_server::Config {
-		addrs: [addr, backup_addr],
-		max_connections: config.rpc_max_connections,
-		max_payload_in_mb: config.rpc_max_request_size,
-		max_payload_out_mb: config.rpc_max_response_size,
-		max_subs_per_conn: config.rpc_max_subs_per_conn,
-		rpc_api: gen_rpc_module(deny_unsafe(addr, &config.rpc_methods))?,
-		metrics,
-		id_provider: rpc_id_provider,
-		cors: config.rpc_cors.as_ref(),
-		tokio_handle: config.tokio_handle.clone(),
-	};
+		let server_config = sc_rpc_server::Config {
+			addrs,
+			max_connections: endpoint.max_connections,
+			max_payload_in_mb: endpoint.max_request_size,
+			max_payload_out_mb: endpoint.max_response_size,
+			max_batch_len: endpoint.max_batch_request_len,
+			max_subs_per_conn: endpoint.max_subs_per_conn,
+			rpc_api: gen_rpc_module(deny_unsafe(addr, &endpoint.rpc_methods))?,
+			allowed_methods: endpoint.allowed_methods,
+			metrics: metrics.clone(),
+			rate_limit: endpoint.rate_limit,
+			id_provider: id_provider
+				.clone()
+				.map(|provider| Box::new(provider) as Box<dyn RpcSubscriptionIdProvider>),
+			cors: endpoint.cors.as_ref(),
+			tokio_handle: config.tokio_handle.clone(),
+		};
 
-	// TODO: https://github.com/paritytech/substrate/issues/13773
-	//
-	// `block_in_place` is a hack to allow callers to call `block_on` prior to
-	// calling `start_rpc_servers`.
-	match tokio::task::block_in_place(|| {
-		config.tokio_handle.block_on(sc_rpc_server::start_server(server_config))
-	}) {
-		Ok(server) => Ok(Box::new(waiting::Server(Some(server)))),
-		Err(e) => Err(Error::Application(e)),
+		// TODO: https://github.com/paritytech/substrate/issues/13773
+		//
+		// `block_in_place` is a hack to allow callers to call `block_on` prior to
+		// calling `start_rpc_servers`.
+		match tokio::task::block_in_place(|| {
+			config.tokio_handle.block_on(sc_rpc_server::start_server(server_config))
+		}) {
+			Ok(server) => servers.push(server),
+			Err(e) => return Err(Error::Application(e)),
+		}
 	}
+
+	Ok(Box::new(waiting::Server(servers)))
 }
 
 /// Transaction pool adapter.
@@ -250,11 +250,15 @@ fn node_config<
 		rpc_methods: Default::default(),
 		rpc_max_request_size: Default::default(),
 		rpc_max_response_size: Default::default(),
+		rpc_max_batch_request_len: Default::default(),
 		rpc_id_provider: Default::default(),
 		rpc_max_subs_per_conn: Default::default(),
+		rpc_rate_limit: Default::default(),
+		rpc_endpoints: Default::default(),
 		rpc_port: 9944,
 		prometheus_config: None,
 		telemetry_endpoints: None,
+		telemetry_local_sink: None,
 		default_heap_pages: None,
 		offchain_worker: Default::default(),
 		force_authoring: false,
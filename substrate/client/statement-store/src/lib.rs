@@ -29,9 +29,11 @@
 //! * For a given account id, there may be at most `max_count` statements with `max_size` total data
 //!   size. To satisfy this, statements for this account ID are removed from the store starting with
 //!   the lowest priority until a constraint is satisfied.
-//! * There may not be more than `MAX_TOTAL_STATEMENTS` total statements with `MAX_TOTAL_SIZE` size.
-//!   To satisfy this, statements are removed from the store starting with the lowest
-//!   `global_priority` until a constraint is satisfied.
+//! * There may not be more than `Options::max_total_statements` total statements with
+//!   `Options::max_total_size` size. To satisfy this, statements of the same account are removed
+//!   from the store starting with the lowest priority until a constraint is satisfied. The limits
+//!   may be changed at runtime with `StatementStore::set_limits`, which then removes the statements
+//!   of the accounts using the most space first.
 //!
 //! When a new statement is inserted that would not satisfy constraints in the first place, no
 //! statements are deleted and `Ignored` result is returned.
@@ -43,6 +45,13 @@
 //! explicitly with the `remove` function) the statement is marked as expired. Expired statements
 //! can't be added to the store for `Options::purge_after_sec` seconds. This is to prevent old
 //! statements from being propagated on the network.
+//!
+//! Subscriptions.
+//!
+//! Subscribers are notified about the statements matching their topics and decryption key that
+//! are inserted into or expired from the store. A subscriber not keeping up with the notifications
+//! is dropped. The notifications are sent to the subscribers by a separate task, so that the index
+//! isn't locked while matching them.
 
 #![warn(missing_docs)]
 #![warn(unused_extern_crates)]
@@ -51,8 +60,12 @@ mod metrics;
 
 pub use sp_statement_store::{Error, StatementStore, MAX_TOPICS};
 
+use futures::{
+	channel::mpsc::{UnboundedReceiver, UnboundedSender},
+	StreamExt,
+};
 use metrics::MetricsLink as PrometheusMetrics;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use prometheus_endpoint::Registry as PrometheusRegistry;
 use sc_keystore::LocalKeystore;
 use sp_api::ProvideRuntimeApi;
@@ -63,16 +76,22 @@ use sp_statement_store::{
 	runtime_api::{
 		InvalidStatement, StatementSource, StatementStoreExt, ValidStatement, ValidateStatement,
 	},
-	AccountId, BlockHash, Channel, DecryptionKey, Hash, NetworkPriority, Proof, Result, Statement,
-	SubmitResult, Topic,
+	AccountId, BlockHash, Channel, DecryptionKey, Hash, NetworkPriority, Proof, RejectionReason,
+	Result, Statement, StatementEvent, StatementEventStream, StatementFilter, StoreLimits,
+	StoreStatus, SubmitResult, Topic,
 };
 use std::{
-	collections::{BTreeMap, HashMap, HashSet},
-	sync::Arc,
+	collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+	sync::{
+		atomic::{AtomicUsize, Ordering},
+		Arc,
+	},
 };
 
 const KEY_VERSION: &[u8] = b"version".as_slice();
 const CURRENT_VERSION: u32 = 1;
+/// Limits set with `StatementStore::set_limits`, along with the configured ones they replaced.
+const KEY_LIMITS: &[u8] = b"limits".as_slice();
 
 const LOG_TARGET: &str = "statement-store";
 
@@ -82,6 +101,12 @@ const DEFAULT_MAX_TOTAL_SIZE: usize = 64 * 1024 * 1024;
 
 const MAINTENANCE_PERIOD: std::time::Duration = std::time::Duration::from_secs(30);
 
+/// Number of notifications buffered for a subscriber before it is dropped.
+const SUBSCRIPTION_BUFFER: usize = 1024;
+
+/// Maximum number of subscribers of the store.
+const MAX_SUBSCRIBERS: usize = 1024;
+
 mod col {
 	pub const META: u8 = 0;
 	pub const STATEMENTS: u8 = 1;
@@ -93,7 +118,7 @@ mod col {
 #[derive(Eq, PartialEq, Debug, Ord, PartialOrd, Clone, Copy)]
 struct Priority(u32);
 
-#[derive(PartialEq, Eq)]
+#[derive(PartialEq, Eq, Clone, Copy)]
 struct PriorityKey {
 	hash: Hash,
 	priority: Priority,
@@ -127,16 +152,37 @@ struct StatementsForAccount {
 	data_size: usize,
 }
 
+impl StatementsForAccount {
+	/// The key of the `account` in the accounts ordered by usage, `None` if it has no statements.
+	fn usage_key(&self, account: &AccountId) -> Option<UsageKey> {
+		self.by_priority.keys().next().map(|lowest| UsageKey {
+			data_size: self.data_size,
+			lowest: std::cmp::Reverse(*lowest),
+			account: *account,
+		})
+	}
+}
+
+/// Key ordering the accounts by the space they use, then by their lowest priority statement,
+/// the greatest key being the account whose statement is evicted first.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+struct UsageKey {
+	data_size: usize,
+	lowest: std::cmp::Reverse<PriorityKey>,
+	account: AccountId,
+}
+
 /// Store configuration
+#[derive(Debug, Clone)]
 pub struct Options {
 	/// Maximum statement allowed in the store. Once this limit is reached lower-priority
 	/// statements may be evicted.
-	max_total_statements: usize,
+	pub max_total_statements: usize,
 	/// Maximum total data size allowed in the store. Once this limit is reached lower-priority
 	/// statements may be evicted.
-	max_total_size: usize,
+	pub max_total_size: usize,
 	/// Number of seconds for which removed statements won't be allowed to be added back in.
-	purge_after_sec: u64,
+	pub purge_after_sec: u64,
 }
 
 impl Default for Options {
@@ -157,8 +203,81 @@ struct Index {
 	entries: HashMap<Hash, (AccountId, Priority, usize)>,
 	expired: HashMap<Hash, u64>, // Value is expiration timestamp.
 	accounts: HashMap<AccountId, StatementsForAccount>,
+	// Accounts with statements, ordered by their usage.
+	by_usage: BTreeSet<UsageKey>,
 	options: Options,
 	total_size: usize,
+	subscriptions: Arc<Subscriptions>,
+	notifications: Option<UnboundedSender<Notification>>,
+}
+
+/// The subscribers of the store.
+#[derive(Default)]
+struct Subscriptions {
+	subscribers: Mutex<Vec<Subscriber>>,
+	/// Number of `subscribers`, read by the index without locking them.
+	count: AtomicUsize,
+}
+
+struct Subscriber {
+	filter: StatementFilter,
+	sender: futures::channel::mpsc::Sender<StatementEvent>,
+}
+
+/// An event about an indexed statement, along with its topics and decryption key.
+struct Notification {
+	topics: [Option<Topic>; MAX_TOPICS],
+	key: Option<DecryptionKey>,
+	event: StatementEvent,
+}
+
+impl Subscriptions {
+	/// Add a subscriber to the statements matching `filter`.
+	fn subscribe(&self, filter: StatementFilter) -> Result<StatementEventStream> {
+		let mut subscribers = self.subscribers.lock();
+		subscribers.retain(|subscriber| !subscriber.sender.is_closed());
+		if subscribers.len() >= MAX_SUBSCRIBERS {
+			self.count.store(subscribers.len(), Ordering::Relaxed);
+			return Err(Error::TooManySubscribers)
+		}
+
+		let (sender, receiver) = futures::channel::mpsc::channel(SUBSCRIPTION_BUFFER);
+		subscribers.push(Subscriber { filter, sender });
+		self.count.store(subscribers.len(), Ordering::Relaxed);
+		Ok(receiver)
+	}
+
+	/// Send the event of `notification` to the interested subscribers, dropping the ones that are
+	/// gone or lagging behind.
+	fn dispatch(&self, notification: Notification) {
+		let Notification { topics, key, event } = notification;
+		let mut subscribers = self.subscribers.lock();
+		subscribers.retain_mut(|subscriber| {
+			let filter = &subscriber.filter;
+			if filter.dest != key ||
+				!filter.match_all_topics.iter().all(|t| topics.contains(&Some(*t)))
+			{
+				return !subscriber.sender.is_closed()
+			}
+			match subscriber.sender.try_send(event.clone()) {
+				Ok(()) => true,
+				Err(e) => {
+					if e.is_full() {
+						log::debug!(target: LOG_TARGET, "Dropped lagging subscriber");
+					}
+					false
+				},
+			}
+		});
+		self.count.store(subscribers.len(), Ordering::Relaxed);
+	}
+
+	/// Dispatch the `notifications` sent by the index until the store is dropped.
+	async fn broadcast(self: Arc<Self>, mut notifications: UnboundedReceiver<Notification>) {
+		while let Some(notification) = notifications.next().await {
+			self.dispatch(notification);
+		}
+	}
 }
 
 struct ClientWrapper<Block, Client> {
@@ -193,6 +312,9 @@ where
 pub struct Store {
 	db: parity_db::Db,
 	index: RwLock<Index>,
+	subscriptions: Arc<Subscriptions>,
+	// Notifications of the index, until taken by the task dispatching them.
+	notifications: Mutex<Option<UnboundedReceiver<Notification>>>,
 	validate_fn: Box<
 		dyn Fn(
 				Option<BlockHash>,
@@ -206,6 +328,9 @@ pub struct Store {
 	// Used for testing
 	time_override: Option<u64>,
 	metrics: PrometheusMetrics,
+	rejections: Mutex<HashMap<RejectionReason, u64>>,
+	// Limits the store was opened with.
+	configured_limits: StoreLimits,
 }
 
 enum IndexQuery {
@@ -216,12 +341,16 @@ enum IndexQuery {
 
 enum MaybeInserted {
 	Inserted(HashSet<Hash>),
-	Ignored,
+	Ignored(RejectionReason),
 }
 
 impl Index {
-	fn new(options: Options) -> Index {
-		Index { options, ..Default::default() }
+	fn new(
+		options: Options,
+		subscriptions: Arc<Subscriptions>,
+		notifications: UnboundedSender<Notification>,
+	) -> Index {
+		Index { options, subscriptions, notifications: Some(notifications), ..Default::default() }
 	}
 
 	fn insert_new(&mut self, hash: Hash, account: AccountId, statement: &Statement) {
@@ -240,6 +369,7 @@ impl Index {
 		let priority = Priority(statement.priority().unwrap_or(0));
 		self.entries.insert(hash, (account, priority, statement.data_len()));
 		self.total_size += statement.data_len();
+		self.unindex_usage(&account);
 		let account_info = self.accounts.entry(account).or_default();
 		account_info.data_size += statement.data_len();
 		if let Some(channel) = statement.channel() {
@@ -248,6 +378,32 @@ impl Index {
 		account_info
 			.by_priority
 			.insert(PriorityKey { hash, priority }, (statement.channel(), statement.data_len()));
+		self.index_usage(&account);
+		self.notify(&hash, || StatementEvent::New(statement.clone()));
+	}
+
+	/// Remove `account` from the accounts ordered by usage, before updating its statements.
+	fn unindex_usage(&mut self, account: &AccountId) {
+		if let Some(key) = self.accounts.get(account).and_then(|info| info.usage_key(account)) {
+			self.by_usage.remove(&key);
+		}
+	}
+
+	/// Add `account` back to the accounts ordered by usage, once its statements are updated.
+	fn index_usage(&mut self, account: &AccountId) {
+		if let Some(key) = self.accounts.get(account).and_then(|info| info.usage_key(account)) {
+			self.by_usage.insert(key);
+		}
+	}
+
+	/// Queue the event built by `event` about the indexed statement `hash` for the subscribers.
+	fn notify(&self, hash: &Hash, event: impl FnOnce() -> StatementEvent) {
+		if self.subscriptions.count.load(Ordering::Relaxed) == 0 {
+			return
+		}
+		let Some(notifications) = &self.notifications else { return };
+		let (topics, key) = self.topics_and_keys.get(hash).copied().unwrap_or_default();
+		let _ = notifications.unbounded_send(Notification { topics, key, event: event() });
 	}
 
 	fn query(&self, hash: &Hash) -> IndexQuery {
@@ -322,6 +478,7 @@ impl Index {
 
 	fn make_expired(&mut self, hash: &Hash, current_time: u64) -> bool {
 		if let Some((account, priority, len)) = self.entries.remove(hash) {
+			self.notify(hash, || StatementEvent::Expired(*hash));
 			self.total_size -= len;
 			if let Some((topics, key)) = self.topics_and_keys.remove(hash) {
 				for t in topics.into_iter().flatten() {
@@ -344,6 +501,7 @@ impl Index {
 				}
 			}
 			self.expired.insert(*hash, current_time);
+			self.unindex_usage(&account);
 			if let std::collections::hash_map::Entry::Occupied(mut account_rec) =
 				self.accounts.entry(account)
 			{
@@ -358,6 +516,7 @@ impl Index {
 					account_rec.remove_entry();
 				}
 			}
+			self.index_usage(&account);
 			log::trace!(target: LOG_TARGET, "Expired statement {:?}", HexDisplay::from(hash));
 			true
 		} else {
@@ -381,7 +540,7 @@ impl Index {
 				HexDisplay::from(&hash),
 				statement_len,
 			);
-			return MaybeInserted::Ignored
+			return MaybeInserted::Ignored(RejectionReason::DataTooLarge)
 		}
 
 		let mut evicted = HashSet::new();
@@ -403,7 +562,7 @@ impl Index {
 							priority,
 							channel_record.priority,
 						);
-						return MaybeInserted::Ignored
+						return MaybeInserted::Ignored(RejectionReason::ChannelPriorityTooLow)
 					} else {
 						// Would replace channel message. Still need to check for size constraints
 						// below.
@@ -446,16 +605,31 @@ impl Index {
 						priority,
 						entry.priority,
 					);
-					return MaybeInserted::Ignored
+					return MaybeInserted::Ignored(RejectionReason::AccountFull)
 				}
 				evicted.insert(entry.hash);
 				would_free_size += len;
 			}
 		}
-		// Now check global constraints as well.
-		if !((self.total_size - would_free_size + statement_len <= self.options.max_total_size) &&
-			self.entries.len() + 1 - evicted.len() <= self.options.max_total_statements)
-		{
+		// Now check global constraints as well. Only lower priority statements of the same account
+		// may be evicted for them, priorities of different accounts are not comparable.
+		let fits = |would_free_size: usize, evicted: &HashSet<Hash>| {
+			self.total_size - would_free_size + statement_len <= self.options.max_total_size &&
+				self.entries.len() + 1 - evicted.len() <= self.options.max_total_statements
+		};
+		let mut global_evictions = 0;
+		if let Some(account_rec) = self.accounts.get(account) {
+			for (entry, (_, len)) in account_rec.by_priority.iter() {
+				if fits(would_free_size, &evicted) || entry.priority >= priority {
+					break
+				}
+				if evicted.insert(entry.hash) {
+					would_free_size += len;
+					global_evictions += 1;
+				}
+			}
+		}
+		if !fits(would_free_size, &evicted) {
 			log::debug!(
 				target: LOG_TARGET,
 				"Ignored statement {} because the store is full (size={}, count={})",
@@ -463,7 +637,15 @@ impl Index {
 				self.total_size,
 				self.entries.len(),
 			);
-			return MaybeInserted::Ignored
+			return MaybeInserted::Ignored(RejectionReason::StoreFull)
+		}
+		if global_evictions > 0 {
+			log::debug!(
+				target: LOG_TARGET,
+				"Evicting {} lower priority statements for {:?}",
+				global_evictions,
+				HexDisplay::from(&hash),
+			);
 		}
 
 		for h in &evicted {
@@ -472,6 +654,27 @@ impl Index {
 		self.insert_new(hash, *account, statement);
 		MaybeInserted::Inserted(evicted)
 	}
+
+	/// Expire statements until the store is within the limits, returning their hashes and
+	/// accounts.
+	///
+	/// The lowest priority statement of the account using the most space is expired first, so that
+	/// no account can keep its statements by giving them a high priority.
+	fn enforce_limits(&mut self, current_time: u64) -> Vec<(Hash, AccountId)> {
+		let mut evicted = Vec::new();
+		while self.total_size > self.options.max_total_size ||
+			self.entries.len() > self.options.max_total_statements
+		{
+			let Some((lowest, account)) =
+				self.by_usage.last().map(|key| (key.lowest.0.hash, key.account))
+			else {
+				break
+			};
+			self.make_expired(&lowest, current_time);
+			evicted.push((lowest, account));
+		}
+		evicted
+	}
 }
 
 impl Store {
@@ -498,6 +701,14 @@ impl Store {
 	{
 		let store = Arc::new(Self::new(path, options, client, keystore, prometheus)?);
 
+		if let Some(notifications) = store.notifications.lock().take() {
+			task_spawner.spawn(
+				"statement-store-notifications",
+				Some("statement-store"),
+				Box::pin(store.subscriptions.clone().broadcast(notifications)),
+			);
+		}
+
 		// Perform periodic statement store maintenance
 		let worker_store = store.clone();
 		task_spawner.spawn(
@@ -561,23 +772,70 @@ impl Store {
 			},
 		}
 
+		let configured_limits = StoreLimits {
+			max_total_statements: options.max_total_statements,
+			max_total_size: options.max_total_size,
+		};
+		let mut options = options;
+		if let Some(limits) = Self::load_limits(&db, configured_limits)? {
+			log::info!(target: LOG_TARGET, "Using statement store limits {:?}", limits);
+			options.max_total_statements = limits.max_total_statements;
+			options.max_total_size = limits.max_total_size;
+		}
+
 		let validator = ClientWrapper { client, _block: Default::default() };
 		let validate_fn = Box::new(move |block, source, statement| {
 			validator.validate_statement(block, source, statement)
 		});
 
+		let subscriptions = Arc::new(Subscriptions::default());
+		let (notifications, notifications_receiver) = futures::channel::mpsc::unbounded();
 		let store = Store {
 			db,
-			index: RwLock::new(Index::new(options)),
+			index: RwLock::new(Index::new(options, subscriptions.clone(), notifications)),
+			subscriptions,
+			notifications: Mutex::new(Some(notifications_receiver)),
 			validate_fn,
 			keystore,
 			time_override: None,
 			metrics: PrometheusMetrics::new(prometheus),
+			rejections: Default::default(),
+			configured_limits,
 		};
 		store.populate()?;
 		Ok(store)
 	}
 
+	/// Load the limits set with `StatementStore::set_limits`.
+	///
+	/// They are discarded once the store is opened with other configured limits than the ones they
+	/// replaced.
+	fn load_limits(db: &parity_db::Db, configured: StoreLimits) -> Result<Option<StoreLimits>> {
+		let Some(encoded) = db.get(col::META, KEY_LIMITS).map_err(|e| Error::Db(e.to_string()))?
+		else {
+			return Ok(None)
+		};
+		let decoded = <[(u64, u64); 2]>::decode(&mut encoded.as_slice())
+			.map_err(|_| Error::Db("Error reading statement store limits".into()))?;
+		let [replaced, limits] =
+			decoded.map(|(max_total_statements, max_total_size)| StoreLimits {
+				max_total_statements: max_total_statements as usize,
+				max_total_size: max_total_size as usize,
+			});
+		if replaced == configured {
+			return Ok(Some(limits))
+		}
+
+		log::info!(
+			target: LOG_TARGET,
+			"Discarding statement store limits {:?}, the configured ones changed",
+			limits,
+		);
+		db.commit([(col::META, KEY_LIMITS.to_vec(), None)])
+			.map_err(|e| Error::Db(e.to_string()))?;
+		Ok(None)
+	}
+
 	/// Create memory index from the data.
 	// This may be moved to a background thread if it slows startup too much.
 	// This function should only be used on startup. There should be no other DB operations when
@@ -626,10 +884,80 @@ impl Store {
 				.map_err(|e| Error::Db(e.to_string()))?;
 		}
 
+		{
+			// Statements are not evicted here, the store may be opened with other limits than the
+			// ones of the node, e.g. by a subcommand. Inserting only evicts once within the limits.
+			let index = self.index.read();
+			if index.total_size > index.options.max_total_size ||
+				index.entries.len() > index.options.max_total_statements
+			{
+				log::info!(
+					target: LOG_TARGET,
+					"Statement store exceeds its limits (size={}, count={})",
+					index.total_size,
+					index.entries.len(),
+				);
+			}
+		}
 		self.maintain();
 		Ok(())
 	}
 
+	/// Remove the lowest priority statements until the store is within the limits.
+	fn enforce_limits(&self) -> Result<()> {
+		let current_time = self.timestamp();
+		let evicted = self.index.write().enforce_limits(current_time);
+		if evicted.is_empty() {
+			return Ok(())
+		}
+		log::debug!(
+			target: LOG_TARGET,
+			"Evicted {} statements to satisfy the store limits",
+			evicted.len(),
+		);
+		// The index is not locked while writing to the database, the evicted statements are
+		// indexed again if that fails.
+		let commit = evicted.iter().flat_map(|(hash, _)| {
+			[
+				(col::STATEMENTS, hash.to_vec(), None),
+				(col::EXPIRED, hash.to_vec(), Some((hash, current_time).encode())),
+			]
+		});
+		if let Err(e) = self.db.commit(commit) {
+			self.restore(evicted);
+			return Err(Error::Db(e.to_string()))
+		}
+		Ok(())
+	}
+
+	/// Index the `evicted` statements again, after failing to remove them from the database.
+	fn restore(&self, evicted: Vec<(Hash, AccountId)>) {
+		let statements = evicted
+			.into_iter()
+			.filter_map(|(hash, account)| match self.db.get(col::STATEMENTS, &hash) {
+				Ok(Some(encoded)) => Statement::decode(&mut encoded.as_slice())
+					.ok()
+					.map(|statement| (hash, account, statement)),
+				_ => None,
+			})
+			.collect::<Vec<_>>();
+		let mut index = self.index.write();
+		for (hash, account, statement) in statements {
+			// Unless purged in the meantime.
+			if index.expired.remove(&hash).is_some() {
+				index.insert_new(hash, account, &statement);
+			}
+		}
+	}
+
+	/// Account for a statement not being accepted into the store.
+	fn reject(&self, reason: RejectionReason) {
+		*self.rejections.lock().entry(reason).or_default() += 1;
+		self.metrics.report(|metrics| {
+			metrics.statements_rejected.with_label_values(&[reason.as_str()]).inc()
+		});
+	}
+
 	fn collect_statements<R>(
 		&self,
 		key: Option<DecryptionKey>,
@@ -726,6 +1054,11 @@ impl StatementStore for Store {
 		Ok(result)
 	}
 
+	/// Return the hashes of all statements.
+	fn statement_hashes(&self) -> Result<Vec<Hash>> {
+		Ok(self.index.read().entries.keys().cloned().collect())
+	}
+
 	/// Returns a statement by hash.
 	fn statement(&self, hash: &Hash) -> Result<Option<Statement>> {
 		Ok(
@@ -820,6 +1153,7 @@ impl StatementStore for Store {
 		match self.index.read().query(&hash) {
 			IndexQuery::Expired =>
 				if !source.can_be_resubmitted() {
+					self.reject(RejectionReason::KnownExpired);
 					return SubmitResult::KnownExpired
 				},
 			IndexQuery::Exists =>
@@ -836,6 +1170,7 @@ impl StatementStore for Store {
 				HexDisplay::from(&hash),
 			);
 			self.metrics.report(|metrics| metrics.validations_invalid.inc());
+			self.reject(RejectionReason::NoProof);
 			return SubmitResult::Bad("No statement proof")
 		};
 
@@ -855,6 +1190,7 @@ impl StatementStore for Store {
 					HexDisplay::from(&hash),
 				);
 				self.metrics.report(|metrics| metrics.validations_invalid.inc());
+				self.reject(RejectionReason::BadProof);
 				return SubmitResult::Bad("Bad statement proof")
 			},
 			Err(InvalidStatement::NoProof) => {
@@ -864,10 +1200,13 @@ impl StatementStore for Store {
 					HexDisplay::from(&hash),
 				);
 				self.metrics.report(|metrics| metrics.validations_invalid.inc());
+				self.reject(RejectionReason::NoProof);
 				return SubmitResult::Bad("Missing statement proof")
 			},
-			Err(InvalidStatement::InternalError) =>
-				return SubmitResult::InternalError(Error::Runtime),
+			Err(InvalidStatement::InternalError) => {
+				self.reject(RejectionReason::InternalError);
+				return SubmitResult::InternalError(Error::Runtime)
+			},
 		};
 
 		let current_time = self.timestamp();
@@ -877,7 +1216,10 @@ impl StatementStore for Store {
 
 			let evicted =
 				match index.insert(hash, &statement, &account_id, &validation, current_time) {
-					MaybeInserted::Ignored => return SubmitResult::Ignored,
+					MaybeInserted::Ignored(reason) => {
+						self.reject(reason);
+						return SubmitResult::Ignored
+					},
 					MaybeInserted::Inserted(evicted) => evicted,
 				};
 
@@ -893,6 +1235,7 @@ impl StatementStore for Store {
 					e,
 					statement
 				);
+				self.reject(RejectionReason::InternalError);
 				return SubmitResult::InternalError(Error::Db(e.to_string()))
 			}
 		} // Release index lock
@@ -925,6 +1268,47 @@ impl StatementStore for Store {
 		}
 		Ok(())
 	}
+
+	/// Subscribe to the statements matching `filter`.
+	fn subscribe(&self, filter: StatementFilter) -> Result<StatementEventStream> {
+		self.subscriptions.subscribe(filter)
+	}
+
+	/// Return the usage of the store.
+	fn status(&self) -> Result<StoreStatus> {
+		let index = self.index.read();
+		let rejections = self.rejections.lock();
+		Ok(StoreStatus {
+			limits: StoreLimits {
+				max_total_statements: index.options.max_total_statements,
+				max_total_size: index.options.max_total_size,
+			},
+			total_statements: index.entries.len(),
+			total_size: index.total_size,
+			expired_statements: index.expired.len(),
+			rejections: RejectionReason::ALL
+				.iter()
+				.map(|reason| (*reason, rejections.get(reason).copied().unwrap_or_default()))
+				.collect(),
+		})
+	}
+
+	/// Change the global limits of the store.
+	fn set_limits(&self, limits: StoreLimits) -> Result<()> {
+		let encoded = [self.configured_limits, limits]
+			.map(|limits| (limits.max_total_statements as u64, limits.max_total_size as u64))
+			.encode();
+		self.db
+			.commit([(col::META, KEY_LIMITS.to_vec(), Some(encoded))])
+			.map_err(|e| Error::Db(e.to_string()))?;
+		{
+			let mut index = self.index.write();
+			index.options.max_total_statements = limits.max_total_statements;
+			index.options.max_total_size = limits.max_total_size;
+		}
+		log::info!(target: LOG_TARGET, "Statement store limits changed to {:?}", limits);
+		self.enforce_limits()
+	}
 }
 
 #[cfg(test)]
@@ -934,8 +1318,9 @@ mod tests {
 	use sp_core::Pair;
 	use sp_statement_store::{
 		runtime_api::{InvalidStatement, ValidStatement, ValidateStatement},
-		AccountId, Channel, DecryptionKey, NetworkPriority, Proof, SignatureVerificationResult,
-		Statement, StatementSource, StatementStore, SubmitResult, Topic,
+		AccountId, Channel, DecryptionKey, NetworkPriority, Proof, RejectionReason,
+		SignatureVerificationResult, Statement, StatementEvent, StatementFilter, StatementSource,
+		StatementStore, StoreLimits, SubmitResult, Topic,
 	};
 
 	type Extrinsic = sp_runtime::OpaqueExtrinsic;
@@ -1033,6 +1418,15 @@ mod tests {
 		(store, temp_dir) // return order is important. Store must be dropped before TempDir
 	}
 
+	/// Dispatch the notifications of the index, which is done by a task of a shared store.
+	fn dispatch_notifications(store: &Store) {
+		let mut notifications = store.notifications.lock();
+		let notifications = notifications.as_mut().expect("Not taken by tests; qed");
+		while let Ok(Some(notification)) = notifications.try_next() {
+			store.subscriptions.dispatch(notification);
+		}
+	}
+
 	fn signed_statement(data: u8) -> Statement {
 		signed_statement_with_topics(data, &[], None)
 	}
@@ -1130,6 +1524,11 @@ mod tests {
 			SubmitResult::New(NetworkPriority::High)
 		);
 		assert_eq!(store.statements().unwrap().len(), 3);
+		let mut hashes = store.statement_hashes().unwrap();
+		hashes.sort();
+		let mut expected = vec![statement0.hash(), statement1.hash(), statement2.hash()];
+		expected.sort();
+		assert_eq!(hashes, expected);
 		assert_eq!(store.broadcasts(&[]).unwrap().len(), 3);
 		assert_eq!(store.statement(&statement1.hash()).unwrap(), Some(statement1.clone()));
 		let keystore = store.keystore.clone();
@@ -1187,6 +1586,52 @@ mod tests {
 		assert_topics(&[0, 1, 2, 3, 42], None, &[]);
 	}
 
+	#[test]
+	fn subscribers_are_notified_about_matching_statements() {
+		let (store, _temp) = test_store();
+		let filter = |topics: &[u64], key: Option<u64>| StatementFilter {
+			match_all_topics: topics.iter().map(|t| topic(*t)).collect(),
+			dest: key.map(dec_key),
+		};
+		let mut broadcasts = store.subscribe(filter(&[0], None)).unwrap();
+		let mut posted = store.subscribe(filter(&[0, 1], Some(2))).unwrap();
+		let closed = store.subscribe(filter(&[], None)).unwrap();
+		drop(closed);
+
+		let statement0 = signed_statement(0);
+		let statement1 = signed_statement_with_topics(1, &[topic(0)], None);
+		let statement2 = signed_statement_with_topics(2, &[topic(0), topic(1)], Some(dec_key(2)));
+		let statement3 = signed_statement_with_topics(3, &[topic(1)], None);
+		for s in [&statement0, &statement1, &statement2, &statement3] {
+			store.submit(s.clone(), StatementSource::Network);
+		}
+		store.remove(&statement1.hash()).unwrap();
+		dispatch_notifications(&store);
+
+		assert_eq!(broadcasts.try_next().unwrap(), Some(StatementEvent::New(statement1.clone())));
+		assert_eq!(
+			broadcasts.try_next().unwrap(),
+			Some(StatementEvent::Expired(statement1.hash()))
+		);
+		assert!(broadcasts.try_next().is_err());
+		assert_eq!(posted.try_next().unwrap(), Some(StatementEvent::New(statement2)));
+		assert!(posted.try_next().is_err());
+		assert_eq!(store.subscriptions.subscribers.lock().len(), 2);
+	}
+
+	#[test]
+	fn subscribers_are_limited() {
+		let (store, _temp) = test_store();
+		let filter = StatementFilter { match_all_topics: Vec::new(), dest: None };
+		let mut subscriptions = (0..MAX_SUBSCRIBERS)
+			.map(|_| store.subscribe(filter.clone()).unwrap())
+			.collect::<Vec<_>>();
+		assert!(matches!(store.subscribe(filter.clone()), Err(Error::TooManySubscribers)));
+
+		subscriptions.pop();
+		assert!(store.subscribe(filter).is_ok());
+	}
+
 	#[test]
 	fn constraints() {
 		let (store, _temp) = test_store();
@@ -1251,6 +1696,88 @@ mod tests {
 		assert_eq!(expected_statements, statements);
 	}
 
+	#[test]
+	fn store_limits_evict_statements_of_the_same_account() {
+		let (store, _temp) = test_store();
+		let source = StatementSource::Network;
+		let ok = SubmitResult::New(NetworkPriority::High);
+
+		store
+			.set_limits(StoreLimits { max_total_statements: 3, max_total_size: 3000 })
+			.unwrap();
+		assert_eq!(store.submit(statement(4, 2, None, 100), source), ok);
+		assert_eq!(store.submit(statement(3, 1, None, 100), source), ok);
+		assert_eq!(store.submit(statement(2, 3, None, 100), source), ok);
+		// Evicts the lower priority statement of the same account.
+		assert_eq!(store.submit(statement(2, 4, None, 300), source), ok);
+		assert!(store.index.read().expired.contains_key(&statement(2, 3, None, 100).hash()));
+		// Statements of other accounts are never evicted, whatever their priority.
+		assert_eq!(store.submit(statement(1, 9, None, 100), source), SubmitResult::Ignored);
+
+		// Lowering the limits evicts the statements of the account using the most space first.
+		store
+			.set_limits(StoreLimits { max_total_statements: 3, max_total_size: 250 })
+			.unwrap();
+		let mut expected_statements =
+			vec![statement(4, 2, None, 100).hash(), statement(3, 1, None, 100).hash()];
+		expected_statements.sort();
+		let mut statements: Vec<_> =
+			store.statements().unwrap().into_iter().map(|(hash, _)| hash).collect();
+		statements.sort();
+		assert_eq!(expected_statements, statements);
+
+		let status = store.status().unwrap();
+		assert_eq!(status.limits, StoreLimits { max_total_statements: 3, max_total_size: 250 });
+		assert_eq!(status.total_statements, 2);
+		assert_eq!(status.total_size, 200);
+		assert_eq!(status.expired_statements, 2);
+		let rejected = |reason| status.rejections.iter().find(|(r, _)| *r == reason).unwrap().1;
+		assert_eq!(rejected(RejectionReason::StoreFull), 1);
+		assert_eq!(rejected(RejectionReason::AccountFull), 0);
+	}
+
+	#[test]
+	fn store_limits_persist_until_configured_ones_change() {
+		let (store, temp) = test_store();
+		let source = StatementSource::Network;
+		let limits = StoreLimits { max_total_statements: 3, max_total_size: 250 };
+		store.set_limits(limits).unwrap();
+		store.submit(statement(2, 1, None, 100), source);
+		store.submit(statement(3, 1, None, 100), source);
+		let keystore = store.keystore.clone();
+		drop(store);
+
+		let open = |options: super::Options| {
+			let client = std::sync::Arc::new(TestClient);
+			let mut path: std::path::PathBuf = temp.path().into();
+			path.push("db");
+			Store::new(&path, options, client, keystore.clone(), None).unwrap()
+		};
+
+		let store = open(Default::default());
+		assert_eq!(store.status().unwrap().limits, limits);
+		drop(store);
+
+		// Other configured limits replace the ones set before, without evicting anything.
+		let options = super::Options { max_total_statements: 1, ..Default::default() };
+		let store = open(options.clone());
+		let status = store.status().unwrap();
+		assert_eq!(status.limits.max_total_statements, 1);
+		assert_eq!(status.limits.max_total_size, options.max_total_size);
+		assert_eq!(status.total_statements, 2);
+		assert_eq!(status.expired_statements, 0);
+		drop(store);
+
+		let store = open(Default::default());
+		assert_eq!(
+			store.status().unwrap().limits,
+			StoreLimits {
+				max_total_statements: super::DEFAULT_MAX_TOTAL_STATEMENTS,
+				max_total_size: super::DEFAULT_MAX_TOTAL_SIZE,
+			}
+		);
+	}
+
 	#[test]
 	fn expired_statements_are_purged() {
 		use super::DEFAULT_PURGE_AFTER_SEC;
@@ -1263,6 +1790,7 @@ mod tests {
 		store.remove(&statement.hash()).unwrap();
 		assert_eq!(store.index.read().entries.len(), 0);
 		assert_eq!(store.index.read().accounts.len(), 0);
+		assert!(store.index.read().by_usage.is_empty());
 		store.set_time(DEFAULT_PURGE_AFTER_SEC + 1);
 		store.maintain();
 		assert_eq!(store.index.read().expired.len(), 0);
@@ -20,7 +20,7 @@
 
 use std::sync::Arc;
 
-use prometheus_endpoint::{register, Counter, PrometheusError, Registry, U64};
+use prometheus_endpoint::{register, Counter, CounterVec, Opts, PrometheusError, Registry, U64};
 
 #[derive(Clone, Default)]
 pub struct MetricsLink(Arc<Option<Metrics>>);
@@ -48,6 +48,7 @@ pub struct Metrics {
 	pub submitted_statements: Counter<U64>,
 	pub validations_invalid: Counter<U64>,
 	pub statements_pruned: Counter<U64>,
+	pub statements_rejected: CounterVec<U64>,
 }
 
 impl Metrics {
@@ -74,6 +75,16 @@ impl Metrics {
 				)?,
 				registry,
 			)?,
+			statements_rejected: register(
+				CounterVec::new(
+					Opts::new(
+						"substrate_sub_statement_store_rejected_statements",
+						"Total number of statements that were not accepted into the store",
+					),
+					&["reason"],
+				)?,
+				registry,
+			)?,
 		})
 	}
 }
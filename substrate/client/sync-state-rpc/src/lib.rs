@@ -78,6 +78,9 @@ pub enum Error<Block: BlockT> {
 		Read the `sc-sync-state-rpc` crate docs on how to do this!"
 	)]
 	LightSyncStateExtensionNotFound,
+
+	#[error("Failed to build the warp sync checkpoint: {0}")]
+	Checkpoint(#[from] sc_consensus_grandpa::warp_proof::Error),
 }
 
 impl<Block: BlockT> From<Error<Block>> for JsonRpseeError {
@@ -130,6 +133,12 @@ pub trait SyncStateApi {
 	/// Returns the JSON serialized chainspec running the node, with a sync state.
 	#[method(name = "sync_state_genSyncSpec")]
 	async fn system_gen_sync_spec(&self, raw: bool) -> RpcResult<serde_json::Value>;
+
+	/// Returns a warp sync checkpoint at the start of the current GRANDPA authority set, or at the
+	/// last finalized block if the start of the set is unknown, to be added to the `checkpoint`
+	/// section of the chain spec.
+	#[method(name = "sync_state_genCheckpoint")]
+	fn system_gen_checkpoint(&self) -> RpcResult<sc_chain_spec::SyncCheckpoint>;
 }
 
 /// An api for sync state RPC calls.
@@ -208,4 +217,13 @@ where
 		let json_str = chain_spec.as_json(raw).map_err(|e| Error::<Block>::JsonRpc(e))?;
 		serde_json::from_str(&json_str).map_err(Into::into)
 	}
+
+	fn system_gen_checkpoint(&self) -> RpcResult<sc_chain_spec::SyncCheckpoint> {
+		let checkpoint = sc_consensus_grandpa::warp_proof::WarpSyncCheckpoint::<Block>::current(
+			&*self.client,
+			&self.shared_authority_set,
+		)
+		.map_err(Error::<Block>::from)?;
+		Ok(checkpoint.to_chain_spec())
+	}
 }
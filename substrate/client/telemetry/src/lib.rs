@@ -33,6 +33,9 @@
 //! [`TelemetryWorkerHandle`]. This handle can be cloned and passed around. It uses an asynchronous
 //! channel to communicate with the running [`TelemetryWorker`] dedicated to registration.
 //! Registering can happen at any point in time during the process execution.
+//!
+//! The messages of a node can additionally be written as newline-delimited JSON to a local file or
+//! Unix socket, see [`Telemetry::with_local_sink`].
 
 #![warn(missing_docs)]
 
@@ -56,11 +59,14 @@ pub use serde_json;
 mod endpoints;
 mod error;
 mod node;
+mod sink;
 mod transport;
 
 pub use endpoints::*;
 pub use error::*;
 use node::*;
+use sink::LocalSink;
+pub use sink::{LocalSinkConfig, LocalSinkOutput};
 use transport::*;
 
 /// Substrate DEBUG log level.
@@ -194,6 +200,7 @@ impl TelemetryWorker {
 		let mut node_map: HashMap<Id, Vec<(VerbosityLevel, Multiaddr)>> = HashMap::new();
 		let mut node_pool: HashMap<Multiaddr, _> = HashMap::new();
 		let mut pending_connection_notifications: Vec<_> = Vec::new();
+		let mut local_sinks: HashMap<Id, LocalSink> = HashMap::new();
 
 		loop {
 			futures::select! {
@@ -201,12 +208,14 @@ impl TelemetryWorker {
 					message,
 					&mut node_pool,
 					&node_map,
+					&local_sinks,
 				).await,
 				init_payload = self.register_receiver.next() => Self::process_register(
 					init_payload,
 					&mut node_pool,
 					&mut node_map,
 					&mut pending_connection_notifications,
+					&mut local_sinks,
 				).await,
 			}
 		}
@@ -217,11 +226,12 @@ impl TelemetryWorker {
 		node_pool: &mut HashMap<Multiaddr, Node<WsTrans>>,
 		node_map: &mut HashMap<Id, Vec<(VerbosityLevel, Multiaddr)>>,
 		pending_connection_notifications: &mut Vec<(Multiaddr, ConnectionNotifierSender)>,
+		local_sinks: &mut HashMap<Id, LocalSink>,
 	) {
 		let input = input.expect("the stream is never closed; qed");
 
 		match input {
-			Register::Telemetry { id, endpoints, connection_message } => {
+			Register::Telemetry { id, endpoints, connection_message, local_sink } => {
 				let endpoints = endpoints.0;
 
 				let connection_message = match serde_json::to_value(&connection_message) {
@@ -245,6 +255,15 @@ impl TelemetryWorker {
 					},
 				};
 
+				if let Some(sink) = local_sink {
+					if let Some(connection_message) = &connection_message {
+						let mut message = connection_message.clone();
+						message.insert("ts".into(), chrono::Local::now().to_rfc3339().into());
+						sink.write(SUBSTRATE_INFO, &message);
+					}
+					local_sinks.insert(id, sink);
+				}
+
 				for (addr, verbosity) in endpoints {
 					log::trace!(
 						target: "telemetry",
@@ -305,6 +324,7 @@ impl TelemetryWorker {
 		input: Option<TelemetryMessage>,
 		node_pool: &mut HashMap<Multiaddr, Node<WsTrans>>,
 		node_map: &HashMap<Id, Vec<(VerbosityLevel, Multiaddr)>>,
+		local_sinks: &HashMap<Id, LocalSink>,
 	) {
 		let (id, verbosity, payload) = input.expect("the stream is never closed; qed");
 
@@ -314,6 +334,10 @@ impl TelemetryWorker {
 		message.insert("ts".into(), ts.into());
 		message.insert("payload".into(), payload.into());
 
+		if let Some(sink) = local_sinks.get(&id) {
+			sink.write(verbosity, &message);
+		}
+
 		let nodes = if let Some(nodes) = node_map.get(&id) {
 			nodes
 		} else {
@@ -380,6 +404,7 @@ impl TelemetryWorkerHandle {
 				addresses,
 			},
 			endpoints: Some(endpoints),
+			local_sink: None,
 		}
 	}
 }
@@ -392,9 +417,18 @@ pub struct Telemetry {
 	id: Id,
 	connection_notifier: TelemetryConnectionNotifier,
 	endpoints: Option<TelemetryEndpoints>,
+	local_sink: Option<LocalSink>,
 }
 
 impl Telemetry {
+	/// Also write the telemetry messages of the node to a local file or socket.
+	///
+	/// Returns an error if the file can't be opened.
+	pub fn with_local_sink(mut self, config: LocalSinkConfig) -> Result<Self> {
+		self.local_sink = Some(LocalSink::new(config)?);
+		Ok(self)
+	}
+
 	/// Initialize the telemetry with the endpoints provided in argument for the current substrate
 	/// node.
 	///
@@ -409,7 +443,12 @@ impl Telemetry {
 		let endpoints = self.endpoints.take().ok_or(Error::TelemetryAlreadyInitialized)?;
 
 		self.register_sender
-			.unbounded_send(Register::Telemetry { id: self.id, endpoints, connection_message })
+			.unbounded_send(Register::Telemetry {
+				id: self.id,
+				endpoints,
+				connection_message,
+				local_sink: self.local_sink.take(),
+			})
 			.map_err(|_| Error::TelemetryWorkerDropped)
 	}
 
@@ -486,8 +525,16 @@ impl TelemetryConnectionNotifier {
 
 #[derive(Debug)]
 enum Register {
-	Telemetry { id: Id, endpoints: TelemetryEndpoints, connection_message: ConnectionMessage },
-	Notifier { addresses: Vec<Multiaddr>, connection_notifier: ConnectionNotifierSender },
+	Telemetry {
+		id: Id,
+		endpoints: TelemetryEndpoints,
+		connection_message: ConnectionMessage,
+		local_sink: Option<LocalSink>,
+	},
+	Notifier {
+		addresses: Vec<Multiaddr>,
+		connection_notifier: ConnectionNotifierSender,
+	},
 }
 
 /// Report a telemetry.
@@ -0,0 +1,341 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Local output of the telemetry messages as newline-delimited JSON.
+//!
+//! The messages are written on a dedicated thread, so that a slow disk or socket reader never
+//! blocks the [`crate::TelemetryWorker`].
+
+use crate::{Result, TelemetryPayload, VerbosityLevel};
+use std::{
+	fs::{self, File, OpenOptions},
+	io::{self, Write},
+	path::{Path, PathBuf},
+	sync::mpsc::{self, SyncSender, TrySendError},
+	thread::{self, JoinHandle},
+};
+#[cfg(unix)]
+use std::{
+	os::unix::net::UnixStream,
+	time::{Duration, Instant},
+};
+
+/// Delay before trying to reach the socket of a [`LocalSinkOutput::UnixSocket`] again.
+#[cfg(unix)]
+const SOCKET_RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Maximum time spent writing a message to a [`LocalSinkOutput::UnixSocket`].
+#[cfg(unix)]
+const SOCKET_WRITE_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Number of messages waiting to be written, further ones are dropped.
+const QUEUE_SIZE: usize = 1024;
+
+/// Where the local telemetry sink writes the messages to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LocalSinkOutput {
+	/// Append to a file, rotated once it reaches `max_size` bytes.
+	///
+	/// The rotated files are renamed `<path>.1`, `<path>.2`, ... up to `<path>.<max_files>`, the
+	/// oldest one being removed.
+	File {
+		/// Path of the current file.
+		path: PathBuf,
+		/// Size in bytes above which the file is rotated.
+		max_size: u64,
+		/// Number of rotated files kept next to the current one.
+		max_files: usize,
+	},
+	/// Stream to the Unix socket listening at the given path.
+	///
+	/// The messages are dropped while nothing listens on the socket or if the reader is too slow.
+	#[cfg(unix)]
+	UnixSocket(PathBuf),
+}
+
+/// Configuration of the local telemetry sink.
+///
+/// The sink receives the messages of its node whose verbosity is lower or equal to `verbosity`, as
+/// well as its `system.connected` message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalSinkConfig {
+	/// Destination of the messages.
+	pub output: LocalSinkOutput,
+	/// Maximum verbosity of the messages written.
+	pub verbosity: VerbosityLevel,
+}
+
+/// Local telemetry sink, handing the messages over to the thread writing them.
+#[derive(Debug)]
+pub(crate) struct LocalSink {
+	sender: SyncSender<Vec<u8>>,
+	verbosity: VerbosityLevel,
+	/// The writing thread, which stops once the sink is dropped.
+	#[cfg_attr(not(test), allow(dead_code))]
+	thread: JoinHandle<()>,
+}
+
+impl LocalSink {
+	/// Open the output of the sink, failing early if the file can't be created.
+	pub(crate) fn new(config: LocalSinkConfig) -> Result<Self> {
+		let mut writer = match config.output {
+			LocalSinkOutput::File { path, max_size, max_files } =>
+				Writer::File(RotatingFile::open(path, max_size, max_files)?),
+			#[cfg(unix)]
+			LocalSinkOutput::UnixSocket(path) => Writer::UnixSocket(UnixSocket::new(path)),
+		};
+		let (sender, receiver) = mpsc::sync_channel::<Vec<u8>>(QUEUE_SIZE);
+		let thread =
+			thread::Builder::new().name("telemetry-local-sink".into()).spawn(move || {
+				for line in receiver {
+					if let Err(err) = writer.write_line(&line) {
+						log::debug!(
+							target: "telemetry",
+							"Could not write to the local telemetry sink: {}",
+							err,
+						);
+					}
+				}
+			})?;
+		Ok(Self { sender, verbosity: config.verbosity, thread })
+	}
+
+	/// Queue `message` to be written if its `verbosity` is accepted by the sink.
+	pub(crate) fn write(&self, verbosity: VerbosityLevel, message: &TelemetryPayload) {
+		if verbosity > self.verbosity {
+			return
+		}
+
+		let mut line = match serde_json::to_vec(message) {
+			Ok(line) => line,
+			Err(err) => {
+				log::debug!(
+					target: "telemetry",
+					"Could not serialize telemetry message for the local sink: {}",
+					err,
+				);
+				return
+			},
+		};
+		line.push(b'\n');
+
+		match self.sender.try_send(line) {
+			Ok(()) => {},
+			Err(TrySendError::Full(_)) => log::trace!(
+				target: "telemetry",
+				"Local telemetry sink full, message dropped.",
+			),
+			Err(TrySendError::Disconnected(_)) => log::trace!(
+				target: "telemetry",
+				"Local telemetry sink closed.",
+			),
+		}
+	}
+}
+
+#[derive(Debug)]
+enum Writer {
+	File(RotatingFile),
+	#[cfg(unix)]
+	UnixSocket(UnixSocket),
+}
+
+impl Writer {
+	fn write_line(&mut self, line: &[u8]) -> io::Result<()> {
+		match self {
+			Writer::File(file) => file.write_line(line),
+			#[cfg(unix)]
+			Writer::UnixSocket(socket) => socket.write_line(line),
+		}
+	}
+}
+
+#[derive(Debug)]
+struct RotatingFile {
+	path: PathBuf,
+	max_size: u64,
+	max_files: usize,
+	file: File,
+	size: u64,
+}
+
+impl RotatingFile {
+	fn open(path: PathBuf, max_size: u64, max_files: usize) -> io::Result<Self> {
+		if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+			fs::create_dir_all(parent)?;
+		}
+		let file = OpenOptions::new().create(true).append(true).open(&path)?;
+		let size = file.metadata()?.len();
+		Ok(Self { path, max_size, max_files, file, size })
+	}
+
+	fn write_line(&mut self, line: &[u8]) -> io::Result<()> {
+		if self.size > 0 && self.size + line.len() as u64 > self.max_size {
+			self.rotate()?;
+		}
+		self.file.write_all(line)?;
+		self.size += line.len() as u64;
+		Ok(())
+	}
+
+	fn rotate(&mut self) -> io::Result<()> {
+		if self.max_files == 0 {
+			self.file.set_len(0)?;
+		} else {
+			let oldest = rotated_path(&self.path, self.max_files);
+			if oldest.exists() {
+				fs::remove_file(oldest)?;
+			}
+			for index in (1..self.max_files).rev() {
+				let from = rotated_path(&self.path, index);
+				if from.exists() {
+					fs::rename(from, rotated_path(&self.path, index + 1))?;
+				}
+			}
+			fs::rename(&self.path, rotated_path(&self.path, 1))?;
+			self.file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+		}
+		self.size = 0;
+		Ok(())
+	}
+}
+
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+	let mut rotated = path.as_os_str().to_owned();
+	rotated.push(format!(".{}", index));
+	rotated.into()
+}
+
+#[cfg(unix)]
+#[derive(Debug)]
+struct UnixSocket {
+	path: PathBuf,
+	stream: Option<UnixStream>,
+	last_attempt: Option<Instant>,
+}
+
+#[cfg(unix)]
+impl UnixSocket {
+	fn new(path: PathBuf) -> Self {
+		Self { path, stream: None, last_attempt: None }
+	}
+
+	fn write_line(&mut self, line: &[u8]) -> io::Result<()> {
+		if self.stream.is_none() {
+			if self.last_attempt.map_or(false, |last| last.elapsed() < SOCKET_RECONNECT_DELAY) {
+				return Ok(())
+			}
+			self.last_attempt = Some(Instant::now());
+			let stream = UnixStream::connect(&self.path)?;
+			stream.set_write_timeout(Some(SOCKET_WRITE_TIMEOUT))?;
+			self.stream = Some(stream);
+		}
+
+		if let Some(stream) = &mut self.stream {
+			// A partially written line would corrupt the stream, so it is reopened on any error.
+			if let Err(err) = stream.write_all(line) {
+				self.stream = None;
+				return Err(err)
+			}
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn file_sink(
+		path: PathBuf,
+		max_size: u64,
+		max_files: usize,
+		verbosity: VerbosityLevel,
+	) -> LocalSink {
+		LocalSink::new(LocalSinkConfig {
+			output: LocalSinkOutput::File { path, max_size, max_files },
+			verbosity,
+		})
+		.unwrap()
+	}
+
+	fn message(index: u64) -> TelemetryPayload {
+		let mut message = serde_json::Map::new();
+		message.insert("id".into(), index.into());
+		message
+	}
+
+	/// Wait for the queued messages to be written.
+	fn close(sink: LocalSink) {
+		let LocalSink { sender, thread, .. } = sink;
+		drop(sender);
+		thread.join().unwrap();
+	}
+
+	fn lines(path: &Path) -> Vec<serde_json::Value> {
+		fs::read_to_string(path)
+			.unwrap()
+			.lines()
+			.map(|line| serde_json::from_str(line).unwrap())
+			.collect()
+	}
+
+	#[test]
+	fn writes_messages_up_to_verbosity() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("telemetry.json");
+		let sink = file_sink(path.clone(), 1024, 1, 1);
+
+		sink.write(0, &message(1));
+		sink.write(2, &message(2));
+		sink.write(1, &message(3));
+		close(sink);
+
+		assert_eq!(lines(&path), vec![serde_json::json!({"id": 1}), serde_json::json!({"id": 3})]);
+	}
+
+	#[test]
+	fn rotates_files_above_max_size() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("telemetry.json");
+		// Each line is `{"id":N}\n`, i.e. 9 bytes, so every file holds two messages.
+		let sink = file_sink(path.clone(), 18, 2, 0);
+
+		for index in 1..=7 {
+			sink.write(0, &message(index));
+		}
+		close(sink);
+
+		assert_eq!(lines(&path), vec![serde_json::json!({"id": 7})]);
+		assert_eq!(
+			lines(&rotated_path(&path, 1)),
+			vec![serde_json::json!({"id": 5}), serde_json::json!({"id": 6})],
+		);
+		assert_eq!(
+			lines(&rotated_path(&path, 2)),
+			vec![serde_json::json!({"id": 3}), serde_json::json!({"id": 4})],
+		);
+		assert!(!rotated_path(&path, 3).exists());
+
+		// Reopening appends to the current file.
+		let sink = file_sink(path.clone(), 18, 2, 0);
+		sink.write(0, &message(8));
+		close(sink);
+		assert_eq!(lines(&path), vec![serde_json::json!({"id": 7}), serde_json::json!({"id": 8})]);
+	}
+}
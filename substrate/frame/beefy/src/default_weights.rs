@@ -53,4 +53,11 @@ impl crate::WeightInfo for () {
 	fn set_new_genesis() -> Weight {
 		DbWeight::get().writes(1)
 	}
+
+	fn report_fork_voting(validator_count: u32, max_nominators_per_validator: u32) -> Weight {
+		// same as reporting an equivocation, with a single vote signature to check instead of
+		// two, plus reading the canonical block hash.
+		Self::report_equivocation(validator_count, max_nominators_per_validator)
+			.saturating_add(DbWeight::get().reads(1))
+	}
 }
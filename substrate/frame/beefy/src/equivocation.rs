@@ -17,7 +17,8 @@
 
 //! An opt-in utility module for reporting equivocations.
 //!
-//! This module defines an offence type for BEEFY equivocations
+//! This module defines offence types for BEEFY equivocations and fork votes
+//! (i.e. votes on blocks which are not part of the canonical chain)
 //! and some utility traits to wire together:
 //! - a key ownership proof system (e.g. to prove that a given authority was part of a session);
 //! - a system for reporting offences;
@@ -36,9 +37,11 @@
 
 use codec::{self as codec, Decode, Encode};
 use frame_support::traits::{Get, KeyOwnerProofSystem};
-use frame_system::pallet_prelude::BlockNumberFor;
+use frame_system::pallet_prelude::{BlockNumberFor, HeaderFor};
 use log::{error, info};
-use sp_consensus_beefy::{EquivocationProof, ValidatorSetId, KEY_TYPE as BEEFY_KEY_TYPE};
+use sp_consensus_beefy::{
+	EquivocationProof, ForkVotingProof, ValidatorSetId, KEY_TYPE as BEEFY_KEY_TYPE,
+};
 use sp_runtime::{
 	transaction_validity::{
 		InvalidTransaction, TransactionPriority, TransactionSource, TransactionValidity,
@@ -111,35 +114,139 @@ where
 	}
 }
 
+/// BEEFY fork voting offence report.
+///
+/// Reported when an authority signs a vote for a block which is not part of the canonical chain.
+pub struct ForkVotingOffence<Offender, N>
+where
+	N: Copy + Clone + PartialOrd + Ord + Eq + PartialEq + Encode + Decode,
+{
+	/// Time slot at which this incident happened.
+	pub time_slot: TimeSlot<N>,
+	/// The session index in which the incident happened.
+	pub session_index: SessionIndex,
+	/// The size of the validator set at the time of the offence.
+	pub validator_set_count: u32,
+	/// The authority which voted on the non-canonical block.
+	pub offender: Offender,
+}
+
+impl<Offender: Clone, N> Offence<Offender> for ForkVotingOffence<Offender, N>
+where
+	N: Copy + Clone + PartialOrd + Ord + Eq + PartialEq + Encode + Decode,
+{
+	const ID: Kind = *b"beefy:fork-votin";
+	type TimeSlot = TimeSlot<N>;
+
+	fn offenders(&self) -> Vec<Offender> {
+		vec![self.offender.clone()]
+	}
+
+	fn session_index(&self) -> SessionIndex {
+		self.session_index
+	}
+
+	fn validator_set_count(&self) -> u32 {
+		self.validator_set_count
+	}
+
+	fn time_slot(&self) -> Self::TimeSlot {
+		self.time_slot
+	}
+
+	// Same formula as for equivocations: min((3k / n)^2, 1)
+	// where k = offenders_number and n = validators_number
+	fn slash_fraction(&self, offenders_count: u32) -> Perbill {
+		Perbill::from_rational(3 * offenders_count, self.validator_set_count).square()
+	}
+}
+
 /// BEEFY equivocation offence report system.
 ///
 /// This type implements `OffenceReportSystem` such that:
-/// - Equivocation reports are published on-chain as unsigned extrinsic via
+/// - Equivocation and fork voting reports are published on-chain as unsigned extrinsic via
 ///   `offchain::SendTransactionTypes`.
 /// - On-chain validity checks and processing are mostly delegated to the user provided generic
 ///   types implementing `KeyOwnerProofSystem` and `ReportOffence` traits.
 /// - Offence reporter for unsigned transactions is fetched via the the authorship pallet.
 pub struct EquivocationReportSystem<T, R, P, L>(sp_std::marker::PhantomData<(T, R, P, L)>);
 
-/// Equivocation evidence convenience alias.
-pub type EquivocationEvidenceFor<T> = (
-	EquivocationProof<
-		BlockNumberFor<T>,
-		<T as Config>::BeefyId,
-		<<T as Config>::BeefyId as RuntimeAppPublic>::Signature,
-	>,
-	<T as Config>::KeyOwnerProof,
-);
+/// Evidence of BEEFY voter misbehavior, together with the key ownership proof of the offender.
+pub enum EquivocationEvidenceFor<T: Config> {
+	/// Two conflicting votes cast in the same round.
+	VoteEquivocationProof(
+		EquivocationProof<
+			BlockNumberFor<T>,
+			T::BeefyId,
+			<T::BeefyId as RuntimeAppPublic>::Signature,
+		>,
+		T::KeyOwnerProof,
+	),
+	/// A vote cast on a block which is not part of the canonical chain.
+	ForkVotingProof(ForkVotingProof<HeaderFor<T>, T::BeefyId>, T::KeyOwnerProof),
+}
+
+impl<T: Config> EquivocationEvidenceFor<T> {
+	/// Returns the authority id of the offender.
+	fn offender_id(&self) -> &T::BeefyId {
+		match self {
+			Self::VoteEquivocationProof(proof, _) => proof.offender_id(),
+			Self::ForkVotingProof(proof, _) => proof.offender_id(),
+		}
+	}
+
+	/// Returns the round number at which the misbehavior happened.
+	fn round_number(&self) -> BlockNumberFor<T> {
+		match self {
+			Self::VoteEquivocationProof(proof, _) => *proof.round_number(),
+			Self::ForkVotingProof(proof, _) => *proof.round_number(),
+		}
+	}
+
+	/// Returns the set id at which the misbehavior happened.
+	fn set_id(&self) -> ValidatorSetId {
+		match self {
+			Self::VoteEquivocationProof(proof, _) => proof.set_id(),
+			Self::ForkVotingProof(proof, _) => proof.set_id(),
+		}
+	}
+
+	/// Returns the key ownership proof of the offender.
+	fn key_owner_proof(&self) -> &T::KeyOwnerProof {
+		match self {
+			Self::VoteEquivocationProof(_, key_owner_proof) |
+			Self::ForkVotingProof(_, key_owner_proof) => key_owner_proof,
+		}
+	}
+
+	/// Checks the misbehavior proof itself, i.e. without the key ownership proof.
+	fn check_proof(&self) -> bool {
+		match self {
+			Self::VoteEquivocationProof(proof, _) =>
+				sp_consensus_beefy::check_equivocation_proof(proof),
+			Self::ForkVotingProof(proof, _) => {
+				// only votes on blocks within the `BlockHashCount` window can be checked
+				// against the canonical chain.
+				let canonical_hash = <frame_system::Pallet<T>>::block_hash(*proof.round_number());
+				sp_consensus_beefy::check_fork_voting_proof(proof, &canonical_hash)
+			},
+		}
+	}
+}
 
 impl<T, R, P, L> OffenceReportSystem<Option<T::AccountId>, EquivocationEvidenceFor<T>>
 	for EquivocationReportSystem<T, R, P, L>
 where
 	T: Config + pallet_authorship::Config + frame_system::offchain::SendTransactionTypes<Call<T>>,
 	R: ReportOffence<
-		T::AccountId,
-		P::IdentificationTuple,
-		EquivocationOffence<P::IdentificationTuple, BlockNumberFor<T>>,
-	>,
+			T::AccountId,
+			P::IdentificationTuple,
+			EquivocationOffence<P::IdentificationTuple, BlockNumberFor<T>>,
+		> + ReportOffence<
+			T::AccountId,
+			P::IdentificationTuple,
+			ForkVotingOffence<P::IdentificationTuple, BlockNumberFor<T>>,
+		>,
 	P: KeyOwnerProofSystem<(KeyTypeId, T::BeefyId), Proof = T::KeyOwnerProof>,
 	P::IdentificationTuple: Clone,
 	L: Get<u64>,
@@ -148,17 +255,24 @@ where
 
 	fn publish_evidence(evidence: EquivocationEvidenceFor<T>) -> Result<(), ()> {
 		use frame_system::offchain::SubmitTransaction;
-		let (equivocation_proof, key_owner_proof) = evidence;
 
-		let call = Call::report_equivocation_unsigned {
-			equivocation_proof: Box::new(equivocation_proof),
-			key_owner_proof,
+		let call = match evidence {
+			EquivocationEvidenceFor::VoteEquivocationProof(equivocation_proof, key_owner_proof) =>
+				Call::report_equivocation_unsigned {
+					equivocation_proof: Box::new(equivocation_proof),
+					key_owner_proof,
+				},
+			EquivocationEvidenceFor::ForkVotingProof(fork_voting_proof, key_owner_proof) =>
+				Call::report_fork_voting_unsigned {
+					fork_voting_proof: Box::new(fork_voting_proof),
+					key_owner_proof,
+				},
 		};
 
 		let res = SubmitTransaction::<T, Call<T>>::submit_unsigned_transaction(call.into());
 		match res {
-			Ok(_) => info!(target: LOG_TARGET, "Submitted equivocation report."),
-			Err(e) => error!(target: LOG_TARGET, "Error submitting equivocation report: {:?}", e),
+			Ok(_) => info!(target: LOG_TARGET, "Submitted misbehavior report."),
+			Err(e) => error!(target: LOG_TARGET, "Error submitting misbehavior report: {:?}", e),
 		}
 		res
 	}
@@ -166,19 +280,28 @@ where
 	fn check_evidence(
 		evidence: EquivocationEvidenceFor<T>,
 	) -> Result<(), TransactionValidityError> {
-		let (equivocation_proof, key_owner_proof) = evidence;
-
 		// Check the membership proof to extract the offender's id
-		let key = (BEEFY_KEY_TYPE, equivocation_proof.offender_id().clone());
-		let offender = P::check_proof(key, key_owner_proof).ok_or(InvalidTransaction::BadProof)?;
+		let key = (BEEFY_KEY_TYPE, evidence.offender_id().clone());
+		let offender = P::check_proof(key, evidence.key_owner_proof().clone())
+			.ok_or(InvalidTransaction::BadProof)?;
 
 		// Check if the offence has already been reported, and if so then we can discard the report.
-		let time_slot = TimeSlot {
-			set_id: equivocation_proof.set_id(),
-			round: *equivocation_proof.round_number(),
+		let time_slot = TimeSlot { set_id: evidence.set_id(), round: evidence.round_number() };
+
+		let is_known_offence = match evidence {
+			EquivocationEvidenceFor::VoteEquivocationProof(..) =>
+				<R as ReportOffence<_, _, EquivocationOffence<_, _>>>::is_known_offence(
+					&[offender],
+					&time_slot,
+				),
+			EquivocationEvidenceFor::ForkVotingProof(..) =>
+				<R as ReportOffence<_, _, ForkVotingOffence<_, _>>>::is_known_offence(
+					&[offender],
+					&time_slot,
+				),
 		};
 
-		if R::is_known_offence(&[offender], &time_slot) {
+		if is_known_offence {
 			Err(InvalidTransaction::Stale.into())
 		} else {
 			Ok(())
@@ -189,99 +312,123 @@ where
 		reporter: Option<T::AccountId>,
 		evidence: EquivocationEvidenceFor<T>,
 	) -> Result<(), DispatchError> {
-		let (equivocation_proof, key_owner_proof) = evidence;
 		let reporter = reporter.or_else(|| <pallet_authorship::Pallet<T>>::author());
-		let offender = equivocation_proof.offender_id().clone();
+		let offender = evidence.offender_id().clone();
+		let invalid_proof_error = || match &evidence {
+			EquivocationEvidenceFor::VoteEquivocationProof(..) =>
+				Error::<T>::InvalidEquivocationProof,
+			EquivocationEvidenceFor::ForkVotingProof(..) => Error::<T>::InvalidForkVotingProof,
+		};
 
-		// We check the equivocation within the context of its set id (and
+		// We check the misbehavior within the context of its set id (and
 		// associated session) and round. We also need to know the validator
 		// set count at the time of the offence since it is required to calculate
 		// the slash amount.
-		let set_id = equivocation_proof.set_id();
-		let round = *equivocation_proof.round_number();
-		let session_index = key_owner_proof.session();
-		let validator_set_count = key_owner_proof.validator_count();
+		let set_id = evidence.set_id();
+		let round = evidence.round_number();
+		let session_index = evidence.key_owner_proof().session();
+		let validator_set_count = evidence.key_owner_proof().validator_count();
 
 		// Validate the key ownership proof extracting the id of the offender.
-		let offender = P::check_proof((BEEFY_KEY_TYPE, offender), key_owner_proof)
-			.ok_or(Error::<T>::InvalidKeyOwnershipProof)?;
-
-		// Validate equivocation proof (check votes are different and signatures are valid).
-		if !sp_consensus_beefy::check_equivocation_proof(&equivocation_proof) {
-			return Err(Error::<T>::InvalidEquivocationProof.into())
+		let offender =
+			P::check_proof((BEEFY_KEY_TYPE, offender), evidence.key_owner_proof().clone())
+				.ok_or(Error::<T>::InvalidKeyOwnershipProof)?;
+
+		// Validate the misbehavior proof (for equivocations check votes are different,
+		// for fork votes check the vote conflicts with the canonical chain) and signatures.
+		if !evidence.check_proof() {
+			return Err(invalid_proof_error().into())
 		}
 
 		// Check that the session id for the membership proof is within the
-		// bounds of the set id reported in the equivocation.
+		// bounds of the set id reported in the misbehavior proof.
 		let set_id_session_index =
-			crate::SetIdSession::<T>::get(set_id).ok_or(Error::<T>::InvalidEquivocationProof)?;
+			crate::SetIdSession::<T>::get(set_id).ok_or_else(invalid_proof_error)?;
 		if session_index != set_id_session_index {
-			return Err(Error::<T>::InvalidEquivocationProof.into())
+			return Err(invalid_proof_error().into())
 		}
 
-		let offence = EquivocationOffence {
-			time_slot: TimeSlot { set_id, round },
-			session_index,
-			validator_set_count,
-			offender,
-		};
-
-		R::report_offence(reporter.into_iter().collect(), offence)
-			.map_err(|_| Error::<T>::DuplicateOffenceReport)?;
+		let time_slot = TimeSlot { set_id, round };
+		let reporters = reporter.into_iter().collect();
+		match evidence {
+			EquivocationEvidenceFor::VoteEquivocationProof(..) => R::report_offence(
+				reporters,
+				EquivocationOffence { time_slot, session_index, validator_set_count, offender },
+			),
+			EquivocationEvidenceFor::ForkVotingProof(..) => R::report_offence(
+				reporters,
+				ForkVotingOffence { time_slot, session_index, validator_set_count, offender },
+			),
+		}
+		.map_err(|_| Error::<T>::DuplicateOffenceReport)?;
 
 		Ok(())
 	}
 }
 
 /// Methods for the `ValidateUnsigned` implementation:
-/// It restricts calls to `report_equivocation_unsigned` to local calls (i.e. extrinsics generated
-/// on this node) or that already in a block. This guarantees that only block authors can include
-/// unsigned equivocation reports.
+/// It restricts calls to `report_equivocation_unsigned` and `report_fork_voting_unsigned` to local
+/// calls (i.e. extrinsics generated on this node) or that already in a block. This guarantees that
+/// only block authors can include unsigned misbehavior reports.
 impl<T: Config> Pallet<T> {
+	fn evidence_from_call(call: &Call<T>) -> Option<(EquivocationEvidenceFor<T>, &'static str)> {
+		match call {
+			Call::report_equivocation_unsigned { equivocation_proof, key_owner_proof } => Some((
+				EquivocationEvidenceFor::VoteEquivocationProof(
+					*equivocation_proof.clone(),
+					key_owner_proof.clone(),
+				),
+				"BeefyEquivocation",
+			)),
+			Call::report_fork_voting_unsigned { fork_voting_proof, key_owner_proof } => Some((
+				EquivocationEvidenceFor::ForkVotingProof(
+					*fork_voting_proof.clone(),
+					key_owner_proof.clone(),
+				),
+				"BeefyForkVoting",
+			)),
+			_ => None,
+		}
+	}
+
 	pub fn validate_unsigned(source: TransactionSource, call: &Call<T>) -> TransactionValidity {
-		if let Call::report_equivocation_unsigned { equivocation_proof, key_owner_proof } = call {
-			// discard equivocation report not coming from the local node
-			match source {
-				TransactionSource::Local | TransactionSource::InBlock => { /* allowed */ },
-				_ => {
-					log::warn!(
-						target: LOG_TARGET,
-						"rejecting unsigned report equivocation transaction because it is not local/in-block."
-					);
-					return InvalidTransaction::Call.into()
-				},
-			}
-
-			let evidence = (*equivocation_proof.clone(), key_owner_proof.clone());
-			T::EquivocationReportSystem::check_evidence(evidence)?;
-
-			let longevity =
-				<T::EquivocationReportSystem as OffenceReportSystem<_, _>>::Longevity::get();
-
-			ValidTransaction::with_tag_prefix("BeefyEquivocation")
-				// We assign the maximum priority for any equivocation report.
-				.priority(TransactionPriority::MAX)
-				// Only one equivocation report for the same offender at the same slot.
-				.and_provides((
-					equivocation_proof.offender_id().clone(),
-					equivocation_proof.set_id(),
-					*equivocation_proof.round_number(),
-				))
-				.longevity(longevity)
-				// We don't propagate this. This can never be included on a remote node.
-				.propagate(false)
-				.build()
-		} else {
-			InvalidTransaction::Call.into()
+		let Some((evidence, tag_prefix)) = Self::evidence_from_call(call) else {
+			return InvalidTransaction::Call.into()
+		};
+
+		// discard misbehavior report not coming from the local node
+		match source {
+			TransactionSource::Local | TransactionSource::InBlock => { /* allowed */ },
+			_ => {
+				log::warn!(
+					target: LOG_TARGET,
+					"rejecting unsigned misbehavior report transaction because it is not local/in-block."
+				);
+				return InvalidTransaction::Call.into()
+			},
 		}
+
+		// Only one report for the same offender at the same slot.
+		let provides = (evidence.offender_id().clone(), evidence.set_id(), evidence.round_number());
+		T::EquivocationReportSystem::check_evidence(evidence)?;
+
+		let longevity =
+			<T::EquivocationReportSystem as OffenceReportSystem<_, _>>::Longevity::get();
+
+		ValidTransaction::with_tag_prefix(tag_prefix)
+			// We assign the maximum priority for any misbehavior report.
+			.priority(TransactionPriority::MAX)
+			.and_provides(provides)
+			.longevity(longevity)
+			// We don't propagate this. This can never be included on a remote node.
+			.propagate(false)
+			.build()
 	}
 
 	pub fn pre_dispatch(call: &Call<T>) -> Result<(), TransactionValidityError> {
-		if let Call::report_equivocation_unsigned { equivocation_proof, key_owner_proof } = call {
-			let evidence = (*equivocation_proof.clone(), key_owner_proof.clone());
-			T::EquivocationReportSystem::check_evidence(evidence)
-		} else {
-			Err(InvalidTransaction::Call.into())
-		}
+		let Some((evidence, _)) = Self::evidence_from_call(call) else {
+			return Err(InvalidTransaction::Call.into())
+		};
+		T::EquivocationReportSystem::check_evidence(evidence)
 	}
 }
@@ -28,7 +28,7 @@ use frame_support::{
 };
 use frame_system::{
 	ensure_none, ensure_signed,
-	pallet_prelude::{BlockNumberFor, OriginFor},
+	pallet_prelude::{BlockNumberFor, HeaderFor, OriginFor},
 };
 use log;
 use sp_runtime::{
@@ -41,8 +41,8 @@ use sp_staking::{offence::OffenceReportSystem, SessionIndex};
 use sp_std::prelude::*;
 
 use sp_consensus_beefy::{
-	AuthorityIndex, BeefyAuthorityId, ConsensusLog, EquivocationProof, OnNewValidatorSet,
-	ValidatorSet, BEEFY_ENGINE_ID, GENESIS_AUTHORITY_SET_ID,
+	AuthorityIndex, BeefyAuthorityId, ConsensusLog, EquivocationProof, ForkVotingProof,
+	OnNewValidatorSet, ValidatorSet, BEEFY_ENGINE_ID, GENESIS_AUTHORITY_SET_ID,
 };
 
 mod default_weights;
@@ -52,11 +52,12 @@ mod mock;
 #[cfg(test)]
 mod tests;
 
-pub use crate::equivocation::{EquivocationOffence, EquivocationReportSystem, TimeSlot};
+pub use crate::equivocation::{
+	EquivocationEvidenceFor, EquivocationOffence, EquivocationReportSystem, ForkVotingOffence,
+	TimeSlot,
+};
 pub use pallet::*;
 
-use crate::equivocation::EquivocationEvidenceFor;
-
 const LOG_TARGET: &str = "runtime::beefy";
 
 #[frame_support::pallet]
@@ -101,14 +102,14 @@ pub mod pallet {
 		/// Weights for this pallet.
 		type WeightInfo: WeightInfo;
 
-		/// The proof of key ownership, used for validating equivocation reports
+		/// The proof of key ownership, used for validating equivocation and fork voting reports
 		/// The proof must include the session index and validator count of the
-		/// session at which the equivocation occurred.
+		/// session at which the offence occurred.
 		type KeyOwnerProof: Parameter + GetSessionNumber + GetValidatorCount;
 
 		/// The equivocation handling subsystem.
 		///
-		/// Defines methods to publish, check and process an equivocation offence.
+		/// Defines methods to publish, check and process equivocation and fork voting offences.
 		type EquivocationReportSystem: OffenceReportSystem<
 			Option<Self::AccountId>,
 			EquivocationEvidenceFor<Self>,
@@ -200,6 +201,8 @@ pub mod pallet {
 		DuplicateOffenceReport,
 		/// Submitted configuration is invalid.
 		InvalidConfiguration,
+		/// A fork voting proof provided as part of a fork voting report is invalid.
+		InvalidForkVotingProof,
 	}
 
 	#[pallet::call]
@@ -228,7 +231,10 @@ pub mod pallet {
 
 			T::EquivocationReportSystem::process_evidence(
 				Some(reporter),
-				(*equivocation_proof, key_owner_proof),
+				EquivocationEvidenceFor::VoteEquivocationProof(
+					*equivocation_proof,
+					key_owner_proof,
+				),
 			)?;
 			// Waive the fee since the report is valid and beneficial
 			Ok(Pays::No.into())
@@ -263,7 +269,10 @@ pub mod pallet {
 
 			T::EquivocationReportSystem::process_evidence(
 				None,
-				(*equivocation_proof, key_owner_proof),
+				EquivocationEvidenceFor::VoteEquivocationProof(
+					*equivocation_proof,
+					key_owner_proof,
+				),
 			)?;
 			Ok(Pays::No.into())
 		}
@@ -284,6 +293,60 @@ pub mod pallet {
 			GenesisBlock::<T>::put(Some(genesis_block));
 			Ok(())
 		}
+
+		/// Report voter voting on a block which is not part of the canonical chain. This method
+		/// will verify the fork voting proof against the canonical block hash known to the
+		/// runtime and validate the given key ownership proof against the extracted offender.
+		/// If both are valid, the offence will be reported.
+		///
+		/// Only votes on blocks still within the `BlockHashCount` window can be reported.
+		#[pallet::call_index(3)]
+		#[pallet::weight(T::WeightInfo::report_fork_voting(
+			key_owner_proof.validator_count(),
+			T::MaxNominators::get(),
+		))]
+		pub fn report_fork_voting(
+			origin: OriginFor<T>,
+			fork_voting_proof: Box<ForkVotingProof<HeaderFor<T>, T::BeefyId>>,
+			key_owner_proof: T::KeyOwnerProof,
+		) -> DispatchResultWithPostInfo {
+			let reporter = ensure_signed(origin)?;
+
+			T::EquivocationReportSystem::process_evidence(
+				Some(reporter),
+				EquivocationEvidenceFor::ForkVotingProof(*fork_voting_proof, key_owner_proof),
+			)?;
+			// Waive the fee since the report is valid and beneficial
+			Ok(Pays::No.into())
+		}
+
+		/// Report voter voting on a block which is not part of the canonical chain. This method
+		/// will verify the fork voting proof against the canonical block hash known to the
+		/// runtime and validate the given key ownership proof against the extracted offender.
+		/// If both are valid, the offence will be reported.
+		///
+		/// This extrinsic must be called unsigned and it is expected that only
+		/// block authors will call it (validated in `ValidateUnsigned`), as such
+		/// if the block author is defined it will be defined as the fork voting
+		/// reporter.
+		#[pallet::call_index(4)]
+		#[pallet::weight(T::WeightInfo::report_fork_voting(
+			key_owner_proof.validator_count(),
+			T::MaxNominators::get(),
+		))]
+		pub fn report_fork_voting_unsigned(
+			origin: OriginFor<T>,
+			fork_voting_proof: Box<ForkVotingProof<HeaderFor<T>, T::BeefyId>>,
+			key_owner_proof: T::KeyOwnerProof,
+		) -> DispatchResultWithPostInfo {
+			ensure_none(origin)?;
+
+			T::EquivocationReportSystem::process_evidence(
+				None,
+				EquivocationEvidenceFor::ForkVotingProof(*fork_voting_proof, key_owner_proof),
+			)?;
+			Ok(Pays::No.into())
+		}
 	}
 
 	#[pallet::validate_unsigned]
@@ -319,7 +382,24 @@ impl<T: Config> Pallet<T> {
 		>,
 		key_owner_proof: T::KeyOwnerProof,
 	) -> Option<()> {
-		T::EquivocationReportSystem::publish_evidence((equivocation_proof, key_owner_proof)).ok()
+		T::EquivocationReportSystem::publish_evidence(
+			EquivocationEvidenceFor::VoteEquivocationProof(equivocation_proof, key_owner_proof),
+		)
+		.ok()
+	}
+
+	/// Submits an extrinsic to report a fork vote. This method will create
+	/// an unsigned extrinsic with a call to `report_fork_voting_unsigned` and
+	/// will push the transaction to the pool. Only useful in an offchain context.
+	pub fn submit_unsigned_fork_voting_report(
+		fork_voting_proof: ForkVotingProof<HeaderFor<T>, T::BeefyId>,
+		key_owner_proof: T::KeyOwnerProof,
+	) -> Option<()> {
+		T::EquivocationReportSystem::publish_evidence(EquivocationEvidenceFor::ForkVotingProof(
+			fork_voting_proof,
+			key_owner_proof,
+		))
+		.ok()
 	}
 
 	fn change_authorities(
@@ -472,4 +552,5 @@ impl<T: Config> IsMember<T::BeefyId> for Pallet<T> {
 pub trait WeightInfo {
 	fn report_equivocation(validator_count: u32, max_nominators_per_validator: u32) -> Weight;
 	fn set_new_genesis() -> Weight;
+	fn report_fork_voting(validator_count: u32, max_nominators_per_validator: u32) -> Weight;
 }
@@ -19,17 +19,19 @@ use std::vec;
 
 use codec::Encode;
 use sp_consensus_beefy::{
-	check_equivocation_proof, generate_equivocation_proof, known_payloads::MMR_ROOT_ID,
-	Keyring as BeefyKeyring, Payload, ValidatorSet, KEY_TYPE as BEEFY_KEY_TYPE,
+	check_equivocation_proof, generate_equivocation_proof, generate_fork_voting_proof,
+	known_payloads::MMR_ROOT_ID, Keyring as BeefyKeyring, MmrRootHash, Payload, ValidatorSet,
+	KEY_TYPE as BEEFY_KEY_TYPE,
 };
 
-use sp_runtime::DigestItem;
+use sp_runtime::{traits::Header as HeaderT, Digest, DigestItem};
 
 use frame_support::{
 	assert_err, assert_ok,
 	dispatch::{GetDispatchInfo, Pays},
 	traits::{Currency, KeyOwnerProofSystem, OnInitialize},
 };
+use frame_system::pallet_prelude::HeaderFor;
 
 use crate::{mock::*, Call, Config, Error, Weight, WeightInfo};
 
@@ -813,3 +815,136 @@ fn set_new_genesis_works() {
 		);
 	});
 }
+
+/// Create a header at `number` carrying the given MMR root and make it the canonical one.
+fn canonical_header_with_mmr_root(number: u64, mmr_root: MmrRootHash) -> HeaderFor<Test> {
+	let digest = Digest { logs: vec![beefy_log(ConsensusLog::MmrRoot(mmr_root))] };
+	let header = HeaderFor::<Test>::new(
+		number,
+		Default::default(),
+		Default::default(),
+		Default::default(),
+		digest,
+	);
+	frame_system::BlockHash::<Test>::insert(number, header.hash());
+	header
+}
+
+#[test]
+fn report_fork_voting_works() {
+	let authorities = test_authorities();
+
+	new_test_ext_raw_authorities(authorities).execute_with(|| {
+		assert_eq!(Staking::current_era(), Some(0));
+		assert_eq!(Session::current_index(), 0);
+
+		start_era(1);
+
+		let block_num = System::block_number();
+		let validator_set = Beefy::validator_set().unwrap();
+		let authorities = validator_set.validators();
+		let set_id = validator_set.id();
+		let validators = Session::validators();
+
+		let offender_authority_index = 1;
+		let offender_key = &authorities[offender_authority_index];
+		let offender_keyring = BeefyKeyring::from_public(offender_key).unwrap();
+
+		// the canonical block commits to one MMR root, the vote to another one
+		let canonical_header =
+			canonical_header_with_mmr_root(block_num, MmrRootHash::repeat_byte(0xbf));
+		let payload =
+			Payload::from_single_entry(MMR_ROOT_ID, MmrRootHash::repeat_byte(0x42).encode());
+		let fork_voting_proof = generate_fork_voting_proof(
+			(block_num, payload, set_id, &offender_keyring),
+			canonical_header,
+		);
+
+		// create the key ownership proof
+		let key_owner_proof = Historical::prove((BEEFY_KEY_TYPE, &offender_key)).unwrap();
+
+		// report the fork vote and the tx should be dispatched successfully
+		assert_ok!(Beefy::report_fork_voting_unsigned(
+			RuntimeOrigin::none(),
+			Box::new(fork_voting_proof.clone()),
+			key_owner_proof.clone(),
+		));
+
+		// the report is now stale
+		let call = Call::report_fork_voting_unsigned {
+			fork_voting_proof: Box::new(fork_voting_proof),
+			key_owner_proof,
+		};
+		assert_err!(
+			<Beefy as sp_runtime::traits::ValidateUnsigned>::pre_dispatch(&call),
+			sp_runtime::transaction_validity::InvalidTransaction::Stale,
+		);
+
+		start_era(2);
+
+		// check that the balance of the offender is slashed 100%.
+		let offender_validator_id = validators[offender_authority_index];
+
+		assert_eq!(Balances::total_balance(&offender_validator_id), 10_000_000 - 10_000);
+		assert_eq!(Staking::slashable_balance_of(&offender_validator_id), 0);
+
+		// check that the balances of all other validators are left intact.
+		for validator in &validators {
+			if *validator == offender_validator_id {
+				continue
+			}
+
+			assert_eq!(Balances::total_balance(validator), 10_000_000);
+			assert_eq!(Staking::slashable_balance_of(validator), 10_000);
+		}
+	});
+}
+
+#[test]
+fn report_fork_voting_invalid_fork_voting_proof() {
+	let authorities = test_authorities();
+
+	new_test_ext_raw_authorities(authorities).execute_with(|| {
+		start_era(1);
+
+		let block_num = System::block_number();
+		let validator_set = Beefy::validator_set().unwrap();
+		let authorities = validator_set.validators();
+		let set_id = validator_set.id();
+
+		let offender_key = &authorities[0];
+		let offender_keyring = BeefyKeyring::from_public(offender_key).unwrap();
+		let key_owner_proof = Historical::prove((BEEFY_KEY_TYPE, &offender_key)).unwrap();
+
+		let canonical_root = MmrRootHash::repeat_byte(0xbf);
+		let fork_root = MmrRootHash::repeat_byte(0x42);
+		let canonical_header = canonical_header_with_mmr_root(block_num, canonical_root);
+
+		let assert_invalid_fork_voting_proof = |root: MmrRootHash, header: HeaderFor<Test>| {
+			let payload = Payload::from_single_entry(MMR_ROOT_ID, root.encode());
+			let fork_voting_proof =
+				generate_fork_voting_proof((block_num, payload, set_id, &offender_keyring), header);
+			assert_err!(
+				Beefy::report_fork_voting_unsigned(
+					RuntimeOrigin::none(),
+					Box::new(fork_voting_proof),
+					key_owner_proof.clone(),
+				),
+				Error::<Test>::InvalidForkVotingProof,
+			);
+		};
+
+		// voting on the canonical block is not an offence
+		assert_invalid_fork_voting_proof(canonical_root, canonical_header.clone());
+
+		// the provided header must be the canonical one
+		let mut non_canonical_header = canonical_header.clone();
+		non_canonical_header.set_state_root([1u8; 32].into());
+		assert_invalid_fork_voting_proof(fork_root, non_canonical_header);
+
+		// the provided header must be at the voted block number
+		let mut other_block_header = canonical_header;
+		other_block_header.set_number(block_num - 1);
+		assert_invalid_fork_voting_proof(fork_root, other_block_header);
+	});
+}
@@ -291,7 +291,9 @@ where
 			<frame_system::Pallet<System>>::note_extrinsic(encoded);
 
 			let dispatch_info = xt.get_dispatch_info();
-			let r = Applyable::apply::<UnsignedValidator>(xt, &dispatch_info, encoded_len)?;
+			let r = frame_support::dispatch_context::with_dispatch_info(&dispatch_info, || {
+				Applyable::apply::<UnsignedValidator>(xt, &dispatch_info, encoded_len)
+			})?;
 
 			<frame_system::Pallet<System>>::note_applied_extrinsic(&r, dispatch_info);
 
@@ -627,7 +629,9 @@ where
 
 		// Decode parameters and dispatch
 		let dispatch_info = xt.get_dispatch_info();
-		let r = Applyable::apply::<UnsignedValidator>(xt, &dispatch_info, encoded_len)?;
+		let r = frame_support::dispatch_context::with_dispatch_info(&dispatch_info, || {
+			Applyable::apply::<UnsignedValidator>(xt, &dispatch_info, encoded_len)
+		})?;
 
 		// Mandatory(inherents) are not allowed to fail.
 		//
@@ -54,7 +54,7 @@ use frame_support::{
 		DispatchErrorWithPostInfo, DispatchResult, DispatchResultWithPostInfo, GetDispatchInfo,
 		PostDispatchInfo,
 	},
-	ensure,
+	dispatch_context, ensure,
 	traits::{Currency, Get, ReservableCurrency},
 	weights::Weight,
 	BoundedVec,
@@ -290,26 +290,16 @@ pub mod pallet {
 			let id = Self::multi_account_id(&signatories, 1);
 
 			let call_len = call.using_encoded(|c| c.len());
-			let result = call.dispatch(RawOrigin::Signed(id).into());
+			let (result, nested) =
+				dispatch_context::measure_nested(|| call.dispatch(RawOrigin::Signed(id).into()));
+			let weight_used =
+				T::WeightInfo::as_multi_threshold_1(call_len as u32).saturating_add(nested.actual);
 
 			result
-				.map(|post_dispatch_info| {
-					post_dispatch_info
-						.actual_weight
-						.map(|actual_weight| {
-							T::WeightInfo::as_multi_threshold_1(call_len as u32)
-								.saturating_add(actual_weight)
-						})
-						.into()
-				})
-				.map_err(|err| match err.post_info.actual_weight {
-					Some(actual_weight) => {
-						let weight_used = T::WeightInfo::as_multi_threshold_1(call_len as u32)
-							.saturating_add(actual_weight);
-						let post_info = Some(weight_used).into();
-						DispatchErrorWithPostInfo { post_info, error: err.error }
-					},
-					None => err,
+				.map(|_| Some(weight_used).into())
+				.map_err(|err| DispatchErrorWithPostInfo {
+					post_info: Some(weight_used).into(),
+					error: err.error,
 				})
 		}
 
@@ -561,7 +551,9 @@ impl<T: Config> Pallet<T> {
 				<Multisigs<T>>::remove(&id, call_hash);
 				T::Currency::unreserve(&m.depositor, m.deposit);
 
-				let result = call.dispatch(RawOrigin::Signed(id.clone()).into());
+				let (result, nested) = dispatch_context::measure_nested(|| {
+					call.dispatch(RawOrigin::Signed(id.clone()).into())
+				});
 				Self::deposit_event(Event::MultisigExecuted {
 					approving: who,
 					timepoint,
@@ -569,15 +561,11 @@ impl<T: Config> Pallet<T> {
 					call_hash,
 					result: result.map(|_| ()).map_err(|e| e.error),
 				});
-				Ok(get_result_weight(result)
-					.map(|actual_weight| {
-						T::WeightInfo::as_multi_complete(
-							other_signatories_len as u32,
-							call_len as u32,
-						)
-						.saturating_add(actual_weight)
-					})
-					.into())
+				Ok(Some(
+					T::WeightInfo::as_multi_complete(other_signatories_len as u32, call_len as u32)
+						.saturating_add(nested.actual),
+				)
+				.into())
 			} else {
 				// We cannot dispatch the call now; either it isn't available, or it is, but we
 				// don't have threshold approvals even with our signature.
@@ -666,13 +654,3 @@ impl<T: Config> Pallet<T> {
 		Ok(signatories)
 	}
 }
-
-/// Return the weight of a dispatch call result as an `Option`.
-///
-/// Will return the weight regardless of what the state of the result is.
-fn get_result_weight(result: DispatchResultWithPostInfo) -> Option<Weight> {
-	match result {
-		Ok(post_info) => post_info.actual_weight,
-		Err(err) => err.post_info.actual_weight,
-	}
-}
@@ -0,0 +1,138 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Benchmarks for the Sassafras pallet.
+
+#![cfg(feature = "runtime-benchmarks")]
+
+use super::{Pallet as Sassafras, *};
+use frame_benchmarking::v2::*;
+use frame_system::RawOrigin;
+use sp_consensus_sassafras::EphemeralPublic;
+use sp_core::{bandersnatch, crypto::Pair};
+
+/// Make the largest ring of next epoch authorities and set up the pallet to accept the
+/// tickets of the first one, returning its key pair.
+fn setup_tickets_submission<T: Config>(tickets_count: u32) -> bandersnatch::Pair {
+	let ring_ctx = vrf::RingContext::new_testing();
+	let authorities_count = T::MaxAuthorities::get().min(ring_ctx.max_keyset_size() as u32);
+	let pairs: Vec<_> = (0..authorities_count)
+		.map(|i| bandersnatch::Pair::from_seed(&sp_io::hashing::blake2_256(&i.encode())))
+		.collect();
+	let authorities: Vec<AuthorityId> = pairs.iter().map(|pair| pair.public().into()).collect();
+
+	NextAuthorities::<T>::put(WeakBoundedVec::force_from(authorities, None));
+	RingContext::<T>::put(ring_ctx);
+	// Accept any ticket produced by the authority.
+	NextEpochConfig::<T>::put(EpochConfiguration {
+		redundancy_factor: u32::MAX,
+		attempts_number: tickets_count,
+	});
+
+	pairs.into_iter().next().expect("there is at least one authority; qed")
+}
+
+/// Make `tickets_count` tickets for the next epoch, as the client does.
+fn make_tickets<T: Config>(tickets_count: u32, pair: &bandersnatch::Pair) -> Vec<TicketEnvelope> {
+	let ring_members: Vec<_> = Sassafras::<T>::next_authorities()
+		.iter()
+		.map(|authority| authority.clone().into_inner())
+		.collect();
+	let prover = Sassafras::<T>::ring_context()
+		.and_then(|ctx| ctx.prover(&ring_members, 0))
+		.expect("ring context is set and the ring members are valid; qed");
+
+	let randomness = Sassafras::<T>::next_randomness();
+	let epoch_idx = EpochIndex::<T>::get() + 1;
+
+	(0..tickets_count)
+		.map(|attempt_idx| {
+			let ticket_id_input = vrf::ticket_id_input(&randomness, attempt_idx, epoch_idx);
+			let body = TicketBody {
+				attempt_idx,
+				erased_public: EphemeralPublic::from_raw([attempt_idx as u8; 32]),
+				revealed_public: EphemeralPublic::from_raw([!attempt_idx as u8; 32]),
+			};
+			let sign_data = vrf::ticket_body_sign_data(&body, ticket_id_input);
+			let signature = pair.ring_vrf_sign(&sign_data, &prover);
+			TicketEnvelope { body, signature }
+		})
+		.collect()
+}
+
+#[benchmarks]
+mod benchmarks {
+	use super::*;
+
+	// The ring signatures are produced during the setup, which makes this benchmark slow to run.
+	#[benchmark]
+	fn submit_tickets(x: Linear<1, { T::MaxTicketsPerCall::get() }>) {
+		let pair = setup_tickets_submission::<T>(x);
+		let tickets = BoundedVec::truncate_from(make_tickets::<T>(x, &pair));
+
+		#[extrinsic_call]
+		_(RawOrigin::None, tickets);
+
+		assert_eq!(TicketsMeta::<T>::get().unsorted_tickets_count, x);
+	}
+
+	#[benchmark]
+	fn plan_config_change() {
+		let config = EpochConfiguration { redundancy_factor: 1, attempts_number: 10 };
+
+		#[extrinsic_call]
+		_(RawOrigin::Root, config);
+
+		assert_eq!(PendingEpochConfigChange::<T>::get(), Some(config));
+	}
+
+	#[benchmark]
+	fn set_ring_context() {
+		let ring_ctx = vrf::RingContext::new_testing();
+
+		#[extrinsic_call]
+		_(RawOrigin::Root, Box::new(ring_ctx));
+
+		assert!(RingContext::<T>::get().is_some());
+	}
+
+	#[benchmark]
+	fn sort_segments(x: Linear<1, 100>) {
+		let tickets = (0..x * SEGMENT_MAX_SIZE)
+			.map(|i| {
+				let ticket_id = TicketId::from_le_bytes(sp_io::hashing::blake2_128(&i.encode()));
+				let body = TicketBody {
+					attempt_idx: i,
+					erased_public: EphemeralPublic::from_raw([0; 32]),
+					revealed_public: EphemeralPublic::from_raw([0; 32]),
+				};
+				(ticket_id, body)
+			})
+			.collect();
+		Sassafras::<T>::append_tickets(tickets);
+		let mut metadata = TicketsMeta::<T>::get();
+
+		#[block]
+		{
+			Sassafras::<T>::sort_segments(x, 1, &mut metadata);
+		}
+
+		assert_eq!(metadata.unsorted_tickets_count, 0);
+	}
+
+	impl_benchmark_test_suite!(Sassafras, crate::mock::new_test_ext(1), crate::mock::Test);
+}
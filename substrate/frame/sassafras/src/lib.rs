@@ -0,0 +1,1324 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Consensus extension module for Sassafras consensus.
+//!
+//! Sassafras is a constant-time block production protocol: authorities submit anonymous tickets
+//! during the epoch preceding the one where the tickets are used, the tickets are then sorted and
+//! assigned to the epoch slots following an "outside-in" strategy.
+//!
+//! The pallet is responsible for:
+//! - tickets submission, verification and sorting;
+//! - on-chain randomness collection from the slot claims VRF outputs;
+//! - epoch transitions;
+//! - authorities equivocation reporting.
+//!
+//! ### Tickets lifecycle
+//!
+//! During the first half of epoch `N` the tickets for epoch `N+1` can be submitted via the
+//! unsigned `submit_tickets` extrinsic. The ring VRF signature attached to each ticket proves that
+//! it was produced by one of the next epoch authorities without revealing which one. The ring
+//! signatures are verified using the ring context, set by governance with `set_ring_context`.
+//!
+//! Accepted tickets identifiers are stored in unsorted segments of at most [`SEGMENT_MAX_SIZE`]
+//! elements. During the second half of the epoch the segments are incrementally sorted on block
+//! finalization, retaining at most `EpochLength` tickets with the smallest identifiers. Sorting is
+//! forcibly completed on epoch change if required.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![warn(unused_must_use, unsafe_code, unused_variables, unused_must_use)]
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use scale_info::TypeInfo;
+
+use frame_support::{
+	dispatch::{DispatchResultWithPostInfo, Pays},
+	traits::{ConstU32, FindAuthor, Get, OneSessionHandler},
+	weights::Weight,
+	BoundedVec, WeakBoundedVec,
+};
+use frame_system::{
+	offchain::{SendTransactionTypes, SubmitTransaction},
+	pallet_prelude::{BlockNumberFor, HeaderFor},
+};
+use sp_consensus_sassafras::{
+	digests::{ConsensusLog, NextEpochDescriptor, SlotClaim},
+	vrf, AuthorityId, AuthorityIndex, Epoch, EpochConfiguration, EquivocationProof, Randomness,
+	Slot, SlotDuration, TicketBody, TicketEnvelope, TicketId, RANDOMNESS_LENGTH,
+	SASSAFRAS_ENGINE_ID,
+};
+use sp_io::hashing;
+use sp_runtime::{
+	generic::DigestItem,
+	traits::{One, SaturatedConversion, Saturating},
+	transaction_validity::{
+		InvalidTransaction, TransactionPriority, TransactionValidity, ValidTransaction,
+	},
+	ConsensusEngineId, Permill,
+};
+use sp_session::{GetSessionNumber, GetValidatorCount};
+use sp_staking::{offence::OffenceReportSystem, SessionIndex};
+use sp_std::prelude::*;
+
+pub use pallet::*;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+mod default_weights;
+mod equivocation;
+
+#[cfg(all(feature = "std", test))]
+mod mock;
+#[cfg(all(feature = "std", test))]
+mod tests;
+
+pub use equivocation::{EquivocationOffence, EquivocationReportSystem};
+
+const LOG_TARGET: &str = "runtime::sassafras";
+
+/// Context used to produce the per-slot randomness from the slot claim VRF output.
+pub const RANDOMNESS_VRF_CONTEXT: &[u8] = b"SassafrasOnChainRandomness";
+
+/// Max number of tickets identifiers stored in a single unsorted segment.
+pub const SEGMENT_MAX_SIZE: u32 = 128;
+
+/// Tickets related metadata that is commonly used together.
+#[derive(Debug, Default, PartialEq, Encode, Decode, TypeInfo, MaxEncodedLen, Clone, Copy)]
+pub struct TicketsMetadata {
+	/// Number of outstanding next epoch tickets which are still stored in the unsorted segments.
+	pub unsorted_tickets_count: u32,
+	/// Number of sorted tickets available for the current and for the next epoch.
+	///
+	/// The array index is given by the epoch index modulo 2 (i.e. the epoch tag).
+	pub tickets_count: [u32; 2],
+}
+
+pub trait WeightInfo {
+	fn submit_tickets(tickets_count: u32) -> Weight;
+	fn plan_config_change() -> Weight;
+	fn set_ring_context() -> Weight;
+	fn report_equivocation(validator_count: u32, max_nominators_per_validator: u32) -> Weight;
+	fn sort_segments(segments_count: u32) -> Weight;
+}
+
+/// Trigger an epoch change, if any should take place.
+pub trait EpochChangeTrigger {
+	/// Trigger an epoch change, if any should take place. This should be called
+	/// during every block, after initialization is done.
+	fn trigger<T: Config>(now: BlockNumberFor<T>);
+}
+
+/// A type signifying to Sassafras that an external trigger for epoch changes
+/// (e.g. pallet-session) is used.
+pub struct EpochChangeExternalTrigger;
+
+impl EpochChangeTrigger for EpochChangeExternalTrigger {
+	fn trigger<T: Config>(_: BlockNumberFor<T>) {} // nothing - trigger is external.
+}
+
+/// A type signifying to Sassafras that it should perform epoch changes with an internal
+/// trigger, recycling the same authorities forever.
+pub struct EpochChangeInternalTrigger;
+
+impl EpochChangeTrigger for EpochChangeInternalTrigger {
+	fn trigger<T: Config>(now: BlockNumberFor<T>) {
+		if <Pallet<T>>::should_epoch_change(now) {
+			let authorities = <Pallet<T>>::next_authorities();
+			let next_authorities = authorities.clone();
+
+			<Pallet<T>>::enact_epoch_change(authorities, next_authorities, None);
+		}
+	}
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	/// The Sassafras pallet.
+	#[pallet::pallet]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config + SendTransactionTypes<Call<Self>> {
+		/// Amount of slots that each epoch should last.
+		/// NOTE: Currently it is not possible to change the epoch length after the chain has
+		/// started. Attempting to do so will brick block production.
+		#[pallet::constant]
+		type EpochLength: Get<u32>;
+
+		/// The slot duration in milliseconds.
+		///
+		/// This is only used to fill the epoch information served to the client.
+		#[pallet::constant]
+		type SlotDuration: Get<u64>;
+
+		/// Max number of authorities allowed.
+		#[pallet::constant]
+		type MaxAuthorities: Get<u32>;
+
+		/// Max number of tickets submitted with a single `submit_tickets` call.
+		///
+		/// Must be low enough for the weight of the call to fit in a block. The tickets of the
+		/// local authorities are split over as many transactions as needed.
+		#[pallet::constant]
+		type MaxTicketsPerCall: Get<u32>;
+
+		/// Sassafras requires some logic to be triggered on every block to query for whether an
+		/// epoch has ended and to perform the transition to the next epoch.
+		///
+		/// Typically, the `EpochChangeExternalTrigger` type should be used. An internal trigger
+		/// should only be used when no other module is responsible for changing authority set.
+		type EpochChangeTrigger: EpochChangeTrigger;
+
+		/// Helper for weights computations.
+		type WeightInfo: WeightInfo;
+
+		/// The maximum number of nominators for each validator.
+		#[pallet::constant]
+		type MaxNominators: Get<u32>;
+
+		/// The proof of key ownership, used for validating equivocation reports.
+		/// The proof must include the session index and validator count of the
+		/// session at which the equivocation occurred.
+		type KeyOwnerProof: Parameter + GetSessionNumber + GetValidatorCount;
+
+		/// The equivocation handling subsystem, defines methods to check/report an
+		/// offence and for submitting a transaction to report an equivocation
+		/// (from an offchain context).
+		type EquivocationReportSystem: OffenceReportSystem<
+			Option<Self::AccountId>,
+			(EquivocationProof<HeaderFor<Self>>, Self::KeyOwnerProof),
+		>;
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// An equivocation proof provided as part of an equivocation report is invalid.
+		InvalidEquivocationProof,
+		/// A key ownership proof provided as part of an equivocation report is invalid.
+		InvalidKeyOwnershipProof,
+		/// A given equivocation report is valid but already previously reported.
+		DuplicateOffenceReport,
+		/// Submitted configuration is invalid.
+		InvalidConfiguration,
+		/// Tickets were submitted outside the tickets submission period.
+		TicketsSubmissionClosed,
+		/// Ring context is not available or the next epoch authorities are not valid ring
+		/// members.
+		RingVerifierUnavailable,
+		/// Ticket identifier is above the current threshold.
+		TicketOverThreshold,
+		/// Ticket has already been submitted.
+		TicketDuplicate,
+		/// Ticket body or ring signature is invalid.
+		TicketInvalid,
+	}
+
+	/// Current epoch index.
+	#[pallet::storage]
+	#[pallet::getter(fn epoch_index)]
+	pub type EpochIndex<T> = StorageValue<_, u64, ValueQuery>;
+
+	/// Current epoch authorities.
+	#[pallet::storage]
+	#[pallet::getter(fn authorities)]
+	pub type Authorities<T: Config> =
+		StorageValue<_, WeakBoundedVec<AuthorityId, T::MaxAuthorities>, ValueQuery>;
+
+	/// Next epoch authorities.
+	#[pallet::storage]
+	#[pallet::getter(fn next_authorities)]
+	pub type NextAuthorities<T: Config> =
+		StorageValue<_, WeakBoundedVec<AuthorityId, T::MaxAuthorities>, ValueQuery>;
+
+	/// The slot at which the first epoch actually started. This is 0
+	/// until the first block of the chain.
+	#[pallet::storage]
+	#[pallet::getter(fn genesis_slot)]
+	pub type GenesisSlot<T> = StorageValue<_, Slot, ValueQuery>;
+
+	/// Current slot number.
+	#[pallet::storage]
+	#[pallet::getter(fn current_slot)]
+	pub type CurrentSlot<T> = StorageValue<_, Slot, ValueQuery>;
+
+	/// Current epoch randomness.
+	#[pallet::storage]
+	#[pallet::getter(fn randomness)]
+	pub type CurrentRandomness<T> = StorageValue<_, Randomness, ValueQuery>;
+
+	/// Next epoch randomness.
+	#[pallet::storage]
+	#[pallet::getter(fn next_randomness)]
+	pub type NextRandomness<T> = StorageValue<_, Randomness, ValueQuery>;
+
+	/// Randomness accumulator.
+	///
+	/// Updated on each block finalization with the randomness derived from the slot claim.
+	#[pallet::storage]
+	pub(crate) type RandomnessAccumulator<T> = StorageValue<_, Randomness, ValueQuery>;
+
+	/// The configuration for the current epoch.
+	#[pallet::storage]
+	#[pallet::getter(fn config)]
+	pub type EpochConfig<T> = StorageValue<_, EpochConfiguration, ValueQuery>;
+
+	/// The configuration for the next epoch.
+	#[pallet::storage]
+	pub type NextEpochConfig<T> = StorageValue<_, EpochConfiguration>;
+
+	/// Pending epoch configuration change that will be set as `NextEpochConfig` when the next
+	/// epoch is enacted.
+	///
+	/// In other words, a configuration change submitted during epoch N will be enacted on epoch
+	/// N+2. This is to maintain coherence for already submitted tickets for epoch N+1 that where
+	/// computed using configuration parameters stored for epoch N+1.
+	#[pallet::storage]
+	pub(crate) type PendingEpochConfigChange<T> = StorageValue<_, EpochConfiguration>;
+
+	/// Stored tickets metadata.
+	#[pallet::storage]
+	pub type TicketsMeta<T> = StorageValue<_, TicketsMetadata, ValueQuery>;
+
+	/// Tickets identifiers map.
+	///
+	/// The map holds tickets ids for the current and next epoch.
+	///
+	/// The key is a tuple composed by:
+	/// - `u8` equal to epoch's index modulo 2;
+	/// - `u32` equal to the ticket's index in a sorted list of epoch's tickets.
+	///
+	/// Epoch X first N-th ticket has key (X mod 2, N)
+	///
+	/// Note that the ticket's index doesn't directly correspond to the slot index within the epoch.
+	/// The assignment is computed dynamically using an *outside-in* strategy.
+	///
+	/// Be aware that entries within this map are never removed, only overwritten.
+	/// Last element index should be fetched from the [`TicketsMeta`] value.
+	#[pallet::storage]
+	pub type TicketsIds<T> = StorageMap<_, Identity, (u8, u32), TicketId>;
+
+	/// Tickets to be used for current and next epoch.
+	#[pallet::storage]
+	pub type TicketsData<T> = StorageMap<_, Identity, TicketId, TicketBody>;
+
+	/// Next epoch tickets unsorted segments.
+	///
+	/// Contains lists of tickets where each list represents a batch of tickets
+	/// received via the `submit_tickets` extrinsic.
+	///
+	/// Each segment has max length [`SEGMENT_MAX_SIZE`].
+	#[pallet::storage]
+	pub type UnsortedSegments<T> =
+		StorageMap<_, Identity, u32, BoundedVec<TicketId, ConstU32<SEGMENT_MAX_SIZE>>, ValueQuery>;
+
+	/// The most recently computed sorted tickets candidates for the next epoch.
+	///
+	/// Used by the incremental sorting procedure to carry the partial result from
+	/// one block to the next one.
+	#[pallet::storage]
+	pub type SortedCandidates<T: Config> =
+		StorageValue<_, BoundedVec<TicketId, T::EpochLength>, ValueQuery>;
+
+	/// Parameters used to construct the epoch's ring verifier.
+	///
+	/// In practice: Updatable Universal Reference String and the seed.
+	#[pallet::storage]
+	#[pallet::getter(fn ring_context)]
+	pub type RingContext<T: Config> = StorageValue<_, vrf::RingContext>;
+
+	/// Slot claim VRF pre-output used to generate per-slot randomness.
+	///
+	/// The value is ephemeral and is cleared on block finalization.
+	#[pallet::storage]
+	pub(crate) type ClaimTemporaryData<T> = StorageValue<_, vrf::VrfOutput>;
+
+	/// A list of the last 100 skipped epochs and the corresponding session index
+	/// when the epoch was skipped.
+	///
+	/// This is only used for validating equivocation proofs. An equivocation proof
+	/// must contains a key-ownership proof for a given session, therefore we need a
+	/// way to tie together sessions and epoch indices, i.e. we need to validate that
+	/// a validator was the owner of a given key on a given session, and what the
+	/// active epoch index was during that session.
+	#[pallet::storage]
+	#[pallet::getter(fn skipped_epochs)]
+	pub(crate) type SkippedEpochs<T> =
+		StorageValue<_, BoundedVec<(u64, SessionIndex), ConstU32<100>>, ValueQuery>;
+
+	/// Genesis configuration for Sassafras protocol.
+	#[derive(frame_support::DefaultNoBound)]
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		/// Genesis authorities.
+		pub authorities: Vec<AuthorityId>,
+		/// Genesis epoch configuration.
+		pub epoch_config: EpochConfiguration,
+		/// Phantom config
+		#[serde(skip)]
+		pub _phantom: sp_std::marker::PhantomData<T>,
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> BuildGenesisConfig for GenesisConfig<T> {
+		fn build(&self) {
+			EpochConfig::<T>::put(self.epoch_config);
+			Pallet::<T>::genesis_authorities_initialize(&self.authorities);
+
+			#[cfg(feature = "construct-dummy-ring-context")]
+			{
+				// NOTE: the ring context parameters are generated from a fixed seed and must be
+				// used only for testing purposes.
+				log::warn!(target: LOG_TARGET, "Constructing testing ring context (in build)");
+				RingContext::<T>::set(Some(vrf::RingContext::new_testing()));
+			}
+		}
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(now: BlockNumberFor<T>) -> Weight {
+			Self::initialize(now);
+
+			// The tickets sorting is performed during block finalization, its weight is
+			// accounted for here as it can be computed in advance.
+			match Self::segments_to_sort() {
+				0 => Weight::zero(),
+				segments_count => T::WeightInfo::sort_segments(segments_count),
+			}
+		}
+
+		fn on_finalize(_now: BlockNumberFor<T>) {
+			// At the end of the block, we can safely include the current slot randomness
+			// to the accumulator. If we've determined that this block was the first in
+			// a new epoch, the changeover logic has already occurred at this point
+			// (i.e. `enact_epoch_change` has already been called).
+			let vrf_output = ClaimTemporaryData::<T>::take().expect(
+				"Unconditionally populated in `on_initialize`; \
+				 `on_finalize` is always called after; qed",
+			);
+			let randomness_input = vrf::slot_claim_input(
+				&Self::randomness(),
+				CurrentSlot::<T>::get(),
+				EpochIndex::<T>::get(),
+			);
+			let randomness = vrf_output
+				.make_bytes::<RANDOMNESS_LENGTH>(RANDOMNESS_VRF_CONTEXT, &randomness_input);
+			Self::deposit_slot_randomness(&randomness);
+
+			// Incrementally sort the next epoch tickets.
+			let segments_count = Self::segments_to_sort();
+			if segments_count != 0 {
+				let next_epoch_tag = (EpochIndex::<T>::get() + 1) as u8 & 1;
+				let mut metadata = TicketsMeta::<T>::get();
+				Self::sort_segments(segments_count, next_epoch_tag, &mut metadata);
+				TicketsMeta::<T>::set(metadata);
+			}
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Submit next epoch tickets candidates.
+		///
+		/// The number of tickets allowed to be submitted in one call is `MaxTicketsPerCall`.
+		/// This extrinsic must be called unsigned, as the ring signature attached to each ticket
+		/// is the only proof of authority the pallet requires.
+		#[pallet::call_index(0)]
+		#[pallet::weight(T::WeightInfo::submit_tickets(tickets.len() as u32))]
+		pub fn submit_tickets(
+			origin: OriginFor<T>,
+			tickets: BoundedVec<TicketEnvelope, T::MaxTicketsPerCall>,
+		) -> DispatchResultWithPostInfo {
+			ensure_none(origin)?;
+
+			let tickets = Self::check_tickets(&tickets)?;
+			log::debug!(target: LOG_TARGET, "Received {} valid tickets", tickets.len());
+			Self::append_tickets(tickets);
+
+			Ok(Pays::No.into())
+		}
+
+		/// Plan an epoch configuration change.
+		///
+		/// The epoch configuration change is recorded and will be announced at the beginning
+		/// of the next epoch together with next epoch authorities information. In other words,
+		/// the configuration will be enacted one epoch later.
+		///
+		/// Multiple calls to this method will replace any existing planned config change
+		/// that had not been enacted yet.
+		#[pallet::call_index(1)]
+		#[pallet::weight(T::WeightInfo::plan_config_change())]
+		pub fn plan_config_change(
+			origin: OriginFor<T>,
+			config: EpochConfiguration,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+
+			ensure!(
+				config.redundancy_factor != 0 && config.attempts_number != 0,
+				Error::<T>::InvalidConfiguration
+			);
+			PendingEpochConfigChange::<T>::put(config);
+			Ok(())
+		}
+
+		/// Report authority equivocation.
+		///
+		/// This method will verify the equivocation proof and validate the given key ownership
+		/// proof against the extracted offender. If both are valid, the offence will be reported.
+		#[pallet::call_index(2)]
+		#[pallet::weight(<T as Config>::WeightInfo::report_equivocation(
+			key_owner_proof.validator_count(),
+			T::MaxNominators::get(),
+		))]
+		pub fn report_equivocation(
+			origin: OriginFor<T>,
+			equivocation_proof: Box<EquivocationProof<HeaderFor<T>>>,
+			key_owner_proof: T::KeyOwnerProof,
+		) -> DispatchResultWithPostInfo {
+			let reporter = ensure_signed(origin)?;
+			T::EquivocationReportSystem::process_evidence(
+				Some(reporter),
+				(*equivocation_proof, key_owner_proof),
+			)?;
+			// Waive the fee since the report is valid and beneficial
+			Ok(Pays::No.into())
+		}
+
+		/// Report authority equivocation.
+		///
+		/// This method will verify the equivocation proof and validate the given key ownership
+		/// proof against the extracted offender. If both are valid, the offence will be reported.
+		///
+		/// This extrinsic must be called unsigned and it is expected that only block authors will
+		/// call it (validated in `ValidateUnsigned`), as such if the block author is defined it
+		/// will be defined as the equivocation reporter.
+		#[pallet::call_index(3)]
+		#[pallet::weight(<T as Config>::WeightInfo::report_equivocation(
+			key_owner_proof.validator_count(),
+			T::MaxNominators::get(),
+		))]
+		pub fn report_equivocation_unsigned(
+			origin: OriginFor<T>,
+			equivocation_proof: Box<EquivocationProof<HeaderFor<T>>>,
+			key_owner_proof: T::KeyOwnerProof,
+		) -> DispatchResultWithPostInfo {
+			ensure_none(origin)?;
+			T::EquivocationReportSystem::process_evidence(
+				None,
+				(*equivocation_proof, key_owner_proof),
+			)?;
+			Ok(Pays::No.into())
+		}
+
+		/// Set the parameters used to construct the epoch's ring verifier.
+		///
+		/// No ticket is accepted until the ring context is set, unless it is constructed on
+		/// genesis with the `construct-dummy-ring-context` feature, for testing only.
+		#[pallet::call_index(4)]
+		#[pallet::weight(T::WeightInfo::set_ring_context())]
+		pub fn set_ring_context(
+			origin: OriginFor<T>,
+			ring_context: Box<vrf::RingContext>,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+
+			RingContext::<T>::put(*ring_context);
+			Ok(())
+		}
+	}
+
+	#[pallet::validate_unsigned]
+	impl<T: Config> ValidateUnsigned for Pallet<T> {
+		type Call = Call<T>;
+
+		fn validate_unsigned(source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+			match call {
+				Call::submit_tickets { tickets } => Self::validate_tickets_unsigned(tickets),
+				Call::report_equivocation_unsigned { equivocation_proof, key_owner_proof } =>
+					Self::validate_equivocation_unsigned(
+						source,
+						equivocation_proof,
+						key_owner_proof,
+					),
+				_ => InvalidTransaction::Call.into(),
+			}
+		}
+
+		fn pre_dispatch(call: &Self::Call) -> Result<(), TransactionValidityError> {
+			match call {
+				// Tickets are fully verified on dispatch, here we only discard the ones
+				// which can't be accepted anymore.
+				Call::submit_tickets { .. } if Self::tickets_submission_open() => Ok(()),
+				Call::submit_tickets { .. } => Err(InvalidTransaction::Stale.into()),
+				Call::report_equivocation_unsigned { equivocation_proof, key_owner_proof } => {
+					let evidence = (*equivocation_proof.clone(), key_owner_proof.clone());
+					T::EquivocationReportSystem::check_evidence(evidence)
+				},
+				_ => Err(InvalidTransaction::Call.into()),
+			}
+		}
+	}
+}
+
+impl<T: Config> FindAuthor<AuthorityIndex> for Pallet<T> {
+	fn find_author<'a, I>(digests: I) -> Option<AuthorityIndex>
+	where
+		I: 'a + IntoIterator<Item = (ConsensusEngineId, &'a [u8])>,
+	{
+		for (id, mut data) in digests.into_iter() {
+			if id == SASSAFRAS_ENGINE_ID {
+				let claim = SlotClaim::decode(&mut data).ok()?;
+				return Some(claim.authority_idx)
+			}
+		}
+
+		None
+	}
+}
+
+impl<T: Config> pallet_session::ShouldEndSession<BlockNumberFor<T>> for Pallet<T> {
+	fn should_end_session(now: BlockNumberFor<T>) -> bool {
+		// It might be (and it is in current implementation) that session module is calling
+		// `should_end_session` from it's own `on_initialize` handler, in which case it's
+		// possible that Sassafras's own `on_initialize` has not run yet, so let's ensure that we
+		// have initialized the pallet and updated the current slot.
+		Self::initialize(now);
+		Self::should_epoch_change(now)
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Determine whether an epoch change should take place at this block.
+	/// Assumes that initialization has already taken place.
+	pub fn should_epoch_change(now: BlockNumberFor<T>) -> bool {
+		// The epoch has technically ended during the passage of time between this block and the
+		// last, but we have to "end" the epoch now, since there is no earlier possible block we
+		// could have done it.
+		//
+		// The exception is for block 1: the genesis has slot 0, so we treat epoch 0 as having
+		// started at the slot of block 1. We want to use the same randomness and validator set as
+		// signalled in the genesis, so we don't rotate the epoch.
+		now != One::one() && Self::current_slot_index() >= T::EpochLength::get()
+	}
+
+	/// Current slot index relative to the current epoch.
+	fn current_slot_index() -> u32 {
+		Self::slot_index(CurrentSlot::<T>::get())
+	}
+
+	/// Slot index relative to the current epoch.
+	fn slot_index(slot: Slot) -> u32 {
+		u64::from(slot)
+			.checked_sub(*Self::current_epoch_start())
+			.and_then(|v| v.try_into().ok())
+			.unwrap_or(u32::MAX)
+	}
+
+	/// Finds the start slot of the current epoch.
+	///
+	/// Only guaranteed to give correct results after `initialize` of the first
+	/// block in the chain (as its result is based off of `GenesisSlot`).
+	fn current_epoch_start() -> Slot {
+		Self::epoch_start(EpochIndex::<T>::get())
+	}
+
+	/// Get the epoch's first slot.
+	fn epoch_start(epoch_index: u64) -> Slot {
+		const PROOF: &str = "slot number is u64; it should relate in some way to wall clock time; \
+							 if u64 is not enough we should crash for safety; qed.";
+
+		let epoch_start = epoch_index.checked_mul(T::EpochLength::get() as u64).expect(PROOF);
+		GenesisSlot::<T>::get().checked_add(epoch_start).expect(PROOF).into()
+	}
+
+	/// Get the epoch index the given slot belongs to.
+	pub(crate) fn epoch_index_of(slot: Slot) -> u64 {
+		*slot.saturating_sub(GenesisSlot::<T>::get()) / T::EpochLength::get() as u64
+	}
+
+	/// Returns `true` if the next epoch tickets can be submitted at the current slot.
+	fn tickets_submission_open() -> bool {
+		Self::current_slot_index() < T::EpochLength::get() / 2
+	}
+
+	/// Number of unsorted segments to be sorted during the current block finalization.
+	///
+	/// Sorting happens during the second half of the epoch, the outstanding segments are evenly
+	/// spread across the remaining slots. Returns 0 if there is nothing to be sorted.
+	fn segments_to_sort() -> u32 {
+		let epoch_length = T::EpochLength::get();
+		let slot_idx = Self::current_slot_index();
+		if slot_idx < epoch_length / 2 || slot_idx >= epoch_length {
+			return 0
+		}
+		let unsorted_tickets_count = TicketsMeta::<T>::get().unsorted_tickets_count;
+		let segments_count = div_ceil(unsorted_tickets_count, SEGMENT_MAX_SIZE);
+		div_ceil(segments_count, epoch_length - slot_idx)
+	}
+
+	/// DANGEROUS: Enact an epoch change. Should be done on every block where `should_epoch_change`
+	/// has returned `true`, and the caller is the only caller of this function.
+	///
+	/// Typically, this is not handled directly, but by a higher-level validator-set
+	/// manager module like `pallet-session`.
+	///
+	/// This doesn't do anything if `authorities` is empty.
+	pub fn enact_epoch_change(
+		authorities: WeakBoundedVec<AuthorityId, T::MaxAuthorities>,
+		next_authorities: WeakBoundedVec<AuthorityId, T::MaxAuthorities>,
+		session_index: Option<SessionIndex>,
+	) {
+		// PRECONDITION: caller has done initialization.
+		// If using the internal trigger or the session pallet then this is guaranteed.
+		debug_assert!(ClaimTemporaryData::<T>::exists());
+
+		if authorities.is_empty() {
+			log::warn!(target: LOG_TARGET, "Ignoring empty epoch change.");
+			return
+		}
+
+		// Update epoch index.
+		//
+		// NOTE: we figure out the epoch index from the slot, which may not
+		// necessarily be contiguous if the chain was offline for more than
+		// `T::EpochLength` slots. When skipping from epoch N to e.g. N+4, we
+		// will be using the randomness and authorities for that epoch that had
+		// been previously announced for epoch N+1, and the randomness collected
+		// during the current epoch (N) will be used for epoch N+5.
+		let epoch_idx = Self::epoch_index_of(CurrentSlot::<T>::get());
+
+		let prev_epoch_idx = EpochIndex::<T>::get();
+		let skipped_epochs = prev_epoch_idx.saturating_add(1) != epoch_idx;
+		if skipped_epochs {
+			log::warn!(
+				target: LOG_TARGET,
+				"Detected {} skipped epochs, resuming from epoch {}",
+				epoch_idx.saturating_sub(prev_epoch_idx + 1),
+				epoch_idx,
+			);
+			// We are skipping epochs therefore we need to update the mapping
+			// of epochs to session.
+			if let Some(session_index) = session_index {
+				Self::note_skipped_epoch(epoch_idx, session_index);
+			}
+		}
+
+		EpochIndex::<T>::put(epoch_idx);
+		Authorities::<T>::put(authorities);
+		NextAuthorities::<T>::put(&next_authorities);
+
+		// Update epoch randomness.
+		let next_epoch_idx = epoch_idx
+			.checked_add(1)
+			.expect("epoch indices will never reach 2^64 before the death of the universe; qed");
+		Self::update_epoch_randomness(next_epoch_idx);
+
+		// Update epoch configuration.
+		if let Some(config) = NextEpochConfig::<T>::take() {
+			EpochConfig::<T>::put(config);
+		}
+		let next_config = PendingEpochConfigChange::<T>::take();
+		if let Some(next_config) = next_config {
+			NextEpochConfig::<T>::put(next_config);
+		}
+
+		// After we update the current epoch, we signal the *next* epoch change
+		// so that nodes can track changes.
+		let next_epoch = NextEpochDescriptor {
+			authorities: next_authorities.into_inner(),
+			randomness: NextRandomness::<T>::get(),
+			config: next_config,
+		};
+		Self::deposit_next_epoch_descriptor_digest(next_epoch);
+
+		if skipped_epochs {
+			// Tickets collected during the previous epoch are bound to an epoch which
+			// never happened.
+			Self::reset_tickets_data();
+		} else {
+			let epoch_tag = (epoch_idx & 1) as u8;
+			let mut metadata = TicketsMeta::<T>::get();
+			// Complete the sorting of the tickets for the epoch which has just started.
+			if metadata.unsorted_tickets_count != 0 {
+				Self::sort_segments(u32::MAX, epoch_tag, &mut metadata);
+			}
+			// Cleanup the tickets of the epoch which has just ended, this frees the slot
+			// for the tickets which are going to be submitted for the next epoch.
+			Self::clear_epoch_tickets(epoch_tag ^ 1, &mut metadata);
+			TicketsMeta::<T>::set(metadata);
+		}
+	}
+
+	fn note_skipped_epoch(epoch_idx: u64, session_index: SessionIndex) {
+		SkippedEpochs::<T>::mutate(|skipped_epochs| {
+			if epoch_idx < session_index as u64 {
+				log::warn!(
+					target: LOG_TARGET,
+					"Current epoch index {} is lower than session index {}.",
+					epoch_idx,
+					session_index,
+				);
+
+				return
+			}
+
+			if skipped_epochs.is_full() {
+				// NOTE: this is O(n) but we currently don't have a bounded `VecDeque`.
+				// this vector is bounded to a small number of elements so performance
+				// shouldn't be an issue.
+				skipped_epochs.remove(0);
+			}
+
+			skipped_epochs.force_push((epoch_idx, session_index));
+		})
+	}
+
+	/// Call this function on epoch change to enact current epoch randomness.
+	///
+	/// Returns the next epoch randomness.
+	fn update_epoch_randomness(next_epoch_index: u64) -> Randomness {
+		let curr_epoch_randomness = NextRandomness::<T>::get();
+		CurrentRandomness::<T>::put(curr_epoch_randomness);
+
+		let accumulator = RandomnessAccumulator::<T>::get();
+
+		let mut buf = [0; RANDOMNESS_LENGTH + 8];
+		buf[..RANDOMNESS_LENGTH].copy_from_slice(&accumulator[..]);
+		buf[RANDOMNESS_LENGTH..].copy_from_slice(&next_epoch_index.to_le_bytes());
+
+		let next_randomness = hashing::blake2_256(&buf);
+		NextRandomness::<T>::put(next_randomness);
+
+		next_randomness
+	}
+
+	/// Deposit per-slot randomness.
+	fn deposit_slot_randomness(randomness: &Randomness) {
+		let accumulator = RandomnessAccumulator::<T>::get();
+
+		let mut buf = [0; 2 * RANDOMNESS_LENGTH];
+		buf[..RANDOMNESS_LENGTH].copy_from_slice(&accumulator[..]);
+		buf[RANDOMNESS_LENGTH..].copy_from_slice(&randomness[..]);
+
+		let accumulator = hashing::blake2_256(&buf);
+		RandomnessAccumulator::<T>::put(accumulator);
+	}
+
+	/// Deposit next epoch descriptor in the block header digest.
+	fn deposit_next_epoch_descriptor_digest(desc: NextEpochDescriptor) {
+		let item = ConsensusLog::NextEpochData(desc);
+		let log = DigestItem::Consensus(SASSAFRAS_ENGINE_ID, item.encode());
+		<frame_system::Pallet<T>>::deposit_log(log)
+	}
+
+	// Initialize authorities on genesis phase.
+	fn genesis_authorities_initialize(authorities: &[AuthorityId]) {
+		let prev_authorities = Authorities::<T>::get();
+
+		if !prev_authorities.is_empty() {
+			// This function has already been called.
+			if prev_authorities.as_slice() == authorities {
+				return
+			} else {
+				panic!("Authorities were already initialized");
+			}
+		}
+
+		let bounded_authorities =
+			WeakBoundedVec::<_, T::MaxAuthorities>::try_from(authorities.to_vec())
+				.expect("Initial number of authorities should be lower than T::MaxAuthorities");
+		Authorities::<T>::put(&bounded_authorities);
+		NextAuthorities::<T>::put(&bounded_authorities);
+	}
+
+	// Method to be called on first block `on_initialize` to properly populate some key parameters.
+	fn post_genesis_initialize(slot: Slot) {
+		// Keep track of the actual first slot used (may not be zero based).
+		GenesisSlot::<T>::put(slot);
+
+		// Deposit a log as this is the first block in first epoch.
+		// The next epoch data is the one announced at genesis.
+		let next_epoch = NextEpochDescriptor {
+			authorities: Self::next_authorities().into_inner(),
+			randomness: Self::next_randomness(),
+			config: None,
+		};
+		Self::deposit_next_epoch_descriptor_digest(next_epoch);
+	}
+
+	fn initialize(now: BlockNumberFor<T>) {
+		// Since `initialize` can be called twice (e.g. if session pallet is used)
+		// let's ensure that we only do the initialization once per block.
+		if ClaimTemporaryData::<T>::exists() {
+			return
+		}
+
+		let claim = <frame_system::Pallet<T>>::digest()
+			.logs
+			.iter()
+			.find_map(|item| SlotClaim::try_from(item).ok())
+			.expect("Valid block must have a slot claim. qed");
+
+		CurrentSlot::<T>::put(claim.slot);
+
+		// On the first non-zero block (i.e. block #1) this is where the first epoch
+		// (epoch #0) actually starts. We need to adjust internal storage accordingly.
+		if *GenesisSlot::<T>::get() == 0 {
+			log::debug!(target: LOG_TARGET, "Genesis slot: {:?}", claim.slot);
+			Self::post_genesis_initialize(claim.slot);
+		}
+
+		let vrf_output = claim
+			.vrf_signature
+			.outputs
+			.get(0)
+			.expect("Valid claim must have VRF signature; qed")
+			.clone();
+		ClaimTemporaryData::<T>::put(vrf_output);
+
+		// Enact epoch change, if necessary.
+		T::EpochChangeTrigger::trigger::<T>(now);
+	}
+
+	/// Current epoch information.
+	pub fn current_epoch() -> Epoch {
+		Epoch {
+			epoch_idx: EpochIndex::<T>::get(),
+			start_slot: Self::current_epoch_start(),
+			slot_duration: SlotDuration::from_millis(T::SlotDuration::get()),
+			epoch_duration: T::EpochLength::get() as u64,
+			authorities: Self::authorities().into_inner(),
+			randomness: Self::randomness(),
+			config: Self::config(),
+		}
+	}
+
+	/// Next epoch information.
+	pub fn next_epoch() -> Epoch {
+		let next_epoch_idx = EpochIndex::<T>::get().checked_add(1).expect(
+			"epoch index is u64; it is always only incremented by one; \
+			 if u64 is not enough we should crash for safety; qed.",
+		);
+
+		Epoch {
+			epoch_idx: next_epoch_idx,
+			start_slot: Self::epoch_start(next_epoch_idx),
+			slot_duration: SlotDuration::from_millis(T::SlotDuration::get()),
+			epoch_duration: T::EpochLength::get() as u64,
+			authorities: Self::next_authorities().into_inner(),
+			randomness: Self::next_randomness(),
+			config: Self::next_config(),
+		}
+	}
+
+	/// Next epoch configuration.
+	fn next_config() -> EpochConfiguration {
+		NextEpochConfig::<T>::get().unwrap_or_else(|| EpochConfig::<T>::get())
+	}
+
+	/// Fetch expected ticket-id for the given slot according to an "outside-in" sorting strategy.
+	///
+	/// Given an ordered sequence of tickets [t0, t1, t2, ..., tk] to be assigned to n slots,
+	/// with n >= k, then the tickets are assigned to the slots according to the following
+	/// strategy:
+	///
+	/// slot-index  : [ 0,  1,  2, ............ , n ]
+	/// tickets     : [ t1, t3, t5, ... , t4, t2, t0 ].
+	///
+	/// With slot-index computed as `epoch_start() - slot`.
+	///
+	/// If `slot` value falls within the current epoch then we fetch tickets from the current epoch
+	/// tickets list.
+	///
+	/// If `slot` value falls within the next epoch then we fetch tickets from the next epoch
+	/// tickets ids list. Note that in this case we may have not finished receiving all the tickets
+	/// for that epoch yet. The next epoch tickets should be considered "stable" only after the
+	/// current epoch first half slots were elapsed (see `submit_tickets`).
+	///
+	/// If `slot` value falls within the next epoch and the next epoch tickets sorting has not
+	/// completed yet, then `None` is returned.
+	///
+	/// Returns `None` if, according to the sorting strategy, there is no ticket associated to the
+	/// specified slot-index (happens if a ticket falls in the middle of an epoch and n > k),
+	/// or if the slot falls beyond the next epoch.
+	pub fn slot_ticket_id(slot: Slot) -> Option<TicketId> {
+		let epoch_length = T::EpochLength::get();
+		let mut slot_idx = Self::slot_index(slot);
+		let mut epoch_tag = (EpochIndex::<T>::get() & 1) as u8;
+
+		if epoch_length <= slot_idx && slot_idx < 2 * epoch_length {
+			// Try to get a ticket for the next epoch.
+			slot_idx -= epoch_length;
+			epoch_tag ^= 1;
+		} else if slot_idx >= epoch_length {
+			// Slot beyond the next epoch.
+			return None
+		}
+
+		let ticket_idx = if slot_idx < epoch_length / 2 {
+			2 * slot_idx + 1
+		} else {
+			2 * (epoch_length - (slot_idx + 1))
+		};
+
+		let tickets_count = TicketsMeta::<T>::get().tickets_count[epoch_tag as usize];
+		if ticket_idx >= tickets_count {
+			return None
+		}
+
+		TicketsIds::<T>::get((epoch_tag, ticket_idx))
+	}
+
+	/// Returns ticket id and data associated with the given `slot`.
+	///
+	/// Refer to the `slot_ticket_id` documentation for the slot-ticket association
+	/// criteria.
+	pub fn slot_ticket(slot: Slot) -> Option<(TicketId, TicketBody)> {
+		Self::slot_ticket_id(slot).and_then(|id| TicketsData::<T>::get(id).map(|body| (id, body)))
+	}
+
+	/// Verify the given tickets against the next epoch parameters.
+	///
+	/// Returns the tickets identifiers together with the tickets bodies.
+	fn check_tickets(tickets: &[TicketEnvelope]) -> Result<Vec<(TicketId, TicketBody)>, Error<T>> {
+		if !Self::tickets_submission_open() {
+			return Err(Error::<T>::TicketsSubmissionClosed)
+		}
+
+		let next_authorities = Self::next_authorities();
+		let ring_members: Vec<_> = next_authorities
+			.iter()
+			.map(|authority| authority.clone().into_inner())
+			.collect();
+		let verifier = Self::ring_context()
+			.and_then(|ctx| ctx.verifier(&ring_members))
+			.ok_or(Error::<T>::RingVerifierUnavailable)?;
+
+		let config = Self::next_config();
+		let threshold = sp_consensus_sassafras::ticket_id_threshold(
+			config.redundancy_factor,
+			T::EpochLength::get(),
+			config.attempts_number,
+			next_authorities.len() as u32,
+		);
+
+		let randomness = Self::next_randomness();
+		let next_epoch_idx = EpochIndex::<T>::get() + 1;
+
+		let mut checked: Vec<(TicketId, TicketBody)> = Vec::with_capacity(tickets.len());
+		for ticket in tickets {
+			if ticket.body.attempt_idx >= config.attempts_number {
+				return Err(Error::<T>::TicketInvalid)
+			}
+
+			let ticket_id_input =
+				vrf::ticket_id_input(&randomness, ticket.body.attempt_idx, next_epoch_idx);
+			let ticket_id_output =
+				ticket.signature.outputs.get(0).ok_or(Error::<T>::TicketInvalid)?;
+			let ticket_id = vrf::make_ticket_id(&ticket_id_input, ticket_id_output);
+
+			if ticket_id >= threshold {
+				return Err(Error::<T>::TicketOverThreshold)
+			}
+			if TicketsData::<T>::contains_key(ticket_id) ||
+				checked.iter().any(|(id, _)| *id == ticket_id)
+			{
+				return Err(Error::<T>::TicketDuplicate)
+			}
+
+			let sign_data = vrf::ticket_body_sign_data(&ticket.body, ticket_id_input);
+			if !ticket.signature.ring_vrf_verify(&sign_data, &verifier) {
+				return Err(Error::<T>::TicketInvalid)
+			}
+
+			checked.push((ticket_id, ticket.body.clone()));
+		}
+
+		Ok(checked)
+	}
+
+	/// Append the verified tickets to the unsorted segments.
+	fn append_tickets(tickets: Vec<(TicketId, TicketBody)>) {
+		let mut metadata = TicketsMeta::<T>::get();
+
+		for (ticket_id, ticket_body) in tickets {
+			let segment_idx = metadata.unsorted_tickets_count / SEGMENT_MAX_SIZE;
+			UnsortedSegments::<T>::mutate(segment_idx, |segment| {
+				segment
+					.try_push(ticket_id)
+					.expect("segment index is derived from the unsorted tickets count; qed")
+			});
+			TicketsData::<T>::insert(ticket_id, ticket_body);
+			metadata.unsorted_tickets_count += 1;
+		}
+
+		TicketsMeta::<T>::set(metadata);
+	}
+
+	/// Incremental sorting of the next epoch tickets.
+	///
+	/// At most `max_segments` unsorted segments are merged into the sorted candidates list,
+	/// which is then truncated to the epoch length. The tickets which don't make it are
+	/// removed from the tickets data.
+	///
+	/// Once all the segments have been processed, the candidates are moved to the tickets
+	/// identifiers map under the given `epoch_tag`.
+	pub(crate) fn sort_segments(max_segments: u32, epoch_tag: u8, metadata: &mut TicketsMetadata) {
+		let segments_count = div_ceil(metadata.unsorted_tickets_count, SEGMENT_MAX_SIZE);
+		let max_segments = max_segments.min(segments_count);
+		let max_tickets = T::EpochLength::get() as usize;
+
+		let mut candidates = SortedCandidates::<T>::take().into_inner();
+
+		// Segments are consumed starting from the last one, which is the only one which may be
+		// partially filled. This way the remaining segments are all full.
+		for segment_idx in (segments_count - max_segments..segments_count).rev() {
+			candidates.extend(UnsortedSegments::<T>::take(segment_idx));
+		}
+		metadata.unsorted_tickets_count = (segments_count - max_segments) * SEGMENT_MAX_SIZE;
+
+		candidates.sort_unstable();
+		if candidates.len() > max_tickets {
+			candidates[max_tickets..].iter().for_each(TicketsData::<T>::remove);
+			candidates.truncate(max_tickets);
+		}
+
+		if metadata.unsorted_tickets_count == 0 {
+			// Sorting is over, publish the tickets for the epoch.
+			candidates.iter().enumerate().for_each(|(idx, ticket_id)| {
+				TicketsIds::<T>::insert((epoch_tag, idx as u32), ticket_id);
+			});
+			metadata.tickets_count[epoch_tag as usize] = candidates.len() as u32;
+		} else {
+			SortedCandidates::<T>::set(BoundedVec::truncate_from(candidates));
+		}
+	}
+
+	/// Remove all the sorted tickets associated to the given epoch tag.
+	fn clear_epoch_tickets(epoch_tag: u8, metadata: &mut TicketsMetadata) {
+		let tickets_count = metadata.tickets_count[epoch_tag as usize];
+		(0..tickets_count).for_each(|idx| {
+			if let Some(ticket_id) = TicketsIds::<T>::take((epoch_tag, idx)) {
+				TicketsData::<T>::remove(ticket_id);
+			}
+		});
+		metadata.tickets_count[epoch_tag as usize] = 0;
+	}
+
+	/// Remove all tickets related data.
+	///
+	/// May not be efficient as the calling places may repeat some of this operations
+	/// but is a very extraordinary operation (hopefully never happens in production)
+	/// and better safe than sorry.
+	fn reset_tickets_data() {
+		let mut metadata = TicketsMeta::<T>::get();
+
+		// Remove sorted tickets.
+		Self::clear_epoch_tickets(0, &mut metadata);
+		Self::clear_epoch_tickets(1, &mut metadata);
+
+		// Remove unsorted tickets.
+		let segments_count = div_ceil(metadata.unsorted_tickets_count, SEGMENT_MAX_SIZE);
+		(0..segments_count).for_each(|segment_idx| {
+			UnsortedSegments::<T>::take(segment_idx)
+				.iter()
+				.for_each(TicketsData::<T>::remove);
+		});
+		SortedCandidates::<T>::take().iter().for_each(TicketsData::<T>::remove);
+
+		TicketsMeta::<T>::kill();
+	}
+
+	/// Validity checks for the tickets submitted via an unsigned extrinsic.
+	fn validate_tickets_unsigned(tickets: &[TicketEnvelope]) -> TransactionValidity {
+		let tickets = Self::check_tickets(tickets).map_err(|err| match err {
+			Error::<T>::TicketsSubmissionClosed => InvalidTransaction::Stale,
+			_ => InvalidTransaction::BadProof,
+		})?;
+
+		// The transaction is valid until the end of the tickets submission period.
+		let longevity = (T::EpochLength::get() / 2).saturating_sub(Self::current_slot_index());
+
+		tickets
+			.iter()
+			.fold(ValidTransaction::with_tag_prefix("Sassafras"), |builder, (ticket_id, _)| {
+				// Each ticket can be included once.
+				builder.and_provides(ticket_id)
+			})
+			// Tickets are submitted once per epoch and must be included before the end of
+			// the submission period, thus we assign the maximum priority.
+			.priority(TransactionPriority::max_value())
+			.longevity(longevity as u64)
+			// Tickets need to reach the block authors.
+			.propagate(true)
+			.build()
+	}
+
+	/// Submits the tickets for the next epoch via unsigned extrinsics, each one carrying at most
+	/// `MaxTicketsPerCall` tickets.
+	///
+	/// Only useful in an offchain context.
+	pub fn submit_tickets_unsigned_extrinsic(tickets: Vec<TicketEnvelope>) -> bool {
+		let chunk_size = T::MaxTicketsPerCall::get().max(1) as usize;
+		let mut submitted = true;
+		for chunk in tickets.chunks(chunk_size) {
+			let tickets = BoundedVec::truncate_from(chunk.to_vec());
+			let call = Call::submit_tickets { tickets };
+			let res = SubmitTransaction::<T, Call<T>>::submit_unsigned_transaction(call.into());
+			if let Err(e) = res {
+				log::error!(target: LOG_TARGET, "Error submitting tickets {:?}", e);
+				submitted = false;
+			}
+		}
+		submitted
+	}
+
+	/// Returns the session index that was live when the given epoch happened,
+	/// taking into account any skipped epochs.
+	///
+	/// This function is only well defined for epochs that actually existed,
+	/// e.g. if we skipped from epoch 10 to 20 then a call for epoch 15 (which
+	/// didn't exist) will return an incorrect session index.
+	pub(crate) fn session_index_for_epoch(epoch_index: u64) -> SessionIndex {
+		let skipped_epochs = SkippedEpochs::<T>::get();
+		match skipped_epochs.binary_search_by_key(&epoch_index, |(epoch_index, _)| *epoch_index) {
+			// we have an exact match so we just return the given session index
+			Ok(index) => skipped_epochs[index].1,
+			// we haven't found any skipped epoch before the given epoch,
+			// so the epoch index and session index should match
+			Err(0) => epoch_index.saturated_into::<u32>(),
+			// we have found a skipped epoch before the given epoch
+			Err(index) => {
+				// the element before the given index should give us the skipped epoch
+				// that's closest to the one we're trying to find the session index for
+				let closest_skipped_epoch = skipped_epochs[index - 1];
+
+				// calculate the number of skipped epochs at this point by checking the difference
+				// between the epoch and session indices. epoch index should always be greater or
+				// equal to session index, this is because epochs can be skipped whereas sessions
+				// can't (this is enforced when pushing into `SkippedEpochs`)
+				let skipped_epochs = closest_skipped_epoch.0 - closest_skipped_epoch.1 as u64;
+				epoch_index.saturating_sub(skipped_epochs).saturated_into::<u32>()
+			},
+		}
+	}
+
+	/// Submits an extrinsic to report an equivocation. This method will create
+	/// an unsigned extrinsic with a call to `report_equivocation_unsigned` and
+	/// will push the transaction to the pool. Only useful in an offchain
+	/// context.
+	pub fn submit_unsigned_equivocation_report(
+		equivocation_proof: EquivocationProof<HeaderFor<T>>,
+		key_owner_proof: T::KeyOwnerProof,
+	) -> bool {
+		T::EquivocationReportSystem::publish_evidence((equivocation_proof, key_owner_proof)).is_ok()
+	}
+}
+
+impl<T: Config> frame_support::traits::EstimateNextSessionRotation<BlockNumberFor<T>>
+	for Pallet<T>
+{
+	fn average_session_length() -> BlockNumberFor<T> {
+		T::EpochLength::get().into()
+	}
+
+	fn estimate_current_session_progress(_now: BlockNumberFor<T>) -> (Option<Permill>, Weight) {
+		let elapsed = Self::current_slot_index().saturating_add(1);
+
+		(
+			Some(Permill::from_rational(elapsed, T::EpochLength::get())),
+			// Read: Current Slot, Epoch Index, Genesis Slot
+			T::DbWeight::get().reads(3),
+		)
+	}
+
+	fn estimate_next_session_rotation(
+		now: BlockNumberFor<T>,
+	) -> (Option<BlockNumberFor<T>>, Weight) {
+		let slots_remaining = T::EpochLength::get().checked_sub(Self::current_slot_index());
+		(
+			// This is a best effort guess. Drifts in the slot/block ratio will cause errors here.
+			slots_remaining.map(|slots| now.saturating_add(slots.into())),
+			// Read: Current Slot, Epoch Index, Genesis Slot
+			T::DbWeight::get().reads(3),
+		)
+	}
+}
+
+impl<T: Config> sp_runtime::BoundToRuntimeAppPublic for Pallet<T> {
+	type Public = AuthorityId;
+}
+
+impl<T: Config> OneSessionHandler<T::AccountId> for Pallet<T>
+where
+	T: pallet_session::Config,
+{
+	type Key = AuthorityId;
+
+	fn on_genesis_session<'a, I: 'a>(validators: I)
+	where
+		I: Iterator<Item = (&'a T::AccountId, AuthorityId)>,
+	{
+		let authorities: Vec<_> = validators.map(|(_, k)| k).collect();
+		Self::genesis_authorities_initialize(&authorities);
+	}
+
+	fn on_new_session<'a, I: 'a>(_changed: bool, validators: I, queued_validators: I)
+	where
+		I: Iterator<Item = (&'a T::AccountId, AuthorityId)>,
+	{
+		let authorities = validators.map(|(_account, k)| k).collect();
+		let bounded_authorities = WeakBoundedVec::<_, T::MaxAuthorities>::force_from(
+			authorities,
+			Some(
+				"Warning: The session has more validators than expected. \
+				A runtime configuration adjustment may be needed.",
+			),
+		);
+
+		let next_authorities = queued_validators.map(|(_account, k)| k).collect();
+		let next_bounded_authorities = WeakBoundedVec::<_, T::MaxAuthorities>::force_from(
+			next_authorities,
+			Some(
+				"Warning: The session has more queued validators than expected. \
+				A runtime configuration adjustment may be needed.",
+			),
+		);
+
+		let session_index = <pallet_session::Pallet<T>>::current_index();
+
+		Self::enact_epoch_change(bounded_authorities, next_bounded_authorities, Some(session_index))
+	}
+
+	fn on_disabled(i: u32) {
+		let log = DigestItem::Consensus(SASSAFRAS_ENGINE_ID, ConsensusLog::OnDisabled(i).encode());
+		<frame_system::Pallet<T>>::deposit_log(log)
+	}
+}
+
+/// Integer division rounding up.
+fn div_ceil(num: u32, den: u32) -> u32 {
+	if den == 0 {
+		return 0
+	}
+	num / den + u32::from(num % den != 0)
+}
@@ -0,0 +1,274 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Test utilities for Sassafras pallet.
+
+use crate::{self as pallet_sassafras, EpochChangeInternalTrigger};
+
+use frame_support::{
+	derive_impl,
+	traits::{ConstU32, ConstU64, OnFinalize, OnInitialize},
+};
+use frame_system::pallet_prelude::HeaderFor;
+use sp_consensus_sassafras::{
+	digests::SlotClaim,
+	vrf::{self, VrfSignature},
+	AuthorityIndex, AuthorityPair, EphemeralPublic, EpochConfiguration, EquivocationProof, Slot,
+	TicketBody, TicketEnvelope, TicketId,
+};
+use sp_core::{
+	crypto::{Pair, VrfSecret},
+	H256,
+};
+use sp_runtime::{
+	testing::{Digest, DigestItem, TestXt},
+	traits::Header as _,
+	BuildStorage,
+};
+
+const LOG_TARGET: &str = "sassafras::tests";
+
+pub const EPOCH_LENGTH: u32 = 10;
+pub const MAX_AUTHORITIES: u32 = 100;
+
+type Block = frame_system::mocking::MockBlock<Test>;
+
+frame_support::construct_runtime!(
+	pub enum Test
+	{
+		System: frame_system,
+		Sassafras: pallet_sassafras,
+	}
+);
+
+#[derive_impl(frame_system::config_preludes::TestDefaultConfig as frame_system::DefaultConfig)]
+impl frame_system::Config for Test {
+	type Block = Block;
+}
+
+impl<C> frame_system::offchain::SendTransactionTypes<C> for Test
+where
+	RuntimeCall: From<C>,
+{
+	type OverarchingCall = RuntimeCall;
+	type Extrinsic = TestXt<RuntimeCall, ()>;
+}
+
+impl pallet_sassafras::Config for Test {
+	type EpochLength = ConstU32<EPOCH_LENGTH>;
+	type SlotDuration = ConstU64<1000>;
+	type MaxAuthorities = ConstU32<MAX_AUTHORITIES>;
+	type MaxTicketsPerCall = ConstU32<4>;
+	type EpochChangeTrigger = EpochChangeInternalTrigger;
+	type WeightInfo = ();
+	type MaxNominators = ConstU32<0>;
+	type KeyOwnerProof = sp_core::Void;
+	type EquivocationReportSystem = ();
+}
+
+/// Default epoch configuration.
+///
+/// The redundancy factor is high enough to make the tickets threshold accept
+/// virtually any ticket produced by the tests authorities.
+pub const TEST_EPOCH_CONFIGURATION: EpochConfiguration =
+	EpochConfiguration { redundancy_factor: 32, attempts_number: 32 };
+
+/// Build and returns test storage externalities.
+pub fn new_test_ext(authorities_len: usize) -> sp_io::TestExternalities {
+	new_test_ext_with_pairs(authorities_len, false).1
+}
+
+/// Build and returns test storage externalities and authority set pairs used
+/// by Sassafras genesis configuration.
+pub fn new_test_ext_with_pairs(
+	authorities_len: usize,
+	with_ring_context: bool,
+) -> (Vec<AuthorityPair>, sp_io::TestExternalities) {
+	let pairs = (0..authorities_len)
+		.map(|i| AuthorityPair::from_seed(&[i as u8; 32]))
+		.collect::<Vec<_>>();
+
+	let authorities: Vec<_> = pairs.iter().map(|p| p.public()).collect();
+
+	let mut storage = frame_system::GenesisConfig::<Test>::default().build_storage().unwrap();
+
+	pallet_sassafras::GenesisConfig::<Test> {
+		authorities: authorities.clone(),
+		epoch_config: TEST_EPOCH_CONFIGURATION,
+		_phantom: core::marker::PhantomData,
+	}
+	.assimilate_storage(&mut storage)
+	.unwrap();
+
+	let mut ext: sp_io::TestExternalities = storage.into();
+
+	if with_ring_context {
+		ext.execute_with(|| {
+			log::debug!(target: LOG_TARGET, "Building testing ring context");
+			let ring_ctx = vrf::RingContext::new_testing();
+			pallet_sassafras::RingContext::<Test>::set(Some(ring_ctx));
+		});
+	}
+
+	(pairs, ext)
+}
+
+/// Construct the next epoch tickets for the given authority.
+///
+/// The tickets are produced using the next epoch randomness and authorities, as the
+/// pallet expects during the tickets submission period.
+pub fn make_tickets(attempts: u32, pair: &AuthorityPair) -> Vec<TicketEnvelope> {
+	let ring_ctx = Sassafras::ring_context().expect("ring context is initialized; qed");
+	let ring_members: Vec<_> = Sassafras::next_authorities()
+		.iter()
+		.map(|authority| authority.clone().into_inner())
+		.collect();
+	let prover_idx = Sassafras::next_authorities()
+		.iter()
+		.position(|authority| *authority == pair.public())
+		.expect("authority is part of the next epoch authorities; qed");
+	let prover = ring_ctx.prover(&ring_members, prover_idx).expect("valid ring members; qed");
+
+	let randomness = Sassafras::next_randomness();
+	let epoch_idx = Sassafras::epoch_index() + 1;
+
+	(0..attempts)
+		.map(|attempt_idx| {
+			let ticket_id_input = vrf::ticket_id_input(&randomness, attempt_idx, epoch_idx);
+			let body = TicketBody {
+				attempt_idx,
+				erased_public: EphemeralPublic::from_raw([attempt_idx as u8; 32]),
+				revealed_public: EphemeralPublic::from_raw([!attempt_idx as u8; 32]),
+			};
+			let sign_data = vrf::ticket_body_sign_data(&body, ticket_id_input);
+			let signature = pair.as_ref().ring_vrf_sign(&sign_data, &prover);
+			TicketEnvelope { body, signature }
+		})
+		.collect()
+}
+
+/// Fake tickets with the given identifiers, bypassing the ring signature verification.
+pub fn append_fake_tickets(ticket_ids: impl IntoIterator<Item = TicketId>) {
+	let tickets = ticket_ids
+		.into_iter()
+		.map(|ticket_id| {
+			let body = TicketBody {
+				attempt_idx: 0,
+				erased_public: EphemeralPublic::from_raw([0; 32]),
+				revealed_public: EphemeralPublic::from_raw([0; 32]),
+			};
+			(ticket_id, body)
+		})
+		.collect();
+	Sassafras::append_tickets(tickets);
+}
+
+fn slot_claim_vrf_signature(slot: Slot, pair: &AuthorityPair) -> VrfSignature {
+	let mut epoch_idx = Sassafras::epoch_index();
+	let mut randomness = Sassafras::randomness();
+
+	// Check if epoch is going to change on initialization.
+	let epoch_start = *Sassafras::current_epoch_start();
+	if epoch_start != 0 && *slot >= epoch_start + EPOCH_LENGTH as u64 {
+		epoch_idx += (*slot - epoch_start) / EPOCH_LENGTH as u64;
+		randomness = Sassafras::next_randomness();
+	}
+
+	let data = vrf::slot_claim_sign_data(&randomness, slot, epoch_idx);
+	pair.as_ref().vrf_sign(&data)
+}
+
+/// Construct a `SlotClaim` instance for the given parameters.
+pub fn make_slot_claim(
+	authority_idx: AuthorityIndex,
+	slot: Slot,
+	pair: &AuthorityPair,
+) -> SlotClaim {
+	let vrf_signature = slot_claim_vrf_signature(slot, pair);
+	SlotClaim { authority_idx, slot, vrf_signature, ticket_claim: None }
+}
+
+/// Construct a `Digest` with a `SlotClaim` item.
+pub fn make_digest(authority_idx: AuthorityIndex, slot: Slot, pair: &AuthorityPair) -> Digest {
+	let claim = make_slot_claim(authority_idx, slot, pair);
+	Digest { logs: vec![DigestItem::from(&claim)] }
+}
+
+/// Initialize the block with the given number and slot.
+pub fn initialize_block(
+	number: u64,
+	slot: Slot,
+	parent_hash: H256,
+	pair: &AuthorityPair,
+) -> Digest {
+	let digest = make_digest(0, slot, pair);
+	System::reset_events();
+	System::initialize(&number, &parent_hash, &digest);
+	Sassafras::on_initialize(number);
+	digest
+}
+
+/// Finalize the current block.
+pub fn finalize_block(number: u64) -> HeaderFor<Test> {
+	Sassafras::on_finalize(number);
+	System::finalize()
+}
+
+/// Progress the pallet state up to the given block `number`, one block per slot.
+///
+/// Returns the last produced block header.
+pub fn progress_to_block(number: u64, pair: &AuthorityPair) -> Option<HeaderFor<Test>> {
+	let mut slot = u64::from(Sassafras::current_slot()) + 1;
+	let mut header = None;
+	for i in System::block_number() + 1..=number {
+		let parent_hash =
+			header.as_ref().map_or_else(System::parent_hash, |h: &HeaderFor<Test>| h.hash());
+		initialize_block(i, slot.into(), parent_hash, pair);
+		header = Some(finalize_block(i));
+		slot += 1;
+	}
+	header
+}
+
+/// Creates an equivocation by generating two sealed headers for the same slot.
+pub fn generate_equivocation_proof(
+	offender_authority_idx: AuthorityIndex,
+	offender_authority_pair: &AuthorityPair,
+	slot: Slot,
+) -> EquivocationProof<HeaderFor<Test>> {
+	let make_header = |number: u64| {
+		let digest = make_digest(offender_authority_idx, slot, offender_authority_pair);
+		let mut header = HeaderFor::<Test>::new(
+			number,
+			Default::default(),
+			Default::default(),
+			System::parent_hash(),
+			digest,
+		);
+		let seal = offender_authority_pair.sign(header.hash().as_ref());
+		header.digest_mut().push(DigestItem::from(&seal));
+		header
+	};
+
+	let current_block = System::block_number();
+	EquivocationProof {
+		slot,
+		offender: offender_authority_pair.public(),
+		first_header: make_header(current_block),
+		second_header: make_header(current_block + 1),
+	}
+}
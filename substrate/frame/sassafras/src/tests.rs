@@ -0,0 +1,379 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tests for Sassafras pallet.
+
+use crate::*;
+use mock::*;
+
+use frame_support::{assert_noop, assert_ok};
+use sp_consensus_sassafras::Slot;
+use sp_runtime::{traits::Header as _, DispatchError};
+
+fn genesis_slot() -> Slot {
+	Slot::from(100)
+}
+
+fn next_epoch_descriptor(header: &HeaderFor<Test>) -> Option<NextEpochDescriptor> {
+	header.digest().logs().iter().find_map(|item| {
+		item.consensus_try_to(&SASSAFRAS_ENGINE_ID).and_then(|log| match log {
+			ConsensusLog::NextEpochData(descriptor) => Some(descriptor),
+			_ => None,
+		})
+	})
+}
+
+#[test]
+fn genesis_values_assumptions_check() {
+	new_test_ext(4).execute_with(|| {
+		assert_eq!(Sassafras::authorities().len(), 4);
+		assert_eq!(Sassafras::next_authorities(), Sassafras::authorities());
+		assert_eq!(Sassafras::genesis_slot(), Slot::from(0));
+		assert_eq!(Sassafras::config(), TEST_EPOCH_CONFIGURATION);
+		assert_eq!(Sassafras::epoch_index(), 0);
+	});
+}
+
+#[test]
+fn on_first_block_after_genesis() {
+	let (pairs, mut ext) = new_test_ext_with_pairs(4, false);
+
+	ext.execute_with(|| {
+		let start_block = 1;
+		let digest = initialize_block(start_block, genesis_slot(), Default::default(), &pairs[0]);
+
+		// Post-initialization status
+
+		assert!(ClaimTemporaryData::<Test>::exists());
+		assert_eq!(Sassafras::genesis_slot(), genesis_slot());
+		assert_eq!(Sassafras::current_slot(), genesis_slot());
+		assert_eq!(Sassafras::epoch_index(), 0);
+		assert_eq!(Sassafras::current_epoch_start(), genesis_slot());
+		assert_eq!(RandomnessAccumulator::<Test>::get(), [0; 32]);
+
+		let header = finalize_block(start_block);
+
+		// Post-finalization status
+
+		assert!(!ClaimTemporaryData::<Test>::exists());
+		assert_ne!(RandomnessAccumulator::<Test>::get(), [0; 32]);
+
+		// Header data check
+
+		assert_eq!(header.digest.logs.len(), 2);
+		assert_eq!(header.digest.logs[0], digest.logs[0]);
+
+		// Genesis epoch start deposits consensus
+		let descriptor = next_epoch_descriptor(&header).unwrap();
+		assert_eq!(
+			descriptor,
+			NextEpochDescriptor {
+				authorities: Sassafras::next_authorities().into_inner(),
+				randomness: Sassafras::next_randomness(),
+				config: None,
+			}
+		);
+	})
+}
+
+#[test]
+fn produce_epoch_change_digest_with_config() {
+	let (pairs, mut ext) = new_test_ext_with_pairs(4, false);
+
+	ext.execute_with(|| {
+		initialize_block(1, genesis_slot(), Default::default(), &pairs[0]);
+		finalize_block(1);
+
+		let invalid_config = EpochConfiguration { redundancy_factor: 0, attempts_number: 3 };
+		assert_noop!(
+			Sassafras::plan_config_change(RuntimeOrigin::root(), invalid_config),
+			Error::<Test>::InvalidConfiguration,
+		);
+
+		let config = EpochConfiguration { redundancy_factor: 1, attempts_number: 3 };
+		assert_ok!(Sassafras::plan_config_change(RuntimeOrigin::root(), config));
+
+		// We want to trigger an epoch change in this test.
+		let epoch_length = EPOCH_LENGTH as u64;
+		let header = progress_to_block(1 + epoch_length, &pairs[0]).unwrap();
+
+		assert_eq!(Sassafras::epoch_index(), 1);
+		assert_eq!(Sassafras::current_epoch_start(), genesis_slot() + epoch_length);
+
+		// The configuration change is announced together with the next epoch data,
+		// it will be enacted one epoch later.
+		let descriptor = next_epoch_descriptor(&header).unwrap();
+		assert_eq!(descriptor.config, Some(config));
+		assert_eq!(Sassafras::config(), TEST_EPOCH_CONFIGURATION);
+		assert_eq!(Sassafras::next_epoch().config, config);
+
+		let header = progress_to_block(1 + 2 * epoch_length, &pairs[0]).unwrap();
+		assert_eq!(Sassafras::epoch_index(), 2);
+		assert_eq!(Sassafras::config(), config);
+		assert_eq!(next_epoch_descriptor(&header).unwrap().config, None);
+	})
+}
+
+#[test]
+fn epoch_change_rotates_randomness() {
+	let (pairs, mut ext) = new_test_ext_with_pairs(4, false);
+
+	ext.execute_with(|| {
+		let epoch_length = EPOCH_LENGTH as u64;
+		initialize_block(1, genesis_slot(), Default::default(), &pairs[0]);
+		finalize_block(1);
+		progress_to_block(epoch_length, &pairs[0]);
+
+		assert_eq!(Sassafras::epoch_index(), 0);
+		let next_randomness = Sassafras::next_randomness();
+		let accumulator = RandomnessAccumulator::<Test>::get();
+
+		let header = progress_to_block(epoch_length + 1, &pairs[0]).unwrap();
+
+		assert_eq!(Sassafras::epoch_index(), 1);
+		assert_eq!(Sassafras::randomness(), next_randomness);
+
+		let mut buf = [0; RANDOMNESS_LENGTH + 8];
+		buf[..RANDOMNESS_LENGTH].copy_from_slice(&accumulator);
+		buf[RANDOMNESS_LENGTH..].copy_from_slice(&2u64.to_le_bytes());
+		assert_eq!(Sassafras::next_randomness(), sp_io::hashing::blake2_256(&buf));
+
+		let descriptor = next_epoch_descriptor(&header).unwrap();
+		assert_eq!(descriptor.randomness, Sassafras::next_randomness());
+		assert_eq!(descriptor.authorities, Sassafras::next_authorities().into_inner());
+	})
+}
+
+#[test]
+fn slot_ticket_id_outside_in_fetch() {
+	let genesis_slot = genesis_slot();
+	let tickets_count = 6;
+
+	// Current epoch tickets
+	let curr_tickets: Vec<TicketId> = (0..tickets_count).map(|i| i as TicketId).collect();
+
+	// Next epoch tickets
+	let next_tickets: Vec<TicketId> =
+		(0..tickets_count - 1).map(|i| (i + tickets_count) as TicketId).collect();
+
+	new_test_ext(4).execute_with(|| {
+		curr_tickets
+			.iter()
+			.enumerate()
+			.for_each(|(i, id)| TicketsIds::<Test>::insert((0, i as u32), id));
+
+		next_tickets
+			.iter()
+			.enumerate()
+			.for_each(|(i, id)| TicketsIds::<Test>::insert((1, i as u32), id));
+
+		TicketsMeta::<Test>::set(TicketsMetadata {
+			tickets_count: [curr_tickets.len() as u32, next_tickets.len() as u32],
+			unsorted_tickets_count: 0,
+		});
+
+		// Initialize genesis slot.
+		GenesisSlot::<Test>::set(genesis_slot);
+
+		// Try to fetch a ticket for a slot before current epoch.
+		assert_eq!(Sassafras::slot_ticket_id(0.into()), None);
+
+		// Current epoch tickets.
+		assert_eq!(Sassafras::slot_ticket_id(genesis_slot + 0), Some(curr_tickets[1]));
+		assert_eq!(Sassafras::slot_ticket_id(genesis_slot + 1), Some(curr_tickets[3]));
+		assert_eq!(Sassafras::slot_ticket_id(genesis_slot + 2), Some(curr_tickets[5]));
+		assert_eq!(Sassafras::slot_ticket_id(genesis_slot + 3), None);
+		assert_eq!(Sassafras::slot_ticket_id(genesis_slot + 4), None);
+		assert_eq!(Sassafras::slot_ticket_id(genesis_slot + 5), None);
+		assert_eq!(Sassafras::slot_ticket_id(genesis_slot + 6), None);
+		assert_eq!(Sassafras::slot_ticket_id(genesis_slot + 7), Some(curr_tickets[4]));
+		assert_eq!(Sassafras::slot_ticket_id(genesis_slot + 8), Some(curr_tickets[2]));
+		assert_eq!(Sassafras::slot_ticket_id(genesis_slot + 9), Some(curr_tickets[0]));
+
+		// Next epoch tickets (note that only 5 tickets are available)
+		assert_eq!(Sassafras::slot_ticket_id(genesis_slot + 10), Some(next_tickets[1]));
+		assert_eq!(Sassafras::slot_ticket_id(genesis_slot + 11), Some(next_tickets[3]));
+		assert_eq!(Sassafras::slot_ticket_id(genesis_slot + 12), None);
+		assert_eq!(Sassafras::slot_ticket_id(genesis_slot + 13), None);
+		assert_eq!(Sassafras::slot_ticket_id(genesis_slot + 14), None);
+		assert_eq!(Sassafras::slot_ticket_id(genesis_slot + 15), None);
+		assert_eq!(Sassafras::slot_ticket_id(genesis_slot + 16), None);
+		assert_eq!(Sassafras::slot_ticket_id(genesis_slot + 17), Some(next_tickets[4]));
+		assert_eq!(Sassafras::slot_ticket_id(genesis_slot + 18), Some(next_tickets[2]));
+		assert_eq!(Sassafras::slot_ticket_id(genesis_slot + 19), Some(next_tickets[0]));
+
+		// Try to fetch the tickets for slots beyond the next epoch.
+		assert_eq!(Sassafras::slot_ticket_id(genesis_slot + 20), None);
+		assert_eq!(Sassafras::slot_ticket_id(genesis_slot + 42), None);
+	});
+}
+
+#[test]
+fn incremental_sortition_works() {
+	let (pairs, mut ext) = new_test_ext_with_pairs(4, false);
+
+	ext.execute_with(|| {
+		let epoch_length = EPOCH_LENGTH as u64;
+		initialize_block(1, genesis_slot(), Default::default(), &pairs[0]);
+		finalize_block(1);
+
+		// Three full segments plus a partial one, inserted in reverse order.
+		let tickets_count = 3 * SEGMENT_MAX_SIZE + 5;
+		append_fake_tickets((0..tickets_count as TicketId).rev());
+
+		let meta = TicketsMeta::<Test>::get();
+		assert_eq!(meta.unsorted_tickets_count, tickets_count);
+		assert_eq!(UnsortedSegments::<Test>::get(3).len(), 5);
+		assert_eq!(TicketsData::<Test>::iter().count(), tickets_count as usize);
+
+		// Nothing is sorted during the first half of the epoch.
+		progress_to_block(epoch_length / 2, &pairs[0]);
+		assert_eq!(TicketsMeta::<Test>::get().unsorted_tickets_count, tickets_count);
+
+		// One segment per block is sorted during the second half.
+		progress_to_block(epoch_length / 2 + 1, &pairs[0]);
+		let meta = TicketsMeta::<Test>::get();
+		assert_eq!(meta.unsorted_tickets_count, 3 * SEGMENT_MAX_SIZE);
+		assert_eq!(SortedCandidates::<Test>::get().len(), 5);
+
+		progress_to_block(epoch_length / 2 + 4, &pairs[0]);
+		let meta = TicketsMeta::<Test>::get();
+		assert_eq!(meta.unsorted_tickets_count, 0);
+		assert_eq!(meta.tickets_count, [0, EPOCH_LENGTH]);
+		assert!(SortedCandidates::<Test>::get().is_empty());
+
+		// Only the best tickets have been retained.
+		assert_eq!(TicketsData::<Test>::iter().count(), EPOCH_LENGTH as usize);
+		(0..EPOCH_LENGTH).for_each(|i| {
+			assert_eq!(TicketsIds::<Test>::get((1, i)), Some(i as TicketId));
+		});
+
+		// The next epoch tickets are used once the epoch is enacted, the ones belonging
+		// to the epoch which has just ended are removed.
+		progress_to_block(epoch_length + 1, &pairs[0]);
+		assert_eq!(Sassafras::epoch_index(), 1);
+		assert_eq!(TicketsMeta::<Test>::get().tickets_count, [0, EPOCH_LENGTH]);
+		assert_eq!(
+			Sassafras::slot_ticket_id(Sassafras::current_epoch_start()),
+			Some(1 as TicketId)
+		);
+	})
+}
+
+#[test]
+fn tickets_are_dropped_on_skipped_epochs() {
+	let (pairs, mut ext) = new_test_ext_with_pairs(4, false);
+
+	ext.execute_with(|| {
+		let epoch_length = EPOCH_LENGTH as u64;
+		initialize_block(1, genesis_slot(), Default::default(), &pairs[0]);
+		let header = finalize_block(1);
+
+		append_fake_tickets(0..10);
+		assert_eq!(TicketsMeta::<Test>::get().unsorted_tickets_count, 10);
+
+		// Jump ahead of two epochs.
+		let slot = genesis_slot() + 2 * epoch_length + 1;
+		initialize_block(2, slot, header.hash(), &pairs[0]);
+		finalize_block(2);
+
+		assert_eq!(Sassafras::epoch_index(), 2);
+		assert_eq!(TicketsMeta::<Test>::get(), TicketsMetadata::default());
+		assert_eq!(TicketsData::<Test>::iter().count(), 0);
+	})
+}
+
+#[test]
+fn submit_tickets_with_ring_proof_check_works() {
+	let (pairs, mut ext) = new_test_ext_with_pairs(3, false);
+
+	ext.execute_with(|| {
+		let epoch_length = EPOCH_LENGTH as u64;
+		initialize_block(1, genesis_slot(), Default::default(), &pairs[0]);
+		finalize_block(1);
+
+		let bounded = |tickets: Vec<TicketEnvelope>| BoundedVec::truncate_from(tickets);
+
+		// No ticket is accepted until the ring context is set by governance.
+		assert_noop!(
+			Sassafras::submit_tickets(RuntimeOrigin::none(), bounded(vec![])),
+			Error::<Test>::RingVerifierUnavailable,
+		);
+		let ring_ctx = Box::new(vrf::RingContext::new_testing());
+		assert_noop!(
+			Sassafras::set_ring_context(RuntimeOrigin::signed(1), ring_ctx.clone()),
+			DispatchError::BadOrigin,
+		);
+		assert_ok!(Sassafras::set_ring_context(RuntimeOrigin::root(), ring_ctx));
+
+		let tickets = make_tickets(2, &pairs[1]);
+
+		// A tampered ticket is rejected.
+		let mut invalid = tickets[0].clone();
+		invalid.body.attempt_idx = 1;
+		assert_noop!(
+			Sassafras::submit_tickets(RuntimeOrigin::none(), bounded(vec![invalid])),
+			Error::<Test>::TicketInvalid,
+		);
+
+		assert_ok!(Sassafras::submit_tickets(RuntimeOrigin::none(), bounded(tickets.clone())));
+		assert_eq!(TicketsMeta::<Test>::get().unsorted_tickets_count, 2);
+
+		// Resubmission is not allowed.
+		assert_noop!(
+			Sassafras::submit_tickets(RuntimeOrigin::none(), bounded(tickets[..1].to_vec())),
+			Error::<Test>::TicketDuplicate,
+		);
+
+		// Submission closes at half epoch.
+		progress_to_block(epoch_length / 2 + 1, &pairs[0]);
+		let tickets = make_tickets(3, &pairs[2]);
+		assert_noop!(
+			Sassafras::submit_tickets(RuntimeOrigin::none(), bounded(tickets)),
+			Error::<Test>::TicketsSubmissionClosed,
+		);
+	})
+}
+
+#[test]
+fn check_equivocation_proof_works() {
+	let (pairs, mut ext) = new_test_ext_with_pairs(3, false);
+
+	ext.execute_with(|| {
+		initialize_block(1, genesis_slot(), Default::default(), &pairs[0]);
+		finalize_block(1);
+
+		let slot = genesis_slot() + 1;
+		let proof = generate_equivocation_proof(1, &pairs[1], slot);
+		assert!(sp_consensus_sassafras::check_equivocation_proof(proof.clone()));
+
+		// Same header twice.
+		let mut invalid = proof.clone();
+		invalid.second_header = invalid.first_header.clone();
+		assert!(!sp_consensus_sassafras::check_equivocation_proof(invalid));
+
+		// Slot mismatch.
+		let mut invalid = proof.clone();
+		invalid.slot = slot + 1;
+		assert!(!sp_consensus_sassafras::check_equivocation_proof(invalid));
+
+		// Wrong offender.
+		let mut invalid = proof;
+		invalid.offender = pairs[2].public();
+		assert!(!sp_consensus_sassafras::check_equivocation_proof(invalid));
+	})
+}
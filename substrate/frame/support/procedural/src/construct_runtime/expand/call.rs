@@ -178,9 +178,14 @@ pub fn expand_outer_dispatch(
 			type PostInfo = #scrate::dispatch::PostDispatchInfo;
 			fn dispatch(self, origin: RuntimeOrigin) -> #scrate::dispatch::DispatchResultWithPostInfo {
 				if !<Self::RuntimeOrigin as #scrate::traits::OriginTrait>::filter_call(&origin, &self) {
-					return #scrate::__private::sp_std::result::Result::Err(
+					let filtered = || #scrate::__private::sp_std::result::Result::Err(
 						#system_path::Error::<#runtime>::CallFiltered.into()
 					);
+					// A filtered call is still charged its declared weight.
+					return match #scrate::dispatch_context::metered_dispatch_info(&self) {
+						Some(info) => #scrate::dispatch_context::dispatch_metered(&info, filtered),
+						None => filtered(),
+					};
 				}
 
 				#scrate::traits::UnfilteredDispatchable::dispatch_bypass_filter(self, origin)
@@ -189,12 +194,17 @@ pub fn expand_outer_dispatch(
 		impl #scrate::traits::UnfilteredDispatchable for RuntimeCall {
 			type RuntimeOrigin = RuntimeOrigin;
 			fn dispatch_bypass_filter(self, origin: RuntimeOrigin) -> #scrate::dispatch::DispatchResultWithPostInfo {
-				match self {
+				let info = #scrate::dispatch_context::metered_dispatch_info(&self);
+				let dispatch = || match self {
 					#(
 						#pallet_attrs
 						#variant_patterns =>
 							#scrate::traits::UnfilteredDispatchable::dispatch_bypass_filter(call, origin),
 					)*
+				};
+				match info {
+					Some(info) => #scrate::dispatch_context::dispatch_metered(&info, dispatch),
+					None => dispatch(),
 				}
 			}
 		}
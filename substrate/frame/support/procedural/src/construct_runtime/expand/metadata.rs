@@ -131,6 +131,22 @@ pub fn expand_runtime_metadata(
 									additional_signed: meta.additional_signed,
 								})
 								.collect(),
+						extension_versions: <
+								#extrinsic as #scrate::sp_runtime::traits::ExtrinsicMetadata
+							>::extension_versions()
+								.into_iter()
+								.map(|version| #scrate::__private::metadata_ir::ExtensionVersionMetadataIR {
+									version: version.version,
+									extensions: version.extensions
+										.into_iter()
+										.map(|meta| #scrate::__private::metadata_ir::SignedExtensionMetadataIR {
+											identifier: meta.identifier,
+											ty: meta.ty,
+											additional_signed: meta.additional_signed,
+										})
+										.collect(),
+								})
+								.collect(),
 					},
 					ty: #scrate::__private::scale_info::meta_type::<#runtime>(),
 					apis: (&rt).runtime_metadata(),
@@ -301,6 +301,18 @@ pub fn expand_outer_origin(
 			}
 		}
 
+		impl #scrate::__private::AsSystemOriginSigner<<#runtime as #system_path::Config>::AccountId> for RuntimeOrigin {
+			fn as_system_origin_signer(&self) -> Option<&<#runtime as #system_path::Config>::AccountId> {
+				<OriginCaller as #scrate::traits::CallerTrait<<#runtime as #system_path::Config>::AccountId>>::as_signed(&self.caller)
+			}
+		}
+
+		impl #scrate::__private::AsTransactionAuthorizedOrigin for RuntimeOrigin {
+			fn is_transaction_authorized(&self) -> bool {
+				!<OriginCaller as #scrate::traits::CallerTrait<<#runtime as #system_path::Config>::AccountId>>::is_none(&self.caller)
+			}
+		}
+
 		#pallet_conversions
 	})
 }
@@ -76,6 +76,12 @@ fn counter_prefix(prefix: &str) -> String {
 	format!("CounterFor{}", prefix)
 }
 
+/// Generate the page_meta_prefix related to the storage.
+/// page_meta_prefix is used by paged storage map.
+fn page_meta_prefix(prefix: &str) -> String {
+	format!("PageMetaFor{}", prefix)
+}
+
 /// Construct a runtime, with the given name and the given pallets.
 ///
 /// The parameters here are specific types for `Block`, `NodeBlock`, and `UncheckedExtrinsic`
@@ -16,7 +16,7 @@
 // limitations under the License.
 
 use crate::{
-	counter_prefix,
+	counter_prefix, page_meta_prefix,
 	pallet::{
 		parse::{
 			helper::two128_str,
@@ -45,6 +45,15 @@ fn counter_prefix_ident(storage_ident: &syn::Ident) -> syn::Ident {
 	)
 }
 
+/// Generate the page_meta_prefix_ident related to the storage.
+/// page_meta_prefix_ident is used for the prefix struct to be given to paged storage map.
+fn page_meta_prefix_ident(storage_ident: &syn::Ident) -> syn::Ident {
+	syn::Ident::new(
+		&format!("_GeneratedPageMetaPrefixForStorage{}", storage_ident),
+		storage_ident.span(),
+	)
+}
+
 /// Check for duplicated storage prefixes. This step is necessary since users can specify an
 /// alternative storage prefix using the #[pallet::storage_prefix] syntax, and we need to ensure
 /// that the prefix specified by the user is not a duplicate of an existing one.
@@ -83,6 +92,26 @@ fn check_prefix_duplicates(
 		}
 	}
 
+	if let Metadata::PagedMap { .. } = storage_def.metadata {
+		let page_meta_prefix = page_meta_prefix(&prefix);
+		let page_meta_dup_err = syn::Error::new(
+			storage_def.prefix_span(),
+			format!(
+				"Duplicate storage prefixes found for `{}`, used for page bookkeeping associated \
+				to paged storage map",
+				page_meta_prefix,
+			),
+		);
+
+		if let Some(other_dup_err) =
+			used_prefixes.insert(page_meta_prefix, page_meta_dup_err.clone())
+		{
+			let mut err = page_meta_dup_err;
+			err.combine(other_dup_err);
+			return Err(err)
+		}
+	}
+
 	Ok(())
 }
 
@@ -257,20 +286,42 @@ pub fn process_generics(def: &mut Def) -> syn::Result<Vec<ResultOnEmptyStructMet
 					let max_values = max_values.unwrap_or_else(|| default_max_values.clone());
 					args.args.push(syn::GenericArgument::Type(max_values));
 				},
+				StorageGenerics::PagedMap {
+					hasher,
+					key,
+					value,
+					values_per_page,
+					max_pages,
+					max_values,
+				} => {
+					args.args.push(syn::GenericArgument::Type(hasher));
+					args.args.push(syn::GenericArgument::Type(key));
+					args.args.push(syn::GenericArgument::Type(value));
+					args.args.push(syn::GenericArgument::Type(values_per_page));
+					let max_pages = max_pages.unwrap_or_else(|| default_max_values.clone());
+					args.args.push(syn::GenericArgument::Type(max_pages));
+					let max_values = max_values.unwrap_or_else(|| default_max_values.clone());
+					args.args.push(syn::GenericArgument::Type(max_values));
+				},
 			}
 		} else {
 			args.args[0] = syn::parse_quote!( #prefix_ident<#type_use_gen> );
 
-			let (value_idx, query_idx, on_empty_idx) = match storage_def.metadata {
-				Metadata::Value { .. } => (1, 2, 3),
-				Metadata::NMap { .. } | Metadata::CountedNMap { .. } => (2, 3, 4),
-				Metadata::Map { .. } | Metadata::CountedMap { .. } => (3, 4, 5),
-				Metadata::DoubleMap { .. } => (5, 6, 7),
+			// The positions of the value, query kind and on empty generics, a paged map has neither
+			// a query kind nor an on empty generic.
+			let query_generics = match storage_def.metadata {
+				Metadata::Value { .. } => Some((1, 2, 3)),
+				Metadata::NMap { .. } | Metadata::CountedNMap { .. } => Some((2, 3, 4)),
+				Metadata::Map { .. } | Metadata::CountedMap { .. } => Some((3, 4, 5)),
+				Metadata::DoubleMap { .. } => Some((5, 6, 7)),
+				Metadata::PagedMap { .. } => None,
 			};
 
 			if storage_def.use_default_hasher {
 				let hasher_indices: Vec<usize> = match storage_def.metadata {
-					Metadata::Map { .. } | Metadata::CountedMap { .. } => vec![1],
+					Metadata::Map { .. } |
+					Metadata::CountedMap { .. } |
+					Metadata::PagedMap { .. } => vec![1],
 					Metadata::DoubleMap { .. } => vec![1, 3],
 					_ => vec![],
 				};
@@ -281,6 +332,8 @@ pub fn process_generics(def: &mut Def) -> syn::Result<Vec<ResultOnEmptyStructMet
 				}
 			}
 
+			let Some((value_idx, query_idx, on_empty_idx)) = query_generics else { continue };
+
 			if query_idx < args.args.len() {
 				if let syn::GenericArgument::Type(query_kind) = args.args.index_mut(query_idx) {
 					set_result_query_type_parameter(query_kind)?;
@@ -376,6 +429,14 @@ fn augment_final_docs(def: &mut Def) {
 			);
 			push_string_literal(&doc_line, storage);
 		},
+		Metadata::PagedMap { key, value } => {
+			let doc_line = format!(
+				"Storage type is [`StoragePagedMap`] with key type {} and value type {}.",
+				key.to_token_stream(),
+				value.to_token_stream()
+			);
+			push_string_literal(&doc_line, storage);
+		},
 	});
 }
 
@@ -621,6 +682,7 @@ pub fn expand_storages(def: &mut Def) -> proc_macro2::TokenStream {
 						}
 					)
 				},
+				Metadata::PagedMap { .. } => unreachable!("Checked by def"),
 			}
 		} else {
 			Default::default()
@@ -726,6 +788,49 @@ pub fn expand_storages(def: &mut Def) -> proc_macro2::TokenStream {
 					}
 				)
 			},
+			Metadata::PagedMap { .. } => {
+				let page_meta_prefix_struct_ident = page_meta_prefix_ident(&storage_def.ident);
+				let page_meta_prefix_struct_const = page_meta_prefix(&prefix_struct_const);
+				let storage_prefix_hash = two128_str(&page_meta_prefix_struct_const);
+				quote::quote_spanned!(storage_def.attr_span =>
+					#(#cfg_attrs)*
+					#[doc(hidden)]
+					#prefix_struct_vis struct #page_meta_prefix_struct_ident<#type_use_gen>(
+						core::marker::PhantomData<(#type_use_gen,)>
+					);
+					#(#cfg_attrs)*
+					impl<#type_impl_gen> #frame_support::traits::StorageInstance
+						for #page_meta_prefix_struct_ident<#type_use_gen>
+						#config_where_clause
+					{
+						fn pallet_prefix() -> &'static str {
+							<
+								<T as #frame_system::Config>::PalletInfo
+								as #frame_support::traits::PalletInfo
+							>::name::<Pallet<#type_use_gen>>()
+								.expect("No name found for the pallet in the runtime! This usually means that the pallet wasn't added to `construct_runtime!`.")
+						}
+						fn pallet_prefix_hash() -> [u8; 16] {
+							<
+								<T as #frame_system::Config>::PalletInfo
+								as #frame_support::traits::PalletInfo
+							>::name_hash::<Pallet<#type_use_gen>>()
+								.expect("No name_hash found for the pallet in the runtime! This usually means that the pallet wasn't added to `construct_runtime!`.")
+						}
+						const STORAGE_PREFIX: &'static str = #page_meta_prefix_struct_const;
+						fn storage_prefix_hash() -> [u8; 16] {
+							#storage_prefix_hash
+						}
+					}
+					#(#cfg_attrs)*
+					impl<#type_impl_gen> #frame_support::storage::types::StoragePagedMapInstance
+						for #prefix_struct_ident<#type_use_gen>
+						#config_where_clause
+					{
+						type MetaPrefix = #page_meta_prefix_struct_ident<#type_use_gen>;
+					}
+				)
+			},
 			_ => proc_macro2::TokenStream::default(),
 		};
 
@@ -885,6 +990,15 @@ pub fn expand_storages(def: &mut Def) -> proc_macro2::TokenStream {
 		)
 	};
 
+	let storage_version = match def.pallet_struct.storage_version.as_ref() {
+		Some(version) => quote::quote! {
+			core::option::Option::Some(
+				<#frame_support::traits::StorageVersion as core::convert::Into<u16>>::into(#version)
+			)
+		},
+		None => quote::quote! { core::option::Option::None },
+	};
+
 	quote::quote!(
 		impl<#type_impl_gen> #pallet_ident<#type_use_gen>
 			#completed_where_clause
@@ -903,6 +1017,7 @@ pub fn expand_storages(def: &mut Def) -> proc_macro2::TokenStream {
 						#( #entries_builder )*
 						entries
 					},
+					version: #storage_version,
 				}
 			}
 		}
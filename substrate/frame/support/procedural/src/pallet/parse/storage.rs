@@ -139,6 +139,7 @@ pub enum Metadata {
 	DoubleMap { value: syn::Type, key1: syn::Type, key2: syn::Type },
 	NMap { keys: Vec<syn::Type>, keygen: syn::Type, value: syn::Type },
 	CountedNMap { keys: Vec<syn::Type>, keygen: syn::Type, value: syn::Type },
+	PagedMap { value: syn::Type, key: syn::Type },
 }
 
 pub enum QueryKind {
@@ -238,6 +239,14 @@ pub enum StorageGenerics {
 		on_empty: Option<syn::Type>,
 		max_values: Option<syn::Type>,
 	},
+	PagedMap {
+		hasher: syn::Type,
+		key: syn::Type,
+		value: syn::Type,
+		values_per_page: syn::Type,
+		max_pages: Option<syn::Type>,
+		max_values: Option<syn::Type>,
+	},
 }
 
 impl StorageGenerics {
@@ -252,6 +261,7 @@ impl StorageGenerics {
 				Metadata::NMap { keys: collect_keys(&keygen)?, keygen, value },
 			Self::CountedNMap { keygen, value, .. } =>
 				Metadata::CountedNMap { keys: collect_keys(&keygen)?, keygen, value },
+			Self::PagedMap { value, key, .. } => Metadata::PagedMap { value, key },
 		};
 
 		Ok(res)
@@ -266,6 +276,7 @@ impl StorageGenerics {
 			Self::Value { query_kind, .. } |
 			Self::NMap { query_kind, .. } |
 			Self::CountedNMap { query_kind, .. } => query_kind.clone(),
+			Self::PagedMap { .. } => None,
 		}
 	}
 }
@@ -277,6 +288,7 @@ enum StorageKind {
 	DoubleMap,
 	NMap,
 	CountedNMap,
+	PagedMap,
 }
 
 /// Check the generics in the `map` contains the generics in `gen` may contains generics in
@@ -528,6 +540,44 @@ fn process_named_generics(
 				max_values: parsed.remove("MaxValues").map(|binding| binding.ty),
 			}
 		},
+		StorageKind::PagedMap => {
+			let mut paged_map_mandatory_generics = vec!["Key", "Value", "ValuesPerPage"];
+			let mut paged_map_optional_generics = vec!["MaxPages", "MaxValues"];
+			if dev_mode {
+				paged_map_optional_generics.push("Hasher");
+			} else {
+				paged_map_mandatory_generics.push("Hasher");
+			}
+
+			check_generics(
+				&parsed,
+				&paged_map_mandatory_generics,
+				&paged_map_optional_generics,
+				"StoragePagedMap",
+				args_span,
+			)?;
+
+			StorageGenerics::PagedMap {
+				hasher: parsed
+					.remove("Hasher")
+					.map(|binding| binding.ty)
+					.unwrap_or(syn::parse_quote!(Blake2_128Concat)),
+				key: parsed
+					.remove("Key")
+					.map(|binding| binding.ty)
+					.expect("checked above as mandatory generic"),
+				value: parsed
+					.remove("Value")
+					.map(|binding| binding.ty)
+					.expect("checked above as mandatory generic"),
+				values_per_page: parsed
+					.remove("ValuesPerPage")
+					.map(|binding| binding.ty)
+					.expect("checked above as mandatory generic"),
+				max_pages: parsed.remove("MaxPages").map(|binding| binding.ty),
+				max_values: parsed.remove("MaxValues").map(|binding| binding.ty),
+			}
+		},
 	};
 
 	let metadata = generics.metadata()?;
@@ -623,6 +673,16 @@ fn process_unnamed_generics(
 				false,
 			)
 		},
+		StorageKind::PagedMap => {
+			// Ensure `ValuesPerPage` is given, it has no default.
+			retrieve_arg(4)?;
+			(
+				None,
+				Metadata::PagedMap { key: retrieve_arg(2)?, value: retrieve_arg(3)? },
+				None,
+				use_default_hasher(1)?,
+			)
+		},
 	};
 
 	Ok(res)
@@ -640,11 +700,12 @@ fn process_generics(
 		"StorageDoubleMap" => StorageKind::DoubleMap,
 		"StorageNMap" => StorageKind::NMap,
 		"CountedStorageNMap" => StorageKind::CountedNMap,
+		"StoragePagedMap" => StorageKind::PagedMap,
 		found => {
 			let msg = format!(
 				"Invalid pallet::storage, expected ident: `StorageValue` or \
 				`StorageMap` or `CountedStorageMap` or `StorageDoubleMap` or `StorageNMap` or `CountedStorageNMap` \
-				in order to expand metadata, found `{}`.",
+				or `StoragePagedMap` in order to expand metadata, found `{}`.",
 				found,
 			);
 			return Err(syn::Error::new(segment.ident.span(), msg))
@@ -898,6 +959,12 @@ impl StorageDef {
 			.transpose()?
 			.unwrap_or(Some(QueryKind::OptionQuery));
 
+		if let (Metadata::PagedMap { .. }, Some(getter)) = (&metadata, getter.as_ref()) {
+			let msg = "Invalid pallet::storage, cannot generate getter for `StoragePagedMap`, use \
+				its `get` or `iter` functions instead.";
+			return Err(syn::Error::new(getter.span(), msg))
+		}
+
 		if let (None, Some(getter)) = (query_kind.as_ref(), getter.as_ref()) {
 			let msg = "Invalid pallet::storage, cannot generate getter because QueryKind is not \
 				identifiable. QueryKind must be `OptionQuery`, `ResultQuery`, `ValueQuery`, or default \
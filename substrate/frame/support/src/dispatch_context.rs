@@ -80,12 +80,29 @@
 //!
 //! In your pallet you will only have to use [`with_context`], because as described above
 //! [`run_in_context`] will be handled by FRAME for you.
+//!
+//! # Nested dispatch weight
+//!
+//! The dispatch context also meters the weight of calls dispatched from within other calls, e.g.
+//! by `pallet-utility`, `pallet-proxy` or `pallet-multisig`. The executive applies every
+//! extrinsic through [`with_dispatch_info`], and every `RuntimeCall` dispatched from within it is
+//! dispatched through [`dispatch_metered`], which records the declared and actual weight of the
+//! call for the call that dispatched it. Once a call finished, the weight its nested calls did not
+//! use is refunded from its own actual weight. Pallets dispatching nested calls thus report an
+//! accurate actual weight without summing up the weight of their nested calls by themselves, or
+//! use [`measure_nested`] to learn the weight of the calls they dispatched.
+//!
+//! The refund only applies if the declared weight of a call covers the declared weight of all the
+//! calls it dispatches, which is required for a correct weight annotation anyway.
 
+use crate::dispatch::{DispatchInfo, DispatchResultWithPostInfo, GetDispatchInfo};
 use sp_std::{
 	any::{Any, TypeId},
 	boxed::Box,
 	collections::btree_map::{BTreeMap, Entry},
+	vec::Vec,
 };
+use sp_weights::Weight;
 
 environmental::environmental!(DISPATCH_CONTEXT: BTreeMap<TypeId, Box<dyn Any>>);
 
@@ -196,9 +213,143 @@ pub fn run_in_context<R>(run: impl FnOnce() -> R) -> R {
 	DISPATCH_CONTEXT::using_once(&mut Default::default(), run)
 }
 
+/// The weight of the calls dispatched from within a call.
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct NestedWeight {
+	/// The sum of the declared weight of the nested calls.
+	pub declared: Weight,
+	/// The sum of the actual weight of the nested calls.
+	pub actual: Weight,
+}
+
+impl NestedWeight {
+	/// The weight that was declared, but not used by the nested calls.
+	pub fn unused(&self) -> Weight {
+		self.declared.saturating_sub(self.actual)
+	}
+}
+
+/// One [`NestedWeight`] for each call currently being metered, innermost last.
+#[derive(Default)]
+struct NestedWeightStack(Vec<NestedWeight>);
+
+/// The dispatch info given to [`with_dispatch_info`], until its call is dispatched.
+#[derive(Default)]
+struct PendingDispatchInfo(Option<DispatchInfo>);
+
+/// Start metering the calls dispatched from now on.
+fn push_nested() {
+	with_context::<NestedWeightStack, _>(|s| s.or_default().0.push(NestedWeight::default()));
+}
+
+/// Stop metering the calls dispatched from now on and return the weight they used.
+fn pop_nested() -> NestedWeight {
+	with_context::<NestedWeightStack, _>(|s| s.or_default().0.pop())
+		.flatten()
+		.unwrap_or_default()
+}
+
+/// Account the given weight of dispatched calls for the innermost metered call, if any.
+fn accrue_nested(declared: Weight, actual: Weight) {
+	with_context::<NestedWeightStack, _>(|s| {
+		if let Some(parent) = s.or_default().0.last_mut() {
+			parent.declared.saturating_accrue(declared);
+			parent.actual.saturating_accrue(actual);
+		}
+	});
+}
+
+/// Dispatch a call with the given `info` by running `dispatch` and meter its weight.
+///
+/// The actual weight reported by the call is lowered by the weight its nested calls declared, but
+/// did not use. The resulting actual weight is then charged to the call that dispatched this one,
+/// if any.
+///
+/// Calls which declare less weight than their nested calls, e.g. because the weight is given
+/// explicitly like for `pallet_utility::with_weight`, are not refunded.
+///
+/// FRAME calls this for every `RuntimeCall` that [`metered_dispatch_info`] returns some info for,
+/// so pallets do not have to.
+pub fn dispatch_metered(
+	info: &DispatchInfo,
+	dispatch: impl FnOnce() -> DispatchResultWithPostInfo,
+) -> DispatchResultWithPostInfo {
+	run_in_context(|| {
+		push_nested();
+		let mut result = dispatch();
+		let nested = pop_nested();
+
+		let post_info = match &mut result {
+			Ok(post_info) => post_info,
+			Err(err) => &mut err.post_info,
+		};
+		if nested.unused() != Weight::zero() && info.weight.all_gte(nested.declared) {
+			let actual = post_info
+				.calc_actual_weight(info)
+				.min(info.weight.saturating_sub(nested.unused()));
+			post_info.actual_weight = Some(actual);
+		}
+
+		accrue_nested(info.weight, post_info.calc_actual_weight(info));
+		result
+	})
+}
+
+/// Run `run`, which dispatches a call with the given `info`, e.g. applies an extrinsic.
+///
+/// The first call dispatched by `run` is metered with `info`, so its dispatch info is not computed
+/// a second time.
+pub fn with_dispatch_info<R>(info: &DispatchInfo, run: impl FnOnce() -> R) -> R {
+	run_in_context(|| {
+		with_context::<PendingDispatchInfo, _>(|p| p.set(PendingDispatchInfo(Some(*info))));
+		let result = run();
+		with_context::<PendingDispatchInfo, _>(|p| p.clear());
+		result
+	})
+}
+
+/// Returns the dispatch info to meter the dispatch of `call` with, or `None` if it is not metered.
+///
+/// A call is metered if it is dispatched from within a metered call or [`measure_nested`], or if
+/// it is the call [`with_dispatch_info`] was given the info of. Only the former requires to compute
+/// the dispatch info of `call`.
+pub fn metered_dispatch_info(call: &impl GetDispatchInfo) -> Option<DispatchInfo> {
+	let pending =
+		with_context::<PendingDispatchInfo, _>(|p| p.get_mut().and_then(|p| p.0.take())).flatten();
+	pending.or_else(|| {
+		let nested =
+			with_context::<NestedWeightStack, _>(|s| s.get().map_or(false, |s| !s.0.is_empty()))
+				.unwrap_or(false);
+		nested.then(|| call.get_dispatch_info())
+	})
+}
+
+/// Run `run` and return the weight of the calls it dispatched.
+///
+/// The calls are still accounted for the enclosing metered call. This allows pallets dispatching
+/// calls to report their actual weight without computing the weight of each call by themselves.
+pub fn measure_nested<R>(run: impl FnOnce() -> R) -> (R, NestedWeight) {
+	run_in_context(|| {
+		push_nested();
+		let result = run();
+		let nested = pop_nested();
+
+		accrue_nested(nested.declared, nested.actual);
+		(result, nested)
+	})
+}
+
+/// Returns the weight of the calls dispatched so far from within the call currently dispatched.
+///
+/// Returns `None` if the current call is not metered.
+pub fn nested_weight() -> Option<NestedWeight> {
+	with_context::<NestedWeightStack, _>(|s| s.get().and_then(|s| s.0.last().copied())).flatten()
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use crate::dispatch::{DispatchErrorWithPostInfo, PostDispatchInfo};
 
 	#[test]
 	fn dispatch_context_works() {
@@ -229,4 +380,98 @@ mod tests {
 		// `run_in_context` calls.
 		assert_eq!(100, res);
 	}
+
+	fn info(ref_time: u64) -> DispatchInfo {
+		DispatchInfo { weight: Weight::from_parts(ref_time, 0), ..Default::default() }
+	}
+
+	fn actual(ref_time: u64) -> PostDispatchInfo {
+		Some(Weight::from_parts(ref_time, 0)).into()
+	}
+
+	#[test]
+	fn nested_dispatch_weight_is_refunded() {
+		assert!(nested_weight().is_none());
+
+		// The outer call declares 100 for itself plus 200 for each of its two nested calls, but
+		// does not report any actual weight.
+		let result = dispatch_metered(&info(500), || {
+			assert_eq!(nested_weight(), Some(NestedWeight::default()));
+
+			dispatch_metered(&info(200), || Ok(actual(50)))?;
+			dispatch_metered(&info(200), || {
+				// Uses its declared weight, but 150 of it are refunded by a nested call.
+				dispatch_metered(&info(180), || Ok(actual(30)))?;
+				Ok(().into())
+			})?;
+
+			assert_eq!(
+				nested_weight(),
+				Some(NestedWeight {
+					declared: Weight::from_parts(400, 0),
+					actual: Weight::from_parts(100, 0),
+				})
+			);
+			Ok(().into())
+		});
+
+		assert_eq!(result, Ok(actual(200)));
+	}
+
+	#[test]
+	fn nested_dispatch_weight_never_increases_actual_weight() {
+		// The outer call already accounted for the nested call.
+		let result = dispatch_metered(&info(300), || {
+			dispatch_metered(&info(200), || Ok(actual(50)))?;
+			Ok(actual(120))
+		});
+		assert_eq!(result, Ok(actual(120)));
+
+		// Failing calls are refunded as well.
+		let result = dispatch_metered(&info(300), || {
+			dispatch_metered(&info(200), || Ok(actual(50)))?;
+			Err(DispatchErrorWithPostInfo { post_info: Default::default(), error: "failed".into() })
+		});
+		assert_eq!(result.unwrap_err().post_info, actual(150));
+	}
+
+	#[test]
+	fn nested_dispatch_weight_is_not_refunded_beyond_declared_weight() {
+		// Like `with_weight`, the outer call declares less than its nested call.
+		let result = dispatch_metered(&info(100), || {
+			dispatch_metered(&info(200), || Ok(actual(50)))?;
+			Ok(().into())
+		});
+		assert_eq!(result, Ok(PostDispatchInfo::default()));
+	}
+
+	struct Call(u64);
+
+	impl GetDispatchInfo for Call {
+		fn get_dispatch_info(&self) -> DispatchInfo {
+			info(self.0)
+		}
+	}
+
+	#[test]
+	fn only_nested_or_given_calls_are_metered() {
+		assert_eq!(run_in_context(|| metered_dispatch_info(&Call(10))), None);
+
+		// The given info is used once, even if it differs from the one of the call.
+		with_dispatch_info(&info(20), || {
+			assert_eq!(metered_dispatch_info(&Call(10)), Some(info(20)));
+			assert_eq!(metered_dispatch_info(&Call(10)), None);
+		});
+
+		let (result, weight) = measure_nested(|| {
+			assert_eq!(metered_dispatch_info(&Call(10)), Some(info(10)));
+			dispatch_metered(&info(10), || Ok(actual(4)))?;
+			dispatch_metered(&info(30), || Ok(().into()))
+		});
+		assert_eq!(result, Ok(PostDispatchInfo::default()));
+		assert_eq!(
+			weight,
+			NestedWeight { declared: Weight::from_parts(40, 0), actual: Weight::from_parts(34, 0) }
+		);
+	}
 }
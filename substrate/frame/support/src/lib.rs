@@ -52,7 +52,8 @@ pub mod __private {
 	#[cfg(feature = "std")]
 	pub use sp_runtime::{bounded_btree_map, bounded_vec};
 	pub use sp_runtime::{
-		traits::Dispatchable, DispatchError, RuntimeDebug, StateVersion, TransactionOutcome,
+		traits::{AsSystemOriginSigner, AsTransactionAuthorizedOrigin, Dispatchable},
+		DispatchError, RuntimeDebug, StateVersion, TransactionOutcome,
 	};
 	#[cfg(feature = "std")]
 	pub use sp_state_machine::BasicExternalities;
@@ -840,7 +841,8 @@ pub mod pallet_prelude {
 			bounded_vec::BoundedVec,
 			types::{
 				CountedStorageMap, CountedStorageNMap, Key as NMapKey, OptionQuery, ResultQuery,
-				StorageDoubleMap, StorageMap, StorageNMap, StorageValue, ValueQuery,
+				StorageDoubleMap, StorageMap, StorageNMap, StoragePagedMap, StorageValue,
+				ValueQuery,
 			},
 			weak_bounded_vec::WeakBoundedVec,
 			StorageList,
@@ -1371,7 +1373,9 @@ pub mod pallet_prelude {
 /// * [`CountedStorageMap`](`pallet_prelude::CountedStorageMap`) expects `Hasher`, `Key`,
 ///   `Value` and optionally `QueryKind` and `OnEmpty`,
 /// * [`StorageDoubleMap`](`pallet_prelude::StorageDoubleMap`) expects `Hasher1`, `Key1`,
-///   `Hasher2`, `Key2`, `Value` and optionally `QueryKind` and `OnEmpty`.
+///   `Hasher2`, `Key2`, `Value` and optionally `QueryKind` and `OnEmpty`,
+/// * [`StoragePagedMap`](`pallet_prelude::StoragePagedMap`) expects `Hasher`, `Key`, `Value`,
+///   `ValuesPerPage` and optionally `MaxPages` and `MaxValues`.
 ///
 /// For unnamed generic arguments: Their first generic must be `_` as it is replaced by the
 /// macro and other generic must declared as a normal generic type declaration.
@@ -1390,6 +1394,12 @@ pub mod pallet_prelude {
 /// CountedStorageaMap<...>` will store its counter at the prefix: `Twox128(b"MyExample") ++
 /// Twox128(b"CounterForFoo")`.
 ///
+/// Similarly, for the [`StoragePagedMap`](`pallet_prelude::StoragePagedMap`) variant, the
+/// `Prefix` also implements
+/// [`StoragePagedMapInstance`](`frame_support::storage::types::StoragePagedMapInstance`). Its
+/// `MetaPrefix` is implemented the same as above, but the storage prefix is prepended with
+/// `"PageMetaFor"`, e.g. `Twox128(b"MyExample") ++ Twox128(b"PageMetaForFoo")`.
+///
 /// E.g:
 ///
 /// ```ignore
@@ -1500,7 +1510,9 @@ pub mod pallet_prelude {
 /// then uses it as the first generic of the aliased type. For
 /// [`CountedStorageMap`](`pallet_prelude::CountedStorageMap`),
 /// [`CountedStorageMapInstance`](`frame_support::storage::types::CountedStorageMapInstance`)
-/// is implemented, and another similar struct is generated.
+/// is implemented, and another similar struct is generated. The same happens for
+/// [`StoragePagedMap`](`pallet_prelude::StoragePagedMap`) and
+/// [`StoragePagedMapInstance`](`frame_support::storage::types::StoragePagedMapInstance`).
 ///
 /// For a named generic, the macro will reorder the generics, and remove the names.
 ///
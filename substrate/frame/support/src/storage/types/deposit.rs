@@ -0,0 +1,765 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Storage deposit wrapper for map types.
+//!
+//! Values stored through [`WithDeposit`] are wrapped into [`Deposited`], which records who paid
+//! for the storage and how much is held. The held amount follows the encoded length of the value
+//! and is adjusted on every insertion, mutation and removal.
+
+use crate::{
+	storage::types::{
+		EncodeLikeTuple, KeyGenerator, QueryKindTrait, StorageDoubleMap, StorageMap, StorageNMap,
+		TupleToEncodedIter,
+	},
+	traits::{
+		tokens::{
+			fungible::{Inspect, InspectHold, MutateHold},
+			Precision,
+		},
+		Get, StorageInstance,
+	},
+};
+use codec::{Decode, Encode, EncodeLike, FullCodec, MaxEncodedLen};
+use scale_info::TypeInfo;
+use sp_arithmetic::traits::{Saturating, UniqueSaturatedFrom, Zero};
+use sp_runtime::{DispatchError, DispatchResult, RuntimeDebug};
+
+/// A value stored together with the deposit held for it.
+///
+/// This is the value type to declare for a storage item which is accessed through
+/// [`WithDeposit`].
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub struct Deposited<AccountId, Balance, Value> {
+	/// The stored value.
+	pub value: Value,
+	/// The account the deposit is held from.
+	pub depositor: AccountId,
+	/// The amount currently held for this value.
+	pub deposit: Balance,
+}
+
+/// The balance type used by a [`StorageDeposit`] implementation.
+pub type DepositBalanceOf<D, AccountId> =
+	<<D as StorageDeposit<AccountId>>::Currency as Inspect<AccountId>>::Balance;
+
+/// Describes the deposit which is held for values stored through [`WithDeposit`].
+pub trait StorageDeposit<AccountId> {
+	/// The currency in which deposits are held.
+	type Currency: MutateHold<AccountId>;
+
+	/// The reason under which deposits are held.
+	type Reason: Get<<Self::Currency as InspectHold<AccountId>>::Reason>;
+
+	/// The deposit required for a value whose encoding is `encoded_len` bytes long.
+	fn deposit_for(encoded_len: usize) -> DepositBalanceOf<Self, AccountId>;
+}
+
+/// A [`StorageDeposit`] requiring `Base` plus `PerByte` for each byte of the encoded value.
+pub struct LinearStorageDeposit<Currency, Reason, Base, PerByte>(
+	core::marker::PhantomData<(Currency, Reason, Base, PerByte)>,
+);
+
+impl<AccountId, Currency, Reason, Base, PerByte> StorageDeposit<AccountId>
+	for LinearStorageDeposit<Currency, Reason, Base, PerByte>
+where
+	Currency: MutateHold<AccountId>,
+	Reason: Get<Currency::Reason>,
+	Base: Get<Currency::Balance>,
+	PerByte: Get<Currency::Balance>,
+{
+	type Currency = Currency;
+	type Reason = Reason;
+
+	fn deposit_for(encoded_len: usize) -> Currency::Balance {
+		let len = Currency::Balance::unique_saturated_from(encoded_len as u64);
+		Base::get().saturating_add(PerByte::get().saturating_mul(len))
+	}
+}
+
+/// Accessor holding and releasing a deposit for every value of a storage map.
+///
+/// `Storage` is a [`StorageMap`], [`StorageDoubleMap`] or [`StorageNMap`] whose value type is
+/// [`Deposited`]. The storage item itself is declared as usual, e.g. with `#[pallet::storage]`,
+/// and `WithDeposit` is used in place of it wherever values are written, so that the deposit
+/// defined by `Deposit` is kept in line with the encoded length of the stored value:
+///
+/// - inserting a value holds the deposit from the given depositor, releasing the deposit of any
+///   value it replaces;
+/// - mutating a value holds or releases the difference from its original depositor;
+/// - removing a value releases its deposit.
+///
+/// Releases are done on a best effort basis, so a deposit which was slashed in the meantime does
+/// not prevent a value from being removed.
+///
+/// Writing to the underlying storage item directly bypasses the deposit accounting.
+///
+/// # Example
+///
+/// ```ignore
+/// #[pallet::storage]
+/// pub type Notes<T: Config> =
+/// 	StorageMap<_, Blake2_128Concat, u32, Deposited<T::AccountId, BalanceOf<T>, Note>>;
+///
+/// type NoteDeposit<T> = LinearStorageDeposit<
+/// 	<T as Config>::Currency,
+/// 	<T as Config>::NoteHoldReason,
+/// 	<T as Config>::BaseDeposit,
+/// 	<T as Config>::ByteDeposit,
+/// >;
+///
+/// WithDeposit::<Notes<T>, NoteDeposit<T>>::insert(&who, id, note)?;
+/// ```
+pub struct WithDeposit<Storage, Deposit>(core::marker::PhantomData<(Storage, Deposit)>);
+
+// Private helper trait to access the storage from the deposit wrapper.
+trait StorageWrapper {
+	type Storage;
+}
+
+impl<Storage, Deposit> StorageWrapper for WithDeposit<Storage, Deposit> {
+	type Storage = Storage;
+}
+
+impl<Storage, Deposit> WithDeposit<Storage, Deposit> {
+	/// Replace `item` with `new`, holding and releasing deposits accordingly.
+	///
+	/// Returns the replaced value.
+	fn update<AccountId, Value>(
+		item: &mut Option<Deposited<AccountId, DepositBalanceOf<Deposit, AccountId>, Value>>,
+		new: Option<(AccountId, Value)>,
+	) -> Result<Option<Value>, DispatchError>
+	where
+		Deposit: StorageDeposit<AccountId>,
+		AccountId: Eq,
+		Value: Encode,
+	{
+		let new = new.map(|(depositor, value)| {
+			let deposit = Deposit::deposit_for(value.encoded_size());
+			Deposited { value, depositor, deposit }
+		});
+		Self::settle(
+			item.as_ref().map(|old| (&old.depositor, old.deposit)),
+			new.as_ref().map(|new| (&new.depositor, new.deposit)),
+		)?;
+		Ok(core::mem::replace(item, new).map(|old| old.value))
+	}
+
+	/// Mutate `item` through `f`, keeping the depositor of an existing value.
+	///
+	/// A value created by `f` has its deposit held from `depositor`.
+	fn mutate<AccountId, Value, R, E, F>(
+		item: &mut Option<Deposited<AccountId, DepositBalanceOf<Deposit, AccountId>, Value>>,
+		depositor: &AccountId,
+		f: F,
+	) -> Result<R, E>
+	where
+		Deposit: StorageDeposit<AccountId>,
+		AccountId: Clone + Eq,
+		Value: Encode,
+		E: From<DispatchError>,
+		F: FnOnce(&mut Option<Value>) -> Result<R, E>,
+	{
+		let (mut value, previous) = match item.take() {
+			Some(Deposited { value, depositor, deposit }) =>
+				(Some(value), Some((depositor, deposit))),
+			None => (None, None),
+		};
+
+		let res = f(&mut value)?;
+
+		let depositor = previous.as_ref().map_or_else(|| depositor.clone(), |(who, _)| who.clone());
+		let new = value.map(|value| {
+			let deposit = Deposit::deposit_for(value.encoded_size());
+			Deposited { value, depositor, deposit }
+		});
+		Self::settle(
+			previous.as_ref().map(|(who, deposit)| (who, *deposit)),
+			new.as_ref().map(|new| (&new.depositor, new.deposit)),
+		)?;
+		*item = new;
+		Ok(res)
+	}
+
+	/// Adjust the deposits held from `old` to `new`.
+	fn settle<AccountId>(
+		old: Option<(&AccountId, DepositBalanceOf<Deposit, AccountId>)>,
+		new: Option<(&AccountId, DepositBalanceOf<Deposit, AccountId>)>,
+	) -> DispatchResult
+	where
+		Deposit: StorageDeposit<AccountId>,
+		AccountId: Eq,
+	{
+		let reason = Deposit::Reason::get();
+		match (old, new) {
+			(Some((old_who, old_deposit)), Some((new_who, new_deposit))) if old_who == new_who =>
+				if new_deposit > old_deposit {
+					Deposit::Currency::hold(&reason, new_who, new_deposit - old_deposit)
+				} else if old_deposit > new_deposit {
+					Deposit::Currency::release(
+						&reason,
+						old_who,
+						old_deposit - new_deposit,
+						Precision::BestEffort,
+					)
+					.map(|_| ())
+				} else {
+					Ok(())
+				},
+			(old, new) => {
+				if let Some((who, deposit)) = new.filter(|(_, deposit)| !deposit.is_zero()) {
+					Deposit::Currency::hold(&reason, who, deposit)?;
+				}
+				if let Some((who, deposit)) = old.filter(|(_, deposit)| !deposit.is_zero()) {
+					Deposit::Currency::release(&reason, who, deposit, Precision::BestEffort)?;
+				}
+				Ok(())
+			},
+		}
+	}
+}
+
+impl<Prefix, Hasher, Key, AccountId, Balance, Value, QueryKind, OnEmpty, MaxValues, Deposit>
+	WithDeposit<
+		StorageMap<
+			Prefix,
+			Hasher,
+			Key,
+			Deposited<AccountId, Balance, Value>,
+			QueryKind,
+			OnEmpty,
+			MaxValues,
+		>,
+		Deposit,
+	>
+where
+	Prefix: StorageInstance,
+	Hasher: crate::hash::StorageHasher,
+	Key: FullCodec,
+	AccountId: FullCodec + Clone + Eq,
+	Balance: FullCodec,
+	Value: FullCodec,
+	QueryKind: QueryKindTrait<Deposited<AccountId, Balance, Value>, OnEmpty>,
+	OnEmpty: Get<QueryKind::Query> + 'static,
+	MaxValues: Get<Option<u32>>,
+	Deposit: StorageDeposit<AccountId>,
+	Deposit::Currency: Inspect<AccountId, Balance = Balance>,
+{
+	/// Does the value (explicitly) exist in storage?
+	pub fn contains_key<KeyArg: EncodeLike<Key>>(key: KeyArg) -> bool {
+		<Self as StorageWrapper>::Storage::contains_key(key)
+	}
+
+	/// Load the value associated with the given key from the map.
+	pub fn get<KeyArg: EncodeLike<Key>>(key: KeyArg) -> Option<Value> {
+		Self::get_deposited(key).map(|item| item.value)
+	}
+
+	/// Load the value associated with the given key along with its depositor and deposit.
+	pub fn get_deposited<KeyArg: EncodeLike<Key>>(
+		key: KeyArg,
+	) -> Option<Deposited<AccountId, Balance, Value>> {
+		<Self as StorageWrapper>::Storage::try_get(key).ok()
+	}
+
+	/// Store a value under the given key, holding its deposit from `depositor`.
+	///
+	/// The deposit held for any previous value under `key` is released to its depositor.
+	pub fn insert<KeyArg: EncodeLike<Key>>(
+		depositor: &AccountId,
+		key: KeyArg,
+		value: Value,
+	) -> DispatchResult {
+		<Self as StorageWrapper>::Storage::try_mutate_exists(key, |item| {
+			Self::update(item, Some((depositor.clone(), value))).map(|_| ())
+		})
+	}
+
+	/// Mutate the value under the given key, only if an `Ok` value is returned. Deletes the item if
+	/// mutated to a `None`.
+	///
+	/// The deposit is adjusted to the new encoded length and taken from, or released to, the
+	/// original depositor. If the value is created by `f`, the deposit is held from `depositor`.
+	pub fn try_mutate_exists<KeyArg, R, E, F>(
+		depositor: &AccountId,
+		key: KeyArg,
+		f: F,
+	) -> Result<R, E>
+	where
+		KeyArg: EncodeLike<Key>,
+		E: From<DispatchError>,
+		F: FnOnce(&mut Option<Value>) -> Result<R, E>,
+	{
+		<Self as StorageWrapper>::Storage::try_mutate_exists(key, |item| {
+			Self::mutate(item, depositor, f)
+		})
+	}
+
+	/// Remove the value under the given key and release its deposit.
+	pub fn remove<KeyArg: EncodeLike<Key>>(key: KeyArg) -> DispatchResult {
+		Self::take(key).map(|_| ())
+	}
+
+	/// Take the value under the given key and release its deposit.
+	pub fn take<KeyArg: EncodeLike<Key>>(key: KeyArg) -> Result<Option<Value>, DispatchError> {
+		<Self as StorageWrapper>::Storage::try_mutate_exists(key, |item| Self::update(item, None))
+	}
+}
+
+impl<
+		Prefix,
+		Hasher1,
+		Key1,
+		Hasher2,
+		Key2,
+		AccountId,
+		Balance,
+		Value,
+		QueryKind,
+		OnEmpty,
+		MaxValues,
+		Deposit,
+	>
+	WithDeposit<
+		StorageDoubleMap<
+			Prefix,
+			Hasher1,
+			Key1,
+			Hasher2,
+			Key2,
+			Deposited<AccountId, Balance, Value>,
+			QueryKind,
+			OnEmpty,
+			MaxValues,
+		>,
+		Deposit,
+	>
+where
+	Prefix: StorageInstance,
+	Hasher1: crate::hash::StorageHasher,
+	Hasher2: crate::hash::StorageHasher,
+	Key1: FullCodec,
+	Key2: FullCodec,
+	AccountId: FullCodec + Clone + Eq,
+	Balance: FullCodec,
+	Value: FullCodec,
+	QueryKind: QueryKindTrait<Deposited<AccountId, Balance, Value>, OnEmpty>,
+	OnEmpty: Get<QueryKind::Query> + 'static,
+	MaxValues: Get<Option<u32>>,
+	Deposit: StorageDeposit<AccountId>,
+	Deposit::Currency: Inspect<AccountId, Balance = Balance>,
+{
+	/// Does the value (explicitly) exist in storage?
+	pub fn contains_key<KArg1, KArg2>(k1: KArg1, k2: KArg2) -> bool
+	where
+		KArg1: EncodeLike<Key1>,
+		KArg2: EncodeLike<Key2>,
+	{
+		<Self as StorageWrapper>::Storage::contains_key(k1, k2)
+	}
+
+	/// Load the value associated with the given keys from the double map.
+	pub fn get<KArg1, KArg2>(k1: KArg1, k2: KArg2) -> Option<Value>
+	where
+		KArg1: EncodeLike<Key1>,
+		KArg2: EncodeLike<Key2>,
+	{
+		Self::get_deposited(k1, k2).map(|item| item.value)
+	}
+
+	/// Load the value associated with the given keys along with its depositor and deposit.
+	pub fn get_deposited<KArg1, KArg2>(
+		k1: KArg1,
+		k2: KArg2,
+	) -> Option<Deposited<AccountId, Balance, Value>>
+	where
+		KArg1: EncodeLike<Key1>,
+		KArg2: EncodeLike<Key2>,
+	{
+		<Self as StorageWrapper>::Storage::try_get(k1, k2).ok()
+	}
+
+	/// Store a value under the given keys, holding its deposit from `depositor`.
+	///
+	/// The deposit held for any previous value under the keys is released to its depositor.
+	pub fn insert<KArg1, KArg2>(
+		depositor: &AccountId,
+		k1: KArg1,
+		k2: KArg2,
+		value: Value,
+	) -> DispatchResult
+	where
+		KArg1: EncodeLike<Key1>,
+		KArg2: EncodeLike<Key2>,
+	{
+		<Self as StorageWrapper>::Storage::try_mutate_exists(k1, k2, |item| {
+			Self::update(item, Some((depositor.clone(), value))).map(|_| ())
+		})
+	}
+
+	/// Mutate the value under the given keys, only if an `Ok` value is returned. Deletes the item
+	/// if mutated to a `None`.
+	///
+	/// The deposit is adjusted to the new encoded length and taken from, or released to, the
+	/// original depositor. If the value is created by `f`, the deposit is held from `depositor`.
+	pub fn try_mutate_exists<KArg1, KArg2, R, E, F>(
+		depositor: &AccountId,
+		k1: KArg1,
+		k2: KArg2,
+		f: F,
+	) -> Result<R, E>
+	where
+		KArg1: EncodeLike<Key1>,
+		KArg2: EncodeLike<Key2>,
+		E: From<DispatchError>,
+		F: FnOnce(&mut Option<Value>) -> Result<R, E>,
+	{
+		<Self as StorageWrapper>::Storage::try_mutate_exists(k1, k2, |item| {
+			Self::mutate(item, depositor, f)
+		})
+	}
+
+	/// Remove the value under the given keys and release its deposit.
+	pub fn remove<KArg1, KArg2>(k1: KArg1, k2: KArg2) -> DispatchResult
+	where
+		KArg1: EncodeLike<Key1>,
+		KArg2: EncodeLike<Key2>,
+	{
+		Self::take(k1, k2).map(|_| ())
+	}
+
+	/// Take the value under the given keys and release its deposit.
+	pub fn take<KArg1, KArg2>(k1: KArg1, k2: KArg2) -> Result<Option<Value>, DispatchError>
+	where
+		KArg1: EncodeLike<Key1>,
+		KArg2: EncodeLike<Key2>,
+	{
+		<Self as StorageWrapper>::Storage::try_mutate_exists(k1, k2, |item| {
+			Self::update(item, None)
+		})
+	}
+}
+
+impl<Prefix, Key, AccountId, Balance, Value, QueryKind, OnEmpty, MaxValues, Deposit>
+	WithDeposit<
+		StorageNMap<
+			Prefix,
+			Key,
+			Deposited<AccountId, Balance, Value>,
+			QueryKind,
+			OnEmpty,
+			MaxValues,
+		>,
+		Deposit,
+	>
+where
+	Prefix: StorageInstance,
+	Key: KeyGenerator,
+	AccountId: FullCodec + Clone + Eq,
+	Balance: FullCodec,
+	Value: FullCodec,
+	QueryKind: QueryKindTrait<Deposited<AccountId, Balance, Value>, OnEmpty>,
+	OnEmpty: Get<QueryKind::Query> + 'static,
+	MaxValues: Get<Option<u32>>,
+	Deposit: StorageDeposit<AccountId>,
+	Deposit::Currency: Inspect<AccountId, Balance = Balance>,
+{
+	/// Does the value (explicitly) exist in storage?
+	pub fn contains_key<KArg: EncodeLikeTuple<Key::KArg> + TupleToEncodedIter>(key: KArg) -> bool {
+		<Self as StorageWrapper>::Storage::contains_key(key)
+	}
+
+	/// Load the value associated with the given key from the map.
+	pub fn get<KArg: EncodeLikeTuple<Key::KArg> + TupleToEncodedIter>(key: KArg) -> Option<Value> {
+		Self::get_deposited(key).map(|item| item.value)
+	}
+
+	/// Load the value associated with the given key along with its depositor and deposit.
+	pub fn get_deposited<KArg: EncodeLikeTuple<Key::KArg> + TupleToEncodedIter>(
+		key: KArg,
+	) -> Option<Deposited<AccountId, Balance, Value>> {
+		<Self as StorageWrapper>::Storage::try_get(key).ok()
+	}
+
+	/// Store a value under the given key, holding its deposit from `depositor`.
+	///
+	/// The deposit held for any previous value under `key` is released to its depositor.
+	pub fn insert<KArg: EncodeLikeTuple<Key::KArg> + TupleToEncodedIter>(
+		depositor: &AccountId,
+		key: KArg,
+		value: Value,
+	) -> DispatchResult {
+		<Self as StorageWrapper>::Storage::try_mutate_exists(key, |item| {
+			Self::update(item, Some((depositor.clone(), value))).map(|_| ())
+		})
+	}
+
+	/// Mutate the value under the given key, only if an `Ok` value is returned. Deletes the item if
+	/// mutated to a `None`.
+	///
+	/// The deposit is adjusted to the new encoded length and taken from, or released to, the
+	/// original depositor. If the value is created by `f`, the deposit is held from `depositor`.
+	pub fn try_mutate_exists<KArg, R, E, F>(depositor: &AccountId, key: KArg, f: F) -> Result<R, E>
+	where
+		KArg: EncodeLikeTuple<Key::KArg> + TupleToEncodedIter,
+		E: From<DispatchError>,
+		F: FnOnce(&mut Option<Value>) -> Result<R, E>,
+	{
+		<Self as StorageWrapper>::Storage::try_mutate_exists(key, |item| {
+			Self::mutate(item, depositor, f)
+		})
+	}
+
+	/// Remove the value under the given key and release its deposit.
+	pub fn remove<KArg: EncodeLikeTuple<Key::KArg> + TupleToEncodedIter>(
+		key: KArg,
+	) -> DispatchResult {
+		Self::take(key).map(|_| ())
+	}
+
+	/// Take the value under the given key and release its deposit.
+	pub fn take<KArg: EncodeLikeTuple<Key::KArg> + TupleToEncodedIter>(
+		key: KArg,
+	) -> Result<Option<Value>, DispatchError> {
+		<Self as StorageWrapper>::Storage::try_mutate_exists(key, |item| Self::update(item, None))
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::{
+		hash::*,
+		storage::types::Key as NMapKey,
+		traits::{
+			tokens::{
+				fungible, DepositConsequence, Fortitude, Preservation, Provenance,
+				WithdrawConsequence,
+			},
+			ConstU64, GetDefault,
+		},
+	};
+	use sp_io::TestExternalities;
+	use sp_runtime::TokenError;
+	use std::{cell::RefCell, collections::BTreeMap};
+
+	thread_local! {
+		static FREE: RefCell<BTreeMap<u64, u64>> = RefCell::new(BTreeMap::new());
+		static HELD: RefCell<BTreeMap<u64, u64>> = RefCell::new(BTreeMap::new());
+	}
+
+	/// Minimal currency with a single hold reason.
+	struct Currency;
+
+	impl fungible::Inspect<u64> for Currency {
+		type Balance = u64;
+
+		fn total_issuance() -> u64 {
+			0
+		}
+		fn minimum_balance() -> u64 {
+			0
+		}
+		fn total_balance(who: &u64) -> u64 {
+			Self::balance(who) + Self::total_balance_on_hold(who)
+		}
+		fn balance(who: &u64) -> u64 {
+			FREE.with(|free| free.borrow().get(who).copied().unwrap_or_default())
+		}
+		fn reducible_balance(who: &u64, _: Preservation, _: Fortitude) -> u64 {
+			Self::balance(who)
+		}
+		fn can_deposit(_: &u64, _: u64, _: Provenance) -> DepositConsequence {
+			DepositConsequence::Success
+		}
+		fn can_withdraw(_: &u64, _: u64) -> WithdrawConsequence<u64> {
+			WithdrawConsequence::Success
+		}
+	}
+
+	impl fungible::Unbalanced<u64> for Currency {
+		fn handle_dust(_: fungible::Dust<u64, Self>) {}
+		fn write_balance(who: &u64, amount: u64) -> Result<Option<u64>, DispatchError> {
+			FREE.with(|free| free.borrow_mut().insert(*who, amount));
+			Ok(None)
+		}
+		fn set_total_issuance(_: u64) {}
+	}
+
+	impl fungible::InspectHold<u64> for Currency {
+		type Reason = ();
+
+		fn total_balance_on_hold(who: &u64) -> u64 {
+			HELD.with(|held| held.borrow().get(who).copied().unwrap_or_default())
+		}
+		fn balance_on_hold(_: &(), who: &u64) -> u64 {
+			Self::total_balance_on_hold(who)
+		}
+	}
+
+	impl fungible::UnbalancedHold<u64> for Currency {
+		fn set_balance_on_hold(_: &(), who: &u64, amount: u64) -> DispatchResult {
+			HELD.with(|held| held.borrow_mut().insert(*who, amount));
+			Ok(())
+		}
+	}
+
+	impl fungible::MutateHold<u64> for Currency {}
+
+	type Deposit = LinearStorageDeposit<Currency, GetDefault, ConstU64<10>, ConstU64<1>>;
+	type Item = Deposited<u64, u64, Vec<u8>>;
+
+	struct Prefix;
+	impl StorageInstance for Prefix {
+		fn pallet_prefix() -> &'static str {
+			"test"
+		}
+		const STORAGE_PREFIX: &'static str = "foo";
+	}
+
+	fn new_test_ext() -> TestExternalities {
+		FREE.with(|free| *free.borrow_mut() = [(1, 100), (2, 100), (3, 5)].into_iter().collect());
+		HELD.with(|held| held.borrow_mut().clear());
+		TestExternalities::default()
+	}
+
+	fn balances(who: u64) -> (u64, u64) {
+		use fungible::{Inspect, InspectHold};
+		(Currency::balance(&who), Currency::total_balance_on_hold(&who))
+	}
+
+	#[test]
+	fn map_deposit_follows_value_length() {
+		type A = StorageMap<Prefix, Blake2_128Concat, u32, Item>;
+		type D = WithDeposit<A, Deposit>;
+
+		new_test_ext().execute_with(|| {
+			// 10 base + 4 bytes.
+			assert_eq!(D::insert(&1, 7, vec![1, 2, 3]), Ok(()));
+			assert_eq!(balances(1), (86, 14));
+			assert_eq!(D::get(7), Some(vec![1, 2, 3]));
+			assert_eq!(
+				A::get(7),
+				Some(Deposited { value: vec![1, 2, 3], depositor: 1, deposit: 14 })
+			);
+
+			// Growing the value holds the difference from the original depositor.
+			assert_eq!(
+				D::try_mutate_exists(&2, 7, |v| {
+					v.as_mut().unwrap().extend([4, 5]);
+					Ok::<_, DispatchError>(())
+				}),
+				Ok(())
+			);
+			assert_eq!(balances(1), (84, 16));
+			assert_eq!(balances(2), (100, 0));
+
+			// Shrinking it releases the difference.
+			assert_eq!(
+				D::try_mutate_exists(&1, 7, |v| {
+					v.as_mut().unwrap().truncate(1);
+					Ok::<_, DispatchError>(())
+				}),
+				Ok(())
+			);
+			assert_eq!(balances(1), (88, 12));
+
+			// A failing mutation leaves everything untouched.
+			assert_eq!(
+				D::try_mutate_exists(&1, 7, |v| {
+					*v = None;
+					Err::<(), _>(DispatchError::Other("nope"))
+				}),
+				Err(DispatchError::Other("nope"))
+			);
+			assert_eq!(D::get(7), Some(vec![1]));
+			assert_eq!(balances(1), (88, 12));
+
+			assert_eq!(D::take(7), Ok(Some(vec![1])));
+			assert!(!D::contains_key(7));
+			assert_eq!(balances(1), (100, 0));
+
+			// Removing a missing value is a no-op.
+			assert_eq!(D::remove(7), Ok(()));
+			assert_eq!(balances(1), (100, 0));
+		});
+	}
+
+	#[test]
+	fn map_insert_moves_deposit_to_new_depositor() {
+		type A = StorageMap<Prefix, Blake2_128Concat, u32, Item>;
+		type D = WithDeposit<A, Deposit>;
+
+		new_test_ext().execute_with(|| {
+			assert_eq!(D::insert(&1, 7, vec![1, 2, 3]), Ok(()));
+			assert_eq!(D::insert(&2, 7, vec![1]), Ok(()));
+			assert_eq!(balances(1), (100, 0));
+			assert_eq!(balances(2), (88, 12));
+			assert_eq!(
+				D::get_deposited(7),
+				Some(Deposited { value: vec![1], depositor: 2, deposit: 12 })
+			);
+
+			// Creating a value through a mutation takes the deposit from the given depositor.
+			assert_eq!(
+				D::try_mutate_exists(&1, 8, |v| {
+					*v = Some(vec![]);
+					Ok::<_, DispatchError>(())
+				}),
+				Ok(())
+			);
+			assert_eq!(balances(1), (89, 11));
+		});
+	}
+
+	#[test]
+	fn insert_fails_without_funds() {
+		type A = StorageMap<Prefix, Blake2_128Concat, u32, Item>;
+		type D = WithDeposit<A, Deposit>;
+
+		new_test_ext().execute_with(|| {
+			assert_eq!(D::insert(&3, 7, vec![]), Err(TokenError::FundsUnavailable.into()));
+			assert!(!D::contains_key(7));
+			assert_eq!(balances(3), (5, 0));
+		});
+	}
+
+	#[test]
+	fn double_map_and_nmap_hold_deposits() {
+		type A = StorageDoubleMap<Prefix, Blake2_128Concat, u16, Twox64Concat, u8, Item>;
+		type DA = WithDeposit<A, Deposit>;
+		type B =
+			StorageNMap<Prefix, (NMapKey<Blake2_128Concat, u16>, NMapKey<Twox64Concat, u8>), Item>;
+		type DB = WithDeposit<B, Deposit>;
+
+		new_test_ext().execute_with(|| {
+			assert_eq!(DA::insert(&1, 3, 30, vec![1, 2]), Ok(()));
+			assert_eq!(DA::get(3, 30), Some(vec![1, 2]));
+			assert_eq!(balances(1), (87, 13));
+			assert_eq!(DA::remove(3, 30), Ok(()));
+			assert_eq!(balances(1), (100, 0));
+		});
+
+		new_test_ext().execute_with(|| {
+			assert_eq!(DB::insert(&2, (3, 30), vec![1, 2]), Ok(()));
+			assert_eq!(DB::get((3, 30)), Some(vec![1, 2]));
+			assert_eq!(balances(2), (87, 13));
+			assert_eq!(DB::take((3, 30)), Ok(Some(vec![1, 2])));
+			assert_eq!(balances(2), (100, 0));
+		});
+	}
+}
@@ -24,14 +24,17 @@ use sp_std::prelude::*;
 
 mod counted_map;
 mod counted_nmap;
+mod deposit;
 mod double_map;
 mod key;
 mod map;
 mod nmap;
+mod paged_map;
 mod value;
 
 pub use counted_map::{CountedStorageMap, CountedStorageMapInstance, Counter};
 pub use counted_nmap::{CountedStorageNMap, CountedStorageNMapInstance};
+pub use deposit::{DepositBalanceOf, Deposited, LinearStorageDeposit, StorageDeposit, WithDeposit};
 pub use double_map::StorageDoubleMap;
 pub use key::{
 	EncodeLikeTuple, HasKeyPrefix, HasReversibleKeyPrefix, Key, KeyGenerator,
@@ -39,6 +42,10 @@ pub use key::{
 };
 pub use map::StorageMap;
 pub use nmap::StorageNMap;
+pub use paged_map::{
+	PageIndex, PagedMapCursor, PagedMapMeta, StoragePagedMap, StoragePagedMapInstance,
+	StoragePagedMapIterator,
+};
 pub use value::StorageValue;
 
 /// Trait implementing how the storage optional value is converted into the queried type.
@@ -0,0 +1,647 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Storage paged map type.
+
+use crate::{
+	hash::{StorageHasher, Twox64Concat},
+	storage::{
+		bounded_vec::BoundedVec,
+		generator::StorageDoubleMap as _,
+		types::{OptionQuery, StorageDoubleMap, StorageEntryMetadataBuilder, StorageMap},
+		unhashed,
+	},
+	traits::{Get, GetDefault, StorageInfo, StorageInstance},
+};
+use codec::{Decode, Encode, EncodeLike, FullCodec, MaxEncodedLen, Ref};
+use scale_info::TypeInfo;
+use sp_metadata_ir::StorageEntryMetadataIR;
+use sp_runtime::{traits::Saturating, RuntimeDebug};
+use sp_std::prelude::*;
+
+/// The index of a page of a [`StoragePagedMap`] value.
+pub type PageIndex = u32;
+
+/// A map whose values are sequences of `Value`s stored across bounded pages.
+///
+/// Every key maps to a list of items which is split into pages of at most `ValuesPerPage` items.
+/// Each page is a separate storage entry, so appending to or iterating over a large list only
+/// touches the pages involved rather than the whole list. This makes it suitable for values which
+/// are themselves large collections, e.g. the exposures of a validator.
+///
+/// Up to `MaxPages` pages can be stored per key, if given. Both bounds are used, together with the
+/// [`MaxEncodedLen`] of `Value`, to report the maximum size of a page in the
+/// [`StorageInfo`] of the map, such that PoV estimation in benchmarks accounts for a single page
+/// per read rather than for the whole list.
+///
+/// The pages are stored under the prefix of the map, keyed by the map key and the page index. A
+/// small bookkeeping entry per key, holding the number of pages and items, is stored under the
+/// prefix of [`StoragePagedMapInstance::MetaPrefix`].
+///
+/// Iteration can be paused and resumed at a later point (e.g. in a later block) through a
+/// [`PagedMapCursor`].
+///
+/// # Warning
+///
+/// `ValuesPerPage` must not be decreased without migrating existing pages, as pages which are
+/// fuller than the new bound would no longer decode.
+///
+/// # Example
+///
+/// ```
+/// #[frame_support::pallet]
+/// mod pallet {
+/// # 	use frame_support::pallet_prelude::*;
+/// # 	#[pallet::config]
+/// # 	pub trait Config: frame_system::Config {}
+/// # 	#[pallet::pallet]
+/// # 	pub struct Pallet<T>(_);
+/// 	/// A paged map with pages of up to 64 values.
+/// 	#[pallet::storage]
+/// 	pub type Foo<T> = StoragePagedMap<_, Blake2_128Concat, u32, u64, ConstU32<64>>;
+///
+/// 	/// Alternative named syntax.
+/// 	#[pallet::storage]
+/// 	pub type Bar<T> = StoragePagedMap<
+/// 		Hasher = Blake2_128Concat,
+/// 		Key = u32,
+/// 		Value = u64,
+/// 		ValuesPerPage = ConstU32<64>,
+/// 		MaxPages = ConstU32<16>,
+/// 	>;
+/// }
+/// ```
+pub struct StoragePagedMap<
+	Prefix,
+	Hasher,
+	Key,
+	Value,
+	ValuesPerPage,
+	MaxPages = GetDefault,
+	MaxValues = GetDefault,
+>(core::marker::PhantomData<(Prefix, Hasher, Key, Value, ValuesPerPage, MaxPages, MaxValues)>);
+
+/// The requirement for an instance of [`StoragePagedMap`].
+pub trait StoragePagedMapInstance: StorageInstance {
+	/// The prefix to use for the page bookkeeping of each key.
+	type MetaPrefix: StorageInstance;
+}
+
+/// The page bookkeeping of a single key of a [`StoragePagedMap`].
+#[derive(
+	Encode, Decode, Clone, Copy, PartialEq, Eq, Default, RuntimeDebug, MaxEncodedLen, TypeInfo,
+)]
+pub struct PagedMapMeta {
+	/// The number of pages in storage.
+	pub pages: PageIndex,
+	/// The number of items in the last page.
+	pub last_page_len: u32,
+	/// The total number of items.
+	pub len: u32,
+}
+
+/// A position within the value of a [`StoragePagedMap`] key, from which iteration can resume.
+#[derive(
+	Encode, Decode, Clone, Copy, PartialEq, Eq, Default, RuntimeDebug, MaxEncodedLen, TypeInfo,
+)]
+pub struct PagedMapCursor {
+	/// The page of the next item.
+	pub page: PageIndex,
+	/// The offset of the next item within `page`.
+	pub offset: u32,
+}
+
+// Private helper trait to access the underlying storages of a paged map.
+trait PagedMapWrapper {
+	type Pages;
+	type Meta;
+}
+
+impl<P: StoragePagedMapInstance, H, K, V, N, MP, M> PagedMapWrapper
+	for StoragePagedMap<P, H, K, V, N, MP, M>
+{
+	type Pages = StorageDoubleMap<P, H, K, Twox64Concat, PageIndex, BoundedVec<V, N>, OptionQuery>;
+	type Meta = StorageMap<P::MetaPrefix, H, K, PagedMapMeta, OptionQuery, GetDefault, M>;
+}
+
+impl<Prefix, Hasher, Key, Value, ValuesPerPage, MaxPages, MaxValues>
+	StoragePagedMap<Prefix, Hasher, Key, Value, ValuesPerPage, MaxPages, MaxValues>
+where
+	Prefix: StoragePagedMapInstance,
+	Hasher: StorageHasher,
+	Key: FullCodec,
+	Value: FullCodec,
+	ValuesPerPage: Get<u32>,
+	MaxPages: Get<Option<u32>>,
+	MaxValues: Get<Option<u32>>,
+{
+	/// Does a value (explicitly) exist in storage for the given key?
+	pub fn contains_key<KeyArg: EncodeLike<Key>>(key: KeyArg) -> bool {
+		<Self as PagedMapWrapper>::Meta::contains_key(key)
+	}
+
+	/// The page bookkeeping for the given key.
+	pub fn meta<KeyArg: EncodeLike<Key>>(key: KeyArg) -> PagedMapMeta {
+		<Self as PagedMapWrapper>::Meta::get(key).unwrap_or_default()
+	}
+
+	/// The number of items stored under the given key.
+	pub fn len<KeyArg: EncodeLike<Key>>(key: KeyArg) -> u32 {
+		Self::meta(key).len
+	}
+
+	/// The number of pages stored under the given key.
+	pub fn page_count<KeyArg: EncodeLike<Key>>(key: KeyArg) -> PageIndex {
+		Self::meta(key).pages
+	}
+
+	/// Load a single page of the value under the given key.
+	pub fn page<KeyArg: EncodeLike<Key>>(
+		key: KeyArg,
+		page: PageIndex,
+	) -> Option<BoundedVec<Value, ValuesPerPage>> {
+		<Self as PagedMapWrapper>::Pages::get(key, page)
+	}
+
+	/// Load all items stored under the given key.
+	///
+	/// This reads every page of the key; prefer [`Self::iter`] for large values.
+	pub fn get<KeyArg: EncodeLike<Key>>(key: KeyArg) -> Vec<Value> {
+		Self::iter(key).collect()
+	}
+
+	/// Iterate over the items stored under the given key, in append order.
+	///
+	/// Pages are read lazily as the iteration reaches them.
+	pub fn iter<KeyArg: EncodeLike<Key>>(
+		key: KeyArg,
+	) -> StoragePagedMapIterator<Value, ValuesPerPage> {
+		Self::iter_from(key, Default::default())
+	}
+
+	/// Iterate over the items stored under the given key, starting at `cursor`.
+	///
+	/// The cursor is usually obtained from [`StoragePagedMapIterator::cursor`] of a previous
+	/// iteration. If the value was modified in between, the iteration resumes at the same position
+	/// of the modified value.
+	pub fn iter_from<KeyArg: EncodeLike<Key>>(
+		key: KeyArg,
+		cursor: PagedMapCursor,
+	) -> StoragePagedMapIterator<Value, ValuesPerPage> {
+		let meta = Self::meta(Ref::from(&key));
+		StoragePagedMapIterator {
+			key_prefix: <Self as PagedMapWrapper>::Pages::storage_double_map_final_key1(key),
+			meta,
+			cursor,
+			page: None,
+			_phantom: Default::default(),
+		}
+	}
+
+	/// Append an item to the value under the given key.
+	///
+	/// Returns `Err` if the value is already at its maximum of `MaxPages` full pages.
+	pub fn try_append<KeyArg, EncodeLikeValue>(key: KeyArg, item: EncodeLikeValue) -> Result<(), ()>
+	where
+		KeyArg: EncodeLike<Key>,
+		EncodeLikeValue: EncodeLike<Value>,
+	{
+		Self::try_extend(key, core::iter::once(item))
+	}
+
+	/// Append many items to the value under the given key.
+	///
+	/// Either all or none of the items are appended. Returns `Err` if they do not fit into
+	/// `MaxPages` pages.
+	pub fn try_extend<KeyArg, EncodeLikeValue, I>(key: KeyArg, items: I) -> Result<(), ()>
+	where
+		KeyArg: EncodeLike<Key>,
+		EncodeLikeValue: EncodeLike<Value>,
+		I: IntoIterator<Item = EncodeLikeValue>,
+		I::IntoIter: ExactSizeIterator,
+	{
+		let items = items.into_iter();
+		let mut meta = Self::meta(Ref::from(&key));
+		let per_page = ValuesPerPage::get().max(1);
+
+		if let Some(max_pages) = MaxPages::get() {
+			// Note: we saturate here in case someone decreased the bounds in a runtime upgrade.
+			let free_in_last =
+				if meta.pages == 0 { 0 } else { per_page.saturating_sub(meta.last_page_len) };
+			let free = max_pages
+				.saturating_sub(meta.pages)
+				.saturating_mul(per_page)
+				.saturating_add(free_in_last);
+			if (items.len() as u64) > free as u64 {
+				return Err(())
+			}
+		}
+
+		let key_prefix =
+			<Self as PagedMapWrapper>::Pages::storage_double_map_final_key1(Ref::from(&key));
+		for item in items {
+			// Note: we use >= here in case someone decreased it in a runtime upgrade.
+			if meta.pages == 0 || meta.last_page_len >= per_page {
+				meta.pages.saturating_inc();
+				meta.last_page_len = 0;
+			}
+			let page_key = page_key(&key_prefix, meta.pages - 1);
+			sp_io::storage::append(&page_key, item.encode());
+			meta.last_page_len.saturating_inc();
+			meta.len.saturating_inc();
+		}
+
+		if meta.pages > 0 {
+			<Self as PagedMapWrapper>::Meta::insert(key, meta);
+		}
+		Ok(())
+	}
+
+	/// Remove all pages of the value under the given key.
+	pub fn remove<KeyArg: EncodeLike<Key>>(key: KeyArg) {
+		let Some(meta) = <Self as PagedMapWrapper>::Meta::take(Ref::from(&key)) else { return };
+		let key_prefix = <Self as PagedMapWrapper>::Pages::storage_double_map_final_key1(key);
+		for page in 0..meta.pages {
+			unhashed::kill(&page_key(&key_prefix, page));
+		}
+	}
+
+	/// Remove the item at position `at` of the value under the given key.
+	///
+	/// The removed item is replaced by the last item of the value, so this only touches the page
+	/// of `at` and the last page. Returns the removed item, or `None` if `at` is out of bounds.
+	pub fn swap_remove<KeyArg: EncodeLike<Key>>(key: KeyArg, at: PagedMapCursor) -> Option<Value> {
+		let mut meta = Self::meta(Ref::from(&key));
+		if at.page >= meta.pages {
+			return None
+		}
+
+		let key_prefix =
+			<Self as PagedMapWrapper>::Pages::storage_double_map_final_key1(Ref::from(&key));
+		let last = meta.pages - 1;
+		let mut last_page =
+			unhashed::get::<Vec<Value>>(&page_key(&key_prefix, last)).unwrap_or_default();
+
+		let removed = if at.page == last {
+			if at.offset as usize >= last_page.len() {
+				return None
+			}
+			last_page.swap_remove(at.offset as usize)
+		} else {
+			let mut page =
+				unhashed::get::<Vec<Value>>(&page_key(&key_prefix, at.page)).unwrap_or_default();
+			if at.offset as usize >= page.len() {
+				return None
+			}
+			let replacement = last_page.pop()?;
+			let removed = core::mem::replace(&mut page[at.offset as usize], replacement);
+			unhashed::put(&page_key(&key_prefix, at.page), &page);
+			removed
+		};
+
+		meta.len.saturating_dec();
+		if last_page.is_empty() {
+			unhashed::kill(&page_key(&key_prefix, last));
+			meta.pages = last;
+			meta.last_page_len = match last {
+				0 => 0,
+				_ => <Self as PagedMapWrapper>::Pages::decode_len(Ref::from(&key), last - 1)
+					.map_or(0, |len| len as u32),
+			};
+		} else {
+			unhashed::put(&page_key(&key_prefix, last), &last_page);
+			meta.last_page_len = last_page.len() as u32;
+		}
+
+		if meta.pages == 0 {
+			<Self as PagedMapWrapper>::Meta::remove(key);
+		} else {
+			<Self as PagedMapWrapper>::Meta::insert(key, meta);
+		}
+		Some(removed)
+	}
+}
+
+impl<Prefix, Hasher, Key, Value, ValuesPerPage, MaxPages, MaxValues>
+	StoragePagedMap<Prefix, Hasher, Key, Value, ValuesPerPage, MaxPages, MaxValues>
+where
+	Prefix: StoragePagedMapInstance,
+	Hasher: StorageHasher + crate::ReversibleStorageHasher,
+	Key: FullCodec,
+	Value: FullCodec,
+	ValuesPerPage: Get<u32>,
+	MaxPages: Get<Option<u32>>,
+	MaxValues: Get<Option<u32>>,
+{
+	/// Enumerate all keys in the map in no particular order.
+	///
+	/// If you alter the map while doing this, you'll get undefined results.
+	pub fn iter_keys() -> crate::storage::KeyPrefixIterator<Key> {
+		<Self as PagedMapWrapper>::Meta::iter_keys()
+	}
+}
+
+/// The storage key of `page` given the final prefix of the map key.
+fn page_key(key_prefix: &[u8], page: PageIndex) -> Vec<u8> {
+	let page_hashed = page.using_encoded(Twox64Concat::hash);
+	let mut final_key = Vec::with_capacity(key_prefix.len() + page_hashed.len());
+	final_key.extend_from_slice(key_prefix);
+	final_key.extend_from_slice(&page_hashed);
+	final_key
+}
+
+/// Iterates over the items stored under a single key of a [`StoragePagedMap`].
+pub struct StoragePagedMapIterator<Value, ValuesPerPage> {
+	/// The final storage prefix of the key, to which the hashed page index is appended.
+	key_prefix: Vec<u8>,
+	meta: PagedMapMeta,
+	/// The position of the next item.
+	cursor: PagedMapCursor,
+	/// The remaining items of the current page, once loaded.
+	page: Option<sp_std::vec::IntoIter<Value>>,
+	_phantom: core::marker::PhantomData<ValuesPerPage>,
+}
+
+impl<Value, ValuesPerPage> StoragePagedMapIterator<Value, ValuesPerPage> {
+	/// The position of the next item, or `None` if the iteration is complete.
+	///
+	/// Pass it to [`StoragePagedMap::iter_from`] to resume the iteration.
+	pub fn cursor(&self) -> Option<PagedMapCursor> {
+		let PagedMapCursor { page, offset } = self.cursor;
+		let exhausted = page >= self.meta.pages ||
+			(page + 1 == self.meta.pages && offset >= self.meta.last_page_len);
+		(!exhausted).then_some(self.cursor)
+	}
+}
+
+impl<Value: FullCodec, ValuesPerPage> Iterator for StoragePagedMapIterator<Value, ValuesPerPage> {
+	type Item = Value;
+
+	fn next(&mut self) -> Option<Value> {
+		loop {
+			if self.page.is_none() {
+				if self.cursor.page >= self.meta.pages {
+					return None
+				}
+				let values =
+					unhashed::get::<Vec<Value>>(&page_key(&self.key_prefix, self.cursor.page))
+						.unwrap_or_default();
+				let mut values = values.into_iter();
+				if self.cursor.offset > 0 {
+					values.nth(self.cursor.offset as usize - 1);
+				}
+				self.page = Some(values);
+			}
+
+			if let Some(value) = self.page.as_mut().and_then(Iterator::next) {
+				self.cursor.offset.saturating_inc();
+				return Some(value)
+			}
+
+			self.page = None;
+			self.cursor = PagedMapCursor { page: self.cursor.page.saturating_add(1), offset: 0 };
+		}
+	}
+}
+
+impl<Prefix, Hasher, Key, Value, ValuesPerPage, MaxPages, MaxValues> StorageEntryMetadataBuilder
+	for StoragePagedMap<Prefix, Hasher, Key, Value, ValuesPerPage, MaxPages, MaxValues>
+where
+	Prefix: StoragePagedMapInstance,
+	Hasher: StorageHasher,
+	Key: FullCodec + scale_info::StaticTypeInfo,
+	Value: FullCodec + scale_info::StaticTypeInfo,
+	ValuesPerPage: Get<u32> + 'static,
+	MaxPages: Get<Option<u32>>,
+	MaxValues: Get<Option<u32>>,
+{
+	fn build_metadata(docs: Vec<&'static str>, entries: &mut Vec<StorageEntryMetadataIR>) {
+		<Self as PagedMapWrapper>::Pages::build_metadata(docs, entries);
+		<Self as PagedMapWrapper>::Meta::build_metadata(
+			if cfg!(feature = "no-metadata-docs") {
+				vec![]
+			} else {
+				vec!["Page bookkeeping for the related paged storage map"]
+			},
+			entries,
+		);
+	}
+}
+
+impl<Prefix, Hasher, Key, Value, ValuesPerPage, MaxPages, MaxValues> crate::traits::StorageInfoTrait
+	for StoragePagedMap<Prefix, Hasher, Key, Value, ValuesPerPage, MaxPages, MaxValues>
+where
+	Prefix: StoragePagedMapInstance,
+	Hasher: StorageHasher,
+	Key: FullCodec + MaxEncodedLen,
+	Value: FullCodec + MaxEncodedLen,
+	ValuesPerPage: Get<u32>,
+	MaxPages: Get<Option<u32>>,
+	MaxValues: Get<Option<u32>>,
+{
+	fn storage_info() -> Vec<StorageInfo> {
+		use crate::traits::StorageInfoTrait;
+		let mut pages = <Self as PagedMapWrapper>::Pages::storage_info();
+		pages.iter_mut().for_each(|info| info.max_values = Self::max_pages_total());
+		[pages, <Self as PagedMapWrapper>::Meta::storage_info()].concat()
+	}
+}
+
+/// It doesn't require to implement `MaxEncodedLen` and give no information for `max_size`.
+impl<Prefix, Hasher, Key, Value, ValuesPerPage, MaxPages, MaxValues>
+	crate::traits::PartialStorageInfoTrait
+	for StoragePagedMap<Prefix, Hasher, Key, Value, ValuesPerPage, MaxPages, MaxValues>
+where
+	Prefix: StoragePagedMapInstance,
+	Hasher: StorageHasher,
+	Key: FullCodec,
+	Value: FullCodec,
+	ValuesPerPage: Get<u32>,
+	MaxPages: Get<Option<u32>>,
+	MaxValues: Get<Option<u32>>,
+{
+	fn partial_storage_info() -> Vec<StorageInfo> {
+		use crate::traits::PartialStorageInfoTrait;
+		let mut pages = <Self as PagedMapWrapper>::Pages::partial_storage_info();
+		pages.iter_mut().for_each(|info| info.max_values = Self::max_pages_total());
+		[pages, <Self as PagedMapWrapper>::Meta::partial_storage_info()].concat()
+	}
+}
+
+impl<Prefix, Hasher, Key, Value, ValuesPerPage, MaxPages, MaxValues>
+	StoragePagedMap<Prefix, Hasher, Key, Value, ValuesPerPage, MaxPages, MaxValues>
+where
+	MaxPages: Get<Option<u32>>,
+	MaxValues: Get<Option<u32>>,
+{
+	/// The maximum number of pages across all keys, if bounded.
+	fn max_pages_total() -> Option<u32> {
+		Some(MaxValues::get()?.saturating_mul(MaxPages::get()?))
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::{
+		hash::*,
+		traits::{ConstU32, StorageInfoTrait},
+	};
+	use sp_io::{hashing::twox_128, TestExternalities};
+
+	struct Prefix;
+	impl StorageInstance for Prefix {
+		fn pallet_prefix() -> &'static str {
+			"test"
+		}
+		const STORAGE_PREFIX: &'static str = "foo";
+	}
+	impl StoragePagedMapInstance for Prefix {
+		type MetaPrefix = MetaPrefix;
+	}
+
+	struct MetaPrefix;
+	impl StorageInstance for MetaPrefix {
+		fn pallet_prefix() -> &'static str {
+			"test"
+		}
+		const STORAGE_PREFIX: &'static str = "PageMetaForfoo";
+	}
+
+	type A = StoragePagedMap<Prefix, Blake2_128Concat, u16, u32, ConstU32<3>>;
+	type B = StoragePagedMap<Prefix, Blake2_128Concat, u16, u32, ConstU32<3>, MaxPages, MaxValues>;
+
+	crate::parameter_types! {
+		pub const MaxPages: Option<u32> = Some(2);
+		pub const MaxValues: Option<u32> = Some(10);
+	}
+
+	#[test]
+	fn append_splits_values_into_pages() {
+		TestExternalities::default().execute_with(|| {
+			assert!(!A::contains_key(1));
+			assert_eq!(A::get(1), Vec::<u32>::new());
+
+			assert_eq!(A::try_extend(1, 0..7u32), Ok(()));
+			assert_eq!(A::try_append(1, 7u32), Ok(()));
+			assert_eq!(A::try_append(2, 100u32), Ok(()));
+
+			assert!(A::contains_key(1));
+			assert_eq!(A::meta(1), PagedMapMeta { pages: 3, last_page_len: 2, len: 8 });
+			assert_eq!(A::page(1, 0).unwrap().into_inner(), vec![0, 1, 2]);
+			assert_eq!(A::page(1, 2).unwrap().into_inner(), vec![6, 7]);
+			assert_eq!(A::page(1, 3), None);
+			assert_eq!(A::get(1), (0..8).collect::<Vec<_>>());
+			assert_eq!(A::get(2), vec![100]);
+
+			let mut keys = A::iter_keys().collect::<Vec<_>>();
+			keys.sort();
+			assert_eq!(keys, vec![1, 2]);
+
+			A::remove(1);
+			assert!(!A::contains_key(1));
+			assert_eq!(A::page(1, 0), None);
+			assert_eq!(A::get(1), Vec::<u32>::new());
+			assert_eq!(A::get(2), vec![100]);
+		});
+	}
+
+	#[test]
+	fn iteration_resumes_from_cursor() {
+		TestExternalities::default().execute_with(|| {
+			assert_eq!(A::try_extend(1, 0..8u32), Ok(()));
+
+			let mut iter = A::iter(1);
+			assert_eq!(iter.cursor(), Some(PagedMapCursor { page: 0, offset: 0 }));
+			assert_eq!(iter.by_ref().take(4).collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+			let cursor = iter.cursor().unwrap();
+			assert_eq!(cursor, PagedMapCursor { page: 1, offset: 1 });
+
+			// Appending in between is picked up by the resumed iteration.
+			assert_eq!(A::try_append(1, 8u32), Ok(()));
+			let mut iter = A::iter_from(1, cursor);
+			assert_eq!(iter.by_ref().collect::<Vec<_>>(), vec![4, 5, 6, 7, 8]);
+			assert_eq!(iter.cursor(), None);
+
+			assert_eq!(A::iter_from(1, PagedMapCursor { page: 5, offset: 0 }).next(), None);
+		});
+	}
+
+	#[test]
+	fn swap_remove_moves_last_item() {
+		TestExternalities::default().execute_with(|| {
+			assert_eq!(A::try_extend(1, 0..7u32), Ok(()));
+			let at = |page, offset| PagedMapCursor { page, offset };
+
+			assert_eq!(A::swap_remove(1, at(0, 3)), None);
+			assert_eq!(A::swap_remove(1, at(2, 1)), None);
+			assert_eq!(A::swap_remove(1, at(3, 0)), None);
+			assert_eq!(A::swap_remove(2, at(0, 0)), None);
+
+			// The single item of the last page moves, and the last page is removed.
+			assert_eq!(A::swap_remove(1, at(0, 1)), Some(1));
+			assert_eq!(A::meta(1), PagedMapMeta { pages: 2, last_page_len: 3, len: 6 });
+			assert_eq!(A::page(1, 2), None);
+			assert_eq!(A::get(1), vec![0, 6, 2, 3, 4, 5]);
+
+			// Removing from the last page itself.
+			assert_eq!(A::swap_remove(1, at(1, 0)), Some(3));
+			assert_eq!(A::meta(1), PagedMapMeta { pages: 2, last_page_len: 2, len: 5 });
+			assert_eq!(A::get(1), vec![0, 6, 2, 5, 4]);
+
+			// Appending continues in the partially filled last page.
+			assert_eq!(A::try_append(1, 7u32), Ok(()));
+			assert_eq!(A::get(1), vec![0, 6, 2, 5, 4, 7]);
+
+			for _ in 0..6 {
+				assert!(A::swap_remove(1, at(0, 0)).is_some());
+			}
+			assert!(!A::contains_key(1));
+			assert_eq!(A::page(1, 0), None);
+		});
+	}
+
+	#[test]
+	fn append_respects_max_pages() {
+		TestExternalities::default().execute_with(|| {
+			assert_eq!(B::try_extend(1, 0..7u32), Err(()));
+			assert!(!B::contains_key(1));
+
+			assert_eq!(B::try_extend(1, 0..5u32), Ok(()));
+			assert_eq!(B::try_append(1, 5u32), Ok(()));
+			assert_eq!(B::try_append(1, 6u32), Err(()));
+			assert_eq!(B::get(1), (0..6).collect::<Vec<_>>());
+		});
+	}
+
+	#[test]
+	fn storage_info_reports_pages() {
+		let info = B::storage_info();
+		assert_eq!(info.len(), 2);
+
+		assert_eq!(info[0].prefix, [twox_128(b"test"), twox_128(b"foo")].concat());
+		assert_eq!(info[0].max_values, Some(20));
+		// (hash + key) + (hash + page index) + (compact length + 3 values).
+		assert_eq!(info[0].max_size, Some(18 + 12 + 1 + 12));
+
+		assert_eq!(info[1].prefix, [twox_128(b"test"), twox_128(b"PageMetaForfoo")].concat());
+		assert_eq!(info[1].max_values, Some(10));
+		assert_eq!(info[1].max_size, Some(18 + 12));
+
+		assert_eq!(A::storage_info()[0].max_values, None);
+	}
+}
@@ -623,6 +623,7 @@ fn expected_metadata() -> PalletStorageMetadataIR {
 				docs: vec![],
 			},
 		],
+		version: None,
 	}
 }
 
@@ -261,6 +261,12 @@ impl Add<u16> for StorageVersion {
 	}
 }
 
+impl From<StorageVersion> for u16 {
+	fn from(version: StorageVersion) -> u16 {
+		version.0
+	}
+}
+
 /// Special marker struct if no storage version is set for a pallet.
 ///
 /// If you (the reader) end up here, it probably means that you tried to compare
@@ -19,9 +19,12 @@
 
 use super::StorageInstance;
 use crate::{
-	storage::types::{
-		CountedStorageMapInstance, CountedStorageNMapInstance, Counter, KeyGenerator,
-		QueryKindTrait,
+	storage::{
+		bounded_vec::BoundedVec,
+		types::{
+			CountedStorageMapInstance, CountedStorageNMapInstance, Counter, KeyGenerator,
+			PagedMapMeta, QueryKindTrait, StoragePagedMapInstance,
+		},
 	},
 	traits::{PartialStorageInfoTrait, StorageInfo},
 	StorageHasher,
@@ -268,6 +271,44 @@ where
 	}
 }
 
+impl<Prefix, Hasher, Key, Value, ValuesPerPage, MaxPages, MaxValues> TryDecodeEntireStorage
+	for crate::storage::types::StoragePagedMap<
+		Prefix,
+		Hasher,
+		Key,
+		Value,
+		ValuesPerPage,
+		MaxPages,
+		MaxValues,
+	> where
+	Prefix: StoragePagedMapInstance,
+	Hasher: StorageHasher,
+	Key: FullCodec,
+	Value: FullCodec,
+	ValuesPerPage: Get<u32>,
+	MaxPages: Get<Option<u32>>,
+	MaxValues: Get<Option<u32>>,
+{
+	fn try_decode_entire_state() -> Result<usize, Vec<TryDecodeEntireStorageError>> {
+		// The pages and the meta of each key, reported as undecodable if the layout changes.
+		let [pages_info, meta_info] = <[StorageInfo; 2]>::try_from(Self::partial_storage_info())
+			.map_err(|infos| {
+				infos
+					.into_iter()
+					.map(|info| TryDecodeEntireStorageError {
+						key: info.prefix.clone(),
+						raw: None,
+						info,
+					})
+					.collect::<Vec<_>>()
+			})?;
+
+		let mut decoded = decode_storage_info::<PagedMapMeta>(meta_info)?;
+		decoded += decode_storage_info::<BoundedVec<Value, ValuesPerPage>>(pages_info)?;
+		Ok(decoded)
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -16,7 +16,11 @@
 // limitations under the License.
 
 use codec::Encode;
-use frame_support::{derive_impl, storage::unhashed, StoragePrefixedMap};
+use frame_support::{
+	derive_impl,
+	storage::{types::PagedMapMeta, unhashed},
+	StoragePrefixedMap,
+};
 use frame_system::pallet_prelude::BlockNumberFor;
 
 use sp_core::{sr25519, ConstU32};
@@ -58,6 +62,9 @@ mod no_instance {
 	pub type DoubleMap2<T> =
 		StorageDoubleMap<_, Twox64Concat, u32, Twox64Concat, u32, u32, ValueQuery>;
 
+	#[pallet::storage]
+	pub type PagedMap<T> = StoragePagedMap<_, Blake2_128Concat, u32, u32, ConstU32<2>>;
+
 	#[pallet::storage]
 	#[pallet::getter(fn test_generic_value)]
 	pub type TestGenericValue<T: Config> = StorageValue<_, BlockNumberFor<T>, OptionQuery>;
@@ -133,6 +140,10 @@ mod instance {
 	pub type DoubleMap2<T: Config<I>, I: 'static = ()> =
 		StorageDoubleMap<_, Twox64Concat, u32, Twox64Concat, u32, u32, ValueQuery>;
 
+	#[pallet::storage]
+	pub type PagedMap<T: Config<I>, I: 'static = ()> =
+		StoragePagedMap<_, Blake2_128Concat, u32, u32, ConstU32<2>>;
+
 	#[pallet::storage]
 	#[pallet::getter(fn test_generic_value)]
 	pub type TestGenericValue<T: Config<I>, I: 'static = ()> =
@@ -259,6 +270,18 @@ fn final_keys_no_instance() {
 		k.extend(2u32.using_encoded(twox_64_concat));
 		assert_eq!(unhashed::get::<u32>(&k), Some(3u32));
 		assert_eq!(&k[..32], &<no_instance::DoubleMap2<Runtime>>::final_prefix());
+
+		<no_instance::PagedMap<Runtime>>::try_extend(1, [2u32, 3, 4]).unwrap();
+		let mut k = [twox_128(b"FinalKeysNone"), twox_128(b"PagedMap")].concat();
+		k.extend(1u32.using_encoded(blake2_128_concat));
+		k.extend(1u32.using_encoded(twox_64_concat));
+		assert_eq!(unhashed::get::<Vec<u32>>(&k), Some(vec![4u32]));
+		let mut k = [twox_128(b"FinalKeysNone"), twox_128(b"PageMetaForPagedMap")].concat();
+		k.extend(1u32.using_encoded(blake2_128_concat));
+		assert_eq!(
+			unhashed::get::<PagedMapMeta>(&k),
+			Some(PagedMapMeta { pages: 2, last_page_len: 1, len: 3 }),
+		);
 	});
 }
 
@@ -294,6 +317,18 @@ fn final_keys_default_instance() {
 		k.extend(2u32.using_encoded(twox_64_concat));
 		assert_eq!(unhashed::get::<u32>(&k), Some(3u32));
 		assert_eq!(&k[..32], &<instance::DoubleMap2<Runtime>>::final_prefix());
+
+		<instance::PagedMap<Runtime>>::try_extend(1, [2u32, 3, 4]).unwrap();
+		let mut k = [twox_128(b"FinalKeysSome"), twox_128(b"PagedMap")].concat();
+		k.extend(1u32.using_encoded(blake2_128_concat));
+		k.extend(1u32.using_encoded(twox_64_concat));
+		assert_eq!(unhashed::get::<Vec<u32>>(&k), Some(vec![4u32]));
+		let mut k = [twox_128(b"FinalKeysSome"), twox_128(b"PageMetaForPagedMap")].concat();
+		k.extend(1u32.using_encoded(blake2_128_concat));
+		assert_eq!(
+			unhashed::get::<PagedMapMeta>(&k),
+			Some(PagedMapMeta { pages: 2, last_page_len: 1, len: 3 }),
+		);
 	});
 }
 
@@ -329,5 +364,18 @@ fn final_keys_instance_2() {
 		k.extend(2u32.using_encoded(twox_64_concat));
 		assert_eq!(unhashed::get::<u32>(&k), Some(3u32));
 		assert_eq!(&k[..32], &<instance::DoubleMap2<Runtime, instance::Instance2>>::final_prefix());
+
+		<instance::PagedMap<Runtime, instance::Instance2>>::try_extend(1, [2u32, 3, 4]).unwrap();
+		let mut k = [twox_128(b"Instance2FinalKeysSome"), twox_128(b"PagedMap")].concat();
+		k.extend(1u32.using_encoded(blake2_128_concat));
+		k.extend(1u32.using_encoded(twox_64_concat));
+		assert_eq!(unhashed::get::<Vec<u32>>(&k), Some(vec![4u32]));
+		let mut k =
+			[twox_128(b"Instance2FinalKeysSome"), twox_128(b"PageMetaForPagedMap")].concat();
+		k.extend(1u32.using_encoded(blake2_128_concat));
+		assert_eq!(
+			unhashed::get::<PagedMapMeta>(&k),
+			Some(PagedMapMeta { pages: 2, last_page_len: 1, len: 3 }),
+		);
 	});
 }
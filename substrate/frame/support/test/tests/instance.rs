@@ -475,6 +475,7 @@ fn expected_metadata() -> PalletStorageMetadataIR {
 				docs: vec![],
 			},
 		],
+		version: None,
 	}
 }
 
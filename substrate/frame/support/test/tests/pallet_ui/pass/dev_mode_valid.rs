@@ -57,6 +57,13 @@ pub mod pallet {
 	#[pallet::storage]
 	type MyCountedStorageMap2<T: Config> = CountedStorageMap<Key = u32, Value = u64>;
 
+	#[pallet::storage]
+	pub type MyStoragePagedMap<T: Config> = StoragePagedMap<_, _, u32, u64, ConstU32<4>>;
+
+	#[pallet::storage]
+	type MyStoragePagedMap2<T: Config> =
+		StoragePagedMap<Key = u32, Value = u64, ValuesPerPage = ConstU32<4>>;
+
 	// Your Pallet's callable functions.
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
@@ -114,7 +121,7 @@ impl pallet::Config for Runtime {}
 fn main() {
 	use frame_support::pallet_prelude::*;
 	use sp_io::{
-		hashing::{blake2_128, twox_128},
+		hashing::{blake2_128, twox_128, twox_64},
 		TestExternalities,
 	};
 	use storage::unhashed;
@@ -130,5 +137,11 @@ fn main() {
 		let mut k = [twox_128(b"Example"), twox_128(b"MyStorageMap")].concat();
 		k.extend(1u32.using_encoded(blake2_128_concat));
 		assert_eq!(unhashed::get::<u64>(&k), Some(2u64));
+
+		pallet::MyStoragePagedMap::<Runtime>::try_append(1, 2u64).unwrap();
+		let mut k = [twox_128(b"Example"), twox_128(b"MyStoragePagedMap")].concat();
+		k.extend(1u32.using_encoded(blake2_128_concat));
+		k.extend(0u32.using_encoded(|d| [&twox_64(d)[..], d].concat()));
+		assert_eq!(unhashed::get::<Vec<u64>>(&k), Some(vec![2u64]));
 	});
 }
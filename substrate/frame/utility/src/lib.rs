@@ -58,7 +58,8 @@ pub mod weights;
 
 use codec::{Decode, Encode};
 use frame_support::{
-	dispatch::{extract_actual_weight, GetDispatchInfo, PostDispatchInfo},
+	dispatch::{GetDispatchInfo, PostDispatchInfo},
+	dispatch_context,
 	traits::{IsSubType, OriginTrait, UnfilteredDispatchable},
 };
 use sp_core::TypeId;
@@ -214,15 +215,16 @@ pub mod pallet {
 			// Track the actual weight of each of the batch calls.
 			let mut weight = Weight::zero();
 			for (index, call) in calls.into_iter().enumerate() {
-				let info = call.get_dispatch_info();
 				// If origin is root, don't apply any dispatch filters; root can call anything.
-				let result = if is_root {
-					call.dispatch_bypass_filter(origin.clone())
-				} else {
-					call.dispatch(origin.clone())
-				};
+				let (result, nested) = dispatch_context::measure_nested(|| {
+					if is_root {
+						call.dispatch_bypass_filter(origin.clone())
+					} else {
+						call.dispatch(origin.clone())
+					}
+				});
 				// Add the weight of this call.
-				weight = weight.saturating_add(extract_actual_weight(&result, &info));
+				weight = weight.saturating_add(nested.actual);
 				if let Err(e) = result {
 					Self::deposit_event(Event::BatchInterrupted {
 						index: index as u32,
@@ -273,13 +275,12 @@ pub mod pallet {
 			let who = ensure_signed(origin.clone())?;
 			let pseudonym = Self::derivative_account_id(who, index);
 			origin.set_caller_from(frame_system::RawOrigin::Signed(pseudonym));
-			let info = call.get_dispatch_info();
-			let result = call.dispatch(origin);
+			let (result, nested) = dispatch_context::measure_nested(|| call.dispatch(origin));
 			// Always take into account the base weight of this call.
 			let mut weight = T::WeightInfo::as_derivative()
 				.saturating_add(T::DbWeight::get().reads_writes(1, 1));
 			// Add the real weight of the dispatch.
-			weight = weight.saturating_add(extract_actual_weight(&result, &info));
+			weight = weight.saturating_add(nested.actual);
 			result
 				.map_err(|mut err| {
 					err.post_info = Some(weight).into();
@@ -336,23 +337,24 @@ pub mod pallet {
 			// Track the actual weight of each of the batch calls.
 			let mut weight = Weight::zero();
 			for (index, call) in calls.into_iter().enumerate() {
-				let info = call.get_dispatch_info();
 				// If origin is root, bypass any dispatch filter; root can call anything.
-				let result = if is_root {
-					call.dispatch_bypass_filter(origin.clone())
-				} else {
-					let mut filtered_origin = origin.clone();
-					// Don't allow users to nest `batch_all` calls.
-					filtered_origin.add_filter(
-						move |c: &<T as frame_system::Config>::RuntimeCall| {
-							let c = <T as Config>::RuntimeCall::from_ref(c);
-							!matches!(c.is_sub_type(), Some(Call::batch_all { .. }))
-						},
-					);
-					call.dispatch(filtered_origin)
-				};
+				let (result, nested) = dispatch_context::measure_nested(|| {
+					if is_root {
+						call.dispatch_bypass_filter(origin.clone())
+					} else {
+						let mut filtered_origin = origin.clone();
+						// Don't allow users to nest `batch_all` calls.
+						filtered_origin.add_filter(
+							move |c: &<T as frame_system::Config>::RuntimeCall| {
+								let c = <T as Config>::RuntimeCall::from_ref(c);
+								!matches!(c.is_sub_type(), Some(Call::batch_all { .. }))
+							},
+						);
+						call.dispatch(filtered_origin)
+					}
+				});
 				// Add the weight of this call.
-				weight = weight.saturating_add(extract_actual_weight(&result, &info));
+				weight = weight.saturating_add(nested.actual);
 				result.map_err(|mut err| {
 					// Take the weight of this function itself into account.
 					let base_weight = T::WeightInfo::batch_all(index.saturating_add(1) as u32);
@@ -447,15 +449,16 @@ pub mod pallet {
 			// Track failed dispatch occur.
 			let mut has_error: bool = false;
 			for call in calls.into_iter() {
-				let info = call.get_dispatch_info();
 				// If origin is root, don't apply any dispatch filters; root can call anything.
-				let result = if is_root {
-					call.dispatch_bypass_filter(origin.clone())
-				} else {
-					call.dispatch(origin.clone())
-				};
+				let (result, nested) = dispatch_context::measure_nested(|| {
+					if is_root {
+						call.dispatch_bypass_filter(origin.clone())
+					} else {
+						call.dispatch(origin.clone())
+					}
+				});
 				// Add the weight of this call.
-				weight = weight.saturating_add(extract_actual_weight(&result, &info));
+				weight = weight.saturating_add(nested.actual);
 				if let Err(e) = result {
 					has_error = true;
 					Self::deposit_event(Event::ItemFailed { error: e.error });
@@ -24,7 +24,7 @@ use super::*;
 use crate as utility;
 use frame_support::{
 	assert_err_ignore_postinfo, assert_noop, assert_ok,
-	dispatch::{DispatchErrorWithPostInfo, Pays},
+	dispatch::{extract_actual_weight, DispatchErrorWithPostInfo, Pays},
 	error::BadOrigin,
 	parameter_types, storage,
 	traits::{ConstU32, ConstU64, Contains},
@@ -15,10 +15,14 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::utils::{
-	extract_block_type_from_trait_path, extract_impl_trait,
-	extract_parameter_names_types_and_borrows, generate_crate_access, return_type_extract_type,
-	AllowSelfRefInParameters, RequireQualifiedTraitPath,
+use crate::{
+	common::API_VERSION_ATTRIBUTE,
+	utils::{
+		extract_block_type_from_trait_path, extract_impl_trait,
+		extract_parameter_names_types_and_borrows, generate_crate_access,
+		parse_runtime_api_version, return_type_extract_type, AllowSelfRefInParameters,
+		RequireQualifiedTraitPath,
+	},
 };
 
 use proc_macro2::{Span, TokenStream};
@@ -30,7 +34,7 @@ use syn::{
 	parse::{Error, Parse, ParseStream, Result},
 	parse_macro_input, parse_quote,
 	spanned::Spanned,
-	Attribute, ItemImpl, Pat, Type, TypePath,
+	Attribute, ItemImpl, Pat, Path, Type, TypePath,
 };
 
 /// The `advanced` attribute.
@@ -61,9 +65,22 @@ impl Parse for RuntimeApiImpls {
 }
 
 /// Implement the `ApiExt` trait and the `Core` runtime api.
-fn implement_common_api_traits(block_type: TypePath, self_ty: Type) -> Result<TokenStream> {
+///
+/// The `ApiExt` implementation reports the given `versions` for the mocked runtime apis and the
+/// version of the declaration for all others.
+fn implement_common_api_traits(
+	block_type: TypePath,
+	self_ty: Type,
+	versions: &[(Path, u32)],
+) -> Result<TokenStream> {
 	let crate_ = generate_crate_access();
 
+	let (traits, versions): (Vec<_>, Vec<_>) = versions.iter().cloned().unzip();
+	let api_version = quote!(
+		#( if A::ID == <dyn #traits as #crate_::RuntimeApiInfo>::ID { #versions } else )*
+		{ A::VERSION }
+	);
+
 	Ok(quote!(
 		impl #crate_::ApiExt<#block_type> for #self_ty {
 			fn execute_in_transaction<F: FnOnce(&Self) -> #crate_::TransactionOutcome<R>, R>(
@@ -85,14 +102,14 @@ fn implement_common_api_traits(block_type: TypePath, self_ty: Type) -> Result<To
 				_: <Block as #crate_::BlockT>::Hash,
 				pred: P,
 			) -> std::result::Result<bool, #crate_::ApiError> where Self: Sized {
-				Ok(pred(A::VERSION))
+				Ok(pred(#api_version))
 			}
 
 			fn api_version<A: #crate_::RuntimeApiInfo + ?Sized>(
 				&self,
 				_: <Block as #crate_::BlockT>::Hash,
 			) -> std::result::Result<Option<u32>, #crate_::ApiError> where Self: Sized {
-				Ok(Some(A::VERSION))
+				Ok(Some(#api_version))
 			}
 
 			fn record_proof(&mut self) {
@@ -358,6 +375,34 @@ struct GeneratedRuntimeApiImpls {
 	block_type: TypePath,
 	/// The type the traits are implemented for.
 	self_ty: Type,
+	/// The traits implemented with an `api_version` attribute and their versions.
+	versions: Vec<(Path, u32)>,
+}
+
+/// Remove the `api_version` attribute from the given attributes and return its version.
+fn take_api_version(attrs: &mut Vec<Attribute>) -> Result<Option<u32>> {
+	let mut version = None;
+	for attr in attrs.iter().filter(|a| a.path().is_ident(API_VERSION_ATTRIBUTE)) {
+		if version.is_some() {
+			return Err(Error::new(
+				attr.span(),
+				format!(
+					"Found multiple #[{}] attributes for an API implementation. \
+					Each runtime API can have only one version.",
+					API_VERSION_ATTRIBUTE
+				),
+			))
+		}
+		version = Some(parse_runtime_api_version(attr)?);
+	}
+	attrs.retain(|a| !a.path().is_ident(API_VERSION_ATTRIBUTE));
+
+	version
+		.map(|v| {
+			u32::try_from(v)
+				.map_err(|_| Error::new(Span::call_site(), "The api version must fit into a `u32`"))
+		})
+		.transpose()
 }
 
 /// Generate the runtime api implementations from the given trait implementations.
@@ -368,6 +413,7 @@ fn generate_runtime_api_impls(impls: &[ItemImpl]) -> Result<GeneratedRuntimeApiI
 	let mut result = Vec::with_capacity(impls.len());
 	let mut global_block_type: Option<TypePath> = None;
 	let mut self_ty: Option<Box<Type>> = None;
+	let mut versions = Vec::new();
 
 	for impl_ in impls {
 		let impl_trait_path = extract_impl_trait(impl_, RequireQualifiedTraitPath::No)?;
@@ -410,13 +456,19 @@ fn generate_runtime_api_impls(impls: &[ItemImpl]) -> Result<GeneratedRuntimeApiI
 			None => Some(block_type.clone()),
 		};
 
-		result.push(FoldRuntimeApiImpl { block_type }.process(impl_.clone()));
+		let mut impl_ = impl_.clone();
+		if let Some(version) = take_api_version(&mut impl_.attrs)? {
+			versions.push((impl_trait_path.clone(), version));
+		}
+
+		result.push(FoldRuntimeApiImpl { block_type }.process(impl_));
 	}
 
 	Ok(GeneratedRuntimeApiImpls {
 		impls: quote!( #( #result )* ),
 		block_type: global_block_type.expect("There is a least one runtime api; qed"),
 		self_ty: *self_ty.expect("There is at least one runtime api; qed"),
+		versions,
 	})
 }
 
@@ -431,9 +483,9 @@ pub fn mock_impl_runtime_apis_impl(input: proc_macro::TokenStream) -> proc_macro
 }
 
 fn mock_impl_runtime_apis_impl_inner(api_impls: &[ItemImpl]) -> Result<TokenStream> {
-	let GeneratedRuntimeApiImpls { impls, block_type, self_ty } =
+	let GeneratedRuntimeApiImpls { impls, block_type, self_ty, versions } =
 		generate_runtime_api_impls(api_impls)?;
-	let api_traits = implement_common_api_traits(block_type, self_ty)?;
+	let api_traits = implement_common_api_traits(block_type, self_ty, &versions)?;
 
 	Ok(quote!(
 		#impls
@@ -508,6 +508,47 @@ pub use sp_api_proc_macro::impl_runtime_apis;
 ///
 /// # fn main() {}
 /// ```
+///
+/// # `api_version` attribute
+///
+/// By default the mocked runtime apis report the version of their declaration through
+/// [`ApiExt`](sp_api::ApiExt). Like with [`impl_runtime_apis!`], an implementation can be
+/// annotated with `#[api_version(N)]` to report version `N` instead, e.g. to test the client
+/// side against methods only available since that version.
+///
+/// ## Example
+/// ```rust
+/// # use sp_api::ApiExt;
+/// # use sp_runtime::traits::Block as BlockT;
+/// # use sp_test_primitives::Block;
+/// #
+/// # sp_api::decl_runtime_apis! {
+/// #     /// Declare the api trait.
+/// #     pub trait Balance {
+/// #         /// Get the balance.
+/// #         fn get_balance() -> u64;
+/// #         /// Reset the balance.
+/// #         #[api_version(2)]
+/// #         fn reset_balance();
+/// #     }
+/// # }
+/// struct MockApi;
+///
+/// sp_api::mock_impl_runtime_apis! {
+///     #[api_version(2)]
+///     impl Balance<Block> for MockApi {
+///         fn get_balance() -> u64 {
+///             0
+///         }
+///         fn reset_balance() {}
+///     }
+/// }
+///
+/// # fn main() {
+/// let version = MockApi.api_version::<dyn Balance<Block>>(Default::default()).unwrap();
+/// assert_eq!(version, Some(2));
+/// # }
+/// ```
 pub use sp_api_proc_macro::mock_impl_runtime_apis;
 
 /// A type that records all accessed trie nodes and generates a proof out of it.
@@ -182,6 +182,13 @@ mock_impl_runtime_apis! {
 	impl ApiWithCustomVersion<Block> for MockApi {
 		fn same_name() {}
 	}
+
+	#[api_version(3)]
+	impl ApiWithMultipleVersions<Block> for MockApi {
+		fn stable_one(_: u64) {}
+
+		fn new_one() {}
+	}
 }
 
 type TestClient = substrate_test_runtime_client::client::Client<
@@ -286,6 +293,25 @@ fn mock_runtime_api_has_api() {
 	assert!(mock.has_api::<dyn Api<Block>>(Hash::default()).unwrap());
 }
 
+#[test]
+fn mock_runtime_api_reports_api_version() {
+	let mock = MockApi { block: None };
+
+	assert_eq!(mock.api_version::<dyn Api<Block>>(Hash::default()).unwrap(), Some(1));
+	assert_eq!(
+		mock.api_version::<dyn ApiWithCustomVersion<Block>>(Hash::default()).unwrap(),
+		Some(2),
+	);
+	assert_eq!(
+		mock.api_version::<dyn ApiWithMultipleVersions<Block>>(Hash::default()).unwrap(),
+		Some(3),
+	);
+	assert!(mock
+		.has_api_with::<dyn ApiWithMultipleVersions<Block>, _>(Hash::default(), |v| v == 3)
+		.unwrap());
+	mock.new_one(Hash::default()).unwrap();
+}
+
 #[test]
 #[should_panic(expected = "Calling deprecated methods is not supported by mocked runtime api.")]
 fn mock_runtime_api_panics_on_calling_old_version() {
@@ -47,7 +47,7 @@ use codec::{Codec, Decode, Encode};
 use scale_info::TypeInfo;
 use sp_application_crypto::RuntimeAppPublic;
 use sp_core::H256;
-use sp_runtime::traits::{Hash, Keccak256, NumberFor};
+use sp_runtime::traits::{Hash, Header as HeaderT, Keccak256, NumberFor};
 use sp_std::prelude::*;
 
 /// Key type for BEEFY module.
@@ -301,6 +301,34 @@ impl<Number, Id, Signature> EquivocationProof<Number, Id, Signature> {
 	}
 }
 
+/// Proof of an authority voting on a block which is not part of the canonical chain.
+///
+/// The fork vote is proven by providing the canonical header at the voted block number and
+/// showing that the MMR root committed to in the vote payload differs from the MMR root found
+/// in the digest of that canonical header.
+#[derive(Clone, Debug, Decode, Encode, PartialEq, TypeInfo)]
+pub struct ForkVotingProof<Header: HeaderT, Id: RuntimeAppPublic> {
+	/// The vote on a block that is not part of the canonical chain.
+	pub vote: VoteMessage<Header::Number, Id, Id::Signature>,
+	/// The canonical header at the block number targeted by the vote.
+	pub canonical_header: Header,
+}
+
+impl<Header: HeaderT, Id: RuntimeAppPublic> ForkVotingProof<Header, Id> {
+	/// Returns the authority id of the misbehaving voter.
+	pub fn offender_id(&self) -> &Id {
+		&self.vote.id
+	}
+	/// Returns the round number at which the fork vote was cast.
+	pub fn round_number(&self) -> &Header::Number {
+		&self.vote.commitment.block_number
+	}
+	/// Returns the set id at which the fork vote was cast.
+	pub fn set_id(&self) -> ValidatorSetId {
+		self.vote.commitment.validator_set_id
+	}
+}
+
 /// Check a commitment signature by encoding the commitment and
 /// verifying the provided signature using the expected authority id.
 pub fn check_commitment_signature<Number, Id, MsgHash>(
@@ -352,6 +380,41 @@ where
 	return valid_first && valid_second
 }
 
+/// Verifies the fork voting proof by making sure that the provided header is the canonical
+/// header (identified by `canonical_hash`) at the voted block number, that the vote commits to
+/// an MMR root different from the one in the canonical header digest and that the vote
+/// signature is valid.
+pub fn check_fork_voting_proof<Header, Id, MsgHash>(
+	proof: &ForkVotingProof<Header, Id>,
+	canonical_hash: &Header::Hash,
+) -> bool
+where
+	Header: HeaderT,
+	Id: BeefyAuthorityId<MsgHash> + PartialEq,
+	MsgHash: Hash,
+{
+	let ForkVotingProof { vote, canonical_header } = proof;
+
+	// the header must be the canonical one at the block number the vote targets
+	if canonical_header.hash() != *canonical_hash ||
+		*canonical_header.number() != vote.commitment.block_number
+	{
+		return false
+	}
+
+	// without an MMR root on both sides there is nothing to compare, and if both roots
+	// match the vote is for the canonical block
+	let canonical_root = mmr::find_header_mmr_root_digest(canonical_header);
+	let voted_root =
+		vote.commitment.payload.get_decoded::<MmrRootHash>(&known_payloads::MMR_ROOT_ID);
+	match (canonical_root, voted_root) {
+		(Some(canonical_root), Some(voted_root)) if canonical_root != voted_root => {},
+		_ => return false,
+	}
+
+	check_commitment_signature(&vote.commitment, &vote.id, &vote.signature)
+}
+
 /// New BEEFY validator set notification hook.
 pub trait OnNewValidatorSet<AuthorityId> {
 	/// Function called by the pallet when BEEFY validator set changes.
@@ -414,6 +477,20 @@ sp_api::decl_runtime_apis! {
 			key_owner_proof: OpaqueKeyOwnershipProof,
 		) -> Option<()>;
 
+		/// Submits an unsigned extrinsic to report a fork vote, i.e. a vote on a
+		/// block which is not part of the canonical chain. The caller must provide
+		/// the fork voting proof and a key ownership proof (should be obtained
+		/// using `generate_key_ownership_proof`). The extrinsic will be unsigned
+		/// and should only be accepted for local authorship (not to be broadcast
+		/// to the network). This method returns `None` when creation of the
+		/// extrinsic fails, e.g. if fork voting reporting is disabled for the
+		/// given runtime. Only useful in an offchain context.
+		#[api_version(4)]
+		fn submit_report_fork_voting_unsigned_extrinsic(
+			fork_voting_proof: ForkVotingProof<Block::Header, AuthorityId>,
+			key_owner_proof: OpaqueKeyOwnershipProof,
+		) -> Option<()>;
+
 		/// Generates a proof of key ownership for the given authority in the
 		/// given set. An example usage of this module is coupled with the
 		/// session historical module to prove that a given authority key is
@@ -135,6 +135,13 @@ pub type BeefyNextAuthoritySet<MerkleRoot> = BeefyAuthoritySet<MerkleRoot>;
 
 /// Extract the MMR root hash from a digest in the given header, if it exists.
 pub fn find_mmr_root_digest<B: Block>(header: &B::Header) -> Option<MmrRootHash> {
+	find_header_mmr_root_digest(header)
+}
+
+/// Extract the MMR root hash from a digest in the given header, if it exists.
+///
+/// Same as [`find_mmr_root_digest`], but usable where only the header type is known.
+pub fn find_header_mmr_root_digest<H: Header>(header: &H) -> Option<MmrRootHash> {
 	let id = OpaqueDigestItemId::Consensus(&BEEFY_ENGINE_ID);
 
 	let filter = |log: ConsensusLog<AuthorityId>| match log {
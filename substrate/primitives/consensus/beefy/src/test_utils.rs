@@ -17,9 +17,13 @@
 
 #![cfg(feature = "std")]
 
-use crate::{ecdsa_crypto, Commitment, EquivocationProof, Payload, ValidatorSetId, VoteMessage};
+use crate::{
+	ecdsa_crypto, Commitment, EquivocationProof, ForkVotingProof, Payload, ValidatorSetId,
+	VoteMessage,
+};
 use codec::Encode;
 use sp_core::{ecdsa, keccak_256, Pair};
+use sp_runtime::traits::Header as HeaderT;
 use std::collections::HashMap;
 use strum::IntoEnumIterator;
 
@@ -91,20 +95,33 @@ impl From<Keyring> for ecdsa_crypto::Public {
 	}
 }
 
+/// Create a new `VoteMessage` signed by `keyring`.
+fn signed_vote(
+	block_number: u64,
+	payload: Payload,
+	validator_set_id: ValidatorSetId,
+	keyring: &Keyring,
+) -> VoteMessage<u64, ecdsa_crypto::Public, ecdsa_crypto::Signature> {
+	let commitment = Commitment { validator_set_id, block_number, payload };
+	let signature = keyring.sign(&commitment.encode());
+	VoteMessage { commitment, id: keyring.public(), signature }
+}
+
 /// Create a new `EquivocationProof` based on given arguments.
 pub fn generate_equivocation_proof(
 	vote1: (u64, Payload, ValidatorSetId, &Keyring),
 	vote2: (u64, Payload, ValidatorSetId, &Keyring),
 ) -> EquivocationProof<u64, ecdsa_crypto::Public, ecdsa_crypto::Signature> {
-	let signed_vote = |block_number: u64,
-	                   payload: Payload,
-	                   validator_set_id: ValidatorSetId,
-	                   keyring: &Keyring| {
-		let commitment = Commitment { validator_set_id, block_number, payload };
-		let signature = keyring.sign(&commitment.encode());
-		VoteMessage { commitment, id: keyring.public(), signature }
-	};
 	let first = signed_vote(vote1.0, vote1.1, vote1.2, vote1.3);
 	let second = signed_vote(vote2.0, vote2.1, vote2.2, vote2.3);
 	EquivocationProof { first, second }
 }
+
+/// Create a new `ForkVotingProof` based on given arguments.
+pub fn generate_fork_voting_proof<Header: HeaderT<Number = u64>>(
+	vote: (u64, Payload, ValidatorSetId, &Keyring),
+	canonical_header: Header,
+) -> ForkVotingProof<Header, ecdsa_crypto::Public> {
+	let vote = signed_vote(vote.0, vote.1, vote.2, vote.3);
+	ForkVotingProof { vote, canonical_header }
+}
@@ -23,8 +23,8 @@
 
 use scale_codec::{Decode, Encode, MaxEncodedLen};
 use scale_info::TypeInfo;
-use sp_core::crypto::KeyTypeId;
-use sp_runtime::{ConsensusEngineId, RuntimeDebug};
+use sp_core::crypto::{KeyTypeId, VrfPublic};
+use sp_runtime::{traits::Header, ConsensusEngineId, RuntimeDebug};
 use sp_std::vec::Vec;
 
 pub use sp_consensus_slots::{Slot, SlotDuration};
@@ -121,6 +121,94 @@ pub struct Epoch {
 #[repr(transparent)]
 pub struct OpaqueKeyOwnershipProof(Vec<u8>);
 
+impl OpaqueKeyOwnershipProof {
+	/// Create a new `OpaqueKeyOwnershipProof` using the given encoded representation.
+	pub fn new(inner: Vec<u8>) -> OpaqueKeyOwnershipProof {
+		OpaqueKeyOwnershipProof(inner)
+	}
+
+	/// Try to decode this `OpaqueKeyOwnershipProof` into the given concrete key
+	/// ownership proof type.
+	pub fn decode<T: Decode>(self) -> Option<T> {
+		Decode::decode(&mut &self.0[..]).ok()
+	}
+}
+
+/// Verify a plain (i.e. non-VRF) signature produced by a Sassafras authority.
+///
+/// The `RuntimeAppPublic` implementation for Bandersnatch keys doesn't support
+/// verification yet, thus the check is performed directly via the VRF primitives.
+pub fn verify_authority_signature(
+	public: &AuthorityId,
+	signature: &AuthoritySignature,
+	data: &[u8],
+) -> bool {
+	use sp_core::bandersnatch;
+	let data = vrf::VrfSignData::new_unchecked(bandersnatch::SIGNING_CTX, &[data], None);
+	let signature = vrf::VrfSignature {
+		signature: *AsRef::<bandersnatch::Signature>::as_ref(signature),
+		outputs: Default::default(),
+	};
+	AsRef::<bandersnatch::Public>::as_ref(public).vrf_verify(&data, &signature)
+}
+
+/// Verifies the equivocation proof by making sure that: both headers have
+/// different hashes, are targetting the same slot, and have valid signatures by
+/// the same authority.
+pub fn check_equivocation_proof<H>(proof: EquivocationProof<H>) -> bool
+where
+	H: Header,
+{
+	let find_slot_claim = |header: &H| {
+		header
+			.digest()
+			.logs()
+			.iter()
+			.find_map(|log| digests::SlotClaim::try_from(log).ok())
+	};
+
+	let verify_seal_signature = |mut header: H, offender: &AuthorityId| {
+		let seal = AuthoritySignature::try_from(&header.digest_mut().pop()?).ok()?;
+		let pre_hash = header.hash();
+
+		if !verify_authority_signature(offender, &seal, pre_hash.as_ref()) {
+			return None
+		}
+
+		Some(())
+	};
+
+	let verify_proof = || {
+		// we must have different headers for the equivocation to be valid
+		if proof.first_header.hash() == proof.second_header.hash() {
+			return None
+		}
+
+		let first_claim = find_slot_claim(&proof.first_header)?;
+		let second_claim = find_slot_claim(&proof.second_header)?;
+
+		// both headers must be targetting the same slot and it must
+		// be the same as the one in the proof.
+		if proof.slot != first_claim.slot || first_claim.slot != second_claim.slot {
+			return None
+		}
+
+		// both headers must have been authored by the same authority
+		if first_claim.authority_idx != second_claim.authority_idx {
+			return None
+		}
+
+		// we finally verify that the expected authority has signed both headers and
+		// that the signature is valid.
+		verify_seal_signature(proof.first_header, &proof.offender)?;
+		verify_seal_signature(proof.second_header, &proof.offender)?;
+
+		Some(())
+	};
+
+	verify_proof().is_some()
+}
+
 // Runtime API.
 sp_api::decl_runtime_apis! {
 	/// API necessary for block authorship with Sassafras.
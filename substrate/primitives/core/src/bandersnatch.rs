@@ -674,6 +674,22 @@ pub mod ring_vrf {
 
 	impl EncodeLike for RingContext {}
 
+	impl PartialEq for RingContext {
+		fn eq(&self, other: &Self) -> bool {
+			self.encode() == other.encode()
+		}
+	}
+
+	impl Eq for RingContext {}
+
+	impl sp_std::fmt::Debug for RingContext {
+		fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+			f.debug_struct("RingContext")
+				.field("max_keyset_size", &self.max_keyset_size())
+				.finish()
+		}
+	}
+
 	impl MaxEncodedLen for RingContext {
 		fn max_encoded_len() -> usize {
 			<[u8; RING_CONTEXT_SERIALIZED_LEN]>::max_encoded_len()
@@ -19,10 +19,17 @@
 //!
 //! Namely all ExecutionExtensions that allow mocking
 //! the extra APIs.
+//!
+//! HTTP requests can either be expected one by one in order with
+//! [`OffchainState::expect_request`], or be answered by [`HttpRoute`]s matching any number of
+//! requests with [`OffchainState::add_route`]. Routes may simulate latency and timeouts against the
+//! simulated time of [`OffchainState::timestamp`]. The requests made since the last call to
+//! [`OffchainState::take_requests`] are returned by it, so that they can be checked after every
+//! block.
 
 use crate::{
 	offchain::{
-		self, storage::InMemOffchainStorage, HttpError, HttpRequestId as RequestId,
+		self, storage::InMemOffchainStorage, Duration, HttpError, HttpRequestId as RequestId,
 		HttpRequestStatus as RequestStatus, OffchainOverlayedChange, OffchainStorage,
 		OpaqueNetworkState, StorageKind, Timestamp, TransactionPool,
 	},
@@ -58,6 +65,118 @@ pub struct PendingRequest {
 	pub response_headers: Vec<(String, String)>,
 }
 
+/// HTTP request made by an offchain worker.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecordedRequest {
+	/// HTTP method
+	pub method: String,
+	/// URI
+	pub uri: String,
+	/// Request headers
+	pub headers: Vec<(String, String)>,
+	/// Request body
+	pub body: Vec<u8>,
+}
+
+/// Response of an [`HttpRoute`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockResponse {
+	/// Status code.
+	pub status: u16,
+	/// Response headers.
+	pub headers: Vec<(String, String)>,
+	/// Response body.
+	pub body: Vec<u8>,
+	/// Time passing before the response is received, `None` if it never is.
+	pub latency: Option<Duration>,
+}
+
+impl MockResponse {
+	/// Immediate `200 OK` response with the given body.
+	pub fn ok(body: impl Into<Vec<u8>>) -> Self {
+		Self {
+			status: 200,
+			headers: Vec::new(),
+			body: body.into(),
+			latency: Some(Duration::from_millis(0)),
+		}
+	}
+
+	/// Response never being received, so that waiting for it reaches the deadline.
+	pub fn timeout() -> Self {
+		Self { latency: None, ..Self::ok(Vec::new()) }
+	}
+
+	/// Set the status code.
+	pub fn with_status(mut self, status: u16) -> Self {
+		self.status = status;
+		self
+	}
+
+	/// Add a response header.
+	pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+		self.headers.push((name.into(), value.into()));
+		self
+	}
+
+	/// Set the time passing before the response is received.
+	pub fn with_latency(mut self, latency: Duration) -> Self {
+		self.latency = Some(latency);
+		self
+	}
+}
+
+/// Answers the HTTP requests whose URI matches a pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpRoute {
+	method: Option<String>,
+	uri: String,
+	response: MockResponse,
+}
+
+impl HttpRoute {
+	/// Route answering requests with any method whose URI matches `uri` with `response`.
+	///
+	/// A `*` in `uri` matches any sequence of characters, e.g. `https://api.example.com/price/*`.
+	pub fn new(uri: impl Into<String>, response: MockResponse) -> Self {
+		Self { method: None, uri: uri.into(), response }
+	}
+
+	/// Only answer requests with the given method.
+	pub fn with_method(mut self, method: impl Into<String>) -> Self {
+		self.method = Some(method.into());
+		self
+	}
+
+	/// Whether the route answers a request with `method` to `uri`.
+	pub fn matches(&self, method: &str, uri: &str) -> bool {
+		self.method.as_ref().map_or(true, |m| m.eq_ignore_ascii_case(method)) &&
+			matches_pattern(self.uri.as_bytes(), uri.as_bytes())
+	}
+
+	/// The response of the route.
+	pub fn response(&self) -> &MockResponse {
+		&self.response
+	}
+}
+
+/// Whether `text` matches `pattern`, in which `*` matches any sequence of characters.
+fn matches_pattern(pattern: &[u8], text: &[u8]) -> bool {
+	match pattern.split_first() {
+		None => text.is_empty(),
+		Some((b'*', rest)) => (0..=text.len()).any(|skip| matches_pattern(rest, &text[skip..])),
+		Some((c, rest)) => text.first() == Some(c) && matches_pattern(rest, &text[1..]),
+	}
+}
+
+/// Status of a request answered by a route.
+#[derive(Debug)]
+struct RoutedResponse {
+	status: u16,
+	/// When the response is received, `None` if never.
+	ready_at: Option<Timestamp>,
+}
+
 /// Sharable "persistent" offchain storage for test.
 #[derive(Debug, Clone, Default)]
 pub struct TestPersistentOffchainDB {
@@ -126,6 +245,12 @@ pub struct OffchainState {
 	pub requests: BTreeMap<RequestId, PendingRequest>,
 	// Queue of requests that the test is expected to perform (in order).
 	expected_requests: VecDeque<PendingRequest>,
+	// Routes answering the requests which are not expected, the first matching one is used.
+	routes: Vec<HttpRoute>,
+	// Responses of the pending requests answered by a route.
+	routed: BTreeMap<RequestId, RoutedResponse>,
+	// Requests made since the last `take_requests`.
+	made_requests: Vec<RecordedRequest>,
 	/// Persistent local storage
 	pub persistent_storage: TestPersistentOffchainDB,
 	/// Local storage
@@ -165,6 +290,45 @@ impl OffchainState {
 		}
 	}
 
+	/// Record the sent request and answer it, by the expected requests first and the routes
+	/// otherwise.
+	fn on_request_sent(&mut self, id: RequestId) {
+		let Some(req) = self.requests.get_mut(&id) else { return };
+		self.made_requests.push(RecordedRequest {
+			method: req.method.clone(),
+			uri: req.uri.clone(),
+			headers: req.headers.clone(),
+			body: req.body.clone(),
+		});
+
+		if !self.expected_requests.is_empty() {
+			return self.fulfill_expected(id.0)
+		}
+
+		let Some(route) = self.routes.iter().find(|route| route.matches(&req.method, &req.uri))
+		else {
+			return
+		};
+		let response = route.response();
+		req.response = Some(response.body.clone());
+		req.response_headers = response.headers.clone();
+		let ready_at = response.latency.map(|latency| self.timestamp.add(latency));
+		self.routed.insert(id, RoutedResponse { status: response.status, ready_at });
+	}
+
+	/// Add a route answering the requests matching it which are not expected with
+	/// [`Self::expect_request`].
+	///
+	/// Routes are matched in the insertion order.
+	pub fn add_route(&mut self, route: HttpRoute) {
+		self.routes.push(route);
+	}
+
+	/// Return the requests made since the last call.
+	pub fn take_requests(&mut self) -> Vec<RecordedRequest> {
+		std::mem::take(&mut self.made_requests)
+	}
+
 	/// Add expected HTTP request.
 	///
 	/// This method can be used to initialize expected HTTP requests and their responses
@@ -285,7 +449,7 @@ impl offchain::Externalities for TestOffchainExt {
 		};
 
 		if sent {
-			state.fulfill_expected(request_id.0);
+			state.on_request_sent(request_id);
 		}
 
 		Ok(())
@@ -294,19 +458,41 @@ impl offchain::Externalities for TestOffchainExt {
 	fn http_response_wait(
 		&mut self,
 		ids: &[RequestId],
-		_deadline: Option<Timestamp>,
+		deadline: Option<Timestamp>,
 	) -> Vec<RequestStatus> {
-		let state = self.0.read();
-
-		ids.iter()
-			.map(|id| match state.requests.get(id) {
-				Some(req) if req.response.is_none() => {
+		let mut state = self.0.write();
+		let mut now = state.timestamp;
+
+		let statuses = ids
+			.iter()
+			.map(|id| match (state.requests.get(id), state.routed.get(id)) {
+				(None, _) => RequestStatus::Invalid,
+				(Some(_), Some(RoutedResponse { status, ready_at })) =>
+					match (ready_at, deadline) {
+						(Some(ready_at), Some(deadline)) if *ready_at > deadline => {
+							now = now.max(deadline);
+							RequestStatus::DeadlineReached
+						},
+						(Some(ready_at), _) => {
+							now = now.max(*ready_at);
+							RequestStatus::Finished(*status)
+						},
+						(None, Some(deadline)) => {
+							now = now.max(deadline);
+							RequestStatus::DeadlineReached
+						},
+						// Would wait forever.
+						(None, None) => RequestStatus::IoError,
+					},
+				(Some(req), None) if req.response.is_none() => {
 					panic!("No `response` provided for request with id: {:?}", id)
 				},
-				None => RequestStatus::Invalid,
-				_ => RequestStatus::Finished(200),
+				(Some(_), None) => RequestStatus::Finished(200),
 			})
-			.collect()
+			.collect();
+
+		state.timestamp = now;
+		statuses
 	}
 
 	fn http_response_headers(&mut self, request_id: RequestId) -> Vec<(Vec<u8>, Vec<u8>)> {
@@ -329,6 +515,9 @@ impl offchain::Externalities for TestOffchainExt {
 		_deadline: Option<Timestamp>,
 	) -> Result<usize, HttpError> {
 		let mut state = self.0.write();
+		if state.routed.get(&request_id).map_or(false, |routed| routed.ready_at.is_none()) {
+			return Err(HttpError::DeadlineReached)
+		}
 		if let Some(req) = state.requests.get_mut(&request_id) {
 			let response = req
 				.response
@@ -338,6 +527,7 @@ impl offchain::Externalities for TestOffchainExt {
 			if req.read >= response.len() {
 				// Remove the pending request as per spec.
 				state.requests.remove(&request_id);
+				state.routed.remove(&request_id);
 				Ok(0)
 			} else {
 				let read = std::cmp::min(buffer.len(), response[req.read..].len());
@@ -431,3 +621,82 @@ impl TransactionPool for TestTransactionPoolExt {
 		Ok(())
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use offchain::Externalities;
+
+	fn get(ext: &mut TestOffchainExt, uri: &str) -> RequestId {
+		let id = ext.http_request_start("GET", uri, &[]).unwrap();
+		ext.http_request_write_body(id, &[], None).unwrap();
+		id
+	}
+
+	#[test]
+	fn routes_answer_matching_requests() {
+		let (mut ext, state) = TestOffchainExt::new();
+		state.write().add_route(HttpRoute::new(
+			"https://example.com/price/*",
+			MockResponse::ok(b"42".to_vec()).with_header("Content-Type", "text/plain"),
+		));
+		state
+			.write()
+			.add_route(HttpRoute::new("*", MockResponse::ok(Vec::new()).with_status(404)));
+
+		let price = get(&mut ext, "https://example.com/price/dot");
+		let other = get(&mut ext, "https://example.com/other");
+		assert_eq!(
+			ext.http_response_wait(&[price, other], None),
+			vec![RequestStatus::Finished(200), RequestStatus::Finished(404)]
+		);
+		assert_eq!(
+			ext.http_response_headers(price),
+			vec![(b"Content-Type".to_vec(), b"text/plain".to_vec())]
+		);
+		let mut buf = [0; 8];
+		assert_eq!(ext.http_response_read_body(price, &mut buf, None), Ok(2));
+		assert_eq!(&buf[..2], b"42");
+
+		let requests = state.write().take_requests();
+		assert_eq!(
+			requests.iter().map(|r| r.uri.as_str()).collect::<Vec<_>>(),
+			vec!["https://example.com/price/dot", "https://example.com/other"]
+		);
+		assert!(state.write().take_requests().is_empty());
+	}
+
+	#[test]
+	fn routes_simulate_latency_and_timeouts() {
+		let (mut ext, state) = TestOffchainExt::new();
+		state.write().add_route(HttpRoute::new(
+			"https://slow.com/*",
+			MockResponse::ok(Vec::new()).with_latency(Duration::from_millis(500)),
+		));
+		state
+			.write()
+			.add_route(HttpRoute::new("https://down.com/*", MockResponse::timeout()));
+
+		let slow = get(&mut ext, "https://slow.com/");
+		let deadline = ext.timestamp().add(Duration::from_millis(100));
+		assert_eq!(
+			ext.http_response_wait(&[slow], Some(deadline)),
+			vec![RequestStatus::DeadlineReached]
+		);
+		assert_eq!(ext.timestamp(), deadline);
+		assert_eq!(ext.http_response_wait(&[slow], None), vec![RequestStatus::Finished(200)]);
+		assert_eq!(ext.timestamp(), Timestamp::from_unix_millis(500));
+
+		let down = get(&mut ext, "https://down.com/");
+		let deadline = ext.timestamp().add(Duration::from_millis(1000));
+		assert_eq!(
+			ext.http_response_wait(&[down], Some(deadline)),
+			vec![RequestStatus::DeadlineReached]
+		);
+		assert_eq!(ext.http_response_wait(&[down], None), vec![RequestStatus::IoError]);
+		assert_eq!(
+			ext.http_response_read_body(down, &mut [0; 8], None),
+			Err(HttpError::DeadlineReached)
+		);
+	}
+}
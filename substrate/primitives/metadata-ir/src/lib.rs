@@ -30,6 +30,7 @@ pub use types::*;
 
 mod v14;
 mod v15;
+pub use v15::{EXTENSION_VERSIONS_KEY, STORAGE_VERSIONS_KEY};
 
 /// Metadata V14.
 const V14: u32 = 14;
@@ -71,8 +72,9 @@ pub fn into_v14(metadata: MetadataIR) -> RuntimeMetadataPrefixed {
 #[cfg(test)]
 mod test {
 	use super::*;
+	use codec::{Compact, Decode};
 	use frame_metadata::{v14::META_RESERVED, RuntimeMetadata};
-	use scale_info::meta_type;
+	use scale_info::{meta_type, TypeDef, TypeDefPrimitive};
 
 	fn ir_metadata() -> MetadataIR {
 		MetadataIR {
@@ -85,6 +87,7 @@ mod test {
 				signature_ty: meta_type::<()>(),
 				extra_ty: meta_type::<()>(),
 				signed_extensions: vec![],
+				extension_versions: vec![],
 			},
 			ty: meta_type::<()>(),
 			apis: vec![],
@@ -115,4 +118,65 @@ mod test {
 
 		assert!(matches!(metadata.1, RuntimeMetadata::V15(_)));
 	}
+
+	#[test]
+	fn extension_versions_are_custom_metadata_of_v15() {
+		let metadata: frame_metadata::v15::RuntimeMetadataV15 = ir_metadata().into();
+		assert!(metadata.custom.map.is_empty());
+
+		let mut ir = ir_metadata();
+		ir.extrinsic.extension_versions = vec![ExtensionVersionMetadataIR {
+			version: 3,
+			extensions: vec![SignedExtensionMetadataIR {
+				identifier: "CheckNonce",
+				ty: meta_type::<u64>(),
+				additional_signed: meta_type::<()>(),
+			}],
+		}];
+		let metadata: frame_metadata::v15::RuntimeMetadataV15 = ir.into();
+
+		let entry = &metadata.custom.map[EXTENSION_VERSIONS_KEY];
+		assert!(metadata.types.resolve(entry.ty.id).is_some());
+		let versions =
+			Vec::<(u8, Vec<(String, Compact<u32>, Compact<u32>)>)>::decode(&mut &entry.value[..])
+				.unwrap();
+		assert_eq!(versions.len(), 1);
+		let (version, extensions) = &versions[0];
+		assert_eq!(*version, 3);
+		assert_eq!(extensions.len(), 1);
+		assert_eq!(extensions[0].0, "CheckNonce");
+		assert_eq!(
+			metadata.types.resolve(extensions[0].1 .0).unwrap().type_def,
+			TypeDef::Primitive(TypeDefPrimitive::U64),
+		);
+	}
+
+	#[test]
+	fn storage_versions_are_custom_metadata_of_v15() {
+		let storage =
+			|prefix, version| PalletStorageMetadataIR { prefix, entries: vec![], version };
+		let pallet = |name, storage| PalletMetadataIR {
+			name,
+			storage,
+			calls: None,
+			event: None,
+			constants: vec![],
+			error: None,
+			index: 0,
+			docs: vec![],
+		};
+
+		let mut ir = ir_metadata();
+		ir.pallets = vec![
+			pallet("Versioned", Some(storage("Versioned", Some(3)))),
+			pallet("Unversioned", Some(storage("Unversioned", None))),
+			pallet("Stateless", None),
+		];
+		let metadata: frame_metadata::v15::RuntimeMetadataV15 = ir.into();
+
+		let entry = &metadata.custom.map[STORAGE_VERSIONS_KEY];
+		assert!(metadata.types.resolve(entry.ty.id).is_some());
+		let versions = Vec::<(String, u16)>::decode(&mut &entry.value[..]).unwrap();
+		assert_eq!(versions, vec![("Versioned".into(), 3), ("Unversioned".into(), 0)]);
+	}
 }
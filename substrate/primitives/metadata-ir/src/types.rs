@@ -170,6 +170,11 @@ pub struct ExtrinsicMetadataIR<T: Form = MetaForm> {
 	pub extra_ty: T::Type,
 	/// The signed extensions in the order they appear in the extrinsic.
 	pub signed_extensions: Vec<SignedExtensionMetadataIR<T>>,
+	/// The versions of transaction extensions general transactions can be built with.
+	///
+	/// Note: Field not part of metadata V14. Metadata V15 exposes it through its custom metadata
+	/// under [`crate::EXTENSION_VERSIONS_KEY`].
+	pub extension_versions: Vec<ExtensionVersionMetadataIR<T>>,
 }
 
 impl IntoPortable for ExtrinsicMetadataIR {
@@ -184,6 +189,27 @@ impl IntoPortable for ExtrinsicMetadataIR {
 			signature_ty: registry.register_type(&self.signature_ty),
 			extra_ty: registry.register_type(&self.extra_ty),
 			signed_extensions: registry.map_into_portable(self.signed_extensions),
+			extension_versions: registry.map_into_portable(self.extension_versions),
+		}
+	}
+}
+
+/// Metadata of one version of transaction extensions of general transactions.
+#[derive(Clone, PartialEq, Eq, Encode, Debug)]
+pub struct ExtensionVersionMetadataIR<T: Form = MetaForm> {
+	/// The extension version carried by general transactions built with these extensions.
+	pub version: u8,
+	/// The transaction extensions in the order they appear in the extrinsic.
+	pub extensions: Vec<SignedExtensionMetadataIR<T>>,
+}
+
+impl IntoPortable for ExtensionVersionMetadataIR {
+	type Output = ExtensionVersionMetadataIR<PortableForm>;
+
+	fn into_portable(self, registry: &mut Registry) -> Self::Output {
+		ExtensionVersionMetadataIR {
+			version: self.version,
+			extensions: registry.map_into_portable(self.extensions),
 		}
 	}
 }
@@ -219,6 +245,8 @@ pub struct PalletStorageMetadataIR<T: Form = MetaForm> {
 	pub prefix: T::String,
 	/// Metadata for all storage entries.
 	pub entries: Vec<StorageEntryMetadataIR<T>>,
+	/// The storage version declared by the pallet, if any.
+	pub version: Option<u16>,
 }
 
 impl IntoPortable for PalletStorageMetadataIR {
@@ -228,6 +256,7 @@ impl IntoPortable for PalletStorageMetadataIR {
 		PalletStorageMetadataIR {
 			prefix: self.prefix.into_portable(registry),
 			entries: registry.map_into_portable(self.entries),
+			version: self.version,
 		}
 	}
 }
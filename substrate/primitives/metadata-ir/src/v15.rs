@@ -20,31 +20,118 @@
 use crate::OuterEnumsIR;
 
 use super::types::{
-	ExtrinsicMetadataIR, MetadataIR, PalletMetadataIR, RuntimeApiMetadataIR,
-	RuntimeApiMethodMetadataIR, RuntimeApiMethodParamMetadataIR, SignedExtensionMetadataIR,
+	ExtensionVersionMetadataIR, ExtrinsicMetadataIR, MetadataIR, PalletMetadataIR,
+	RuntimeApiMetadataIR, RuntimeApiMethodMetadataIR, RuntimeApiMethodParamMetadataIR,
+	SignedExtensionMetadataIR,
 };
 
+use codec::{Compact, Encode};
 use frame_metadata::v15::{
-	CustomMetadata, ExtrinsicMetadata, OuterEnums, PalletMetadata, RuntimeApiMetadata,
-	RuntimeApiMethodMetadata, RuntimeApiMethodParamMetadata, RuntimeMetadataV15,
-	SignedExtensionMetadata,
+	CustomMetadata, CustomValueMetadata, ExtrinsicMetadata, OuterEnums, PalletMetadata,
+	RuntimeApiMetadata, RuntimeApiMethodMetadata, RuntimeApiMethodParamMetadata,
+	RuntimeMetadataV15, SignedExtensionMetadata,
 };
+use scale_info::{meta_type, IntoPortable, Registry};
+use sp_std::{collections::btree_map::BTreeMap, prelude::*, string::String};
+
+/// The key of the custom metadata entry listing the transaction extension versions.
+///
+/// The entry is only present if the runtime supports general transactions. Its value is a list of
+/// the supported extension versions, each with the identifier, type id and implicit type id of
+/// its extensions in the order they appear in the transaction.
+pub const EXTENSION_VERSIONS_KEY: &str = "extension_versions";
+
+/// The type of the value of the [`EXTENSION_VERSIONS_KEY`] custom metadata entry.
+type ExtensionVersions = Vec<(u8, Vec<(String, Compact<u32>, Compact<u32>)>)>;
+
+/// The key of the custom metadata entry listing the storage versions of the pallets.
+///
+/// The entry is only present if at least one pallet has storage. Its value is a list of the
+/// storage prefix and the storage version of every pallet with storage, the latter being `0` if
+/// the pallet doesn't declare a storage version.
+pub const STORAGE_VERSIONS_KEY: &str = "storage_versions";
+
+/// The type of the value of the [`STORAGE_VERSIONS_KEY`] custom metadata entry.
+type StorageVersions = Vec<(String, u16)>;
 
 impl From<MetadataIR> for RuntimeMetadataV15 {
-	fn from(ir: MetadataIR) -> Self {
-		RuntimeMetadataV15::new(
-			ir.pallets.into_iter().map(Into::into).collect(),
-			ir.extrinsic.into(),
-			ir.ty,
-			ir.apis.into_iter().map(Into::into).collect(),
-			ir.outer_enums.into(),
-			// Substrate does not collect yet the custom metadata fields.
-			// This allows us to extend the V15 easily.
-			CustomMetadata { map: Default::default() },
-		)
+	fn from(mut ir: MetadataIR) -> Self {
+		// This follows `RuntimeMetadataV15::new`, but keeps the registry around to refer to the
+		// types of the extension versions from the custom metadata.
+		let mut registry = Registry::new();
+		let extension_versions = sp_std::mem::take(&mut ir.extrinsic.extension_versions);
+		let storage_versions: StorageVersions = ir
+			.pallets
+			.iter()
+			.filter_map(|pallet| pallet.storage.as_ref())
+			.map(|storage| (storage.prefix.into(), storage.version.unwrap_or_default()))
+			.collect();
+		let pallets =
+			registry.map_into_portable(ir.pallets.into_iter().map(Into::<PalletMetadata>::into));
+		let extrinsic = ExtrinsicMetadata::from(ir.extrinsic).into_portable(&mut registry);
+		let ty = registry.register_type(&ir.ty);
+		let apis =
+			registry.map_into_portable(ir.apis.into_iter().map(Into::<RuntimeApiMetadata>::into));
+		let outer_enums = OuterEnums::from(ir.outer_enums).into_portable(&mut registry);
+
+		// Substrate does not collect yet other custom metadata fields.
+		// This allows us to extend the V15 easily.
+		let mut custom = CustomMetadata { map: BTreeMap::new() };
+		if !extension_versions.is_empty() {
+			let value = extension_versions_value(extension_versions, &mut registry);
+			custom.map.insert(
+				EXTENSION_VERSIONS_KEY.into(),
+				CustomValueMetadata {
+					ty: registry.register_type(&meta_type::<ExtensionVersions>()),
+					value: value.encode(),
+				},
+			);
+		}
+		if !storage_versions.is_empty() {
+			custom.map.insert(
+				STORAGE_VERSIONS_KEY.into(),
+				CustomValueMetadata {
+					ty: registry.register_type(&meta_type::<StorageVersions>()),
+					value: storage_versions.encode(),
+				},
+			);
+		}
+
+		RuntimeMetadataV15 {
+			types: registry.into(),
+			pallets,
+			extrinsic,
+			ty,
+			apis,
+			outer_enums,
+			custom,
+		}
 	}
 }
 
+/// Register the types of the given extension versions and build the value of their custom
+/// metadata entry.
+fn extension_versions_value(
+	versions: Vec<ExtensionVersionMetadataIR>,
+	registry: &mut Registry,
+) -> ExtensionVersions {
+	versions
+		.into_iter()
+		.map(|version| {
+			let extensions = version
+				.extensions
+				.into_iter()
+				.map(|extension| {
+					let ty = registry.register_type(&extension.ty).id;
+					let implicit = registry.register_type(&extension.additional_signed).id;
+					(extension.identifier.into(), Compact(ty), Compact(implicit))
+				})
+				.collect();
+			(version.version, extensions)
+		})
+		.collect()
+}
+
 impl From<RuntimeApiMetadataIR> for RuntimeApiMetadata {
 	fn from(ir: RuntimeApiMetadataIR) -> Self {
 		RuntimeApiMetadata {
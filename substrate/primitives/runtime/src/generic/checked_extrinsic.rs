@@ -21,7 +21,7 @@
 use crate::{
 	traits::{
 		self, DispatchInfoOf, Dispatchable, MaybeDisplay, Member, PostDispatchInfoOf,
-		SignedExtension, ValidateUnsigned,
+		SignedExtension, ValidateUnsigned, VersionedExtension,
 	},
 	transaction_validity::{TransactionSource, TransactionValidity},
 };
@@ -30,21 +30,26 @@ use crate::{
 /// existence implies that it has been checked and is good, particularly with
 /// regards to the signature.
 #[derive(PartialEq, Eq, Clone, sp_core::RuntimeDebug)]
-pub struct CheckedExtrinsic<AccountId, Call, Extra> {
+pub struct CheckedExtrinsic<AccountId, Call, Extra, Extension = ()> {
 	/// Who this purports to be from and the number of extrinsics have come before
 	/// from the same signer, if anyone (note this is not a signature).
 	pub signed: Option<(AccountId, Extra)>,
 
+	/// The transaction extension of a general transaction, if this is one. `signed` is always
+	/// `None` in this case.
+	pub extension: Option<Extension>,
+
 	/// The function that should be called.
 	pub function: Call,
 }
 
-impl<AccountId, Call, Extra, RuntimeOrigin> traits::Applyable
-	for CheckedExtrinsic<AccountId, Call, Extra>
+impl<AccountId, Call, Extra, Extension, RuntimeOrigin> traits::Applyable
+	for CheckedExtrinsic<AccountId, Call, Extra, Extension>
 where
 	AccountId: Member + MaybeDisplay,
 	Call: Member + Dispatchable<RuntimeOrigin = RuntimeOrigin>,
 	Extra: SignedExtension<AccountId = AccountId, Call = Call>,
+	Extension: VersionedExtension<Call>,
 	RuntimeOrigin: From<Option<AccountId>>,
 {
 	type Call = Call;
@@ -59,6 +64,9 @@ where
 	) -> TransactionValidity {
 		if let Some((ref id, ref extra)) = self.signed {
 			Extra::validate(extra, id, &self.function, info, len)
+		} else if let Some(ref extension) = self.extension {
+			let origin = RuntimeOrigin::from(None::<AccountId>);
+			extension.validate_general(origin, &self.function, info, len)
 		} else {
 			let valid = Extra::validate_unsigned(&self.function, info, len)?;
 			let unsigned_validation = U::validate_unsigned(source, &self.function)?;
@@ -71,6 +79,11 @@ where
 		info: &DispatchInfoOf<Self::Call>,
 		len: usize,
 	) -> crate::ApplyExtrinsicResultWithInfo<PostDispatchInfoOf<Self::Call>> {
+		if let Some(extension) = self.extension {
+			let origin = RuntimeOrigin::from(None::<AccountId>);
+			return extension.apply_general(origin, self.function, info, len)
+		}
+
 		let (maybe_who, maybe_pre) = if let Some((id, extra)) = self.signed {
 			let pre = Extra::pre_dispatch(extra, &id, &self.function, info, len)?;
 			(Some(id), Some(pre))
@@ -20,8 +20,8 @@
 use crate::{
 	generic::CheckedExtrinsic,
 	traits::{
-		self, Checkable, Extrinsic, ExtrinsicMetadata, IdentifyAccount, MaybeDisplay, Member,
-		SignaturePayload, SignedExtension,
+		self, Checkable, ExtensionVersionMetadata, Extrinsic, ExtrinsicMetadata, IdentifyAccount,
+		MaybeDisplay, Member, SignaturePayload, SignedExtension, VersionedExtensionBase,
 	},
 	transaction_validity::{InvalidTransaction, TransactionValidityError},
 	OpaqueExtrinsic,
@@ -40,13 +40,30 @@ use sp_std::{fmt, prelude::*};
 /// the decoding fails.
 const EXTRINSIC_FORMAT_VERSION: u8 = 4;
 
+/// Version of the [`UncheckedExtrinsic`] encoded format of general transactions.
+///
+/// On top of the general bit, this format carries the version of the transaction extensions the
+/// transaction was built with.
+const GENERAL_EXTRINSIC_FORMAT_VERSION: u8 = 5;
+
+/// Bit of the version byte set for signed extrinsics.
+const SIGNED_EXTRINSIC_BIT: u8 = 0b1000_0000;
+
+/// Bit of the version byte set for general extrinsics, i.e. transactions which are authorized by
+/// their [`TransactionExtension`](crate::traits::TransactionExtension) rather than a signature.
+const GENERAL_EXTRINSIC_BIT: u8 = 0b0100_0000;
+
 /// The `SingaturePayload` of `UncheckedExtrinsic`.
 type UncheckedSignaturePayload<Address, Signature, Extra> = (Address, Signature, Extra);
 
 /// A extrinsic right from the external world. This is unchecked and so
 /// can contain a signature.
+///
+/// Besides signed and unsigned extrinsics, this can also be a general transaction. A general
+/// transaction carries no signature but only the data of the `Extension`, which is responsible
+/// for authorizing an origin for it.
 #[derive(PartialEq, Eq, Clone)]
-pub struct UncheckedExtrinsic<Address, Call, Signature, Extra>
+pub struct UncheckedExtrinsic<Address, Call, Signature, Extra, Extension = ()>
 where
 	Extra: SignedExtension,
 {
@@ -54,6 +71,9 @@ where
 	/// the same signer and an era describing the longevity of this transaction,
 	/// if this is a signed extrinsic.
 	pub signature: Option<UncheckedSignaturePayload<Address, Signature, Extra>>,
+	/// The transaction extension, if this is a general transaction. `signature` is always `None`
+	/// in this case.
+	pub extension: Option<Extension>,
 	/// The function that should be called.
 	pub function: Call,
 }
@@ -70,15 +90,16 @@ impl<Address: TypeInfo, Signature: TypeInfo, Extra: TypeInfo> SignaturePayload
 /// `Vec<u8>`, but requires some logic to extract the signature and payload.
 ///
 /// See [`UncheckedExtrinsic::encode`] and [`UncheckedExtrinsic::decode`].
-impl<Address, Call, Signature, Extra> TypeInfo
-	for UncheckedExtrinsic<Address, Call, Signature, Extra>
+impl<Address, Call, Signature, Extra, Extension> TypeInfo
+	for UncheckedExtrinsic<Address, Call, Signature, Extra, Extension>
 where
 	Address: StaticTypeInfo,
 	Call: StaticTypeInfo,
 	Signature: StaticTypeInfo,
 	Extra: SignedExtension + StaticTypeInfo,
+	Extension: VersionedExtensionBase,
 {
-	type Identity = UncheckedExtrinsic<Address, Call, Signature, Extra>;
+	type Identity = UncheckedExtrinsic<Address, Call, Signature, Extra, Extension>;
 
 	fn type_info() -> Type {
 		Type::builder()
@@ -91,6 +112,7 @@ where
 				TypeParameter::new("Call", Some(meta_type::<Call>())),
 				TypeParameter::new("Signature", Some(meta_type::<Signature>())),
 				TypeParameter::new("Extra", Some(meta_type::<Extra>())),
+				TypeParameter::new("Extension", Some(meta_type::<Extension>())),
 			])
 			.docs(&["UncheckedExtrinsic raw bytes, requires custom decoding routine"])
 			// Because of the custom encoding, we can only accurately describe the encoding as an
@@ -100,29 +122,45 @@ where
 	}
 }
 
-impl<Address, Call, Signature, Extra: SignedExtension>
-	UncheckedExtrinsic<Address, Call, Signature, Extra>
+impl<Address, Call, Signature, Extra: SignedExtension, Extension>
+	UncheckedExtrinsic<Address, Call, Signature, Extra, Extension>
 {
 	/// New instance of a signed extrinsic aka "transaction".
 	pub fn new_signed(function: Call, signed: Address, signature: Signature, extra: Extra) -> Self {
-		Self { signature: Some((signed, signature, extra)), function }
+		Self { signature: Some((signed, signature, extra)), extension: None, function }
 	}
 
 	/// New instance of an unsigned extrinsic aka "inherent".
 	pub fn new_unsigned(function: Call) -> Self {
-		Self { signature: None, function }
+		Self { signature: None, extension: None, function }
+	}
+
+	/// New instance of a general transaction, authorized by its `extension`.
+	pub fn new_general(function: Call, extension: Extension) -> Self {
+		Self { signature: None, extension: Some(extension), function }
+	}
+
+	/// Returns `true` if this is a general transaction.
+	pub fn is_general(&self) -> bool {
+		self.extension.is_some()
 	}
 }
 
-impl<Address: TypeInfo, Call: TypeInfo, Signature: TypeInfo, Extra: SignedExtension + TypeInfo>
-	Extrinsic for UncheckedExtrinsic<Address, Call, Signature, Extra>
+impl<
+		Address: TypeInfo,
+		Call: TypeInfo,
+		Signature: TypeInfo,
+		Extra: SignedExtension + TypeInfo,
+		Extension,
+	> Extrinsic for UncheckedExtrinsic<Address, Call, Signature, Extra, Extension>
 {
 	type Call = Call;
 
 	type SignaturePayload = UncheckedSignaturePayload<Address, Signature, Extra>;
 
 	fn is_signed(&self) -> Option<bool> {
-		Some(self.signature.is_some())
+		// General transactions are no inherents, so they are reported alongside signed ones.
+		Some(self.signature.is_some() || self.extension.is_some())
 	}
 
 	fn new(function: Call, signed_data: Option<Self::SignaturePayload>) -> Option<Self> {
@@ -134,8 +172,8 @@ impl<Address: TypeInfo, Call: TypeInfo, Signature: TypeInfo, Extra: SignedExtens
 	}
 }
 
-impl<LookupSource, AccountId, Call, Signature, Extra, Lookup> Checkable<Lookup>
-	for UncheckedExtrinsic<LookupSource, Call, Signature, Extra>
+impl<LookupSource, AccountId, Call, Signature, Extra, Extension, Lookup> Checkable<Lookup>
+	for UncheckedExtrinsic<LookupSource, Call, Signature, Extra, Extension>
 where
 	LookupSource: Member + MaybeDisplay,
 	Call: Encode + Member,
@@ -145,7 +183,7 @@ where
 	AccountId: Member + MaybeDisplay,
 	Lookup: traits::Lookup<Source = LookupSource, Target = AccountId>,
 {
-	type Checked = CheckedExtrinsic<AccountId, Call, Extra>;
+	type Checked = CheckedExtrinsic<AccountId, Call, Extra, Extension>;
 
 	fn check(self, lookup: &Lookup) -> Result<Self::Checked, TransactionValidityError> {
 		Ok(match self.signature {
@@ -157,9 +195,14 @@ where
 				}
 
 				let (function, extra, _) = raw_payload.deconstruct();
-				CheckedExtrinsic { signed: Some((signed, extra)), function }
+				CheckedExtrinsic { signed: Some((signed, extra)), extension: None, function }
+			},
+			// The extension of a general transaction is checked when it is validated.
+			None => CheckedExtrinsic {
+				signed: None,
+				extension: self.extension,
+				function: self.function,
 			},
-			None => CheckedExtrinsic { signed: None, function: self.function },
 		})
 	}
 
@@ -173,20 +216,29 @@ where
 				let signed = lookup.lookup(signed)?;
 				let raw_payload = SignedPayload::new(self.function, extra)?;
 				let (function, extra, _) = raw_payload.deconstruct();
-				CheckedExtrinsic { signed: Some((signed, extra)), function }
+				CheckedExtrinsic { signed: Some((signed, extra)), extension: None, function }
+			},
+			None => CheckedExtrinsic {
+				signed: None,
+				extension: self.extension,
+				function: self.function,
 			},
-			None => CheckedExtrinsic { signed: None, function: self.function },
 		})
 	}
 }
 
-impl<Address, Call, Signature, Extra> ExtrinsicMetadata
-	for UncheckedExtrinsic<Address, Call, Signature, Extra>
+impl<Address, Call, Signature, Extra, Extension> ExtrinsicMetadata
+	for UncheckedExtrinsic<Address, Call, Signature, Extra, Extension>
 where
 	Extra: SignedExtension,
+	Extension: VersionedExtensionBase,
 {
 	const VERSION: u8 = EXTRINSIC_FORMAT_VERSION;
 	type SignedExtensions = Extra;
+
+	fn extension_versions() -> Vec<ExtensionVersionMetadata> {
+		Extension::metadata()
+	}
 }
 
 /// A payload that has been signed for an unchecked extrinsics.
@@ -247,12 +299,14 @@ where
 {
 }
 
-impl<Address, Call, Signature, Extra> Decode for UncheckedExtrinsic<Address, Call, Signature, Extra>
+impl<Address, Call, Signature, Extra, Extension> Decode
+	for UncheckedExtrinsic<Address, Call, Signature, Extra, Extension>
 where
 	Address: Decode,
 	Signature: Decode,
 	Call: Decode,
 	Extra: SignedExtension,
+	Extension: VersionedExtensionBase,
 {
 	fn decode<I: Input>(input: &mut I) -> Result<Self, Error> {
 		// This is a little more complicated than usual since the binary format must be compatible
@@ -263,13 +317,22 @@ where
 
 		let version = input.read_byte()?;
 
-		let is_signed = version & 0b1000_0000 != 0;
-		let version = version & 0b0111_1111;
-		if version != EXTRINSIC_FORMAT_VERSION {
-			return Err("Invalid transaction version".into())
+		let is_signed = version & SIGNED_EXTRINSIC_BIT != 0;
+		let is_general = version & GENERAL_EXTRINSIC_BIT != 0;
+		let version = version & !(SIGNED_EXTRINSIC_BIT | GENERAL_EXTRINSIC_BIT);
+		match (version, is_signed, is_general) {
+			(EXTRINSIC_FORMAT_VERSION, _, false) |
+			(GENERAL_EXTRINSIC_FORMAT_VERSION, false, true) => (),
+			_ => return Err("Invalid transaction version".into()),
 		}
 
 		let signature = is_signed.then(|| Decode::decode(input)).transpose()?;
+		let extension = if is_general {
+			let extension_version = input.read_byte()?;
+			Some(Extension::decode_with_version(extension_version, input)?)
+		} else {
+			None
+		};
 		let function = Decode::decode(input)?;
 
 		if let Some((before_length, after_length)) =
@@ -282,28 +345,35 @@ where
 			}
 		}
 
-		Ok(Self { signature, function })
+		Ok(Self { signature, extension, function })
 	}
 }
 
-impl<Address, Call, Signature, Extra> Encode for UncheckedExtrinsic<Address, Call, Signature, Extra>
+impl<Address, Call, Signature, Extra, Extension> Encode
+	for UncheckedExtrinsic<Address, Call, Signature, Extra, Extension>
 where
 	Address: Encode,
 	Signature: Encode,
 	Call: Encode,
 	Extra: SignedExtension,
+	Extension: VersionedExtensionBase,
 {
 	fn encode(&self) -> Vec<u8> {
 		let mut tmp = Vec::with_capacity(sp_std::mem::size_of::<Self>());
 
 		// 1 byte version id.
-		match self.signature.as_ref() {
-			Some(s) => {
-				tmp.push(EXTRINSIC_FORMAT_VERSION | 0b1000_0000);
+		match (self.signature.as_ref(), self.extension.as_ref()) {
+			(Some(s), _) => {
+				tmp.push(EXTRINSIC_FORMAT_VERSION | SIGNED_EXTRINSIC_BIT);
 				s.encode_to(&mut tmp);
 			},
-			None => {
-				tmp.push(EXTRINSIC_FORMAT_VERSION & 0b0111_1111);
+			(None, Some(e)) => {
+				tmp.push(GENERAL_EXTRINSIC_FORMAT_VERSION | GENERAL_EXTRINSIC_BIT);
+				tmp.push(e.extension_version());
+				e.encode_to(&mut tmp);
+			},
+			(None, None) => {
+				tmp.push(EXTRINSIC_FORMAT_VERSION);
 			},
 		}
 		self.function.encode_to(&mut tmp);
@@ -320,19 +390,25 @@ where
 	}
 }
 
-impl<Address, Call, Signature, Extra> EncodeLike
-	for UncheckedExtrinsic<Address, Call, Signature, Extra>
+impl<Address, Call, Signature, Extra, Extension> EncodeLike
+	for UncheckedExtrinsic<Address, Call, Signature, Extra, Extension>
 where
 	Address: Encode,
 	Signature: Encode,
 	Call: Encode,
 	Extra: SignedExtension,
+	Extension: VersionedExtensionBase,
 {
 }
 
 #[cfg(feature = "serde")]
-impl<Address: Encode, Signature: Encode, Call: Encode, Extra: SignedExtension> serde::Serialize
-	for UncheckedExtrinsic<Address, Call, Signature, Extra>
+impl<
+		Address: Encode,
+		Signature: Encode,
+		Call: Encode,
+		Extra: SignedExtension,
+		Extension: VersionedExtensionBase,
+	> serde::Serialize for UncheckedExtrinsic<Address, Call, Signature, Extra, Extension>
 {
 	fn serialize<S>(&self, seq: S) -> Result<S::Ok, S::Error>
 	where
@@ -343,8 +419,14 @@ impl<Address: Encode, Signature: Encode, Call: Encode, Extra: SignedExtension> s
 }
 
 #[cfg(feature = "serde")]
-impl<'a, Address: Decode, Signature: Decode, Call: Decode, Extra: SignedExtension>
-	serde::Deserialize<'a> for UncheckedExtrinsic<Address, Call, Signature, Extra>
+impl<
+		'a,
+		Address: Decode,
+		Signature: Decode,
+		Call: Decode,
+		Extra: SignedExtension,
+		Extension: VersionedExtensionBase,
+	> serde::Deserialize<'a> for UncheckedExtrinsic<Address, Call, Signature, Extra, Extension>
 {
 	fn deserialize<D>(de: D) -> Result<Self, D::Error>
 	where
@@ -356,32 +438,38 @@ impl<'a, Address: Decode, Signature: Decode, Call: Decode, Extra: SignedExtensio
 	}
 }
 
-impl<Address, Call, Signature, Extra> fmt::Debug
-	for UncheckedExtrinsic<Address, Call, Signature, Extra>
+impl<Address, Call, Signature, Extra, Extension> fmt::Debug
+	for UncheckedExtrinsic<Address, Call, Signature, Extra, Extension>
 where
 	Address: fmt::Debug,
 	Call: fmt::Debug,
 	Extra: SignedExtension,
+	Extension: fmt::Debug,
 {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-		write!(
-			f,
-			"UncheckedExtrinsic({:?}, {:?})",
-			self.signature.as_ref().map(|x| (&x.0, &x.2)),
-			self.function,
-		)
+		match self.extension.as_ref() {
+			Some(extension) =>
+				write!(f, "UncheckedExtrinsic(general: {:?}, {:?})", extension, self.function),
+			None => write!(
+				f,
+				"UncheckedExtrinsic({:?}, {:?})",
+				self.signature.as_ref().map(|x| (&x.0, &x.2)),
+				self.function,
+			),
+		}
 	}
 }
 
-impl<Address, Call, Signature, Extra> From<UncheckedExtrinsic<Address, Call, Signature, Extra>>
-	for OpaqueExtrinsic
+impl<Address, Call, Signature, Extra, Extension>
+	From<UncheckedExtrinsic<Address, Call, Signature, Extra, Extension>> for OpaqueExtrinsic
 where
 	Address: Encode,
 	Signature: Encode,
 	Call: Encode,
 	Extra: SignedExtension,
+	Extension: VersionedExtensionBase,
 {
-	fn from(extrinsic: UncheckedExtrinsic<Address, Call, Signature, Extra>) -> Self {
+	fn from(extrinsic: UncheckedExtrinsic<Address, Call, Signature, Extra, Extension>) -> Self {
 		Self::from_bytes(extrinsic.encode().as_slice()).expect(
 			"both OpaqueExtrinsic and UncheckedExtrinsic have encoding that is compatible with \
 				raw Vec<u8> encoding; qed",
@@ -395,7 +483,10 @@ mod tests {
 	use crate::{
 		codec::{Decode, Encode},
 		testing::TestSignature as TestSig,
-		traits::{DispatchInfoOf, IdentityLookup, SignedExtension},
+		traits::{
+			AsTransactionExtension, DispatchInfoOf, ExtensionAtVersion, IdentityLookup,
+			MultiVersion, SignedExtension,
+		},
 	};
 	use sp_io::hashing::blake2_256;
 
@@ -432,6 +523,19 @@ mod tests {
 
 	type Ex = UncheckedExtrinsic<TestAccountId, TestCall, TestSig, TestExtra>;
 	type CEx = CheckedExtrinsic<TestAccountId, TestCall, TestExtra>;
+	type TestExtension = MultiVersion<
+		ExtensionAtVersion<0, AsTransactionExtension<TestExtra>>,
+		ExtensionAtVersion<3, AsTransactionExtension<TestExtra>>,
+	>;
+	type GEx = UncheckedExtrinsic<TestAccountId, TestCall, TestSig, TestExtra, TestExtension>;
+	type GCEx = CheckedExtrinsic<TestAccountId, TestCall, TestExtra, TestExtension>;
+
+	fn test_extension(version: u8) -> TestExtension {
+		match version {
+			0 => MultiVersion::A(AsTransactionExtension(TestExtra).into()),
+			_ => MultiVersion::B(AsTransactionExtension(TestExtra).into()),
+		}
+	}
 
 	#[test]
 	fn unsigned_codec_should_work() {
@@ -511,10 +615,73 @@ mod tests {
 		assert!(ux.is_signed().unwrap_or(false));
 		assert_eq!(
 			<Ex as Checkable<TestContext>>::check(ux, &Default::default()),
-			Ok(CEx { signed: Some((TEST_ACCOUNT, TestExtra)), function: vec![0u8; 0] }),
+			Ok(CEx {
+				signed: Some((TEST_ACCOUNT, TestExtra)),
+				extension: None,
+				function: vec![0u8; 0]
+			}),
+		);
+	}
+
+	#[test]
+	fn general_codec_should_work() {
+		for version in [0, 3] {
+			let ux = GEx::new_general(vec![0u8; 0], test_extension(version));
+			let encoded = ux.encode();
+			assert_eq!(encoded[1], GENERAL_EXTRINSIC_FORMAT_VERSION | GENERAL_EXTRINSIC_BIT);
+			assert_eq!(encoded[2], version);
+			assert_eq!(GEx::decode(&mut &encoded[..]), Ok(ux));
+		}
+	}
+
+	#[test]
+	fn unsupported_extension_version_is_rejected() {
+		let ux = GEx::new_general(vec![0u8; 0], test_extension(3));
+		let mut encoded = ux.encode();
+		encoded[2] = 1;
+
+		assert_eq!(GEx::decode(&mut &encoded[..]), Err("Unsupported extension version".into()));
+		assert_eq!(
+			Ex::decode(&mut &ux.encode()[..]),
+			Err("General transactions are not supported".into())
 		);
 	}
 
+	#[test]
+	fn invalid_format_versions_are_rejected() {
+		let ux = GEx::new_general(vec![0u8; 0], test_extension(0));
+		let mut encoded = ux.encode();
+		encoded[1] |= SIGNED_EXTRINSIC_BIT;
+		assert_eq!(GEx::decode(&mut &encoded[..]), Err("Invalid transaction version".into()));
+
+		encoded[1] = EXTRINSIC_FORMAT_VERSION | GENERAL_EXTRINSIC_BIT;
+		assert_eq!(GEx::decode(&mut &encoded[..]), Err("Invalid transaction version".into()));
+
+		// A bare transaction must not use the general format version.
+		let mut encoded = Ex::new_unsigned(vec![0u8; 0]).encode();
+		encoded[1] = GENERAL_EXTRINSIC_FORMAT_VERSION;
+		assert_eq!(GEx::decode(&mut &encoded[..]), Err("Invalid transaction version".into()));
+	}
+
+	#[test]
+	fn general_check_should_work() {
+		let ux = GEx::new_general(vec![0u8; 0], test_extension(3));
+		assert!(ux.is_general());
+		assert!(ux.is_signed().unwrap_or(false));
+		assert_eq!(
+			<GEx as Checkable<TestContext>>::check(ux, &Default::default()),
+			Ok(GCEx { signed: None, extension: Some(test_extension(3)), function: vec![0u8; 0] }),
+		);
+	}
+
+	#[test]
+	fn extension_versions_are_in_metadata() {
+		let versions = <GEx as ExtrinsicMetadata>::extension_versions();
+		assert_eq!(versions.iter().map(|v| v.version).collect::<Vec<_>>(), vec![0, 3]);
+		assert!(versions.iter().all(|v| v.extensions.len() == 1));
+		assert!(<Ex as ExtrinsicMetadata>::extension_versions().is_empty());
+	}
+
 	#[test]
 	fn encoding_matches_vec() {
 		let ex = Ex::new_unsigned(vec![0u8; 0]);
@@ -52,6 +52,18 @@ use std::fmt::Display;
 #[cfg(feature = "std")]
 use std::str::FromStr;
 
+mod transaction_extension;
+pub use transaction_extension::{
+	AsSystemOriginSigner, AsTransactionAuthorizedOrigin, AsTransactionExtension, ImplicationParts,
+	OriginOf, TransactionExtension, TransactionExtensionBase, ValidateResult, VerifySignature,
+};
+
+mod versioned_extension;
+pub use versioned_extension::{
+	ExtensionAtVersion, ExtensionVersionMetadata, MultiVersion, VersionedExtension,
+	VersionedExtensionBase,
+};
+
 /// A lazy value.
 pub trait Lazy<T: ?Sized> {
 	/// Get a reference to the underlying value.
@@ -1342,6 +1354,12 @@ pub trait ExtrinsicMetadata {
 
 	/// Signed extensions attached to this `Extrinsic`.
 	type SignedExtensions: SignedExtension;
+
+	/// The versions of transaction extensions general transactions of this `Extrinsic` can be
+	/// built with.
+	fn extension_versions() -> Vec<ExtensionVersionMetadata> {
+		Vec::new()
+	}
 }
 
 /// Extract the hashing type for a block.
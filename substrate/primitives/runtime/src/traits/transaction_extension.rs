@@ -0,0 +1,608 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The [`TransactionExtension`] trait and its adapter for [`SignedExtension`]s.
+
+use super::{
+	DispatchInfoOf, Dispatchable, IdentifyAccount, PostDispatchInfoOf, SignedExtension, Verify,
+};
+use crate::{
+	scale_info::{StaticTypeInfo, TypeInfo},
+	traits::SignedExtensionMetadata,
+	transaction_validity::{InvalidTransaction, TransactionValidityError, ValidTransaction},
+	DispatchResult,
+};
+use codec::{Codec, Decode, Encode, Output};
+use impl_trait_for_tuples::impl_for_tuples;
+use sp_io::hashing::blake2_256;
+use sp_std::{fmt::Debug, prelude::*};
+
+/// Shortcut for the origin type of a [`Dispatchable`].
+pub type OriginOf<Call> = <Call as Dispatchable>::RuntimeOrigin;
+
+/// The result of [`TransactionExtension::validate`]: the validity of the transaction, the value
+/// passed on to [`TransactionExtension::prepare`] and the origin to continue with.
+pub type ValidateResult<Val, Call> =
+	Result<(ValidTransaction, Val, OriginOf<Call>), TransactionValidityError>;
+
+/// Something which can tell whether it is a system `Signed` origin and extract the signer.
+pub trait AsSystemOriginSigner<AccountId> {
+	/// Extract the signer if this is a system `Signed` origin, `None` otherwise.
+	fn as_system_origin_signer(&self) -> Option<&AccountId>;
+}
+
+/// Something which can tell whether it is an origin a general transaction may be dispatched
+/// with.
+///
+/// General transactions start out with a system `None` origin. If none of their extensions
+/// replaced it, the transaction was not authorized by anyone and must not be dispatched.
+pub trait AsTransactionAuthorizedOrigin {
+	/// Whether this origin was authorized by the extensions of a transaction.
+	fn is_transaction_authorized(&self) -> bool;
+}
+
+/// The data and identity of a transaction extension, independent of the call it is used with.
+pub trait TransactionExtensionBase:
+	Codec + Debug + Sync + Send + Clone + Eq + PartialEq + StaticTypeInfo
+{
+	/// Unique identifier of this transaction extension.
+	///
+	/// This will be exposed in the metadata to identify the extension used in an extrinsic.
+	const IDENTIFIER: &'static str;
+
+	/// Any additional data that is implied by the transaction without being part of it. It is
+	/// part of the implication handed to the extensions preceding this one.
+	type Implicit: Encode + TypeInfo;
+
+	/// Construct the implicit data of this extension. Can also perform any checks that do not
+	/// depend on the transaction and return an error if needed.
+	fn implicit(&self) -> Result<Self::Implicit, TransactionValidityError>;
+
+	/// Returns the metadata for this transaction extension.
+	///
+	/// As a [`TransactionExtensionBase`] can be a tuple of extensions, each individual extension
+	/// must return *exactly* one [`SignedExtensionMetadata`].
+	fn metadata() -> Vec<SignedExtensionMetadata> {
+		sp_std::vec![SignedExtensionMetadata {
+			identifier: Self::IDENTIFIER,
+			ty: scale_info::meta_type::<Self>(),
+			additional_signed: scale_info::meta_type::<Self::Implicit>()
+		}]
+	}
+}
+
+/// Means by which a transaction may be extended.
+///
+/// Unlike [`SignedExtension`], a transaction extension does not assume that the transaction is
+/// signed. It gets the origin the transaction is about to be dispatched with and may replace it,
+/// e.g. after verifying a signature or some other kind of authorization carried in its data.
+/// Extensions are applied in order and each one gets the origin returned by its predecessor.
+pub trait TransactionExtension<Call: Dispatchable>: TransactionExtensionBase {
+	/// The value passed from [`Self::validate`] to [`Self::prepare`].
+	type Val;
+
+	/// The value passed from [`Self::prepare`] to [`Self::post_dispatch`].
+	type Pre;
+
+	/// Validate a transaction for the transaction queue.
+	///
+	/// `inherited_implication` is everything the transaction implies that is not handled by this
+	/// extension or its predecessors: the call, followed by the data and implicit data of all
+	/// extensions following this one. This is what a signature carried by this extension should
+	/// sign.
+	///
+	/// Returns the validity of the transaction, a value for [`Self::prepare`] and the origin
+	/// the remaining extensions and the call are given.
+	fn validate(
+		&self,
+		origin: OriginOf<Call>,
+		call: &Call,
+		info: &DispatchInfoOf<Call>,
+		len: usize,
+		inherited_implication: &impl Encode,
+	) -> ValidateResult<Self::Val, Call>;
+
+	/// Do any pre-flight stuff for a transaction after it was validated.
+	///
+	/// This is always called right after [`Self::validate`] when the transaction is applied, so
+	/// checks done there do not need to be repeated.
+	fn prepare(
+		self,
+		val: Self::Val,
+		origin: &OriginOf<Call>,
+		call: &Call,
+		info: &DispatchInfoOf<Call>,
+		len: usize,
+	) -> Result<Self::Pre, TransactionValidityError>;
+
+	/// Do any post-flight stuff for a transaction.
+	///
+	/// The same warning as for [`SignedExtension::post_dispatch`] applies: returning an error
+	/// here invalidates the block the transaction is included in.
+	fn post_dispatch(
+		_pre: Self::Pre,
+		_info: &DispatchInfoOf<Call>,
+		_post_info: &PostDispatchInfoOf<Call>,
+		_len: usize,
+		_result: &DispatchResult,
+	) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+}
+
+/// The implication handed to an element of a tuple of transaction extensions.
+///
+/// Encodes as the inherited implication of the tuple, followed by the data and then the implicit
+/// data of the elements following the element.
+pub struct ImplicationParts<'a, Base> {
+	/// The implication inherited by the whole tuple.
+	pub base: Base,
+	/// The encoded data of the following elements.
+	pub explicit: &'a [Vec<u8>],
+	/// The encoded implicit data of the following elements.
+	pub implicit: &'a [Vec<u8>],
+}
+
+impl<'a, Base: Encode> Encode for ImplicationParts<'a, Base> {
+	fn encode_to<T: Output + ?Sized>(&self, dest: &mut T) {
+		self.base.encode_to(dest);
+		self.explicit.iter().for_each(|e| dest.write(e));
+		self.implicit.iter().for_each(|i| dest.write(i));
+	}
+}
+
+#[impl_for_tuples(1, 12)]
+impl TransactionExtensionBase for Tuple {
+	for_tuples!( where #( Tuple: TransactionExtensionBase )* );
+	const IDENTIFIER: &'static str = "You should call `identifier()`!";
+	for_tuples!( type Implicit = ( #( Tuple::Implicit ),* ); );
+
+	fn implicit(&self) -> Result<Self::Implicit, TransactionValidityError> {
+		Ok(for_tuples!( ( #( Tuple.implicit()? ),* ) ))
+	}
+
+	fn metadata() -> Vec<SignedExtensionMetadata> {
+		let mut ids = Vec::new();
+		for_tuples!( #( ids.extend(Tuple::metadata()); )* );
+		ids
+	}
+}
+
+#[impl_for_tuples(1, 12)]
+impl<Call: Dispatchable> TransactionExtension<Call> for Tuple {
+	for_tuples!( where #( Tuple: TransactionExtension<Call> )* );
+	for_tuples!( type Val = ( #( Tuple::Val ),* ); );
+	for_tuples!( type Pre = ( #( Tuple::Pre ),* ); );
+
+	fn validate(
+		&self,
+		origin: OriginOf<Call>,
+		call: &Call,
+		info: &DispatchInfoOf<Call>,
+		len: usize,
+		inherited_implication: &impl Encode,
+	) -> ValidateResult<Self::Val, Call> {
+		let explicit = [for_tuples!( #( Tuple.encode() ),* )];
+		let implicit = [for_tuples!( #( Tuple.implicit()?.encode() ),* )];
+		let mut valid = ValidTransaction::default();
+		let mut origin = origin;
+		let mut index = 0;
+		let val = for_tuples!( ( #( {
+			index += 1;
+			let implication = ImplicationParts {
+				base: inherited_implication,
+				explicit: &explicit[index..],
+				implicit: &implicit[index..],
+			};
+			let (item_valid, item_val, item_origin) =
+				Tuple.validate(origin, call, info, len, &implication)?;
+			valid = valid.combine_with(item_valid);
+			origin = item_origin;
+			item_val
+		} ),* ) );
+		Ok((valid, val, origin))
+	}
+
+	fn prepare(
+		self,
+		val: Self::Val,
+		origin: &OriginOf<Call>,
+		call: &Call,
+		info: &DispatchInfoOf<Call>,
+		len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		Ok(
+			for_tuples!( ( #( Tuple::prepare(self.Tuple, val.Tuple, origin, call, info, len)? ),* ) ),
+		)
+	}
+
+	fn post_dispatch(
+		pre: Self::Pre,
+		info: &DispatchInfoOf<Call>,
+		post_info: &PostDispatchInfoOf<Call>,
+		len: usize,
+		result: &DispatchResult,
+	) -> Result<(), TransactionValidityError> {
+		for_tuples!( #( Tuple::post_dispatch(pre.Tuple, info, post_info, len, result)?; )* );
+		Ok(())
+	}
+}
+
+impl TransactionExtensionBase for () {
+	const IDENTIFIER: &'static str = "UnitTransactionExtension";
+	type Implicit = ();
+
+	fn implicit(&self) -> sp_std::result::Result<(), TransactionValidityError> {
+		Ok(())
+	}
+}
+
+impl<Call: Dispatchable> TransactionExtension<Call> for () {
+	type Val = ();
+	type Pre = ();
+
+	fn validate(
+		&self,
+		origin: OriginOf<Call>,
+		_call: &Call,
+		_info: &DispatchInfoOf<Call>,
+		_len: usize,
+		_inherited_implication: &impl Encode,
+	) -> ValidateResult<(), Call> {
+		Ok((ValidTransaction::default(), (), origin))
+	}
+
+	fn prepare(
+		self,
+		_val: (),
+		_origin: &OriginOf<Call>,
+		_call: &Call,
+		_info: &DispatchInfoOf<Call>,
+		_len: usize,
+	) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+}
+
+/// Adapter to use a [`SignedExtension`] as a [`TransactionExtension`].
+///
+/// If the origin is a system `Signed` origin, the signed hooks of the wrapped extension are used,
+/// the unsigned ones otherwise. The origin is never changed.
+#[derive(Encode, Decode, Debug, Clone, Eq, PartialEq, TypeInfo)]
+pub struct AsTransactionExtension<SE: SignedExtension>(pub SE);
+
+impl<SE: SignedExtension + Default> Default for AsTransactionExtension<SE> {
+	fn default() -> Self {
+		Self(SE::default())
+	}
+}
+
+impl<SE: SignedExtension> From<SE> for AsTransactionExtension<SE> {
+	fn from(value: SE) -> Self {
+		Self(value)
+	}
+}
+
+impl<SE: SignedExtension> TransactionExtensionBase for AsTransactionExtension<SE> {
+	const IDENTIFIER: &'static str = SE::IDENTIFIER;
+	type Implicit = SE::AdditionalSigned;
+
+	fn implicit(&self) -> Result<Self::Implicit, TransactionValidityError> {
+		self.0.additional_signed()
+	}
+
+	fn metadata() -> Vec<SignedExtensionMetadata> {
+		SE::metadata()
+	}
+}
+
+impl<SE, Call> TransactionExtension<Call> for AsTransactionExtension<SE>
+where
+	SE: SignedExtension<Call = Call>,
+	Call: Dispatchable,
+	OriginOf<Call>: AsSystemOriginSigner<SE::AccountId>,
+{
+	type Val = ();
+	type Pre = Option<SE::Pre>;
+
+	fn validate(
+		&self,
+		origin: OriginOf<Call>,
+		call: &Call,
+		info: &DispatchInfoOf<Call>,
+		len: usize,
+		_inherited_implication: &impl Encode,
+	) -> ValidateResult<(), Call> {
+		let valid = match origin.as_system_origin_signer() {
+			Some(who) => self.0.validate(who, call, info, len)?,
+			None => SE::validate_unsigned(call, info, len)?,
+		};
+		Ok((valid, (), origin))
+	}
+
+	fn prepare(
+		self,
+		_val: (),
+		origin: &OriginOf<Call>,
+		call: &Call,
+		info: &DispatchInfoOf<Call>,
+		len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		match origin.as_system_origin_signer() {
+			Some(who) => self.0.pre_dispatch(who, call, info, len).map(Some),
+			None => SE::pre_dispatch_unsigned(call, info, len).map(|_| None),
+		}
+	}
+
+	fn post_dispatch(
+		pre: Self::Pre,
+		info: &DispatchInfoOf<Call>,
+		post_info: &PostDispatchInfoOf<Call>,
+		len: usize,
+		result: &DispatchResult,
+	) -> Result<(), TransactionValidityError> {
+		SE::post_dispatch(pre, info, post_info, len, result)
+	}
+}
+
+/// Transaction extension which verifies a signature and authorizes its signer.
+///
+/// The signature is over the implication of the extension, i.e. the call followed by the data
+/// and implicit data of the extensions following this one. Like for signed transactions, payloads
+/// longer than 256 bytes are `blake2_256`-hashed before being signed.
+///
+/// A valid signature replaces the origin by a system `Signed` origin of `account`. If the
+/// extension is [`VerifySignature::Disabled`], the origin is passed on unchanged such that another
+/// extension may authorize the transaction.
+#[derive(Encode, Decode, Debug, Clone, Eq, PartialEq, TypeInfo)]
+pub enum VerifySignature<Signature, AccountId> {
+	/// The transaction is signed by `account`.
+	Signed {
+		/// The signature over the implication of the extension.
+		signature: Signature,
+		/// The account which signed the transaction.
+		account: AccountId,
+	},
+	/// No signature is checked and the origin is left untouched.
+	Disabled,
+}
+
+impl<Signature, AccountId> TransactionExtensionBase for VerifySignature<Signature, AccountId>
+where
+	Signature: Codec + Debug + Sync + Send + Clone + Eq + PartialEq + StaticTypeInfo,
+	AccountId: Codec + Debug + Sync + Send + Clone + Eq + PartialEq + StaticTypeInfo,
+{
+	const IDENTIFIER: &'static str = "VerifySignature";
+	type Implicit = ();
+
+	fn implicit(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+}
+
+impl<Signature, AccountId, Call> TransactionExtension<Call>
+	for VerifySignature<Signature, AccountId>
+where
+	Signature: Verify + Codec + Debug + Sync + Send + Clone + Eq + PartialEq + StaticTypeInfo,
+	Signature::Signer: IdentifyAccount<AccountId = AccountId>,
+	AccountId: Codec + Debug + Sync + Send + Clone + Eq + PartialEq + StaticTypeInfo,
+	Call: Dispatchable,
+	OriginOf<Call>: From<Option<AccountId>>,
+{
+	type Val = ();
+	type Pre = ();
+
+	fn validate(
+		&self,
+		origin: OriginOf<Call>,
+		_call: &Call,
+		_info: &DispatchInfoOf<Call>,
+		_len: usize,
+		inherited_implication: &impl Encode,
+	) -> ValidateResult<(), Call> {
+		let (signature, account) = match self {
+			Self::Signed { signature, account } => (signature, account),
+			Self::Disabled => return Ok((ValidTransaction::default(), (), origin)),
+		};
+
+		let verified = inherited_implication.using_encoded(|payload| {
+			if payload.len() > 256 {
+				signature.verify(&blake2_256(payload)[..], account)
+			} else {
+				signature.verify(payload, account)
+			}
+		});
+		if !verified {
+			return Err(InvalidTransaction::BadProof.into())
+		}
+
+		Ok((ValidTransaction::default(), (), Some(account.clone()).into()))
+	}
+
+	fn prepare(
+		self,
+		_val: (),
+		_origin: &OriginOf<Call>,
+		_call: &Call,
+		_info: &DispatchInfoOf<Call>,
+		_len: usize,
+	) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{testing::TestSignature, transaction_validity::TransactionValidity};
+
+	#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+	struct TestCall;
+
+	impl Dispatchable for TestCall {
+		type RuntimeOrigin = Option<u64>;
+		type Config = ();
+		type Info = ();
+		type PostInfo = ();
+
+		fn dispatch(
+			self,
+			_origin: Self::RuntimeOrigin,
+		) -> crate::DispatchResultWithInfo<Self::PostInfo> {
+			Ok(())
+		}
+	}
+
+	impl AsSystemOriginSigner<u64> for Option<u64> {
+		fn as_system_origin_signer(&self) -> Option<&u64> {
+			self.as_ref()
+		}
+	}
+
+	/// Provides the implication it was given and authorizes the account it carries.
+	#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq, TypeInfo)]
+	struct Authorize(u64);
+
+	impl TransactionExtensionBase for Authorize {
+		const IDENTIFIER: &'static str = "Authorize";
+		type Implicit = u8;
+
+		fn implicit(&self) -> Result<u8, TransactionValidityError> {
+			Ok(self.0 as u8 + 100)
+		}
+	}
+
+	impl TransactionExtension<TestCall> for Authorize {
+		type Val = u64;
+		type Pre = u64;
+
+		fn validate(
+			&self,
+			_origin: Option<u64>,
+			_call: &TestCall,
+			_info: &(),
+			_len: usize,
+			inherited_implication: &impl Encode,
+		) -> ValidateResult<u64, TestCall> {
+			let valid = ValidTransaction {
+				provides: vec![inherited_implication.encode()],
+				..Default::default()
+			};
+			Ok((valid, self.0, Some(self.0)))
+		}
+
+		fn prepare(
+			self,
+			val: u64,
+			origin: &Option<u64>,
+			_call: &TestCall,
+			_info: &(),
+			_len: usize,
+		) -> Result<u64, TransactionValidityError> {
+			assert_eq!(origin, &Some(val));
+			Ok(val)
+		}
+	}
+
+	#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq, TypeInfo)]
+	struct SignedOnly;
+
+	impl SignedExtension for SignedOnly {
+		const IDENTIFIER: &'static str = "SignedOnly";
+		type AccountId = u64;
+		type Call = TestCall;
+		type AdditionalSigned = ();
+		type Pre = u64;
+
+		fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+			Ok(())
+		}
+
+		fn pre_dispatch(
+			self,
+			who: &u64,
+			_call: &TestCall,
+			_info: &(),
+			_len: usize,
+		) -> Result<u64, TransactionValidityError> {
+			Ok(*who)
+		}
+
+		fn validate_unsigned(_call: &TestCall, _info: &(), _len: usize) -> TransactionValidity {
+			Err(InvalidTransaction::BadSigner.into())
+		}
+	}
+
+	#[test]
+	fn tuple_threads_origin_and_implication() {
+		let ext = (Authorize(1), Authorize(2));
+		let (valid, val, origin) = ext.validate(None, &TestCall, &(), 0, &7u8).unwrap();
+		assert_eq!(val, (1, 2));
+		assert_eq!(origin, Some(2));
+		// The first element implies the data of the second one, the second only the call.
+		assert_eq!(valid.provides, vec![vec![7u8, 2, 0, 0, 0, 0, 0, 0, 0, 102], vec![7u8]]);
+		assert_eq!(ext.prepare(val, &origin, &TestCall, &(), 0), Ok((1, 2)));
+	}
+
+	#[test]
+	fn signed_extension_adapter_follows_origin() {
+		let ext = AsTransactionExtension(SignedOnly);
+		let (_, val, origin) = ext.validate(Some(3), &TestCall, &(), 0, &()).unwrap();
+		assert_eq!(origin, Some(3));
+		assert_eq!(ext.clone().prepare(val, &origin, &TestCall, &(), 0), Ok(Some(3)));
+
+		assert_eq!(
+			ext.validate(None, &TestCall, &(), 0, &()).map(|_| ()),
+			Err(InvalidTransaction::BadSigner.into()),
+		);
+
+		let ext = (Authorize(4), AsTransactionExtension(SignedOnly));
+		let (_, val, origin) = ext.validate(None, &TestCall, &(), 0, &()).unwrap();
+		assert_eq!(ext.prepare(val, &origin, &TestCall, &(), 0), Ok((4, Some(4))));
+	}
+
+	#[test]
+	fn verify_signature_authorizes_signer() {
+		let signed = |signature: Vec<u8>, account| VerifySignature::Signed {
+			signature: TestSignature(5, signature),
+			account,
+		};
+
+		let (_, _, origin) = signed(vec![7], 5).validate(None, &TestCall, &(), 0, &7u8).unwrap();
+		assert_eq!(origin, Some(5));
+
+		// Long implications are hashed.
+		let long = vec![1u8; 300];
+		let ext = signed(blake2_256(&long.encode()).to_vec(), 5);
+		assert_eq!(ext.validate(None, &TestCall, &(), 0, &long).unwrap().2, Some(5));
+
+		for ext in [signed(vec![8], 5), signed(vec![7], 6)] {
+			assert_eq!(
+				ext.validate(None, &TestCall, &(), 0, &7u8).map(|_| ()),
+				Err(InvalidTransaction::BadProof.into()),
+			);
+		}
+
+		let disabled = VerifySignature::<TestSignature, u64>::Disabled;
+		assert_eq!(disabled.validate(None, &TestCall, &(), 0, &7u8).unwrap().2, None);
+
+		// Extensions following the signature are part of what is signed.
+		let ext = (signed(vec![7, 2, 0, 0, 0, 0, 0, 0, 0, 102], 5), Authorize(2));
+		let (_, _, origin) = ext.validate(None, &TestCall, &(), 0, &7u8).unwrap();
+		assert_eq!(origin, Some(2));
+	}
+}
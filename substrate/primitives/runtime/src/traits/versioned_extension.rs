@@ -0,0 +1,333 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Versioning of the transaction extensions general transactions are built with.
+//!
+//! A general transaction carries the version of the extensions it was built with. This allows a
+//! runtime to accept several sets of extensions at the same time, e.g. the old and the new one
+//! across a runtime upgrade changing them.
+
+use super::{
+	AsTransactionAuthorizedOrigin, DispatchInfoOf, Dispatchable, OriginOf, PostDispatchInfoOf,
+	SignedExtensionMetadata, TransactionExtension, TransactionExtensionBase,
+};
+use crate::{
+	scale_info::{build::Fields, meta_type, Path, StaticTypeInfo, Type, TypeInfo, TypeParameter},
+	transaction_validity::{InvalidTransaction, TransactionValidity, TransactionValidityError},
+	ApplyExtrinsicResultWithInfo,
+};
+use codec::{Decode, Encode, Input, Output};
+use sp_std::{fmt::Debug, prelude::*};
+
+/// Metadata of one version of transaction extensions.
+pub struct ExtensionVersionMetadata {
+	/// The extension version.
+	pub version: u8,
+	/// The extensions in the order they appear in the transaction.
+	pub extensions: Vec<SignedExtensionMetadata>,
+}
+
+/// The data of one or several versions of transaction extensions, independent of the call they
+/// are used with.
+///
+/// The extension version is not part of the encoding of the data, but encoded in front of it by
+/// the extrinsic.
+pub trait VersionedExtensionBase:
+	Encode + Debug + Sync + Send + Clone + Eq + PartialEq + StaticTypeInfo
+{
+	/// The extension version this data belongs to.
+	fn extension_version(&self) -> u8;
+
+	/// Whether data of the given extension version can be decoded.
+	fn supports_version(extension_version: u8) -> bool;
+
+	/// Decode the data of the given extension version.
+	fn decode_with_version<I: Input>(
+		extension_version: u8,
+		input: &mut I,
+	) -> Result<Self, codec::Error>;
+
+	/// Returns the metadata of all supported extension versions.
+	fn metadata() -> Vec<ExtensionVersionMetadata>;
+}
+
+/// One or several versions of transaction extensions general transactions can be built with.
+pub trait VersionedExtension<Call: Dispatchable>: VersionedExtensionBase {
+	/// Validate a general transaction with the given initial `origin` for the transaction queue.
+	fn validate_general(
+		&self,
+		origin: OriginOf<Call>,
+		call: &Call,
+		info: &DispatchInfoOf<Call>,
+		len: usize,
+	) -> TransactionValidity;
+
+	/// Validate, prepare and dispatch a general transaction with the given initial `origin`.
+	fn apply_general(
+		self,
+		origin: OriginOf<Call>,
+		call: Call,
+		info: &DispatchInfoOf<Call>,
+		len: usize,
+	) -> ApplyExtrinsicResultWithInfo<PostDispatchInfoOf<Call>>;
+}
+
+/// The transaction extensions `Extension` at extension version `VERSION`.
+///
+/// The implication handed to the extensions is the extension version followed by the call, so
+/// a signature of one version is never valid for another one.
+#[derive(Encode, Clone, Debug, PartialEq, Eq)]
+pub struct ExtensionAtVersion<const VERSION: u8, Extension>(pub Extension);
+
+impl<const VERSION: u8, Extension> From<Extension> for ExtensionAtVersion<VERSION, Extension> {
+	fn from(extension: Extension) -> Self {
+		Self(extension)
+	}
+}
+
+impl<const VERSION: u8, Extension: StaticTypeInfo> TypeInfo
+	for ExtensionAtVersion<VERSION, Extension>
+{
+	type Identity = Self;
+
+	fn type_info() -> Type {
+		Type::builder()
+			.path(Path::new("ExtensionAtVersion", module_path!()))
+			.type_params(vec![TypeParameter::new("Extension", Some(meta_type::<Extension>()))])
+			.docs(&["Transaction extensions at a specific extension version"])
+			.composite(Fields::unnamed().field(|f| f.ty::<Extension>()))
+	}
+}
+
+impl<const VERSION: u8, Extension: TransactionExtensionBase> VersionedExtensionBase
+	for ExtensionAtVersion<VERSION, Extension>
+{
+	fn extension_version(&self) -> u8 {
+		VERSION
+	}
+
+	fn supports_version(extension_version: u8) -> bool {
+		extension_version == VERSION
+	}
+
+	fn decode_with_version<I: Input>(
+		extension_version: u8,
+		input: &mut I,
+	) -> Result<Self, codec::Error> {
+		if extension_version != VERSION {
+			return Err("Unsupported extension version".into())
+		}
+		Extension::decode(input).map(Self)
+	}
+
+	fn metadata() -> Vec<ExtensionVersionMetadata> {
+		sp_std::vec![ExtensionVersionMetadata {
+			version: VERSION,
+			extensions: Extension::metadata()
+		}]
+	}
+}
+
+impl<const VERSION: u8, Extension, Call> VersionedExtension<Call>
+	for ExtensionAtVersion<VERSION, Extension>
+where
+	Extension: TransactionExtension<Call>,
+	Call: Dispatchable + Encode,
+	OriginOf<Call>: AsTransactionAuthorizedOrigin,
+{
+	fn validate_general(
+		&self,
+		origin: OriginOf<Call>,
+		call: &Call,
+		info: &DispatchInfoOf<Call>,
+		len: usize,
+	) -> TransactionValidity {
+		let (valid, _, origin) = self.0.validate(origin, call, info, len, &(VERSION, call))?;
+		ensure_authorized(&origin)?;
+		Ok(valid)
+	}
+
+	fn apply_general(
+		self,
+		origin: OriginOf<Call>,
+		call: Call,
+		info: &DispatchInfoOf<Call>,
+		len: usize,
+	) -> ApplyExtrinsicResultWithInfo<PostDispatchInfoOf<Call>> {
+		let (_, val, origin) = self.0.validate(origin, &call, info, len, &(VERSION, &call))?;
+		ensure_authorized(&origin)?;
+		let pre = self.0.prepare(val, &origin, &call, info, len)?;
+		let res = call.dispatch(origin);
+		let post_info = match res {
+			Ok(info) => info,
+			Err(err) => err.post_info,
+		};
+		Extension::post_dispatch(
+			pre,
+			info,
+			&post_info,
+			len,
+			&res.map(|_| ()).map_err(|e| e.error),
+		)?;
+		Ok(res)
+	}
+}
+
+/// The transaction extension versions of both `A` and `B`.
+///
+/// Nest it to support more than two versions, e.g. `MultiVersion<V0, MultiVersion<V1, V2>>`.
+/// `A` takes precedence if both support the same extension version.
+#[derive(Clone, Debug, PartialEq, Eq, TypeInfo)]
+pub enum MultiVersion<A, B> {
+	/// Data of a version supported by `A`.
+	A(A),
+	/// Data of a version supported by `B`.
+	B(B),
+}
+
+impl<A: Encode, B: Encode> Encode for MultiVersion<A, B> {
+	fn size_hint(&self) -> usize {
+		match self {
+			Self::A(a) => a.size_hint(),
+			Self::B(b) => b.size_hint(),
+		}
+	}
+
+	fn encode_to<T: Output + ?Sized>(&self, dest: &mut T) {
+		match self {
+			Self::A(a) => a.encode_to(dest),
+			Self::B(b) => b.encode_to(dest),
+		}
+	}
+}
+
+impl<A: VersionedExtensionBase, B: VersionedExtensionBase> VersionedExtensionBase
+	for MultiVersion<A, B>
+{
+	fn extension_version(&self) -> u8 {
+		match self {
+			Self::A(a) => a.extension_version(),
+			Self::B(b) => b.extension_version(),
+		}
+	}
+
+	fn supports_version(extension_version: u8) -> bool {
+		A::supports_version(extension_version) || B::supports_version(extension_version)
+	}
+
+	fn decode_with_version<I: Input>(
+		extension_version: u8,
+		input: &mut I,
+	) -> Result<Self, codec::Error> {
+		if A::supports_version(extension_version) {
+			A::decode_with_version(extension_version, input).map(Self::A)
+		} else {
+			B::decode_with_version(extension_version, input).map(Self::B)
+		}
+	}
+
+	fn metadata() -> Vec<ExtensionVersionMetadata> {
+		let mut versions = A::metadata();
+		versions.extend(B::metadata().into_iter().filter(|v| !A::supports_version(v.version)));
+		versions
+	}
+}
+
+impl<A, B, Call> VersionedExtension<Call> for MultiVersion<A, B>
+where
+	A: VersionedExtension<Call>,
+	B: VersionedExtension<Call>,
+	Call: Dispatchable,
+{
+	fn validate_general(
+		&self,
+		origin: OriginOf<Call>,
+		call: &Call,
+		info: &DispatchInfoOf<Call>,
+		len: usize,
+	) -> TransactionValidity {
+		match self {
+			Self::A(a) => a.validate_general(origin, call, info, len),
+			Self::B(b) => b.validate_general(origin, call, info, len),
+		}
+	}
+
+	fn apply_general(
+		self,
+		origin: OriginOf<Call>,
+		call: Call,
+		info: &DispatchInfoOf<Call>,
+		len: usize,
+	) -> ApplyExtrinsicResultWithInfo<PostDispatchInfoOf<Call>> {
+		match self {
+			Self::A(a) => a.apply_general(origin, call, info, len),
+			Self::B(b) => b.apply_general(origin, call, info, len),
+		}
+	}
+}
+
+/// No extension version at all, i.e. general transactions are not supported.
+impl VersionedExtensionBase for () {
+	fn extension_version(&self) -> u8 {
+		0
+	}
+
+	fn supports_version(_: u8) -> bool {
+		false
+	}
+
+	fn decode_with_version<I: Input>(_: u8, _: &mut I) -> Result<Self, codec::Error> {
+		Err("General transactions are not supported".into())
+	}
+
+	fn metadata() -> Vec<ExtensionVersionMetadata> {
+		Vec::new()
+	}
+}
+
+impl<Call: Dispatchable> VersionedExtension<Call> for () {
+	fn validate_general(
+		&self,
+		_origin: OriginOf<Call>,
+		_call: &Call,
+		_info: &DispatchInfoOf<Call>,
+		_len: usize,
+	) -> TransactionValidity {
+		Err(InvalidTransaction::UnknownOrigin.into())
+	}
+
+	fn apply_general(
+		self,
+		_origin: OriginOf<Call>,
+		_call: Call,
+		_info: &DispatchInfoOf<Call>,
+		_len: usize,
+	) -> ApplyExtrinsicResultWithInfo<PostDispatchInfoOf<Call>> {
+		Err(InvalidTransaction::UnknownOrigin.into())
+	}
+}
+
+/// Ensure the extensions of a general transaction replaced its initial `None` origin.
+fn ensure_authorized<Origin: AsTransactionAuthorizedOrigin>(
+	origin: &Origin,
+) -> Result<(), TransactionValidityError> {
+	if origin.is_transaction_authorized() {
+		Ok(())
+	} else {
+		Err(InvalidTransaction::UnknownOrigin.into())
+	}
+}
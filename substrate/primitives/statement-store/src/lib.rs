@@ -46,7 +46,8 @@ pub const MAX_TOPICS: usize = 4;
 
 #[cfg(feature = "std")]
 pub use store_api::{
-	Error, NetworkPriority, Result, StatementSource, StatementStore, SubmitResult,
+	Error, NetworkPriority, RejectionReason, Result, StatementEvent, StatementEventStream,
+	StatementFilter, StatementSource, StatementStore, StoreLimits, StoreStatus, SubmitResult,
 };
 
 #[cfg(feature = "std")]
@@ -16,7 +16,7 @@
 // limitations under the License.
 
 pub use crate::runtime_api::StatementSource;
-use crate::{Hash, Statement, Topic};
+use crate::{DecryptionKey, Hash, Statement, Topic};
 
 /// Statement store error.
 #[derive(Debug, Eq, PartialEq, thiserror::Error)]
@@ -30,6 +30,9 @@ pub enum Error {
 	/// Error making runtime call.
 	#[error("Error calling into the runtime")]
 	Runtime,
+	/// The store has as many subscribers as it serves.
+	#[error("Too many subscribers")]
+	TooManySubscribers,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -61,6 +64,100 @@ pub enum SubmitResult {
 /// Result type for `Error`
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// Global limits of the statement store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StoreLimits {
+	/// Maximum number of statements in the store.
+	pub max_total_statements: usize,
+	/// Maximum total data size of the statements in the store.
+	pub max_total_size: usize,
+}
+
+/// Reason for a statement not being accepted into the store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum RejectionReason {
+	/// The statement was removed from the store recently and can't be added back yet.
+	KnownExpired,
+	/// The statement has no proof.
+	NoProof,
+	/// The statement proof is invalid.
+	BadProof,
+	/// The statement data exceeds the size allowed for its account.
+	DataTooLarge,
+	/// The statement would replace a channel message of equal or higher priority.
+	ChannelPriorityTooLow,
+	/// The account quota is used up by statements of equal or higher priority.
+	AccountFull,
+	/// The store is full of statements of equal or higher priority.
+	StoreFull,
+	/// Validating or storing the statement failed.
+	InternalError,
+}
+
+impl RejectionReason {
+	/// All the reasons.
+	pub const ALL: [RejectionReason; 8] = [
+		RejectionReason::KnownExpired,
+		RejectionReason::NoProof,
+		RejectionReason::BadProof,
+		RejectionReason::DataTooLarge,
+		RejectionReason::ChannelPriorityTooLow,
+		RejectionReason::AccountFull,
+		RejectionReason::StoreFull,
+		RejectionReason::InternalError,
+	];
+
+	/// Name of the reason.
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			RejectionReason::KnownExpired => "known_expired",
+			RejectionReason::NoProof => "no_proof",
+			RejectionReason::BadProof => "bad_proof",
+			RejectionReason::DataTooLarge => "data_too_large",
+			RejectionReason::ChannelPriorityTooLow => "channel_priority_too_low",
+			RejectionReason::AccountFull => "account_full",
+			RejectionReason::StoreFull => "store_full",
+			RejectionReason::InternalError => "internal_error",
+		}
+	}
+}
+
+/// Usage of the statement store.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoreStatus {
+	/// Current global limits.
+	pub limits: StoreLimits,
+	/// Number of statements in the store.
+	pub total_statements: usize,
+	/// Total data size of the statements in the store.
+	pub total_size: usize,
+	/// Number of removed statements that can't be added back yet.
+	pub expired_statements: usize,
+	/// Number of statements rejected since the store was opened, by reason.
+	pub rejections: Vec<(RejectionReason, u64)>,
+}
+
+/// Filter of the statements a subscription is notified about.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StatementFilter {
+	/// Topics the statements must all include.
+	pub match_all_topics: Vec<Topic>,
+	/// Decryption key of the statements, `None` for statements without one.
+	pub dest: Option<DecryptionKey>,
+}
+
+/// Event of a statement subscription.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StatementEvent {
+	/// A statement was accepted into the store.
+	New(Statement),
+	/// A statement expired and was removed from the store.
+	Expired(Hash),
+}
+
+/// Stream of [`StatementEvent`]s of a subscription.
+pub type StatementEventStream = futures::channel::mpsc::Receiver<StatementEvent>;
+
 /// Statement store API.
 pub trait StatementStore: Send + Sync {
 	/// Return all statements.
@@ -69,6 +166,13 @@ pub trait StatementStore: Send + Sync {
 	/// Get statement by hash.
 	fn statement(&self, hash: &Hash) -> Result<Option<Statement>>;
 
+	/// Return the hashes of all statements.
+	///
+	/// Allows to iterate over the statements without loading all of them at once.
+	fn statement_hashes(&self) -> Result<Vec<Hash>> {
+		Ok(self.statements()?.into_iter().map(|(hash, _)| hash).collect())
+	}
+
 	/// Return the data of all known statements which include all topics and have no `DecryptionKey`
 	/// field.
 	fn broadcasts(&self, match_all_topics: &[Topic]) -> Result<Vec<Vec<u8>>>;
@@ -87,4 +191,21 @@ pub trait StatementStore: Send + Sync {
 
 	/// Remove a statement from the store.
 	fn remove(&self, hash: &Hash) -> Result<()>;
+
+	/// Subscribe to the statements matching `filter` being accepted into or expiring from the
+	/// store.
+	///
+	/// The stream ends if the subscriber doesn't keep up with the events. Fails with
+	/// [`Error::TooManySubscribers`] if the store has as many subscribers as it serves.
+	fn subscribe(&self, filter: StatementFilter) -> Result<StatementEventStream>;
+
+	/// Return the usage of the store.
+	fn status(&self) -> Result<StoreStatus>;
+
+	/// Change the global limits of the store.
+	///
+	/// The limits are kept across restarts, until the store is opened with other configured limits.
+	/// If the store is above the new limits, the lowest priority statements of the accounts using
+	/// the most space are removed.
+	fn set_limits(&self, limits: StoreLimits) -> Result<()>;
 }
@@ -67,10 +67,12 @@ impl TryFrom<&Extrinsic> for TransferData {
 			Extrinsic {
 				function: RuntimeCall::Balances(BalancesCall::transfer_allow_death { dest, value }),
 				signature: Some((from, _, (CheckNonce(nonce), ..))),
+				..
 			} => Ok(TransferData { from: *from, to: *dest, amount: *value, nonce: *nonce }),
 			Extrinsic {
 				function: RuntimeCall::SubstrateTest(PalletCall::bench_call { transfer }),
 				signature: None,
+				..
 			} => Ok(transfer.clone()),
 			_ => Err(()),
 		}
@@ -15,11 +15,19 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{build_executor, state_machine_call_with_proof, SharedParams, State, LOG_TARGET};
+use crate::{
+	build_executor, state_machine_call, state_machine_call_with_proof,
+	storage_layout::{self, StorageLayoutChange},
+	SharedParams, State, LOG_TARGET,
+};
+use frame_support::traits::StorageVersion;
 use frame_try_runtime::UpgradeCheckSelect;
 use parity_scale_codec::{Decode, Encode};
-use sc_executor::sp_wasm_interface::HostFunctions;
-use sp_runtime::traits::{Block as BlockT, NumberFor};
+use sc_executor::{sp_wasm_interface::HostFunctions, WasmExecutor};
+use sp_core::storage::well_known_keys;
+use sp_metadata_ir::frame_metadata::RuntimeMetadataPrefixed;
+use sp_runtime::traits::{Block as BlockT, HashingFor, NumberFor};
+use sp_state_machine::TestExternalities;
 use sp_weights::Weight;
 use std::{fmt::Debug, str::FromStr};
 
@@ -47,6 +55,16 @@ pub struct OnRuntimeUpgradeCmd {
 		require_equals = true,
 		verbatim_doc_comment)]
 	pub checks: UpgradeCheckSelect,
+
+	/// Check that every pallet whose storage layout changed also bumped its storage version.
+	///
+	/// Before the migrations are executed, the storage types in the metadata of the new runtime
+	/// are compared to the ones of the runtime in the state. The command fails if the encoding of
+	/// a storage item changed, but the storage version declared by its pallet in the new runtime
+	/// is not above the on-chain one. Requires `--runtime` to point to a wasm blob exposing
+	/// metadata V15.
+	#[clap(long)]
+	pub check_storage_layout: bool,
 }
 
 pub(crate) async fn on_runtime_upgrade<Block, HostFns>(
@@ -62,7 +80,56 @@ where
 	HostFns: HostFunctions,
 {
 	let executor = build_executor(&shared);
-	let ext = command.state.into_ext::<Block, HostFns>(&shared, &executor, None, true).await?;
+	let (mut ext, maybe_original_code) = command
+		.state
+		.into_ext_with_original_code::<Block, HostFns>(&shared, &executor, None, true)
+		.await?;
+
+	let unmigrated = match (command.check_storage_layout, maybe_original_code) {
+		(false, _) => None,
+		(true, None) => {
+			log::warn!(
+				target: LOG_TARGET,
+				"the runtime of the state is not overwritten, skipping the storage layout check."
+			);
+			None
+		},
+		(true, Some(original_code)) => {
+			let new_code = ext
+				.execute_with(|| sp_io::storage::get(well_known_keys::CODE))
+				.expect("':CODE:' is always downloaded in try-runtime-cli; qed");
+			let old_metadata = read_metadata::<Block, HostFns>(&executor, &original_code)?;
+			let new_metadata = read_metadata::<Block, HostFns>(&executor, &new_code)?;
+			let changes = storage_layout::diff(
+				&storage_layout::layout_from_metadata(&old_metadata)?,
+				&storage_layout::layout_from_metadata(&new_metadata)?,
+			);
+			for change in &changes {
+				log::info!(
+					target: LOG_TARGET,
+					"storage layout of {}::{} changed: {:?}",
+					change.pallet,
+					change.item,
+					change.kind,
+				);
+			}
+
+			// Only runtimes exposing metadata V15 declare their storage versions.
+			let declared = if changes.is_empty() {
+				Default::default()
+			} else {
+				storage_layout::storage_versions_from_metadata(&new_metadata)?
+			};
+			Some(storage_layout::unmigrated_changes(&changes, |pallet| {
+				let key = storage_layout::storage_version_key(pallet);
+				let on_chain = ext
+					.execute_with(|| sp_io::storage::get(&key))
+					.and_then(|raw| StorageVersion::decode(&mut &*raw).ok())
+					.unwrap_or_default();
+				(on_chain, declared.get(pallet).copied().unwrap_or_default())
+			}))
+		},
+	};
 
 	let (_, encoded_result) = state_machine_call_with_proof::<Block, HostFns>(
 		&ext,
@@ -85,5 +152,57 @@ where
 		(weight.proof_size() as f64 / total_weight.proof_size().max(1) as f64) * 100.0,
 	);
 
+	if let Some(unmigrated) = unmigrated {
+		ensure_migrated(&unmigrated)?;
+	}
+
 	Ok(())
 }
+
+/// Read the metadata of the given runtime `code`, in V15 if the runtime supports it.
+fn read_metadata<Block: BlockT, HostFns: HostFunctions>(
+	executor: &WasmExecutor<HostFns>,
+	code: &[u8],
+) -> sc_cli::Result<RuntimeMetadataPrefixed> {
+	let ext = TestExternalities::<HashingFor<Block>>::new_with_code(code, Default::default());
+	// Older runtimes don't implement `Metadata_metadata_at_version`.
+	if let Ok((_, encoded)) = state_machine_call::<Block, HostFns>(
+		&ext,
+		executor,
+		"Metadata_metadata_at_version",
+		&15u32.encode(),
+		Default::default(),
+	) {
+		if let Some(metadata) = storage_layout::decode_metadata_at_version(&encoded)? {
+			return Ok(metadata)
+		}
+	}
+
+	let (_, encoded) = state_machine_call::<Block, HostFns>(
+		&ext,
+		executor,
+		"Metadata_metadata",
+		&[],
+		Default::default(),
+	)?;
+	Ok(storage_layout::decode_metadata(&encoded)?)
+}
+
+fn ensure_migrated(unmigrated: &[StorageLayoutChange]) -> sc_cli::Result<()> {
+	if unmigrated.is_empty() {
+		log::info!(target: LOG_TARGET, "all storage layout changes are covered by migrations.");
+		return Ok(())
+	}
+
+	for change in unmigrated {
+		log::error!(
+			target: LOG_TARGET,
+			"storage layout of {}::{} changed ({:?}), but the storage version of {} was not bumped.",
+			change.pallet,
+			change.item,
+			change.kind,
+			change.pallet,
+		);
+	}
+	Err(format!("{} storage item(s) changed without a migration.", unmigrated.len()).into())
+}
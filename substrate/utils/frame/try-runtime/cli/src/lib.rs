@@ -63,6 +63,7 @@ use std::{fmt::Debug, path::PathBuf, str::FromStr};
 pub mod block_building_info;
 pub mod commands;
 pub(crate) mod parse;
+pub mod storage_layout;
 pub(crate) const LOG_TARGET: &str = "try-runtime::cli";
 
 /// Possible commands of `try-runtime`.
@@ -289,6 +290,32 @@ impl State {
 		state_snapshot: Option<SnapshotConfig>,
 		try_runtime_check: bool,
 	) -> sc_cli::Result<RemoteExternalities<Block>>
+	where
+		Block::Header: DeserializeOwned,
+		<Block::Hash as FromStr>::Err: Debug,
+	{
+		self.into_ext_with_original_code::<Block, HostFns>(
+			shared,
+			executor,
+			state_snapshot,
+			try_runtime_check,
+		)
+		.await
+		.map(|(ext, _)| ext)
+	}
+
+	/// Same as [`Self::into_ext`], but also returns the original code of the state if it was
+	/// overwritten.
+	pub(crate) async fn into_ext_with_original_code<
+		Block: BlockT + DeserializeOwned,
+		HostFns: HostFunctions,
+	>(
+		&self,
+		shared: &SharedParams,
+		executor: &WasmExecutor<HostFns>,
+		state_snapshot: Option<SnapshotConfig>,
+		try_runtime_check: bool,
+	) -> sc_cli::Result<(RemoteExternalities<Block>, Option<Vec<u8>>)>
 	where
 		Block::Header: DeserializeOwned,
 		<Block::Hash as FromStr>::Err: Debug,
@@ -347,6 +374,7 @@ impl State {
 		let mut ext = builder.build().await?;
 
 		// actually replace the code if needed.
+		let mut maybe_original_code = None;
 		if let Some(new_code) = maybe_code_to_overwrite {
 			let original_code = ext
 				.execute_with(|| sp_io::storage::get(well_known_keys::CODE))
@@ -381,6 +409,7 @@ impl State {
 			if new_version.spec_name != old_version.spec_name {
 				return Err("Spec names must match.".into())
 			}
+			maybe_original_code = Some(original_code);
 		}
 
 		// whatever runtime we have in store now must have been compiled with try-runtime feature.
@@ -390,7 +419,7 @@ impl State {
 			}
 		}
 
-		Ok(ext)
+		Ok((ext, maybe_original_code))
 	}
 }
 
@@ -0,0 +1,457 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Detection of storage layout changes between two runtimes.
+//!
+//! The storage items of both runtimes are extracted from their metadata and reduced to the shape
+//! of their encoding, i.e. names and type paths are ignored, such that only changes which make
+//! existing storage undecodable are reported. Each such change requires a migration, which is
+//! expected to bump the [`StorageVersion`] declared by the pallet in the new runtime.
+
+use frame_support::traits::{StorageVersion, STORAGE_VERSION_STORAGE_KEY_POSTFIX};
+use parity_scale_codec::Decode;
+use scale_info::{form::PortableForm, PortableRegistry, TypeDef, TypeDefPrimitive};
+use sp_metadata_ir::{
+	frame_metadata::{
+		v14::{PalletStorageMetadata, StorageEntryType, StorageHasher},
+		RuntimeMetadata, RuntimeMetadataPrefixed,
+	},
+	STORAGE_VERSIONS_KEY,
+};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The encoding of a single storage item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageItemLayout {
+	/// The hashers and the shape of the key, if the item is a map.
+	pub key: Option<(Vec<StorageHasher>, String)>,
+	/// The shape of the value.
+	pub value: String,
+}
+
+/// The storage items of a runtime, by pallet storage prefix and item name.
+pub type StorageLayout = BTreeMap<(String, String), StorageItemLayout>;
+
+/// What changed about a storage item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+	/// The item changed between a plain value and a map.
+	Kind,
+	/// The hashers of the map changed.
+	Hashers,
+	/// The key type of the map changed.
+	Key,
+	/// The value type changed.
+	Value,
+	/// The item no longer exists.
+	Removed,
+}
+
+/// A storage item whose layout differs between two runtimes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageLayoutChange {
+	/// The storage prefix of the pallet, i.e. its name in the runtime.
+	pub pallet: String,
+	/// The name of the storage item.
+	pub item: String,
+	/// What changed.
+	pub kind: ChangeKind,
+}
+
+/// Extract the storage layout from the given metadata.
+pub fn layout_from_metadata(metadata: &RuntimeMetadataPrefixed) -> Result<StorageLayout, String> {
+	let (types, storages): (_, Vec<&PalletStorageMetadata<PortableForm>>) = match &metadata.1 {
+		RuntimeMetadata::V14(metadata) =>
+			(&metadata.types, metadata.pallets.iter().filter_map(|p| p.storage.as_ref()).collect()),
+		RuntimeMetadata::V15(metadata) =>
+			(&metadata.types, metadata.pallets.iter().filter_map(|p| p.storage.as_ref()).collect()),
+		_ => return Err("only metadata V14 and V15 are supported".into()),
+	};
+
+	let mut layout = StorageLayout::new();
+	for storage in storages {
+		for entry in &storage.entries {
+			let item = match &entry.ty {
+				StorageEntryType::Plain(value) =>
+					StorageItemLayout { key: None, value: shape(types, value.id, &mut Vec::new()) },
+				StorageEntryType::Map { hashers, key, value } => StorageItemLayout {
+					key: Some((hashers.clone(), shape(types, key.id, &mut Vec::new()))),
+					value: shape(types, value.id, &mut Vec::new()),
+				},
+			};
+			layout.insert((storage.prefix.clone(), entry.name.clone()), item);
+		}
+	}
+
+	Ok(layout)
+}
+
+/// Extract the storage versions declared by the pallets of the runtime, by storage prefix.
+///
+/// They are only part of the metadata since V15.
+pub fn storage_versions_from_metadata(
+	metadata: &RuntimeMetadataPrefixed,
+) -> Result<BTreeMap<String, StorageVersion>, String> {
+	let RuntimeMetadata::V15(metadata) = &metadata.1 else {
+		return Err("metadata V15 is required to read the storage versions of the pallets".into())
+	};
+	let entry = metadata.custom.map.get(STORAGE_VERSIONS_KEY).ok_or_else(|| {
+		format!("the metadata has no `{}` entry, the runtime is too old", STORAGE_VERSIONS_KEY)
+	})?;
+	let versions = <Vec<(String, u16)>>::decode(&mut &*entry.value)
+		.map_err(|e| format!("failed to decode the storage versions: {:?}", e))?;
+
+	Ok(versions
+		.into_iter()
+		.map(|(prefix, version)| (prefix, StorageVersion::new(version)))
+		.collect())
+}
+
+/// Decode the metadata as returned by the `Metadata_metadata` runtime api.
+pub fn decode_metadata(encoded: &[u8]) -> Result<RuntimeMetadataPrefixed, String> {
+	let opaque = <Vec<u8> as Decode>::decode(&mut &*encoded)
+		.map_err(|e| format!("failed to decode opaque metadata: {:?}", e))?;
+	RuntimeMetadataPrefixed::decode(&mut &*opaque)
+		.map_err(|e| format!("failed to decode metadata: {:?}", e))
+}
+
+/// Decode the metadata as returned by the `Metadata_metadata_at_version` runtime api.
+pub fn decode_metadata_at_version(
+	encoded: &[u8],
+) -> Result<Option<RuntimeMetadataPrefixed>, String> {
+	let opaque = <Option<Vec<u8>> as Decode>::decode(&mut &*encoded)
+		.map_err(|e| format!("failed to decode opaque metadata: {:?}", e))?;
+	opaque
+		.map(|opaque| {
+			RuntimeMetadataPrefixed::decode(&mut &*opaque)
+				.map_err(|e| format!("failed to decode metadata: {:?}", e))
+		})
+		.transpose()
+}
+
+/// Compare the storage layouts of two runtimes.
+///
+/// Items which only exist in `new` are not reported, as they don't need a migration.
+pub fn diff(old: &StorageLayout, new: &StorageLayout) -> Vec<StorageLayoutChange> {
+	old.iter()
+		.filter_map(|((pallet, item), old_item)| {
+			let kind = match new.get(&(pallet.clone(), item.clone())) {
+				None => ChangeKind::Removed,
+				Some(new_item) => match (&old_item.key, &new_item.key) {
+					(None, Some(_)) | (Some(_), None) => ChangeKind::Kind,
+					(Some((old_hashers, _)), Some((new_hashers, _)))
+						if old_hashers != new_hashers =>
+						ChangeKind::Hashers,
+					(Some((_, old_key)), Some((_, new_key))) if old_key != new_key =>
+						ChangeKind::Key,
+					_ if old_item.value != new_item.value => ChangeKind::Value,
+					_ => return None,
+				},
+			};
+			Some(StorageLayoutChange { pallet: pallet.clone(), item: item.clone(), kind })
+		})
+		.collect()
+}
+
+/// The storage key under which the [`StorageVersion`] of the given pallet is stored.
+pub fn storage_version_key(pallet: &str) -> [u8; 32] {
+	frame_support::storage::storage_prefix(pallet.as_bytes(), STORAGE_VERSION_STORAGE_KEY_POSTFIX)
+}
+
+/// Return the changes of all pallets whose storage version was not bumped.
+///
+/// `version_of` returns the on-chain storage version of a pallet before the upgrade and the
+/// storage version declared by the pallet in the new runtime. Removed items are not considered,
+/// as leaving stale storage behind does not corrupt the state.
+pub fn unmigrated_changes(
+	changes: &[StorageLayoutChange],
+	mut version_of: impl FnMut(&str) -> (StorageVersion, StorageVersion),
+) -> Vec<StorageLayoutChange> {
+	let migrated = changes
+		.iter()
+		.map(|change| change.pallet.as_str())
+		.collect::<BTreeSet<_>>()
+		.into_iter()
+		.filter(|pallet| {
+			let (on_chain, declared) = version_of(pallet);
+			on_chain < declared
+		})
+		.collect::<BTreeSet<_>>();
+
+	changes
+		.iter()
+		.filter(|change| change.kind != ChangeKind::Removed)
+		.filter(|change| !migrated.contains(change.pallet.as_str()))
+		.cloned()
+		.collect()
+}
+
+/// Render the shape of the encoding of the given type.
+///
+/// Two types with the same shape have the same encoding. Recursive types are cut at the first
+/// repetition.
+fn shape(registry: &PortableRegistry, id: u32, visiting: &mut Vec<u32>) -> String {
+	if visiting.contains(&id) {
+		return "<recursive>".into()
+	}
+	let Some(ty) = registry.resolve(id) else { return format!("<unknown {}>", id) };
+
+	visiting.push(id);
+	let rendered = match &ty.type_def {
+		TypeDef::Composite(composite) => match &composite.fields[..] {
+			// A single field is encoded transparently.
+			[field] => shape(registry, field.ty.id, visiting),
+			fields => tuple(registry, fields.iter().map(|f| f.ty.id), visiting),
+		},
+		TypeDef::Tuple(tup) => match &tup.fields[..] {
+			[field] => shape(registry, field.id, visiting),
+			fields => tuple(registry, fields.iter().map(|f| f.id), visiting),
+		},
+		TypeDef::Variant(variant) => {
+			let variants = variant
+				.variants
+				.iter()
+				.map(|v| {
+					let fields = tuple(registry, v.fields.iter().map(|f| f.ty.id), visiting);
+					format!("{}:{}", v.index, fields)
+				})
+				.collect::<Vec<_>>();
+			format!("enum{{{}}}", variants.join(","))
+		},
+		TypeDef::Sequence(seq) => format!("[{}]", shape(registry, seq.type_param.id, visiting)),
+		TypeDef::Array(arr) =>
+			format!("[{};{}]", shape(registry, arr.type_param.id, visiting), arr.len),
+		TypeDef::Primitive(primitive) => primitive_name(primitive).into(),
+		TypeDef::Compact(compact) =>
+			format!("compact<{}>", shape(registry, compact.type_param.id, visiting)),
+		TypeDef::BitSequence(bits) => {
+			let order = registry
+				.resolve(bits.bit_order_type.id)
+				.and_then(|ty| ty.path.segments.last().cloned())
+				.unwrap_or_default();
+			format!("bits<{},{}>", shape(registry, bits.bit_store_type.id, visiting), order)
+		},
+	};
+	visiting.pop();
+
+	rendered
+}
+
+fn tuple(
+	registry: &PortableRegistry,
+	ids: impl Iterator<Item = u32>,
+	visiting: &mut Vec<u32>,
+) -> String {
+	let fields = ids.map(|id| shape(registry, id, visiting)).collect::<Vec<_>>();
+	format!("({})", fields.join(","))
+}
+
+fn primitive_name(primitive: &TypeDefPrimitive) -> &'static str {
+	match primitive {
+		TypeDefPrimitive::Bool => "bool",
+		TypeDefPrimitive::Char => "char",
+		TypeDefPrimitive::Str => "str",
+		TypeDefPrimitive::U8 => "u8",
+		TypeDefPrimitive::U16 => "u16",
+		TypeDefPrimitive::U32 => "u32",
+		TypeDefPrimitive::U64 => "u64",
+		TypeDefPrimitive::U128 => "u128",
+		TypeDefPrimitive::U256 => "u256",
+		TypeDefPrimitive::I8 => "i8",
+		TypeDefPrimitive::I16 => "i16",
+		TypeDefPrimitive::I32 => "i32",
+		TypeDefPrimitive::I64 => "i64",
+		TypeDefPrimitive::I128 => "i128",
+		TypeDefPrimitive::I256 => "i256",
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use scale_info::{meta_type, MetaType, TypeInfo};
+	use sp_metadata_ir::{
+		ExtrinsicMetadataIR, MetadataIR, OuterEnumsIR, PalletMetadataIR, PalletStorageMetadataIR,
+		StorageEntryMetadataIR, StorageEntryModifierIR, StorageEntryTypeIR, StorageHasherIR,
+	};
+
+	#[derive(TypeInfo)]
+	struct Old {
+		_a: u32,
+		_b: u64,
+	}
+
+	#[derive(TypeInfo)]
+	struct Renamed {
+		_first: u32,
+		_second: u64,
+	}
+
+	#[derive(TypeInfo)]
+	struct Extended {
+		_a: u32,
+		_b: u64,
+		_c: bool,
+	}
+
+	fn plain(name: &'static str, value: MetaType) -> StorageEntryMetadataIR {
+		StorageEntryMetadataIR {
+			name,
+			modifier: StorageEntryModifierIR::Optional,
+			ty: StorageEntryTypeIR::Plain(value),
+			default: vec![],
+			docs: vec![],
+		}
+	}
+
+	fn map(
+		name: &'static str,
+		hasher: StorageHasherIR,
+		key: MetaType,
+		value: MetaType,
+	) -> StorageEntryMetadataIR {
+		StorageEntryMetadataIR {
+			name,
+			modifier: StorageEntryModifierIR::Optional,
+			ty: StorageEntryTypeIR::Map { hashers: vec![hasher], key, value },
+			default: vec![],
+			docs: vec![],
+		}
+	}
+
+	fn metadata_ir(entries: Vec<StorageEntryMetadataIR>, version: Option<u16>) -> MetadataIR {
+		MetadataIR {
+			pallets: vec![PalletMetadataIR {
+				name: "Example",
+				storage: Some(PalletStorageMetadataIR { prefix: "Example", entries, version }),
+				calls: None,
+				event: None,
+				constants: vec![],
+				error: None,
+				index: 0,
+				docs: vec![],
+			}],
+			extrinsic: ExtrinsicMetadataIR {
+				ty: meta_type::<()>(),
+				version: 4,
+				address_ty: meta_type::<()>(),
+				call_ty: meta_type::<()>(),
+				signature_ty: meta_type::<()>(),
+				extra_ty: meta_type::<()>(),
+				signed_extensions: vec![],
+				extension_versions: vec![],
+			},
+			ty: meta_type::<()>(),
+			apis: vec![],
+			outer_enums: OuterEnumsIR {
+				call_enum_ty: meta_type::<()>(),
+				event_enum_ty: meta_type::<()>(),
+				error_enum_ty: meta_type::<()>(),
+			},
+		}
+	}
+
+	fn layout(entries: Vec<StorageEntryMetadataIR>) -> StorageLayout {
+		layout_from_metadata(&sp_metadata_ir::into_v14(metadata_ir(entries, None))).unwrap()
+	}
+
+	fn change(item: &str, kind: ChangeKind) -> StorageLayoutChange {
+		StorageLayoutChange { pallet: "Example".into(), item: item.into(), kind }
+	}
+
+	#[test]
+	fn renames_are_not_changes() {
+		let old = layout(vec![plain("Value", meta_type::<Old>())]);
+		let new = layout(vec![plain("Value", meta_type::<Renamed>())]);
+		assert!(diff(&old, &new).is_empty());
+
+		// a newtype is encoded like its inner type.
+		let old = layout(vec![plain("Value", meta_type::<u32>())]);
+		let new = layout(vec![plain("Value", meta_type::<(u32,)>())]);
+		assert!(diff(&old, &new).is_empty());
+	}
+
+	#[test]
+	fn detects_changes() {
+		let old = layout(vec![
+			plain("Value", meta_type::<Old>()),
+			plain("Plain", meta_type::<u32>()),
+			map("Map", StorageHasherIR::Twox64Concat, meta_type::<u32>(), meta_type::<u32>()),
+			map("Hashed", StorageHasherIR::Twox64Concat, meta_type::<u32>(), meta_type::<u32>()),
+			plain("Gone", meta_type::<u32>()),
+			plain("Same", meta_type::<Vec<u8>>()),
+		]);
+		let new = layout(vec![
+			plain("Value", meta_type::<Extended>()),
+			map("Plain", StorageHasherIR::Twox64Concat, meta_type::<u32>(), meta_type::<u32>()),
+			map("Map", StorageHasherIR::Twox64Concat, meta_type::<u64>(), meta_type::<u32>()),
+			map(
+				"Hashed",
+				StorageHasherIR::Blake2_128Concat,
+				meta_type::<u32>(),
+				meta_type::<u32>(),
+			),
+			plain("Same", meta_type::<Vec<u8>>()),
+			plain("Added", meta_type::<u32>()),
+		]);
+
+		assert_eq!(
+			diff(&old, &new),
+			vec![
+				change("Gone", ChangeKind::Removed),
+				change("Hashed", ChangeKind::Hashers),
+				change("Map", ChangeKind::Key),
+				change("Plain", ChangeKind::Kind),
+				change("Value", ChangeKind::Value),
+			]
+		);
+	}
+
+	#[test]
+	fn reads_metadata_v15() {
+		let entries = || vec![plain("Value", meta_type::<Old>())];
+		let v14 = sp_metadata_ir::into_v14(metadata_ir(entries(), Some(2)));
+		let v15 = sp_metadata_ir::into_latest(metadata_ir(entries(), Some(2)));
+
+		assert_eq!(layout_from_metadata(&v15).unwrap(), layout_from_metadata(&v14).unwrap());
+		assert_eq!(
+			storage_versions_from_metadata(&v15).unwrap(),
+			BTreeMap::from([("Example".to_string(), StorageVersion::new(2))]),
+		);
+		// V14 doesn't carry the storage versions.
+		assert!(storage_versions_from_metadata(&v14).is_err());
+	}
+
+	#[test]
+	fn unmigrated_changes_works() {
+		let changes = vec![
+			change("Value", ChangeKind::Value),
+			change("Gone", ChangeKind::Removed),
+			StorageLayoutChange {
+				pallet: "Migrated".into(),
+				item: "Value".into(),
+				kind: ChangeKind::Value,
+			},
+		];
+
+		let unmigrated = unmigrated_changes(&changes, |pallet| match pallet {
+			"Migrated" => (StorageVersion::new(1), StorageVersion::new(2)),
+			_ => (StorageVersion::new(1), StorageVersion::new(1)),
+		});
+		assert_eq!(unmigrated, vec![change("Value", ChangeKind::Value)]);
+	}
+}